@@ -0,0 +1,121 @@
+//! Benchmarks for the combined analyses run by `codesearch report`
+//!
+//! Run with: cargo bench --bench analysis_benchmark
+
+use codesearch::callgraph::{build_call_graph, build_call_graph_in};
+use codesearch::deadcode::{find_dead_code, find_dead_code_in, DeadCodeOptions};
+use codesearch::designmetrics::{analyze_design_metrics, analyze_design_metrics_in};
+use codesearch::duplicates::{find_duplicates_enhanced, find_duplicates_enhanced_in, DuplicateConfig};
+use codesearch::parser::{count_identifiers, identifiers, ExtractionContext};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use regex::Regex;
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn write_tree(root: &Path) {
+    for d in 0..10 {
+        let sub = root.join(format!("module{d}"));
+        fs::create_dir_all(&sub).unwrap();
+        for i in 0..20 {
+            let content = format!(
+                "use crate::module{d}::shared;\n\n\
+                 pub struct Handler{i} {{\n    count: usize,\n}}\n\n\
+                 fn handler_{i}(input: usize) -> usize {{\n    let doubled = input * 2;\n    helper_{i}(doubled)\n}}\n\n\
+                 fn helper_{i}(value: usize) -> usize {{\n    // TODO: cache this\n    if value > 10 {{\n        return value - 10;\n    }}\n    value\n}}\n"
+            );
+            fs::write(sub.join(format!("file{i}.rs")), content).unwrap();
+        }
+    }
+}
+
+fn config() -> DuplicateConfig {
+    DuplicateConfig {
+        suggest_keep: false,
+        ..Default::default()
+    }
+}
+
+fn benchmark_report_analyses(c: &mut Criterion) {
+    let dir = tempdir().unwrap();
+    write_tree(dir.path());
+    let exts = vec!["rs".to_string()];
+    let exts = Some(exts.as_slice());
+
+    let mut group = c.benchmark_group("report_analyses");
+    group.sample_size(10);
+
+    // Each analysis walks, reads and extracts every file itself
+    group.bench_function("standalone", |b| {
+        b.iter(|| {
+            let path = black_box(dir.path());
+            black_box(find_dead_code(path, exts, None, &DeadCodeOptions::default()).unwrap());
+            black_box(find_duplicates_enhanced(path, exts, None, config()).unwrap());
+            black_box(build_call_graph(path, exts, None).unwrap());
+            black_box(analyze_design_metrics(path, exts, None).unwrap());
+        })
+    });
+
+    // One extraction pass shared by all four analyses
+    group.bench_function("shared_context", |b| {
+        b.iter(|| {
+            let path = black_box(dir.path());
+            let context = ExtractionContext::build(path, exts, None).unwrap();
+            black_box(find_dead_code_in(&context, &DeadCodeOptions::default()));
+            black_box(find_duplicates_enhanced_in(&context, path, config()));
+            black_box(build_call_graph_in(&context));
+            black_box(analyze_design_metrics_in(&context));
+        })
+    });
+
+    group.finish();
+}
+
+/// Text files of the test fixtures
+fn fixture_corpus() -> Vec<String> {
+    walkdir::WalkDir::new("tests/fixtures")
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| fs::read_to_string(entry.path()).ok())
+        .collect()
+}
+
+/// Counting N identifiers: N whole-word regex scans per file, against one
+/// tokenizing pass per file followed by N map lookups
+fn benchmark_identifier_lookup(c: &mut Criterion) {
+    let corpus = fixture_corpus();
+    let mut names: Vec<&str> = corpus.iter().flat_map(|file| identifiers(file).map(|(_, name)| name)).collect();
+    names.sort_unstable();
+    names.dedup();
+
+    let mut group = c.benchmark_group("identifier_lookup");
+    group.sample_size(10);
+    for n in [5, 20, 50, 100] {
+        let wanted = &names[..n.min(names.len())];
+        group.bench_with_input(BenchmarkId::new("regex_scans", n), wanted, |b, wanted| {
+            b.iter(|| {
+                let mut total = 0;
+                for name in wanted {
+                    let re = Regex::new(&format!(r"\b{}\b", regex::escape(name))).unwrap();
+                    total += corpus.iter().map(|file| re.find_iter(file).count()).sum::<usize>();
+                }
+                black_box(total)
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("one_pass", n), wanted, |b, wanted| {
+            b.iter(|| {
+                let counts: Vec<_> = corpus.iter().map(|file| count_identifiers(file)).collect();
+                let total: usize = wanted
+                    .iter()
+                    .flat_map(|name| counts.iter().map(move |c| c.get(*name).copied().unwrap_or(0)))
+                    .sum();
+                black_box(total)
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, benchmark_report_analyses, benchmark_identifier_lookup);
+criterion_main!(benches);
@@ -2,13 +2,50 @@
 //!
 //! Run with: cargo bench
 
-use codesearch::search::search_code;
+use codesearch::search::{search_code, search_code_streaming};
 use codesearch::search::pure::*;
 use codesearch::types::SearchOptions;
 use criterion::{black_box, criterion_group, criterion_main, Criterion, BenchmarkId};
+use std::alloc::{GlobalAlloc, Layout, System};
 use std::fs;
+use std::ops::ControlFlow;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
 use tempfile::tempdir;
 
+/// System allocator that tracks the bytes in use and their high-water mark
+struct PeakAlloc;
+
+static IN_USE: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for PeakAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc(layout) };
+        if !ptr.is_null() {
+            let in_use = IN_USE.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+            PEAK.fetch_max(in_use, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) };
+        IN_USE.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: PeakAlloc = PeakAlloc;
+
+/// Bytes allocated at the peak of `f`, above what was in use when it started
+fn peak_allocation<T>(f: impl FnOnce() -> T) -> usize {
+    let base = IN_USE.load(Ordering::Relaxed);
+    PEAK.store(base, Ordering::Relaxed);
+    black_box(f());
+    PEAK.load(Ordering::Relaxed) - base
+}
+
 fn benchmark_search_small(c: &mut Criterion) {
     let dir = tempdir().unwrap();
     
@@ -50,6 +87,54 @@ fn benchmark_search_medium(c: &mut Criterion) {
     });
 }
 
+fn benchmark_search_large_tree(c: &mut Criterion) {
+    let dir = tempdir().unwrap();
+
+    // Nested synthetic tree: walking and matching overlap, so time-to-first-result
+    // and peak memory no longer scale with the number of paths collected up front
+    for d in 0..50 {
+        let sub = dir.path().join(format!("module{d}"));
+        fs::create_dir_all(&sub).unwrap();
+        for i in 0..200 {
+            let content = format!("fn handler_{i}() {{\n    let needle = {i};\n}}\n");
+            fs::write(sub.join(format!("file{i}.rs")), content).unwrap();
+        }
+    }
+
+    let options = SearchOptions::default();
+
+    let mut group = c.benchmark_group("search_large_tree");
+    group.sample_size(10);
+    group.bench_function("search_10k_files", |b| {
+        b.iter(|| {
+            search_code(black_box("needle"), black_box(dir.path()), black_box(&options))
+        })
+    });
+    // Time from the start of the search until the first result is handed over
+    group.bench_function("first_result_10k_files", |b| {
+        b.iter_custom(|iters| {
+            let mut total = Duration::ZERO;
+            for _ in 0..iters {
+                let start = Instant::now();
+                let mut first = None;
+                search_code_streaming("needle", dir.path(), &options, |_| {
+                    first.get_or_insert_with(|| start.elapsed());
+                    ControlFlow::Break(())
+                })
+                .unwrap();
+                total += first.expect("the tree has matches");
+            }
+            total
+        })
+    });
+    group.finish();
+
+    // Criterion has no memory measurement, so peak allocation is printed alongside
+    let streamed = peak_allocation(|| search_code_streaming("needle", dir.path(), &options, |_| ControlFlow::Continue(())));
+    let collected = peak_allocation(|| search_code("needle", dir.path(), &options));
+    println!("search_large_tree peak allocation: streaming {} KiB, collected {} KiB", streamed / 1024, collected / 1024);
+}
+
 fn benchmark_relevance_score(c: &mut Criterion) {
     c.bench_function("relevance_score_calculation", |b| {
         b.iter(|| {
@@ -136,6 +221,7 @@ criterion_group!(
     benches,
     benchmark_search_small,
     benchmark_search_medium,
+    benchmark_search_large_tree,
     benchmark_relevance_score,
     benchmark_fuzzy_match_quality,
     benchmark_search_with_options,
@@ -2,25 +2,57 @@
 //!
 //! Provides metrics and statistics about the codebase.
 
-use crate::language::{get_language_by_extension, get_supported_languages};
+use crate::complexity::{calculate_function_complexities, exceeded_thresholds};
+use crate::config::ComplexityConfig;
+use crate::deadcode::detect_trivial_delegation;
+use crate::language::{blank_regions, extract_embedded_regions, get_language_by_extension, get_supported_languages, overridden_extensions, ComplexityThresholds};
+use crate::lines::split_lines;
 use crate::parser::{get_file_extension, read_file_content};
 use crate::search::list_files;
-use crate::types::RefactorSuggestion;
+use crate::text::truncate_str_safe;
+use crate::types::{FileInfo, RefactorSuggestion, SuggestionType, SNIPPET_CHARS};
+use crate::theme::{self, Glyph};
 use colored::*;
 use regex::Regex;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::Path;
 
-/// Analyze codebase and print metrics
-pub fn analyze_codebase(
+/// Size, language and code-pattern statistics for a codebase
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CodebaseStats {
+    pub total_files: usize,
+    pub total_lines: usize,
+    /// Total size in bytes
+    pub total_size: u64,
+    /// Per-language breakdown, most lines first
+    pub languages: Vec<LanguageStats>,
+    pub functions: usize,
+    pub classes: usize,
+    pub comment_lines: usize,
+    /// Comment lines as a fraction of all lines (0.0 for an empty codebase)
+    pub comment_ratio: f64,
+    /// The five files with the most lines
+    pub largest_files: Vec<FileInfo>,
+}
+
+/// Files, lines and bytes of one language, embedded code included
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LanguageStats {
+    pub extension: String,
+    /// Display name, or the extension for unknown languages
+    pub language: String,
+    pub files: usize,
+    pub lines: usize,
+    pub size: u64,
+}
+
+/// Compute size, language and code-pattern statistics for the files under `path`
+pub fn compute_codebase_stats(
     path: &Path,
     extensions: Option<&[String]>,
     exclude: Option<&[String]>,
-) -> Result<(), Box<dyn std::error::Error>> {
-    println!("{}", "Codebase Analysis".cyan().bold());
-    println!("{}", "─".repeat(30).cyan());
-    println!();
-
+) -> Result<CodebaseStats, Box<dyn std::error::Error>> {
     let files = list_files(path, extensions, exclude)?;
 
     let mut total_lines = 0;
@@ -34,77 +66,147 @@ pub fn analyze_codebase(
         total_lines += file.lines;
         total_size += file.size;
 
-        let ext = get_file_extension(&file.path);
+        // Stats keep the file's own extension; an overridden one still gets its language's name
+        let ext = Path::new(&file.path).extension().and_then(|e| e.to_str()).unwrap_or("");
         let ext_str = if ext.is_empty() { "unknown" } else { ext }.to_string();
+        let content = read_file_content(&file.path);
+
+        // Embedded code (e.g. <script> in HTML) counts under its own language
+        let regions = extract_embedded_regions(&file.path, &content);
+        let embedded_lines: usize = regions.iter().map(|r| r.line_count()).sum();
+        let embedded_bytes: u64 = regions.iter().map(|r| r.content.len() as u64).sum();
 
         let entry = language_stats.entry(ext_str.clone()).or_insert((0, 0, 0));
         entry.0 += 1;
-        entry.1 += file.lines;
-        entry.2 += file.size;
-
-        let content = read_file_content(&file.path);
-        if let Some(lang_info) = get_language_by_extension(ext) {
-            for pattern in lang_info.function_patterns {
-                if let Ok(regex) = Regex::new(pattern) {
-                    function_count += regex.find_iter(&content).count();
-                }
-            }
-            for pattern in lang_info.class_patterns {
-                if let Ok(regex) = Regex::new(pattern) {
-                    class_count += regex.find_iter(&content).count();
-                }
-            }
-            for pattern in lang_info.comment_patterns {
-                if let Ok(regex) = Regex::new(pattern) {
-                    comment_lines += regex.find_iter(&content).count();
-                }
+        entry.1 += file.lines.saturating_sub(embedded_lines);
+        entry.2 += file.size.saturating_sub(embedded_bytes);
+
+        let mut embedded_languages: Vec<&str> = Vec::new();
+        for region in &regions {
+            let entry = language_stats.entry(region.extension.clone()).or_insert((0, 0, 0));
+            if !embedded_languages.contains(&region.extension.as_str()) {
+                embedded_languages.push(&region.extension);
+                entry.0 += 1;
             }
-        } else {
-            function_count += count_generic_functions(&content);
-            class_count += count_generic_classes(&content);
-            comment_lines += count_generic_comments(&content);
+            entry.1 += region.line_count();
+            entry.2 += region.content.len() as u64;
+        }
+
+        let host = if regions.is_empty() { content.clone() } else { blank_regions(&content, &regions) };
+        let parts = std::iter::once((ext, host.as_str()))
+            .chain(regions.iter().map(|r| (r.extension.as_str(), r.content.as_str())));
+        for (ext, content) in parts {
+            let (functions, classes, comments) = count_code_patterns(ext, content);
+            function_count += functions;
+            class_count += classes;
+            comment_lines += comments;
         }
     }
 
+    let mut languages: Vec<LanguageStats> = language_stats
+        .into_iter()
+        .map(|(extension, (files, lines, size))| LanguageStats {
+            language: get_language_by_extension(&extension).map_or_else(|| extension.clone(), |l| l.name.to_string()),
+            extension,
+            files,
+            lines,
+            size,
+        })
+        .collect();
+    languages.sort_by(|a, b| b.lines.cmp(&a.lines).then_with(|| a.extension.cmp(&b.extension)));
+
+    let mut largest_files = files.clone();
+    largest_files.sort_by(|a, b| b.lines.cmp(&a.lines).then_with(|| a.path.cmp(&b.path)));
+    largest_files.truncate(5);
+
+    Ok(CodebaseStats {
+        total_files: files.len(),
+        total_lines,
+        total_size,
+        languages,
+        functions: function_count,
+        classes: class_count,
+        comment_lines,
+        comment_ratio: if total_lines > 0 { comment_lines as f64 / total_lines as f64 } else { 0.0 },
+        largest_files,
+    })
+}
+
+/// Analyze codebase and print metrics
+pub fn analyze_codebase(
+    path: &Path,
+    extensions: Option<&[String]>,
+    exclude: Option<&[String]>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    println!("{}", "Codebase Analysis".cyan().bold());
+    println!("{}", theme::rule(30).cyan());
+    println!();
+
+    let stats = compute_codebase_stats(path, extensions, exclude)?;
+    print_codebase_stats(&stats);
+
+    println!();
+    println!("{}", "Hygiene".yellow().bold());
+    let hygiene = crate::hygiene::scan_path(path, extensions, exclude)?;
+    crate::hygiene::print_summary(&hygiene.summary);
+    if !hygiene.files.is_empty() {
+        println!("  {}", "Run `codesearch hygiene` for the worst offenders".dimmed());
+    }
+
+    println!();
+    println!("{}", "Analysis complete!".green().italic());
+
+    Ok(())
+}
+
+/// Print the overview, language and code-pattern sections of `stats`
+pub fn print_codebase_stats(stats: &CodebaseStats) {
     println!("{}", "Overview".yellow().bold());
-    println!("  Total files: {}", files.len().to_string().green());
-    println!("  Total lines: {}", total_lines.to_string().green());
-    println!("  Total size: {}", format_size(total_size).green());
+    println!("  Total files: {}", stats.total_files.to_string().green());
+    println!("  Total lines: {}", stats.total_lines.to_string().green());
+    println!("  Total size: {}", format_size(stats.total_size).green());
     println!();
 
     println!("{}", "Languages".yellow().bold());
-    let mut lang_vec: Vec<_> = language_stats.iter().collect();
-    lang_vec.sort_by(|a, b| b.1 .1.cmp(&a.1 .1));
-
-    for (ext, (file_count, lines, bytes)) in lang_vec.iter().take(10) {
-        let lang_name = get_language_by_extension(ext)
-            .map(|l| l.name)
-            .unwrap_or(*ext);
+    for language in stats.languages.iter().take(10) {
         println!(
             "  {} {}: {} files, {} lines ({})",
-            "•".dimmed(),
-            lang_name.cyan(),
-            file_count.to_string().yellow(),
-            lines.to_string().green(),
-            format_size(*bytes).dimmed()
+            theme::bullet().dimmed(),
+            language.language.cyan(),
+            language.files.to_string().yellow(),
+            language.lines.to_string().green(),
+            format_size(language.size).dimmed()
         );
     }
     println!();
 
     println!("{}", "Code Patterns".yellow().bold());
-    println!("  Functions/Methods: {}", function_count.to_string().green());
-    println!("  Classes/Structs: {}", class_count.to_string().green());
-    println!("  Comment lines: {}", comment_lines.to_string().green());
+    println!("  Functions/Methods: {}", stats.functions.to_string().green());
+    println!("  Classes/Structs: {}", stats.classes.to_string().green());
+    println!("  Comment lines: {}", stats.comment_lines.to_string().green());
 
-    if total_lines > 0 {
-        let comment_ratio = (comment_lines as f64 / total_lines as f64) * 100.0;
-        println!("  Comment ratio: {comment_ratio:.1}%");
+    if stats.total_lines > 0 {
+        println!("  Comment ratio: {:.1}%", stats.comment_ratio * 100.0);
     }
+}
 
-    println!();
-    println!("{}", "Analysis complete!".green().italic());
-
-    Ok(())
+/// Functions, classes and comment lines in `content`, using the language's patterns when known
+fn count_code_patterns(ext: &str, content: &str) -> (usize, usize, usize) {
+    let Some(lang_info) = get_language_by_extension(ext) else {
+        return (count_generic_functions(content), count_generic_classes(content), count_generic_comments(content));
+    };
+    let count = |patterns: &[&str]| -> usize {
+        patterns
+            .iter()
+            .filter_map(|p| Regex::new(p).ok())
+            .map(|re| re.find_iter(content).count())
+            .sum()
+    };
+    (
+        count(lang_info.function_patterns),
+        count(lang_info.class_patterns),
+        count(lang_info.comment_patterns),
+    )
 }
 
 fn count_generic_functions(content: &str) -> usize {
@@ -126,8 +228,7 @@ fn count_generic_classes(content: &str) -> usize {
 }
 
 fn count_generic_comments(content: &str) -> usize {
-    content
-        .lines()
+    split_lines(content)
         .filter(|l| {
             let trimmed = l.trim();
             trimmed.starts_with("//")
@@ -138,7 +239,8 @@ fn count_generic_comments(content: &str) -> usize {
         .count()
 }
 
-fn format_size(bytes: u64) -> String {
+/// Human-readable size, e.g. `1.5 KB`
+pub fn format_size(bytes: u64) -> String {
     const KB: u64 = 1024;
     const MB: u64 = KB * 1024;
     const GB: u64 = MB * 1024;
@@ -154,15 +256,25 @@ fn format_size(bytes: u64) -> String {
     }
 }
 
+/// The suggestion types to look for: those in `only` (every type when it's empty), minus `disabled`
+pub fn enabled_suggestion_types(only: &[SuggestionType], disabled: &[SuggestionType]) -> HashSet<SuggestionType> {
+    let candidates: &[SuggestionType] = if only.is_empty() { &SuggestionType::ALL } else { only };
+    candidates.iter().copied().filter(|kind| !disabled.contains(kind)).collect()
+}
+
 /// Suggest refactoring improvements for the codebase
+///
+/// Only the `enabled` suggestion types are looked for.
 pub fn suggest_refactoring(
     path: &Path,
     extensions: Option<&[String]>,
     exclude: Option<&[String]>,
     high_priority_only: bool,
+    limits: &ComplexityConfig,
+    enabled: &HashSet<SuggestionType>,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    println!("{}", "🔧 Code Refactoring Suggestions".cyan().bold());
-    println!("{}", "─".repeat(30).cyan());
+    println!("{}", theme::heading(Glyph::Refactor, "Code Refactoring Suggestions").cyan().bold());
+    println!("{}", theme::rule(30).cyan());
     println!();
 
     let files = list_files(path, extensions, exclude)?;
@@ -171,7 +283,7 @@ pub fn suggest_refactoring(
     for file in &files {
         let content = read_file_content(&file.path);
         if !content.is_empty() {
-            analyze_file_for_refactoring(&file.path, &content, &mut suggestions);
+            analyze_file_for_refactoring(&file.path, &content, limits, enabled, &mut suggestions);
         }
     }
 
@@ -188,13 +300,13 @@ pub fn suggest_refactoring(
         return Ok(());
     }
 
-    let mut grouped: HashMap<String, Vec<&RefactorSuggestion>> = HashMap::new();
+    let mut grouped: BTreeMap<SuggestionType, Vec<&RefactorSuggestion>> = BTreeMap::new();
     for suggestion in &filtered_suggestions {
-        grouped.entry(suggestion.suggestion_type.clone()).or_default().push(suggestion);
+        grouped.entry(suggestion.suggestion_type).or_default().push(suggestion);
     }
 
     for (suggestion_type, type_suggestions) in grouped {
-        println!("{}", format!("📋 {} ({})", suggestion_type, type_suggestions.len()).yellow().bold());
+        println!("{}", theme::heading(Glyph::Group, &format!("{} ({})", suggestion_type, type_suggestions.len())).yellow().bold());
 
         for suggestion in type_suggestions {
             let priority_color = match suggestion.priority {
@@ -222,12 +334,17 @@ pub fn suggest_refactoring(
 }
 
 /// Analyze a file for refactoring opportunities
+///
+/// Functions over their language's complexity limits in `limits` are
+/// reported with the limits they exceed. Types not in `enabled` aren't looked for.
 pub fn analyze_file_for_refactoring(
     file_path: &str,
     content: &str,
+    limits: &ComplexityConfig,
+    enabled: &HashSet<SuggestionType>,
     suggestions: &mut Vec<RefactorSuggestion>,
 ) {
-    let lines: Vec<&str> = content.lines().collect();
+    let lines: Vec<&str> = split_lines(content).collect();
     let magic_regex = Regex::new(r"[^\w](\d{2,})[^\w]").unwrap();
 
     for (i, line) in lines.iter().enumerate() {
@@ -235,24 +352,24 @@ pub fn analyze_file_for_refactoring(
         let trimmed = line.trim();
 
         // Long lines
-        if line.len() > 100 {
+        if enabled.contains(&SuggestionType::LongLine) && line.len() > 100 {
             suggestions.push(RefactorSuggestion {
                 file: file_path.to_string(),
                 line_number: line_num,
-                suggestion_type: "Long Line".to_string(),
+                suggestion_type: SuggestionType::LongLine,
                 description: "Line exceeds 100 characters".to_string(),
                 priority: 3,
-                code_snippet: trimmed.chars().take(50).collect::<String>() + "...",
+                code_snippet: truncate_str_safe(trimmed, SNIPPET_CHARS),
                 improvement: "Break into multiple lines".to_string(),
             });
         }
 
         // TODO comments
-        if trimmed.contains("TODO") || trimmed.contains("FIXME") || trimmed.contains("HACK") {
+        if enabled.contains(&SuggestionType::TodoComment) && (trimmed.contains("TODO") || trimmed.contains("FIXME") || trimmed.contains("HACK")) {
             suggestions.push(RefactorSuggestion {
                 file: file_path.to_string(),
                 line_number: line_num,
-                suggestion_type: "Todo Comment".to_string(),
+                suggestion_type: SuggestionType::TodoComment,
                 description: "Unfinished work marker found".to_string(),
                 priority: 5,
                 code_snippet: trimmed.to_string(),
@@ -261,11 +378,11 @@ pub fn analyze_file_for_refactoring(
         }
 
         // Magic numbers
-        if magic_regex.is_match(trimmed) && !trimmed.contains("//") {
+        if enabled.contains(&SuggestionType::MagicNumber) && magic_regex.is_match(trimmed) && !trimmed.contains("//") {
             suggestions.push(RefactorSuggestion {
                 file: file_path.to_string(),
                 line_number: line_num,
-                suggestion_type: "Magic Number".to_string(),
+                suggestion_type: SuggestionType::MagicNumber,
                 description: "Consider extracting to a named constant".to_string(),
                 priority: 4,
                 code_snippet: trimmed.to_string(),
@@ -275,26 +392,26 @@ pub fn analyze_file_for_refactoring(
 
         // Deep nesting
         let indent_level = line.len() - line.trim_start().len();
-        if indent_level > 16 {
+        if enabled.contains(&SuggestionType::DeepNesting) && indent_level > 16 {
             suggestions.push(RefactorSuggestion {
                 file: file_path.to_string(),
                 line_number: line_num,
-                suggestion_type: "Deep Nesting".to_string(),
+                suggestion_type: SuggestionType::DeepNesting,
                 description: "Code is deeply nested (>4 levels)".to_string(),
                 priority: 7,
-                code_snippet: trimmed.chars().take(50).collect::<String>(),
+                code_snippet: truncate_str_safe(trimmed, SNIPPET_CHARS),
                 improvement: "Extract to separate functions or use early returns".to_string(),
             });
         }
 
         // Empty catch blocks
-        if trimmed.contains("catch") && i + 1 < lines.len() {
+        if enabled.contains(&SuggestionType::EmptyCatchBlock) && trimmed.contains("catch") && i + 1 < lines.len() {
             let next_line = lines[i + 1].trim();
             if next_line == "}" || next_line.is_empty() {
                 suggestions.push(RefactorSuggestion {
                     file: file_path.to_string(),
                     line_number: line_num,
-                    suggestion_type: "Empty Catch Block".to_string(),
+                    suggestion_type: SuggestionType::EmptyCatchBlock,
                     description: "Empty catch block swallows errors".to_string(),
                     priority: 8,
                     code_snippet: trimmed.to_string(),
@@ -303,12 +420,65 @@ pub fn analyze_file_for_refactoring(
             }
         }
     }
+
+    if enabled.contains(&SuggestionType::ComplexFunction) {
+        let (language, thresholds) = limits.thresholds_for_path(file_path);
+        let ext = get_file_extension(file_path);
+        for function in calculate_function_complexities(content, ext) {
+            let over = exceeded_thresholds(&function, &thresholds);
+            if over.is_empty() {
+                continue;
+            }
+            suggestions.push(RefactorSuggestion {
+                file: file_path.to_string(),
+                line_number: function.start_line,
+                suggestion_type: SuggestionType::ComplexFunction,
+                description: format!("`{}` is over the {language} limits: {}", function.name, over.join(", ")),
+                priority: 6,
+                code_snippet: lines.get(function.start_line - 1).map(|l| truncate_str_safe(l.trim(), SNIPPET_CHARS)).unwrap_or_default(),
+                improvement: "Split into smaller functions or simplify the branching".to_string(),
+            });
+        }
+    }
+
+    if enabled.contains(&SuggestionType::TrivialDelegation) {
+        detect_trivial_delegation(file_path, content, suggestions);
+    }
+}
+
+/// A supported language as listed by `languages --format json`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LanguageEntry {
+    pub name: &'static str,
+    pub extensions: &'static [&'static str],
+    /// Further extensions mapped to this language by `[language.overrides]`
+    pub overridden_extensions: Vec<String>,
+    /// Built-in per-function complexity limits
+    pub default_thresholds: ComplexityThresholds,
+    /// Limits in effect, after the config's `[complexity]` overrides
+    pub thresholds: ComplexityThresholds,
+}
+
+/// Every supported language with its complexity limits
+pub fn language_table(limits: &ComplexityConfig) -> Vec<LanguageEntry> {
+    get_supported_languages()
+        .iter()
+        .map(|language| LanguageEntry {
+            name: language.name,
+            extensions: language.extensions,
+            overridden_extensions: overridden_extensions(language.name),
+            default_thresholds: language.default_thresholds,
+            thresholds: limits.thresholds(language),
+        })
+        .collect()
 }
 
 /// List all supported programming languages
+///
+/// Extensions from `[language.overrides]` are listed after the built-in ones.
 pub fn list_supported_languages() -> Result<(), Box<dyn std::error::Error>> {
-    println!("{}", "🌐 Supported Programming Languages".cyan().bold());
-    println!("{}", "─".repeat(35).cyan());
+    println!("{}", theme::heading(Glyph::Languages, "Supported Programming Languages").cyan().bold());
+    println!("{}", theme::rule(35).cyan());
     println!();
 
     let languages = get_supported_languages();
@@ -329,8 +499,12 @@ pub fn list_supported_languages() -> Result<(), Box<dyn std::error::Error>> {
         println!("{}", format!("[{}]", category).yellow().bold());
         for lang_name in category_langs {
             if let Some(lang) = languages.iter().find(|l| l.name == *lang_name) {
-                let exts = lang.extensions.join(", ");
-                println!("   {} {} ({})", "•".dimmed(), lang.name.green(), exts.dimmed());
+                let mut exts = lang.extensions.join(", ");
+                let overridden = overridden_extensions(lang.name);
+                if !overridden.is_empty() {
+                    exts.push_str(&format!("; from config: {}", overridden.join(", ")));
+                }
+                println!("   {} {} ({})", theme::bullet().dimmed(), lang.name.green(), exts.dimmed());
             }
         }
         println!();
@@ -345,6 +519,32 @@ pub fn list_supported_languages() -> Result<(), Box<dyn std::error::Error>> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn test_compute_codebase_stats() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("lib.rs"), "// math\nfn add() {}\nfn sub() {}\nstruct Calc;\n").unwrap();
+        std::fs::write(dir.path().join("page.html"), "<p>hi</p>\n<script>\nfunction go() {}\n</script>\n").unwrap();
+
+        let stats = compute_codebase_stats(dir.path(), None, None).unwrap();
+        assert_eq!(stats.total_files, 2);
+        assert_eq!(stats.total_lines, 8);
+        assert_eq!(stats.functions, 3);
+        // `struct Calc`, plus the <p> and <script> elements XML/HTML counts as classes
+        assert_eq!(stats.classes, 3);
+        assert_eq!(stats.comment_lines, 1);
+        assert!((stats.comment_ratio - 1.0 / 8.0).abs() < 1e-9);
+        assert_eq!(stats.languages[0].extension, "rs");
+        assert_eq!(stats.languages[0].language, "Rust");
+        // The script block counts as JavaScript, not HTML
+        assert!(stats.languages.iter().any(|l| l.extension == "js" && l.files == 1));
+        assert_eq!(stats.largest_files.len(), 2);
+
+        let json = serde_json::to_value(&stats).unwrap();
+        assert_eq!(json["total_files"], 2);
+        assert_eq!(json["languages"][0]["lines"], 4);
+    }
 
     #[test]
     fn test_format_size() {
@@ -365,11 +565,84 @@ mod tests {
         assert!(count_generic_classes(code) >= 3);
     }
 
+    fn every_type() -> HashSet<SuggestionType> {
+        enabled_suggestion_types(&[], &[])
+    }
+
     #[test]
     fn test_analyze_file_for_refactoring() {
         let content = "// TODO: fix this\nlet x = 12345;";
         let mut suggestions = Vec::new();
-        analyze_file_for_refactoring("test.rs", content, &mut suggestions);
+        analyze_file_for_refactoring("test.rs", content, &ComplexityConfig::default(), &every_type(), &mut suggestions);
         assert!(!suggestions.is_empty());
     }
+
+    #[test]
+    fn test_long_line_snippet_keeps_emoji_sequences_whole() {
+        // A family emoji (joined with zero width joiners) straddles the snippet's end
+        let line = format!("let s = \"{}\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}{}\";", "x".repeat(36), "y".repeat(80));
+        let mut suggestions = Vec::new();
+        analyze_file_for_refactoring("lib.rs", &line, &ComplexityConfig::default(), &every_type(), &mut suggestions);
+        let long = suggestions.iter().find(|s| s.suggestion_type == SuggestionType::LongLine).unwrap();
+        assert_eq!(long.code_snippet, format!("let s = \"{}...", "x".repeat(36)));
+    }
+
+    #[test]
+    fn test_refactoring_reports_trivial_delegation() {
+        let content = "fn load(path: &str) -> String {\n    read_config(path)\n}\n";
+        let mut suggestions = Vec::new();
+        analyze_file_for_refactoring("lib.rs", content, &ComplexityConfig::default(), &every_type(), &mut suggestions);
+        let delegation = suggestions.iter().find(|s| s.suggestion_type == SuggestionType::TrivialDelegation).unwrap();
+        assert_eq!(delegation.line_number, 1);
+        assert!(delegation.improvement.contains("read_config"));
+    }
+
+    #[test]
+    fn test_language_table_applies_overrides() {
+        let limits = Config::parse("[complexity.languages.sql]\ncyclomatic = 3\n").unwrap().complexity;
+        let table = language_table(&limits);
+        let sql = table.iter().find(|l| l.name == "SQL").unwrap();
+        assert_eq!(sql.default_thresholds.cyclomatic, 5);
+        assert_eq!(sql.thresholds, ComplexityThresholds { cyclomatic: 3, ..sql.default_thresholds });
+        let python = table.iter().find(|l| l.name == "Python").unwrap();
+        assert_eq!(python.thresholds, python.default_thresholds);
+    }
+
+    #[test]
+    fn test_refactoring_flags_functions_over_language_limits() {
+        let branches = "    if a { 1 } else if b { 2 } else { 3 };\n".repeat(4);
+        let content = format!("fn branchy(a: bool, b: bool) {{\n{branches}}}\n");
+        let mut suggestions = Vec::new();
+        analyze_file_for_refactoring("lib.rs", &content, &ComplexityConfig::default(), &every_type(), &mut suggestions);
+        let complex = suggestions.iter().find(|s| s.suggestion_type == SuggestionType::ComplexFunction).unwrap();
+        assert_eq!(complex.line_number, 1);
+        assert!(complex.description.contains("over the Rust limits: CC 21 > 10"), "{}", complex.description);
+
+        let lenient = Config::parse("[complexity.languages.rust]\ncyclomatic = 30\ncognitive = 100\n").unwrap().complexity;
+        let mut suggestions = Vec::new();
+        analyze_file_for_refactoring("lib.rs", &content, &lenient, &every_type(), &mut suggestions);
+        assert!(!suggestions.iter().any(|s| s.suggestion_type == SuggestionType::ComplexFunction));
+    }
+
+    #[test]
+    fn test_disabled_suggestion_types_are_not_looked_for() {
+        let content = format!("// TODO: fix this\nlet x = 12345; // {}\nfn load(path: &str) -> String {{\n    read_config(path)\n}}\n", "x".repeat(100));
+        let types = |enabled: &HashSet<SuggestionType>| {
+            let mut suggestions = Vec::new();
+            analyze_file_for_refactoring("lib.rs", &content, &ComplexityConfig::default(), enabled, &mut suggestions);
+            suggestions.into_iter().map(|s| s.suggestion_type).collect::<HashSet<_>>()
+        };
+
+        let all = types(&every_type());
+        assert!(all.contains(&SuggestionType::LongLine) && all.contains(&SuggestionType::TrivialDelegation), "{all:?}");
+
+        let enabled = enabled_suggestion_types(&[], &[SuggestionType::LongLine, SuggestionType::TodoComment]);
+        let found = types(&enabled);
+        assert!(!found.contains(&SuggestionType::LongLine) && !found.contains(&SuggestionType::TodoComment), "{found:?}");
+        assert!(found.contains(&SuggestionType::TrivialDelegation));
+
+        let only = enabled_suggestion_types(&[SuggestionType::TodoComment, SuggestionType::LongLine], &[SuggestionType::LongLine]);
+        assert_eq!(only, HashSet::from([SuggestionType::TodoComment]));
+        assert_eq!(types(&only), only);
+    }
 }
@@ -0,0 +1,553 @@
+//! API Surface Diff
+//!
+//! `codesearch api-diff FROM..TO` lists the public symbols added, removed or
+//! changed between two git revisions, for release notes. Definitions are read
+//! from each revision's blobs, not from the working tree:
+//!
+//! - Rust: `pub` functions, structs, enums, traits and type aliases; methods
+//!   are qualified by the type of their `impl` block
+//! - Python: top-level `def`s and classes whose name doesn't start with `_`
+//! - JavaScript/TypeScript: `export`ed functions, classes, variables,
+//!   interfaces, types and enums
+//!
+//! Symbols are matched by kind and qualified name (module path and name), so
+//! a moved file shows up as removals and additions. A signature change is
+//! textual: the declaration up to its body, with whitespace normalized.
+
+use crate::errors::SearchError;
+use crate::githistory::GitError;
+use crate::lines::split_lines;
+use crate::options::has_extension;
+use crate::theme;
+use colored::*;
+use git2::{ObjectType, Oid, Repository, TreeWalkMode, TreeWalkResult};
+use regex::Regex;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Extensions compared when none are given
+pub const DEFAULT_EXTENSIONS: &[&str] = &["rs", "py", "js", "mjs", "cjs", "jsx", "ts", "tsx", "mts", "cts"];
+
+/// Lines a declaration may span before its body starts
+const MAX_DECLARATION_LINES: usize = 30;
+
+/// A public symbol at one revision
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ApiSymbol {
+    /// Module path and name, e.g. `crate::search::search_code` or `pkg.util.parse`
+    pub name: String,
+    /// `fn`, `struct`, `enum`, `trait`, `type`, `def`, `class`, `function`, `const`, `interface`
+    pub kind: String,
+    pub file: String,
+    /// 1-based line of the declaration
+    pub line: usize,
+    /// Declaration up to its body, whitespace normalized
+    pub signature: String,
+}
+
+/// A symbol present at both revisions with a different signature
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ApiChange {
+    pub before: ApiSymbol,
+    pub after: ApiSymbol,
+}
+
+/// Output of [`api_diff`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ApiDiff {
+    pub from: String,
+    pub to: String,
+    pub added: Vec<ApiSymbol>,
+    pub removed: Vec<ApiSymbol>,
+    pub changed: Vec<ApiChange>,
+}
+
+impl ApiDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Split `FROM..TO` into its revisions; a single revision is compared with `HEAD`
+pub fn parse_range(range: &str) -> Result<(String, String), SearchError> {
+    let (from, to) = match range.split_once("..") {
+        Some((from, to)) => (from.trim(), to.trim()),
+        None => (range.trim(), "HEAD"),
+    };
+    if from.is_empty() || to.is_empty() || to.starts_with('.') {
+        return Err(SearchError::InvalidOptions {
+            message: format!("invalid revision range {range:?} (expected FROM..TO, e.g. v1.2.0..HEAD)"),
+        });
+    }
+    Ok((from.to_string(), to.to_string()))
+}
+
+/// Compare the public API of the repository at `repo_path` between the two revisions of `range`
+///
+/// Only files with one of `extensions` ([`DEFAULT_EXTENSIONS`] if `None`) are
+/// read, skipping directories named in `exclude`.
+pub fn api_diff(
+    repo_path: &Path,
+    range: &str,
+    extensions: Option<&[String]>,
+    exclude: Option<&[String]>,
+) -> Result<ApiDiff, Box<dyn std::error::Error>> {
+    let (from, to) = parse_range(range)?;
+    let repo = Repository::discover(repo_path).map_err(|_| GitError::NotARepository { path: repo_path.to_path_buf() })?;
+    let extensions: Vec<String> = match extensions {
+        Some(extensions) => extensions.to_vec(),
+        None => DEFAULT_EXTENSIONS.iter().map(|e| e.to_string()).collect(),
+    };
+
+    let before = surface_at(&repo, &from, &extensions, exclude.unwrap_or_default())?;
+    let after = surface_at(&repo, &to, &extensions, exclude.unwrap_or_default())?;
+    Ok(diff_surfaces(from, to, before, after))
+}
+
+/// Public symbols of every matching file in `rev`'s tree
+fn surface_at(repo: &Repository, rev: &str, extensions: &[String], exclude: &[String]) -> Result<Vec<ApiSymbol>, Box<dyn std::error::Error>> {
+    let tree = repo
+        .revparse_single(rev)
+        .and_then(|object| object.peel_to_tree())
+        .map_err(|e| format!("unknown revision {rev}: {}", e.message()))?;
+
+    let mut files: Vec<(String, Oid)> = Vec::new();
+    tree.walk(TreeWalkMode::PreOrder, |dir, entry| {
+        let name = entry.name().unwrap_or_default();
+        match entry.kind() {
+            Some(ObjectType::Tree) if exclude.iter().any(|e| e == name) => TreeWalkResult::Skip,
+            Some(ObjectType::Blob) => {
+                let path = format!("{dir}{name}");
+                if has_extension(Path::new(&path), extensions) {
+                    files.push((path, entry.id()));
+                }
+                TreeWalkResult::Ok
+            }
+            _ => TreeWalkResult::Ok,
+        }
+    })?;
+
+    let mut symbols = Vec::new();
+    for (path, id) in files {
+        // Blobs a partial clone left out can't be compared; neither can binary files
+        let Ok(blob) = repo.find_blob(id) else { continue };
+        let Ok(content) = std::str::from_utf8(blob.content()) else { continue };
+        symbols.extend(public_symbols(&path, content));
+    }
+    Ok(symbols)
+}
+
+/// Match symbols by kind and name, sorting each group by name
+pub fn diff_surfaces(from: String, to: String, before: Vec<ApiSymbol>, after: Vec<ApiSymbol>) -> ApiDiff {
+    let index = |symbols: Vec<ApiSymbol>| {
+        let mut map: BTreeMap<(String, String), ApiSymbol> = BTreeMap::new();
+        for symbol in symbols {
+            // The first of several cfg-dependent definitions stands for all of them
+            map.entry((symbol.name.clone(), symbol.kind.clone())).or_insert(symbol);
+        }
+        map
+    };
+    let mut before = index(before);
+    let after = index(after);
+
+    let mut diff = ApiDiff { from, to, ..ApiDiff::default() };
+    for (key, symbol) in after {
+        match before.remove(&key) {
+            None => diff.added.push(symbol),
+            Some(old) if old.signature != symbol.signature => diff.changed.push(ApiChange { before: old, after: symbol }),
+            Some(_) => {}
+        }
+    }
+    diff.removed = before.into_values().collect();
+    diff
+}
+
+/// Public symbols declared in a file, by its extension
+pub fn public_symbols(file_path: &str, content: &str) -> Vec<ApiSymbol> {
+    let lines: Vec<&str> = split_lines(content).collect();
+    match crate::parser::get_file_extension(file_path) {
+        "rs" => rust_symbols(file_path, &lines),
+        "py" | "pyi" => python_symbols(file_path, &lines),
+        "js" | "mjs" | "cjs" | "jsx" | "ts" | "tsx" | "mts" | "cts" => js_symbols(file_path, &lines),
+        _ => Vec::new(),
+    }
+}
+
+fn rust_symbols(file_path: &str, lines: &[&str]) -> Vec<ApiSymbol> {
+    static ITEM: OnceLock<Regex> = OnceLock::new();
+    static BLOCK: OnceLock<Regex> = OnceLock::new();
+    let item = ITEM.get_or_init(|| {
+        Regex::new(r#"^\s*pub\s+(?:(?:const|async|unsafe|extern\s+"\w+")\s+)*(fn|struct|enum|trait|type)\s+(\w+)"#).unwrap()
+    });
+    // `impl` blocks and inline modules opened at the start of a line
+    let block = BLOCK.get_or_init(|| {
+        Regex::new(r"^(?:impl(?:<.*?>)?\s+(?:[\w:<>, ]+\s+for\s+)?(?:\w+::)*(\w+)|(pub\s+)?mod\s+(\w+)\s*\{)").unwrap()
+    });
+
+    let module = rust_module(file_path);
+    let mut symbols = Vec::new();
+    // Qualifier of the enclosing block, and whether its items are public
+    let mut enclosing: Option<(String, bool)> = None;
+    for (i, line) in lines.iter().enumerate() {
+        if let Some(caps) = block.captures(line) {
+            enclosing = match (caps.get(1), caps.get(3)) {
+                (Some(ty), _) => Some((ty.as_str().to_string(), true)),
+                (None, Some(name)) => Some((name.as_str().to_string(), caps.get(2).is_some())),
+                _ => None,
+            };
+            continue;
+        }
+        if line.starts_with('}') {
+            enclosing = None;
+            continue;
+        }
+        let Some(caps) = item.captures(line) else { continue };
+        let nested = line.starts_with(char::is_whitespace);
+        let qualifier = match (&enclosing, nested) {
+            (Some((_, false)), true) => continue,
+            (Some((name, true)), true) => format!("{module}::{name}"),
+            _ => module.clone(),
+        };
+        symbols.push(ApiSymbol {
+            name: format!("{qualifier}::{}", &caps[2]),
+            kind: caps[1].to_string(),
+            file: file_path.to_string(),
+            line: i + 1,
+            signature: declaration(lines, i, Terminator::Brace),
+        });
+    }
+    symbols
+}
+
+fn python_symbols(file_path: &str, lines: &[&str]) -> Vec<ApiSymbol> {
+    static ITEM: OnceLock<Regex> = OnceLock::new();
+    let item = ITEM.get_or_init(|| Regex::new(r"^(?:async\s+)?(def|class)\s+([A-Za-z]\w*)").unwrap());
+
+    let module = script_module(file_path, "__init__").replace('/', ".");
+    lines
+        .iter()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let caps = item.captures(line)?;
+            Some(ApiSymbol {
+                name: format!("{module}.{}", &caps[2]),
+                kind: caps[1].to_string(),
+                file: file_path.to_string(),
+                line: i + 1,
+                signature: declaration(lines, i, Terminator::Colon),
+            })
+        })
+        .collect()
+}
+
+fn js_symbols(file_path: &str, lines: &[&str]) -> Vec<ApiSymbol> {
+    static ITEM: OnceLock<Regex> = OnceLock::new();
+    let item = ITEM.get_or_init(|| {
+        Regex::new(r"^export\s+(?:default\s+)?(?:declare\s+)?(?:async\s+)?(?:abstract\s+)?(function|class|const|let|var|interface|type|enum)\*?\s+(\w+)").unwrap()
+    });
+
+    let module = script_module(file_path, "index");
+    lines
+        .iter()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let caps = item.captures(line)?;
+            let kind = match &caps[1] {
+                "let" | "var" => "const",
+                kind => kind,
+            };
+            Some(ApiSymbol {
+                name: format!("{module}.{}", &caps[2]),
+                kind: kind.to_string(),
+                file: file_path.to_string(),
+                line: i + 1,
+                signature: declaration(lines, i, Terminator::Brace),
+            })
+        })
+        .collect()
+}
+
+/// `crate::a::b` for `src/a/b.rs`; files outside a `src/` directory keep their directories,
+/// and crates in a workspace are named after their directory
+fn rust_module(file_path: &str) -> String {
+    let path = file_path.trim_end_matches(".rs");
+    let (root, path) = match path.rsplit_once("src/") {
+        Some((crate_dir, rest)) => {
+            let crate_name = crate_dir.trim_end_matches('/').rsplit('/').next().filter(|n| !n.is_empty());
+            (crate_name.unwrap_or("crate").replace('-', "_"), rest)
+        }
+        None => ("crate".to_string(), path),
+    };
+    let mut segments: Vec<&str> = path.split('/').collect();
+    if matches!(segments.last(), Some(&("lib" | "main" | "mod"))) {
+        segments.pop();
+    }
+    std::iter::once(root.as_str()).chain(segments).collect::<Vec<_>>().join("::")
+}
+
+/// Module path of a script file: its path without extension or a leading `src/`,
+/// and without a trailing `index_name` segment (`__init__`, `index`)
+fn script_module(file_path: &str, index_name: &str) -> String {
+    let path = file_path.strip_prefix("src/").unwrap_or(file_path);
+    let path = path.rsplit_once('.').map_or(path, |(stem, _)| stem);
+    let path = path.strip_suffix(index_name).map_or(path, |p| p.trim_end_matches('/'));
+    if path.is_empty() { index_name.to_string() } else { path.to_string() }
+}
+
+/// Where a declaration's body begins
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Terminator {
+    /// `{` or `;` (Rust, JavaScript)
+    Brace,
+    /// `:` (Python)
+    Colon,
+}
+
+/// The declaration starting at `lines[start]`, up to its body outside any brackets
+fn declaration(lines: &[&str], start: usize, terminator: Terminator) -> String {
+    let mut text = String::new();
+    let mut depth = 0i32;
+    'lines: for line in lines.iter().skip(start).take(MAX_DECLARATION_LINES) {
+        for c in line.chars() {
+            match c {
+                '(' | '[' => depth += 1,
+                ')' | ']' => depth -= 1,
+                '{' | ';' if depth <= 0 && terminator == Terminator::Brace => break 'lines,
+                ':' if depth <= 0 && terminator == Terminator::Colon => break 'lines,
+                _ => {}
+            }
+            text.push(c);
+        }
+        text.push(' ');
+    }
+    normalize_signature(&text)
+}
+
+/// Collapse whitespace, and drop it inside brackets and before commas, along with trailing commas
+pub fn normalize_signature(text: &str) -> String {
+    let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut out = String::with_capacity(collapsed.len());
+    let mut chars = collapsed.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            ' ' if matches!(chars.peek(), Some(')' | ']' | ',')) => {}
+            ' ' if out.ends_with(['(', '[']) => {}
+            ',' if matches!(chars.peek(), Some(')' | ']')) => {}
+            ',' if chars.peek() == Some(&' ') && matches!(chars.clone().nth(1), Some(')' | ']')) => {}
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Print a diff grouped by added, removed and changed symbols
+pub fn print_api_diff(diff: &ApiDiff) {
+    println!("{}", format!("API changes {}..{}", diff.from, diff.to).cyan().bold());
+    println!("{}", theme::rule(30).cyan());
+    if diff.is_empty() {
+        println!("{}", "No public symbols were added, removed or changed.".green());
+        return;
+    }
+
+    let location = |symbol: &ApiSymbol| theme::link(format!("{}:{}", symbol.file, symbol.line).dimmed(), &symbol.file, Some(symbol.line));
+    if !diff.added.is_empty() {
+        println!("\n{}", format!("Added ({}):", diff.added.len()).green().bold());
+        for symbol in &diff.added {
+            println!("  + {} {}  {}", symbol.kind.dimmed(), symbol.name, location(symbol));
+        }
+    }
+    if !diff.removed.is_empty() {
+        println!("\n{}", format!("Removed ({}):", diff.removed.len()).red().bold());
+        for symbol in &diff.removed {
+            println!("  - {} {}  {}", symbol.kind.dimmed(), symbol.name, location(symbol));
+        }
+    }
+    if !diff.changed.is_empty() {
+        println!("\n{}", format!("Changed ({}):", diff.changed.len()).yellow().bold());
+        for change in &diff.changed {
+            println!("  ~ {} {}", change.after.kind.dimmed(), change.after.name);
+            println!("      {} {}  {}", "before:".dimmed(), change.before.signature.red(), location(&change.before));
+            println!("      {} {}  {}", "after: ".dimmed(), change.after.signature.green(), location(&change.after));
+        }
+    }
+    println!(
+        "\n{}",
+        format!("{} added, {} removed, {} changed", diff.added.len(), diff.removed.len(), diff.changed.len()).cyan()
+    );
+}
+
+/// The diff as a Markdown section for a changelog
+pub fn render_markdown(diff: &ApiDiff) -> String {
+    let mut out = format!("## API changes `{}..{}`\n", diff.from, diff.to);
+    if diff.is_empty() {
+        out.push_str("\nNo public symbols were added, removed or changed.\n");
+        return out;
+    }
+    let entry = |symbol: &ApiSymbol| format!("- `{}`: `{}` ({}:{})\n", symbol.name, symbol.signature, symbol.file, symbol.line);
+    if !diff.added.is_empty() {
+        out.push_str("\n### Added\n\n");
+        diff.added.iter().for_each(|symbol| out.push_str(&entry(symbol)));
+    }
+    if !diff.removed.is_empty() {
+        out.push_str("\n### Removed\n\n");
+        diff.removed.iter().for_each(|symbol| out.push_str(&entry(symbol)));
+    }
+    if !diff.changed.is_empty() {
+        out.push_str("\n### Changed\n\n");
+        for change in &diff.changed {
+            out.push_str(&format!(
+                "- `{}` ({}:{} -> {}:{})\n  - before: `{}`\n  - after: `{}`\n",
+                change.after.name,
+                change.before.file,
+                change.before.line,
+                change.after.file,
+                change.after.line,
+                change.before.signature,
+                change.after.signature
+            ));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::{IndexAddOption, Signature};
+    use std::fs;
+    use tempfile::TempDir;
+
+    /// Write `files` into the repository's working tree (`None` deletes) and commit them all
+    fn commit(repo: &Repository, files: &[(&str, Option<&str>)], message: &str) {
+        let root = repo.workdir().unwrap();
+        for (path, content) in files {
+            let path = root.join(path);
+            match content {
+                Some(content) => {
+                    fs::create_dir_all(path.parent().unwrap()).unwrap();
+                    fs::write(path, content).unwrap();
+                }
+                None => fs::remove_file(path).unwrap(),
+            }
+        }
+        let mut index = repo.index().unwrap();
+        index.add_all(["*"], IndexAddOption::DEFAULT, None).unwrap();
+        index.update_all(["*"], None).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::now("Test", "test@example.com").unwrap();
+        let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        repo.commit(Some("HEAD"), &signature, &signature, message, &tree, &parents).unwrap();
+    }
+
+    #[test]
+    fn test_api_diff_between_revisions() {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        commit(
+            &repo,
+            &[
+                ("src/lib.rs", Some("pub fn stays() {}\n\npub fn grows(a: u32) -> u32 {\n    a\n}\n\npub fn dropped() {}\n\nfn private() {}\n")),
+                ("tools/util.py", Some("def parse(text):\n    return text\n\ndef _helper():\n    pass\n")),
+            ],
+            "v1",
+        );
+        let v1 = repo.head().unwrap().peel_to_commit().unwrap();
+        repo.tag_lightweight("v1.0.0", v1.as_object(), false).unwrap();
+
+        commit(
+            &repo,
+            &[
+                (
+                    "src/lib.rs",
+                    Some("pub fn stays() {}\n\npub fn grows(\n    a: u32,\n    b: u32,\n) -> u32 {\n    a + b\n}\n\npub fn added() {}\n\nfn private_too() {}\n"),
+                ),
+                ("tools/util.py", Some("def parse(text):\n    return text.strip()\n\ndef _helper2():\n    pass\n")),
+            ],
+            "v2",
+        );
+
+        let rs = ["rs".to_string(), "py".to_string()];
+        let diff = api_diff(dir.path(), "v1.0.0..HEAD", Some(&rs), None).unwrap();
+        let names = |symbols: &[ApiSymbol]| symbols.iter().map(|s| s.name.clone()).collect::<Vec<_>>();
+        assert_eq!(names(&diff.added), vec!["crate::added"]);
+        assert_eq!(names(&diff.removed), vec!["crate::dropped"]);
+        assert_eq!(diff.changed.len(), 1, "{:?}", diff.changed);
+
+        let change = &diff.changed[0];
+        assert_eq!(change.after.name, "crate::grows");
+        assert_eq!(change.before.signature, "pub fn grows(a: u32) -> u32");
+        assert_eq!(change.after.signature, "pub fn grows(a: u32, b: u32) -> u32");
+        assert_eq!((change.before.line, change.after.line), (3, 3));
+        assert_eq!(diff.added[0].line, 10);
+
+        // A single revision is compared with HEAD
+        assert_eq!(api_diff(dir.path(), "v1.0.0", Some(&rs), None).unwrap(), diff);
+        assert!(api_diff(dir.path(), "v1.0.0..nope", None, None).unwrap_err().to_string().contains("unknown revision nope"));
+
+        let markdown = render_markdown(&diff);
+        assert!(markdown.contains("### Added\n\n- `crate::added`: `pub fn added()` (src/lib.rs:10)\n"), "{markdown}");
+        assert!(markdown.contains("  - after: `pub fn grows(a: u32, b: u32) -> u32`\n"), "{markdown}");
+    }
+
+    #[test]
+    fn test_rust_symbols_are_public_and_qualified() {
+        let content = "pub struct Parser;\n\nimpl Parser {\n    pub fn new() -> Self {\n        Parser\n    }\n\n    fn hidden(&self) {}\n}\n\npub(crate) fn internal() {}\n\nmod private {\n    pub fn unreachable() {}\n}\n\npub mod nested {\n    pub async fn fetch(url: &str) {}\n}\n\npub trait Visit {}\npub enum Kind { A, B }\n";
+        let symbols: Vec<(String, String)> = public_symbols("crates/my-lib/src/parse/mod.rs", content)
+            .into_iter()
+            .map(|s| (s.kind, s.name))
+            .collect();
+        let expected = [
+            ("struct", "my_lib::parse::Parser"),
+            ("fn", "my_lib::parse::Parser::new"),
+            ("fn", "my_lib::parse::nested::fetch"),
+            ("trait", "my_lib::parse::Visit"),
+            ("enum", "my_lib::parse::Kind"),
+        ];
+        assert_eq!(symbols, expected.map(|(k, n)| (k.to_string(), n.to_string())));
+    }
+
+    #[test]
+    fn test_python_and_js_symbols() {
+        let python = "class Client(Base):\n    def method(self):\n        pass\n\ndef _private():\n    pass\n\nasync def fetch(url: str,\n          retries: int = 3) -> Dict[str, int]:\n    pass\n";
+        let symbols = public_symbols("src/pkg/__init__.py", python);
+        let summary: Vec<(&str, &str, &str)> = symbols.iter().map(|s| (s.kind.as_str(), s.name.as_str(), s.signature.as_str())).collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("class", "pkg.Client", "class Client(Base)"),
+                ("def", "pkg.fetch", "async def fetch(url: str, retries: int = 3) -> Dict[str, int]"),
+            ]
+        );
+
+        let js = "export function parse(text, options) {\n}\nfunction local() {}\nexport default class App extends Base {\n}\nexport const VERSION = '1.0';\nexport interface Props {\n}\n";
+        let symbols = public_symbols("src/util/index.ts", js);
+        let summary: Vec<(&str, &str, &str)> = symbols.iter().map(|s| (s.kind.as_str(), s.name.as_str(), s.signature.as_str())).collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("function", "util.parse", "export function parse(text, options)"),
+                ("class", "util.App", "export default class App extends Base"),
+                ("const", "util.VERSION", "export const VERSION = '1.0'"),
+                ("interface", "util.Props", "export interface Props"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("v1.2.0..HEAD").unwrap(), ("v1.2.0".to_string(), "HEAD".to_string()));
+        assert_eq!(parse_range("v1.2.0").unwrap(), ("v1.2.0".to_string(), "HEAD".to_string()));
+        assert!(parse_range("..HEAD").is_err());
+        assert!(parse_range("a...b").is_err());
+    }
+
+    #[test]
+    fn test_normalize_signature() {
+        assert_eq!(normalize_signature("pub fn f(\n    a: u32,\n    b: Vec<u8>,\n) -> u32 "), "pub fn f(a: u32, b: Vec<u8>) -> u32");
+        assert_eq!(normalize_signature("def  f( x ,y )"), "def f(x,y)");
+    }
+}
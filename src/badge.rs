@@ -0,0 +1,360 @@
+//! Status Badges
+//!
+//! `codesearch badge` turns one analysis number (average maintainability
+//! index, average cyclomatic complexity, dead code count) into a shields.io
+//! style flat SVG, or the JSON a shields.io endpoint badge reads. The value's
+//! color band comes from `[badge.<metric>]` thresholds in the config. Every
+//! computed value is appended to `.codesearch/history.jsonl`, so `--cached`
+//! can redraw the badge without analyzing again.
+
+use crate::codemetrics::analyze_project_metrics;
+use crate::deadcode::{find_dead_code_in, DeadCodeOptions};
+use crate::parser::ExtractionContext;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Where computed badge values are recorded, relative to the working directory
+pub const HISTORY_FILE: &str = ".codesearch/history.jsonl";
+
+/// A number a badge can show
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BadgeMetric {
+    /// Average maintainability index of the files, 0-100, higher is better
+    Maintainability,
+    /// Average cyclomatic complexity of the files, lower is better
+    Complexity,
+    /// Dead code findings, lower is better
+    Deadcode,
+}
+
+impl std::str::FromStr for BadgeMetric {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "maintainability" => Ok(Self::Maintainability),
+            "complexity" => Ok(Self::Complexity),
+            "deadcode" | "dead-code" => Ok(Self::Deadcode),
+            other => Err(format!("Unknown badge metric: {other} (expected: maintainability, complexity, deadcode)")),
+        }
+    }
+}
+
+impl BadgeMetric {
+    /// Text on the left of the badge
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Maintainability => "maintainability",
+            Self::Complexity => "complexity",
+            Self::Deadcode => "dead code",
+        }
+    }
+
+    fn higher_is_better(self) -> bool {
+        self == Self::Maintainability
+    }
+
+    /// Text on the right of the badge
+    pub fn format_value(self, value: f64) -> String {
+        match self {
+            Self::Maintainability => format!("{value:.0}/100"),
+            Self::Complexity => format!("{value:.1}"),
+            Self::Deadcode => format!("{value:.0}"),
+        }
+    }
+
+    /// Thresholds used when the config has none for this metric
+    pub fn default_bands(self) -> Bands {
+        match self {
+            // The ratings of `codesearch metrics`
+            Self::Maintainability => Bands { good: 80.0, fair: 60.0 },
+            Self::Complexity => Bands { good: 10.0, fair: 20.0 },
+            Self::Deadcode => Bands { good: 0.0, fair: 10.0 },
+        }
+    }
+}
+
+/// Thresholds of a metric's color bands: values at least as good as `good`
+/// are green, at least as good as `fair` yellow, the rest red
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Bands {
+    pub good: f64,
+    pub fair: f64,
+}
+
+/// Badge background color
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BadgeColor {
+    Green,
+    Yellow,
+    Red,
+}
+
+impl BadgeColor {
+    /// shields.io color name
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Green => "brightgreen",
+            Self::Yellow => "yellow",
+            Self::Red => "red",
+        }
+    }
+
+    /// The hex color shields.io draws for [`BadgeColor::name`]
+    pub fn hex(self) -> &'static str {
+        match self {
+            Self::Green => "#4c1",
+            Self::Yellow => "#dfb317",
+            Self::Red => "#e05d44",
+        }
+    }
+}
+
+/// Color band of `value` for `metric`
+pub fn color_for(metric: BadgeMetric, value: f64, bands: Bands) -> BadgeColor {
+    let at_least = |threshold: f64| if metric.higher_is_better() { value >= threshold } else { value <= threshold };
+    if at_least(bands.good) {
+        BadgeColor::Green
+    } else if at_least(bands.fair) {
+        BadgeColor::Yellow
+    } else {
+        BadgeColor::Red
+    }
+}
+
+/// A badge, serialized as a shields.io endpoint response
+/// (`{"schemaVersion": 1, "label": ..., "message": ..., "color": ...}`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Badge {
+    pub schema_version: u8,
+    pub label: String,
+    pub message: String,
+    pub color: &'static str,
+    #[serde(skip)]
+    color_hex: &'static str,
+}
+
+impl Badge {
+    /// The badge showing `value` for `metric`
+    pub fn new(metric: BadgeMetric, value: f64, bands: Bands) -> Self {
+        let color = color_for(metric, value, bands);
+        Self {
+            schema_version: 1,
+            label: metric.label().to_string(),
+            message: metric.format_value(value),
+            color: color.name(),
+            color_hex: color.hex(),
+        }
+    }
+}
+
+/// Approximate width in pixels of `text` in 11px Verdana, as shields.io sizes badges
+fn text_width(text: &str) -> u32 {
+    let width: f64 = text
+        .chars()
+        .map(|c| match c {
+            'i' | 'j' | 'l' | '.' | ',' | ':' | ';' | '!' | '|' | '\'' => 3.5,
+            'f' | 't' | 'r' | 'I' | ' ' | '/' | '(' | ')' | '-' => 4.5,
+            'm' | 'w' | 'M' | 'W' | '%' => 10.0,
+            c if c.is_ascii_uppercase() => 7.5,
+            _ => 6.8,
+        })
+        .sum();
+    width.round() as u32
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;").replace('\'', "&apos;")
+}
+
+/// A shields.io style flat badge
+pub fn render_svg(badge: &Badge) -> String {
+    let label_width = text_width(&badge.label) + 10;
+    let message_width = text_width(&badge.message) + 10;
+    let width = label_width + message_width;
+    // Text is drawn at 10x and scaled down, like shields.io, for sub-pixel centering
+    let label_x = label_width * 5;
+    let message_x = label_width * 10 + message_width * 5;
+    let (label_length, message_length) = ((label_width - 10) * 10, (message_width - 10) * 10);
+    let label = escape_xml(&badge.label);
+    let message = escape_xml(&badge.message);
+    let color = badge.color_hex;
+    format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="20" role="img" aria-label="{label}: {message}">
+<title>{label}: {message}</title>
+<linearGradient id="s" x2="0" y2="100%"><stop offset="0" stop-color="#bbb" stop-opacity=".1"/><stop offset="1" stop-opacity=".1"/></linearGradient>
+<clipPath id="r"><rect width="{width}" height="20" rx="3" fill="#fff"/></clipPath>
+<g clip-path="url(#r)"><rect width="{label_width}" height="20" fill="#555"/><rect x="{label_width}" width="{message_width}" height="20" fill="{color}"/><rect width="{width}" height="20" fill="url(#s)"/></g>
+<g fill="#fff" text-anchor="middle" font-family="Verdana,Geneva,DejaVu Sans,sans-serif" text-rendering="geometricPrecision" font-size="110">
+<text aria-hidden="true" x="{label_x}" y="150" fill="#010101" fill-opacity=".3" transform="scale(.1)" textLength="{label_length}">{label}</text>
+<text x="{label_x}" y="140" transform="scale(.1)" textLength="{label_length}">{label}</text>
+<text aria-hidden="true" x="{message_x}" y="150" fill="#010101" fill-opacity=".3" transform="scale(.1)" textLength="{message_length}">{message}</text>
+<text x="{message_x}" y="140" transform="scale(.1)" textLength="{message_length}">{message}</text>
+</g>
+</svg>
+"##
+    )
+}
+
+/// Run the analysis behind `metric` on `path`
+///
+/// Fails when `path` has no files the analysis understands.
+pub fn compute_metric(
+    metric: BadgeMetric,
+    path: &Path,
+    extensions: Option<&[String]>,
+    exclude: Option<&[String]>,
+) -> Result<f64, Box<dyn std::error::Error>> {
+    if !path.exists() {
+        return Err(format!("Cannot compute {} for {}: path not found", metric.label(), path.display()).into());
+    }
+    let no_files = || format!("Cannot compute {} for {}: no source files to analyze", metric.label(), path.display()).into();
+    match metric {
+        BadgeMetric::Maintainability | BadgeMetric::Complexity => {
+            let metrics = analyze_project_metrics(path, extensions, exclude)?;
+            if metrics.totals.total_files == 0 {
+                return Err(no_files());
+            }
+            Ok(match metric {
+                BadgeMetric::Maintainability => metrics.totals.avg_maintainability,
+                _ => metrics.totals.avg_cyclomatic,
+            })
+        }
+        BadgeMetric::Deadcode => {
+            let context = ExtractionContext::build(path, extensions, exclude)?;
+            if context.is_empty() {
+                return Err(no_files());
+            }
+            Ok(find_dead_code_in(&context, &DeadCodeOptions::default()).len() as f64)
+        }
+    }
+}
+
+/// A computed badge value, one line of [`HISTORY_FILE`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+    /// The analyzed path, as given
+    pub path: PathBuf,
+    pub metric: BadgeMetric,
+    pub value: f64,
+}
+
+impl HistoryEntry {
+    pub fn now(path: &Path, metric: BadgeMetric, value: f64) -> Self {
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        Self { timestamp, path: path.to_path_buf(), metric, value }
+    }
+}
+
+/// Append `entry` to the history file `file`
+pub fn record_history(file: &Path, entry: &HistoryEntry) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(dir) = file.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)?;
+    }
+    let mut out = OpenOptions::new().create(true).append(true).open(file)?;
+    writeln!(out, "{}", serde_json::to_string(entry)?)?;
+    Ok(())
+}
+
+/// The latest entry in the history file `file` for `metric` on `path`
+///
+/// Lines that aren't entries are skipped.
+pub fn latest_history(file: &Path, metric: BadgeMetric, path: &Path) -> Result<Option<HistoryEntry>, Box<dyn std::error::Error>> {
+    let content = match fs::read_to_string(file) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    Ok(content
+        .lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str::<HistoryEntry>(line).ok())
+        .find(|entry| entry.metric == metric && entry.path == path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_color_bands() {
+        let mi = BadgeMetric::Maintainability.default_bands();
+        assert_eq!(color_for(BadgeMetric::Maintainability, 85.0, mi), BadgeColor::Green);
+        assert_eq!(color_for(BadgeMetric::Maintainability, 80.0, mi), BadgeColor::Green);
+        assert_eq!(color_for(BadgeMetric::Maintainability, 65.0, mi), BadgeColor::Yellow);
+        assert_eq!(color_for(BadgeMetric::Maintainability, 59.9, mi), BadgeColor::Red);
+
+        // Lower is better for the others
+        let dead = Bands { good: 0.0, fair: 5.0 };
+        assert_eq!(color_for(BadgeMetric::Deadcode, 0.0, dead), BadgeColor::Green);
+        assert_eq!(color_for(BadgeMetric::Deadcode, 5.0, dead), BadgeColor::Yellow);
+        assert_eq!(color_for(BadgeMetric::Deadcode, 6.0, dead), BadgeColor::Red);
+        assert_eq!(color_for(BadgeMetric::Complexity, 4.2, BadgeMetric::Complexity.default_bands()), BadgeColor::Green);
+    }
+
+    #[test]
+    fn test_badge_json_is_a_shields_endpoint() {
+        let badge = Badge::new(BadgeMetric::Complexity, 12.345, BadgeMetric::Complexity.default_bands());
+        assert_eq!(
+            serde_json::to_value(&badge).unwrap(),
+            serde_json::json!({ "schemaVersion": 1, "label": "complexity", "message": "12.3", "color": "yellow" })
+        );
+        assert_eq!(Badge::new(BadgeMetric::Maintainability, 82.6, BadgeMetric::Maintainability.default_bands()).message, "83/100");
+    }
+
+    #[test]
+    fn test_render_svg() {
+        let badge = Badge::new(BadgeMetric::Deadcode, 3.0, BadgeMetric::Deadcode.default_bands());
+        let svg = render_svg(&badge);
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(svg.contains("<title>dead code: 3</title>"), "{svg}");
+        assert!(svg.contains("fill=\"#dfb317\""), "{svg}");
+
+        // The badge is as wide as its two halves, which grow with their text
+        let width = |svg: &str| svg.split("width=\"").nth(1).unwrap().split('"').next().unwrap().parse::<u32>().unwrap();
+        let label_width = text_width("dead code") + 10;
+        assert_eq!(width(&svg), label_width + text_width("3") + 10);
+        let wider = Badge { message: "1234".to_string(), ..badge.clone() };
+        assert!(width(&render_svg(&wider)) > width(&svg));
+
+        let escaped = render_svg(&Badge { label: "a<b & \"c\"".to_string(), ..badge });
+        assert!(escaped.contains("a&lt;b &amp; &quot;c&quot;") && !escaped.contains("a<b"), "{escaped}");
+    }
+
+    #[test]
+    fn test_history_keeps_the_latest_entry_per_metric_and_path() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let file = dir.path().join("nested/history.jsonl");
+        assert_eq!(latest_history(&file, BadgeMetric::Complexity, Path::new(".")).unwrap(), None);
+
+        let entry = |path: &str, metric, value| HistoryEntry { timestamp: 1, path: PathBuf::from(path), metric, value };
+        record_history(&file, &entry(".", BadgeMetric::Complexity, 4.0)).unwrap();
+        record_history(&file, &entry(".", BadgeMetric::Complexity, 5.0)).unwrap();
+        record_history(&file, &entry(".", BadgeMetric::Deadcode, 9.0)).unwrap();
+        record_history(&file, &entry("src", BadgeMetric::Complexity, 7.0)).unwrap();
+
+        let latest = latest_history(&file, BadgeMetric::Complexity, Path::new(".")).unwrap().unwrap();
+        assert_eq!(latest.value, 5.0);
+        assert_eq!(latest_history(&file, BadgeMetric::Maintainability, Path::new(".")).unwrap(), None);
+    }
+
+    #[test]
+    fn test_metric_needs_source_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let err = compute_metric(BadgeMetric::Deadcode, dir.path(), None, None).unwrap_err().to_string();
+        assert!(err.starts_with("Cannot compute dead code for") && err.ends_with("no source files to analyze"), "{err}");
+        let err = compute_metric(BadgeMetric::Complexity, &dir.path().join("missing"), None, None).unwrap_err().to_string();
+        assert!(err.ends_with("path not found"), "{err}");
+
+        fs::write(dir.path().join("lib.rs"), "fn unused() {}\n").unwrap();
+        assert!(compute_metric(BadgeMetric::Deadcode, dir.path(), None, None).unwrap() >= 1.0);
+    }
+}
@@ -0,0 +1,363 @@
+//! Bookmarks Module
+//!
+//! Persists search-result bookmarks per repository in `.codesearch/bookmarks.json`
+//! and re-validates them against the current file contents.
+
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::lines::split_lines;
+use crate::theme::{self, Glyph};
+
+/// Default location of the bookmark store, relative to the repository root
+pub const DEFAULT_BOOKMARKS_FILE: &str = ".codesearch/bookmarks.json";
+
+/// How many lines above and below the original line are rescanned for a moved bookmark
+pub const RESCAN_WINDOW: usize = 50;
+
+/// A bookmarked line
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub file: String,
+    pub line_number: usize,
+    /// FNV-1a hash of the trimmed line content, used to find the line after edits
+    pub content_hash: String,
+    pub label: String,
+    /// Seconds since the Unix epoch
+    pub created_at: u64,
+}
+
+/// Result of re-validating a bookmark against the current file
+#[derive(Debug, Clone, PartialEq)]
+pub enum BookmarkStatus {
+    /// The line is still at the bookmarked position
+    Valid,
+    /// The line was found nearby at a new line number
+    Moved(usize),
+    /// The line could not be found within the rescan window
+    Changed,
+    /// The file no longer exists or cannot be read
+    FileMissing,
+}
+
+/// Bookmark collection backed by a JSON file
+#[derive(Debug, Clone)]
+pub struct BookmarkStore {
+    path: PathBuf,
+    bookmarks: Vec<Bookmark>,
+}
+
+impl BookmarkStore {
+    /// Load the store from the default location in the current directory
+    pub fn load_default() -> Result<Self, Box<dyn std::error::Error>> {
+        Self::load(Path::new(DEFAULT_BOOKMARKS_FILE))
+    }
+
+    /// Load the store from `path`, starting empty if the file does not exist
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let bookmarks = if path.exists() {
+            serde_json::from_str(&fs::read_to_string(path)?)?
+        } else {
+            Vec::new()
+        };
+        Ok(Self {
+            path: path.to_path_buf(),
+            bookmarks,
+        })
+    }
+
+    /// Write the store back to disk
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.path, serde_json::to_string_pretty(&self.bookmarks)?)?;
+        Ok(())
+    }
+
+    /// All bookmarks in insertion order
+    pub fn bookmarks(&self) -> &[Bookmark] {
+        &self.bookmarks
+    }
+
+    /// Bookmark `file:line_number`, replacing any existing bookmark on that line
+    pub fn add(
+        &mut self,
+        file: &str,
+        line_number: usize,
+        label: &str,
+    ) -> Result<&Bookmark, Box<dyn std::error::Error>> {
+        let content = fs::read_to_string(file)?;
+        let line = split_lines(&content)
+            .nth(line_number.saturating_sub(1))
+            .filter(|_| line_number > 0)
+            .ok_or_else(|| format!("{file} has no line {line_number}"))?;
+
+        let file = normalize_path(file);
+        self.bookmarks
+            .retain(|b| !(b.file == file && b.line_number == line_number));
+        self.bookmarks.push(Bookmark {
+            file,
+            line_number,
+            content_hash: hash_line(line),
+            label: label.to_string(),
+            created_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+        });
+        Ok(self.bookmarks.last().unwrap())
+    }
+
+    /// Remove the bookmark on `file:line_number`, returning whether one existed
+    pub fn remove(&mut self, file: &str, line_number: usize) -> bool {
+        let file = normalize_path(file);
+        let before = self.bookmarks.len();
+        self.bookmarks
+            .retain(|b| !(b.file == file && b.line_number == line_number));
+        self.bookmarks.len() != before
+    }
+
+    /// Remove the bookmark at a 1-based position in the list
+    pub fn remove_at(&mut self, position: usize) -> Option<Bookmark> {
+        if position == 0 || position > self.bookmarks.len() {
+            return None;
+        }
+        Some(self.bookmarks.remove(position - 1))
+    }
+
+    /// Remove all bookmarks
+    pub fn clear(&mut self) {
+        self.bookmarks.clear();
+    }
+
+    /// Label of the bookmark on `file:line_number`, if any
+    pub fn label_for(&self, file: &str, line_number: usize) -> Option<&str> {
+        let file = normalize_path(file);
+        self.bookmarks
+            .iter()
+            .find(|b| b.file == file && b.line_number == line_number)
+            .map(|b| b.label.as_str())
+    }
+}
+
+/// Check a bookmark against the current contents of its file
+pub fn validate_bookmark(bookmark: &Bookmark) -> BookmarkStatus {
+    match fs::read_to_string(&bookmark.file) {
+        Ok(content) => {
+            let lines: Vec<&str> = split_lines(&content).collect();
+            validate_against_lines(bookmark, &lines)
+        }
+        Err(_) => BookmarkStatus::FileMissing,
+    }
+}
+
+/// Check a bookmark against already-loaded file lines
+pub fn validate_against_lines(bookmark: &Bookmark, lines: &[&str]) -> BookmarkStatus {
+    match find_nearby(lines, bookmark.line_number, &bookmark.content_hash, RESCAN_WINDOW) {
+        Some(line) if line == bookmark.line_number => BookmarkStatus::Valid,
+        Some(line) => BookmarkStatus::Moved(line),
+        None => BookmarkStatus::Changed,
+    }
+}
+
+/// Find the line closest to `line_number` whose hash matches, within `window` lines
+///
+/// Returns a 1-based line number. On equal distance the earlier line wins.
+pub fn find_nearby(lines: &[&str], line_number: usize, hash: &str, window: usize) -> Option<usize> {
+    let matches_at = |line: usize| {
+        line >= 1 && line <= lines.len() && hash_line(lines[line - 1]) == hash
+    };
+
+    for distance in 0..=window {
+        if distance < line_number && matches_at(line_number - distance) {
+            return Some(line_number - distance);
+        }
+        if distance > 0 && matches_at(line_number + distance) {
+            return Some(line_number + distance);
+        }
+    }
+    None
+}
+
+/// Stable hash of a line, ignoring surrounding whitespace
+pub fn hash_line(line: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in line.trim().bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{hash:016x}")
+}
+
+/// Parse a `file:line` location
+pub fn parse_location(location: &str) -> Result<(String, usize), Box<dyn std::error::Error>> {
+    let (file, line) = location
+        .rsplit_once(':')
+        .ok_or_else(|| format!("Expected file:line, got '{location}'"))?;
+    let line: usize = line
+        .parse()
+        .map_err(|_| format!("Invalid line number in '{location}'"))?;
+    Ok((file.to_string(), line))
+}
+
+fn normalize_path(file: &str) -> String {
+    file.strip_prefix("./").unwrap_or(file).replace('\\', "/")
+}
+
+/// Print all bookmarks with their validation status
+pub fn print_bookmarks(store: &BookmarkStore) {
+    if store.bookmarks().is_empty() {
+        println!("{}", "No bookmarks.".dimmed());
+        return;
+    }
+
+    println!("{}", theme::heading(Glyph::Bookmark, "Bookmarks").cyan().bold());
+    println!("{}", theme::rule(30).cyan());
+    for (i, bookmark) in store.bookmarks().iter().enumerate() {
+        let status = match validate_bookmark(bookmark) {
+            BookmarkStatus::Valid => "ok".green().to_string(),
+            BookmarkStatus::Moved(line) => format!("moved to line {line}").yellow().to_string(),
+            BookmarkStatus::Changed => "line changed".red().to_string(),
+            BookmarkStatus::FileMissing => "file missing".red().to_string(),
+        };
+        println!(
+            "  {:>3}. {}:{} [{}] ({})",
+            i + 1,
+            bookmark.file,
+            bookmark.line_number,
+            bookmark.label.yellow(),
+            status
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn write_source(dir: &Path) -> String {
+        let file = dir.join("lib.rs");
+        fs::write(&file, "fn one() {}\nfn two() {}\nfn three() {}\n").unwrap();
+        file.to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn test_add_save_and_reload() {
+        let dir = tempdir().unwrap();
+        let file = write_source(dir.path());
+        let store_path = dir.path().join(".codesearch/bookmarks.json");
+
+        let mut store = BookmarkStore::load(&store_path).unwrap();
+        assert!(store.bookmarks().is_empty());
+        store.add(&file, 2, "needs fix").unwrap();
+        store.save().unwrap();
+
+        let reloaded = BookmarkStore::load(&store_path).unwrap();
+        assert_eq!(reloaded.bookmarks().len(), 1);
+        assert_eq!(reloaded.bookmarks()[0].content_hash, hash_line("fn two() {}"));
+        assert_eq!(reloaded.label_for(&file, 2), Some("needs fix"));
+    }
+
+    #[test]
+    fn test_add_replaces_same_line_and_rejects_missing_line() {
+        let dir = tempdir().unwrap();
+        let file = write_source(dir.path());
+        let mut store = BookmarkStore::load(&dir.path().join("b.json")).unwrap();
+
+        store.add(&file, 1, "reviewed").unwrap();
+        store.add(&file, 1, "needs fix").unwrap();
+        assert_eq!(store.bookmarks().len(), 1);
+        assert_eq!(store.label_for(&file, 1), Some("needs fix"));
+
+        assert!(store.add(&file, 10, "x").is_err());
+        assert!(store.add(&file, 0, "x").is_err());
+    }
+
+    #[test]
+    fn test_remove_and_clear() {
+        let dir = tempdir().unwrap();
+        let file = write_source(dir.path());
+        let mut store = BookmarkStore::load(&dir.path().join("b.json")).unwrap();
+
+        store.add(&file, 1, "a").unwrap();
+        store.add(&file, 2, "b").unwrap();
+        store.add(&file, 3, "c").unwrap();
+
+        assert!(store.remove(&file, 2));
+        assert!(!store.remove(&file, 2));
+        assert_eq!(store.remove_at(1).map(|b| b.label), Some("a".to_string()));
+        assert!(store.remove_at(5).is_none());
+
+        store.clear();
+        assert!(store.bookmarks().is_empty());
+    }
+
+    #[test]
+    fn test_validate_detects_moved_changed_and_missing() {
+        let dir = tempdir().unwrap();
+        let file = write_source(dir.path());
+        let mut store = BookmarkStore::load(&dir.path().join("b.json")).unwrap();
+        store.add(&file, 2, "check").unwrap();
+        let bookmark = store.bookmarks()[0].clone();
+
+        assert_eq!(validate_bookmark(&bookmark), BookmarkStatus::Valid);
+
+        fs::write(&file, "// header\n// more\nfn one() {}\n    fn two() {}\nfn three() {}\n").unwrap();
+        assert_eq!(validate_bookmark(&bookmark), BookmarkStatus::Moved(4));
+
+        fs::write(&file, "fn one() {}\nfn renamed() {}\n").unwrap();
+        assert_eq!(validate_bookmark(&bookmark), BookmarkStatus::Changed);
+
+        fs::remove_file(&file).unwrap();
+        assert_eq!(validate_bookmark(&bookmark), BookmarkStatus::FileMissing);
+    }
+
+    #[test]
+    fn test_find_nearby_prefers_closest_match() {
+        let hash = hash_line("target");
+        let lines = ["target", "a", "b", "c", "target"];
+
+        assert_eq!(find_nearby(&lines, 4, &hash, 10), Some(5));
+        assert_eq!(find_nearby(&lines, 2, &hash, 10), Some(1));
+        // Equidistant: earlier line wins
+        assert_eq!(find_nearby(&lines, 3, &hash, 10), Some(1));
+    }
+
+    #[test]
+    fn test_find_nearby_respects_window() {
+        let hash = hash_line("target");
+        let mut lines = vec!["x"; 100];
+        lines[89] = "target";
+
+        assert_eq!(find_nearby(&lines, 10, &hash, 50), None);
+        assert_eq!(find_nearby(&lines, 60, &hash, 50), Some(90));
+    }
+
+    #[test]
+    fn test_parse_location() {
+        assert_eq!(parse_location("src/main.rs:42").unwrap(), ("src/main.rs".to_string(), 42));
+        assert_eq!(parse_location("C:/code/a.rs:7").unwrap(), ("C:/code/a.rs".to_string(), 7));
+        assert!(parse_location("src/main.rs").is_err());
+        assert!(parse_location("src/main.rs:abc").is_err());
+    }
+
+    #[test]
+    fn test_label_for_normalizes_dot_slash() {
+        let dir = tempdir().unwrap();
+        let store_path = dir.path().join("b.json");
+        let mut store = BookmarkStore::load(&store_path).unwrap();
+        store.bookmarks.push(Bookmark {
+            file: "src/lib.rs".to_string(),
+            line_number: 3,
+            content_hash: hash_line("x"),
+            label: "reviewed".to_string(),
+            created_at: 0,
+        });
+        assert_eq!(store.label_for("./src/lib.rs", 3), Some("reviewed"));
+        assert_eq!(store.label_for("src/lib.rs", 4), None);
+    }
+}
@@ -1,92 +1,419 @@
 //! Cache Module
 //!
 //! Provides intelligent caching for faster repeated searches.
+//!
+//! Result lines are stored once in a content table keyed by (file, line, content
+//! hash); each cached query only holds ids into that table plus its own match
+//! positions and scores, so overlapping queries share storage.
+//!
+//! The process-wide cache is persisted in `.codesearch/cache/`, so `--cache`
+//! carries over between runs. Each query also records a fingerprint of the
+//! modification times and sizes of the files it searched; a query whose files
+//! changed since is dropped instead of returned.
+
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock, RwLock};
+use std::time::UNIX_EPOCH;
+
+use crate::types::{Match, SearchOptions, SearchResult};
 
-use dashmap::DashMap;
-use std::collections::HashMap;
-use std::sync::OnceLock;
-use std::time::SystemTime;
+/// Version of the serialized cache format, written as the first byte
+///
+/// Bump whenever the layout of [`CacheStore`] or what a search stores in it
+/// changes; data written with any other version is discarded on load.
+pub const CACHE_FORMAT_VERSION: u8 = 6;
 
-use crate::types::SearchResult;
+/// Version stamp of cached results: the format version plus the crate version
+///
+/// Results cached by another release may have been ranked or matched
+/// differently, so a cache written under another stamp is dropped on load.
+pub fn cache_version() -> String {
+    format!("{CACHE_FORMAT_VERSION}:{}", env!("CARGO_PKG_VERSION"))
+}
+
+/// Number of cached queries kept before the oldest is evicted
+pub const DEFAULT_MAX_ENTRIES: usize = 256;
+
+/// Default location of the persisted search cache, relative to the current directory
+pub const DEFAULT_CACHE_FILE: &str = ".codesearch/cache/search.cache";
 
 /// Global search cache instance
 static SEARCH_CACHE: OnceLock<SearchCache> = OnceLock::new();
 
-/// Get the global search cache
+/// Get the global search cache, loaded from [`DEFAULT_CACHE_FILE`] on first use
 pub fn get_search_cache() -> &'static SearchCache {
-    SEARCH_CACHE.get_or_init(SearchCache::new)
+    SEARCH_CACHE.get_or_init(|| SearchCache::load(Path::new(DEFAULT_CACHE_FILE)))
 }
 
-/// Cache for search results and file modification times
+/// Cache for search results, optionally backed by a file
 pub struct SearchCache {
-    results: DashMap<String, Vec<SearchResult>>,
-    file_mtimes: DashMap<String, SystemTime>,
+    store: RwLock<CacheStore>,
+    max_entries: usize,
+    /// Stamp of this process's results (see [`cache_version`])
+    version: String,
+    /// Queries dropped on load because they were cached under another version
+    invalidated: AtomicUsize,
+    /// Queries dropped because the files they searched changed
+    stale: AtomicUsize,
+    /// File [`save`](Self::save) writes to, for caches from [`load`](Self::load)
+    path: Option<PathBuf>,
+    /// Held while saving, so concurrent saves can't leave an older snapshot on disk
+    saving: Mutex<()>,
+}
+
+/// A unique result line, shared by every query that returned it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedLine {
+    file: String,
+    line_number: usize,
+    content: String,
+}
+
+/// The per-query part of a result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedResult {
+    line: u64,
+    matches: Vec<Match>,
+    score: f64,
+    relevance: String,
+    /// Window of a truncated long line; it depends on where the query matched
+    #[serde(default)]
+    content_offset: Option<usize>,
+}
+
+/// Content table plus the cached queries referencing it
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheStore {
+    /// [`cache_version`] stamp of the process that wrote the entries
+    #[serde(default)]
+    version: String,
+    lines: HashMap<u64, CachedLine>,
+    entries: HashMap<String, Vec<CachedResult>>,
+    /// [`fingerprint_files`] of the files each query searched, when recorded
+    #[serde(default)]
+    fingerprints: HashMap<String, u64>,
+    /// Keys in insertion order, oldest first
+    order: VecDeque<String>,
+    next_line_id: u64,
+    /// (file, line number, content hash) -> line id; rebuilt on load
+    #[serde(skip)]
+    line_index: HashMap<(String, usize, u64), u64>,
+}
+
+impl CacheStore {
+    fn intern(&mut self, result: &SearchResult) -> u64 {
+        let key = (result.file.clone(), result.line_number, content_hash(&result.content));
+        if let Some(&id) = self.line_index.get(&key) {
+            return id;
+        }
+        let id = self.next_line_id;
+        self.next_line_id += 1;
+        self.lines.insert(
+            id,
+            CachedLine {
+                file: result.file.clone(),
+                line_number: result.line_number,
+                content: result.content.clone(),
+            },
+        );
+        self.line_index.insert(key, id);
+        id
+    }
+
+    fn resolve(&self, cached: &CachedResult) -> Option<SearchResult> {
+        let line = self.lines.get(&cached.line)?;
+        Some(SearchResult {
+            file: line.file.clone(),
+            line_number: line.line_number,
+            end_line_number: None,
+            content: line.content.clone(),
+            matches: cached.matches.clone(),
+            score: cached.score,
+            relevance: cached.relevance.clone(),
+            context_before: Vec::new(),
+            context_after: Vec::new(),
+            line_truncated: cached.content_offset.is_some(),
+            content_offset: cached.content_offset,
+            annotations: Default::default(),
+        })
+    }
+
+    fn remove_entry(&mut self, key: &str) -> bool {
+        self.order.retain(|k| k != key);
+        self.fingerprints.remove(key);
+        self.entries.remove(key).is_some()
+    }
+
+    /// Drop content lines no cached query references any more
+    fn compact(&mut self) -> usize {
+        let referenced: HashSet<u64> = self.entries.values().flatten().map(|r| r.line).collect();
+        let before = self.lines.len();
+        self.lines.retain(|id, _| referenced.contains(id));
+        self.line_index.retain(|_, id| referenced.contains(id));
+        before - self.lines.len()
+    }
+
+    fn rebuild_index(&mut self) {
+        self.line_index = self
+            .lines
+            .iter()
+            .map(|(&id, line)| ((line.file.clone(), line.line_number, content_hash(&line.content)), id))
+            .collect();
+    }
+}
+
+fn content_hash(content: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Fingerprint of the modification times and sizes of `files`, in any order
+///
+/// Adding, removing, touching or resizing a file changes it. Files that can't
+/// be read still count, by path, so they keep the fingerprint stable.
+pub fn fingerprint_files(files: impl IntoIterator<Item = PathBuf>) -> u64 {
+    let mut stamps: Vec<(PathBuf, u128, u64)> = files
+        .into_iter()
+        .map(|file| {
+            let metadata = std::fs::metadata(&file).ok();
+            let modified = metadata
+                .as_ref()
+                .and_then(|m| m.modified().ok())
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map_or(0, |d| d.as_nanos());
+            (file, modified, metadata.map_or(0, |m| m.len()))
+        })
+        .collect();
+    stamps.sort_unstable();
+    let mut hasher = DefaultHasher::new();
+    stamps.hash(&mut hasher);
+    hasher.finish()
 }
 
 impl SearchCache {
     /// Create a new search cache
     pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_MAX_ENTRIES)
+    }
+
+    /// Create a cache that keeps at most `max_entries` queries
+    pub fn with_capacity(max_entries: usize) -> Self {
+        Self::with_version(max_entries, cache_version())
+    }
+
+    /// Create a cache stamping its results with `version` instead of [`cache_version`]
+    pub fn with_version(max_entries: usize, version: impl Into<String>) -> Self {
+        let version = version.into();
         SearchCache {
-            results: DashMap::new(),
-            file_mtimes: DashMap::new(),
+            store: RwLock::new(CacheStore {
+                version: version.clone(),
+                ..CacheStore::default()
+            }),
+            max_entries: max_entries.max(1),
+            version,
+            invalidated: AtomicUsize::new(0),
+            stale: AtomicUsize::new(0),
+            path: None,
+            saving: Mutex::new(()),
         }
     }
 
+    /// Load the cache persisted at `path`, starting empty if there is none
+    ///
+    /// [`save`](Self::save) writes back to `path`. Unreadable or outdated data
+    /// is discarded as by [`from_bytes`](Self::from_bytes).
+    pub fn load(path: &Path) -> Self {
+        let mut cache = match std::fs::read(path) {
+            Ok(bytes) => Self::from_bytes(&bytes),
+            Err(_) => Self::new(),
+        };
+        cache.path = Some(path.to_path_buf());
+        cache
+    }
+
+    /// Write the cache to the file it was loaded from; does nothing for in-memory caches
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let _saving = self.saving.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        // Write then rename, so a concurrent run never reads half a cache
+        let temp = path.with_extension("tmp");
+        std::fs::write(&temp, self.to_bytes()?)?;
+        std::fs::rename(&temp, path)?;
+        Ok(())
+    }
+
+    /// The file [`save`](Self::save) writes to, if any
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
     /// Generate a cache key from search parameters
-    pub fn get_cache_key(
-        &self,
-        query: &str,
-        path: &str,
-        extensions: Option<&[String]>,
-        fuzzy: bool,
-    ) -> String {
-        let ext_str = extensions
-            .map(|e| e.join(","))
-            .unwrap_or_else(|| "all".to_string());
-        format!("{}:{}:{}:{}", query, path, ext_str, fuzzy)
+    ///
+    /// The key covers the cache version and every field of `options`, so a
+    /// change to any option, or to how a release interprets it, misses.
+    pub fn get_cache_key(&self, query: &str, path: &str, options: &SearchOptions) -> String {
+        // SearchOptions holds floats, so hash its Debug form; the version in the
+        // key keeps that stable for as long as entries can be reused. An index
+        // only saves reads, benchmarking only reports timings, and post-processors
+        // run after the cache, so none of them changes the cached results.
+        let options = SearchOptions { index: None, benchmark: false, post_processors: Vec::new(), ..options.clone() };
+        let options_hash = content_hash(&format!("{options:?}"));
+        format!("{}:{}:{}:{:016x}", self.version, query, path, options_hash)
     }
 
     /// Get cached results if available
     pub fn get(&self, key: &str) -> Option<Vec<SearchResult>> {
-        self.results.get(key).map(|v| v.clone())
+        let store = self.store.read().unwrap();
+        let cached = store.entries.get(key)?;
+        cached.iter().map(|r| store.resolve(r)).collect()
+    }
+
+    /// Get cached results if the files they came from still have `fingerprint`
+    ///
+    /// An entry recorded with another fingerprint is removed, and counted in
+    /// [`stats`](Self::stats); one stored without a fingerprint is returned as is.
+    pub fn get_fresh(&self, key: &str, fingerprint: u64) -> Option<Vec<SearchResult>> {
+        let recorded = self.store.read().unwrap().fingerprints.get(key).copied();
+        if recorded.is_some_and(|recorded| recorded != fingerprint) {
+            if self.remove(key) {
+                self.stale.fetch_add(1, Ordering::Relaxed);
+            }
+            return None;
+        }
+        self.get(key)
     }
 
-    /// Store results in cache
+    /// Store results in cache, evicting the oldest query when full
     pub fn set(&self, key: String, results: Vec<SearchResult>) {
-        self.results.insert(key, results);
+        self.insert(key, results, None);
     }
 
-    /// Check if a file has been modified since last cache
-    pub fn is_file_modified(&self, file_path: &str) -> bool {
-        if let Ok(metadata) = std::fs::metadata(file_path) {
-            if let Ok(mtime) = metadata.modified() {
-                if let Some(cached_mtime) = self.file_mtimes.get(file_path) {
-                    if *cached_mtime == mtime {
-                        return false; // Not modified
-                    }
+    /// Store results with the [`fingerprint_files`] of the files searched for them
+    pub fn set_fresh(&self, key: String, results: Vec<SearchResult>, fingerprint: u64) {
+        self.insert(key, results, Some(fingerprint));
+    }
+
+    fn insert(&self, key: String, results: Vec<SearchResult>, fingerprint: Option<u64>) {
+        let mut store = self.store.write().unwrap();
+        let replaced = store.remove_entry(&key);
+
+        let cached: Vec<CachedResult> = results
+            .into_iter()
+            .map(|result| CachedResult {
+                line: store.intern(&result),
+                matches: result.matches,
+                score: result.score,
+                relevance: result.relevance,
+                content_offset: result.content_offset,
+            })
+            .collect();
+        store.entries.insert(key.clone(), cached);
+        if let Some(fingerprint) = fingerprint {
+            store.fingerprints.insert(key.clone(), fingerprint);
+        }
+        store.order.push_back(key);
+
+        let mut evicted = false;
+        while store.entries.len() > self.max_entries {
+            match store.order.pop_front() {
+                Some(oldest) => {
+                    store.fingerprints.remove(&oldest);
+                    evicted |= store.entries.remove(&oldest).is_some();
                 }
-                // Update cache with new mtime
-                self.file_mtimes.insert(file_path.to_string(), mtime);
+                None => break,
             }
         }
-        true // Modified or couldn't check
+        if replaced || evicted {
+            store.compact();
+        }
+    }
+
+    /// Remove one cached query and any content lines only it referenced
+    pub fn remove(&self, key: &str) -> bool {
+        let mut store = self.store.write().unwrap();
+        let removed = store.remove_entry(key);
+        if removed {
+            store.compact();
+        }
+        removed
+    }
+
+    /// Drop unreferenced content lines; returns how many were removed
+    pub fn compact(&self) -> usize {
+        self.store.write().unwrap().compact()
     }
 
     /// Clear the entire cache
-    #[allow(dead_code)]
     pub fn clear(&self) {
-        self.results.clear();
-        self.file_mtimes.clear();
+        *self.store.write().unwrap() = CacheStore {
+            version: self.version.clone(),
+            ..CacheStore::default()
+        };
     }
 
     /// Get cache statistics
-    #[allow(dead_code)]
     pub fn stats(&self) -> HashMap<String, usize> {
+        let store = self.store.read().unwrap();
         let mut stats = HashMap::new();
-        stats.insert("result_entries".to_string(), self.results.len());
-        stats.insert("file_entries".to_string(), self.file_mtimes.len());
+        stats.insert("result_entries".to_string(), store.entries.len());
+        stats.insert("content_entries".to_string(), store.lines.len());
+        stats.insert("fingerprinted_entries".to_string(), store.fingerprints.len());
+        stats.insert("invalidated_by_version".to_string(), self.invalidated.load(Ordering::Relaxed));
+        stats.insert("invalidated_by_change".to_string(), self.stale.load(Ordering::Relaxed));
         stats
     }
+
+    /// Serialize the cached queries, prefixed with [`CACHE_FORMAT_VERSION`]
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let store = self.store.read().unwrap();
+        let mut bytes = vec![CACHE_FORMAT_VERSION];
+        bytes.extend(serde_json::to_vec(&*store)?);
+        Ok(bytes)
+    }
+
+    /// Restore a cache written by [`to_bytes`](Self::to_bytes)
+    ///
+    /// Data from another format or crate version, or that fails to parse, yields
+    /// an empty cache rather than an error, so an upgrade invalidates old caches
+    /// wholesale; the dropped queries are counted in [`stats`](Self::stats).
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        Self::from_bytes_with_version(bytes, &cache_version())
+    }
+
+    /// Like [`from_bytes`](Self::from_bytes), for a process stamping results with `version`
+    pub fn from_bytes_with_version(bytes: &[u8], version: &str) -> Self {
+        let cache = Self::with_version(DEFAULT_MAX_ENTRIES, version);
+        let Some((&format, body)) = bytes.split_first() else {
+            return cache;
+        };
+        match serde_json::from_slice::<CacheStore>(body) {
+            Ok(mut store) if format == CACHE_FORMAT_VERSION && store.version == version => {
+                store.rebuild_index();
+                *cache.store.write().unwrap() = store;
+            }
+            Ok(store) => cache.invalidated.store(store.entries.len(), Ordering::Relaxed),
+            // An older layout may not parse as a store, but its queries can still be counted
+            Err(_) => {
+                let stale = serde_json::from_slice::<serde_json::Value>(body)
+                    .ok()
+                    .and_then(|value| value.get("entries").and_then(|e| e.as_object()).map(|e| e.len()))
+                    .unwrap_or(0);
+                cache.invalidated.store(stale, Ordering::Relaxed);
+            }
+        }
+        cache
+    }
 }
 
 impl Default for SearchCache {
@@ -99,12 +426,68 @@ impl Default for SearchCache {
 mod tests {
     use super::*;
 
+    fn result(file: &str, line_number: usize, content: &str, matched: &str) -> SearchResult {
+        let start = content.find(matched).unwrap();
+        SearchResult {
+            file: file.to_string(),
+            line_number,
+            end_line_number: None,
+            content: content.to_string(),
+            matches: vec![Match {
+                start,
+                end: start + matched.len(),
+                text: matched.to_string(),
+                pattern_index: 0,
+            }],
+            score: 50.0,
+            relevance: "Medium".to_string(),
+            context_before: Vec::new(),
+            context_after: Vec::new(),
+            line_truncated: false,
+            content_offset: None,
+            annotations: Default::default(),
+        }
+    }
+
+    fn lines() -> Vec<(&'static str, usize, &'static str)> {
+        (0..50)
+            .map(|i| ("src/lib.rs", i + 1, if i % 2 == 0 { "fn parse_config() {}" } else { "let config = parse();" }))
+            .collect()
+    }
+
     #[test]
     fn test_cache_key_generation() {
         let cache = SearchCache::new();
-        let key1 = cache.get_cache_key("test", "/path", Some(&["rs".to_string()]), false);
-        let key2 = cache.get_cache_key("test", "/path", Some(&["rs".to_string()]), true);
+        let options = SearchOptions::default().with_extensions(vec!["rs".to_string()]);
+        let key1 = cache.get_cache_key("test", "/path", &options);
+        let key2 = cache.get_cache_key("test", "/path", &options.clone().with_fuzzy(true));
         assert_ne!(key1, key2);
+        assert_eq!(key1, cache.get_cache_key("test", "/path", &options));
+
+        // Options that only change ranking or matching semantics also miss
+        assert_ne!(key1, cache.get_cache_key("test", "/path", &options.clone().with_rank(true)));
+        assert_ne!(key1, cache.get_cache_key("test", "/path", &options.clone().with_word_boundary(true)));
+        assert_ne!(key1, SearchCache::with_version(8, "0:0.0.0").get_cache_key("test", "/path", &options));
+    }
+
+    #[test]
+    fn test_version_bump_invalidates_stored_entries() {
+        let options = SearchOptions::default();
+        let old = SearchCache::with_version(DEFAULT_MAX_ENTRIES, "3:0.1.0");
+        let old_key = old.get_cache_key("parse", "src", &options);
+        old.set(old_key.clone(), vec![result("a.rs", 3, "parse(x)", "parse")]);
+        old.set("other".to_string(), vec![]);
+        let bytes = old.to_bytes().unwrap();
+
+        let same = SearchCache::from_bytes_with_version(&bytes, "3:0.1.0");
+        assert_eq!(same.get(&old_key).unwrap().len(), 1);
+        assert_eq!(same.stats()["invalidated_by_version"], 0);
+
+        let upgraded = SearchCache::from_bytes_with_version(&bytes, "3:0.2.0");
+        assert!(upgraded.get(&old_key).is_none());
+        assert!(upgraded.get(&upgraded.get_cache_key("parse", "src", &options)).is_none());
+        assert_eq!(upgraded.stats()["result_entries"], 0);
+        assert_eq!(upgraded.stats()["invalidated_by_version"], 2);
     }
 
     #[test]
@@ -123,5 +506,117 @@ mod tests {
         cache.clear();
         assert!(cache.get("key1").is_none());
     }
-}
 
+    #[test]
+    fn test_overlapping_queries_share_content() {
+        let cache = SearchCache::new();
+        let by_config: Vec<_> = lines().into_iter().map(|(f, l, c)| result(f, l, c, "config")).collect();
+        let by_parse: Vec<_> = lines().into_iter().map(|(f, l, c)| result(f, l, c, "parse")).collect();
+
+        cache.set("config".to_string(), by_config);
+        let after_one = cache.stats()["content_entries"];
+        cache.set("parse".to_string(), by_parse);
+        let after_two = cache.stats()["content_entries"];
+
+        assert_eq!(after_one, 50);
+        assert_eq!(after_two, after_one);
+        assert_eq!(cache.stats()["result_entries"], 2);
+    }
+
+    #[test]
+    fn test_retrieval_reconstructs_identical_results() {
+        let cache = SearchCache::new();
+        let original: Vec<_> = lines().into_iter().map(|(f, l, c)| result(f, l, c, "parse")).collect();
+        cache.set("parse".to_string(), original.clone());
+
+        let restored = cache.get("parse").unwrap();
+        assert_eq!(serde_json::to_value(&restored).unwrap(), serde_json::to_value(&original).unwrap());
+    }
+
+    #[test]
+    fn test_remove_and_eviction_compact_content() {
+        let cache = SearchCache::with_capacity(1);
+        cache.set("a".to_string(), vec![result("a.rs", 1, "fn a() {}", "a")]);
+        cache.set("b".to_string(), vec![result("b.rs", 1, "fn b() {}", "b")]);
+
+        assert!(cache.get("a").is_none());
+        assert_eq!(cache.stats()["content_entries"], 1);
+
+        assert!(cache.remove("b"));
+        assert_eq!(cache.stats()["content_entries"], 0);
+    }
+
+    #[test]
+    fn test_changed_line_content_is_stored_separately() {
+        let cache = SearchCache::new();
+        cache.set("old".to_string(), vec![result("a.rs", 1, "fn old() {}", "old")]);
+        cache.set("new".to_string(), vec![result("a.rs", 1, "fn new() {}", "new")]);
+
+        assert_eq!(cache.get("old").unwrap()[0].content, "fn old() {}");
+        assert_eq!(cache.get("new").unwrap()[0].content, "fn new() {}");
+    }
+
+    #[test]
+    fn test_changed_files_invalidate_fingerprinted_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("a.rs");
+        std::fs::write(&file, "parse(x)\n").unwrap();
+        let before = fingerprint_files([file.clone()]);
+
+        let cache = SearchCache::new();
+        cache.set_fresh("parse".to_string(), vec![result("a.rs", 1, "parse(x)", "parse")], before);
+        assert_eq!(cache.get_fresh("parse", before).unwrap().len(), 1);
+
+        std::fs::write(&file, "let parsed = parse(y);\n").unwrap();
+        let after = fingerprint_files([file.clone()]);
+        assert_ne!(before, after);
+        assert!(cache.get_fresh("parse", after).is_none());
+        assert!(cache.get("parse").is_none(), "the stale entry is removed");
+        assert_eq!(cache.stats()["invalidated_by_change"], 1);
+
+        // A new file changes the fingerprint too
+        let other = dir.path().join("b.rs");
+        std::fs::write(&other, "").unwrap();
+        assert_ne!(fingerprint_files([file.clone(), other.clone()]), after);
+        assert_eq!(fingerprint_files([other, file]), fingerprint_files([dir.path().join("a.rs"), dir.path().join("b.rs")]));
+    }
+
+    #[test]
+    fn test_load_and_save_persist_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".codesearch/cache/search.cache");
+
+        let first = SearchCache::load(&path);
+        assert_eq!(first.stats()["result_entries"], 0);
+        first.set_fresh("parse".to_string(), vec![result("a.rs", 3, "parse(x)", "parse")], 7);
+        first.save().unwrap();
+
+        let second = SearchCache::load(&path);
+        assert_eq!(second.path(), Some(path.as_path()));
+        assert_eq!(second.get_fresh("parse", 7).unwrap()[0].line_number, 3);
+        assert_eq!(second.stats()["fingerprinted_entries"], 1);
+
+        // In-memory caches have nowhere to save to
+        assert!(SearchCache::new().save().is_ok());
+        assert!(SearchCache::new().path().is_none());
+    }
+
+    #[test]
+    fn test_bytes_round_trip_and_version_invalidation() {
+        let cache = SearchCache::new();
+        cache.set("parse".to_string(), vec![result("a.rs", 3, "parse(x)", "parse")]);
+        let bytes = cache.to_bytes().unwrap();
+        assert_eq!(bytes[0], CACHE_FORMAT_VERSION);
+
+        let restored = SearchCache::from_bytes(&bytes);
+        assert_eq!(restored.get("parse").unwrap()[0].line_number, 3);
+        // The rebuilt index still deduplicates new results against restored lines
+        restored.set("again".to_string(), vec![result("a.rs", 3, "parse(x)", "x")]);
+        assert_eq!(restored.stats()["content_entries"], 1);
+
+        let mut stale = bytes.clone();
+        stale[0] = CACHE_FORMAT_VERSION - 1;
+        assert!(SearchCache::from_bytes(&stale).get("parse").is_none());
+        assert!(SearchCache::from_bytes(&[]).get("parse").is_none());
+    }
+}
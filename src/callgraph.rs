@@ -2,10 +2,14 @@
 //!
 //! Analyzes function call relationships in code.
 
+use crate::callgraph_metrics::{adjacency, fan_in};
+use crate::lines::split_lines;
+use crate::parser::ExtractionContext;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::path::Path;
-use walkdir::WalkDir;
+use std::sync::OnceLock;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CallGraph {
@@ -172,23 +176,32 @@ impl CallGraph {
         visited.remove(current);
     }
 
+    /// Render the graph in DOT format
+    ///
+    /// Nodes are shaded by fan-in (darker = more callers); recursive functions
+    /// get a double border.
     pub fn to_dot(&self) -> String {
+        const FAN_IN_PALETTE: [&str; 5] = ["#feedde", "#fdbe85", "#fd8d3c", "#e6550d", "#a63603"];
+
+        let fan_in = fan_in(&adjacency(self));
+        let max_fan_in = fan_in.values().copied().max().unwrap_or(0);
+
         let mut dot = String::from("digraph CallGraph {\n");
         dot.push_str("  rankdir=LR;\n");
         dot.push_str("  node [shape=box];\n\n");
 
-        for (func_name, node) in &self.nodes {
-            let color = if node.is_recursive {
-                "lightcoral"
-            } else if self.get_callers(func_name).is_empty() {
-                "lightgreen"
-            } else {
-                "lightblue"
-            };
+        let mut names: Vec<&String> = self.nodes.keys().collect();
+        names.sort();
+        for func_name in names {
+            let node = &self.nodes[func_name];
+            let callers = fan_in.get(func_name).copied().unwrap_or(0);
+            let shade = (callers * (FAN_IN_PALETTE.len() - 1)).checked_div(max_fan_in).unwrap_or(0);
+            let font = if shade >= 3 { ", fontcolor=white" } else { "" };
+            let border = if node.is_recursive { ", peripheries=2" } else { "" };
 
             dot.push_str(&format!(
-                "  \"{}\" [label=\"{}\\n({}:{})\", fillcolor={}, style=filled];\n",
-                func_name, func_name, node.file_path, node.line, color
+                "  \"{}\" [label=\"{}\\n({}:{})\\nfan-in: {}\", fillcolor=\"{}\", style=filled{}{}];\n",
+                func_name, func_name, node.file_path, node.line, callers, FAN_IN_PALETTE[shade], font, border
             ));
         }
 
@@ -209,91 +222,54 @@ pub fn build_call_graph(
     extensions: Option<&[String]>,
     exclude: Option<&[String]>,
 ) -> Result<CallGraph, Box<dyn std::error::Error>> {
+    let context = ExtractionContext::build(path, extensions, exclude)?;
+    Ok(build_call_graph_in(&context))
+}
+
+/// Build the call graph from an already built extraction context
+///
+/// Function definitions come from the context; call sites are attributed to
+/// the most recent definition above them in the same file.
+pub fn build_call_graph_in(context: &ExtractionContext) -> CallGraph {
+    static CALL_RE: OnceLock<Regex> = OnceLock::new();
+    let func_call_pattern = CALL_RE.get_or_init(|| Regex::new(r"(\w+)\s*\(").unwrap());
+
     let mut graph = CallGraph::new();
-    let mut function_definitions: HashMap<String, (String, usize)> = HashMap::new();
-
-    let walker = WalkDir::new(path)
-        .into_iter()
-        .filter_entry(|e| {
-            if let Some(name) = e.file_name().to_str() {
-                if let Some(exclude_dirs) = exclude {
-                    for exclude_dir in exclude_dirs {
-                        if name == exclude_dir {
-                            return false;
-                        }
-                    }
-                }
-            }
-            true
-        })
-        .filter_map(|e| e.ok())
-        .filter(|e| e.file_type().is_file());
-
-    let files: Vec<_> = walker
-        .filter(|entry| {
-            let file_path = entry.path();
-            if let Some(exts) = extensions {
-                if let Some(ext) = file_path.extension().and_then(|s| s.to_str()) {
-                    exts.iter().any(|e| e == ext)
-                } else {
-                    false
-                }
-            } else {
-                true
-            }
-        })
-        .collect();
-
-    let func_def_pattern = regex::Regex::new(r"(?:fn|def|function)\s+(\w+)")?;
-    let func_call_pattern = regex::Regex::new(r"(\w+)\s*\(")?;
-
-    for entry in &files {
-        let file_path = entry.path();
-        let content = std::fs::read_to_string(file_path)?;
-
-        for (line_num, line) in content.lines().enumerate() {
-            if let Some(caps) = func_def_pattern.captures(line) {
-                if let Some(func_name) = caps.get(1) {
-                    let func_name_str = func_name.as_str().to_string();
-                    function_definitions.insert(
-                        func_name_str.clone(),
-                        (file_path.to_string_lossy().to_string(), line_num + 1),
-                    );
-
-                    let node = CallNode {
-                        function_name: func_name_str,
-                        file_path: file_path.to_string_lossy().to_string(),
-                        line: line_num + 1,
-                        is_recursive: false,
-                        call_count: 0,
-                    };
-                    graph.add_node(node);
-                }
-            }
+
+    for file in context.files() {
+        for (func_name, line) in &file.functions {
+            graph.add_node(CallNode {
+                function_name: func_name.clone(),
+                file_path: file.path.clone(),
+                line: *line,
+                is_recursive: false,
+                call_count: 0,
+            });
         }
     }
 
-    for entry in &files {
-        let file_path = entry.path();
-        let content = std::fs::read_to_string(file_path)?;
-        let mut current_function = None;
+    for file in context.files() {
+        let definitions: HashMap<usize, &str> = file
+            .functions
+            .iter()
+            .map(|(name, line)| (*line, name.as_str()))
+            .collect();
+        let mut current_function: Option<&str> = None;
 
-        for (line_num, line) in content.lines().enumerate() {
-            if let Some(caps) = func_def_pattern.captures(line) {
-                if let Some(func_name) = caps.get(1) {
-                    current_function = Some(func_name.as_str().to_string());
-                }
+        for (line_num, line) in split_lines(&file.content).enumerate() {
+            if let Some(&name) = definitions.get(&(line_num + 1)) {
+                current_function = Some(name);
             }
 
-            if let Some(caller) = &current_function {
+            if let Some(caller) = current_function {
                 for cap in func_call_pattern.captures_iter(line) {
                     if let Some(callee_match) = cap.get(1) {
-                        let callee = callee_match.as_str().to_string();
+                        let callee = callee_match.as_str();
                         
-                        if function_definitions.contains_key(&callee) && callee != *caller {
+                        if graph.nodes.contains_key(callee) && callee != caller {
                             graph.add_edge(
-                                caller.clone(),
-                                callee,
+                                caller.to_string(),
+                                callee.to_string(),
                                 line_num + 1,
                                 true,
                             );
@@ -312,7 +288,7 @@ pub fn build_call_graph(
         }
     }
 
-    Ok(graph)
+    graph
 }
 
 #[cfg(test)]
@@ -390,4 +366,40 @@ mod tests {
         let dead = graph.find_dead_functions();
         assert!(dead.contains(&"unused".to_string()));
     }
+
+    #[test]
+    fn test_build_from_context() {
+        let context = ExtractionContext::from_sources([
+            ("a.rs", "fn main() {\n    run();\n}\n"),
+            ("b.rs", "fn run() {\n    helper(1);\n}\nfn helper(n: u32) {}\n"),
+        ]);
+        let graph = build_call_graph_in(&context);
+        assert_eq!(graph.nodes.len(), 3);
+        assert_eq!(graph.nodes["helper"].file_path, "b.rs");
+        assert_eq!(graph.get_callees("main"), vec!["run"]);
+        assert_eq!(graph.get_callees("run"), vec!["helper"]);
+        assert!(graph.get_callees("helper").is_empty());
+    }
+
+    #[test]
+    fn test_dot_shades_nodes_by_fan_in() {
+        let mut graph = CallGraph::new();
+        for name in ["main", "run", "util"] {
+            graph.add_node(CallNode {
+                function_name: name.to_string(),
+                file_path: "test.rs".to_string(),
+                line: 1,
+                is_recursive: false,
+                call_count: 0,
+            });
+        }
+        graph.add_edge("main".to_string(), "util".to_string(), 2, true);
+        graph.add_edge("run".to_string(), "util".to_string(), 3, true);
+        graph.add_edge("main".to_string(), "run".to_string(), 4, true);
+
+        let dot = graph.to_dot();
+        assert!(dot.contains("\"util\" [label=\"util\\n(test.rs:1)\\nfan-in: 2\", fillcolor=\"#a63603\""));
+        assert!(dot.contains("\"run\" [label=\"run\\n(test.rs:1)\\nfan-in: 1\", fillcolor=\"#fd8d3c\""));
+        assert!(dot.contains("\"main\" [label=\"main\\n(test.rs:1)\\nfan-in: 0\", fillcolor=\"#feedde\""));
+    }
 }
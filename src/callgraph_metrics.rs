@@ -0,0 +1,466 @@
+//! Call Graph Metrics
+//!
+//! Fan-in, fan-out, call depth and longest call chains, computed as pure
+//! functions over an adjacency map. Mutually recursive functions are collapsed
+//! into one strongly connected component first, so cycles never loop forever
+//! and count as a single step in a chain.
+
+use crate::callgraph::CallGraph;
+use crate::theme;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Caller -> set of distinct callees; every function appears as a key
+pub type Adjacency = BTreeMap<String, BTreeSet<String>>;
+
+/// Per-function metrics
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FunctionMetrics {
+    pub name: String,
+    /// Number of distinct callers
+    pub fan_in: usize,
+    /// Number of distinct callees
+    pub fan_out: usize,
+    /// Longest call chain starting at this function, in calls
+    pub depth: usize,
+    pub recursive: bool,
+}
+
+/// Maximum call depth reachable from an entry point (a function nobody calls)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EntryDepth {
+    pub name: String,
+    pub depth: usize,
+}
+
+/// Metrics for a whole call graph
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CallGraphMetrics {
+    pub functions: Vec<FunctionMetrics>,
+    pub entry_points: Vec<EntryDepth>,
+    pub max_depth: usize,
+    /// One longest chain; a recursive cycle is shown as `{a, b}`
+    pub longest_chain: Vec<String>,
+    /// Every function on some chain of length `max_depth`
+    pub on_longest_chains: Vec<String>,
+}
+
+/// JSON document for the `callgraph` command: the graph plus its metrics
+#[derive(Debug, Serialize)]
+pub struct CallGraphReport<'a> {
+    #[serde(flatten)]
+    pub graph: &'a CallGraph,
+    pub metrics: CallGraphMetrics,
+}
+
+impl CallGraphMetrics {
+    /// Functions sorted by fan-in, highest first
+    pub fn top_by_fan_in(&self, n: usize) -> Vec<&FunctionMetrics> {
+        self.top_by(n, |f| f.fan_in)
+    }
+
+    /// Functions sorted by fan-out, highest first
+    pub fn top_by_fan_out(&self, n: usize) -> Vec<&FunctionMetrics> {
+        self.top_by(n, |f| f.fan_out)
+    }
+
+    fn top_by(&self, n: usize, key: impl Fn(&FunctionMetrics) -> usize) -> Vec<&FunctionMetrics> {
+        let mut sorted: Vec<&FunctionMetrics> = self.functions.iter().filter(|f| key(f) > 0).collect();
+        sorted.sort_by(|a, b| key(b).cmp(&key(a)).then_with(|| a.name.cmp(&b.name)));
+        sorted.truncate(n);
+        sorted
+    }
+}
+
+/// Print the summary table: top functions by fan-in and fan-out, entry point depths,
+/// and the longest chain
+pub fn print_metrics(metrics: &CallGraphMetrics, top: usize) {
+    use colored::*;
+
+    fn print_table(title: &str, rows: Vec<(&str, usize)>) {
+        if rows.is_empty() {
+            return;
+        }
+        println!("\n{}", title.yellow().bold());
+        for (name, value) in rows {
+            println!("  {:<40} {:>5}", name, value);
+        }
+    }
+
+    print_table(
+        &format!("Top {top} by fan-in (callers):"),
+        metrics.top_by_fan_in(top).iter().map(|f| (f.name.as_str(), f.fan_in)).collect(),
+    );
+    print_table(
+        &format!("Top {top} by fan-out (callees):"),
+        metrics.top_by_fan_out(top).iter().map(|f| (f.name.as_str(), f.fan_out)).collect(),
+    );
+    print_table(
+        "Call depth from entry points:",
+        metrics.entry_points.iter().take(top).map(|e| (e.name.as_str(), e.depth)).collect(),
+    );
+
+    if !metrics.longest_chain.is_empty() {
+        println!("\n{}", format!("Longest call chain ({} calls):", metrics.max_depth).yellow().bold());
+        println!("  {}", metrics.longest_chain.join(&format!(" {} ", theme::arrow())));
+        println!(
+            "  {} function(s) lie on a longest chain (refactoring candidates)",
+            metrics.on_longest_chains.len()
+        );
+    }
+}
+
+/// Build the adjacency map of a call graph, dropping duplicate edges
+pub fn adjacency(graph: &CallGraph) -> Adjacency {
+    let mut adj: Adjacency = graph.nodes.keys().map(|name| (name.clone(), BTreeSet::new())).collect();
+    for edge in &graph.edges {
+        adj.entry(edge.caller.clone()).or_default().insert(edge.callee.clone());
+        adj.entry(edge.callee.clone()).or_default();
+    }
+    adj
+}
+
+/// Number of distinct callers of each function (self-calls excluded)
+pub fn fan_in(adj: &Adjacency) -> BTreeMap<String, usize> {
+    let mut counts: BTreeMap<String, usize> = adj.keys().map(|name| (name.clone(), 0)).collect();
+    for (caller, callees) in adj {
+        for callee in callees.iter().filter(|c| *c != caller) {
+            *counts.entry(callee.clone()).or_insert(0) += 1;
+        }
+    }
+    counts
+}
+
+/// Number of distinct callees of each function (self-calls excluded)
+pub fn fan_out(adj: &Adjacency) -> BTreeMap<String, usize> {
+    adj.iter()
+        .map(|(caller, callees)| (caller.clone(), callees.iter().filter(|c| *c != caller).count()))
+        .collect()
+}
+
+/// Call graph with each strongly connected component collapsed to one node
+struct Condensation {
+    names: Vec<String>,
+    /// Component of each function, indexed like `names`
+    component: Vec<usize>,
+    /// Function indices of each component, in reverse topological order (callees first)
+    members: Vec<Vec<usize>>,
+    /// Distinct successor components of each component
+    successors: Vec<BTreeSet<usize>>,
+    /// Whether the component contains a cycle (more than one member, or a self-call)
+    cyclic: Vec<bool>,
+}
+
+impl Condensation {
+    fn new(adj: &Adjacency) -> Self {
+        let names: Vec<String> = adj.keys().cloned().collect();
+        let index: BTreeMap<&str, usize> = names.iter().enumerate().map(|(i, n)| (n.as_str(), i)).collect();
+        let succ: Vec<Vec<usize>> = adj
+            .values()
+            .map(|callees| callees.iter().filter_map(|c| index.get(c.as_str()).copied()).collect())
+            .collect();
+
+        let members = tarjan(&succ);
+        let mut component = vec![0; names.len()];
+        for (c, scc) in members.iter().enumerate() {
+            for &v in scc {
+                component[v] = c;
+            }
+        }
+
+        let successors = members
+            .iter()
+            .enumerate()
+            .map(|(c, scc)| {
+                scc.iter()
+                    .flat_map(|&v| succ[v].iter().map(|&w| component[w]))
+                    .filter(|&d| d != c)
+                    .collect()
+            })
+            .collect();
+        let cyclic = members
+            .iter()
+            .map(|scc| scc.len() > 1 || succ[scc[0]].contains(&scc[0]))
+            .collect();
+
+        Self { names, component, members, successors, cyclic }
+    }
+
+    /// Longest chain (in calls) leaving each component
+    fn depths(&self) -> Vec<usize> {
+        let mut down = vec![0; self.members.len()];
+        // Components are in reverse topological order, so successors come first
+        for c in 0..self.members.len() {
+            down[c] = self.successors[c].iter().map(|&d| down[d] + 1).max().unwrap_or(0);
+        }
+        down
+    }
+
+    /// Longest chain (in calls) reaching each component from an entry point
+    fn heights(&self) -> Vec<usize> {
+        let mut up = vec![0; self.members.len()];
+        for c in (0..self.members.len()).rev() {
+            for &d in &self.successors[c] {
+                up[d] = up[d].max(up[c] + 1);
+            }
+        }
+        up
+    }
+
+    fn label(&self, c: usize) -> String {
+        let mut names: Vec<&str> = self.members[c].iter().map(|&v| self.names[v].as_str()).collect();
+        if self.members[c].len() == 1 {
+            return names[0].to_string();
+        }
+        names.sort();
+        format!("{{{}}}", names.join(", "))
+    }
+}
+
+/// Tarjan's strongly connected components, iterative to survive deep graphs
+///
+/// Components are returned in reverse topological order.
+fn tarjan(succ: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    const UNVISITED: usize = usize::MAX;
+    let n = succ.len();
+    let mut index = vec![UNVISITED; n];
+    let mut low = vec![0; n];
+    let mut on_stack = vec![false; n];
+    let mut stack = Vec::new();
+    let mut components = Vec::new();
+    let mut next = 0;
+
+    for root in 0..n {
+        if index[root] != UNVISITED {
+            continue;
+        }
+        index[root] = next;
+        low[root] = next;
+        next += 1;
+        stack.push(root);
+        on_stack[root] = true;
+        let mut frames = vec![(root, 0)];
+
+        while let Some(frame) = frames.last_mut() {
+            let v = frame.0;
+            if let Some(&w) = succ[v].get(frame.1) {
+                frame.1 += 1;
+                if index[w] == UNVISITED {
+                    index[w] = next;
+                    low[w] = next;
+                    next += 1;
+                    stack.push(w);
+                    on_stack[w] = true;
+                    frames.push((w, 0));
+                } else if on_stack[w] {
+                    low[v] = low[v].min(index[w]);
+                }
+                continue;
+            }
+
+            frames.pop();
+            if let Some(&(parent, _)) = frames.last() {
+                low[parent] = low[parent].min(low[v]);
+            }
+            if low[v] == index[v] {
+                let mut component = Vec::new();
+                while let Some(w) = stack.pop() {
+                    on_stack[w] = false;
+                    component.push(w);
+                    if w == v {
+                        break;
+                    }
+                }
+                components.push(component);
+            }
+        }
+    }
+
+    components
+}
+
+/// Longest call chain starting at each function, in calls
+pub fn call_depths(adj: &Adjacency) -> BTreeMap<String, usize> {
+    let cond = Condensation::new(adj);
+    let down = cond.depths();
+    cond.names
+        .iter()
+        .enumerate()
+        .map(|(v, name)| (name.clone(), down[cond.component[v]]))
+        .collect()
+}
+
+/// Compute all call graph metrics
+pub fn compute_metrics(adj: &Adjacency) -> CallGraphMetrics {
+    let cond = Condensation::new(adj);
+    let down = cond.depths();
+    let up = cond.heights();
+    let fan_in = fan_in(adj);
+    let fan_out = fan_out(adj);
+    let max_depth = down.iter().copied().max().unwrap_or(0);
+
+    let functions: Vec<FunctionMetrics> = cond
+        .names
+        .iter()
+        .enumerate()
+        .map(|(v, name)| FunctionMetrics {
+            name: name.clone(),
+            fan_in: fan_in[name],
+            fan_out: fan_out[name],
+            depth: down[cond.component[v]],
+            recursive: cond.cyclic[cond.component[v]],
+        })
+        .collect();
+
+    let mut entry_points: Vec<EntryDepth> = functions
+        .iter()
+        .filter(|f| f.fan_in == 0)
+        .map(|f| EntryDepth { name: f.name.clone(), depth: f.depth })
+        .collect();
+    entry_points.sort_by(|a, b| b.depth.cmp(&a.depth).then_with(|| a.name.cmp(&b.name)));
+
+    let on_longest_chains = cond
+        .names
+        .iter()
+        .enumerate()
+        .filter(|&(v, _)| {
+            let c = cond.component[v];
+            max_depth > 0 && up[c] + down[c] == max_depth
+        })
+        .map(|(_, name)| name.clone())
+        .collect();
+
+    CallGraphMetrics {
+        functions,
+        entry_points,
+        max_depth,
+        longest_chain: longest_chain(&cond, &down, &up),
+        on_longest_chains,
+    }
+}
+
+/// Walk one longest chain, preferring alphabetically first labels on ties
+fn longest_chain(cond: &Condensation, down: &[usize], up: &[usize]) -> Vec<String> {
+    let max_depth = down.iter().copied().max().unwrap_or(0);
+    if max_depth == 0 {
+        return Vec::new();
+    }
+
+    let start = (0..down.len())
+        .filter(|&c| up[c] == 0 && down[c] == max_depth)
+        .min_by_key(|&c| cond.label(c));
+    let Some(mut current) = start else {
+        return Vec::new();
+    };
+
+    let mut chain = vec![cond.label(current)];
+    while let Some(next) = cond.successors[current]
+        .iter()
+        .copied()
+        .filter(|&d| down[d] + 1 == down[current])
+        .min_by_key(|&d| cond.label(d))
+    {
+        chain.push(cond.label(next));
+        current = next;
+    }
+    chain
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(edges: &[(&str, &str)]) -> Adjacency {
+        let mut adj = Adjacency::new();
+        for (caller, callee) in edges {
+            adj.entry(caller.to_string()).or_default().insert(callee.to_string());
+            adj.entry(callee.to_string()).or_default();
+        }
+        adj
+    }
+
+    fn metric<'a>(metrics: &'a CallGraphMetrics, name: &str) -> &'a FunctionMetrics {
+        metrics.functions.iter().find(|f| f.name == name).unwrap()
+    }
+
+    #[test]
+    fn test_fan_in_and_fan_out() {
+        let adj = graph(&[("main", "parse"), ("main", "run"), ("run", "parse"), ("run", "log"), ("parse", "log")]);
+        let fan_in = fan_in(&adj);
+        let fan_out = fan_out(&adj);
+
+        assert_eq!(fan_in["log"], 2);
+        assert_eq!(fan_in["parse"], 2);
+        assert_eq!(fan_in["main"], 0);
+        assert_eq!(fan_out["main"], 2);
+        assert_eq!(fan_out["log"], 0);
+    }
+
+    #[test]
+    fn test_depth_of_a_chain() {
+        let adj = graph(&[("a", "b"), ("b", "c"), ("c", "d"), ("a", "d")]);
+        let depths = call_depths(&adj);
+        assert_eq!(depths["a"], 3);
+        assert_eq!(depths["c"], 1);
+        assert_eq!(depths["d"], 0);
+
+        let metrics = compute_metrics(&adj);
+        assert_eq!(metrics.max_depth, 3);
+        assert_eq!(metrics.longest_chain, vec!["a", "b", "c", "d"]);
+        assert_eq!(metrics.entry_points, vec![EntryDepth { name: "a".to_string(), depth: 3 }]);
+    }
+
+    #[test]
+    fn test_cycle_is_collapsed_and_terminates() {
+        // main -> a <-> b -> c, with a self-call on c
+        let adj = graph(&[("main", "a"), ("a", "b"), ("b", "a"), ("b", "c"), ("c", "c")]);
+        let metrics = compute_metrics(&adj);
+
+        assert_eq!(metrics.max_depth, 2);
+        assert_eq!(metrics.longest_chain, vec!["main", "{a, b}", "c"]);
+        assert!(metric(&metrics, "a").recursive);
+        assert!(metric(&metrics, "b").recursive);
+        assert!(metric(&metrics, "c").recursive);
+        assert!(!metric(&metrics, "main").recursive);
+        assert_eq!(metric(&metrics, "a").depth, metric(&metrics, "b").depth);
+        // The self-call doesn't count towards fan-in/fan-out
+        assert_eq!(metric(&metrics, "c").fan_in, 1);
+        assert_eq!(metric(&metrics, "c").fan_out, 0);
+    }
+
+    #[test]
+    fn test_pure_cycle_has_no_entry_points() {
+        let adj = graph(&[("a", "b"), ("b", "c"), ("c", "a")]);
+        let metrics = compute_metrics(&adj);
+        assert!(metrics.entry_points.is_empty());
+        assert_eq!(metrics.max_depth, 0);
+        assert!(metrics.longest_chain.is_empty());
+    }
+
+    #[test]
+    fn test_functions_on_longest_chains() {
+        let adj = graph(&[("a", "b"), ("b", "c"), ("x", "y"), ("y", "c"), ("z", "c")]);
+        let metrics = compute_metrics(&adj);
+        assert_eq!(metrics.max_depth, 2);
+        assert_eq!(metrics.on_longest_chains, vec!["a", "b", "c", "x", "y"]);
+        assert_eq!(metrics.longest_chain, vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_top_by_fan_in_and_fan_out() {
+        let adj = graph(&[("a", "util"), ("b", "util"), ("c", "util"), ("a", "b"), ("a", "c")]);
+        let metrics = compute_metrics(&adj);
+
+        let top_in: Vec<&str> = metrics.top_by_fan_in(2).iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(top_in, vec!["util", "b"]);
+        let top_out: Vec<&str> = metrics.top_by_fan_out(10).iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(top_out, vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_empty_graph() {
+        let metrics = compute_metrics(&Adjacency::new());
+        assert!(metrics.functions.is_empty());
+        assert_eq!(metrics.max_depth, 0);
+    }
+}
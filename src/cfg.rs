@@ -5,6 +5,7 @@
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::path::Path;
+use crate::lines::split_lines;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ControlFlowGraph {
@@ -189,7 +190,7 @@ pub fn build_cfg_from_source(content: &str, function_name: &str, file_path: &str
     let mut cfg = ControlFlowGraph::new(function_name.to_string(), file_path.to_string());
     let mut block_id = 0;
 
-    let lines: Vec<&str> = content.lines().collect();
+    let lines: Vec<&str> = split_lines(content).collect();
     let mut in_function = false;
     let mut brace_depth = 0;
 
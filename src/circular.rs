@@ -2,8 +2,10 @@
 //!
 //! Detects circular function calls (cycles in the call graph).
 
+use crate::lines::split_lines;
 use crate::parser::{extract_function_calls, extract_functions, read_file_content};
 use crate::search::list_files;
+use crate::theme;
 use colored::*;
 use std::collections::{HashMap, HashSet};
 use std::path::Path;
@@ -23,7 +25,7 @@ pub fn detect_circular_calls(
     exclude: Option<&[String]>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     println!("{}", "Circular Call Detection".cyan().bold());
-    println!("{}", "─".repeat(30).cyan());
+    println!("{}", theme::rule(30).cyan());
     println!();
 
     let cycles = find_circular_calls(path, extensions, exclude)?;
@@ -47,7 +49,7 @@ pub fn detect_circular_calls(
             println!();
         }
 
-        println!("{}", "─".repeat(50).dimmed());
+        println!("{}", theme::rule(50).dimmed());
         println!(
             "{} {} circular call chain(s) found",
             "-".dimmed(),
@@ -116,7 +118,7 @@ pub fn find_circular_calls(
 
 /// Extract function calls from a function body starting at a given line
 fn extract_calls_from_function_body(content: &str, start_line: usize) -> HashSet<String> {
-    let lines: Vec<&str> = content.lines().collect();
+    let lines: Vec<&str> = split_lines(content).collect();
     let mut calls = HashSet::new();
     let mut brace_count = 0;
     let mut in_body = false;
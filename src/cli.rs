@@ -2,13 +2,34 @@
 //!
 //! This module contains all command-line interface definitions using clap.
 
-use clap::{Parser, Subcommand};
+use crate::badge::BadgeMetric;
+use crate::config::Config;
+use crate::errors::SearchError;
+use crate::flag_rules::has_regex_syntax;
+use crate::options::normalize_extension_option;
+use crate::profiles::{fill, Profile};
+use crate::types::{SortKey, SuggestionType};
+use clap::parser::ValueSource;
+use clap::{ArgMatches, Parser, Subcommand};
+use std::collections::HashMap;
 use std::path::PathBuf;
 
+/// Exit codes, kept in sync with [`crate::errors::exit_code`]
+pub const EXIT_CODES_HELP: &str = "Exit codes:
+  0   Success, including findings when no gate flag is given
+  1   search --fail-on-matches: matches found
+  2   complexity --max-complexity: a file or function exceeds the limit
+  3   duplicates --max-duplicates: more duplicates than allowed
+  4   deadcode --fail-on-deadcode: dead code found
+  5   lint --fail-on: a rule failed
+  6   watch --fail-on-regression: a change made a file worse
+  10  Error: invalid pattern, missing path, bad arguments";
+
 #[derive(Parser)]
 #[command(name = "codesearch")]
 #[command(about = "A fast CLI tool for searching codebases")]
 #[command(version)]
+#[command(after_help = EXIT_CODES_HELP)]
 pub struct Cli {
     #[command(subcommand)]
     pub command: Option<Commands>,
@@ -33,15 +54,198 @@ pub struct Cli {
     #[arg(short, long, default_value = "true")]
     pub ignore_case: bool,
     
-    /// Maximum results per file
-    #[arg(short, long, default_value = "10")]
+    /// Maximum matching lines per file (see --max-total for a global cap)
+    #[arg(short, long, default_value_t = crate::types::DEFAULT_MAX_RESULTS)]
     pub max_results: usize,
     
+    /// Maximum results in total, keeping the best-ranked ones
+    #[arg(long)]
+    pub max_total: Option<usize>,
+    
     /// Exclude directories
     #[arg(long, value_delimiter = ',')]
     pub exclude: Option<Vec<String>>,
+
+    /// Plain ASCII decorations instead of emoji and box-drawing characters (colors unaffected)
+    #[arg(long, global = true)]
+    pub no_emoji: bool,
+
+    /// Print file paths as clickable terminal hyperlinks: auto, always, never (default: auto;
+    /// never when output is piped)
+    #[arg(long, global = true, value_name = "WHEN")]
+    pub hyperlinks: Option<String>,
+
+    /// Worker threads for the parallel file scans of every command (default: one per CPU)
+    #[arg(long, global = true, value_name = "N")]
+    pub jobs: Option<usize>,
+
+    /// Option bundle filling in unset flags: quick, thorough, ci, or a [profiles.NAME] from the config
+    #[arg(long, global = true, value_name = "NAME")]
+    pub profile: Option<String>,
+
+    /// Config file to use instead of the CODESEARCH_CONFIG one or the nearest .codesearch.toml
+    /// above the searched path
+    #[arg(long, global = true, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+}
+
+impl Cli {
+    /// Normalize every `--extensions` list (dots, case, duplicates), rejecting invalid entries
+    pub fn normalize_extensions(&mut self) -> Result<(), SearchError> {
+        normalize_extension_option(&mut self.extensions)?;
+        match &mut self.command {
+            Some(
+                Commands::Search { extensions, .. }
+            | Commands::Files { extensions, .. }
+            | Commands::Interactive { extensions, .. }
+            | Commands::Analyze { extensions, .. }
+            | Commands::Complexity { extensions, .. }
+            | Commands::DesignMetrics { extensions, .. }
+            | Commands::Metrics { extensions, .. }
+            | Commands::Duplicates { extensions, .. }
+            | Commands::Deadcode { extensions, .. }
+            | Commands::Refactor { extensions, .. }
+            | Commands::Badge { extensions, .. }
+            | Commands::Circular { extensions, .. }
+            | Commands::Index { extensions, .. }
+            | Commands::Warm { extensions, .. }
+            | Commands::Watch { extensions, .. }
+            | Commands::Ast { extensions, .. }
+            | Commands::Cfg { extensions, .. }
+            | Commands::Dfg { extensions, .. }
+            | Commands::Callgraph { extensions, .. }
+            | Commands::Pdg { extensions, .. }
+            | Commands::Depgraph { extensions, .. }
+            | Commands::Dependents { extensions, .. }
+            | Commands::SemanticSearch { extensions, .. }
+            | Commands::Remote { extensions, .. }
+            | Commands::Report { extensions, .. }
+            | Commands::PrivacyScan { extensions, .. }
+            | Commands::Hygiene { extensions, .. }
+            | Commands::Lint { extensions, .. },
+            ) => normalize_extension_option(extensions),
+            _ => Ok(()),
+        }
+    }
+
+    /// The path the command works on, where config file discovery starts
+    ///
+    /// `matches` must be what `self` was parsed from. Commands without a
+    /// path argument work on the current directory.
+    pub fn target_path(&self, matches: &ArgMatches) -> PathBuf {
+        let Some((_, mut sub)) = matches.subcommand() else {
+            return self.path.clone();
+        };
+        while let Some((_, nested)) = sub.subcommand() {
+            sub = nested;
+        }
+        sub.try_get_one::<PathBuf>("path").ok().flatten().cloned().unwrap_or_else(|| PathBuf::from("."))
+    }
+
+    /// Fill in the analysis options the command line left unset from the config file
+    ///
+    /// `matches` must be what `self` was parsed from. Runs before
+    /// [`Cli::apply_profile`], so a profile wins over the config.
+    pub fn apply_config(&mut self, matches: &ArgMatches, config: &Config) {
+        let Some((_, sub)) = matches.subcommand() else {
+            return;
+        };
+        let explicit = |id: &str| sub.value_source(id) == Some(ValueSource::CommandLine);
+        let scope = &config.analyze;
+
+        match &mut self.command {
+            Some(Commands::Analyze { extensions, exclude, .. }) => {
+                fill(extensions, Some(&scope.extensions), explicit("extensions"));
+                fill(exclude, Some(&scope.exclude), explicit("exclude"));
+            }
+            Some(Commands::Complexity { extensions, exclude, threshold, sort, .. }) => {
+                fill(extensions, Some(&scope.extensions), explicit("extensions"));
+                fill(exclude, Some(&scope.exclude), explicit("exclude"));
+                fill(threshold, Some(&config.complexity.threshold), explicit("threshold"));
+                fill(sort, Some(&config.complexity.sort), explicit("sort"));
+            }
+            Some(Commands::Duplicates { extensions, exclude, min_lines, similarity, .. }) => {
+                fill(extensions, Some(&scope.extensions), explicit("extensions"));
+                fill(exclude, Some(&scope.exclude), explicit("exclude"));
+                fill(min_lines, Some(&config.duplicates.min_lines), explicit("min_lines"));
+                fill(similarity, Some(&config.duplicates.similarity), explicit("similarity"));
+            }
+            Some(Commands::Deadcode { extensions, exclude, include_public, .. }) => {
+                fill(extensions, Some(&scope.extensions), explicit("extensions"));
+                fill(exclude, Some(&scope.exclude), explicit("exclude"));
+                fill(include_public, Some(&config.deadcode.include_public), explicit("include_public"));
+            }
+            _ => {}
+        }
+    }
+
+    /// Fill in the options the command line left unset from the `--profile` profile
+    ///
+    /// `matches` must be what `self` was parsed from; they tell explicit flags
+    /// from defaults. Returns the active profile, if any.
+    pub fn apply_profile(
+        &mut self,
+        matches: &ArgMatches,
+        configured: &HashMap<String, Profile>,
+    ) -> Result<Option<Profile>, SearchError> {
+        let Some(name) = &self.profile else {
+            return Ok(None);
+        };
+        let profile = Profile::resolve(name, configured)?;
+        let Some((_, sub)) = matches.subcommand() else {
+            return Ok(Some(profile));
+        };
+        let explicit = |id: &str| sub.value_source(id) == Some(ValueSource::CommandLine);
+
+        match &mut self.command {
+            Some(Commands::Search {
+                query,
+                literal,
+                fuzzy,
+                word,
+                max_results,
+                max_total,
+                format,
+                multiline,
+                multiline_max_bytes,
+                no_auto_exclude,
+                no_gitignore,
+                rank,
+                rank_group,
+                sort_by,
+                fail_on_matches,
+                ..
+            }) => {
+                // Literal first: only where the query has no regex syntax and no other matching mode is asked for
+                if !*fuzzy && !*word && !has_regex_syntax(query) {
+                    fill(literal, profile.literal.as_ref(), explicit("literal"));
+                }
+                fill(max_results, profile.max_results.as_ref(), explicit("max_results"));
+                fill(max_total, profile.max_total.map(Some).as_ref(), explicit("max_total"));
+                fill(format, profile.format.as_ref(), explicit("format"));
+                fill(multiline, profile.multiline.as_ref(), explicit("multiline"));
+                fill(multiline_max_bytes, profile.multiline_max_bytes.as_ref(), explicit("multiline_max_bytes"));
+                fill(no_auto_exclude, profile.auto_exclude.map(|on| !on).as_ref(), explicit("no_auto_exclude"));
+                fill(no_gitignore, profile.gitignore.map(|on| !on).as_ref(), explicit("no_gitignore"));
+                fill(rank, profile.rank.as_ref(), explicit("rank"));
+                // An explicit result order replaces the profile's
+                let ordered = explicit("rank") || rank_group.is_some();
+                fill(sort_by, profile.sort_by.map(Some).as_ref(), explicit("sort_by") || ordered);
+                fill(fail_on_matches, profile.fail_on_findings.as_ref(), explicit("fail_on_matches"));
+            }
+            Some(Commands::Deadcode { format, quiet, fail_on_deadcode, .. }) => {
+                fill(format, profile.format.as_ref(), explicit("format"));
+                fill(quiet, profile.quiet.as_ref(), explicit("quiet"));
+                fill(fail_on_deadcode, profile.fail_on_findings.as_ref(), explicit("fail_on_deadcode"));
+            }
+            _ => {}
+        }
+        Ok(Some(profile))
+    }
 }
 
+// Parsed once per run, so the size of the `Search` variant doesn't matter
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand)]
 pub enum Commands {
     /// Search for text patterns in code files
@@ -60,45 +264,133 @@ pub enum Commands {
         /// Hide line numbers (line numbers shown by default)
         #[arg(short = 'N', long)]
         no_line_numbers: bool,
-        /// Maximum number of results per file
-        #[arg(long, default_value = "10")]
+        /// Maximum matching lines per file; results across files can exceed this (see --max-total)
+        #[arg(long, default_value_t = crate::types::DEFAULT_MAX_RESULTS)]
         max_results: usize,
-        /// Output format (text, json)
+        /// Maximum results in total, applied after ranking so the best results are kept
+        #[arg(long)]
+        max_total: Option<usize>,
+        /// Output format: text, json (compact, streamed as results are found) or json-pretty
         #[arg(long, default_value = "text")]
         format: String,
-        /// Show search statistics
+        /// Print only the matched text, one match per line
+        #[arg(short = 'o', long)]
+        only_matching: bool,
+        /// With -o, print this template per match instead (e.g. '$1' for the first capture group)
+        #[arg(long, requires = "only_matching")]
+        replace_output: Option<String>,
+        /// With -o, omit the file name prefix
+        #[arg(long)]
+        no_filename: bool,
+        /// Fit text output to N columns (default: the terminal's; piped output is never cut)
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u16).range(1..))]
+        width: Option<u16>,
+        /// Wrap long lines under themselves instead of cutting them with an ellipsis
+        #[arg(long)]
+        wrap: bool,
+        /// Show search statistics (on stderr; also keeps other notices when output is piped)
         #[arg(long)]
         stats: bool,
         /// Enable fuzzy search (handles typos and variations)
         #[arg(long)]
         fuzzy: bool,
-        /// Fuzzy search threshold (0.0 = exact match, 1.0 = very loose)
-        #[arg(long, default_value = "0.6")]
+        /// Minimum fuzzy similarity, 0.0-1.0: higher is stricter (0.0 = any match, 1.0 = the query verbatim)
+        #[arg(long, default_value_t = crate::types::DEFAULT_FUZZY_THRESHOLD)]
         fuzzy_threshold: f64,
+        /// Treat the query as plain text instead of a regex
+        #[arg(short = 'F', long)]
+        literal: bool,
+        /// Only match the query as a whole word
+        #[arg(short = 'w', long)]
+        word: bool,
+        /// Lines of context to show around each match
+        #[arg(short = 'C', long)]
+        context: Option<usize>,
+        /// Lines of context to show before each match (overrides --context)
+        #[arg(short = 'B', long)]
+        before: Option<usize>,
+        /// Lines of context to show after each match (overrides --context)
+        #[arg(short = 'A', long)]
+        after: Option<usize>,
+        /// Let the pattern span lines: match whole files, with `.` matching newlines
+        #[arg(short = 'U', long)]
+        multiline: bool,
+        /// With --multiline, skip files larger than this many bytes
+        #[arg(long, default_value_t = crate::types::DEFAULT_MULTILINE_MAX_BYTES)]
+        multiline_max_bytes: u64,
         /// Exclude directories (default: auto-excludes common build dirs)
         #[arg(long, value_delimiter = ',')]
         exclude: Option<Vec<String>>,
         /// Don't auto-exclude common build directories
         #[arg(long)]
         no_auto_exclude: bool,
+        /// Don't skip paths matched by .gitignore files
+        #[arg(long)]
+        no_gitignore: bool,
+        /// Search only the files listed in this file ('-' for stdin), one per line, instead of walking PATH
+        #[arg(long, value_name = "PATH|-")]
+        files_from: Option<String>,
+        /// With --files-from, search every listed file, ignoring --extensions and --multiline-max-bytes
+        #[arg(long, requires = "files_from")]
+        no_filter_list: bool,
         /// Sort results by relevance score
         #[arg(long)]
         rank: bool,
+        /// With --rank: file (files by best score, each file's results in line order)
+        /// or none (one flat list by score); default file on a terminal, none otherwise
+        #[arg(long, value_name = "file|none", requires = "rank")]
+        rank_group: Option<String>,
+        /// Sort results deterministically; file order otherwise varies between runs
+        #[arg(long, value_enum)]
+        sort_by: Option<SortKey>,
         /// Enable intelligent caching for faster repeated searches
         #[arg(long)]
         cache: bool,
+        /// Skip files the index (built by `codesearch index`) shows can't match
+        #[arg(long)]
+        use_index: bool,
+        /// Index file consulted with --use-index
+        #[arg(long, default_value = ".codesearch/index.json", requires = "use_index")]
+        index_file: PathBuf,
+        /// Search an archive from `codesearch index --pack --include-contents` instead of PATH
+        #[arg(long, value_name = "FILE", conflicts_with_all = ["files_from", "use_index"])]
+        index_archive: Option<PathBuf>,
         /// Enable semantic search (context-aware matching)
         #[arg(long)]
         semantic: bool,
+        /// Explain how the query is interpreted (e.g. applied semantic expansions)
+        #[arg(long)]
+        explain: bool,
+        /// Regex engine: default, or fancy for lookaround/backreferences (needs the `fancy` feature)
+        #[arg(long, default_value = "default")]
+        engine: String,
+        /// File encoding: auto (UTF-16 when the file starts with a byte order mark), utf8 or utf16
+        #[arg(long, default_value = "auto")]
+        encoding: String,
         /// Performance benchmark mode
         #[arg(long)]
         benchmark: bool,
         /// Compare performance with grep
         #[arg(long)]
         vs_grep: bool,
-        /// Export results to file (csv, markdown, md)
+        /// Export results to file (csv, markdown, md, html, xml for JUnit)
         #[arg(long)]
         export: Option<String>,
+        /// Columns of a CSV or Markdown export (file,line,content,score,relevance,matches)
+        #[arg(long, value_delimiter = ',', requires = "export")]
+        export_columns: Option<Vec<String>>,
+        /// Cut Markdown export content to N characters
+        #[arg(long, value_name = "N", requires = "export")]
+        export_width: Option<usize>,
+        /// Annotate bookmarked lines with their label
+        #[arg(long)]
+        show_bookmarks: bool,
+        /// JSON schema version to emit (e.g. 1.0.0, or 0 for the legacy shape)
+        #[arg(long, default_value = crate::types::SCHEMA_VERSION)]
+        schema_version: String,
+        /// Exit with code 1 when anything matches, e.g. to forbid a pattern in CI
+        #[arg(long)]
+        fail_on_matches: bool,
     },
     /// List all searchable files
     Files {
@@ -111,6 +403,9 @@ pub enum Commands {
         /// Exclude directories (e.g., target,node_modules)
         #[arg(long, value_delimiter = ',')]
         exclude: Option<Vec<String>>,
+        /// Don't skip paths matched by .gitignore files
+        #[arg(long)]
+        no_gitignore: bool,
     },
     /// Interactive search mode
     Interactive {
@@ -123,6 +418,12 @@ pub enum Commands {
         /// Exclude directories (e.g., target,node_modules)
         #[arg(long, value_delimiter = ',')]
         exclude: Option<Vec<String>>,
+        /// Run the commands of a script written by `save-session`
+        #[arg(long, value_name = "FILE")]
+        replay: Option<PathBuf>,
+        /// Replay steps 1 to STEP only, then continue interactively
+        #[arg(long, value_name = "STEP", requires = "replay")]
+        replay_to: Option<usize>,
     },
     /// Analyze codebase metrics and statistics
     Analyze {
@@ -135,6 +436,12 @@ pub enum Commands {
         /// Exclude directories (e.g., target,node_modules)
         #[arg(long, value_delimiter = ',')]
         exclude: Option<Vec<String>>,
+        /// Output format (text, json)
+        #[arg(long, default_value = "text")]
+        format: String,
+        /// JSON schema version to emit (e.g. 1.0.0, or 0 for the legacy shape)
+        #[arg(long, default_value = crate::types::SCHEMA_VERSION)]
+        schema_version: String,
     },
     /// Analyze code complexity metrics
     Complexity {
@@ -147,12 +454,27 @@ pub enum Commands {
         /// Exclude directories (e.g., target,node_modules)
         #[arg(long, value_delimiter = ',')]
         exclude: Option<Vec<String>>,
-        /// Show only files above complexity threshold
+        /// Show only files above complexity threshold (functions with --per-function)
         #[arg(long)]
         threshold: Option<u32>,
         /// Sort by complexity (highest first)
         #[arg(long)]
         sort: bool,
+        /// Output format (text, json, sarif)
+        #[arg(long, default_value = "text")]
+        format: String,
+        /// Write complex files to a SARIF file (an HTML report if FILE ends in .html) instead of printing them
+        #[arg(long)]
+        export: Option<String>,
+        /// Report individual functions with file:line locations, most complex first
+        #[arg(long, conflicts_with = "export")]
+        per_function: bool,
+        /// Number of functions to list with --per-function
+        #[arg(long, default_value = "20", requires = "per_function")]
+        top: usize,
+        /// Exit with code 2 if a file (a function with --per-function) has a cyclomatic complexity above N
+        #[arg(long, value_name = "N")]
+        max_complexity: Option<u32>,
     },
     /// Analyze design metrics (coupling, cohesion, instability)
     DesignMetrics {
@@ -171,6 +493,9 @@ pub enum Commands {
         /// Output format (text, json)
         #[arg(long, default_value = "text")]
         format: String,
+        /// JSON schema version to emit (e.g. 1.0.0, or 0 for the legacy shape)
+        #[arg(long, default_value = crate::types::SCHEMA_VERSION)]
+        schema_version: String,
     },
     /// Comprehensive code metrics (complexity, size, maintainability)
     Metrics {
@@ -189,6 +514,12 @@ pub enum Commands {
         /// Output format (text, json)
         #[arg(long, default_value = "text")]
         format: String,
+        /// JSON schema version to emit (e.g. 1.0.0, or 0 for the legacy shape)
+        #[arg(long, default_value = crate::types::SCHEMA_VERSION)]
+        schema_version: String,
+        /// Print per-phase timings on stderr and add them to JSON output under `meta`
+        #[arg(long)]
+        verbose: bool,
     },
     /// Detect code duplication in the codebase
     Duplicates {
@@ -202,11 +533,26 @@ pub enum Commands {
         #[arg(long, value_delimiter = ',')]
         exclude: Option<Vec<String>>,
         /// Minimum lines for a duplicate block
-        #[arg(long, default_value = "3")]
+        #[arg(long, default_value_t = crate::duplicates::DEFAULT_MIN_LINES)]
         min_lines: usize,
         /// Similarity threshold (0.0 - 1.0)
-        #[arg(long, default_value = "0.9")]
+        #[arg(long, default_value_t = crate::duplicates::DEFAULT_SIMILARITY)]
         similarity: f64,
+        /// Compare import blocks and field/variant lists regardless of their order
+        #[arg(long)]
+        order_insensitive: bool,
+        /// Unit of comparison: window (sliding line windows) or function (whole function bodies)
+        #[arg(long, default_value = "window")]
+        granularity: String,
+        /// Output format (text, json, sarif)
+        #[arg(long, default_value = "text")]
+        format: String,
+        /// Write duplicates to a SARIF file (an HTML report if FILE ends in .html) instead of printing them
+        #[arg(long)]
+        export: Option<String>,
+        /// Exit with code 3 if more than N duplicate clusters are found
+        #[arg(long, value_name = "N")]
+        max_duplicates: Option<usize>,
     },
     /// Detect potentially dead/unused code
     Deadcode {
@@ -219,6 +565,74 @@ pub enum Commands {
         /// Exclude directories (e.g., target,node_modules)
         #[arg(long, value_delimiter = ',')]
         exclude: Option<Vec<String>>,
+        /// Judge functions by call-graph reachability from entry points ([deadcode] entry_points)
+        #[arg(long)]
+        reachability: bool,
+        /// Extra entry points as path::function, globs allowed (implies --reachability)
+        #[arg(long, value_delimiter = ',')]
+        entry_point: Option<Vec<String>>,
+        /// Also report public items (pub, __all__, exports), at low confidence
+        #[arg(long)]
+        include_public: bool,
+        /// Output format (text, json, sarif)
+        #[arg(long, default_value = "text")]
+        format: String,
+        /// In text output, print only the summary counts, not each item
+        #[arg(short, long)]
+        quiet: bool,
+        /// Write findings to a SARIF file (an HTML report if FILE ends in .html) instead of printing them
+        #[arg(long)]
+        export: Option<String>,
+        /// Exit with code 4 if any dead code is found
+        #[arg(long)]
+        fail_on_deadcode: bool,
+    },
+    /// Suggest refactorings: long lines, TODOs, magic numbers, deep nesting, complex functions, ...
+    Refactor {
+        /// Path to analyze (default: current directory)
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// File extensions to include (e.g., rs,py,js)
+        #[arg(short, long, value_delimiter = ',')]
+        extensions: Option<Vec<String>>,
+        /// Exclude directories (e.g., target,node_modules)
+        #[arg(long, value_delimiter = ',')]
+        exclude: Option<Vec<String>>,
+        /// Show only suggestions of priority 7 and above
+        #[arg(long)]
+        high_priority: bool,
+        /// Suggestion type not to look for, on top of [refactor] disabled_types (repeatable):
+        /// Long Line, Todo Comment, Magic Number, Deep Nesting, Empty Catch Block,
+        /// Complex Function, Trivial Delegation
+        #[arg(long, value_name = "TYPE", value_delimiter = ',')]
+        disable: Vec<SuggestionType>,
+        /// Look only for these suggestion types (repeatable), ignoring [refactor] disabled_types
+        #[arg(long, value_name = "TYPE", value_delimiter = ',')]
+        only: Vec<SuggestionType>,
+    },
+    /// Write a README status badge (SVG, or shields.io endpoint JSON) for one analysis metric
+    Badge {
+        /// Metric to show: maintainability, complexity (average cyclomatic) or deadcode (finding count)
+        #[arg(long)]
+        metric: BadgeMetric,
+        /// Path to analyze (default: current directory)
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// File extensions to include (e.g., rs,py,js)
+        #[arg(short, long, value_delimiter = ',')]
+        extensions: Option<Vec<String>>,
+        /// Exclude directories (e.g., target,node_modules), on top of the common build directories
+        #[arg(long, value_delimiter = ',')]
+        exclude: Option<Vec<String>>,
+        /// File to write the badge to (default: stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Output format: svg, or json (label, message and color for shields.io endpoint badges)
+        #[arg(long, default_value = "svg")]
+        format: String,
+        /// Use the value last computed for this metric and path (.codesearch/history.jsonl) instead of analyzing
+        #[arg(long)]
+        cached: bool,
     },
     /// Detect circular function calls
     Circular {
@@ -233,9 +647,23 @@ pub enum Commands {
         exclude: Option<Vec<String>>,
     },
     /// List all supported programming languages
-    Languages,
+    Languages {
+        /// Output format (text, json); json includes each language's complexity limits
+        #[arg(long, default_value = "text")]
+        format: String,
+    },
     /// Run as MCP server
-    McpServer,
+    McpServer {
+        /// Maximum tool calls executing at once; others wait for a slot
+        #[arg(long, default_value = "4")]
+        max_concurrent: usize,
+        /// Wall-time budget per tool call in seconds, including the wait for a slot
+        #[arg(long, default_value = "60")]
+        call_timeout: u64,
+        /// Serialized result size in bytes above which results are truncated
+        #[arg(long, default_value = "1000000")]
+        max_result_bytes: usize,
+    },
     /// Build or update code index for faster searches
     Index {
         /// Path to index (default: current directory)
@@ -250,6 +678,33 @@ pub enum Commands {
         /// Index file path
         #[arg(long, default_value = ".codesearch/index.json")]
         index_file: PathBuf,
+        /// Also write a self-contained, read-only archive of the index for `search --index-archive`
+        #[arg(long, value_name = "FILE")]
+        pack: Option<PathBuf>,
+        /// With --pack, store every file's contents so the archive can be searched without the tree
+        #[arg(long, requires = "pack")]
+        include_contents: bool,
+    },
+    /// Run the queries in a file ahead of time so later searches hit the cache and index
+    Warm {
+        /// File with one query per line; blank lines and lines starting with # are skipped
+        #[arg(long)]
+        queries_file: PathBuf,
+        /// Path to search; later searches must use the same path to hit the cache
+        #[arg(long, default_value = ".")]
+        path: PathBuf,
+        /// File extensions to include (e.g., rs,py,js)
+        #[arg(short, long, value_delimiter = ',')]
+        extensions: Option<Vec<String>>,
+        /// Exclude directories (e.g., target,node_modules), on top of the common build directories
+        #[arg(long, value_delimiter = ',')]
+        exclude: Option<Vec<String>>,
+        /// Index file to refresh and search with
+        #[arg(long, default_value = ".codesearch/index.json")]
+        index_file: PathBuf,
+        /// Queries to run at the same time
+        #[arg(long, default_value = "1")]
+        parallel: usize,
     },
     /// Watch directory for changes and update index
     Watch {
@@ -262,6 +717,21 @@ pub enum Commands {
         /// Index file path
         #[arg(long, default_value = ".codesearch/index.json")]
         index_file: PathBuf,
+        /// Re-run analyses on changed files and print deltas (complexity, lint)
+        #[arg(long, value_delimiter = ',')]
+        analyze: Option<Vec<String>>,
+        /// Milliseconds without events that end a batch of changes
+        #[arg(long, default_value = "200", requires = "analyze")]
+        debounce_ms: u64,
+        /// Ring the terminal bell when a change makes a file worse
+        #[arg(long, requires = "analyze")]
+        bell: bool,
+        /// Exit with a nonzero status when the watch ends if any change made a file worse
+        #[arg(long, requires = "analyze")]
+        fail_on_regression: bool,
+        /// Stop watching after this many seconds
+        #[arg(long, requires = "analyze")]
+        duration: Option<u64>,
     },
     /// Analyze code using AST (Abstract Syntax Tree)
     Ast {
@@ -373,6 +843,89 @@ pub enum Commands {
         #[arg(long)]
         circular_only: bool,
     },
+    /// Find functions by meaning with local embeddings (experimental, `embeddings` feature)
+    ///
+    /// Without the feature, falls back to keyword search with `--semantic` expansion.
+    SemanticSearch {
+        /// What the code does, in plain words
+        query: String,
+        /// Path to search (default: current directory)
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// File extensions to include (e.g., rs,py,js)
+        #[arg(short, long, value_delimiter = ',')]
+        extensions: Option<Vec<String>>,
+        /// Exclude directories (e.g., target,node_modules)
+        #[arg(long, value_delimiter = ',')]
+        exclude: Option<Vec<String>>,
+        /// Number of functions to return
+        #[arg(short = 'k', long, default_value = "10")]
+        top_k: usize,
+        /// Sentence-transformer directory or word-vector file to embed with (default: `[semantic] model` in the config)
+        #[arg(long)]
+        model: Option<PathBuf>,
+        /// Index the vectors are stored beside
+        #[arg(long, default_value = ".codesearch/index.json")]
+        index_file: PathBuf,
+        /// Output format (text, json)
+        #[arg(long, default_value = "text")]
+        format: String,
+    },
+    /// List the files that import a file, directly or transitively
+    Dependents {
+        /// File whose dependents to find
+        file: PathBuf,
+        /// Project root to build the import graph from (default: current directory)
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// File extensions to include (e.g., rs,py,js)
+        #[arg(short, long, value_delimiter = ',')]
+        extensions: Option<Vec<String>>,
+        /// Exclude directories (e.g., target,node_modules)
+        #[arg(long, value_delimiter = ',')]
+        exclude: Option<Vec<String>>,
+        /// Also list files that import it through other files
+        #[arg(long)]
+        transitive: bool,
+        /// Maximum import distance followed by --transitive (1 = direct only)
+        #[arg(long, requires = "transitive")]
+        depth: Option<usize>,
+        /// Output format (text, json, dot)
+        #[arg(long, default_value = "text")]
+        format: String,
+    },
+    /// List the dependencies declared in Cargo.toml, package.json and pyproject.toml
+    Deps {
+        /// Project root to look for manifests under (default: current directory)
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// Exclude directories (e.g., vendor,examples)
+        #[arg(long, value_delimiter = ',')]
+        exclude: Option<Vec<String>>,
+        /// Report declared dependencies nothing imports, and imports nothing declares
+        #[arg(long)]
+        unused: bool,
+        /// Output format (text, json)
+        #[arg(long, default_value = "text")]
+        format: String,
+    },
+    /// List the public symbols added, removed or changed between two git revisions
+    ApiDiff {
+        /// Revision range FROM..TO (e.g. v1.2.0..HEAD); a single revision is compared with HEAD
+        range: String,
+        /// Repository path (default: current directory)
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// File extensions to compare (default: rs,py,js,ts and their variants)
+        #[arg(short, long, value_delimiter = ',')]
+        extensions: Option<Vec<String>>,
+        /// Exclude directories (e.g., vendor,tests)
+        #[arg(long, value_delimiter = ',')]
+        exclude: Option<Vec<String>>,
+        /// Output format (text, json, markdown)
+        #[arg(long, default_value = "text")]
+        format: String,
+    },
     /// Search git history
     GitHistory {
         /// Search pattern
@@ -393,6 +946,21 @@ pub enum Commands {
         #[arg(long)]
         file: Option<String>,
     },
+    /// Manage bookmarks on search results (stored in .codesearch/bookmarks.json)
+    Bookmarks {
+        #[command(subcommand)]
+        action: BookmarkAction,
+    },
+    /// Manage the `search --cache` result cache (stored in .codesearch/cache/)
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+    /// Inspect the configuration (.codesearch.toml, or the file CODESEARCH_CONFIG names)
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
     /// Search remote repositories
     Remote {
         /// Search pattern
@@ -416,6 +984,148 @@ pub enum Commands {
         #[arg(long, default_value = "20")]
         max_results: usize,
     },
+    /// Onboarding report: languages, complexity, dead code, README and owners
+    Report {
+        /// Path to analyze (default: current directory)
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// File extensions to include (e.g., rs,py,js)
+        #[arg(short, long, value_delimiter = ',')]
+        extensions: Option<Vec<String>>,
+        /// Exclude directories (e.g., target,node_modules)
+        #[arg(long, value_delimiter = ',')]
+        exclude: Option<Vec<String>>,
+        /// Break the report down by directory
+        #[arg(long)]
+        per_directory: bool,
+        /// Directory depth to group by with --per-directory
+        #[arg(long, default_value = "1")]
+        depth: usize,
+        /// Write one Markdown file per directory into this directory
+        #[arg(long)]
+        export_dir: Option<PathBuf>,
+        /// Output format (markdown, json)
+        #[arg(long, default_value = "markdown")]
+        format: String,
+        /// Print per-phase timings on stderr and add them to JSON output under `meta`
+        #[arg(long)]
+        verbose: bool,
+    },
+    /// Find logging/print calls that interpolate sensitive-looking values (email, token, ...)
+    PrivacyScan {
+        /// Path to scan (default: current directory)
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// File extensions to include (e.g., rs,py,js)
+        #[arg(short, long, value_delimiter = ',')]
+        extensions: Option<Vec<String>>,
+        /// Exclude directories (e.g., target,node_modules)
+        #[arg(long, value_delimiter = ',')]
+        exclude: Option<Vec<String>>,
+        /// Extra sensitive names on top of the built-in and configured ones (e.g., salary,tax_id)
+        #[arg(long, value_delimiter = ',')]
+        sensitive: Option<Vec<String>>,
+        /// Output format (text, json, sarif)
+        #[arg(long, default_value = "text")]
+        format: String,
+    },
+    /// Report line-ending style, BOMs, trailing whitespace and missing final newlines
+    Hygiene {
+        /// Path to scan (default: current directory)
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// File extensions to include (e.g., rs,py,js)
+        #[arg(short, long, value_delimiter = ',')]
+        extensions: Option<Vec<String>>,
+        /// Exclude directories (e.g., target,node_modules)
+        #[arg(long, value_delimiter = ',')]
+        exclude: Option<Vec<String>>,
+        /// Number of worst offenders to list
+        #[arg(long, default_value = "10")]
+        top: usize,
+        /// Normalize mixed line endings and strip trailing whitespace in place
+        #[arg(long)]
+        fix: bool,
+        /// Output format (text, json)
+        #[arg(long, default_value = "text")]
+        format: String,
+    },
+    /// Check regex and metric rules from a rules file, including regressions against a baseline
+    Lint {
+        /// Path to lint (default: current directory)
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// File extensions to include (e.g., rs,py,js)
+        #[arg(short, long, value_delimiter = ',')]
+        extensions: Option<Vec<String>>,
+        /// Exclude directories (e.g., target,node_modules)
+        #[arg(long, value_delimiter = ',')]
+        exclude: Option<Vec<String>>,
+        /// TOML file of [[rule]] entries (kinds: regex, metric, metric_regression)
+        #[arg(long, default_value = crate::lint::DEFAULT_RULES_FILE)]
+        rules: PathBuf,
+        /// Metrics baseline for metric_regression rules
+        #[arg(long)]
+        baseline: Option<PathBuf>,
+        /// Record the current metrics as a baseline in FILE instead of linting
+        #[arg(long, value_name = "FILE", conflicts_with = "baseline")]
+        write_baseline: Option<PathBuf>,
+        /// Output format (text, json, sarif)
+        #[arg(long, default_value = "text")]
+        format: String,
+        /// Write findings to a SARIF file instead of printing them
+        #[arg(long)]
+        export: Option<String>,
+        /// Exit with code 5 if a finding is at LEVEL or above (note, warning, error)
+        #[arg(long, value_name = "LEVEL")]
+        fail_on: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum BookmarkAction {
+    /// Bookmark a line
+    Add {
+        /// Location as file:line
+        location: String,
+        /// Label (e.g. reviewed, needs fix)
+        #[arg(short, long, default_value = "")]
+        label: String,
+    },
+    /// List bookmarks and check whether they still point at the same line
+    List,
+    /// Remove a bookmark by list number or file:line
+    Remove {
+        /// List number or file:line
+        target: String,
+    },
+    /// Remove all bookmarks
+    Clear,
+}
+
+#[derive(Subcommand)]
+pub enum CacheAction {
+    /// Delete every cached search
+    Clear,
+    /// Show how many searches and lines are cached
+    Stats {
+        /// Output format (text, json)
+        #[arg(long, default_value = "text")]
+        format: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ConfigAction {
+    /// Print the effective configuration: the config file merged over the defaults
+    Show {
+        /// Path whose config file to show, as commands on it would find it (default: current directory)
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// Output format (toml, json)
+        #[arg(long, default_value = "toml")]
+        format: String,
+    },
 }
 
 /// Default directories to exclude from search
@@ -424,6 +1134,7 @@ pub fn get_default_exclude_dirs() -> Vec<String> {
         "target".to_string(),
         "node_modules".to_string(),
         ".git".to_string(),
+        ".codesearch".to_string(),
         "build".to_string(),
         "dist".to_string(),
         "__pycache__".to_string(),
@@ -431,3 +1142,294 @@ pub fn get_default_exclude_dirs() -> Vec<String> {
         "vendor".to_string(),
     ]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::error::ErrorKind;
+    use clap::{CommandFactory, FromArgMatches};
+
+    /// Every subcommand as the words that invoke it, nested ones included
+    fn subcommand_paths(command: &clap::Command, prefix: &[String]) -> Vec<Vec<String>> {
+        command
+            .get_subcommands()
+            .flat_map(|sub| {
+                let mut path = prefix.to_vec();
+                path.push(sub.get_name().to_string());
+                let mut paths = subcommand_paths(sub, &path);
+                paths.insert(0, path);
+                paths
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_cli_definition_is_consistent() {
+        Cli::command().debug_assert();
+    }
+
+    // `main` matches `Commands` exhaustively, so every subcommand that parses is dispatched
+    #[test]
+    fn test_every_subcommand_parses_help() {
+        let paths = subcommand_paths(&Cli::command(), &[]);
+        assert!(paths.iter().any(|p| p == &["bookmarks", "add"]));
+        assert!(paths.iter().any(|p| p == &["graph-all"]));
+
+        for path in paths {
+            let args = std::iter::once("codesearch")
+                .chain(path.iter().map(String::as_str))
+                .chain(std::iter::once("--help"));
+            match Cli::try_parse_from(args) {
+                Err(e) => assert_eq!(e.kind(), ErrorKind::DisplayHelp, "{path:?}: {e}"),
+                Ok(_) => panic!("{path:?} --help parsed as a command"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_search_options_builder_matches_cli_defaults() {
+        let cli = Cli::try_parse_from(["codesearch", "search", "needle"]).unwrap();
+        let Some(Commands::Search {
+            ignore_case,
+            max_results,
+            max_total,
+            fuzzy,
+            fuzzy_threshold,
+            literal,
+            word,
+            multiline,
+            multiline_max_bytes,
+            rank,
+            cache,
+            semantic,
+            engine,
+            encoding,
+            benchmark,
+            vs_grep,
+            no_gitignore,
+            ..
+        }) = cli.command
+        else {
+            panic!("expected the search command");
+        };
+
+        let options = crate::types::SearchOptions::builder().build();
+        assert_eq!(options.ignore_case, ignore_case);
+        assert_eq!(options.max_results, max_results);
+        assert_eq!(options.max_total, max_total);
+        assert_eq!(options.fuzzy, fuzzy);
+        assert_eq!(options.fuzzy_threshold, fuzzy_threshold);
+        assert_eq!(options.literal, literal);
+        assert_eq!(options.word_boundary, word);
+        assert_eq!(options.multiline, multiline);
+        assert_eq!(options.multiline_max_bytes, multiline_max_bytes);
+        assert_eq!(options.rank, rank);
+        assert_eq!(options.cache, cache);
+        assert_eq!(options.semantic, semantic);
+        assert_eq!(options.engine, engine.parse().unwrap());
+        assert_eq!(options.encoding, encoding.parse().unwrap());
+        assert_eq!(options.benchmark, benchmark);
+        assert_eq!(options.vs_grep, vs_grep);
+        assert_eq!(options.gitignore, !no_gitignore);
+        assert!(options.context.is_empty());
+    }
+
+    /// The search options a profile can set, as `--profile` leaves them
+    #[derive(Debug, PartialEq)]
+    struct ProfiledSearch {
+        literal: bool,
+        max_results: usize,
+        format: &'static str,
+        multiline: bool,
+        auto_exclude: bool,
+        gitignore: bool,
+        rank: bool,
+        sort_by: Option<SortKey>,
+        fail_on_matches: bool,
+    }
+
+    const DEFAULT_SEARCH: ProfiledSearch = ProfiledSearch {
+        literal: false,
+        max_results: crate::types::DEFAULT_MAX_RESULTS,
+        format: "text",
+        multiline: false,
+        auto_exclude: true,
+        gitignore: true,
+        rank: false,
+        sort_by: None,
+        fail_on_matches: false,
+    };
+
+    fn profiled(args: &[&str], configured: &HashMap<String, Profile>) -> Cli {
+        let matches = Cli::command().try_get_matches_from(args).unwrap();
+        let mut cli = Cli::from_arg_matches(&matches).unwrap();
+        cli.apply_profile(&matches, configured).unwrap();
+        cli
+    }
+
+    fn profiled_search(args: &[&str]) -> ProfiledSearch {
+        let args: Vec<&str> = ["codesearch", "search"].iter().chain(args).copied().collect();
+        let Some(Commands::Search {
+            literal,
+            max_results,
+            format,
+            multiline,
+            no_auto_exclude,
+            no_gitignore,
+            rank,
+            sort_by,
+            fail_on_matches,
+            ..
+        }) = profiled(&args, &HashMap::new()).command
+        else {
+            panic!("expected the search command");
+        };
+        ProfiledSearch {
+            literal,
+            max_results,
+            format: format.leak(),
+            multiline,
+            auto_exclude: !no_auto_exclude,
+            gitignore: !no_gitignore,
+            rank,
+            sort_by,
+            fail_on_matches,
+        }
+    }
+
+    #[test]
+    fn test_builtin_profiles_fill_search_options() {
+        let cases: &[(&[&str], ProfiledSearch)] = &[
+            (&["needle"], DEFAULT_SEARCH),
+            (&["--profile", "quick", "needle"], ProfiledSearch { literal: true, max_results: 5, ..DEFAULT_SEARCH }),
+            (
+                &["--profile", "thorough", "needle"],
+                ProfiledSearch { max_results: 1000, multiline: true, auto_exclude: false, gitignore: false, rank: true, ..DEFAULT_SEARCH },
+            ),
+            (
+                &["--profile", "ci", "needle"],
+                ProfiledSearch { format: "json", sort_by: Some(SortKey::Path), fail_on_matches: true, ..DEFAULT_SEARCH },
+            ),
+            // A profile given after the subcommand works the same
+            (&["needle", "--profile", "quick"], ProfiledSearch { literal: true, max_results: 5, ..DEFAULT_SEARCH }),
+        ];
+        for (args, expected) in cases {
+            assert_eq!(&profiled_search(args), expected, "{args:?}");
+        }
+    }
+
+    #[test]
+    fn test_explicit_flags_override_profiles() {
+        let cases: &[(&[&str], ProfiledSearch)] = &[
+            (&["--profile", "quick", "--max-results", "9", "needle"], ProfiledSearch { literal: true, max_results: 9, ..DEFAULT_SEARCH }),
+            // The default value typed out still counts as explicit
+            (&["--profile", "ci", "--format", "text", "needle"], ProfiledSearch { sort_by: Some(SortKey::Path), fail_on_matches: true, ..DEFAULT_SEARCH }),
+            (
+                &["--profile", "ci", "--rank", "needle"],
+                ProfiledSearch { format: "json", rank: true, fail_on_matches: true, ..DEFAULT_SEARCH },
+            ),
+            // Literal first: regex queries and other matching modes stay as they are
+            (&["--profile", "quick", "fo+o"], ProfiledSearch { max_results: 5, ..DEFAULT_SEARCH }),
+            (&["--profile", "quick", "--word", "needle"], ProfiledSearch { max_results: 5, ..DEFAULT_SEARCH }),
+        ];
+        for (args, expected) in cases {
+            assert_eq!(&profiled_search(args), expected, "{args:?}");
+        }
+    }
+
+    #[test]
+    fn test_unknown_sort_key_fails_parsing() {
+        let err = Cli::command().try_get_matches_from(["codesearch", "search", "x", "--sort-by", "paht"]).unwrap_err();
+        assert_eq!(err.kind(), clap::error::ErrorKind::InvalidValue);
+        assert!(err.to_string().contains("possible values: path"), "{err}");
+        assert_eq!(profiled_search(&["--sort-by", "path", "needle"]).sort_by, Some(SortKey::Path));
+    }
+
+    #[test]
+    fn test_ci_profile_gates_deadcode() {
+        let cli = profiled(&["codesearch", "deadcode", "--profile", "ci"], &HashMap::new());
+        let Some(Commands::Deadcode { format, quiet, fail_on_deadcode, .. }) = cli.command else {
+            panic!("expected the deadcode command");
+        };
+        assert_eq!((format.as_str(), quiet, fail_on_deadcode), ("json", true, true));
+    }
+
+    #[test]
+    fn test_configured_profiles_are_applied() {
+        let config = crate::config::Config::parse("[profiles.myteam]\nmax_results = 20\nrank = true\n").unwrap();
+        let cli = profiled(&["codesearch", "--profile", "myteam", "search", "needle"], &config.profiles);
+        let Some(Commands::Search { max_results, rank, .. }) = cli.command else {
+            panic!("expected the search command");
+        };
+        assert_eq!((max_results, rank), (20, true));
+
+        let matches = Cli::command().try_get_matches_from(["codesearch", "search", "x", "--profile", "nope"]).unwrap();
+        let mut cli = Cli::from_arg_matches(&matches).unwrap();
+        assert!(cli.apply_profile(&matches, &config.profiles).is_err());
+    }
+
+    #[test]
+    fn test_config_fills_unset_analysis_options() {
+        let config = crate::config::Config::parse(
+            "[duplicates]\nmin_lines = 5\n\n[complexity]\nthreshold = 15\n\n[deadcode]\ninclude_public = true\n\n[analyze]\nexclude = [\"vendor\"]\n",
+        )
+        .unwrap();
+        let configured = |args: &[&str]| {
+            let matches = Cli::command().try_get_matches_from(args).unwrap();
+            let mut cli = Cli::from_arg_matches(&matches).unwrap();
+            cli.apply_config(&matches, &config);
+            cli.command
+        };
+
+        let Some(Commands::Duplicates { min_lines, similarity, exclude, .. }) = configured(&["codesearch", "duplicates"]) else {
+            panic!("expected the duplicates command");
+        };
+        assert_eq!((min_lines, similarity, exclude), (5, crate::duplicates::DEFAULT_SIMILARITY, Some(vec!["vendor".to_string()])));
+
+        // Flags on the command line win, even when they repeat the default
+        let Some(Commands::Duplicates { min_lines, exclude, .. }) = configured(&["codesearch", "duplicates", "--min-lines", "3", "--exclude", "gen"]) else {
+            panic!("expected the duplicates command");
+        };
+        assert_eq!((min_lines, exclude), (3, Some(vec!["gen".to_string()])));
+
+        let Some(Commands::Complexity { threshold, sort, .. }) = configured(&["codesearch", "complexity"]) else {
+            panic!("expected the complexity command");
+        };
+        assert_eq!((threshold, sort), (Some(15), false));
+
+        let Some(Commands::Deadcode { include_public, .. }) = configured(&["codesearch", "deadcode"]) else {
+            panic!("expected the deadcode command");
+        };
+        assert!(include_public);
+    }
+
+    #[test]
+    fn test_refactor_suggestion_type_flags() {
+        let cli = Cli::try_parse_from(["codesearch", "refactor", "--disable", "Long Line,magic-number", "--only", "Todo Comment"]).unwrap();
+        let Some(Commands::Refactor { disable, only, .. }) = cli.command else {
+            panic!("expected the refactor command");
+        };
+        assert_eq!(disable, vec![SuggestionType::LongLine, SuggestionType::MagicNumber]);
+        assert_eq!(only, vec![SuggestionType::TodoComment]);
+
+        let err = Cli::try_parse_from(["codesearch", "refactor", "--disable", "Long Lines"]).err().unwrap().to_string();
+        assert!(err.contains("expected one of: Long Line, Todo Comment"), "{err}");
+    }
+
+    #[test]
+    fn test_exit_codes_help_matches_constants() {
+        use crate::errors::exit_code;
+        for (code, flag) in [
+            (exit_code::MATCHES_FOUND, "--fail-on-matches"),
+            (exit_code::COMPLEXITY_EXCEEDED, "--max-complexity"),
+            (exit_code::DUPLICATES_EXCEEDED, "--max-duplicates"),
+            (exit_code::DEAD_CODE_FOUND, "--fail-on-deadcode"),
+            (exit_code::LINT_FAILED, "lint --fail-on:"),
+            (exit_code::REGRESSION, "--fail-on-regression"),
+        ] {
+            let line = EXIT_CODES_HELP.lines().find(|l| l.contains(flag)).unwrap();
+            assert_eq!(line.split_whitespace().next(), Some(code.to_string().as_str()), "{line}");
+        }
+        assert!(EXIT_CODES_HELP.contains(&format!("  {}  Error", exit_code::ERROR)));
+    }
+}
@@ -0,0 +1,128 @@
+//! Clipboard Support
+//!
+//! Formats search results for the interactive `copy` command and places the
+//! text on the system clipboard when the `clipboard` feature is enabled.
+
+use crate::types::SearchResult;
+
+/// What a `copy` command selects from the last results
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CopySelection {
+    /// `file:line` of the n-th result (1-based)
+    Location(usize),
+    /// Full line content of the n-th result (1-based)
+    Line(usize),
+    /// `file:line` of every result, one per line
+    All,
+}
+
+/// Parse the arguments after `copy`: `<n>`, `<n> line` or `all`
+pub fn parse_copy_args(args: &[&str]) -> Option<CopySelection> {
+    match args {
+        ["all"] => Some(CopySelection::All),
+        [n] => n.parse().ok().map(CopySelection::Location),
+        [n, "line"] => n.parse().ok().map(CopySelection::Line),
+        _ => None,
+    }
+}
+
+/// Text to copy for `selection`, or `None` when the result number is out of range
+pub fn format_copy_text(results: &[SearchResult], selection: CopySelection) -> Option<String> {
+    let nth = |n: usize| n.checked_sub(1).and_then(|i| results.get(i));
+    match selection {
+        CopySelection::Location(n) => nth(n).map(|r| format!("{}:{}", r.file, r.line_number)),
+        CopySelection::Line(n) => nth(n).map(|r| r.content.trim_end_matches(['\r', '\n']).to_string()),
+        CopySelection::All if results.is_empty() => None,
+        CopySelection::All => Some(
+            results
+                .iter()
+                .map(|r| format!("{}:{}", r.file, r.line_number))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        ),
+    }
+}
+
+/// Place `text` on the system clipboard
+///
+/// Fails when no clipboard is reachable (e.g. an SSH session without a display).
+#[cfg(feature = "clipboard")]
+pub fn copy_to_clipboard(text: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut clipboard = arboard::Clipboard::new()?;
+    clipboard.set_text(text.to_string())?;
+    Ok(())
+}
+
+/// Place `text` on the system clipboard
+///
+/// Always fails in builds without the `clipboard` feature.
+#[cfg(not(feature = "clipboard"))]
+pub fn copy_to_clipboard(_text: &str) -> Result<(), Box<dyn std::error::Error>> {
+    Err("clipboard support is not available in this build (rebuild with --features clipboard)".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(file: &str, line_number: usize, content: &str) -> SearchResult {
+        SearchResult {
+            file: file.to_string(),
+            line_number,
+            end_line_number: None,
+            content: content.to_string(),
+            matches: Vec::new(),
+            score: 0.0,
+            relevance: String::new(),
+            context_before: Vec::new(),
+            context_after: Vec::new(),
+            line_truncated: false,
+            content_offset: None,
+            annotations: Default::default(),
+        }
+    }
+
+    fn sample() -> Vec<SearchResult> {
+        vec![
+            result("src/main.rs", 3, "fn main() {\n"),
+            result("src/lib.rs", 12, "    pub fn run() {}"),
+        ]
+    }
+
+    #[test]
+    fn test_parse_copy_args() {
+        assert_eq!(parse_copy_args(&["2"]), Some(CopySelection::Location(2)));
+        assert_eq!(parse_copy_args(&["2", "line"]), Some(CopySelection::Line(2)));
+        assert_eq!(parse_copy_args(&["all"]), Some(CopySelection::All));
+        assert_eq!(parse_copy_args(&[]), None);
+        assert_eq!(parse_copy_args(&["x"]), None);
+        assert_eq!(parse_copy_args(&["1", "file"]), None);
+    }
+
+    #[test]
+    fn test_format_location() {
+        let text = format_copy_text(&sample(), CopySelection::Location(2));
+        assert_eq!(text.as_deref(), Some("src/lib.rs:12"));
+    }
+
+    #[test]
+    fn test_format_line_strips_trailing_newline_only() {
+        let results = sample();
+        assert_eq!(format_copy_text(&results, CopySelection::Line(1)).as_deref(), Some("fn main() {"));
+        assert_eq!(format_copy_text(&results, CopySelection::Line(2)).as_deref(), Some("    pub fn run() {}"));
+    }
+
+    #[test]
+    fn test_format_all() {
+        let text = format_copy_text(&sample(), CopySelection::All);
+        assert_eq!(text.as_deref(), Some("src/main.rs:3\nsrc/lib.rs:12"));
+    }
+
+    #[test]
+    fn test_format_out_of_range() {
+        let results = sample();
+        assert_eq!(format_copy_text(&results, CopySelection::Location(0)), None);
+        assert_eq!(format_copy_text(&results, CopySelection::Line(3)), None);
+        assert_eq!(format_copy_text(&[], CopySelection::All), None);
+    }
+}
@@ -2,8 +2,9 @@
 //!
 //! Implements various complexity metrics including Cyclomatic, Halstead, Essential, and NPath.
 
+use crate::parser::strip_comments_and_strings_for;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ComplexityMetrics {
@@ -26,6 +27,12 @@ pub struct HalsteadMetrics {
     pub effort: f64,
     pub time: f64,
     pub bugs: f64,
+    /// How often each operator occurred, for checking what was counted
+    #[serde(skip)]
+    pub operators: BTreeMap<String, usize>,
+    /// How often each operand occurred
+    #[serde(skip)]
+    pub operands: BTreeMap<String, usize>,
 }
 
 impl HalsteadMetrics {
@@ -65,10 +72,49 @@ impl HalsteadMetrics {
             effort,
             time,
             bugs,
+            operators: operators.iter().map(|(k, v)| (k.clone(), *v)).collect(),
+            operands: operands.iter().map(|(k, v)| (k.clone(), *v)).collect(),
+        }
+    }
+
+    /// Combine with the metrics of another part of the same file
+    ///
+    /// Totals add up. Vocabularies of different languages can't be merged
+    /// without the operators themselves, so the larger distinct counts are kept.
+    pub fn merge(&mut self, other: &HalsteadMetrics) {
+        self.n1 = self.n1.max(other.n1);
+        self.n2 = self.n2.max(other.n2);
+        self.N1 += other.N1;
+        self.N2 += other.N2;
+        self.vocabulary = self.n1 + self.n2;
+        self.length = self.N1 + self.N2;
+        self.volume += other.volume;
+        self.effort += other.effort;
+        self.time += other.time;
+        self.bugs += other.bugs;
+        self.difficulty = if self.volume > 0.0 { self.effort / self.volume } else { 0.0 };
+        for (operator, count) in &other.operators {
+            *self.operators.entry(operator.clone()).or_insert(0) += count;
+        }
+        for (operand, count) in &other.operands {
+            *self.operands.entry(operand.clone()).or_insert(0) += count;
         }
     }
 }
 
+impl ComplexityMetrics {
+    /// Add the metrics of an embedded region measured separately
+    ///
+    /// Decision points add up (each part brings its own base path of 1, counted
+    /// once), and paths through consecutive parts multiply.
+    pub fn merge_embedded(&mut self, embedded: &ComplexityMetrics) {
+        self.cyclomatic_complexity += embedded.cyclomatic_complexity.saturating_sub(1);
+        self.essential_complexity += embedded.essential_complexity.saturating_sub(1);
+        self.npath_complexity = self.npath_complexity.saturating_mul(embedded.npath_complexity);
+        self.halstead.merge(&embedded.halstead);
+    }
+}
+
 pub fn calculate_complexity_metrics(content: &str, ext: &str) -> ComplexityMetrics {
     let cyclomatic = calculate_cyclomatic_complexity(content, ext);
     let halstead = calculate_halstead_metrics(content, ext);
@@ -105,51 +151,133 @@ pub fn calculate_cyclomatic_complexity(content: &str, ext: &str) -> usize {
     complexity
 }
 
+/// Operators shared by the C family of languages, longest first where one is a prefix of another
+const C_OPERATORS: &[&str] = &[
+    "<<=", ">>=", "==", "!=", "<=", ">=", "&&", "||", "<<", ">>", "+=", "-=", "*=", "/=", "%=", "&=", "|=", "^=",
+    "->", "++", "--", "+", "-", "*", "/", "%", "=", "<", ">", "!", "&", "|", "^", "~", "?", ":", ".",
+];
+
+const RUST_OPERATORS: &[&str] = &[
+    "..=", "<<=", ">>=", "::", "=>", "->", "..", "==", "!=", "<=", ">=", "&&", "||", "<<", ">>", "+=", "-=", "*=",
+    "/=", "%=", "&=", "|=", "^=", "+", "-", "*", "/", "%", "=", "<", ">", "!", "&", "|", "^", "?", ":", ".", "@",
+];
+
+const PYTHON_OPERATORS: &[&str] = &[
+    "**=", "//=", ">>=", "<<=", "**", "//", "==", "!=", "<=", ">=", ":=", "->", "+=", "-=", "*=", "/=", "%=", "&=",
+    "|=", "^=", "@=", "<<", ">>", "+", "-", "*", "/", "%", "=", "<", ">", "&", "|", "^", "~", "@", ":", ".",
+];
+
+/// JavaScript and TypeScript operators on top of [`C_OPERATORS`]
+const SCRIPT_OPERATORS: &[&str] = &[">>>=", "===", "!==", "**=", "??=", "&&=", "||=", ">>>", "...", "=>", "?.", "??", "**"];
+
+/// Go operators on top of [`C_OPERATORS`]
+const GO_OPERATORS: &[&str] = &["&^=", "...", ":=", "<-", "&^"];
+
+/// Kotlin operators on top of [`C_OPERATORS`]
+const KOTLIN_OPERATORS: &[&str] = &["::", "?.", "?:", "!!", "..", "=>"];
+
+/// C++ and Java operators on top of [`C_OPERATORS`]
+const SCOPE_OPERATORS: &[&str] = &["::"];
+
+const FALLBACK_OPERATORS: &[&str] = &["+", "-", "*", "/", "="];
+
+/// Operator tables of the language with extension `ext`, searched together for the longest match
+fn operator_tables(ext: &str) -> [&'static [&'static str]; 2] {
+    match ext {
+        "rs" => [RUST_OPERATORS, &[]],
+        "py" | "pyw" | "pyi" => [PYTHON_OPERATORS, &[]],
+        "js" | "mjs" | "cjs" | "jsx" | "ts" | "tsx" | "mts" | "cts" => [SCRIPT_OPERATORS, C_OPERATORS],
+        "go" => [GO_OPERATORS, C_OPERATORS],
+        "kt" | "kts" => [KOTLIN_OPERATORS, C_OPERATORS],
+        "cpp" | "cc" | "cxx" | "hpp" | "hh" | "hxx" | "java" => [SCOPE_OPERATORS, C_OPERATORS],
+        "c" | "h" | "cs" | "swift" | "dart" | "scala" | "groovy" | "php" => [C_OPERATORS, &[]],
+        _ => [FALLBACK_OPERATORS, &[]],
+    }
+}
+
+/// Keywords, which count as operators rather than operands
+fn keywords(ext: &str) -> &'static [&'static str] {
+    match ext {
+        "rs" => &[
+            "as", "break", "const", "continue", "else", "enum", "fn", "for", "if", "impl", "in", "let", "loop", "match",
+            "mod", "move", "mut", "pub", "ref", "return", "static", "struct", "trait", "type", "unsafe", "use", "where",
+            "while", "async", "await", "dyn",
+        ],
+        "py" | "pyw" | "pyi" => &[
+            "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del", "elif", "else",
+            "except", "finally", "for", "from", "global", "if", "import", "in", "is", "lambda", "nonlocal", "not", "or",
+            "pass", "raise", "return", "try", "while", "with", "yield",
+        ],
+        "java" | "kt" | "kts" => &[
+            "class", "interface", "public", "private", "protected", "static", "void", "if", "else", "for", "while",
+            "return", "import", "new", "try", "catch", "throw", "switch", "case", "break", "continue", "fun", "val",
+            "var", "when",
+        ],
+        "js" | "mjs" | "cjs" | "jsx" | "ts" | "tsx" | "mts" | "cts" => &[
+            "function", "const", "let", "var", "if", "else", "for", "while", "return", "class", "import", "export",
+            "new", "try", "catch", "throw", "switch", "case", "break", "continue", "typeof", "instanceof", "async",
+            "await", "of", "in",
+        ],
+        "go" => &[
+            "func", "var", "const", "type", "struct", "interface", "if", "else", "for", "range", "return", "switch",
+            "case", "break", "continue", "go", "defer", "select", "package", "import", "map", "chan",
+        ],
+        _ => &[],
+    }
+}
+
+/// Count the operators and operands of `content`
+///
+/// Comments are dropped and string literals reduced to their quotes first
+/// (see [`strip_comments_and_strings_for`]), so prose doesn't count as code;
+/// each string literal is then one operand. Operators are matched longest
+/// first from the language's table, and keywords count as operators.
 fn calculate_halstead_metrics(content: &str, ext: &str) -> HalsteadMetrics {
     let mut operators: HashMap<String, usize> = HashMap::new();
     let mut operands: HashMap<String, usize> = HashMap::new();
-    
-    let operator_patterns = match ext {
-        "rs" | "c" | "cpp" | "java" | "js" | "ts" | "go" | "kt" => {
-            vec![
-                r"\+", r"-", r"\*", r"/", r"%", r"=", r"==", r"!=", r"<", r">", 
-                r"<=", r">=", r"&&", r"\|\|", r"!", r"&", r"\|", r"\^", r"<<", r">>",
-                r"\+=", r"-=", r"\*=", r"/=", r"\?", r":", r"\.", r"->", r"::",
-            ]
-        }
-        "py" => {
-            vec![
-                r"\+", r"-", r"\*", r"/", r"%", r"=", r"==", r"!=", r"<", r">",
-                r"<=", r">=", r"\band\b", r"\bor\b", r"\bnot\b", r"\bin\b", r"\bis\b",
-            ]
+
+    let code = strip_comments_and_strings_for(content, ext);
+    let tables = operator_tables(ext);
+    let keywords = keywords(ext);
+    // Rust's `'` starts lifetimes and char literals, which the stripping pass leaves alone
+    let single_quote_strings = ext != "rs";
+
+    let mut chars = code.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if c.is_whitespace() {
+            continue;
         }
-        _ => vec![r"\+", r"-", r"\*", r"/", r"="],
-    };
-    
-    for pattern in operator_patterns {
-        if let Ok(re) = regex::Regex::new(pattern) {
-            let count = re.find_iter(content).count();
-            if count > 0 {
-                *operators.entry(pattern.to_string()).or_insert(0) += count;
-            }
+        if c == '"' || c == '`' || (c == '\'' && single_quote_strings) {
+            // The contents are blanked, so the next matching quote closes the literal
+            let end = code[i + 1..].find(c).map_or(code.len(), |n| i + 1 + n + 1);
+            *operands.entry(content[i..end].to_string()).or_insert(0) += 1;
+            while chars.next_if(|&(j, _)| j < end).is_some() {}
+            continue;
         }
-    }
-    
-    if let Ok(identifier_re) = regex::Regex::new(r"\b[a-zA-Z_][a-zA-Z0-9_]*\b") {
-        for cap in identifier_re.find_iter(content) {
-            let operand = cap.as_str().to_string();
-            if !is_keyword(&operand, ext) {
-                *operands.entry(operand).or_insert(0) += 1;
+        if c.is_alphanumeric() || c == '_' {
+            let mut end = i + c.len_utf8();
+            while let Some(&(j, next)) = chars.peek() {
+                // Decimal points belong to numbers: `1.5`, but not `x.len`
+                let decimal = next == '.' && c.is_ascii_digit() && code[j + 1..].starts_with(|d: char| d.is_ascii_digit());
+                if !(next.is_alphanumeric() || next == '_' || decimal) {
+                    break;
+                }
+                end = j + next.len_utf8();
+                chars.next();
             }
+            let token = &code[i..end];
+            let counts = if keywords.contains(&token) { &mut operators } else { &mut operands };
+            *counts.entry(token.to_string()).or_insert(0) += 1;
+            continue;
         }
-    }
-    
-    if let Ok(number_re) = regex::Regex::new(r"\b\d+\.?\d*\b") {
-        for cap in number_re.find_iter(content) {
-            *operands.entry(cap.as_str().to_string()).or_insert(0) += 1;
+        let rest = &code[i..];
+        let operator = tables.iter().flat_map(|table| table.iter()).filter(|op| rest.starts_with(*op)).max_by_key(|op| op.len());
+        if let Some(operator) = operator {
+            *operators.entry(operator.to_string()).or_insert(0) += 1;
+            while chars.next_if(|&(j, _)| j < i + operator.len()).is_some() {}
         }
     }
-    
+
     HalsteadMetrics::calculate(&operators, &operands)
 }
 
@@ -188,15 +316,3 @@ fn calculate_npath_complexity(content: &str, ext: &str) -> u64 {
     
     2u64.saturating_pow(decision_count.min(30) as u32)
 }
-
-fn is_keyword(word: &str, ext: &str) -> bool {
-    let keywords = match ext {
-        "rs" => vec!["fn", "let", "mut", "if", "else", "for", "while", "loop", "match", "return", "struct", "enum", "impl", "trait", "pub", "use", "mod"],
-        "py" => vec!["def", "class", "if", "else", "elif", "for", "while", "return", "import", "from", "as", "with", "try", "except"],
-        "java" | "kt" => vec!["class", "interface", "public", "private", "protected", "static", "void", "if", "else", "for", "while", "return", "import"],
-        "js" | "ts" => vec!["function", "const", "let", "var", "if", "else", "for", "while", "return", "class", "import", "export"],
-        _ => vec![],
-    };
-    
-    keywords.contains(&word)
-}
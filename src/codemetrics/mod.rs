@@ -7,6 +7,10 @@ pub mod size;
 pub mod maintainability;
 pub mod helpers;
 
+use crate::encoding::{read_text, Encoding};
+use crate::language::{blank_regions, extract_embedded_regions, get_language_by_extension, language_extension, EmbeddedRegion};
+use crate::options::has_extension;
+use crate::timings::{Phase, Timings};
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 
@@ -24,6 +28,18 @@ pub struct FileMetrics {
     pub complexity: ComplexityMetrics,
     pub size: SizeMetrics,
     pub maintainability: MaintainabilityMetrics,
+    /// Metrics of code embedded in another language (e.g. `<script>` in HTML),
+    /// one entry per language; already included in the headline numbers
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sub_languages: Vec<SubLanguageMetrics>,
+}
+
+/// Metrics of the code of one embedded language within a file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubLanguageMetrics {
+    pub language: String,
+    pub size: SizeMetrics,
+    pub complexity: ComplexityMetrics,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -50,69 +66,110 @@ pub struct TotalMetrics {
 // ============================================================================
 
 pub fn analyze_file_metrics(path: &Path) -> Result<FileMetrics, Box<dyn std::error::Error>> {
-    let content = std::fs::read_to_string(path)?;
-    let ext = path.extension().and_then(|s| s.to_str()).unwrap_or("");
-    
-    let size = size::calculate_size_metrics(&content, ext);
-    let complexity = complexity::calculate_complexity_metrics(&content, ext);
-    let maintainability = maintainability::calculate_maintainability_metrics(&complexity, &size, &content, ext);
+    let content = read_text(path, Encoding::Auto)?;
+    Ok(file_metrics_from_content(path, &content))
+}
+
+/// Compute the metrics of a file whose content is already in memory
+pub fn file_metrics_from_content(path: &Path, content: &str) -> FileMetrics {
+    let ext = language_extension(path.extension().and_then(|s| s.to_str()).unwrap_or(""));
+    let regions = extract_embedded_regions(&path.to_string_lossy(), content);
+
+    // The host language is measured without its embedded regions, which are
+    // measured in their own language and then added in
+    let host = if regions.is_empty() { content.to_string() } else { blank_regions(content, &regions) };
+    let mut size = size::calculate_size_metrics(&host, ext);
+    let mut complexity = complexity::calculate_complexity_metrics(&host, ext);
+    let sub_languages = sub_language_metrics(&regions);
+    for sub in &sub_languages {
+        size.merge_embedded(&sub.size);
+        complexity.merge_embedded(&sub.complexity);
+    }
+    let maintainability = maintainability::calculate_maintainability_metrics(&complexity, &size, content, ext);
     
-    Ok(FileMetrics {
+    FileMetrics {
         file_path: path.to_string_lossy().to_string(),
         complexity,
         size,
         maintainability,
-    })
+        sub_languages,
+    }
+}
+
+/// Measure embedded regions, merging the regions of each language in file order
+fn sub_language_metrics(regions: &[EmbeddedRegion]) -> Vec<SubLanguageMetrics> {
+    let mut by_extension: Vec<(&str, String)> = Vec::new();
+    for region in regions {
+        match by_extension.iter_mut().find(|(ext, _)| *ext == region.extension) {
+            Some((_, content)) => content.push_str(&region.content),
+            None => by_extension.push((region.extension.as_str(), region.content.clone())),
+        }
+    }
+
+    by_extension
+        .into_iter()
+        .map(|(ext, content)| SubLanguageMetrics {
+            language: get_language_by_extension(ext).map_or_else(|| ext.to_string(), |lang| lang.name.to_string()),
+            size: size::calculate_size_metrics(&content, ext),
+            complexity: complexity::calculate_complexity_metrics(&content, ext),
+        })
+        .collect()
 }
 
 pub fn analyze_project_metrics(
     path: &Path,
     extensions: Option<&[String]>,
     exclude: Option<&[String]>,
+) -> Result<ProjectMetrics, Box<dyn std::error::Error>> {
+    analyze_project_metrics_timed(path, extensions, exclude, &Timings::disabled())
+}
+
+/// Like [`analyze_project_metrics`], recording the walk, read, extract and aggregate phases
+pub fn analyze_project_metrics_timed(
+    path: &Path,
+    extensions: Option<&[String]>,
+    exclude: Option<&[String]>,
+    timings: &Timings,
 ) -> Result<ProjectMetrics, Box<dyn std::error::Error>> {
     use walkdir::WalkDir;
     
-    let walker = WalkDir::new(path)
-        .into_iter()
-        .filter_entry(|e| {
-            if let Some(name) = e.file_name().to_str() {
-                if let Some(exclude_dirs) = exclude {
-                    for exclude_dir in exclude_dirs {
-                        if name == exclude_dir {
-                            return false;
+    let files: Vec<_> = timings.time(Phase::Walk, || {
+        WalkDir::new(path)
+            .into_iter()
+            .filter_entry(|e| {
+                if let Some(name) = e.file_name().to_str() {
+                    if let Some(exclude_dirs) = exclude {
+                        for exclude_dir in exclude_dirs {
+                            if name == exclude_dir {
+                                return false;
+                            }
                         }
                     }
                 }
-            }
-            true
-        })
-        .filter_map(|e| e.ok())
-        .filter(|e| e.file_type().is_file());
-
-    let files: Vec<_> = walker
-        .filter(|entry| {
-            let file_path = entry.path();
-            if let Some(exts) = extensions {
-                if let Some(ext) = file_path.extension().and_then(|s| s.to_str()) {
-                    exts.iter().any(|e| e == ext)
-                } else {
-                    false
-                }
-            } else {
                 true
-            }
-        })
-        .collect();
+            })
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .filter(|entry| {
+                let file_path = entry.path();
+                match extensions {
+                    Some(exts) => has_extension(file_path, exts),
+                    None => true,
+                }
+            })
+            .collect()
+    });
 
     let mut file_metrics = Vec::new();
     
     for entry in files {
-        if let Ok(metrics) = analyze_file_metrics(entry.path()) {
-            file_metrics.push(metrics);
-        }
+        let Ok(content) = timings.time(Phase::Read, || read_text(entry.path(), Encoding::Auto)) else {
+            continue;
+        };
+        file_metrics.push(timings.time(Phase::Extract, || file_metrics_from_content(entry.path(), &content)));
     }
 
-    let totals = calculate_totals(&file_metrics);
+    let totals = timings.time(Phase::Aggregate, || calculate_totals(&file_metrics));
 
     Ok(ProjectMetrics {
         files: file_metrics,
@@ -227,6 +284,16 @@ pub fn print_metrics_report(metrics: &ProjectMetrics, detailed: bool) {
             println!("    DIT: {}", file.maintainability.depth_of_inheritance);
             println!("    CBO: {}", file.maintainability.coupling_between_objects);
             println!("    LCOM: {:.2}", file.maintainability.lack_of_cohesion);
+
+            if !file.sub_languages.is_empty() {
+                println!("  Embedded Languages:");
+                for sub in &file.sub_languages {
+                    println!(
+                        "    {}: {} lines, {} functions, cyclomatic {}",
+                        sub.language, sub.size.total_lines, sub.size.num_functions, sub.complexity.cyclomatic_complexity
+                    );
+                }
+            }
         }
     }
 }
@@ -253,6 +320,52 @@ mod tests {
         assert!(halstead.volume > 0.0);
     }
 
+    #[test]
+    fn test_halstead_ignores_comments() {
+        let code = "fn area(w: f64, h: f64) -> f64 {\n    let a = w * h;\n    a\n}\n";
+        let notes: String = (0..50).map(|i| format!("// note {i}: if x == y then z = x + y\n")).collect();
+        let block = format!("/*\n{}*/\n", "fn fake(a: i32) -> i32 { a ? b :: c }\n".repeat(3));
+        let commented = format!("{notes}{block}{code}");
+        let plain = complexity::calculate_complexity_metrics(code, "rs").halstead;
+        let with_comments = complexity::calculate_complexity_metrics(&commented, "rs").halstead;
+        assert_eq!(with_comments.volume, plain.volume);
+        assert_eq!(with_comments.operators, plain.operators);
+
+        let code = "def area(w, h):\n    return w ** h // 2\n";
+        let notes: String = (0..50).map(|i| format!("# note {i}: x = y + z\n")).collect();
+        let plain = complexity::calculate_complexity_metrics(code, "py").halstead;
+        let with_comments = complexity::calculate_complexity_metrics(&format!("{notes}{code}"), "py").halstead;
+        assert_eq!(with_comments.volume, plain.volume);
+    }
+
+    #[test]
+    fn test_halstead_counts_string_literals_as_single_operands() {
+        let halstead = complexity::calculate_complexity_metrics("let s = \"a + b == c\";", "rs").halstead;
+        assert_eq!(halstead.operands.get("\"a + b == c\""), Some(&1));
+        assert!(!halstead.operators.contains_key("+"));
+        assert!(!halstead.operators.contains_key("=="));
+        assert_eq!(halstead.operators.get("="), Some(&1));
+    }
+
+    #[test]
+    fn test_halstead_uses_language_operators() {
+        let rust = "fn f() -> Result<u8, E> { let v = std::env::var(\"X\")?; match v { a => a == b } }";
+        let operators = complexity::calculate_complexity_metrics(rust, "rs").halstead.operators;
+        assert_eq!(operators.get("::"), Some(&2));
+        assert_eq!(operators.get("?"), Some(&1));
+        assert_eq!(operators.get("=>"), Some(&1));
+        assert_eq!(operators.get("=="), Some(&1));
+        assert_eq!(operators.get("="), Some(&1));
+        assert!(!operators.contains_key(":"));
+
+        let python = "x = a ** 2 // b\ny = a * b / c\n";
+        let operators = complexity::calculate_complexity_metrics(python, "py").halstead.operators;
+        assert_eq!(operators.get("**"), Some(&1));
+        assert_eq!(operators.get("//"), Some(&1));
+        assert_eq!(operators.get("*"), Some(&1));
+        assert_eq!(operators.get("/"), Some(&1));
+    }
+
     #[test]
     fn test_size_metrics() {
         let content = "fn main() {\n    let x = 5;\n    // comment\n\n    println!(\"test\");\n}";
@@ -276,4 +389,45 @@ mod tests {
         let mi = MaintainabilityMetrics::calculate_maintainability_index(100.0, 5, 50);
         assert!(mi >= 0.0 && mi <= 100.0);
     }
+
+    #[test]
+    fn test_embedded_script_counted_under_javascript() {
+        let metrics = analyze_file_metrics(Path::new("tests/fixtures/polyglot/page.html")).unwrap();
+
+        let languages: Vec<&str> = metrics.sub_languages.iter().map(|s| s.language.as_str()).collect();
+        assert_eq!(languages, vec!["CSS", "JavaScript"]);
+        let js = &metrics.sub_languages[1];
+        assert_eq!(js.size.num_functions, 2);
+        assert_eq!(js.size.total_lines, 14);
+        assert_eq!(js.size.comment_lines, 1);
+
+        // Headline numbers include the embedded code, and every line is counted once
+        assert_eq!(metrics.size.num_functions, 2);
+        assert_eq!(metrics.size.total_lines, 28);
+        assert_eq!(metrics.size.source_lines + metrics.size.comment_lines + metrics.size.blank_lines, 28);
+        assert_eq!(metrics.complexity.cyclomatic_complexity, js.complexity.cyclomatic_complexity);
+    }
+
+    #[test]
+    fn test_phase_timings_sum_to_total() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let body = "fn f(x: i32) -> i32 {\n    if x > 0 { x * 2 } else { -x }\n}\n".repeat(200);
+        for i in 0..100 {
+            std::fs::write(dir.path().join(format!("m{i}.rs")), &body).unwrap();
+        }
+
+        let timings = Timings::enabled();
+        let start = std::time::Instant::now();
+        let metrics = analyze_project_metrics_timed(dir.path(), None, None, &timings).unwrap();
+        let wall = start.elapsed();
+
+        assert_eq!(metrics.files.len(), 100);
+        for phase in [Phase::Walk, Phase::Read, Phase::Extract, Phase::Aggregate] {
+            assert!(timings.get(phase) > std::time::Duration::ZERO, "{} not recorded", phase.name());
+        }
+        // The phases run one after another, so they cover most of the run and never exceed it
+        let total = timings.total();
+        assert!(total <= wall, "{total:?} > {wall:?}");
+        assert!(total.as_secs_f64() >= wall.as_secs_f64() * 0.5, "{total:?} vs {wall:?}");
+    }
 }
@@ -3,6 +3,7 @@
 //! Implements size-related metrics including LOC, SLOC, LLOC, and code density.
 
 use serde::{Deserialize, Serialize};
+use crate::lines::split_lines;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SizeMetrics {
@@ -34,6 +35,21 @@ impl SizeMetrics {
         }
     }
 
+    /// Add the metrics of an embedded region measured separately
+    ///
+    /// The host was measured with the region's lines blanked, so they move from
+    /// its blank lines to whatever the embedded language made of them.
+    pub fn merge_embedded(&mut self, embedded: &SizeMetrics) {
+        self.blank_lines = self.blank_lines.saturating_sub(embedded.total_lines) + embedded.blank_lines;
+        self.source_lines += embedded.source_lines;
+        self.logical_lines += embedded.logical_lines;
+        self.comment_lines += embedded.comment_lines;
+        self.num_classes += embedded.num_classes;
+        self.num_methods += embedded.num_methods;
+        self.num_functions += embedded.num_functions;
+        self.calculate_ratios();
+    }
+
     pub fn calculate_ratios(&mut self) {
         if self.total_lines > 0 {
             self.code_density = self.source_lines as f64 / self.total_lines as f64;
@@ -47,7 +63,7 @@ impl SizeMetrics {
 pub fn calculate_size_metrics(content: &str, ext: &str) -> SizeMetrics {
     let mut metrics = SizeMetrics::new();
     
-    let lines: Vec<&str> = content.lines().collect();
+    let lines: Vec<&str> = split_lines(content).collect();
     metrics.total_lines = lines.len();
     
     let is_comment_line = |line: &str, ext: &str| -> bool {
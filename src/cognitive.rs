@@ -0,0 +1,495 @@
+//! Cognitive Complexity
+//!
+//! Scores code by the Cognitive Complexity spec (G. Ann Campbell, SonarSource):
+//! every break in the linear flow (`if`, loops, `catch`, `switch`/`match`, a
+//! ternary, a labeled jump) adds 1, plus its nesting level when it sits inside
+//! other structures or a nested function. `else`, `else if` and `elif` add 1
+//! without the nesting part, a run of the same boolean operator adds 1 however
+//! long it is, and each function that calls itself adds 1.
+//!
+//! Rust, Python and JavaScript/TypeScript get their own rules (`loop` and
+//! closures, indentation, arrow functions and `??`); anything else is read as
+//! a C-like brace language.
+
+use crate::language::language_extension;
+use crate::lines::{line_starts, split_lines};
+use crate::parser::{extract_functions, find_block, mask_literals};
+use regex::Regex;
+
+/// Cognitive complexity of `content`, written in the language of extension `ext`
+///
+/// String literals and comments are ignored. An `ext` mapped in
+/// `[language.overrides]` is read as its language.
+pub fn cognitive_complexity(content: &str, ext: &str) -> u32 {
+    let ext = language_extension(ext);
+    let file_path = format!("code.{ext}");
+    let masked = mask_literals(content, &file_path);
+    let flow = match Syntax::of(ext) {
+        Syntax::Python => indented_complexity(&masked),
+        syntax => braced_complexity(content, &masked, syntax),
+    };
+    flow + recursive_functions(&masked, &file_path)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Syntax {
+    Rust,
+    Python,
+    Script,
+    CLike,
+}
+
+impl Syntax {
+    fn of(ext: &str) -> Self {
+        match ext {
+            "rs" => Syntax::Rust,
+            "py" | "pyw" | "pyi" => Syntax::Python,
+            "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "mts" | "cts" => Syntax::Script,
+            _ => Syntax::CLike,
+        }
+    }
+}
+
+/// What a block is the body of
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Frame {
+    /// Anything that doesn't nest: a type, an `impl`, a `try`, a plain block
+    Plain,
+    /// A branch or loop
+    Structure,
+    /// A `do` loop, whose `while` comes after the body
+    Do,
+    /// A function or closure
+    Function,
+}
+
+/// Nesting level inside `frames`; the outermost function doesn't count
+fn nesting_level(frames: impl Iterator<Item = Frame>) -> u32 {
+    let (mut structures, mut functions) = (0, 0u32);
+    for frame in frames {
+        match frame {
+            Frame::Structure | Frame::Do => structures += 1,
+            Frame::Function => functions += 1,
+            Frame::Plain => {}
+        }
+    }
+    structures + functions.saturating_sub(1)
+}
+
+fn is_word_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_' || b == b'$' || b >= 0x80
+}
+
+/// First byte after `from` that isn't a space or tab, on the same line
+fn next_on_line(bytes: &[u8], from: usize) -> Option<u8> {
+    bytes[from..].iter().copied().find(|b| *b != b' ' && *b != b'\t').filter(|b| *b != b'\n' && *b != b'\r')
+}
+
+/// Set the frame the next `{` at parenthesis depth `depth` opens
+///
+/// A frame already pending further out wins: `fn(i32)` in a signature
+/// doesn't replace the function the signature belongs to.
+fn expect_block(pending: &mut Option<(Frame, usize)>, frame: Frame, depth: usize) {
+    if pending.is_none_or(|(_, at)| at >= depth) {
+        *pending = Some((frame, depth));
+    }
+}
+
+/// Complexity of brace-delimited code; `content` is the original of `masked`
+fn braced_complexity(content: &str, masked: &str, syntax: Syntax) -> u32 {
+    let original = content.as_bytes();
+    let bytes = masked.as_bytes();
+    let mut total = 0;
+    let mut frames: Vec<Frame> = Vec::new();
+    let mut depth = 0usize;
+    let mut pending: Option<(Frame, usize)> = None;
+    let mut last_operator: Option<&[u8]> = None;
+    // Previous token: its last byte, and the word if it was one
+    let mut prev = 0u8;
+    let mut prev_word = "";
+    let mut after_do = false;
+    let mut statement_word: Option<&str> = None;
+
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        if b.is_ascii_whitespace() {
+            if !original[i].is_ascii_whitespace() {
+                // A masked literal is an operand
+                prev = b'"';
+                prev_word = "";
+            }
+            i += 1;
+            continue;
+        }
+
+        if is_word_byte(b) {
+            let start = i;
+            while i < bytes.len() && is_word_byte(bytes[i]) {
+                i += 1;
+            }
+            let word = &masked[start..i];
+            let nesting = nesting_level(frames.iter().copied());
+            let statement = *statement_word.get_or_insert(word);
+            let was_do = std::mem::take(&mut after_do);
+            match word {
+                "if" => {
+                    if prev_word != "else" {
+                        total += 1 + nesting;
+                    }
+                    expect_block(&mut pending, Frame::Structure, depth);
+                }
+                "else" => {
+                    total += 1;
+                    expect_block(&mut pending, Frame::Structure, depth);
+                }
+                "while" if was_do => {}
+                "for" if syntax == Syntax::Rust && (statement == "impl" || next_on_line(bytes, i) == Some(b'<')) => {}
+                "for" | "while" | "switch" | "catch" => {
+                    total += 1 + nesting;
+                    expect_block(&mut pending, Frame::Structure, depth);
+                }
+                "match" | "loop" if syntax == Syntax::Rust => {
+                    total += 1 + nesting;
+                    expect_block(&mut pending, Frame::Structure, depth);
+                }
+                "do" if syntax != Syntax::Rust => {
+                    total += 1 + nesting;
+                    expect_block(&mut pending, Frame::Do, depth);
+                }
+                "goto" => total += 1,
+                "break" | "continue" => {
+                    let labeled = match syntax {
+                        Syntax::Rust => next_on_line(original, i) == Some(b'\''),
+                        _ => next_on_line(bytes, i).is_some_and(is_word_byte),
+                    };
+                    if labeled {
+                        total += 1;
+                    }
+                }
+                "fn" if syntax == Syntax::Rust => expect_block(&mut pending, Frame::Function, depth),
+                "function" | "func" | "fun" if syntax != Syntax::Rust => {
+                    expect_block(&mut pending, Frame::Function, depth)
+                }
+                _ => {}
+            }
+            prev = bytes[i - 1];
+            prev_word = word;
+            continue;
+        }
+
+        let next = bytes.get(i + 1).copied();
+        let operand_before = is_word_byte(prev) || matches!(prev, b')' | b']' | b'"' | b'?');
+        let mut len = 1;
+        match b {
+            b'(' | b'[' => depth += 1,
+            b')' | b']' => {
+                depth = depth.saturating_sub(1);
+                if pending.is_some_and(|(_, at)| at > depth) {
+                    pending = None;
+                }
+            }
+            b'{' => {
+                let frame = if syntax == Syntax::Rust && prev == b'|' {
+                    Frame::Function
+                } else {
+                    match pending {
+                        Some((frame, at)) if at == depth => {
+                            pending = None;
+                            frame
+                        }
+                        _ => Frame::Plain,
+                    }
+                };
+                frames.push(frame);
+                last_operator = None;
+                statement_word = None;
+            }
+            b'}' => {
+                after_do = frames.pop() == Some(Frame::Do);
+                last_operator = None;
+                statement_word = None;
+            }
+            b';' => {
+                if pending.is_some_and(|(_, at)| at == depth) {
+                    pending = None;
+                }
+                last_operator = None;
+                statement_word = None;
+            }
+            b',' => last_operator = None,
+            b'&' | b'|' | b'?' if next == Some(b) => {
+                len = 2;
+                let closure = b == b'|'
+                    && syntax == Syntax::Rust
+                    && (!operand_before || prev_word == "move" || next_on_line(bytes, i + 2) == Some(b'{'));
+                let coalesce = b == b'?' && syntax != Syntax::Script;
+                if operand_before && !closure && !coalesce {
+                    let operator = &bytes[i..i + 2];
+                    if last_operator != Some(operator) {
+                        total += 1;
+                    }
+                    last_operator = Some(operator);
+                }
+            }
+            b'?' if syntax != Syntax::Rust
+                && operand_before
+                && !matches!(next_on_line(bytes, i + 1), Some(b'.' | b':' | b')' | b',' | b'=' | b';' | b'>')) =>
+            {
+                total += 1 + nesting_level(frames.iter().copied());
+            }
+            b'=' if next == Some(b'>') && syntax != Syntax::Rust => {
+                len = 2;
+                expect_block(&mut pending, Frame::Function, depth);
+            }
+            _ => {}
+        }
+        prev = bytes[i + len - 1];
+        prev_word = "";
+        i += len;
+    }
+    total
+}
+
+/// Complexity of indented (Python) code
+fn indented_complexity(masked: &str) -> u32 {
+    let mut total = 0;
+    // (indentation of the header, frame of its suite)
+    let mut blocks: Vec<(usize, Frame)> = Vec::new();
+    let mut depth = 0usize;
+    let mut last_operator: Option<&str> = None;
+
+    for line in split_lines(masked) {
+        let code = line.trim_start();
+        if code.trim_end().is_empty() {
+            continue;
+        }
+        let logical_start = depth == 0;
+        let indent = line.len() - code.len();
+        if logical_start {
+            while blocks.last().is_some_and(|&(header, _)| header >= indent) {
+                blocks.pop();
+            }
+            last_operator = None;
+        }
+        let nesting = nesting_level(blocks.iter().map(|&(_, frame)| frame));
+
+        let bytes = code.as_bytes();
+        let mut first = logical_start;
+        let mut i = 0;
+        while i < bytes.len() {
+            let b = bytes[i];
+            if !is_word_byte(b) {
+                match b {
+                    b'(' | b'[' | b'{' => depth += 1,
+                    b')' | b']' | b'}' => depth = depth.saturating_sub(1),
+                    b',' | b':' => last_operator = None,
+                    _ => {}
+                }
+                i += 1;
+                continue;
+            }
+            let start = i;
+            while i < bytes.len() && is_word_byte(bytes[i]) {
+                i += 1;
+            }
+            let word = &code[start..i];
+            if std::mem::take(&mut first) {
+                let frame = match word {
+                    "async" => {
+                        first = true;
+                        continue;
+                    }
+                    "if" | "for" | "while" | "except" => {
+                        total += 1 + nesting;
+                        Frame::Structure
+                    }
+                    "match" if code.trim_end().ends_with(':') && next_on_line(bytes, i).is_some_and(|b| b != b'=') => {
+                        total += 1 + nesting;
+                        Frame::Structure
+                    }
+                    "elif" | "else" => {
+                        total += 1;
+                        Frame::Structure
+                    }
+                    "def" => Frame::Function,
+                    "class" | "try" | "with" | "finally" | "case" => Frame::Plain,
+                    _ => continue,
+                };
+                blocks.push((indent, frame));
+                continue;
+            }
+            match word {
+                // Conditional expressions and comprehension filters
+                "if" => total += 1 + nesting,
+                "and" | "or" => {
+                    if last_operator != Some(word) {
+                        total += 1;
+                    }
+                    last_operator = Some(word);
+                }
+                _ => {}
+            }
+        }
+    }
+    total
+}
+
+/// Number of functions in `masked` whose body calls the function itself
+fn recursive_functions(masked: &str, file_path: &str) -> u32 {
+    let starts = line_starts(masked);
+    let recursive = extract_functions(masked, file_path).into_iter().filter(|(name, line)| {
+        let Some(body) = starts.get(line - 1).and_then(|&header| find_block(masked, header, file_path)) else {
+            return false;
+        };
+        // A call to the name, not a method of the same name on something else
+        let call = format!(r"(?:^|[^\w.:]|\bself\.|\bthis\.|\bSelf::){}\s*\(", regex::escape(name));
+        Regex::new(&call).is_ok_and(|call| call.is_match(&masked[body.start..body.end]))
+    });
+    recursive.count() as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Snippets from the Cognitive Complexity spec, translated where the
+    /// language needs it, with the spec's scores
+    #[test]
+    fn test_spec_examples() {
+        let cases = [
+            (
+                "sumOfPrimes",
+                "js",
+                "function sumOfPrimes(max) {\n  let total = 0;\n  OUT: for (let i = 1; i <= max; ++i) {\n    for (let j = 2; j < i; ++j) {\n      if (i % j == 0) {\n        continue OUT;\n      }\n    }\n    total += i;\n  }\n  return total;\n}\n",
+                7,
+            ),
+            (
+                "getWords",
+                "js",
+                "function getWords(number) {\n  switch (number) {\n    case 1:\n      return \"one\";\n    case 2:\n      return \"a couple\";\n    default:\n      return \"lots\";\n  }\n}\n",
+                1,
+            ),
+            (
+                "try/catch nesting",
+                "java",
+                "void myMethod() {\n  try {\n    if (condition1) {\n      for (int i = 0; i < 10; i++) {\n        while (condition2) { }\n      }\n    }\n  } catch (ExcepType1 | ExcepType2 e) {\n    if (condition2) { }\n  }\n}\n",
+                9,
+            ),
+            (
+                "lambda nesting",
+                "js",
+                "function myMethod2() {\n  const r = () => {\n    if (condition1) { }\n  };\n}\n",
+                2,
+            ),
+            (
+                "else if chain",
+                "js",
+                "function sign(x) {\n  if (x > 0) {\n    return 1;\n  } else if (x < 0) {\n    return -1;\n  } else {\n    return 0;\n  }\n}\n",
+                3,
+            ),
+            (
+                "boolean operator sequences",
+                "js",
+                "function check() {\n  if (a && b && c || d || e && f) { }\n}\n",
+                4,
+            ),
+            (
+                "recursion",
+                "js",
+                "function fact(n) {\n  if (n <= 1) { return 1; }\n  return n * fact(n - 1);\n}\n",
+                2,
+            ),
+            (
+                "ternary and nullish coalescing",
+                "ts",
+                "function pick(a?: number, b?: Options) {\n  return b?.flag ? a : a ?? 0;\n}\n",
+                2,
+            ),
+            (
+                "do while",
+                "c",
+                "int drain(Queue *q) {\n  do {\n    if (pop(q)) { }\n  } while (!empty(q));\n  return 0;\n}\n",
+                3,
+            ),
+        ];
+        for (name, ext, code, expected) in cases {
+            assert_eq!(cognitive_complexity(code, ext), expected, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_rust_rules() {
+        let cases = [
+            (
+                "sumOfPrimes with a labeled continue",
+                "fn sum_of_primes(max: u32) -> u32 {\n    let mut total = 0;\n    'out: for i in 1..=max {\n        for j in 2..i {\n            if i % j == 0 {\n                continue 'out;\n            }\n        }\n        total += i;\n    }\n    total\n}\n",
+                7,
+            ),
+            (
+                "match and loop",
+                "fn run(rx: &Receiver<Msg>) {\n    loop {\n        match rx.recv() {\n            Ok(Msg::Stop) => break,\n            Ok(_) => {}\n            Err(_) => return,\n        }\n    }\n}\n",
+                3,
+            ),
+            (
+                "closures nest",
+                "fn positives(items: &[i32]) -> usize {\n    items.iter().filter(|x| {\n        if **x > 0 { true } else { false }\n    }).count()\n}\n",
+                3,
+            ),
+            (
+                "move closure with a boolean run",
+                "fn spawn(a: bool, b: bool) {\n    thread::spawn(move || {\n        if a && b && !a { }\n    });\n}\n",
+                3,
+            ),
+            (
+                "impl for and references are not flow",
+                "impl Display for Point {\n    fn fmt(&self, f: &mut Formatter) -> fmt::Result {\n        let v: Vec<_> = self.xs.iter().filter(|&&x| x > 0).collect();\n        if v.is_empty() || self.name == \"origin\" || &&v == &&v { }\n        Ok(())\n    }\n}\n",
+                2,
+            ),
+            (
+                "method of the same name is not recursion",
+                "impl Stack {\n    fn len(&self) -> usize {\n        self.items.len()\n    }\n    fn depth(&self, n: usize) -> usize {\n        if n == 0 { 0 } else { 1 + self.depth(n - 1) }\n    }\n}\n",
+                3,
+            ),
+        ];
+        for (name, code, expected) in cases {
+            assert_eq!(cognitive_complexity(code, "rs"), expected, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_python_rules() {
+        let cases = [
+            (
+                "elif and else add no nesting",
+                "def classify(n):\n    if n < 0:\n        return 'neg'\n    elif n == 0:\n        return 'zero'\n    else:\n        for d in range(2, n):\n            if n % d == 0 and d > 1 or n < 3:\n                return 'composite'\n    return 'prime'\n",
+                10,
+            ),
+            (
+                "except and recursion",
+                "def load(path, retry):\n    try:\n        with open(path) as f:\n            return f.read()\n    except OSError:\n        if retry:\n            return load(path, False)\n    return None\n",
+                4,
+            ),
+            (
+                "nested function and conditional expression",
+                "def outer(flag):\n    def inner(x):\n        if x:\n            pass\n    value = 1 if flag else 2\n    return inner\n",
+                3,
+            ),
+            (
+                "continuation lines stay in their statement",
+                "def check(a, b, c):\n    if (a and\n            b and\n            c):\n        return True\n",
+                2,
+            ),
+        ];
+        for (name, code, expected) in cases {
+            assert_eq!(cognitive_complexity(code, "py"), expected, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_literals_and_comments_are_ignored() {
+        let code = "function f() {\n  // if (a && b) { }\n  const s = \"if (x) { while (y) }\";\n}\n";
+        assert_eq!(cognitive_complexity(code, "js"), 0);
+        assert_eq!(cognitive_complexity("def f():\n    # if a and b:\n    return 'if x else y'\n", "py"), 0);
+    }
+}
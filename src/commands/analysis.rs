@@ -84,7 +84,7 @@ pub fn handle_deadcode_command(
     path: &Path,
     extensions: Option<&[String]>,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    deadcode::detect_dead_code(path, extensions, None)?;
+    deadcode::detect_dead_code(path, extensions, None, &deadcode::DeadCodeOptions::default())?;
     Ok(())
 }
 
@@ -2,7 +2,8 @@
 //!
 //! Handles all search-related CLI commands.
 
-use crate::search::{print_results, print_search_stats, search_code};
+use crate::schema::{SchemaVersion, search_results_to_json};
+use crate::search::{print_results, print_search_stats, search_path_or_glob};
 use crate::types::SearchOptions;
 use crate::export;
 use colored::*;
@@ -51,7 +52,7 @@ pub fn handle_search_command(
     stats: bool,
     export_path: Option<String>,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let results = search_code(query, path, &options)?;
+    let results = search_path_or_glob(query, path, &options)?;
 
     if let Some(path) = export_path {
         export::export_results(&results, &path, query)?;
@@ -59,7 +60,7 @@ pub fn handle_search_command(
     } else {
         match format {
             "json" => {
-                let json = serde_json::to_string_pretty(&results)?;
+                let json = search_results_to_json(query, &results, SchemaVersion::current())?;
                 println!("{}", json);
             }
             _ => {
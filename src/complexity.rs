@@ -2,51 +2,81 @@
 //!
 //! Provides code complexity metrics including cyclomatic and cognitive complexity.
 
-use crate::language::get_language_by_extension;
+use crate::cognitive::cognitive_complexity;
+use crate::config::ComplexityConfig;
+use crate::language::{extract_embedded_regions, get_language_by_extension, language_extension, ComplexityThresholds};
+use crate::lines::{line_number_at, line_starts, split_lines};
+use crate::parser::{extract_functions, find_block, mask_literals};
 use crate::search::list_files;
-use crate::types::ComplexityMetrics;
+use crate::types::{ComplexityMetrics, FunctionComplexity};
+use crate::theme::{self, Glyph};
 use colored::*;
 use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
 use std::fs;
 use std::path::Path;
 
-/// Analyze complexity for all files in a directory
-pub fn analyze_complexity(
-    path: &Path,
-    extensions: Option<&[String]>,
-    exclude: Option<&[String]>,
-    threshold: Option<u32>,
-    sort: bool,
-) -> Result<(), Box<dyn std::error::Error>> {
-    println!("{}", "Code Complexity Analysis".cyan().bold());
-    println!("{}", "─".repeat(30).cyan());
-    println!();
-
-    let files = list_files(path, extensions, exclude)?;
-    let mut all_metrics: Vec<ComplexityMetrics> = Vec::new();
+/// Complexity of one file, as listed by the `complexity` command
+///
+/// Also the record type of `complexity --format json`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct FileComplexityReport {
+    pub path: String,
+    pub cyclomatic: u32,
+    pub cognitive: u32,
+    /// Number of functions, including those in embedded regions
+    pub functions: usize,
+    pub max_nesting: u32,
+    /// Non-blank lines
+    pub lines: usize,
+}
 
-    for file in &files {
-        if let Ok(content) = fs::read_to_string(&file.path) {
-            let metrics = calculate_file_complexity(&file.path, &content);
-            all_metrics.push(metrics);
+impl From<ComplexityMetrics> for FileComplexityReport {
+    fn from(metrics: ComplexityMetrics) -> Self {
+        Self {
+            path: metrics.file_path,
+            cyclomatic: metrics.cyclomatic_complexity,
+            cognitive: metrics.cognitive_complexity,
+            functions: metrics.function_count,
+            max_nesting: metrics.max_nesting_depth,
+            lines: metrics.lines_of_code,
         }
     }
+}
 
-    // Filter by threshold if specified
+/// Complexity report of every readable file in a directory, in path order
+pub fn complexity_reports(
+    path: &Path,
+    extensions: Option<&[String]>,
+    exclude: Option<&[String]>,
+) -> Result<Vec<FileComplexityReport>, Box<dyn std::error::Error>> {
+    Ok(calculate_complexity(path, extensions, exclude)?.into_iter().map(FileComplexityReport::from).collect())
+}
+
+/// Keep the reports with a cyclomatic complexity of at least `threshold`, most complex first with `sort`
+///
+/// The sort is stable, so files of equal complexity stay in path order.
+pub fn filter_complexity_reports(reports: &mut Vec<FileComplexityReport>, threshold: Option<u32>, sort: bool) {
     if let Some(thresh) = threshold {
-        all_metrics.retain(|m| m.cyclomatic_complexity >= thresh);
+        reports.retain(|r| r.cyclomatic >= thresh);
     }
-
-    // Sort by complexity if requested
     if sort {
-        all_metrics.sort_by(|a, b| b.cyclomatic_complexity.cmp(&a.cyclomatic_complexity));
+        reports.sort_by_key(|r| Reverse(r.cyclomatic));
     }
+}
 
-    // Print results
-    for metrics in &all_metrics {
-        let complexity_color = if metrics.cyclomatic_complexity > 20 {
+/// Print complexity reports with a summary, the text output of the `complexity` command
+pub fn print_complexity_reports(reports: &[FileComplexityReport]) {
+    println!("{}", "Code Complexity Analysis".cyan().bold());
+    println!("{}", theme::rule(30).cyan());
+    println!();
+
+    for report in reports {
+        let complexity_color = if report.cyclomatic > 20 {
             "red"
-        } else if metrics.cyclomatic_complexity > 10 {
+        } else if report.cyclomatic > 10 {
             "yellow"
         } else {
             "green"
@@ -54,36 +84,258 @@ pub fn analyze_complexity(
 
         println!(
             "{} {} CC: {} COG: {} LOC: {} Functions: {} Nesting: {}",
-            "📄".dimmed(),
-            metrics.file_path.blue(),
-            metrics.cyclomatic_complexity.to_string().color(complexity_color).bold(),
-            metrics.cognitive_complexity.to_string().yellow(),
-            metrics.lines_of_code.to_string().dimmed(),
-            metrics.function_count.to_string().dimmed(),
-            metrics.max_nesting_depth.to_string().dimmed(),
+            theme::glyph(Glyph::File).dimmed(),
+            report.path.blue(),
+            report.cyclomatic.to_string().color(complexity_color).bold(),
+            report.cognitive.to_string().yellow(),
+            report.lines.to_string().dimmed(),
+            report.functions.to_string().dimmed(),
+            report.max_nesting.to_string().dimmed(),
         );
     }
 
     // Summary
-    if !all_metrics.is_empty() {
-        let total_cc: u32 = all_metrics.iter().map(|m| m.cyclomatic_complexity).sum();
-        let total_cog: u32 = all_metrics.iter().map(|m| m.cognitive_complexity).sum();
-        let total_loc: usize = all_metrics.iter().map(|m| m.lines_of_code).sum();
-        let total_funcs: usize = all_metrics.iter().map(|m| m.function_count).sum();
-        let avg_cc = total_cc as f64 / all_metrics.len() as f64;
+    if !reports.is_empty() {
+        let total_cc: u32 = reports.iter().map(|r| r.cyclomatic).sum();
+        let total_cog: u32 = reports.iter().map(|r| r.cognitive).sum();
+        let total_loc: usize = reports.iter().map(|r| r.lines).sum();
+        let total_funcs: usize = reports.iter().map(|r| r.functions).sum();
+        let avg_cc = total_cc as f64 / reports.len() as f64;
 
         println!();
-        println!("{}", "─".repeat(50).dimmed());
-        println!("{}", "📈 Summary".cyan().bold());
-        println!("  Files analyzed: {}", all_metrics.len().to_string().green());
+        println!("{}", theme::rule(50).dimmed());
+        println!("{}", theme::heading(Glyph::Summary, "Summary").cyan().bold());
+        println!("  Files analyzed: {}", reports.len().to_string().green());
         println!("  Total cyclomatic complexity: {}", total_cc.to_string().yellow());
         println!("  Average complexity per file: {:.1}", avg_cc);
         println!("  Total cognitive complexity: {}", total_cog.to_string().yellow());
         println!("  Total lines of code: {}", total_loc.to_string().blue());
         println!("  Total functions: {}", total_funcs.to_string().blue());
     }
+}
+
+/// Analyze complexity for all files in a directory
+///
+/// Prints the files passing `threshold` (sorted with `sort`) and returns them.
+pub fn analyze_complexity(
+    path: &Path,
+    extensions: Option<&[String]>,
+    exclude: Option<&[String]>,
+    threshold: Option<u32>,
+    sort: bool,
+) -> Result<Vec<FileComplexityReport>, Box<dyn std::error::Error>> {
+    let mut reports = complexity_reports(path, extensions, exclude)?;
+    filter_complexity_reports(&mut reports, threshold, sort);
+    print_complexity_reports(&reports);
+    Ok(reports)
+}
+
+/// Report the most complex functions in a directory, highest cyclomatic complexity first
+///
+/// `threshold` applies to each function rather than to whole files. Functions
+/// over their language's limits in `limits` are flagged with the limits they
+/// exceed. Returns every analyzed function, not just those listed.
+pub fn analyze_function_complexity(
+    path: &Path,
+    extensions: Option<&[String]>,
+    exclude: Option<&[String]>,
+    threshold: Option<u32>,
+    top: usize,
+    limits: &ComplexityConfig,
+) -> Result<Vec<(String, FunctionComplexity)>, Box<dyn std::error::Error>> {
+    println!("{}", "Function Complexity Analysis".cyan().bold());
+    println!("{}", theme::rule(30).cyan());
+    println!();
+
+    let analyzed = calculate_function_complexity(path, extensions, exclude)?;
+    let mut functions = analyzed.clone();
+    if let Some(thresh) = threshold {
+        functions.retain(|(_, f)| f.cyclomatic_complexity >= thresh);
+    }
+    let matching = functions.len();
+    let flagged = analyzed
+        .iter()
+        .filter(|(file, f)| !exceeded_thresholds(f, &limits.thresholds_for_path(file).1).is_empty())
+        .count();
+    functions.sort_by(|(_, a), (_, b)| {
+        b.cyclomatic_complexity
+            .cmp(&a.cyclomatic_complexity)
+            .then(b.cognitive_complexity.cmp(&a.cognitive_complexity))
+    });
+    functions.truncate(top);
+
+    for (file, function) in &functions {
+        let (language, thresholds) = limits.thresholds_for_path(file);
+        let complexity_color = if function.cyclomatic_complexity > 2 * thresholds.cyclomatic {
+            "red"
+        } else if function.cyclomatic_complexity > thresholds.cyclomatic {
+            "yellow"
+        } else {
+            "green"
+        };
+        let over = exceeded_thresholds(function, &thresholds);
+        let flag = if over.is_empty() {
+            String::new()
+        } else {
+            format!(" {}", format!("[over {language} limits: {}]", over.join(", ")).red())
+        };
+
+        println!(
+            "{} {}:{} {} CC: {} COG: {} Nesting: {} Lines: {}{flag}",
+            theme::glyph(Glyph::File).dimmed(),
+            file.blue(),
+            function.start_line.to_string().blue(),
+            function.name.bold(),
+            function.cyclomatic_complexity.to_string().color(complexity_color).bold(),
+            function.cognitive_complexity.to_string().yellow(),
+            function.max_nesting_depth.to_string().dimmed(),
+            function_length(function).to_string().dimmed(),
+        );
+    }
 
-    Ok(())
+    println!();
+    println!("{}", theme::rule(50).dimmed());
+    println!("{}", theme::heading(Glyph::Summary, "Summary").cyan().bold());
+    println!("  Functions analyzed: {}", analyzed.len().to_string().green());
+    println!("  Over their language's limits: {}", flagged.to_string().yellow());
+    if let Some(thresh) = threshold {
+        println!("  At or above threshold {}: {}", thresh, matching.to_string().yellow());
+    }
+    println!("  Shown: {}", functions.len().to_string().blue());
+
+    Ok(analyzed)
+}
+
+/// Lines from a function's signature to its closing brace
+pub fn function_length(function: &FunctionComplexity) -> usize {
+    function.end_line - function.start_line + 1
+}
+
+/// The limits `function` exceeds, e.g. `CC 14 > 12`; empty when it is within all of them
+pub fn exceeded_thresholds(function: &FunctionComplexity, thresholds: &ComplexityThresholds) -> Vec<String> {
+    let mut over = Vec::new();
+    if function.cyclomatic_complexity > thresholds.cyclomatic {
+        over.push(format!("CC {} > {}", function.cyclomatic_complexity, thresholds.cyclomatic));
+    }
+    if function.cognitive_complexity > thresholds.cognitive {
+        over.push(format!("COG {} > {}", function.cognitive_complexity, thresholds.cognitive));
+    }
+    if function_length(function) > thresholds.function_length {
+        over.push(format!("{} lines > {}", function_length(function), thresholds.function_length));
+    }
+    over
+}
+
+/// Calculate per-function complexity for every readable file in a directory,
+/// as `(file path, metrics)` pairs
+pub fn calculate_function_complexity(
+    path: &Path,
+    extensions: Option<&[String]>,
+    exclude: Option<&[String]>,
+) -> Result<Vec<(String, FunctionComplexity)>, Box<dyn std::error::Error>> {
+    let files = list_files(path, extensions, exclude)?;
+    Ok(files
+        .iter()
+        .filter_map(|file| {
+            let content = fs::read_to_string(&file.path).ok()?;
+            let ext = Path::new(&file.path).extension()?.to_str()?;
+            let functions = calculate_function_complexities(&content, ext);
+            Some(functions.into_iter().map(|f| (file.path.clone(), f)).collect::<Vec<_>>())
+        })
+        .flatten()
+        .collect())
+}
+
+/// Calculate complexity metrics for each function defined in `content`
+///
+/// Functions are found with the language's `function_patterns`; bodies end at
+/// the matching brace, or where the indentation drops back for Python. String
+/// literals and comments are ignored, declarations without a body are skipped,
+/// and nested named functions are reported separately from their parent.
+/// An `ext` mapped in `[language.overrides]` is measured as its language.
+pub fn calculate_function_complexities(content: &str, ext: &str) -> Vec<FunctionComplexity> {
+    let ext = language_extension(ext);
+    let file_path = format!("function.{ext}");
+    let masked = mask_literals(content, &file_path);
+    let starts = line_starts(content);
+    let python = matches!(ext, "py" | "pyw" | "pyi");
+
+    let mut definitions = extract_functions(&masked, &file_path);
+    definitions.sort_by_key(|(_, line)| *line);
+
+    // (name, header offset, body, end of the function including its closing brace)
+    let spans: Vec<_> = definitions
+        .into_iter()
+        .filter_map(|(name, line)| {
+            let header = *starts.get(line - 1)?;
+            let body = find_block(&masked, header, &file_path)?;
+            let end = if masked.as_bytes().get(body.end) == Some(&b'}') { body.end + 1 } else { body.end };
+            Some((name, header, body, end))
+        })
+        .collect();
+
+    spans
+        .iter()
+        .map(|(name, header, body, end)| {
+            let mut text = masked.as_bytes()[*header..*end].to_vec();
+            for (_, nested_header, _, nested_end) in &spans {
+                if nested_header > header && nested_end <= end {
+                    for byte in &mut text[nested_header - header..nested_end - header] {
+                        if *byte != b'\n' && *byte != b'\r' {
+                            *byte = b' ';
+                        }
+                    }
+                }
+            }
+            let text = String::from_utf8(text).unwrap_or_default();
+            let inner = &text[body.start - header..body.end - header];
+
+            FunctionComplexity {
+                name: name.clone(),
+                start_line: line_number_at(content, *header),
+                end_line: line_number_at(content, body.end),
+                cyclomatic_complexity: calculate_cyclomatic_complexity(&text),
+                cognitive_complexity: cognitive_complexity(&text, ext),
+                max_nesting_depth: if python { indentation_depth(inner) } else { calculate_nesting_depth(inner) },
+            }
+        })
+        .collect()
+}
+
+/// Nesting depth of an indented (Python) body, relative to its first line
+fn indentation_depth(body: &str) -> u32 {
+    let mut levels: Vec<usize> = Vec::new();
+    let mut max_depth = 0;
+    for line in split_lines(body) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let indent = line.len() - line.trim_start().len();
+        while levels.last().is_some_and(|&top| indent < top) {
+            levels.pop();
+        }
+        if levels.last().is_none_or(|&top| indent > top) {
+            levels.push(indent);
+        }
+        max_depth = max_depth.max(levels.len());
+    }
+    max_depth.saturating_sub(1) as u32
+}
+
+/// Calculate complexity metrics for every readable file in a directory
+pub fn calculate_complexity(
+    path: &Path,
+    extensions: Option<&[String]>,
+    exclude: Option<&[String]>,
+) -> Result<Vec<ComplexityMetrics>, Box<dyn std::error::Error>> {
+    let files = list_files(path, extensions, exclude)?;
+    Ok(files
+        .iter()
+        .filter_map(|file| {
+            let content = fs::read_to_string(&file.path).ok()?;
+            Some(calculate_file_complexity(&file.path, &content))
+        })
+        .collect())
 }
 
 /// Calculate complexity metrics for a single file
@@ -91,9 +343,9 @@ pub fn calculate_file_complexity(file_path: &str, content: &str) -> ComplexityMe
     ComplexityMetrics {
         file_path: file_path.to_string(),
         cyclomatic_complexity: calculate_cyclomatic_complexity(content),
-        cognitive_complexity: calculate_cognitive_complexity(content),
-        lines_of_code: content.lines().filter(|l| !l.trim().is_empty()).count(),
-        function_count: count_functions(content, file_path),
+        cognitive_complexity: cognitive_complexity(content, Path::new(file_path).extension().and_then(|e| e.to_str()).unwrap_or_default()),
+        lines_of_code: split_lines(content).filter(|l| !l.trim().is_empty()).count(),
+        function_count: count_functions(content, file_path) + count_embedded_functions(content, file_path),
         max_nesting_depth: calculate_nesting_depth(content),
     }
 }
@@ -128,51 +380,11 @@ pub fn calculate_cyclomatic_complexity(content: &str) -> u32 {
     complexity
 }
 
-/// Calculate cognitive complexity of code
+/// Calculate cognitive complexity of code in a C-like brace language
+///
+/// See [`cognitive_complexity`] to score code by the rules of its own language.
 pub fn calculate_cognitive_complexity(content: &str) -> u32 {
-    let mut complexity = 0;
-    let mut nesting_level = 0;
-
-    for line in content.lines() {
-        let trimmed = line.trim();
-
-        // Track nesting increases
-        if trimmed.contains('{') {
-            nesting_level += trimmed.matches('{').count();
-        }
-
-        // Cognitive complexity additions with nesting penalty
-        let cognitive_patterns = [
-            (r"\bif\b", 1),
-            (r"\belse\b", 1),
-            (r"\bfor\b", 1),
-            (r"\bwhile\b", 1),
-            (r"\bmatch\b", 1),
-            (r"\b\?\s*:", 1),
-            (r"\b&&\b", 1),
-            (r"\b\|\|\b", 1),
-            (r"\bbreak\b", 1),
-            (r"\bcontinue\b", 1),
-            (r"\bgoto\b", 3), // High penalty for goto
-            (r"\brecursion\b", 2),
-        ];
-
-        for (pattern, base_cost) in &cognitive_patterns {
-            if let Ok(regex) = Regex::new(pattern) {
-                let count = regex.find_iter(trimmed).count() as u32;
-                // Add nesting penalty
-                complexity += count * (base_cost + nesting_level as u32);
-            }
-        }
-
-        // Track nesting decreases
-        if trimmed.contains('}') {
-            let closes = trimmed.matches('}').count();
-            nesting_level = nesting_level.saturating_sub(closes);
-        }
-    }
-
-    complexity
+    cognitive_complexity(content, "")
 }
 
 /// Count functions in code based on file extension
@@ -212,12 +424,20 @@ pub fn count_functions(content: &str, file_path: &str) -> usize {
     count
 }
 
+/// Count functions in code embedded in another language, such as `<script>` blocks in HTML
+fn count_embedded_functions(content: &str, file_path: &str) -> usize {
+    extract_embedded_regions(file_path, content)
+        .iter()
+        .map(|region| count_functions(&region.content, &format!("embedded.{}", region.extension)))
+        .sum()
+}
+
 /// Calculate maximum nesting depth
 pub fn calculate_nesting_depth(content: &str) -> u32 {
     let mut max_depth: u32 = 0;
     let mut current_depth: u32 = 0;
 
-    for line in content.lines() {
+    for line in split_lines(content) {
         for ch in line.chars() {
             match ch {
                 '{' | '(' | '[' => {
@@ -304,5 +524,92 @@ mod tests {
         assert!(!metrics.file_path.is_empty());
         assert!(metrics.cyclomatic_complexity >= 1);
     }
-}
 
+    #[test]
+    fn test_function_complexities_are_per_function() {
+        let code = "fn simple() {\n    1\n}\n\nfn branchy(x: i32) -> i32 {\n    if x > 0 {\n        for i in 0..x {\n            if i == 2 { return i; }\n        }\n    }\n    0\n}\n";
+        let functions = calculate_function_complexities(code, "rs");
+        assert_eq!(functions.len(), 2);
+
+        assert_eq!(functions[0].name, "simple");
+        assert_eq!((functions[0].start_line, functions[0].end_line), (1, 3));
+        assert_eq!(functions[0].cyclomatic_complexity, 1);
+        assert_eq!(functions[0].max_nesting_depth, 0);
+
+        assert_eq!(functions[1].name, "branchy");
+        assert_eq!((functions[1].start_line, functions[1].end_line), (5, 12));
+        assert_eq!(functions[1].cyclomatic_complexity, 4);
+        assert!(functions[1].max_nesting_depth >= 3);
+    }
+
+    #[test]
+    fn test_function_complexities_separate_nested_functions() {
+        let code = "fn outer() {\n    fn inner(x: bool) {\n        if x { while x {} }\n    }\n    let f = |y: bool| if y { 1 } else { 0 };\n}\n";
+        let functions = calculate_function_complexities(code, "rs");
+        let outer = functions.iter().find(|f| f.name == "outer").unwrap();
+        let inner = functions.iter().find(|f| f.name == "inner").unwrap();
+
+        // The closure's `if`/`else` stay with `outer`, `inner`'s branches don't
+        assert_eq!(outer.cyclomatic_complexity, 3);
+        assert_eq!(inner.cyclomatic_complexity, 3);
+        assert_eq!((inner.start_line, inner.end_line), (2, 4));
+    }
+
+    #[test]
+    fn test_function_complexities_ignore_strings_and_declarations() {
+        let code = "trait T {\n    fn declared(&self);\n}\nfn one_line() -> &'static str { \"if { while }\" }\n";
+        let functions = calculate_function_complexities(code, "rs");
+        assert_eq!(functions.len(), 1);
+        assert_eq!(functions[0].name, "one_line");
+        assert_eq!((functions[0].start_line, functions[0].end_line), (4, 4));
+        assert_eq!(functions[0].cyclomatic_complexity, 1);
+    }
+
+    #[test]
+    fn test_function_complexities_python_indentation() {
+        let code = "def short(x): return x if x else 0\n\ndef nested(items):\n    for item in items:\n        if item:\n            print('if')\n    return None\n\nprint('done')\n";
+        let functions = calculate_function_complexities(code, "py");
+        assert_eq!(functions.len(), 2);
+
+        assert_eq!((functions[0].start_line, functions[0].end_line), (1, 1));
+        assert_eq!(functions[0].cyclomatic_complexity, 3);
+
+        assert_eq!(functions[1].name, "nested");
+        assert_eq!((functions[1].start_line, functions[1].end_line), (3, 7));
+        assert_eq!(functions[1].cyclomatic_complexity, 3);
+        assert_eq!(functions[1].max_nesting_depth, 2);
+    }
+
+    #[test]
+    fn test_language_limits_decide_what_is_flagged() {
+        // Seven branches: routine in Python, suspicious in SQL
+        let body = "if a then x\nelse if b then y\nwhile c loop z\nfor d in e loop w\n";
+        let cyclomatic = calculate_cyclomatic_complexity(body);
+        let function = FunctionComplexity {
+            name: "report".to_string(),
+            start_line: 1,
+            end_line: 4,
+            cyclomatic_complexity: cyclomatic,
+            cognitive_complexity: calculate_cognitive_complexity(body),
+            max_nesting_depth: 0,
+        };
+        assert_eq!(cyclomatic, 7);
+
+        let defaults = ComplexityConfig::default();
+        let (language, sql) = defaults.thresholds_for_path("report.sql");
+        assert_eq!(language, "SQL");
+        assert_eq!(exceeded_thresholds(&function, &sql), vec!["CC 7 > 5"]);
+        assert!(exceeded_thresholds(&function, &defaults.thresholds_for_path("report.py").1).is_empty());
+
+        // A configured limit moves the boundary for its language only
+        let config = crate::config::Config::parse("[complexity.languages.python]\ncyclomatic = 6\n").unwrap().complexity;
+        assert_eq!(exceeded_thresholds(&function, &config.thresholds_for_path("report.py").1), vec!["CC 7 > 6"]);
+        assert!(exceeded_thresholds(&function, &config.thresholds_for_path("report.rs").1).is_empty());
+    }
+
+    #[test]
+    fn test_embedded_script_functions_counted() {
+        let code = "<html>\n<script>\nfunction a() {}\nfunction b() {}\n</script>\n</html>\n";
+        assert_eq!(calculate_file_complexity("page.html", code).function_count, 2);
+    }
+}
@@ -0,0 +1,591 @@
+//! Configuration Module
+//!
+//! Loads user configuration from the file `--config` or `CODESEARCH_CONFIG`
+//! names, or else the nearest `.codesearchrc` / `.codesearch.toml` above the
+//! searched path. See `.codesearchrc.example` for the documented keys;
+//! `codesearch config show` prints the effective values.
+
+use crate::badge::{BadgeMetric, Bands};
+use crate::errors::ConfigError;
+use crate::language::{find_language, get_language_by_extension, get_supported_languages, set_language_overrides, ComplexityThresholds, LanguageInfo};
+use crate::profiles::Profile;
+use crate::types::SuggestionType;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// File names checked, in order, in each directory [`Config::discover_from`] looks in
+pub const CONFIG_FILE_NAMES: [&str; 2] = [".codesearchrc", ".codesearch.toml"];
+
+/// Environment variable naming the config file to use instead of looking for one
+pub const CONFIG_ENV_VAR: &str = "CODESEARCH_CONFIG";
+
+/// Top-level configuration file contents
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Config {
+    pub search: SearchConfig,
+    pub semantic: SemanticConfig,
+    pub privacy: PrivacyConfig,
+    pub deadcode: DeadcodeConfig,
+    pub deps: DepsConfig,
+    pub complexity: ComplexityConfig,
+    pub duplicates: DuplicatesConfig,
+    pub analyze: AnalyzeConfig,
+    pub refactor: RefactorConfig,
+    pub badge: BadgeConfig,
+    pub output: OutputConfig,
+    pub language: LanguageConfig,
+    /// Custom `--profile` option bundles, `[profiles.<name>]`
+    pub profiles: HashMap<String, Profile>,
+    /// File the configuration was read from; `None` for the defaults
+    #[serde(skip)]
+    pub source: Option<PathBuf>,
+}
+
+/// `[search]` section
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SearchConfig {
+    /// Default file extensions, normalized like `--extensions`
+    #[serde(deserialize_with = "crate::options::deserialize_extensions")]
+    pub extensions: Option<Vec<String>>,
+    /// Skip paths matched by `.gitignore` files (default true)
+    pub gitignore: Option<bool>,
+}
+
+impl SearchConfig {
+    /// Whether walks should honor `.gitignore`, unless `--no-gitignore` says otherwise
+    pub fn respect_gitignore(&self) -> bool {
+        self.gitignore.unwrap_or(true)
+    }
+}
+
+/// `[semantic]` section
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SemanticConfig {
+    /// Don't use the built-in synonym table, only `synonyms`
+    pub disable_builtins: bool,
+    /// Custom synonym groups, e.g. `order = ["booking", "purchase"]`
+    pub synonyms: HashMap<String, Vec<String>>,
+    /// Sentence-transformer directory or word-vector file used by `semantic-search` (`embeddings` feature)
+    pub model: Option<PathBuf>,
+}
+
+/// `[privacy]` section
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PrivacyConfig {
+    /// Don't use the built-in sensitive names (email, password, token, ...), only `sensitive_names`
+    pub disable_builtins: bool,
+    /// Extra sensitive names, in any case style (`date_of_birth`, `taxId`)
+    pub sensitive_names: Vec<String>,
+    /// Identifiers that are never reported, e.g. `public_key`
+    pub allow: Vec<String>,
+}
+
+/// `[deadcode]` section
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct DeadcodeConfig {
+    /// Roots for `deadcode --reachability`, as `path::function` relative to the
+    /// analyzed directory, globs allowed (e.g. `src/routes.rs::register_*`)
+    pub entry_points: Vec<String>,
+    /// Also report public items, like `--include-public`
+    pub include_public: bool,
+}
+
+/// `[deps]` section
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct DepsConfig {
+    /// Modules a package is imported as, when they differ from its name,
+    /// e.g. `python-jose = ["jose"]`
+    pub aliases: HashMap<String, Vec<String>>,
+}
+
+/// `[complexity]` section: `complexity` defaults, and limits that override the languages' own
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ComplexityConfig {
+    /// Show only files (functions with `--per-function`) above this complexity, like `--threshold`
+    pub threshold: Option<u32>,
+    /// Sort by complexity, like `--sort`
+    pub sort: bool,
+    /// Limits for every language, e.g. `cyclomatic = 12`
+    #[serde(flatten)]
+    pub all: ThresholdOverrides,
+    /// Limits for one language, by name or extension, e.g. `[complexity.languages.sql]`;
+    /// these win over the ones for every language
+    pub languages: HashMap<String, ThresholdOverrides>,
+}
+
+/// Complexity limits to override; unset ones keep the language default
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThresholdOverrides {
+    pub cyclomatic: Option<u32>,
+    pub cognitive: Option<u32>,
+    pub function_length: Option<usize>,
+}
+
+impl ThresholdOverrides {
+    fn apply(&self, thresholds: &mut ComplexityThresholds) {
+        thresholds.cyclomatic = self.cyclomatic.unwrap_or(thresholds.cyclomatic);
+        thresholds.cognitive = self.cognitive.unwrap_or(thresholds.cognitive);
+        thresholds.function_length = self.function_length.unwrap_or(thresholds.function_length);
+    }
+}
+
+impl ComplexityConfig {
+    /// Limits for `language`: its defaults, then the overrides for all languages, then its own
+    pub fn thresholds(&self, language: &LanguageInfo) -> ComplexityThresholds {
+        let mut thresholds = language.default_thresholds;
+        self.all.apply(&mut thresholds);
+        let own = self.languages.iter().find(|(key, _)| {
+            key.eq_ignore_ascii_case(language.name) || language.extensions.iter().any(|ext| key.eq_ignore_ascii_case(ext))
+        });
+        if let Some((_, overrides)) = own {
+            overrides.apply(&mut thresholds);
+        }
+        thresholds
+    }
+
+    /// Limits for a file, from its extension, and the name of its language
+    ///
+    /// Files in no known language get [`ComplexityThresholds::FALLBACK`] with
+    /// the overrides for all languages, under the name `"Unknown"`.
+    pub fn thresholds_for_path(&self, file_path: &str) -> (&'static str, ComplexityThresholds) {
+        let language = Path::new(file_path).extension().and_then(|ext| ext.to_str()).and_then(get_language_by_extension);
+        match language {
+            Some(language) => (language.name, self.thresholds(&language)),
+            None => {
+                let mut thresholds = ComplexityThresholds::FALLBACK;
+                self.all.apply(&mut thresholds);
+                ("Unknown", thresholds)
+            }
+        }
+    }
+}
+
+/// `[duplicates]` section: `duplicates` defaults
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct DuplicatesConfig {
+    /// Minimum lines for a duplicate block, like `--min-lines`
+    pub min_lines: usize,
+    /// Similarity threshold (0.0 - 1.0), like `--similarity`
+    pub similarity: f64,
+    /// Blank comments and string contents before comparing (default true)
+    pub ignore_comments: bool,
+}
+
+impl Default for DuplicatesConfig {
+    fn default() -> Self {
+        Self {
+            min_lines: crate::duplicates::DEFAULT_MIN_LINES,
+            similarity: crate::duplicates::DEFAULT_SIMILARITY,
+            ignore_comments: true,
+        }
+    }
+}
+
+/// `[analyze]` section: the files `analyze`, `complexity`, `duplicates` and `deadcode` look at
+/// when `--extensions` / `--exclude` aren't given
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct AnalyzeConfig {
+    /// Default file extensions, normalized like `--extensions`
+    #[serde(deserialize_with = "crate::options::deserialize_extensions")]
+    pub extensions: Option<Vec<String>>,
+    /// Default directories to exclude
+    pub exclude: Option<Vec<String>>,
+}
+
+/// `[refactor]` section
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RefactorConfig {
+    /// Suggestion types never looked for, e.g. `["Long Line", "Magic Number"]`;
+    /// `refactor --only` ignores them
+    pub disabled_types: Vec<SuggestionType>,
+}
+
+/// `[badge]` section: color thresholds of `codesearch badge`, e.g.
+/// `[badge.maintainability]` with `good = 85` and `fair = 65`
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BadgeConfig {
+    pub maintainability: Option<Bands>,
+    pub complexity: Option<Bands>,
+    pub deadcode: Option<Bands>,
+}
+
+impl BadgeConfig {
+    /// Thresholds for `metric`: the configured ones, or else its defaults
+    pub fn bands(&self, metric: BadgeMetric) -> Bands {
+        let configured = match metric {
+            BadgeMetric::Maintainability => self.maintainability,
+            BadgeMetric::Complexity => self.complexity,
+            BadgeMetric::Deadcode => self.deadcode,
+        };
+        configured.unwrap_or_else(|| metric.default_bands())
+    }
+}
+
+/// `[output]` section
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct OutputConfig {
+    /// Use emoji and box-drawing characters (`true`) or plain ASCII (`false`);
+    /// unset picks ASCII when stdout isn't a UTF-8 terminal
+    pub emoji: Option<bool>,
+    /// Print file paths as clickable hyperlinks: `auto` (the default), `always` or `never`
+    pub hyperlinks: Option<String>,
+    /// URL the hyperlinks open, e.g. `vscode://file/{path}:{line}`; unset means `file://` URLs
+    pub hyperlink_format: Option<String>,
+    /// Colors of highlighted matches: `default` (one per alternative of the query) or `mono`
+    pub theme: Option<String>,
+}
+
+/// `[language]` section
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LanguageConfig {
+    /// Languages for extensions the built-in table doesn't know or gets wrong,
+    /// `[language.overrides]` with e.g. `inc = "PHP"`; names as `languages` lists them
+    pub overrides: BTreeMap<String, String>,
+}
+
+impl LanguageConfig {
+    /// Check that every override names a supported language
+    pub fn validate(&self) -> Result<(), String> {
+        for (ext, name) in &self.overrides {
+            if find_language(name).is_none() {
+                let known: Vec<&str> = get_supported_languages().iter().map(|language| language.name).collect();
+                return Err(format!(
+                    "[language.overrides] maps \"{ext}\" to unknown language \"{name}\" (expected one of: {})",
+                    known.join(", ")
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Config {
+    /// The configuration in effect
+    ///
+    /// That is the one the CLI [activated](Config::activate) (from `--config`
+    /// or the search path), or else the file [`Config::discover`] finds. A file
+    /// that fails to load is then reported on stderr and ignored.
+    pub fn load() -> Self {
+        if let Some(active) = ACTIVE.get() {
+            return active.clone();
+        }
+        match Self::discover() {
+            Some(path) => Self::from_file(&path).unwrap_or_else(|e| {
+                eprintln!("Warning: {e}; using the defaults");
+                Self::default()
+            }),
+            None => Self::default(),
+        }
+    }
+
+    /// Load the config file to use for a command on `start`, or the defaults if there is none
+    ///
+    /// `explicit` (`--config`) wins over `CODESEARCH_CONFIG`, which wins over
+    /// discovery from `start` (see [`Config::discover_from`]). Unlike
+    /// [`Config::load`], a file that fails to load is an error.
+    pub fn resolve(explicit: Option<&Path>, start: &Path) -> Result<Self, ConfigError> {
+        match explicit.map(Path::to_path_buf).or_else(env_config).or_else(|| Self::discover_from(start)) {
+            Some(path) => Self::from_file(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Make this the configuration [`Config::load`] returns for the rest of the process
+    ///
+    /// Also installs its `[language.overrides]` (see [`set_language_overrides`]).
+    /// Only the first call has an effect.
+    pub fn activate(&self) {
+        if ACTIVE.set(self.clone()).is_ok() {
+            // Validated when parsed
+            let _ = set_language_overrides(self.language.overrides.iter().map(|(ext, name)| (ext.as_str(), name.as_str())));
+        }
+    }
+
+    /// Parse a config file
+    pub fn from_file(path: &Path) -> Result<Self, ConfigError> {
+        let content = std::fs::read_to_string(path).map_err(|source| ConfigError::Unreadable { path: path.to_path_buf(), source })?;
+        let mut config = Self::parse(&content).map_err(|e| ConfigError::Invalid { path: path.to_path_buf(), message: e.to_string() })?;
+        config.source = Some(path.to_path_buf());
+        Ok(config)
+    }
+
+    /// Parse config file contents
+    pub fn parse(content: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let config: Self = toml::from_str(content)?;
+        config.language.validate()?;
+        Ok(config)
+    }
+
+    /// Find the config file to use from the current directory: the one
+    /// `CODESEARCH_CONFIG` names, or else what [`Config::discover_from`] finds
+    pub fn discover() -> Option<PathBuf> {
+        env_config().or_else(|| Self::discover_from(Path::new(".")))
+    }
+
+    /// Find the nearest config file for `start`, a file or directory
+    ///
+    /// Looks in `start` and then each parent, up to the root of the git
+    /// repository `start` is in (the directory with `.git`) or else the
+    /// filesystem root, and finally in the home directory. In each directory,
+    /// `.codesearchrc` comes before `.codesearch.toml`.
+    pub fn discover_from(start: &Path) -> Option<PathBuf> {
+        let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE")).map(PathBuf::from);
+        discover_in(start, home.as_deref())
+    }
+}
+
+/// Configuration [`Config::activate`] set
+static ACTIVE: OnceLock<Config> = OnceLock::new();
+
+/// The file `CODESEARCH_CONFIG` names, if it is set
+fn env_config() -> Option<PathBuf> {
+    std::env::var_os(CONFIG_ENV_VAR).filter(|path| !path.is_empty()).map(PathBuf::from)
+}
+
+fn discover_in(start: &Path, home: Option<&Path>) -> Option<PathBuf> {
+    let start = std::path::absolute(start).unwrap_or_else(|_| start.to_path_buf());
+    let first_dir = if start.is_file() { start.parent() } else { Some(start.as_path()) };
+    let in_dir = |dir: &Path| CONFIG_FILE_NAMES.iter().map(|name| dir.join(name)).find(|path| path.is_file());
+
+    for dir in first_dir.into_iter().flat_map(Path::ancestors) {
+        if let Some(path) = in_dir(dir) {
+            return Some(path);
+        }
+        if dir.join(".git").exists() {
+            break;
+        }
+    }
+    home.and_then(in_dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_semantic_synonyms() {
+        let config = Config::parse(
+            r#"
+[search]
+fuzzy_threshold = 0.6
+
+[semantic]
+disable_builtins = true
+
+[semantic.synonyms]
+order = ["booking", "purchase"]
+user = ["account", "member"]
+"#,
+        )
+        .unwrap();
+
+        assert!(config.semantic.disable_builtins);
+        assert_eq!(config.semantic.synonyms["order"], vec!["booking", "purchase"]);
+        assert_eq!(config.semantic.synonyms.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_deadcode_entry_points() {
+        let config = Config::parse(
+            r#"
+[deadcode]
+entry_points = ["src/main.rs::main", "src/routes.rs::register_*"]
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.deadcode.entry_points, vec!["src/main.rs::main", "src/routes.rs::register_*"]);
+        assert!(Config::parse("").unwrap().deadcode.entry_points.is_empty());
+    }
+
+    #[test]
+    fn test_parse_language_overrides() {
+        let config = Config::parse("[language.overrides]\ninc = \"PHP\"\nbzl = \"python\"\n").unwrap();
+        assert_eq!(config.language.overrides["inc"], "PHP");
+        assert_eq!(config.language.overrides["bzl"], "python");
+
+        let err = Config::parse("[language.overrides]\ncgi = \"Perl 6\"\n").unwrap_err().to_string();
+        assert!(err.contains("maps \"cgi\" to unknown language \"Perl 6\""), "{err}");
+        assert!(err.contains("expected one of: Rust, Python"), "{err}");
+    }
+
+    #[test]
+    fn test_parse_empty_config() {
+        let config = Config::parse("").unwrap();
+        assert!(!config.semantic.disable_builtins);
+        assert!(config.semantic.synonyms.is_empty());
+    }
+
+    #[test]
+    fn test_search_gitignore_defaults_to_on() {
+        assert!(Config::parse("").unwrap().search.respect_gitignore());
+        assert!(!Config::parse("[search]\ngitignore = false\n").unwrap().search.respect_gitignore());
+    }
+
+    #[test]
+    fn test_search_extensions_are_normalized() {
+        let config = Config::parse("[search]\nextensions = [\".RS\", \"rs\", \" Py\"]\n").unwrap();
+        assert_eq!(config.search.extensions, Some(vec!["rs".to_string(), "py".to_string()]));
+
+        let err = Config::parse("[search]\nextensions = [\"src/\"]\n").unwrap_err();
+        assert!(err.to_string().contains("invalid extension"));
+    }
+
+    #[test]
+    fn test_parse_privacy_section() {
+        let config = Config::parse("[privacy]\nsensitive_names = [\"tax_id\"]\nallow = [\"public_key\"]\n").unwrap();
+        assert!(!config.privacy.disable_builtins);
+        assert_eq!(config.privacy.sensitive_names, vec!["tax_id"]);
+        assert_eq!(config.privacy.allow, vec!["public_key"]);
+    }
+
+    #[test]
+    fn test_parse_output_section() {
+        assert_eq!(Config::parse("").unwrap().output.emoji, None);
+        assert_eq!(Config::parse("[output]\nemoji = false\n").unwrap().output.emoji, Some(false));
+    }
+
+    #[test]
+    fn test_parse_profiles() {
+        let config = Config::parse("[profiles.myteam]\nmax_results = 20\nformat = \"json\"\n").unwrap();
+        let profile = &config.profiles["myteam"];
+        assert_eq!(profile.max_results, Some(20));
+        assert_eq!(profile.format.as_deref(), Some("json"));
+        assert_eq!(profile.rank, None);
+
+        assert!(Config::parse("[profiles.myteam]\nmax_resluts = 20\n").is_err());
+    }
+
+    #[test]
+    fn test_complexity_overrides() {
+        let config = Config::parse("[complexity]\ncyclomatic = 20\n\n[complexity.languages.sql]\ncognitive = 4\n\n[complexity.languages.Python]\ncyclomatic = 7\n").unwrap();
+        let (language, sql) = config.complexity.thresholds_for_path("db/report.sql");
+        assert_eq!(language, "SQL");
+        assert_eq!(sql, ComplexityThresholds { cyclomatic: 20, cognitive: 4, function_length: 80 });
+        assert_eq!(config.complexity.thresholds_for_path("app.py").1.cyclomatic, 7);
+        assert_eq!(config.complexity.thresholds_for_path("notes.xyz"), ("Unknown", ComplexityThresholds { cyclomatic: 20, ..ComplexityThresholds::FALLBACK }));
+
+        assert!(Config::parse("[complexity.languages.sql]\ncyclomatc = 4\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_analysis_sections() {
+        let config = Config::parse(
+            "[complexity]\nthreshold = 15\nsort = true\ncyclomatic = 12\n\n[duplicates]\nmin_lines = 5\nignore_comments = false\n\n\
+             [deadcode]\ninclude_public = true\n\n[analyze]\nextensions = [\".RS\"]\nexclude = [\"vendor\"]\n",
+        )
+        .unwrap();
+        assert_eq!((config.complexity.threshold, config.complexity.sort, config.complexity.all.cyclomatic), (Some(15), true, Some(12)));
+        assert_eq!((config.duplicates.min_lines, config.duplicates.similarity, config.duplicates.ignore_comments), (5, 0.9, false));
+        assert!(config.deadcode.include_public);
+        assert_eq!(config.analyze.extensions, Some(vec!["rs".to_string()]));
+        assert_eq!(config.analyze.exclude, Some(vec!["vendor".to_string()]));
+
+        let defaults = Config::parse("").unwrap();
+        assert_eq!((defaults.duplicates.min_lines, defaults.duplicates.ignore_comments), (crate::duplicates::DEFAULT_MIN_LINES, true));
+        assert_eq!(defaults.complexity.threshold, None);
+    }
+
+    #[test]
+    fn test_effective_config_round_trips() {
+        let config = Config::parse("[duplicates]\nsimilarity = 0.8\n\n[complexity.languages.sql]\ncognitive = 4\n").unwrap();
+        let shown = toml::to_string(&config).unwrap();
+        let reparsed = Config::parse(&shown).unwrap();
+        assert_eq!(reparsed.duplicates.similarity, 0.8);
+        assert_eq!(reparsed.duplicates.min_lines, crate::duplicates::DEFAULT_MIN_LINES);
+        assert_eq!(reparsed.complexity.languages["sql"].cognitive, Some(4));
+    }
+
+    #[test]
+    fn test_parse_refactor_disabled_types() {
+        let config = Config::parse("[refactor]\ndisabled_types = [\"Long Line\", \"magic-number\"]\n").unwrap();
+        assert_eq!(config.refactor.disabled_types, vec![SuggestionType::LongLine, SuggestionType::MagicNumber]);
+
+        let err = Config::parse("[refactor]\ndisabled_types = [\"Long Lines\"]\n").unwrap_err().to_string();
+        assert!(err.contains("Unknown suggestion type: Long Lines (expected one of: Long Line,"), "{err}");
+    }
+
+    #[test]
+    fn test_discovery_finds_the_nearest_config() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let repo = dir.path().join("repo");
+        let package = repo.join("packages/web");
+        std::fs::create_dir_all(package.join("src")).unwrap();
+        std::fs::create_dir(repo.join(".git")).unwrap();
+        std::fs::write(repo.join(".codesearch.toml"), "").unwrap();
+        std::fs::write(package.join(".codesearch.toml"), "").unwrap();
+        std::fs::write(package.join("src/app.js"), "").unwrap();
+        let home = dir.path().join("home");
+        std::fs::create_dir(&home).unwrap();
+        std::fs::write(home.join(".codesearchrc"), "").unwrap();
+        let found = |start: &Path| discover_in(start, Some(&home)).unwrap().canonicalize().unwrap();
+
+        // The nested package's config wins over the repository's, for files too
+        assert_eq!(found(&package.join("src")), package.join(".codesearch.toml").canonicalize().unwrap());
+        assert_eq!(found(&package.join("src/app.js")), package.join(".codesearch.toml").canonicalize().unwrap());
+        std::fs::remove_file(package.join(".codesearch.toml")).unwrap();
+        assert_eq!(found(&package.join("src")), repo.join(".codesearch.toml").canonicalize().unwrap());
+
+        // Discovery stops at the repository root, then falls back to the home directory
+        std::fs::write(dir.path().join(".codesearch.toml"), "").unwrap();
+        std::fs::remove_file(repo.join(".codesearch.toml")).unwrap();
+        assert_eq!(found(&package), home.join(".codesearchrc").canonicalize().unwrap());
+        assert_eq!(discover_in(&package, None), None);
+    }
+
+    #[test]
+    fn test_explicit_config_wins_over_discovery() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join(".codesearch.toml"), "[duplicates]\nmin_lines = 4\n").unwrap();
+        let explicit = dir.path().join("ci.toml");
+        std::fs::write(&explicit, "[duplicates]\nmin_lines = 8\n").unwrap();
+
+        let config = Config::resolve(Some(&explicit), dir.path()).unwrap();
+        assert_eq!((config.duplicates.min_lines, config.source), (8, Some(explicit)));
+    }
+
+    #[test]
+    fn test_malformed_config_error_names_the_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join(".codesearch.toml");
+        std::fs::write(&path, "[duplicates\nmin_lines = 4\n").unwrap();
+
+        let err = Config::resolve(Some(&path), dir.path()).unwrap_err().to_string();
+        assert!(err.starts_with(&format!("Invalid config file {}: ", path.display())), "{err}");
+        assert!(err.contains("line 1"), "{err}");
+
+        let missing = dir.path().join("missing.toml");
+        let err = Config::resolve(Some(&missing), dir.path()).unwrap_err().to_string();
+        assert!(err.contains(&format!("Cannot read config file {}", missing.display())), "{err}");
+    }
+
+    #[test]
+    fn test_parse_badge_thresholds() {
+        let config = Config::parse("[badge.maintainability]\ngood = 85\nfair = 65\n").unwrap();
+        assert_eq!(config.badge.bands(BadgeMetric::Maintainability), Bands { good: 85.0, fair: 65.0 });
+        assert_eq!(config.badge.bands(BadgeMetric::Deadcode), BadgeMetric::Deadcode.default_bands());
+
+        assert!(Config::parse("[badge.maintainability]\ngood = 85\n").is_err());
+        assert!(Config::parse("[badge.coverage]\ngood = 85\nfair = 65\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_invalid_config() {
+        assert!(Config::parse("[semantic\nsynonyms = 1").is_err());
+    }
+}
@@ -0,0 +1,241 @@
+//! Trivial delegation detection
+//!
+//! Flags functions whose whole body forwards their parameters, unchanged and in
+//! order, to another call: `fn load(p: &Path) -> T { read(p) }`. Such wrappers
+//! are often left over from refactors. Functions with a fixed signature (trait
+//! impls, `extern` shims, overrides) and documented or attributed wrappers are
+//! skipped, since those usually exist on purpose.
+
+use super::parameters::{
+    function_headers, has_fixed_signature, js_bindings, matching_paren, python_binding, rust_bindings,
+    rust_fixed_signature_spans, split_top_level, FunctionHeader, ParamLanguage,
+};
+use crate::lines::line_number_at;
+use crate::parser::get_file_extension;
+use crate::text::truncate_str_safe;
+use crate::types::{RefactorSuggestion, SuggestionType, SNIPPET_CHARS};
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// Line prefixes that document or annotate the definition below them
+const DOC_OR_ATTRIBUTE_PREFIXES: &[&str] = &["///", "//!", "/**", "*", "#[", "@"];
+
+fn callee_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"^[A-Za-z_$][\w$]*(?:(?:\.|::)[A-Za-z_$][\w$]*)*\s*\(").expect("valid callee regex")
+    })
+}
+
+/// Report functions that only pass their parameters through to another call
+pub fn detect_trivial_delegation(file_path: &str, content: &str, suggestions: &mut Vec<RefactorSuggestion>) {
+    let Some(lang) = ParamLanguage::from_extension(get_file_extension(file_path)) else {
+        return;
+    };
+    let fixed_spans = if lang == ParamLanguage::Rust { rust_fixed_signature_spans(content) } else { Vec::new() };
+
+    for header in function_headers(content, file_path, lang) {
+        if fixed_spans.iter().any(|span| span.start <= header.line_start && header.line_start < span.end) {
+            continue;
+        }
+        if has_fixed_signature(content, &header) || is_documented(content, header.line_start) {
+            continue;
+        }
+        // Without parameters the wrapper usually hides a field or constant on purpose
+        let Some(params) = pass_through_names(content, &header, lang).filter(|params| !params.is_empty()) else {
+            continue;
+        };
+        let Some(callee) = delegated_call(&content[header.body.start..header.body.end], &params, lang) else {
+            continue;
+        };
+
+        let definition = content[header.line_start..].lines().next().unwrap_or_default().trim();
+        suggestions.push(RefactorSuggestion {
+            file: file_path.to_string(),
+            line_number: line_number_at(content, header.line_start),
+            suggestion_type: SuggestionType::TrivialDelegation,
+            description: format!("'{}' only passes its parameters through to '{callee}'", header.name),
+            priority: 2,
+            code_snippet: truncate_str_safe(definition, SNIPPET_CHARS),
+            improvement: format!("Consider inlining or re-exporting '{callee}'"),
+        });
+    }
+}
+
+/// Whether a doc comment, attribute or decorator sits right above the definition
+fn is_documented(content: &str, line_start: usize) -> bool {
+    content[..line_start]
+        .lines()
+        .rev()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .is_some_and(|line| DOC_OR_ATTRIBUTE_PREFIXES.iter().any(|prefix| line.starts_with(prefix)) || line.ends_with("*/"))
+}
+
+/// Parameter names in order, skipping `self`; `None` if any parameter is destructured
+fn pass_through_names(content: &str, header: &FunctionHeader, lang: ParamLanguage) -> Option<Vec<String>> {
+    let text = &content[header.params.0..header.params.1];
+    let mut names = Vec::new();
+    for (_, param) in split_top_level(text) {
+        if param.trim().is_empty() {
+            continue;
+        }
+        let name = match lang {
+            ParamLanguage::Rust => match rust_bindings(param).as_slice() {
+                // `self`, `&self` and `&mut self`
+                [] => continue,
+                [(name, true)] => name.clone(),
+                _ => return None,
+            },
+            // `self`, `cls` and the bare `*` and `/` markers
+            ParamLanguage::Python => match python_binding(param) {
+                Some(name) => name,
+                None => continue,
+            },
+            ParamLanguage::JavaScript => match js_bindings(param).as_slice() {
+                [(name, true)] => name.clone(),
+                _ => return None,
+            },
+        };
+        names.push(name);
+    }
+    Some(names)
+}
+
+/// The callee when `body` is a single call passing exactly `params`, allowing `return`, `await` and `?`
+fn delegated_call(body: &str, params: &[String], lang: ParamLanguage) -> Option<String> {
+    let mut expr = body.trim().trim_end_matches(';').trim_end();
+    expr = expr.strip_prefix("return").filter(|rest| rest.starts_with(char::is_whitespace)).unwrap_or(expr).trim_start();
+    expr = expr.strip_prefix("await").filter(|rest| rest.starts_with(char::is_whitespace)).unwrap_or(expr).trim_start();
+    expr = expr.strip_suffix('?').unwrap_or(expr);
+    expr = expr.strip_suffix(".await").unwrap_or(expr);
+
+    let head = callee_regex().find(expr)?;
+    let open = head.end() - 1;
+    if matching_paren(expr, open)? != expr.len() - 1 {
+        return None;
+    }
+    let callee = expr[..open].trim_end();
+    if callee == "super" || callee.starts_with("super.") {
+        return None;
+    }
+
+    let args: Vec<&str> = split_top_level(&expr[open + 1..expr.len() - 1])
+        .into_iter()
+        .map(|(_, arg)| arg.trim())
+        .filter(|arg| !arg.is_empty())
+        .map(|arg| match lang {
+            ParamLanguage::Rust => arg,
+            ParamLanguage::Python => arg.trim_start_matches('*'),
+            ParamLanguage::JavaScript => arg.trim_start_matches("..."),
+        })
+        .collect();
+    (args.len() == params.len() && args.iter().zip(params).all(|(arg, param)| *arg == param.as_str()))
+        .then(|| callee.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delegators(file: &str, content: &str) -> Vec<String> {
+        let mut suggestions = Vec::new();
+        detect_trivial_delegation(file, content, &mut suggestions);
+        assert!(suggestions.iter().all(|s| s.suggestion_type == SuggestionType::TrivialDelegation && s.priority <= 3));
+        suggestions.into_iter().map(|s| s.description.split('\'').nth(1).unwrap_or_default().to_string()).collect()
+    }
+
+    #[test]
+    fn test_rust_delegation() {
+        let content = r#"
+fn load(path: &str) -> Result<String, Error> {
+    read_config(path)
+}
+
+fn load_default(path: &str) -> Config {
+    parse(path, 10)
+}
+
+async fn fetch(url: &str, retries: u32) -> Result<Body> {
+    client::get(url, retries).await?
+}
+
+impl Display for Thing {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+impl Thing {
+    fn len(&self) -> usize {
+        self.items.len()
+    }
+}
+
+/// Reads the configuration
+fn documented(path: &str) -> String {
+    read_config(path)
+}
+
+#[no_mangle]
+pub extern "C" fn shim(x: u32) -> u32 {
+    inner(x)
+}
+"#;
+        assert_eq!(delegators("lib.rs", content), vec!["load", "fetch"]);
+    }
+
+    #[test]
+    fn test_python_delegation() {
+        let content = r#"
+def load(path, mode):
+    return read_config(path, mode)
+
+def load_default(path):
+    return read_config(path, "r")
+
+def forward(*args, **kwargs): return target(*args, **kwargs)
+
+def described(path):
+    """Load the configuration."""
+    return read_config(path)
+
+class Repo(Base):
+    @override
+    def save(self, item):
+        return self.store.save(item)
+
+    def get(self, key):
+        return self._get(key)
+"#;
+        assert_eq!(delegators("repo.py", content), vec!["load", "forward", "get"]);
+    }
+
+    #[test]
+    fn test_js_delegation() {
+        let content = r#"
+function load(path) {
+  return readConfig(path);
+}
+
+function loadDefault(path) {
+  return readConfig(path, {});
+}
+
+class Repo extends Base {
+  override save(item) {
+    return this.store.save(item);
+  }
+
+  async get(key) {
+    return await this.cache.get(key);
+  }
+
+  constructor(store) {
+    super(store);
+  }
+}
+"#;
+        assert_eq!(delegators("repo.ts", content), vec!["load", "get"]);
+    }
+}
@@ -1,13 +1,25 @@
 //! Detection functions for various types of dead code
 
-use super::types::DeadCodeItem;
-use super::helpers::{is_special_function, truncate_string, is_commented_out_code, extract_import_name};
-use crate::parser::get_file_extension;
+use super::types::{Confidence, DeadCodeItem};
+use super::helpers::{is_special_function, is_commented_out_code, extract_import_names};
+use crate::text::truncate_str_safe;
+use crate::lines::split_lines;
+use crate::parser::{get_file_extension, identifiers as identifiers_in};
 use crate::language::get_language_by_extension;
 use regex::Regex;
+use std::collections::HashMap;
+use std::sync::OnceLock;
 
 /// Detect unused variables in a file
-pub fn detect_unused_variables(file_path: &str, content: &str, items: &mut Vec<DeadCodeItem>) {
+///
+/// `identifiers` holds the file's identifier counts
+/// ([`crate::parser::FileExtraction::identifiers`]), so each variable is a lookup.
+pub fn detect_unused_variables_with(
+    file_path: &str,
+    content: &str,
+    identifiers: &HashMap<String, usize>,
+    items: &mut Vec<DeadCodeItem>,
+) {
     let ext = get_file_extension(file_path);
     let lang = get_language_by_extension(ext);
     
@@ -25,7 +37,7 @@ pub fn detect_unused_variables(file_path: &str, content: &str, items: &mut Vec<D
     
     for (pattern, group) in &variable_patterns {
         if let Ok(re) = Regex::new(pattern) {
-            for (line_num, line) in content.lines().enumerate() {
+            for (line_num, line) in split_lines(content).enumerate() {
                 if let Some(caps) = re.captures(line) {
                     if let Some(var_name) = caps.get(*group) {
                         let name = var_name.as_str();
@@ -33,14 +45,14 @@ pub fn detect_unused_variables(file_path: &str, content: &str, items: &mut Vec<D
                             continue;
                         }
                         
-                        let usage_count = content.matches(name).count();
-                        if usage_count <= 1 {
+                        if uses_outside_line(identifiers, line, name) == 0 {
                             items.push(DeadCodeItem {
                                 file: file_path.to_string(),
                                 line_number: line_num + 1,
                                 item_type: "variable".to_string(),
                                 name: name.to_string(),
                                 reason: "Variable declared but never used".to_string(),
+                                confidence: Confidence::Medium,
                             });
                         }
                     }
@@ -50,9 +62,19 @@ pub fn detect_unused_variables(file_path: &str, content: &str, items: &mut Vec<D
     }
 }
 
+/// Whole-word occurrences of `name` in a file, not counting those on `declaration`, one of its lines
+///
+/// `identifiers` holds the file's counts, so each lookup is a map hit instead of a
+/// rescan. Substring counts would let `res` be kept alive by `result`, or `Write` by `Writer2`.
+fn uses_outside_line(identifiers: &HashMap<String, usize>, declaration: &str, name: &str) -> usize {
+    let total = identifiers.get(name).copied().unwrap_or(0);
+    let declared = identifiers_in(declaration).filter(|(_, word)| *word == name).count();
+    total.saturating_sub(declared)
+}
+
 /// Detect unreachable code after return statements
 pub fn detect_unreachable_code(file_path: &str, content: &str, items: &mut Vec<DeadCodeItem>) {
-    let lines: Vec<&str> = content.lines().collect();
+    let lines: Vec<&str> = split_lines(content).collect();
     let mut in_function = false;
     let mut brace_depth = 0;
     let mut found_return = false;
@@ -86,8 +108,9 @@ pub fn detect_unreachable_code(file_path: &str, content: &str, items: &mut Vec<D
                                 file: file_path.to_string(),
                                 line_number: line_num + 2,
                                 item_type: "unreachable".to_string(),
-                                name: truncate_string(next_line, 40),
+                                name: truncate_str_safe(next_line, 40),
                                 reason: "Code after return statement is unreachable".to_string(),
+                                confidence: Confidence::High,
                             });
                             found_return = false;
                         }
@@ -105,7 +128,7 @@ pub fn detect_unreachable_code(file_path: &str, content: &str, items: &mut Vec<D
 
 /// Detect empty functions
 pub fn detect_empty_functions(file_path: &str, content: &str, items: &mut Vec<DeadCodeItem>) {
-    let lines: Vec<&str> = content.lines().collect();
+    let lines: Vec<&str> = split_lines(content).collect();
     
     let function_patterns = [
         r"fn\s+(\w+)\s*\(",
@@ -177,6 +200,7 @@ pub fn detect_empty_functions(file_path: &str, content: &str, items: &mut Vec<De
                                         item_type: "empty".to_string(),
                                         name: name.to_string(),
                                         reason: "Empty function with no implementation".to_string(),
+                                        confidence: Confidence::Medium,
                                     });
                                 }
                                 break;
@@ -191,35 +215,82 @@ pub fn detect_empty_functions(file_path: &str, content: &str, items: &mut Vec<De
 
 /// Detect TODO/FIXME markers
 pub fn detect_todo_fixme(file_path: &str, content: &str, items: &mut Vec<DeadCodeItem>) {
-    let markers = [
-        ("TODO", "TODO marker - incomplete implementation"),
-        ("FIXME", "FIXME marker - needs fixing"),
-        ("HACK", "HACK marker - temporary workaround"),
-        ("XXX", "XXX marker - problematic code"),
-        ("BUG", "BUG marker - known bug"),
-    ];
-    
-    for (line_num, line) in content.lines().enumerate() {
+    static MARKER: OnceLock<Regex> = OnceLock::new();
+    let marker_re = MARKER.get_or_init(|| {
+        Regex::new(r"\b(TODO|FIXME|HACK|XXX|BUG)\b(?:\([^)]*\))?:?\s*(.*)").expect("valid TODO marker regex")
+    });
+    let prefixes = comment_prefixes(file_path);
+    let mut in_triple_quoted = false;
+
+    for (line_num, line) in split_lines(content).enumerate() {
         let trimmed = line.trim();
-        
-        for (marker, reason) in &markers {
-            if trimmed.contains(marker) && (trimmed.starts_with("//") || trimmed.starts_with('#') || trimmed.starts_with("/*")) {
-                items.push(DeadCodeItem {
-                    file: file_path.to_string(),
-                    line_number: line_num + 1,
-                    item_type: "todo".to_string(),
-                    name: truncate_string(trimmed, 50),
-                    reason: reason.to_string(),
-                });
-                break;
-            }
+        // Lines inside a triple-quoted string are text, even when they start with `#`
+        let was_in_string = in_triple_quoted;
+        if (trimmed.matches("\"\"\"").count() + trimmed.matches("'''").count()) % 2 == 1 {
+            in_triple_quoted = !in_triple_quoted;
+        }
+        if was_in_string {
+            continue;
         }
+        let Some(comment) = prefixes.iter().find_map(|prefix| trimmed.strip_prefix(prefix.as_str())) else {
+            continue;
+        };
+        let Some(caps) = marker_re.captures(comment) else {
+            continue;
+        };
+
+        let marker = &caps[1];
+        let message = caps[2].trim_end_matches(|c: char| c.is_whitespace() || matches!(c, '*' | '/' | '-' | '}' | ']'));
+        let reason = match marker {
+            "TODO" => "TODO marker - incomplete implementation",
+            "FIXME" => "FIXME marker - needs fixing",
+            "HACK" => "HACK marker - temporary workaround",
+            "XXX" => "XXX marker - problematic code",
+            _ => "BUG marker - known bug",
+        };
+        items.push(DeadCodeItem {
+            file: file_path.to_string(),
+            line_number: line_num + 1,
+            item_type: "todo".to_string(),
+            name: if message.is_empty() { marker.to_string() } else { truncate_str_safe(&format!("{marker}: {message}"), 50) },
+            reason: reason.to_string(),
+            confidence: Confidence::High,
+        });
     }
 }
 
+/// Prefixes that start a comment line in `file_path`'s language, longest first
+///
+/// Taken from the language's `comment_patterns`; languages with `/*` blocks also
+/// get `*` for the interior lines of a block. Triple quotes are left out: they
+/// open strings, not comments. Unknown extensions fall back to `//`, `#` and `/*`.
+fn comment_prefixes(file_path: &str) -> Vec<String> {
+    let Some(lang) = get_language_by_extension(get_file_extension(file_path)) else {
+        return vec!["//".to_string(), "/*".to_string(), "#".to_string()];
+    };
+    let mut prefixes: Vec<String> = lang
+        .comment_patterns
+        .iter()
+        .map(|pattern| pattern.replace('\\', ""))
+        .filter(|prefix| !prefix.is_empty() && prefix != "'''" && prefix != "\"\"\"")
+        .collect();
+    if prefixes.iter().any(|prefix| prefix == "/*") {
+        prefixes.push("*".to_string());
+    }
+    prefixes.sort_by_key(|prefix| std::cmp::Reverse(prefix.len()));
+    prefixes
+}
+
 /// Detect dead code patterns (commented code and unused imports)
-pub fn detect_dead_code_patterns(file_path: &str, content: &str, items: &mut Vec<DeadCodeItem>) {
-    for (line_num, line) in content.lines().enumerate() {
+///
+/// `identifiers` holds the file's identifier counts, as for [`detect_unused_variables_with`].
+pub fn detect_dead_code_patterns_with(
+    file_path: &str,
+    content: &str,
+    identifiers: &HashMap<String, usize>,
+    items: &mut Vec<DeadCodeItem>,
+) {
+    for (line_num, line) in split_lines(content).enumerate() {
         let trimmed = line.trim();
         
         // Detect commented-out code
@@ -228,26 +299,23 @@ pub fn detect_dead_code_patterns(file_path: &str, content: &str, items: &mut Vec
                 file: file_path.to_string(),
                 line_number: line_num + 1,
                 item_type: "commented code".to_string(),
-                name: truncate_string(trimmed, 40),
+                name: truncate_str_safe(trimmed, 40),
                 reason: "Commented-out code should be removed".to_string(),
+                confidence: Confidence::High,
             });
         }
         
-        // Detect unused imports
-        if (trimmed.starts_with("use ") || trimmed.starts_with("import ") || trimmed.starts_with("from ")) 
-            && !trimmed.contains('*') 
-        {
-            if let Some(imported) = extract_import_name(trimmed) {
-                let usage_count = content.matches(&imported).count();
-                if usage_count <= 1 {
-                    items.push(DeadCodeItem {
-                        file: file_path.to_string(),
-                        line_number: line_num + 1,
-                        item_type: "import".to_string(),
-                        name: imported,
-                        reason: "Imported but never used".to_string(),
-                    });
-                }
+        // Detect unused imports, each name of a grouped import on its own
+        for imported in extract_import_names(trimmed, get_file_extension(file_path)) {
+            if uses_outside_line(identifiers, line, &imported) == 0 {
+                items.push(DeadCodeItem {
+                    file: file_path.to_string(),
+                    line_number: line_num + 1,
+                    item_type: "import".to_string(),
+                    reason: format!("Imported but never used: {}", truncate_str_safe(trimmed, 60)),
+                    name: imported,
+                    confidence: Confidence::Medium,
+                });
             }
         }
     }
@@ -256,6 +324,15 @@ pub fn detect_dead_code_patterns(file_path: &str, content: &str, items: &mut Vec
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parser::count_identifiers;
+
+    fn detect_unused_variables(file_path: &str, content: &str, items: &mut Vec<DeadCodeItem>) {
+        detect_unused_variables_with(file_path, content, &count_identifiers(content), items);
+    }
+
+    fn detect_dead_code_patterns(file_path: &str, content: &str, items: &mut Vec<DeadCodeItem>) {
+        detect_dead_code_patterns_with(file_path, content, &count_identifiers(content), items);
+    }
 
     #[test]
     fn test_detect_unused_variables() {
@@ -347,6 +424,82 @@ fn test() {
         assert!(items.iter().any(|i| i.item_type == "commented code"));
     }
 
+    #[test]
+    fn test_commented_code_with_cjk_is_shortened_on_char_boundaries() {
+        // Byte 37 falls inside a CJK character
+        let content = "// return compute(total); // 计算总和并返回结果给调用者以便显示\nfn main() {}\n";
+        let mut items = Vec::new();
+        detect_dead_code_patterns("test.rs", content, &mut items);
+
+        let item = items.iter().find(|i| i.item_type == "commented code").unwrap();
+        assert_eq!(item.name, "// return compute(total); // 计算总和并返回结...");
+        assert_eq!(item.name.chars().count(), 40);
+    }
+
+    #[test]
+    fn test_unused_variable_not_kept_alive_by_longer_name() {
+        let content = r#"
+fn test() {
+    let res = fetch();
+    let result = compute();
+    println!("{}", result);
+}
+"#;
+        let mut items = Vec::new();
+        detect_unused_variables("test.rs", content, &mut items);
+        assert!(items.iter().any(|i| i.name == "res" && i.line_number == 3));
+        assert!(!items.iter().any(|i| i.name == "result"));
+    }
+
+    #[test]
+    fn test_unused_import_not_kept_alive_by_longer_name() {
+        let content = r#"
+use std::io::Write;
+use std::fmt::Display;
+struct Writer2;
+fn show(value: &dyn Display) {}
+"#;
+        let mut items = Vec::new();
+        detect_dead_code_patterns("test.rs", content, &mut items);
+        let imports: Vec<&str> = items.iter().filter(|i| i.item_type == "import").map(|i| i.name.as_str()).collect();
+        assert_eq!(imports, vec!["Write"]);
+    }
+
+    #[test]
+    fn test_grouped_imports_report_each_unused_name() {
+        let content = r#"
+use std::{io, fmt::{self, Display}};
+use std::collections::HashMap as Map;
+use std::fmt::Write as _;
+fn show(value: &dyn Display) -> fmt::Result { Ok(()) }
+"#;
+        let mut items = Vec::new();
+        detect_dead_code_patterns("test.rs", content, &mut items);
+        let imports: Vec<(&str, usize)> = items
+            .iter()
+            .filter(|i| i.item_type == "import")
+            .map(|i| (i.name.as_str(), i.line_number))
+            .collect();
+        assert_eq!(imports, vec![("io", 2), ("Map", 3)]);
+        let io = items.iter().find(|i| i.name == "io").unwrap();
+        assert!(io.reason.contains("use std::{io, fmt::{self, Display}}"), "{}", io.reason);
+    }
+
+    #[test]
+    fn test_multi_name_python_and_js_imports() {
+        let python = "from os import path, sep\nimport numpy as np\nprint(path.join('a', 'b'))\n";
+        let mut items = Vec::new();
+        detect_dead_code_patterns("app.py", python, &mut items);
+        let unused: Vec<&str> = items.iter().filter(|i| i.item_type == "import").map(|i| i.name.as_str()).collect();
+        assert_eq!(unused, vec!["sep", "np"]);
+
+        let js = "import React, { useState, useMemo } from 'react';\nimport './app.css';\nimport * as api from './api';\nuseState(api.load());\n";
+        let mut items = Vec::new();
+        detect_dead_code_patterns("app.js", js, &mut items);
+        let unused: Vec<&str> = items.iter().filter(|i| i.item_type == "import").map(|i| i.name.as_str()).collect();
+        assert_eq!(unused, vec!["React", "useMemo"]);
+    }
+
     #[test]
     fn test_multi_language_support() {
         let python_content = r#"def empty_python_func():
@@ -478,6 +631,46 @@ fn test() {
         assert!(items.is_empty());
     }
 
+    fn todo_names(file_path: &str, content: &str) -> Vec<(usize, String)> {
+        let mut items = Vec::new();
+        detect_todo_fixme(file_path, content, &mut items);
+        items.into_iter().map(|i| (i.line_number, i.name)).collect()
+    }
+
+    #[test]
+    fn test_detect_todo_fixme_language_comments() {
+        let lua = "-- TODO: cache the lookup\nlocal TODO = 1\n--[[ FIXME: leaks ]]\n";
+        assert_eq!(
+            todo_names("init.lua", lua),
+            vec![(1, "TODO: cache the lookup".to_string()), (3, "FIXME: leaks".to_string())]
+        );
+
+        let sql = "SELECT 1; -- not a line comment\n-- HACK: works around the planner\n";
+        assert_eq!(todo_names("query.sql", sql), vec![(2, "HACK: works around the planner".to_string())]);
+
+        let haskell = "main = print 1\n-- TODO(ana): handle errors\n{- XXX: slow -}\n";
+        assert_eq!(
+            todo_names("Main.hs", haskell),
+            vec![(2, "TODO: handle errors".to_string()), (3, "XXX: slow".to_string())]
+        );
+
+        // Erlang comments start with `%`, which is not a comment in the fallback
+        assert_eq!(todo_names("app.erl", "% BUG: off by one\n"), vec![(1, "BUG: off by one".to_string())]);
+        assert_eq!(todo_names("notes.unknown", "% BUG: off by one\n# TODO\n"), vec![(2, "TODO".to_string())]);
+    }
+
+    #[test]
+    fn test_detect_todo_fixme_block_comment_interior() {
+        let content = "/*\n * Parser state.\n * TODO: support nested blocks\n */\nint DEBUG = 1;\n";
+        assert_eq!(todo_names("parse.c", content), vec![(3, "TODO: support nested blocks".to_string())]);
+    }
+
+    #[test]
+    fn test_detect_todo_fixme_python_strings() {
+        let content = "HELP = \"\"\"\n# TODO: this is help text\n\"\"\"\n# FIXME: real comment\n";
+        assert_eq!(todo_names("cli.py", content), vec![(4, "FIXME: real comment".to_string())]);
+    }
+
     // Complex scenario tests
     #[test]
     fn test_complex_nested_functions() {
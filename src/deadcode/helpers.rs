@@ -16,15 +16,6 @@ pub fn is_special_function(name: &str) -> bool {
       || name.starts_with("handle")
 }
 
-/// Truncate a string to a maximum length
-pub fn truncate_string(s: &str, max_len: usize) -> String {
-    if s.len() <= max_len {
-        s.to_string()
-    } else {
-        format!("{}...", &s[..max_len - 3])
-    }
-}
-
 /// Check if a line contains commented-out code
 pub fn is_commented_out_code(line: &str) -> bool {
     let (_prefix, rest) = if line.starts_with("// ") {
@@ -77,33 +68,182 @@ pub fn is_commented_out_code(line: &str) -> bool {
     false
 }
 
-/// Extract import name from an import statement
-pub fn extract_import_name(line: &str) -> Option<String> {
-    let line = line.trim_end_matches(';').trim();
-    
-    if line.starts_with("use ") {
-        let path = line.trim_start_matches("use ").trim();
-        if let Some(last) = path.split("::").last() {
-            let name = last.trim_matches(|c| c == '{' || c == '}');
-            if !name.is_empty() && name != "*" && !name.contains(',') {
-                return Some(name.to_string());
+/// Names an import statement binds, each of which must be referenced for the import to be used
+///
+/// `extension` picks the syntax (`rs`, `py`, JavaScript/TypeScript, or dotted
+/// Java-style paths); other extensions are guessed from the line. Aliases
+/// yield the alias. Globs, side-effect-only imports (`import './x.css'`,
+/// `use foo as _`, `from __future__ import ...`) and statements continuing
+/// on the next line yield nothing, so they are never reported as unused.
+pub fn extract_import_names(line: &str, extension: &str) -> Vec<String> {
+    let line = line.trim();
+    let mut names = Vec::new();
+    if let Some(tree) = line.strip_prefix("use ") {
+        let tree = tree.trim().trim_end_matches(';').trim();
+        if tree.matches('{').count() == tree.matches('}').count() {
+            rust_use_tree_names(tree, None, &mut names);
+        }
+    } else if let Some(rest) = line.strip_prefix("from ") {
+        python_from_import_names(rest, &mut names);
+    } else if let Some(rest) = line.strip_prefix("import ") {
+        match extension {
+            "py" | "pyw" | "pyi" => python_import_names(rest, &mut names),
+            "js" | "mjs" | "cjs" | "jsx" | "ts" | "mts" | "cts" | "tsx" | "vue" | "svelte" => {
+                js_import_names(rest, &mut names)
             }
+            "java" | "kt" | "kts" | "scala" | "groovy" => dotted_import_names(rest, &mut names),
+            _ if rest.contains(['\'', '"', '`', '{']) => js_import_names(rest, &mut names),
+            _ if rest.trim_end().ends_with(';') => dotted_import_names(rest, &mut names),
+            _ => python_import_names(rest, &mut names),
         }
-    } else if line.starts_with("import ") {
-        let name = line.trim_start_matches("import ").trim();
-        if let Some(first) = name.split_whitespace().next() {
-            return Some(first.to_string());
+    }
+    names
+}
+
+/// `name as alias` -> the alias; `None` for `as _`
+fn bound_name(item: &str) -> Option<&str> {
+    let bound = match item.rsplit_once(" as ") {
+        Some((_, alias)) => alias.trim(),
+        None => item.trim(),
+    };
+    (bound != "_").then_some(bound)
+}
+
+/// Split `text` on commas outside braces
+fn split_top_level(text: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let (mut depth, mut start) = (0usize, 0);
+    for (i, c) in text.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                parts.push(&text[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+/// Names bound by a Rust use tree; `parent` is what `self` stands for inside a brace group
+fn rust_use_tree_names(tree: &str, parent: Option<&str>, names: &mut Vec<String>) {
+    let tree = tree.trim().trim_start_matches("::");
+    if let Some(open) = tree.find('{') {
+        let Some(inner) = tree[open + 1..].strip_suffix('}') else {
+            return;
+        };
+        let prefix = tree[..open].trim_end_matches("::");
+        let parent = prefix.rsplit("::").next().filter(|p| !p.is_empty()).or(parent);
+        for item in split_top_level(inner) {
+            rust_use_tree_names(item, parent, names);
         }
-    } else if line.starts_with("from ") && line.contains(" import ") {
-        if let Some(after_import) = line.split(" import ").nth(1) {
-            let name = after_import.split(',').next().unwrap_or("").trim();
-            if !name.is_empty() && name != "*" {
-                return Some(name.to_string());
+        return;
+    }
+    let Some(bound) = bound_name(tree) else {
+        return;
+    };
+    let name = match bound.rsplit("::").next().unwrap_or(bound) {
+        "self" => match parent {
+            Some(parent) => parent,
+            None => return,
+        },
+        last => last,
+    };
+    if !name.is_empty() && name != "*" && !bound.contains(' ') {
+        names.push(name.to_string());
+    }
+}
+
+/// `import a.b as c, d.e` binds `c` and `d`: the alias, else the first dotted segment
+fn python_import_names(rest: &str, names: &mut Vec<String>) {
+    let rest = rest.split('#').next().unwrap_or("");
+    for item in rest.split(',') {
+        let item = item.trim();
+        let bound = match item.rsplit_once(" as ") {
+            Some((_, alias)) => alias.trim(),
+            None => item.split('.').next().unwrap_or(""),
+        };
+        if is_identifier(bound) {
+            names.push(bound.to_string());
+        }
+    }
+}
+
+/// `from m import a, b as c` (optionally parenthesized on one line) binds `a` and `c`
+fn python_from_import_names(rest: &str, names: &mut Vec<String>) {
+    let Some((module, imported)) = rest.split_once(" import ") else {
+        return;
+    };
+    let imported = imported.split('#').next().unwrap_or("").trim();
+    let imported = match imported.strip_prefix('(') {
+        Some(inner) => match inner.strip_suffix(')') {
+            Some(inner) => inner,
+            // The list continues on the next lines
+            None => return,
+        },
+        None if imported.ends_with('\\') => return,
+        None => imported,
+    };
+    if module.trim() == "__future__" {
+        return;
+    }
+    for item in imported.split(',') {
+        if let Some(bound) = bound_name(item).filter(|b| is_identifier(b)) {
+            names.push(bound.to_string());
+        }
+    }
+}
+
+/// `import d, { a, b as c } from 'm'`, `import * as ns from 'm'`; `import 'm'` binds nothing
+fn js_import_names(rest: &str, names: &mut Vec<String>) {
+    let rest = rest.trim();
+    let rest = rest.strip_prefix("type ").unwrap_or(rest);
+    let Some((clause, _)) = rest.split_once(" from ") else {
+        // `import 'polyfill'`, or a clause continuing on the next lines
+        return;
+    };
+    if clause.matches('{').count() != clause.matches('}').count() {
+        return;
+    }
+    for part in split_top_level(clause) {
+        let part = part.trim();
+        if let Some(inner) = part.strip_prefix('{').and_then(|p| p.strip_suffix('}')) {
+            for item in inner.split(',') {
+                let item = item.trim();
+                let item = item.strip_prefix("type ").unwrap_or(item);
+                if let Some(bound) = bound_name(item).filter(|b| is_identifier(b)) {
+                    names.push(bound.to_string());
+                }
             }
+        } else if let Some(namespace) = part.strip_prefix('*') {
+            if let Some(bound) = bound_name(namespace).filter(|b| is_identifier(b)) {
+                names.push(bound.to_string());
+            }
+        } else if is_identifier(part) {
+            names.push(part.to_string());
         }
     }
-    
-    None
+}
+
+/// `import a.b.C;`, `import static a.B.member;` and Kotlin's `import a.B as C` bind the last segment
+fn dotted_import_names(rest: &str, names: &mut Vec<String>) {
+    let rest = rest.trim().trim_end_matches(';').trim();
+    let rest = rest.strip_prefix("static ").unwrap_or(rest);
+    let bound = match rest.rsplit_once(" as ") {
+        Some((_, alias)) => alias.trim(),
+        None => rest.rsplit('.').next().unwrap_or(""),
+    };
+    if is_identifier(bound) {
+        names.push(bound.to_string());
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    name.chars().next().is_some_and(|c| c.is_alphabetic() || c == '_' || c == '$')
+        && name.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '$')
 }
 
 #[cfg(test)]
@@ -121,12 +261,6 @@ mod tests {
         assert!(!is_special_function("calculate"));
     }
 
-    #[test]
-    fn test_truncate_string() {
-        assert_eq!(truncate_string("short", 10), "short");
-        assert_eq!(truncate_string("this is a long string", 10), "this is...");
-    }
-
     #[test]
     fn test_is_commented_out_code() {
         assert!(is_commented_out_code("// let x = 10;"));
@@ -135,10 +269,88 @@ mod tests {
         assert!(!is_commented_out_code("// TODO: fix this"));
     }
 
+    fn names(line: &str, extension: &str) -> Vec<String> {
+        extract_import_names(line, extension)
+    }
+
+    #[test]
+    fn test_rust_use_names() {
+        let cases: &[(&str, &[&str])] = &[
+            ("use std::io::Write;", &["Write"]),
+            ("use std::{io, fmt};", &["io", "fmt"]),
+            ("use std::io::{self, Write};", &["io", "Write"]),
+            ("use std::{io::{self, Read}, fmt::Display as Shown};", &["io", "Read", "Shown"]),
+            ("use std::collections::{HashMap as Map, hash_map::{Entry, self}};", &["Map", "Entry", "hash_map"]),
+            ("use crate::parser;", &["parser"]),
+            ("use ::serde::Serialize;", &["Serialize"]),
+            ("use std::io::Write as _;", &[]),
+            ("use std::{io::Write as _, fmt};", &["fmt"]),
+            ("use super::*;", &[]),
+            ("use std::{fmt::*, io};", &["io"]),
+            // Continues on the next lines
+            ("use std::{", &[]),
+            ("use std::{io,", &[]),
+        ];
+        for (line, expected) in cases {
+            assert_eq!(names(line, "rs"), *expected, "{line}");
+        }
+    }
+
+    #[test]
+    fn test_python_import_names() {
+        let cases: &[(&str, &[&str])] = &[
+            ("import os", &["os"]),
+            ("import os.path", &["os"]),
+            ("import numpy as np", &["np"]),
+            ("import os, sys", &["os", "sys"]),
+            ("import os.path as osp, json  # noqa", &["osp", "json"]),
+            ("from os import path", &["path"]),
+            ("from os import path, sep", &["path", "sep"]),
+            ("from os.path import join as pjoin, exists", &["pjoin", "exists"]),
+            ("from . import sibling", &["sibling"]),
+            ("from typing import (List, Dict)", &["List", "Dict"]),
+            ("from os import *", &[]),
+            ("from __future__ import annotations", &[]),
+            ("from typing import (", &[]),
+            ("from typing import List, \\", &[]),
+        ];
+        for (line, expected) in cases {
+            assert_eq!(names(line, "py"), *expected, "{line}");
+        }
+    }
+
+    #[test]
+    fn test_javascript_import_names() {
+        let cases: &[(&str, &[&str])] = &[
+            ("import React from 'react';", &["React"]),
+            ("import {a, b} from 'mod'", &["a", "b"]),
+            ("import { a as x, b } from \"mod\";", &["x", "b"]),
+            ("import React, { useState, useEffect } from 'react';", &["React", "useState", "useEffect"]),
+            ("import * as path from 'path';", &["path"]),
+            ("import type { Props } from './types';", &["Props"]),
+            ("import { type Props, render } from './view';", &["Props", "render"]),
+            ("import './styles.css';", &[]),
+            ("import 'polyfill'", &[]),
+            ("import {", &[]),
+        ];
+        for (line, expected) in cases {
+            assert_eq!(names(line, "ts"), *expected, "{line}");
+        }
+    }
+
+    #[test]
+    fn test_dotted_import_names() {
+        assert_eq!(names("import java.util.List;", "java"), vec!["List"]);
+        assert_eq!(names("import static org.junit.Assert.assertEquals;", "java"), vec!["assertEquals"]);
+        assert!(names("import java.util.*;", "java").is_empty());
+        assert_eq!(names("import kotlinx.coroutines.flow.Flow as F", "kt"), vec!["F"]);
+    }
+
     #[test]
-    fn test_extract_import_name() {
-        assert_eq!(extract_import_name("use std::io::Write;"), Some("Write".to_string()));
-        assert_eq!(extract_import_name("import os"), Some("os".to_string()));
-        assert_eq!(extract_import_name("from os import path"), Some("path".to_string()));
+    fn test_unknown_extension_guesses_syntax() {
+        assert_eq!(names("import java.util.List;", ""), vec!["List"]);
+        assert_eq!(names("import {a} from 'm'", ""), vec!["a"]);
+        assert_eq!(names("import os.path", ""), vec!["os"]);
+        assert!(names("fn main() {}", "rs").is_empty());
     }
 }
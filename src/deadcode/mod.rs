@@ -6,105 +6,181 @@
 //! - `types`: Data structures for dead code items
 //! - `helpers`: Utility functions for detection
 //! - `detectors`: Individual detection functions for different code patterns
+//! - `parameters`: Unused function parameter detection
+//! - `delegation`: Functions that only forward their parameters to another call
+//! - `reachability`: Call graph reachability from configured entry points
+//! - `visibility`: Public API items, whose users may be outside the scanned tree
 
 mod types;
 mod helpers;
 mod detectors;
+mod parameters;
+mod delegation;
+mod reachability;
+mod visibility;
 
-pub use types::DeadCodeItem;
+pub use types::{Confidence, DeadCodeItem, DeadCodeOptions, DeadCodeReport, DeadCodeSummary};
+pub use delegation::detect_trivial_delegation;
+pub use reachability::{EntryPoint, Reachability, DEFAULT_ENTRY_POINTS};
 
-use crate::parser::{extract_classes, extract_functions, extract_identifier_references, read_file_content};
-use crate::search::list_files;
+use crate::callgraph::build_call_graph_in;
+use crate::parser::{extract_identifier_references, ExtractionContext};
+use crate::theme::{self, Glyph};
 use colored::*;
-use std::collections::HashMap;
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 
 use helpers::is_special_function;
 use detectors::{
-    detect_unused_variables,
+    detect_unused_variables_with,
     detect_unreachable_code,
     detect_empty_functions,
     detect_todo_fixme,
-    detect_dead_code_patterns,
+    detect_dead_code_patterns_with,
 };
+use parameters::detect_unused_parameters;
+use visibility::public_definitions;
 
-/// Detect potentially dead/unused code in the codebase
+/// Detect potentially dead/unused code in the codebase, printing and returning it
 pub fn detect_dead_code(
     path: &Path,
     extensions: Option<&[String]>,
     exclude: Option<&[String]>,
-) -> Result<(), Box<dyn std::error::Error>> {
-    println!("{}", "Dead Code Detection".cyan().bold());
-    println!("{}", "─".repeat(30).cyan());
-    println!();
+    options: &DeadCodeOptions,
+) -> Result<Vec<DeadCodeItem>, Box<dyn std::error::Error>> {
+    print_dead_code_header();
 
-    let dead_code_items = find_dead_code(path, extensions, exclude)?;
+    let dead_code_items = find_dead_code(path, extensions, exclude, options)?;
     
     if dead_code_items.is_empty() {
         println!("{}", "No files found to analyze.".dimmed());
-        return Ok(());
+        return Ok(dead_code_items);
     }
 
-    print_dead_code_results(&dead_code_items);
+    print_dead_code_results(&dead_code_items, false);
 
-    Ok(())
+    Ok(dead_code_items)
 }
 
-/// Find dead code and return the results (shared implementation)
-pub fn find_dead_code(
+/// Detect dead code, using reachability from `entry_points` to refine the results
+///
+/// See [`find_dead_code_reachable_in`].
+pub fn detect_dead_code_reachable(
     path: &Path,
     extensions: Option<&[String]>,
     exclude: Option<&[String]>,
+    entry_points: &[EntryPoint],
+    options: &DeadCodeOptions,
 ) -> Result<Vec<DeadCodeItem>, Box<dyn std::error::Error>> {
-    let files = list_files(path, extensions, exclude)?;
-    
-    if files.is_empty() {
+    print_dead_code_header();
+
+    let context = ExtractionContext::build(path, extensions, exclude)?;
+    if context.is_empty() {
+        println!("{}", "No files found to analyze.".dimmed());
         return Ok(Vec::new());
     }
 
-    let mut dead_code_items: Vec<DeadCodeItem> = Vec::new();
-    let mut all_definitions: HashMap<String, (String, usize, String)> = HashMap::new();
-    let mut all_references: HashMap<String, usize> = HashMap::new();
-
-    // First pass: collect all definitions and references
-    for file in &files {
-        let content = read_file_content(&file.path);
-
-        // Extract function definitions
-        for (name, line_num) in extract_functions(&content, &file.path) {
-            if !is_special_function(&name) {
-                all_definitions.insert(
-                    name.clone(),
-                    (file.path.clone(), line_num, "function".to_string()),
-                );
-            }
-        }
+    let (items, reachability) = find_dead_code_reachable_in(&context, path, entry_points, options);
+    print_reachability(&reachability, entry_points);
+    print_dead_code_results(&items, false);
+    Ok(items)
+}
 
-        // Extract class definitions
-        for (name, line_num) in extract_classes(&content, &file.path) {
-            all_definitions.insert(
-                name.clone(),
-                (file.path.clone(), line_num, "class/struct".to_string()),
-            );
-        }
+/// Print the title of the dead code report
+pub fn print_dead_code_header() {
+    println!("{}", "Dead Code Detection".cyan().bold());
+    println!("{}", theme::rule(30).cyan());
+    println!();
+}
 
-        // Count all identifier references
-        for ref_name in extract_identifier_references(&content) {
-            *all_references.entry(ref_name).or_insert(0) += 1;
-        }
+/// Print which entry points reachability started from, or warn that none matched
+pub fn print_reachability(reachability: &Reachability, entry_points: &[EntryPoint]) {
+    if reachability.roots.is_empty() {
+        let specs: Vec<&str> = entry_points.iter().map(|ep| ep.spec.as_str()).collect();
+        eprintln!(
+            "{} no function matches the entry points ({}); showing reference-count results",
+            "Warning:".yellow().bold(),
+            specs.join(", ")
+        );
+    } else {
+        println!(
+            "Entry points: {} ({} functions reachable)",
+            reachability.roots.join(", ").green(),
+            reachability.reachable.len()
+        );
+        println!();
     }
+}
+
+/// Find dead code and return the results (shared implementation)
+pub fn find_dead_code(
+    path: &Path,
+    extensions: Option<&[String]>,
+    exclude: Option<&[String]>,
+    options: &DeadCodeOptions,
+) -> Result<Vec<DeadCodeItem>, Box<dyn std::error::Error>> {
+    let context = ExtractionContext::build(path, extensions, exclude)?;
+    Ok(find_dead_code_in(&context, options))
+}
+
+/// Where a name is defined: file, line and item type
+type Definition = (String, usize, String);
+
+/// Find dead code using an already built extraction context
+///
+/// Public items are skipped unless `options.include_public`, which reports them
+/// at low confidence. Files are analyzed in parallel; the results are the same
+/// as a sequential run (see `test_parallel_matches_sequential`).
+pub fn find_dead_code_in(context: &ExtractionContext, options: &DeadCodeOptions) -> Vec<DeadCodeItem> {
+    let mut dead_code_items: Vec<DeadCodeItem> = Vec::new();
+    let mut all_definitions: HashMap<String, Definition> = HashMap::new();
+    let all_references = context.reference_counts();
+    let public = public_definitions(context);
+
+    // First pass: collect all definitions. Shards are merged in path order, so a
+    // name defined in several files keeps the last one, as a sequential scan would.
+    let shards: Vec<Vec<(String, Definition)>> = context
+        .files()
+        .par_iter()
+        .map(|file| {
+            let functions = file
+                .functions
+                .iter()
+                .filter(|(name, _)| !is_special_function(name))
+                .map(|(name, line_num)| (name.clone(), (file.path.clone(), *line_num, "function".to_string())));
+            let classes = file
+                .classes
+                .iter()
+                .map(|(name, line_num)| (name.clone(), (file.path.clone(), *line_num, "class/struct".to_string())));
+            functions.chain(classes).collect()
+        })
+        .collect();
+    all_definitions.extend(shards.into_iter().flatten());
 
     // Second pass: find definitions with low reference count
     for (name, (file, line, item_type)) in &all_definitions {
         let ref_count = all_references.get(name).copied().unwrap_or(0);
-        
-        if ref_count <= 1 {
+
+        if public.contains(&(file.clone(), *line)) {
+            if options.include_public && ref_count <= 1 {
+                dead_code_items.push(DeadCodeItem {
+                    file: file.clone(),
+                    line_number: *line,
+                    item_type: item_type.clone(),
+                    name: name.clone(),
+                    reason: PUBLIC_REASON.to_string(),
+                    confidence: Confidence::Low,
+                });
+            }
+        } else if ref_count <= 1 {
             dead_code_items.push(DeadCodeItem {
                 file: file.clone(),
                 line_number: *line,
                 item_type: item_type.clone(),
                 name: name.clone(),
                 reason: "Only defined, never used elsewhere".to_string(),
+                confidence: Confidence::High,
             });
         } else if ref_count == 2 && item_type == "function" {
             dead_code_items.push(DeadCodeItem {
@@ -113,29 +189,126 @@ pub fn find_dead_code(
                 item_type: item_type.clone(),
                 name: name.clone(),
                 reason: "Used only once - consider inlining".to_string(),
+                confidence: Confidence::Medium,
             });
         }
     }
+    downgrade_dynamic(&mut dead_code_items, &context.dynamic_names());
 
-    // Third pass: detect other dead code patterns
-    for file in &files {
-        let content = read_file_content(&file.path);
-        detect_dead_code_patterns(&file.path, &content, &mut dead_code_items);
-        detect_unused_variables(&file.path, &content, &mut dead_code_items);
-        detect_unreachable_code(&file.path, &content, &mut dead_code_items);
-        detect_empty_functions(&file.path, &content, &mut dead_code_items);
-        detect_todo_fixme(&file.path, &content, &mut dead_code_items);
-    }
+    // Third pass: detect other dead code patterns, one file per task
+    let patterns: Vec<DeadCodeItem> = context
+        .files()
+        .par_iter()
+        .flat_map_iter(|file| {
+            let content = &file.content;
+            let mut items = Vec::new();
+            detect_dead_code_patterns_with(&file.path, content, &file.identifiers, &mut items);
+            detect_unused_variables_with(&file.path, content, &file.identifiers, &mut items);
+            detect_unreachable_code(&file.path, content, &mut items);
+            detect_empty_functions(&file.path, content, &mut items);
+            detect_unused_parameters(&file.path, content, &mut items);
+            detect_todo_fixme(&file.path, content, &mut items);
+            items
+        })
+        .collect();
+    dead_code_items.extend(patterns);
 
     // Sort by file and line number
     dead_code_items.sort_by(|a, b| {
         a.file.cmp(&b.file).then(a.line_number.cmp(&b.line_number))
     });
 
-    Ok(dead_code_items)
+    dead_code_items
+}
+
+/// Like [`find_dead_code_in`], refined by reachability over the call graph
+///
+/// Functions reachable from an entry point are never reported, however few
+/// references they have. Unreachable functions are reported even when they are
+/// referenced, since their callers are dead too. Functions only referenced
+/// without a call (e.g. passed as callbacks) count as unreachable unless an entry
+/// point names them. When no function matches an entry point, the plain results
+/// are returned unchanged.
+pub fn find_dead_code_reachable_in(
+    context: &ExtractionContext,
+    root: &Path,
+    entry_points: &[EntryPoint],
+    options: &DeadCodeOptions,
+) -> (Vec<DeadCodeItem>, Reachability) {
+    let mut items = find_dead_code_in(context, options);
+    let graph = build_call_graph_in(context);
+    let reachability = Reachability::compute(&graph, root, entry_points);
+    if reachability.roots.is_empty() {
+        return (items, reachability);
+    }
+
+    items.retain(|item| item.item_type != "function" || !reachability.is_reachable(&item.name));
+    let mut flagged = HashSet::new();
+    for item in items.iter_mut().filter(|item| item.item_type == "function") {
+        item.reason = format!("{} (not reachable from any entry point)", item.reason);
+        flagged.insert(item.name.clone());
+    }
+
+    let public = public_definitions(context);
+    let mut unreachable: Vec<_> = graph
+        .nodes
+        .values()
+        .filter(|node| {
+            !reachability.is_reachable(&node.function_name)
+                && !flagged.contains(&node.function_name)
+                && !is_special_function(&node.function_name)
+                && (options.include_public || !public.contains(&(node.file_path.clone(), node.line)))
+        })
+        .collect();
+    unreachable.sort_by(|a, b| a.function_name.cmp(&b.function_name));
+    items.extend(unreachable.into_iter().map(|node| {
+        let is_public = public.contains(&(node.file_path.clone(), node.line));
+        DeadCodeItem {
+            file: node.file_path.clone(),
+            line_number: node.line,
+            item_type: "function".to_string(),
+            name: node.function_name.clone(),
+            reason: if is_public { PUBLIC_REASON } else { "Not reachable from any entry point" }.to_string(),
+            confidence: if is_public { Confidence::Low } else { Confidence::High },
+        }
+    }));
+    downgrade_dynamic(&mut items, &context.dynamic_names());
+
+    items.sort_by(|a, b| a.file.cmp(&b.file).then(a.line_number.cmp(&b.line_number)));
+    (items, reachability)
 }
 
-fn print_dead_code_results(items: &[DeadCodeItem]) {
+/// Reason given to public definitions, reported with `include_public`
+const PUBLIC_REASON: &str = "Public, never used in the scanned tree (callers may live outside it)";
+
+/// Reason given to definitions whose name appears in a string or dynamic-call marker
+const DYNAMIC_REASON: &str = "referenced dynamically (string match)";
+
+/// Lower the confidence of unused functions and classes that may be invoked dynamically
+///
+/// Names used through `getattr`, dispatch tables, serde renames and the like
+/// only show up in strings, so plain reference counting misses them.
+fn downgrade_dynamic(items: &mut [DeadCodeItem], dynamic_names: &HashSet<&str>) {
+    for item in items.iter_mut() {
+        let definition = matches!(item.item_type.as_str(), "function" | "class/struct");
+        if definition && dynamic_names.contains(item.name.as_str()) {
+            item.confidence = Confidence::Low;
+            item.reason = DYNAMIC_REASON.to_string();
+        }
+    }
+}
+
+/// Add one reference for every identifier that `content` mentions
+///
+/// Counts are per file: an identifier used several times in one file counts once.
+pub fn count_references(content: &str, references: &mut HashMap<String, usize>) {
+    for ref_name in extract_identifier_references(content) {
+        *references.entry(ref_name).or_insert(0) += 1;
+    }
+}
+
+/// Print dead code items grouped by file, then counts per type; `quiet` prints only the counts
+pub fn print_dead_code_results(items: &[DeadCodeItem], quiet: bool) {
     if items.is_empty() {
         println!("{}", "No obvious dead code detected!".green().bold());
     } else {
@@ -146,43 +319,171 @@ fn print_dead_code_results(items: &[DeadCodeItem]) {
                 .bold()
         );
         println!();
-
-        let mut current_file = String::new();
-        for item in items {
-            if item.file != current_file {
-                current_file = item.file.clone();
-                println!("{}", format!("[{}]", current_file).cyan());
-            }
-            println!(
-                "   {} L{}: {} '{}' - {}",
-                match item.item_type.as_str() {
-                    "function" => "[fn]",
-                    "class/struct" => "[cls]",
-                    "variable" => "[var]",
-                    "import" => "[imp]",
-                    "unreachable" => "[!]",
-                    "empty" => "[∅]",
-                    "todo" => "[?]",
-                    "parameter" => "[prm]",
-                    _ => "[-]",
-                },
-                format!("{:4}", item.line_number).yellow(),
-                item.item_type.blue(),
-                item.name.green(),
-                item.reason.dimmed()
-            );
+        if !quiet {
+            print_dead_code_items(items);
         }
 
-        println!();
         println!("{}", "Summary:".cyan().bold());
-        
-        let mut type_counts: HashMap<String, usize> = HashMap::new();
+        for (item_type, count) in &DeadCodeSummary::from_items(items).by_type {
+            println!("   {} {}: {}", theme::bullet().dimmed(), item_type, count);
+        }
+    }
+}
+
+fn print_dead_code_items(items: &[DeadCodeItem]) {
+    let mut current_file = String::new();
+    let empty_tag = format!("[{}]", theme::glyph(Glyph::Empty));
+    for item in items {
+        if item.file != current_file {
+            current_file = item.file.clone();
+            println!("{}", theme::link(format!("[{}]", current_file).cyan(), &current_file, None));
+        }
+        let confidence = match item.confidence {
+            Confidence::High => String::new(),
+            other => format!(" ({} confidence)", other.as_str()),
+        };
+        println!(
+            "   {} L{}: {} '{}' - {}{}",
+            match item.item_type.as_str() {
+                "function" => "[fn]",
+                "class/struct" => "[cls]",
+                "variable" => "[var]",
+                "import" => "[imp]",
+                "unreachable" => "[!]",
+                "empty" => empty_tag.as_str(),
+                "todo" => "[?]",
+                "parameter" => "[prm]",
+                _ => "[-]",
+            },
+            theme::link(format!("{:4}", item.line_number).yellow(), &item.file, Some(item.line_number)),
+            item.item_type.blue(),
+            item.name.green(),
+            item.reason.dimmed(),
+            confidence.dimmed()
+        );
+    }
+    println!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item<'a>(items: &'a [DeadCodeItem], name: &str) -> &'a DeadCodeItem {
+        items.iter().find(|item| item.name == name).unwrap_or_else(|| panic!("{name} not reported"))
+    }
+
+    #[test]
+    fn test_name_only_in_getattr_is_low_confidence() {
+        let context = ExtractionContext::from_sources([
+            ("plugins.py", "def export_csv(rows):\n    return rows\n\ndef unused_helper():\n    return 1\n"),
+            ("main.py", "import plugins\n\nhandler = getattr(plugins, \"export_csv\")\n"),
+        ]);
+        let items = find_dead_code_in(&context, &DeadCodeOptions::default());
+
+        let dynamic = item(&items, "export_csv");
+        assert_eq!(dynamic.confidence, Confidence::Low);
+        assert_eq!(dynamic.reason, "referenced dynamically (string match)");
+        assert_eq!(item(&items, "unused_helper").confidence, Confidence::High);
+    }
+
+    #[test]
+    fn test_ruby_send_symbol_is_low_confidence() {
+        let context = ExtractionContext::from_sources([
+            ("job.rb", "def archive_all\n  1\nend\n\ndef run(obj)\n  obj.send(:archive_all)\nend\n"),
+        ]);
+        let items = find_dead_code_in(&context, &DeadCodeOptions::default());
+        assert_eq!(item(&items, "archive_all").confidence, Confidence::Low);
+    }
+
+    #[test]
+    fn test_public_items_need_include_public() {
+        let context = ExtractionContext::from_sources([(
+            "lib.rs",
+            "pub fn api_entry() -> u32 {\n    7\n}\n\nfn private_unused() -> u32 {\n    8\n}\n",
+        )]);
+
+        let items = find_dead_code_in(&context, &DeadCodeOptions::default());
+        assert!(items.iter().all(|item| item.name != "api_entry"), "{items:?}");
+        assert_eq!(item(&items, "private_unused").confidence, Confidence::High);
+
+        let items = find_dead_code_in(&context, &DeadCodeOptions { include_public: true });
+        assert_eq!(item(&items, "api_entry").confidence, Confidence::Low);
+        assert_eq!(item(&items, "private_unused").confidence, Confidence::High);
+    }
+
+    #[test]
+    fn test_report_json_has_items_and_summary() {
+        let context = ExtractionContext::from_sources([(
+            "app.py",
+            "import os\n\ndef unused_helper():\n    return 1\n\ndef other_unused():\n    return 2\n",
+        )]);
+        let report = DeadCodeReport::new(find_dead_code_in(&context, &DeadCodeOptions::default()));
+        let json: serde_json::Value =
+            serde_json::from_str(&crate::schema::to_versioned_json(&report, crate::schema::SchemaVersion::current()).unwrap()).unwrap();
+
+        assert_eq!(json["schema_version"], crate::types::SCHEMA_VERSION);
+        let items = json["dead_code"].as_array().unwrap();
+        assert_eq!(json["total_items"], items.len());
         for item in items {
-            *type_counts.entry(item.item_type.clone()).or_insert(0) += 1;
+            for field in ["file", "line_number", "item_type", "name", "reason"] {
+                assert!(item.get(field).is_some(), "{field} missing from {item}");
+            }
         }
-        
-        for (item_type, count) in &type_counts {
-            println!("   {} {}: {}", "•".dimmed(), item_type, count);
+        assert!(json["summary"]["by_type"]["function"].as_u64().unwrap() >= 2);
+        let total: u64 = json["summary"]["by_type"].as_object().unwrap().values().map(|v| v.as_u64().unwrap()).sum();
+        assert_eq!(total as usize, items.len());
+    }
+
+    #[test]
+    fn test_dead_code_item_round_trip_and_display() {
+        let item = DeadCodeItem {
+            file: "src/lib.rs".to_string(),
+            line_number: 12,
+            item_type: "function".to_string(),
+            name: "unused".to_string(),
+            reason: "never called".to_string(),
+            confidence: Confidence::High,
+        };
+        let json = serde_json::to_string(&item).unwrap();
+        assert!(json.contains("\"confidence\":\"high\""));
+        assert_eq!(serde_json::from_str::<DeadCodeItem>(&json).unwrap(), item);
+        assert_eq!(item.to_string(), "src/lib.rs:12: function `unused` never called (high confidence)");
+    }
+
+    #[cfg(feature = "schemars")]
+    #[test]
+    fn test_dead_code_item_schema_lists_fields() {
+        let schema = serde_json::to_value(schemars::schema_for!(DeadCodeItem)).unwrap();
+        for field in ["file", "line_number", "item_type", "name", "confidence"] {
+            assert!(schema["properties"].get(field).is_some(), "{field} missing from {schema}");
         }
     }
+
+    /// Run `f` on a rayon pool of `threads` threads
+    fn with_threads<T: Send>(threads: usize, f: impl FnOnce() -> T + Send) -> T {
+        rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap().install(f)
+    }
+
+    #[test]
+    #[ignore = "benchmark-style: scans the repository's own sources; run with --ignored"]
+    fn test_parallel_matches_sequential() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let context = ExtractionContext::build(root, None, Some(&["target".to_string(), ".git".to_string()])).unwrap();
+        let options = DeadCodeOptions { include_public: true };
+        let sorted = |mut items: Vec<DeadCodeItem>| {
+            items.sort_by(|a, b| (&a.file, a.line_number, &a.item_type, &a.name).cmp(&(&b.file, b.line_number, &b.item_type, &b.name)));
+            items
+        };
+
+        let started = std::time::Instant::now();
+        let sequential = sorted(with_threads(1, || find_dead_code_in(&context, &options)));
+        let sequential_time = started.elapsed();
+        let started = std::time::Instant::now();
+        let parallel = sorted(with_threads(8, || find_dead_code_in(&context, &options)));
+        eprintln!("{} files: sequential {sequential_time:?}, parallel {:?}", context.files().len(), started.elapsed());
+
+        assert!(!sequential.is_empty());
+        assert_eq!(sequential, parallel);
+    }
 }
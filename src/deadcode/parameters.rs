@@ -0,0 +1,641 @@
+//! Unused parameter detection
+//!
+//! Flags function parameters that are never mentioned in the function body.
+//! Supports Rust, Python and JavaScript/TypeScript; destructured bindings are
+//! checked one by one.
+
+use super::types::{Confidence, DeadCodeItem};
+use crate::lines::line_number_at;
+use crate::parser::{find_block, get_file_extension, BlockSpan};
+use regex::Regex;
+use std::sync::OnceLock;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum ParamLanguage {
+    Rust,
+    Python,
+    JavaScript,
+}
+
+impl ParamLanguage {
+    pub(super) fn from_extension(ext: &str) -> Option<Self> {
+        match ext {
+            "rs" => Some(Self::Rust),
+            "py" | "pyw" | "pyi" => Some(Self::Python),
+            "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "mts" | "cts" => Some(Self::JavaScript),
+            _ => None,
+        }
+    }
+}
+
+/// Decorators that pin a method's signature to a base class
+const FIXED_SIGNATURE_DECORATORS: &[&str] = &[
+    "override",
+    "overrides",
+    "typing.override",
+    "typing_extensions.override",
+    "abstractmethod",
+    "abc.abstractmethod",
+];
+
+/// Bodies that only mark a method as not implemented
+const STUB_BODIES: &[&str] = &[
+    "pass",
+    "...",
+    "raise NotImplementedError",
+    "raise NotImplementedError()",
+    "todo!()",
+    "unimplemented!()",
+];
+
+/// Words that look like a method header in JS/TS but start a statement
+const JS_STATEMENT_KEYWORDS: &[&str] = &[
+    "if", "for", "while", "switch", "catch", "return", "function", "typeof", "await", "new", "super",
+    "with", "yield", "delete", "void",
+];
+
+/// TypeScript modifiers that turn a constructor parameter into a field
+const TS_PARAMETER_PROPERTIES: &[&str] = &["public", "private", "protected", "readonly", "override"];
+
+/// A function definition found in the source
+pub(super) struct FunctionHeader {
+    pub(super) name: String,
+    /// Start of the line holding the definition
+    pub(super) line_start: usize,
+    /// Byte range of the parameter list, inside the parentheses
+    pub(super) params: (usize, usize),
+    pub(super) body: BlockSpan,
+}
+
+/// A name bound by a parameter
+#[derive(Debug)]
+struct Binding {
+    name: String,
+    /// Byte offset in the file
+    offset: usize,
+    /// Bound by a plain positional parameter, not by destructuring
+    positional: bool,
+    /// Index of the parameter it belongs to
+    index: usize,
+}
+
+/// Detect function parameters that are never referenced in the function body
+///
+/// Parameters starting with `_` are skipped, as are functions whose signature is
+/// fixed elsewhere: methods of `impl Trait for Type` blocks and trait definitions,
+/// `extern` functions, `override` methods and methods decorated with `@override`,
+/// `@overrides` or `@abstractmethod`. In JS/TS only parameters after the last used
+/// one are reported, since earlier ones can't be dropped from a callback signature.
+pub fn detect_unused_parameters(file_path: &str, content: &str, items: &mut Vec<DeadCodeItem>) {
+    let Some(lang) = ParamLanguage::from_extension(get_file_extension(file_path)) else {
+        return;
+    };
+    let fixed_spans = if lang == ParamLanguage::Rust { rust_fixed_signature_spans(content) } else { Vec::new() };
+
+    for header in function_headers(content, file_path, lang) {
+        if fixed_spans.iter().any(|span| span.start <= header.line_start && header.line_start < span.end) {
+            continue;
+        }
+        if has_fixed_signature(content, &header) {
+            continue;
+        }
+        let body = &content[header.body.start..header.body.end];
+        if is_stub_body(body) {
+            continue;
+        }
+
+        let bindings = parameter_bindings(content, header.params, lang);
+        let last_used_positional = bindings
+            .iter()
+            .filter(|b| b.positional && mentions_word(body, &b.name))
+            .map(|b| b.index)
+            .max();
+
+        for binding in &bindings {
+            if mentions_word(body, &binding.name) {
+                continue;
+            }
+            if lang == ParamLanguage::JavaScript
+                && binding.positional
+                && last_used_positional.is_some_and(|last| binding.index < last)
+            {
+                continue;
+            }
+            items.push(DeadCodeItem {
+                file: file_path.to_string(),
+                line_number: line_number_at(content, binding.offset),
+                item_type: "parameter".to_string(),
+                name: binding.name.clone(),
+                reason: format!("Parameter of '{}' is never used in its body", header.name),
+                confidence: Confidence::Medium,
+            });
+        }
+    }
+}
+
+fn rust_fn_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"\bfn\s+(\w+)").expect("valid fn regex"))
+}
+
+fn python_def_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"(?m)^[ \t]*(?:async[ \t]+)?def[ \t]+(\w+)[ \t]*\(").expect("valid def regex"))
+}
+
+fn js_function_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"\bfunction\b\s*\*?\s*(\w*)\s*(?:<[^>(]*>)?\s*\(").expect("valid function regex"))
+}
+
+fn js_method_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"(?m)^[ \t]*(?:(?:public|private|protected|static|async|override|readonly|get|set)[ \t]+)*\*?(\w+)[ \t]*(?:<[^>(]*>)?[ \t]*\(")
+            .expect("valid method regex")
+    })
+}
+
+fn js_arrow_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"\)\s*(?::[^=;{()]*)?=>\s*\{").expect("valid arrow regex"))
+}
+
+fn impl_or_trait_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"(?m)^[ \t]*(?:pub(?:\([^)]*\))?[ \t]+)?(?:unsafe[ \t]+)?(?:impl\b[^{;]*\bfor\b|trait[ \t]+\w+)")
+            .expect("valid impl regex")
+    })
+}
+
+/// Bodies of `impl Trait for Type` blocks and trait definitions
+pub(super) fn rust_fixed_signature_spans(content: &str) -> Vec<BlockSpan> {
+    impl_or_trait_regex()
+        .find_iter(content)
+        .filter_map(|m| find_block(content, m.start(), "lib.rs"))
+        .collect()
+}
+
+pub(super) fn function_headers(content: &str, file_path: &str, lang: ParamLanguage) -> Vec<FunctionHeader> {
+    let header = |name: &str, at: usize, open: usize, gap: fn(&str) -> bool| -> Option<FunctionHeader> {
+        let close = matching_paren(content, open)?;
+        let body = find_block(content, close + 1, file_path)?;
+        if !gap(&content[close + 1..body.start.saturating_sub(1).max(close + 1)]) {
+            return None;
+        }
+        Some(FunctionHeader {
+            name: name.to_string(),
+            line_start: content[..at].rfind('\n').map_or(0, |n| n + 1),
+            params: (open + 1, close),
+            body,
+        })
+    };
+    let any_gap: fn(&str) -> bool = |_| true;
+
+    let mut headers = Vec::new();
+    match lang {
+        ParamLanguage::Rust => {
+            for caps in rust_fn_regex().captures_iter(content) {
+                let name = caps.get(1).expect("name group");
+                let Some(open) = rust_params_open(content, name.end()) else { continue };
+                headers.extend(header(name.as_str(), name.start(), open, any_gap));
+            }
+        }
+        ParamLanguage::Python => {
+            for caps in python_def_regex().captures_iter(content) {
+                let name = caps.get(1).expect("name group");
+                let open = caps.get(0).expect("whole match").end() - 1;
+                headers.extend(header(name.as_str(), name.start(), open, any_gap));
+            }
+        }
+        ParamLanguage::JavaScript => {
+            // Only a return type annotation may sit between `)` and `{`
+            let annotation_gap: fn(&str) -> bool = |gap| {
+                let gap = gap.trim();
+                gap.is_empty() || (gap.starts_with(':') && !gap.contains([';', '=']))
+            };
+            for caps in js_function_regex().captures_iter(content) {
+                let whole = caps.get(0).expect("whole match");
+                let name = caps.get(1).map_or("", |m| m.as_str());
+                let name = if name.is_empty() { "<anonymous>" } else { name };
+                headers.extend(header(name, whole.start(), whole.end() - 1, annotation_gap));
+            }
+            for caps in js_method_regex().captures_iter(content) {
+                let whole = caps.get(0).expect("whole match");
+                let name = caps.get(1).expect("name group").as_str();
+                if JS_STATEMENT_KEYWORDS.contains(&name) {
+                    continue;
+                }
+                headers.extend(header(name, whole.start(), whole.end() - 1, annotation_gap));
+            }
+            for arrow in js_arrow_regex().find_iter(content) {
+                let Some(open) = matching_open_paren(content, arrow.start()) else { continue };
+                let name = arrow_name(&content[..open]).unwrap_or("<anonymous>");
+                let arrow_gap: fn(&str) -> bool = |gap| gap.contains("=>");
+                headers.extend(header(name, open, open, arrow_gap));
+            }
+            headers.sort_by_key(|h| h.params.0);
+            headers.dedup_by_key(|h| h.params.0);
+        }
+    }
+    headers
+}
+
+/// The `(` opening the parameter list after a Rust function name, past any generics
+fn rust_params_open(content: &str, from: usize) -> Option<usize> {
+    let bytes = content.as_bytes();
+    let mut angle = 0usize;
+    for (i, &b) in bytes.iter().enumerate().skip(from) {
+        match b {
+            b'<' => angle += 1,
+            b'>' if i > 0 && bytes[i - 1] == b'-' => {}
+            b'>' => angle = angle.saturating_sub(1),
+            b'(' if angle == 0 => return Some(i),
+            b'{' | b';' => return None,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Index of the `)` closing the `(` at `open`
+pub(super) fn matching_paren(content: &str, open: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    for (i, b) in content.bytes().enumerate().skip(open) {
+        match b {
+            b'(' => depth += 1,
+            b')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Index of the `(` matching the `)` at `close`
+fn matching_open_paren(content: &str, close: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    for (i, &b) in content.as_bytes()[..=close].iter().enumerate().rev() {
+        match b {
+            b')' => depth += 1,
+            b'(' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Name an arrow function is assigned to (`const name = (...) =>` or `name: (...) =>`)
+fn arrow_name(before: &str) -> Option<&str> {
+    let before = before.trim_end();
+    let before = before.strip_suffix("async").unwrap_or(before).trim_end();
+    let before = before.strip_suffix(['=', ':'])?.trim_end();
+    let start = before.rfind(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$')).map_or(0, |i| i + 1);
+    let name = &before[start..];
+    (!name.is_empty()).then_some(name)
+}
+
+/// Whether the definition line or its decorators pin the signature
+pub(super) fn has_fixed_signature(content: &str, header: &FunctionHeader) -> bool {
+    let prefix = &content[header.line_start..header.params.0];
+    if prefix.split(|c: char| !c.is_alphanumeric()).any(|word| word == "override" || word == "extern") {
+        return true;
+    }
+
+    content[..header.line_start]
+        .lines()
+        .rev()
+        .map(str::trim)
+        .skip_while(|line| line.is_empty())
+        .take_while(|line| line.starts_with('@') || line.starts_with("#["))
+        .filter_map(|line| line.strip_prefix('@'))
+        .any(|decorator| {
+            let name = decorator.split('(').next().unwrap_or(decorator).trim();
+            FIXED_SIGNATURE_DECORATORS.contains(&name)
+        })
+}
+
+fn is_stub_body(body: &str) -> bool {
+    let lines: Vec<&str> = body
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with("//"))
+        .collect();
+    let lines = match lines.first() {
+        // A docstring followed by the stub
+        Some(first) if first.starts_with("\"\"\"") || first.starts_with("'''") => &lines[1..],
+        _ => &lines[..],
+    };
+    matches!(lines, [only] if STUB_BODIES.contains(only))
+}
+
+/// Whether `word` appears in `text` with no identifier characters around it
+fn mentions_word(text: &str, word: &str) -> bool {
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_' || c == '$';
+    text.match_indices(word).any(|(i, _)| {
+        let before = text[..i].chars().next_back();
+        let after = text[i + word.len()..].chars().next();
+        !before.is_some_and(is_ident) && !after.is_some_and(is_ident)
+    })
+}
+
+/// Split `text` at commas outside brackets, keeping each part's offset
+pub(super) fn split_top_level(text: &str) -> Vec<(usize, &str)> {
+    let bytes = text.as_bytes();
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, &b) in bytes.iter().enumerate() {
+        match b {
+            b'(' | b'[' | b'{' => depth += 1,
+            b'<' if i == 0 || !matches!(bytes[i - 1], b'=' | b' ') => depth += 1,
+            b')' | b']' | b'}' => depth = depth.saturating_sub(1),
+            b'>' if i > 0 && !matches!(bytes[i - 1], b'=' | b'-' | b' ') => depth = depth.saturating_sub(1),
+            b',' if depth == 0 => {
+                parts.push((start, &text[start..i]));
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push((start, &text[start..]));
+    parts
+}
+
+/// Position of the first `c` outside brackets, ignoring `::` for Rust paths
+fn find_top_level(text: &str, targets: &[char]) -> Option<usize> {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let mut depth = 0usize;
+    for (n, &(i, c)) in chars.iter().enumerate() {
+        match c {
+            '(' | '[' | '{' | '<' => depth += 1,
+            ')' | ']' | '}' | '>' => depth = depth.saturating_sub(1),
+            ':' if chars.get(n + 1).is_some_and(|&(_, next)| next == ':')
+                || (n > 0 && chars[n - 1].1 == ':') => {}
+            c if depth == 0 && targets.contains(&c) => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn parameter_bindings(content: &str, params: (usize, usize), lang: ParamLanguage) -> Vec<Binding> {
+    let text = &content[params.0..params.1];
+    let mut bindings = Vec::new();
+    for (index, (offset, param)) in split_top_level(text).into_iter().enumerate() {
+        let base = params.0 + offset;
+        let names = match lang {
+            ParamLanguage::Rust => rust_bindings(param),
+            ParamLanguage::Python => python_binding(param).into_iter().map(|n| (n, true)).collect(),
+            ParamLanguage::JavaScript => js_bindings(param),
+        };
+        for (name, positional) in names {
+            if name.starts_with('_') {
+                continue;
+            }
+            let offset = param.find(&name).map_or(base, |i| base + i);
+            bindings.push(Binding { name, offset, positional, index });
+        }
+    }
+    bindings
+}
+
+/// Names bound by a Rust parameter pattern (`x: T`, `mut x: T`, `(a, b): (T, U)`, `Point { x, y }: Point`)
+pub(super) fn rust_bindings(param: &str) -> Vec<(String, bool)> {
+    let pattern = find_top_level(param, &[':']).map_or(param, |i| &param[..i]).trim();
+    if pattern.is_empty() || pattern.split(|c: char| !c.is_alphanumeric()).any(|w| w == "self") {
+        return Vec::new();
+    }
+
+    let positional = pattern.chars().all(|c| c.is_alphanumeric() || c == '_' || c == ' ' || c == '&')
+        && pattern.split_whitespace().filter(|w| !matches!(*w, "mut" | "ref")).count() == 1;
+    let mut names = Vec::new();
+    let mut rest = pattern;
+    while let Some(start) = rest.find(|c: char| c.is_alphabetic() || c == '_') {
+        let len = rest[start..].find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(rest.len() - start);
+        let word = &rest[start..start + len];
+        rest = &rest[start + len..];
+        // Struct field names (`field: binding`), type names and keywords aren't bindings
+        let is_field = rest.trim_start().starts_with(':') && !rest.trim_start().starts_with("::");
+        let is_path = rest.starts_with("::");
+        if is_field || is_path || matches!(word, "mut" | "ref") || word.starts_with(char::is_uppercase) {
+            continue;
+        }
+        names.push((word.to_string(), positional));
+    }
+    names
+}
+
+/// Name bound by a Python parameter (`x`, `x: int = 1`, `*args`, `**kwargs`)
+pub(super) fn python_binding(param: &str) -> Option<String> {
+    let param = param.trim().trim_start_matches('*');
+    let end = param.find([':', '=']).unwrap_or(param.len());
+    let name = param[..end].trim();
+    let valid = !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_');
+    (valid && name != "self" && name != "cls").then(|| name.to_string())
+}
+
+/// Names bound by a JS/TS parameter, flagged as positional unless destructured
+pub(super) fn js_bindings(param: &str) -> Vec<(String, bool)> {
+    let param = param.trim();
+    let first_word = param.split_whitespace().next().unwrap_or("");
+    if TS_PARAMETER_PROPERTIES.contains(&first_word) {
+        return Vec::new();
+    }
+    let param = param.trim_start_matches("...");
+    if param.starts_with(['{', '[']) {
+        let mut names = Vec::new();
+        destructured_names(param, &mut names);
+        return names.into_iter().map(|n| (n, false)).collect();
+    }
+
+    let end = param.find([':', '=', '?']).unwrap_or(param.len());
+    let name = param[..end].trim();
+    let valid = !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '$');
+    if valid && name != "this" {
+        vec![(name.to_string(), true)]
+    } else {
+        Vec::new()
+    }
+}
+
+/// Collect the bindings of a destructuring pattern (`{ a, b: c, d = 1, ...rest }`, `[x, y]`)
+fn destructured_names(pattern: &str, names: &mut Vec<String>) {
+    let pattern = pattern.trim();
+    let Some(open) = pattern.chars().next().filter(|c| matches!(c, '{' | '[')) else {
+        let end = pattern.find(['=', ':']).unwrap_or(pattern.len());
+        let name = pattern[..end].trim().trim_start_matches("...");
+        if !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '$') {
+            names.push(name.to_string());
+        }
+        return;
+    };
+    let close = if open == '{' { '}' } else { ']' };
+    let inner_end = split_top_level(pattern)
+        .first()
+        .and_then(|(_, whole)| whole.rfind(close))
+        .unwrap_or(pattern.len());
+    let inner = &pattern[1..inner_end.max(1)];
+
+    for (_, element) in split_top_level(inner) {
+        let element = element.trim().trim_start_matches("...");
+        if element.is_empty() {
+            continue;
+        }
+        match (open, find_top_level(element, &[':'])) {
+            // `key: binding` renames; the binding may itself be a pattern
+            ('{', Some(colon)) => destructured_names(&element[colon + 1..], names),
+            _ => destructured_names(element, names),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unused(file: &str, content: &str) -> Vec<String> {
+        let mut items = Vec::new();
+        detect_unused_parameters(file, content, &mut items);
+        assert!(items.iter().all(|i| i.item_type == "parameter"));
+        items.into_iter().map(|i| i.name).collect()
+    }
+
+    #[test]
+    fn test_rust_parameters() {
+        let content = r#"
+fn add(a: i32, b: i32, _scale: i32) -> i32 {
+    a + 1
+}
+
+fn apply<F: Fn(i32) -> i32>(f: F, (x, y): (i32, i32), Point { px, py: renamed }: Point) -> i32 {
+    f(x) + renamed
+}
+
+impl Greeter {
+    fn greet(&self, name: &str, loud: bool) -> String {
+        format!("hi {name}")
+    }
+}
+
+fn declared(value: u8);
+"#;
+        assert_eq!(unused("lib.rs", content), vec!["b", "y", "px", "loud"]);
+    }
+
+    #[test]
+    fn test_rust_trait_impls_and_definitions_skipped() {
+        let content = r#"
+trait Visitor {
+    fn visit(&mut self, node: &Node) {}
+}
+
+impl Visitor for Printer {
+    fn visit(&mut self, node: &Node) {
+        println!("visited");
+    }
+}
+
+impl<T> From<T> for Wrapper where T: Clone {
+    fn from(value: T) -> Self {
+        Wrapper
+    }
+}
+
+extern "C" fn callback(data: *mut u8) {}
+"#;
+        assert!(unused("lib.rs", content).is_empty());
+    }
+
+    #[test]
+    fn test_python_parameters() {
+        let content = r#"
+def handler(request, verbose=False, *args, _unused=None, **kwargs):
+    return request.body
+
+class Service:
+    def run(self, job, retries: int = 3):
+        """Run a job."""
+        return job()
+
+    @classmethod
+    def build(cls, config):
+        return cls()
+
+    @override
+    def close(self, reason):
+        return None
+
+    @abstractmethod
+    def reset(self, state):
+        return None
+
+    def todo(self, later):
+        raise NotImplementedError
+"#;
+        assert_eq!(unused("svc.py", content), vec!["verbose", "args", "kwargs", "retries", "config"]);
+    }
+
+    #[test]
+    fn test_javascript_parameters() {
+        let content = r#"
+function render({ title, body: content, footer = "", ...rest }, [first, second], options) {
+  return title + content + first + options;
+}
+
+const onEvent = (event, context) => {
+  return event.type;
+};
+
+items.forEach(function (item, index) {
+  console.log(index);
+});
+
+class Store {
+  save(key, value) {
+    if (key) {
+      return key;
+    }
+  }
+
+  override load(key) {
+    return null;
+  }
+}
+"#;
+        assert_eq!(unused("app.js", content), vec!["footer", "rest", "second", "context", "value"]);
+    }
+
+    #[test]
+    fn test_typescript_parameter_properties_and_annotations() {
+        let content = r#"
+class Api {
+  constructor(private readonly client: Client, timeout: number) {
+    this.ready = true;
+  }
+
+  fetch<T>(path: string, retries?: number): Promise<T> {
+    return this.client.get(path);
+  }
+}
+"#;
+        assert_eq!(unused("api.ts", content), vec!["timeout", "retries"]);
+    }
+
+    #[test]
+    fn test_word_boundary_references() {
+        // `id` only appears inside `identifier`, so it is unused
+        let content = "fn lookup(id: u32, identifier: &str) -> usize {\n    identifier.len()\n}\n";
+        assert_eq!(unused("lib.rs", content), vec!["id"]);
+    }
+}
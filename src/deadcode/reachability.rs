@@ -0,0 +1,171 @@
+//! Entry-point reachability for dead code detection
+//!
+//! Entry points are written `path::function`, relative to the analyzed root,
+//! with globs allowed on both sides (`src/routes.rs::register_*`, `**::main`).
+//! A bare function name matches in any file.
+
+use crate::callgraph::CallGraph;
+use crate::pathglob::compile_glob;
+use globset::GlobMatcher;
+use std::collections::{HashSet, VecDeque};
+use std::path::Path;
+
+/// Used when reachability is requested but no entry points are configured
+pub const DEFAULT_ENTRY_POINTS: [&str; 1] = ["**::main"];
+
+/// A configured entry point, compiled for matching
+#[derive(Debug, Clone)]
+pub struct EntryPoint {
+    pub spec: String,
+    file: Option<GlobMatcher>,
+    function: GlobMatcher,
+}
+
+impl EntryPoint {
+    /// Parse `path::function`, or a bare `function` matching in any file
+    pub fn parse(spec: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let spec = spec.trim();
+        let (file, function) = match spec.rsplit_once("::") {
+            Some((file, function)) => (Some(file), function),
+            None => (None, spec),
+        };
+        if function.is_empty() || file.is_some_and(str::is_empty) {
+            return Err(format!("Invalid entry point '{spec}' (expected path::function)").into());
+        }
+        Ok(Self {
+            spec: spec.to_string(),
+            file: file.map(compile_glob).transpose()?,
+            function: compile_glob(function)?,
+        })
+    }
+
+    /// Parse every spec, stopping at the first invalid one
+    pub fn parse_all<S: AsRef<str>>(specs: &[S]) -> Result<Vec<Self>, Box<dyn std::error::Error>> {
+        specs.iter().map(|s| Self::parse(s.as_ref())).collect()
+    }
+
+    /// Whether `function`, defined in `file` (relative, `/`-separated), is this entry point
+    pub fn matches(&self, file: &str, function: &str) -> bool {
+        self.function.is_match(function) && self.file.as_ref().is_none_or(|glob| glob.is_match(file))
+    }
+}
+
+/// Functions reachable from the entry points
+#[derive(Debug, Clone, Default)]
+pub struct Reachability {
+    /// Functions matched by an entry point
+    pub roots: Vec<String>,
+    /// Roots and everything they call, transitively
+    pub reachable: HashSet<String>,
+}
+
+impl Reachability {
+    /// Walk `graph` from every function matching one of `entry_points`
+    ///
+    /// File paths in the graph are matched relative to `root`.
+    pub fn compute(graph: &CallGraph, root: &Path, entry_points: &[EntryPoint]) -> Self {
+        let mut roots: Vec<String> = graph
+            .nodes
+            .values()
+            .filter(|node| {
+                let file = relative_path(root, &node.file_path);
+                entry_points.iter().any(|ep| ep.matches(&file, &node.function_name))
+            })
+            .map(|node| node.function_name.clone())
+            .collect();
+        roots.sort();
+
+        let mut reachable: HashSet<String> = roots.iter().cloned().collect();
+        let mut queue: VecDeque<String> = roots.iter().cloned().collect();
+        while let Some(function) = queue.pop_front() {
+            for callee in graph.get_callees(&function) {
+                if reachable.insert(callee.clone()) {
+                    queue.push_back(callee);
+                }
+            }
+        }
+        Self { roots, reachable }
+    }
+
+    pub fn is_reachable(&self, function: &str) -> bool {
+        self.reachable.contains(function)
+    }
+}
+
+fn relative_path(root: &Path, file: &str) -> String {
+    Path::new(file)
+        .strip_prefix(root)
+        .map(|p| p.to_string_lossy().replace('\\', "/"))
+        .unwrap_or_else(|_| file.replace('\\', "/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deadcode::{find_dead_code_in, find_dead_code_reachable_in, DeadCodeItem, DeadCodeOptions};
+    use crate::parser::ExtractionContext;
+    use std::path::PathBuf;
+
+    fn fixture_root() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/deadcode_reachability")
+    }
+
+    fn flagged_functions(items: &[DeadCodeItem]) -> Vec<&str> {
+        let mut names: Vec<&str> = items
+            .iter()
+            .filter(|item| item.item_type == "function")
+            .map(|item| item.name.as_str())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_parse_and_match() {
+        let ep = EntryPoint::parse("src/routes.rs::register_*").unwrap();
+        assert!(ep.matches("src/routes.rs", "register_users"));
+        assert!(!ep.matches("src/routes.rs", "users"));
+        assert!(!ep.matches("src/other.rs", "register_users"));
+
+        let ep = EntryPoint::parse("main").unwrap();
+        assert!(ep.matches("anything/lib.rs", "main"));
+
+        let ep = EntryPoint::parse(DEFAULT_ENTRY_POINTS[0]).unwrap();
+        assert!(ep.matches("src/bin/tool.rs", "main"));
+        assert!(ep.matches("main.rs", "main"));
+
+        assert!(EntryPoint::parse("src/main.rs::").is_err());
+        assert!(EntryPoint::parse("::main").is_err());
+    }
+
+    #[test]
+    fn test_helper_reachable_only_via_main_is_suppressed() {
+        let root = fixture_root();
+        let context = ExtractionContext::build(&root, None, None).unwrap();
+
+        // Reference counts alone flag the helpers that main calls
+        let plain = find_dead_code_in(&context, &DeadCodeOptions::default());
+        assert!(flagged_functions(&plain).contains(&"build_report"));
+
+        let entry_points = EntryPoint::parse_all(&["src/main.rs::main", "src/routes.rs::register_*"]).unwrap();
+        let (items, reachability) = find_dead_code_reachable_in(&context, &root, &entry_points, &DeadCodeOptions::default());
+        assert_eq!(reachability.roots, vec!["main", "register_users"]);
+        assert!(reachability.is_reachable("format_line"));
+        assert!(reachability.is_reachable("load_users"));
+
+        assert_eq!(flagged_functions(&items), vec!["cleanup_stale", "orphan_helper", "stale_route"]);
+        let orphan = items.iter().find(|item| item.name == "orphan_helper").unwrap();
+        assert!(orphan.reason.contains("not reachable from any entry point"));
+    }
+
+    #[test]
+    fn test_unmatched_entry_points_keep_plain_results() {
+        let root = fixture_root();
+        let context = ExtractionContext::build(&root, None, None).unwrap();
+        let entry_points = EntryPoint::parse_all(&["src/missing.rs::main"]).unwrap();
+
+        let (items, reachability) = find_dead_code_reachable_in(&context, &root, &entry_points, &DeadCodeOptions::default());
+        assert!(reachability.roots.is_empty());
+        assert_eq!(flagged_functions(&items), flagged_functions(&find_dead_code_in(&context, &DeadCodeOptions::default())));
+    }
+}
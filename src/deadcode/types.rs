@@ -1,13 +1,104 @@
 //! Data types for dead code detection
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
 
 /// Dead code detection result
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct DeadCodeItem {
     pub file: String,
     pub line_number: usize,
     pub item_type: String,
     pub name: String,
     pub reason: String,
+    /// How likely the item really is dead
+    pub confidence: Confidence,
+}
+
+/// Dead code items with counts per type and confidence
+///
+/// The document printed by `deadcode --format json` and returned by the MCP
+/// `detect_deadcode` tool.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct DeadCodeReport {
+    pub dead_code: Vec<DeadCodeItem>,
+    pub total_items: usize,
+    pub summary: DeadCodeSummary,
+}
+
+/// Item counts of a [`DeadCodeReport`], keyed by `item_type` and by confidence
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct DeadCodeSummary {
+    pub by_type: BTreeMap<String, usize>,
+    pub by_confidence: BTreeMap<String, usize>,
+}
+
+impl DeadCodeSummary {
+    pub fn from_items(items: &[DeadCodeItem]) -> Self {
+        let mut summary = Self::default();
+        for item in items {
+            *summary.by_type.entry(item.item_type.clone()).or_insert(0) += 1;
+            *summary.by_confidence.entry(item.confidence.as_str().to_string()).or_insert(0) += 1;
+        }
+        summary
+    }
+}
+
+impl DeadCodeReport {
+    pub fn new(items: Vec<DeadCodeItem>) -> Self {
+        Self {
+            total_items: items.len(),
+            summary: DeadCodeSummary::from_items(&items),
+            dead_code: items,
+        }
+    }
+}
+
+/// Options for dead code detection
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DeadCodeOptions {
+    /// Also report public items (Rust `pub`, Python `__all__`, JS exports) at low
+    /// confidence; by default they are skipped, since their users may live outside
+    /// the scanned tree
+    pub include_public: bool,
+}
+
+/// How likely a reported item really is dead
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum Confidence {
+    Low,
+    Medium,
+    High,
+}
+
+impl Confidence {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Confidence::Low => "low",
+            Confidence::Medium => "medium",
+            Confidence::High => "high",
+        }
+    }
+}
+
+impl fmt::Display for Confidence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Display for DeadCodeItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {} `{}` {} ({} confidence)",
+            self.file, self.line_number, self.item_type, self.name, self.reason, self.confidence
+        )
+    }
 }
@@ -0,0 +1,143 @@
+//! Public API detection
+//!
+//! Items other crates or modules can import are used outside the scanned tree,
+//! so a missing reference says little about them. Public means `pub` (but not
+//! `pub(crate)` and friends) in Rust, listed in `__all__` in Python, and
+//! `export`ed or assigned to `module.exports`/`exports` in JavaScript/TypeScript.
+
+use crate::parser::{get_file_extension, ExtractionContext, FileExtraction};
+use rayon::prelude::*;
+use regex::Regex;
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+fn python_all_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"(?m)^__all__\s*\+?=\s*[\[(]([^\])]*)[\])]").expect("valid __all__ regex"))
+}
+
+fn quoted_name_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r#"["'](\w+)["']"#).expect("valid quoted name regex"))
+}
+
+fn js_export_list_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"(?:\bexport\s*|\bmodule\.exports\s*=\s*)\{([^}]*)\}").expect("valid export list regex")
+    })
+}
+
+fn js_export_assignment_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"\b(?:module\.)?exports\.(\w+)\s*=|\bmodule\.exports\s*=\s*(\w+)\s*;?\s*$")
+            .expect("valid export assignment regex")
+    })
+}
+
+/// Definitions, as (file, line), that belong to a file's public API
+pub(super) fn public_definitions(context: &ExtractionContext) -> HashSet<(String, usize)> {
+    context
+        .files()
+        .par_iter()
+        .flat_map_iter(|file| {
+            let exported = exported_names(file);
+            let lines: Vec<&str> = file.content.lines().collect();
+            file.functions
+                .iter()
+                .chain(&file.classes)
+                .filter(|(name, line)| {
+                    let definition = line.checked_sub(1).and_then(|n| lines.get(n)).copied().unwrap_or_default();
+                    exported.contains(name.as_str()) || is_public_definition(&file.path, definition)
+                })
+                .map(|(_, line)| (file.path.clone(), *line))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Whether the definition line itself makes the item public
+fn is_public_definition(path: &str, definition: &str) -> bool {
+    let definition = definition.trim_start();
+    match get_file_extension(path) {
+        // `pub(crate)`, `pub(super)` and `pub(in path)` stay inside the crate
+        "rs" => definition.strip_prefix("pub").is_some_and(|rest| rest.starts_with(char::is_whitespace)),
+        "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "mts" | "cts" => {
+            definition.strip_prefix("export").is_some_and(|rest| rest.starts_with(char::is_whitespace))
+        }
+        _ => false,
+    }
+}
+
+/// Names a file exports by listing them: Python's `__all__`, JS export lists and `exports` assignments
+fn exported_names(file: &FileExtraction) -> HashSet<&str> {
+    let content = file.content.as_str();
+    let mut names = HashSet::new();
+    match get_file_extension(&file.path) {
+        "py" | "pyw" | "pyi" => {
+            for list in python_all_regex().captures_iter(content) {
+                let list = list.get(1).expect("list group").as_str();
+                names.extend(quoted_name_regex().captures_iter(list).map(|c| c.get(1).expect("name group").as_str()));
+            }
+        }
+        "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "mts" | "cts" => {
+            for list in js_export_list_regex().captures_iter(content) {
+                // `{ a, b as c, d: e }` exports the local names a, b and e
+                for entry in list.get(1).expect("list group").as_str().split(',') {
+                    let entry = entry.trim();
+                    let local = entry.rsplit(':').next().unwrap_or(entry).split_whitespace().next().unwrap_or("");
+                    if !local.is_empty() {
+                        names.insert(local);
+                    }
+                }
+            }
+            for line in content.lines() {
+                for caps in js_export_assignment_regex().captures_iter(line) {
+                    names.extend(caps.get(1).or(caps.get(2)).map(|m| m.as_str()));
+                }
+            }
+        }
+        _ => {}
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn public_names(path: &str, content: &str) -> Vec<String> {
+        let context = ExtractionContext::from_sources([(path, content)]);
+        let public = public_definitions(&context);
+        let file = &context.files()[0];
+        let mut names: Vec<String> = file
+            .functions
+            .iter()
+            .chain(&file.classes)
+            .filter(|(_, line)| public.contains(&(file.path.clone(), *line)))
+            .map(|(name, _)| name.clone())
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    #[test]
+    fn test_rust_visibility() {
+        let content = "pub fn api() {}\npub(crate) fn internal() {}\nfn private() {}\npub struct Config;\npub async fn fetch() {}\n";
+        assert_eq!(public_names("lib.rs", content), vec!["Config", "api", "fetch"]);
+    }
+
+    #[test]
+    fn test_python_all_exports() {
+        let content = "__all__ = [\"load\", 'Parser']\n\ndef load():\n    pass\n\ndef helper():\n    pass\n\nclass Parser:\n    pass\n";
+        assert_eq!(public_names("api.py", content), vec!["Parser", "load"]);
+    }
+
+    #[test]
+    fn test_js_exports() {
+        let content = "export function render() {}\nfunction parse() {}\nfunction format() {}\nfunction hidden() {}\nfunction legacy() {}\nexport { parse as parseInput };\nmodule.exports = { format };\nexports.legacy = legacy;\n";
+        assert_eq!(public_names("index.js", content), vec!["format", "legacy", "parse", "render"]);
+    }
+}
@@ -2,9 +2,13 @@
 //!
 //! Provides dependency graph construction and analysis for codebases.
 
+use crate::lines::split_lines;
+use crate::options::has_extension;
+use crate::theme;
+use colored::*;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
-use std::path::{Path, PathBuf};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Component, Path, PathBuf};
 use walkdir::WalkDir;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,6 +30,40 @@ pub struct DependencyEdge {
     pub from: String,
     pub to: String,
     pub edge_type: EdgeType,
+    /// The import statement in `from` that produced this edge
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub site: Option<ImportSite>,
+}
+
+/// An import statement: its 1-based line and trimmed text
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ImportSite {
+    pub line: usize,
+    pub text: String,
+}
+
+/// Files that import a target, directly or through other files
+#[derive(Debug, Clone, Serialize)]
+pub struct Dependents {
+    pub target: String,
+    pub direct: Vec<DirectDependent>,
+    pub transitive: Vec<TransitiveDependent>,
+}
+
+/// A file importing the target itself
+#[derive(Debug, Clone, Serialize)]
+pub struct DirectDependent {
+    pub file: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub site: Option<ImportSite>,
+}
+
+/// A file reaching the target through `via`, `depth` imports away
+#[derive(Debug, Clone, Serialize)]
+pub struct TransitiveDependent {
+    pub file: String,
+    pub depth: usize,
+    pub via: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -48,7 +86,58 @@ impl DependencyGraph {
     }
 
     pub fn add_edge(&mut self, from: String, to: String, edge_type: EdgeType) {
-        self.edges.push(DependencyEdge { from, to, edge_type });
+        self.edges.push(DependencyEdge { from, to, edge_type, site: None });
+    }
+
+    /// Look up a node by path, comparing canonical paths so `./src/a.rs` and `src/a.rs` agree
+    pub fn find_node(&self, path: &Path) -> Option<&str> {
+        if let Some((key, _)) = self.nodes.get_key_value(path.to_string_lossy().as_ref()) {
+            return Some(key);
+        }
+        let wanted = path.canonicalize().ok()?;
+        self.nodes
+            .keys()
+            .find(|key| Path::new(key).canonicalize().is_ok_and(|p| p == wanted))
+            .map(String::as_str)
+    }
+
+    /// Files importing `target`, walking reverse import edges breadth-first
+    ///
+    /// `max_depth` of 1 stops at direct dependents; `None` follows every chain.
+    /// Each file is reported once, at its shortest distance.
+    pub fn dependents_of(&self, target: &str, max_depth: Option<usize>) -> Dependents {
+        let mut reverse: HashMap<&str, Vec<&DependencyEdge>> = HashMap::new();
+        for edge in self.edges.iter().filter(|e| e.edge_type == EdgeType::Import && e.from != e.to) {
+            reverse.entry(edge.to.as_str()).or_default().push(edge);
+        }
+        for edges in reverse.values_mut() {
+            edges.sort_by(|a, b| a.from.cmp(&b.from));
+        }
+
+        let mut dependents = Dependents { target: target.to_string(), direct: Vec::new(), transitive: Vec::new() };
+        let mut seen: HashSet<&str> = HashSet::from([target]);
+        let mut queue = VecDeque::from([(target, 0)]);
+        while let Some((node, depth)) = queue.pop_front() {
+            if max_depth.is_some_and(|max| depth >= max) {
+                continue;
+            }
+            for edge in reverse.get(node).into_iter().flatten() {
+                if !seen.insert(edge.from.as_str()) {
+                    continue;
+                }
+                if depth == 0 {
+                    dependents.direct.push(DirectDependent { file: edge.from.clone(), site: edge.site.clone() });
+                } else {
+                    dependents.transitive.push(TransitiveDependent {
+                        file: edge.from.clone(),
+                        depth: depth + 1,
+                        via: node.to_string(),
+                    });
+                }
+                queue.push_back((edge.from.as_str(), depth + 1));
+            }
+        }
+        dependents
     }
 
     pub fn get_dependencies(&self, path: &str) -> Vec<String> {
@@ -172,6 +261,68 @@ impl DependencyGraph {
     }
 }
 
+impl Dependents {
+    /// Every file found, direct dependents first
+    pub fn files(&self) -> impl Iterator<Item = &str> {
+        self.direct.iter().map(|d| d.file.as_str()).chain(self.transitive.iter().map(|t| t.file.as_str()))
+    }
+
+    /// The reverse-dependency subtree as DOT, edges pointing from importer to imported
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph Dependents {\n");
+        dot.push_str("  rankdir=LR;\n");
+        dot.push_str("  node [shape=box];\n\n");
+        dot.push_str(&format!("  \"{}\" [style=filled, fillcolor=lightyellow];\n", self.target));
+        for file in self.files() {
+            dot.push_str(&format!("  \"{}\";\n", file));
+        }
+        dot.push('\n');
+        for direct in &self.direct {
+            dot.push_str(&format!("  \"{}\" -> \"{}\" [color=blue];\n", direct.file, self.target));
+        }
+        for transitive in &self.transitive {
+            dot.push_str(&format!("  \"{}\" -> \"{}\" [color=gray];\n", transitive.file, transitive.via));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// Print dependents grouped as direct (with their import line) and transitive
+pub fn print_dependents(dependents: &Dependents) {
+    println!("{}", format!("Dependents of {}", dependents.target).cyan().bold());
+    println!("{}", theme::rule(30).cyan());
+
+    if dependents.direct.is_empty() {
+        println!("{}", "No files import it.".green());
+        return;
+    }
+
+    println!("{}", format!("Direct ({}):", dependents.direct.len()).yellow().bold());
+    for direct in &dependents.direct {
+        match &direct.site {
+            Some(site) => println!(
+                "  {}:{}  {}",
+                theme::link(direct.file.blue(), &direct.file, Some(site.line)),
+                site.line.to_string().yellow(),
+                site.text.dimmed()
+            ),
+            None => println!("  {}", theme::link(direct.file.blue(), &direct.file, None)),
+        }
+    }
+
+    if !dependents.transitive.is_empty() {
+        println!("\n{}", format!("Transitive ({}):", dependents.transitive.len()).yellow().bold());
+        for transitive in &dependents.transitive {
+            println!(
+                "  {}  {}",
+                theme::link(transitive.file.blue(), &transitive.file, None),
+                format!("(depth {}, via {})", transitive.depth, transitive.via).dimmed()
+            );
+        }
+    }
+}
+
 pub fn build_dependency_graph(
     path: &Path,
     extensions: Option<&[String]>,
@@ -199,40 +350,35 @@ pub fn build_dependency_graph(
     let files: Vec<PathBuf> = walker
         .filter(|entry| {
             let file_path = entry.path();
-            if let Some(exts) = extensions {
-                if let Some(ext) = file_path.extension().and_then(|s| s.to_str()) {
-                    exts.iter().any(|e| e == ext)
-                } else {
-                    false
-                }
-            } else {
-                true
+            match extensions {
+                Some(exts) => has_extension(file_path, exts),
+                None => true,
             }
         })
         .map(|e| e.path().to_path_buf())
         .collect();
 
+    let mut sites = Vec::new();
     for file in &files {
-        if let Ok(node) = extract_dependencies(file) {
+        if let Ok((node, file_sites)) = extract_dependencies(file) {
             graph.add_node(node);
+            sites.push((file, file_sites));
         }
     }
 
-    for file in &files {
+    let mut linked = HashSet::new();
+    for (file, file_sites) in sites {
         let file_str = file.to_string_lossy().to_string();
-        let imports: Vec<String> = if let Some(node) = graph.nodes.get(&file_str) {
-            node.imports.clone()
-        } else {
-            Vec::new()
-        };
-        
-        for import in imports {
+        for (import, site) in file_sites {
             if let Some(target_path) = resolve_import(&import, file, &files) {
-                graph.add_edge(
-                    file_str.clone(),
-                    target_path,
-                    EdgeType::Import,
-                );
+                if linked.insert((file_str.clone(), target_path.clone())) {
+                    graph.edges.push(DependencyEdge {
+                        from: file_str.clone(),
+                        to: target_path,
+                        edge_type: EdgeType::Import,
+                        site: Some(site),
+                    });
+                }
             }
         }
     }
@@ -240,10 +386,14 @@ pub fn build_dependency_graph(
     Ok(graph)
 }
 
-fn extract_dependencies(path: &Path) -> Result<DependencyNode, Box<dyn std::error::Error>> {
+/// An imported path and the line that imports it
+type Import = (String, ImportSite);
+
+fn extract_dependencies(path: &Path) -> Result<(DependencyNode, Vec<Import>), Box<dyn std::error::Error>> {
     let content = std::fs::read_to_string(path)?;
     let ext = path.extension().and_then(|s| s.to_str()).unwrap_or("");
-    let imports = extract_imports_from_content(&content, ext);
+    let sites = extract_imports_from_content(&content, ext);
+    let imports = sites.iter().map(|(import, _)| import.clone()).collect();
     let exports = extract_exports_from_content(&content, ext);
 
     let module_name = path
@@ -252,15 +402,17 @@ fn extract_dependencies(path: &Path) -> Result<DependencyNode, Box<dyn std::erro
         .unwrap_or("unknown")
         .to_string();
 
-    Ok(DependencyNode {
+    let node = DependencyNode {
         path: path.to_string_lossy().to_string(),
         module_name,
         imports,
         exports,
-    })
+    };
+    Ok((node, sites))
 }
 
-fn extract_imports_from_content(content: &str, ext: &str) -> Vec<String> {
+/// Imports in `content`, each with the statement it came from
+fn extract_imports_from_content(content: &str, ext: &str) -> Vec<Import> {
     let mut imports = Vec::new();
 
     let patterns = match ext {
@@ -273,10 +425,11 @@ fn extract_imports_from_content(content: &str, ext: &str) -> Vec<String> {
 
     for pattern in patterns {
         if let Ok(re) = regex::Regex::new(pattern) {
-            for line in content.lines() {
+            for (index, line) in split_lines(content).enumerate() {
                 if let Some(caps) = re.captures(line) {
                     if let Some(import) = caps.get(1) {
-                        imports.push(import.as_str().to_string());
+                        let site = ImportSite { line: index + 1, text: line.trim().to_string() };
+                        imports.push((import.as_str().to_string(), site));
                     }
                 }
             }
@@ -298,7 +451,7 @@ fn extract_exports_from_content(content: &str, ext: &str) -> Vec<String> {
 
     for pattern in patterns {
         if let Ok(re) = regex::Regex::new(pattern) {
-            for line in content.lines() {
+            for line in split_lines(content) {
                 if let Some(caps) = re.captures(line) {
                     if let Some(export) = caps.get(1) {
                         exports.push(export.as_str().to_string());
@@ -311,15 +464,76 @@ fn extract_exports_from_content(content: &str, ext: &str) -> Vec<String> {
     exports
 }
 
-fn resolve_import(import: &str, _current_file: &Path, all_files: &[PathBuf]) -> Option<String> {
-    for file in all_files {
-        if let Some(stem) = file.file_stem().and_then(|s| s.to_str()) {
-            if import.contains(stem) {
-                return Some(file.to_string_lossy().to_string());
+/// Resolve an import to one of `all_files`
+///
+/// Relative imports (`./util`, `../lib/date`) are joined to the importing
+/// file's directory. Module paths (`crate::utils::date`, `pkg.utils.date`,
+/// `github.com/x/utils`) match files whose path ends with the longest prefix
+/// of their segments, so `crate::utils::date::format` finds `utils/date.rs`.
+/// Ties go to the file sharing the most directories with the importer.
+fn resolve_import(import: &str, current_file: &Path, all_files: &[PathBuf]) -> Option<String> {
+    let candidates: Vec<(&PathBuf, Vec<String>)> = all_files
+        .iter()
+        .filter(|file| file.as_path() != current_file)
+        .map(|file| (file, module_segments(file)))
+        .collect();
+
+    if import.starts_with("./") || import.starts_with("../") {
+        let base = current_file.parent().unwrap_or(Path::new(""));
+        let wanted = module_segments(&normalize(&base.join(import)));
+        return candidates
+            .iter()
+            .find(|(_, segments)| *segments == wanted)
+            .map(|(file, _)| file.to_string_lossy().to_string());
+    }
+
+    let segments: Vec<&str> = import
+        .split([':', '.', '/'])
+        .filter(|s| !s.is_empty() && !matches!(*s, "crate" | "self" | "super"))
+        .collect();
+    let current: Vec<String> = module_segments(current_file);
+    (1..=segments.len()).rev().find_map(|len| {
+        let prefix = &segments[..len];
+        candidates
+            .iter()
+            .filter(|(_, path)| path.len() >= len && path[path.len() - len..].iter().zip(prefix).all(|(a, b)| a.as_str() == *b))
+            .max_by_key(|(file, path)| {
+                let shared = path.iter().zip(&current).take_while(|(a, b)| a == b).count();
+                (shared, std::cmp::Reverse(file.as_path()))
+            })
+            .map(|(file, _)| file.to_string_lossy().to_string())
+    })
+}
+
+/// Path components without the extension, dropping `mod`/`index`/`__init__` file names
+fn module_segments(path: &Path) -> Vec<String> {
+    let mut segments: Vec<String> = path
+        .with_extension("")
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(part) => Some(part.to_string_lossy().to_string()),
+            _ => None,
+        })
+        .collect();
+    if segments.last().is_some_and(|last| matches!(last.as_str(), "mod" | "index" | "__init__")) {
+        segments.pop();
+    }
+    segments
+}
+
+/// Collapse `.` and `..` without touching the filesystem
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
             }
+            other => out.push(other),
         }
     }
-    None
+    out
 }
 
 #[cfg(test)]
@@ -385,4 +599,74 @@ mod tests {
         let cycles = graph.find_circular_dependencies();
         assert!(!cycles.is_empty());
     }
+
+    fn chain_fixture() -> (tempfile::TempDir, DependencyGraph) {
+        let dir = tempfile::TempDir::new().unwrap();
+        let src = dir.path().join("src");
+        std::fs::create_dir(&src).unwrap();
+        std::fs::write(src.join("a.rs"), "use crate::b::run;\n\npub fn main() { run(); }\n").unwrap();
+        std::fs::write(src.join("b.rs"), "// b\nuse crate::c::helper;\n\npub fn run() { helper(); }\n").unwrap();
+        std::fs::write(src.join("c.rs"), "pub fn helper() {}\n").unwrap();
+        let graph = build_dependency_graph(dir.path(), None, None).unwrap();
+        (dir, graph)
+    }
+
+    #[test]
+    fn test_dependents_direct_and_transitive() {
+        let (dir, graph) = chain_fixture();
+        let target = graph.find_node(&dir.path().join("src/c.rs")).unwrap().to_string();
+
+        let dependents = graph.dependents_of(&target, None);
+        assert_eq!(dependents.direct.len(), 1);
+        assert!(dependents.direct[0].file.ends_with("b.rs"));
+        assert_eq!(
+            dependents.direct[0].site,
+            Some(ImportSite { line: 2, text: "use crate::c::helper;".to_string() })
+        );
+        assert_eq!(dependents.transitive.len(), 1);
+        assert!(dependents.transitive[0].file.ends_with("a.rs"));
+        assert_eq!(dependents.transitive[0].depth, 2);
+        assert_eq!(dependents.transitive[0].via, dependents.direct[0].file);
+
+        let direct_only = graph.dependents_of(&target, Some(1));
+        assert_eq!(direct_only.direct.len(), 1);
+        assert!(direct_only.transitive.is_empty());
+
+        let dot = dependents.to_dot();
+        assert!(dot.contains(&format!("\"{}\" -> \"{}\"", dependents.direct[0].file, target)));
+        assert!(dot.contains(&format!("\"{}\" -> \"{}\"", dependents.transitive[0].file, dependents.direct[0].file)));
+    }
+
+    #[test]
+    fn test_dependents_of_root_file_is_empty() {
+        let (dir, graph) = chain_fixture();
+        let target = graph.find_node(&dir.path().join("src/a.rs")).unwrap().to_string();
+        let dependents = graph.dependents_of(&target, None);
+        assert!(dependents.direct.is_empty() && dependents.transitive.is_empty());
+    }
+
+    #[test]
+    fn test_find_node_unknown_file() {
+        let (dir, graph) = chain_fixture();
+        assert!(graph.find_node(&dir.path().join("src/missing.rs")).is_none());
+    }
+
+    #[test]
+    fn test_resolve_import_by_module_path() {
+        let files: Vec<PathBuf> = ["src/a.rs", "src/utils/date.rs", "src/utils/mod.rs", "web/lib/date.js", "web/app.js"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+        let from = Path::new("src/main.rs");
+
+        // "crate" contains "a" but must not resolve to a.rs
+        assert_eq!(resolve_import("crate::utils::date::format", from, &files).as_deref(), Some("src/utils/date.rs"));
+        assert_eq!(resolve_import("crate::utils", from, &files).as_deref(), Some("src/utils/mod.rs"));
+        assert_eq!(resolve_import("serde::Serialize", from, &files), None);
+        assert_eq!(
+            resolve_import("./lib/date", Path::new("web/app.js"), &files).as_deref(),
+            Some("web/lib/date.js")
+        );
+        assert_eq!(resolve_import("../lib/date", Path::new("web/app.js"), &files), None);
+    }
 }
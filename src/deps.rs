@@ -0,0 +1,770 @@
+//! Manifest Dependencies
+//!
+//! `codesearch deps` reads the dependencies declared in `Cargo.toml`,
+//! `package.json` and `pyproject.toml`, and cross-references them with the
+//! imports of the source files each manifest covers: those under its directory
+//! and not under a nested manifest of the same ecosystem.
+//!
+//! With `--unused`, declared dependencies that are never imported are reported,
+//! and so are imports of packages nobody declared. Declarations whose use an
+//! import scan can't see (build dependencies, proc-macro crates, dev tools run
+//! from scripts) are reported in categories of their own rather than as unused.
+
+use crate::config::DepsConfig;
+use crate::gitignore::GitIgnore;
+use crate::lines::split_lines;
+use crate::theme;
+use colored::*;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use walkdir::WalkDir;
+
+/// Directories never scanned: vendored packages and build output import
+/// things the project itself doesn't declare
+const SKIPPED_DIRS: &[&str] = &[".git", "node_modules", "target", ".venv", "venv", "__pycache__", "dist", "build"];
+
+/// Crate roots that are never dependencies
+const RUST_BUILTINS: &[&str] = &["std", "core", "alloc", "crate", "self", "super", "proc_macro", "test"];
+
+/// Node.js built-in modules, importable with or without `node:`
+const NODE_BUILTINS: &[&str] = &[
+    "assert", "async_hooks", "buffer", "child_process", "cluster", "console", "constants", "crypto", "dgram",
+    "diagnostics_channel", "dns", "domain", "events", "fs", "http", "http2", "https", "inspector", "module", "net",
+    "os", "path", "perf_hooks", "process", "punycode", "querystring", "readline", "repl", "stream", "string_decoder",
+    "timers", "tls", "trace_events", "tty", "url", "util", "v8", "vm", "wasi", "worker_threads", "zlib",
+];
+
+/// Python standard library modules commonly imported
+const PYTHON_STDLIB: &[&str] = &[
+    "__future__", "abc", "argparse", "array", "ast", "asyncio", "base64", "binascii", "bisect", "builtins", "bz2",
+    "calendar", "cmath", "codecs", "collections", "concurrent", "configparser", "contextlib", "contextvars", "copy",
+    "csv", "ctypes", "dataclasses", "datetime", "decimal", "difflib", "dis", "email", "enum", "errno", "fcntl",
+    "filecmp", "fnmatch", "fractions", "ftplib", "functools", "gc", "getpass", "gettext", "glob", "gzip", "hashlib",
+    "heapq", "hmac", "html", "http", "imaplib", "importlib", "inspect", "io", "ipaddress", "itertools", "json",
+    "keyword", "locale", "logging", "lzma", "math", "mimetypes", "multiprocessing", "numbers", "operator", "os",
+    "pathlib", "pickle", "pkgutil", "platform", "pprint", "queue", "random", "re", "secrets", "select", "shlex",
+    "shutil", "signal", "smtplib", "socket", "sqlite3", "ssl", "stat", "statistics", "string", "struct",
+    "subprocess", "sys", "sysconfig", "tarfile", "tempfile", "textwrap", "threading", "time", "timeit", "tkinter",
+    "token", "tokenize", "tomllib", "traceback", "types", "typing", "unicodedata", "unittest", "urllib", "uuid",
+    "venv", "warnings", "weakref", "xml", "zipfile", "zlib", "zoneinfo",
+];
+
+/// Python distributions whose module has another name; extended by `[deps] aliases`
+const PYTHON_ALIASES: &[(&str, &[&str])] = &[
+    ("beautifulsoup4", &["bs4"]),
+    ("pillow", &["PIL"]),
+    ("pyyaml", &["yaml"]),
+    ("scikit-learn", &["sklearn"]),
+    ("scikit-image", &["skimage"]),
+    ("python-dateutil", &["dateutil"]),
+    ("python-dotenv", &["dotenv"]),
+    ("opencv-python", &["cv2"]),
+    ("protobuf", &["google"]),
+    ("pymysql", &["pymysql"]),
+    ("attrs", &["attr", "attrs"]),
+    ("typing-extensions", &["typing_extensions"]),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Ecosystem {
+    Cargo,
+    Npm,
+    Python,
+}
+
+impl Ecosystem {
+    const ALL: [Ecosystem; 3] = [Ecosystem::Cargo, Ecosystem::Npm, Ecosystem::Python];
+
+    /// File name of this ecosystem's manifest
+    pub fn manifest_name(self) -> &'static str {
+        match self {
+            Ecosystem::Cargo => "Cargo.toml",
+            Ecosystem::Npm => "package.json",
+            Ecosystem::Python => "pyproject.toml",
+        }
+    }
+
+    /// The ecosystem whose source files have extension `ext`
+    fn of_source(ext: &str) -> Option<Self> {
+        match ext {
+            "rs" => Some(Ecosystem::Cargo),
+            "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "mts" | "cts" | "vue" | "svelte" => Some(Ecosystem::Npm),
+            "py" | "pyi" => Some(Ecosystem::Python),
+            _ => None,
+        }
+    }
+}
+
+/// Which table of the manifest a dependency is declared in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DependencyKind {
+    Normal,
+    /// `[dev-dependencies]`, `devDependencies`, Poetry and PEP 735 groups
+    Dev,
+    /// `[build-dependencies]`, `[build-system] requires`
+    Build,
+}
+
+/// A dependency declared in a manifest
+#[derive(Debug, Clone, Serialize)]
+pub struct Dependency {
+    pub name: String,
+    pub kind: DependencyKind,
+    /// Module names the package is imported as
+    pub import_names: Vec<String>,
+}
+
+/// A manifest and the dependencies it declares
+#[derive(Debug, Clone, Serialize)]
+pub struct Manifest {
+    pub path: String,
+    pub ecosystem: Ecosystem,
+    /// The package the manifest describes, which its own tests and examples import
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub package: Option<String>,
+    pub dependencies: Vec<Dependency>,
+}
+
+/// Why a declared dependency is listed as never imported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnusedCategory {
+    /// A regular dependency nothing imports: most likely safe to remove
+    Unused,
+    /// A build dependency, used by build scripts or packaging rather than imported
+    Build,
+    /// A proc-macro crate, used through `#[derive]` or attributes
+    ProcMacro,
+    /// A dev dependency no test imports, e.g. a linter or test runner run from scripts
+    Dev,
+}
+
+impl UnusedCategory {
+    fn describe(self) -> &'static str {
+        match self {
+            UnusedCategory::Unused => "Unused dependencies",
+            UnusedCategory::Build => "Build dependencies not imported (check build scripts and packaging)",
+            UnusedCategory::ProcMacro => "Proc-macro crates not imported (may be used through #[derive])",
+            UnusedCategory::Dev => "Dev dependencies not imported (may be tools run from scripts)",
+        }
+    }
+}
+
+/// A declared dependency no covered source file imports
+#[derive(Debug, Clone, Serialize)]
+pub struct UnusedDependency {
+    pub manifest: String,
+    pub ecosystem: Ecosystem,
+    pub name: String,
+    pub kind: DependencyKind,
+    pub category: UnusedCategory,
+}
+
+/// A package imported by a source file but not declared in its manifest
+#[derive(Debug, Clone, Serialize)]
+pub struct UndeclaredImport {
+    pub manifest: String,
+    pub ecosystem: Ecosystem,
+    pub module: String,
+    /// First file and 1-based line importing it
+    pub file: String,
+    pub line: usize,
+    /// Import statements naming it across all covered files
+    pub occurrences: usize,
+}
+
+/// Output of [`analyze_dependencies`]
+#[derive(Debug, Clone, Serialize)]
+pub struct DepsReport {
+    pub manifests: Vec<Manifest>,
+    pub unused: Vec<UnusedDependency>,
+    pub undeclared: Vec<UndeclaredImport>,
+}
+
+/// What the files covered by one manifest import
+#[derive(Debug, Default)]
+struct ImportScan {
+    /// Every package root referenced, including Rust paths like `serde_json::to_string`
+    used: HashSet<String>,
+    /// Package roots of import statements, with their first site and count
+    imported: BTreeMap<String, (String, usize, usize)>,
+    /// Modules defined in the covered tree, which import statements may name
+    local: HashSet<String>,
+}
+
+/// Read every manifest under `root` and cross-reference it with the imports it covers
+pub fn analyze_dependencies(
+    root: &Path,
+    exclude: Option<&[String]>,
+    config: &DepsConfig,
+) -> Result<DepsReport, Box<dyn std::error::Error>> {
+    let mut gitignore = GitIgnore::new(root);
+    let files: Vec<PathBuf> = WalkDir::new(root)
+        .into_iter()
+        .filter_entry(|e| {
+            let name = e.file_name().to_string_lossy();
+            let name: &str = &name;
+            let excluded = e.depth() > 0
+                && e.file_type().is_dir()
+                && (SKIPPED_DIRS.contains(&name) || exclude.is_some_and(|dirs| dirs.iter().any(|d| d == name)));
+            !excluded && !gitignore.is_ignored_entry(e)
+        })
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .map(|e| e.into_path())
+        .collect();
+
+    let mut manifests = Vec::new();
+    for file in &files {
+        let Some(name) = file.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        let Some(ecosystem) = Ecosystem::ALL.into_iter().find(|e| e.manifest_name() == name) else {
+            continue;
+        };
+        let content = fs::read_to_string(file)?;
+        let path = file.display().to_string();
+        let manifest = parse_manifest(ecosystem, &path, &content, config)
+            .map_err(|e| format!("Failed to parse {path}: {e}"))?;
+        manifests.push((file.parent().unwrap_or(Path::new("")).to_path_buf(), manifest));
+    }
+    manifests.sort_by(|a, b| a.1.path.cmp(&b.1.path));
+
+    // Each source file belongs to the nearest manifest of its ecosystem above it
+    let owners: HashMap<(Ecosystem, &Path), usize> = manifests
+        .iter()
+        .enumerate()
+        .map(|(i, (dir, manifest))| ((manifest.ecosystem, dir.as_path()), i))
+        .collect();
+    let mut scans: Vec<ImportScan> = manifests.iter().map(|_| ImportScan::default()).collect();
+    for file in &files {
+        let Some(ecosystem) = file.extension().and_then(|e| e.to_str()).and_then(Ecosystem::of_source) else {
+            continue;
+        };
+        let Some(&owner) = file.ancestors().skip(1).find_map(|dir| owners.get(&(ecosystem, dir))) else {
+            continue;
+        };
+        let Ok(content) = fs::read_to_string(file) else {
+            continue;
+        };
+        scan_imports(ecosystem, file, &content, &mut scans[owner]);
+    }
+
+    let mut unused = Vec::new();
+    let mut undeclared = Vec::new();
+    for ((_, manifest), scan) in manifests.iter().zip(&scans) {
+        for dependency in &manifest.dependencies {
+            if dependency.import_names.iter().any(|name| scan.used.contains(&normalize_module(manifest.ecosystem, name))) {
+                continue;
+            }
+            unused.push(UnusedDependency {
+                manifest: manifest.path.clone(),
+                ecosystem: manifest.ecosystem,
+                name: dependency.name.clone(),
+                kind: dependency.kind,
+                category: categorize(manifest.ecosystem, dependency),
+            });
+        }
+
+        let declared: HashSet<String> = manifest
+            .dependencies
+            .iter()
+            .flat_map(|d| &d.import_names)
+            .chain(manifest.package.iter())
+            .map(|name| normalize_module(manifest.ecosystem, name))
+            .collect();
+        for (module, (file, line, occurrences)) in &scan.imported {
+            if declared.contains(module) || scan.local.contains(module) || is_builtin(manifest.ecosystem, module) {
+                continue;
+            }
+            undeclared.push(UndeclaredImport {
+                manifest: manifest.path.clone(),
+                ecosystem: manifest.ecosystem,
+                module: module.clone(),
+                file: file.clone(),
+                line: *line,
+                occurrences: *occurrences,
+            });
+        }
+    }
+    unused.sort_by(|a, b| (&a.manifest, a.category, &a.name).cmp(&(&b.manifest, b.category, &b.name)));
+
+    Ok(DepsReport {
+        manifests: manifests.into_iter().map(|(_, manifest)| manifest).collect(),
+        unused,
+        undeclared,
+    })
+}
+
+/// Parse one manifest's dependency tables
+pub fn parse_manifest(
+    ecosystem: Ecosystem,
+    path: &str,
+    content: &str,
+    config: &DepsConfig,
+) -> Result<Manifest, Box<dyn std::error::Error>> {
+    let mut declared: Vec<(String, DependencyKind)> = Vec::new();
+    let package = match ecosystem {
+        Ecosystem::Cargo => {
+            let value: toml::Value = toml::from_str(content)?;
+            let mut tables = vec![&value];
+            if let Some(targets) = value.get("target").and_then(|t| t.as_table()) {
+                tables.extend(targets.values());
+            }
+            for table in tables {
+                for (key, kind) in [
+                    ("dependencies", DependencyKind::Normal),
+                    ("dev-dependencies", DependencyKind::Dev),
+                    ("build-dependencies", DependencyKind::Build),
+                ] {
+                    if let Some(deps) = table.get(key).and_then(|d| d.as_table()) {
+                        declared.extend(deps.keys().map(|name| (name.clone(), kind)));
+                    }
+                }
+            }
+            value
+                .get("lib")
+                .and_then(|lib| lib.get("name"))
+                .or_else(|| value.get("package").and_then(|p| p.get("name")))
+                .and_then(|name| name.as_str())
+                .map(str::to_string)
+        }
+        Ecosystem::Npm => {
+            let value: serde_json::Value = serde_json::from_str(content)?;
+            for (key, kind) in [
+                ("dependencies", DependencyKind::Normal),
+                ("peerDependencies", DependencyKind::Normal),
+                ("optionalDependencies", DependencyKind::Normal),
+                ("devDependencies", DependencyKind::Dev),
+            ] {
+                if let Some(deps) = value.get(key).and_then(|d| d.as_object()) {
+                    declared.extend(deps.keys().map(|name| (name.clone(), kind)));
+                }
+            }
+            value.get("name").and_then(|n| n.as_str()).map(str::to_string)
+        }
+        Ecosystem::Python => {
+            let value: toml::Value = toml::from_str(content)?;
+            let requirements = |value: Option<&toml::Value>| -> Vec<String> {
+                value
+                    .and_then(|v| v.as_array())
+                    .map(|items| items.iter().filter_map(|item| item.as_str()).filter_map(requirement_name).collect())
+                    .unwrap_or_default()
+            };
+            let project = value.get("project");
+            declared.extend(requirements(project.and_then(|p| p.get("dependencies"))).into_iter().map(|n| (n, DependencyKind::Normal)));
+            for extra in project.and_then(|p| p.get("optional-dependencies")).and_then(|o| o.as_table()).into_iter().flat_map(|t| t.values()) {
+                declared.extend(requirements(Some(extra)).into_iter().map(|n| (n, DependencyKind::Normal)));
+            }
+            for group in value.get("dependency-groups").and_then(|g| g.as_table()).into_iter().flat_map(|t| t.values()) {
+                declared.extend(requirements(Some(group)).into_iter().map(|n| (n, DependencyKind::Dev)));
+            }
+            declared.extend(requirements(value.get("build-system").and_then(|b| b.get("requires"))).into_iter().map(|n| (n, DependencyKind::Build)));
+
+            let poetry = value.get("tool").and_then(|t| t.get("poetry"));
+            let poetry_names = |table: Option<&toml::Value>| -> Vec<String> {
+                table
+                    .and_then(|t| t.as_table())
+                    .map(|t| t.keys().filter(|name| *name != "python").cloned().collect())
+                    .unwrap_or_default()
+            };
+            declared.extend(poetry_names(poetry.and_then(|p| p.get("dependencies"))).into_iter().map(|n| (n, DependencyKind::Normal)));
+            declared.extend(poetry_names(poetry.and_then(|p| p.get("dev-dependencies"))).into_iter().map(|n| (n, DependencyKind::Dev)));
+            for group in poetry.and_then(|p| p.get("group")).and_then(|g| g.as_table()).into_iter().flat_map(|t| t.values()) {
+                declared.extend(poetry_names(group.get("dependencies")).into_iter().map(|n| (n, DependencyKind::Dev)));
+            }
+
+            project
+                .or(poetry)
+                .and_then(|p| p.get("name"))
+                .and_then(|name| name.as_str())
+                .map(str::to_string)
+        }
+    };
+
+    // A name declared in several tables counts once, in its most regular kind
+    let mut seen = HashSet::new();
+    declared.sort_by_key(|(_, kind)| *kind as u8);
+    let dependencies = declared
+        .into_iter()
+        .filter(|(name, _)| seen.insert(name.clone()))
+        .map(|(name, kind)| Dependency {
+            import_names: import_names(ecosystem, &name, config),
+            name,
+            kind,
+        })
+        .collect();
+
+    Ok(Manifest {
+        path: path.to_string(),
+        ecosystem,
+        package,
+        dependencies,
+    })
+}
+
+/// Module names a declared package is imported as
+fn import_names(ecosystem: Ecosystem, name: &str, config: &DepsConfig) -> Vec<String> {
+    if let Some(aliases) = config.aliases.iter().find(|(package, _)| package.eq_ignore_ascii_case(name)).map(|(_, a)| a) {
+        return aliases.clone();
+    }
+    match ecosystem {
+        Ecosystem::Cargo => vec![name.replace('-', "_")],
+        Ecosystem::Npm => match name.strip_prefix("@types/") {
+            // `@types/node` describes a built-in; `@types/scope__pkg` types `@scope/pkg`
+            Some(typed) => match typed.split_once("__") {
+                Some((scope, package)) => vec![format!("@{scope}/{package}")],
+                None => vec![typed.to_string()],
+            },
+            None => vec![name.to_string()],
+        },
+        Ecosystem::Python => {
+            let normalized = name.to_lowercase().replace(['.', '_'], "-");
+            match PYTHON_ALIASES.iter().find(|(package, _)| *package == normalized) {
+                Some((_, modules)) => modules.iter().map(|m| m.to_string()).collect(),
+                None => vec![normalized.replace('-', "_")],
+            }
+        }
+    }
+}
+
+/// The distribution name of a PEP 508 requirement, e.g. `requests` in `requests[socks]>=2.31`
+fn requirement_name(requirement: &str) -> Option<String> {
+    let name: String = requirement
+        .trim()
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        .collect();
+    (!name.is_empty()).then_some(name)
+}
+
+/// Module names compare like this: Python modules case-insensitively
+fn normalize_module(ecosystem: Ecosystem, module: &str) -> String {
+    match ecosystem {
+        Ecosystem::Cargo => module.replace('-', "_"),
+        Ecosystem::Npm => module.to_string(),
+        Ecosystem::Python => module.to_lowercase(),
+    }
+}
+
+fn is_builtin(ecosystem: Ecosystem, module: &str) -> bool {
+    match ecosystem {
+        Ecosystem::Cargo => RUST_BUILTINS.contains(&module),
+        Ecosystem::Npm => NODE_BUILTINS.contains(&module) || module.starts_with("node:"),
+        Ecosystem::Python => PYTHON_STDLIB.contains(&module),
+    }
+}
+
+/// Where a never-imported dependency is reported
+fn categorize(ecosystem: Ecosystem, dependency: &Dependency) -> UnusedCategory {
+    match dependency.kind {
+        DependencyKind::Build => UnusedCategory::Build,
+        DependencyKind::Dev => UnusedCategory::Dev,
+        DependencyKind::Normal if ecosystem == Ecosystem::Cargo && is_proc_macro_name(&dependency.name) => {
+            UnusedCategory::ProcMacro
+        }
+        DependencyKind::Normal => UnusedCategory::Unused,
+    }
+}
+
+/// Crates named like proc-macro crates (`serde_derive`, `derive_more`, `tokio-macros`)
+fn is_proc_macro_name(name: &str) -> bool {
+    let name = name.replace('-', "_");
+    name.contains("derive") || name.ends_with("_macros") || name.ends_with("_macro") || name.ends_with("_codegen")
+}
+
+/// Record the package roots `content` imports
+fn scan_imports(ecosystem: Ecosystem, file: &Path, content: &str, scan: &mut ImportScan) {
+    let file_name = file.display().to_string();
+    let record = |module: String, line: usize, scan: &mut ImportScan| {
+        let module = normalize_module(ecosystem, &module);
+        scan.used.insert(module.clone());
+        let site = scan.imported.entry(module).or_insert_with(|| (file_name.clone(), line, 0));
+        site.2 += 1;
+    };
+
+    match ecosystem {
+        Ecosystem::Cargo => {
+            static USE: OnceLock<Regex> = OnceLock::new();
+            static PATH: OnceLock<Regex> = OnceLock::new();
+            static MOD: OnceLock<Regex> = OnceLock::new();
+            let use_re = USE.get_or_init(|| {
+                Regex::new(r"^\s*(?:pub(?:\([^)]*\))?\s+)?(?:use\s+(?:::)?|extern\s+crate\s+)(\w+)").expect("valid use regex")
+            });
+            let path_re = PATH.get_or_init(|| Regex::new(r"(?:^|[^:\w])([a-z_][a-z0-9_]*)::").expect("valid path regex"));
+            let mod_re = MOD.get_or_init(|| Regex::new(r"^\s*(?:pub(?:\([^)]*\))?\s+)?mod\s+(\w+)").expect("valid mod regex"));
+            for (index, line) in split_lines(content).enumerate() {
+                if line.trim_start().starts_with("//") {
+                    continue;
+                }
+                if let Some(caps) = use_re.captures(line) {
+                    record(caps[1].to_string(), index + 1, scan);
+                }
+                if let Some(caps) = mod_re.captures(line) {
+                    scan.local.insert(caps[1].to_string());
+                }
+                // Crates are often used by full path without a `use`
+                scan.used.extend(path_re.captures_iter(line).map(|caps| caps[1].to_string()));
+            }
+        }
+        Ecosystem::Npm => {
+            static IMPORT: OnceLock<Regex> = OnceLock::new();
+            let import_re = IMPORT.get_or_init(|| {
+                Regex::new(r#"\b(?:from|import|require)\s*\(?\s*['"]([^'"\n]+)['"]"#).expect("valid import regex")
+            });
+            for (index, line) in split_lines(content).enumerate() {
+                for caps in import_re.captures_iter(line) {
+                    if let Some(package) = npm_package(&caps[1]) {
+                        record(package, index + 1, scan);
+                    }
+                }
+            }
+        }
+        Ecosystem::Python => {
+            static IMPORT: OnceLock<Regex> = OnceLock::new();
+            static FROM: OnceLock<Regex> = OnceLock::new();
+            let import_re = IMPORT.get_or_init(|| Regex::new(r"^\s*import\s+([\w.,\s]+)").expect("valid import regex"));
+            let from_re = FROM.get_or_init(|| Regex::new(r"^\s*from\s+(\w[\w.]*)\s+import\b").expect("valid from regex"));
+            for (index, line) in split_lines(content).enumerate() {
+                let modules: Vec<&str> = if let Some(caps) = from_re.captures(line) {
+                    vec![caps.get(1).map_or("", |m| m.as_str())]
+                } else if let Some(caps) = import_re.captures(line) {
+                    let names = caps.get(1).map_or("", |m| m.as_str());
+                    names.split(',').filter_map(|name| name.split_whitespace().next()).collect()
+                } else {
+                    continue;
+                };
+                for module in modules {
+                    let top = module.split('.').next().unwrap_or(module);
+                    if !top.is_empty() {
+                        record(top.to_string(), index + 1, scan);
+                    }
+                }
+            }
+            // Sibling modules and packages are imported by their bare names
+            if let Some(stem) = file.file_stem().and_then(|s| s.to_str()) {
+                scan.local.insert(stem.to_lowercase());
+            }
+            for dir in file.ancestors().skip(1) {
+                if let Some(name) = dir.file_name().and_then(|n| n.to_str()) {
+                    scan.local.insert(name.to_lowercase());
+                }
+            }
+        }
+    }
+}
+
+/// The package an npm import specifier names; `None` for relative, absolute,
+/// `#subpath` and URL imports
+fn npm_package(specifier: &str) -> Option<String> {
+    if specifier.starts_with(['.', '/', '#']) || (specifier.contains(':') && !specifier.starts_with("node:")) {
+        return None;
+    }
+    if specifier.starts_with("node:") {
+        return Some(specifier.to_string());
+    }
+    let mut segments = specifier.split('/');
+    let first = segments.next()?;
+    if first.starts_with('@') {
+        Some(format!("{first}/{}", segments.next()?))
+    } else {
+        Some(first.to_string())
+    }
+}
+
+/// Print the manifests found and the dependencies they declare
+pub fn print_manifests(report: &DepsReport) {
+    println!("{}", "Declared dependencies".cyan().bold());
+    println!("{}", theme::rule(30).cyan());
+    if report.manifests.is_empty() {
+        println!("{}", "No Cargo.toml, package.json or pyproject.toml found.".yellow());
+        return;
+    }
+    for manifest in &report.manifests {
+        println!("\n{} ({} dependencies)", manifest.path.blue(), manifest.dependencies.len());
+        for dependency in &manifest.dependencies {
+            let kind = match dependency.kind {
+                DependencyKind::Normal => String::new(),
+                DependencyKind::Dev => " (dev)".dimmed().to_string(),
+                DependencyKind::Build => " (build)".dimmed().to_string(),
+            };
+            println!("  {} {}{}", theme::bullet(), dependency.name, kind);
+        }
+    }
+}
+
+/// Print the dependencies nothing imports, by category, and the undeclared imports
+pub fn print_unused_report(report: &DepsReport) {
+    println!("{}", "Dependency usage".cyan().bold());
+    println!("{}", theme::rule(30).cyan());
+    if report.manifests.is_empty() {
+        println!("{}", "No Cargo.toml, package.json or pyproject.toml found.".yellow());
+        return;
+    }
+    if report.unused.is_empty() && report.undeclared.is_empty() {
+        println!("{}", "Every declared dependency is imported, and every import is declared.".green());
+        return;
+    }
+
+    for category in [UnusedCategory::Unused, UnusedCategory::ProcMacro, UnusedCategory::Build, UnusedCategory::Dev] {
+        let items: Vec<&UnusedDependency> = report.unused.iter().filter(|d| d.category == category).collect();
+        if items.is_empty() {
+            continue;
+        }
+        let title = format!("{} ({}):", category.describe(), items.len());
+        let title = if category == UnusedCategory::Unused { title.red().bold() } else { title.yellow().bold() };
+        println!("\n{title}");
+        for item in items {
+            println!("  {}  {}", item.name, item.manifest.dimmed());
+        }
+    }
+
+    if !report.undeclared.is_empty() {
+        println!("\n{}", format!("Imported but not declared ({}):", report.undeclared.len()).red().bold());
+        for import in &report.undeclared {
+            println!(
+                "  {}  {}:{}  {}",
+                import.module,
+                theme::link(import.file.blue(), &import.file, Some(import.line)),
+                import.line.to_string().yellow(),
+                format!("({} in {})", import.occurrences, import.manifest).dimmed()
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn write(root: &Path, path: &str, content: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    fn unused(report: &DepsReport, ecosystem: Ecosystem) -> Vec<(&str, UnusedCategory)> {
+        report.unused.iter().filter(|d| d.ecosystem == ecosystem).map(|d| (d.name.as_str(), d.category)).collect()
+    }
+
+    fn undeclared(report: &DepsReport, ecosystem: Ecosystem) -> Vec<&str> {
+        report.undeclared.iter().filter(|i| i.ecosystem == ecosystem).map(|i| i.module.as_str()).collect()
+    }
+
+    /// One unused and one undeclared dependency per ecosystem, plus the false-positive sources
+    fn fixture(root: &Path) {
+        write(
+            root,
+            "Cargo.toml",
+            "[package]\nname = \"demo-app\"\n\n[dependencies]\nserde-json = \"1\"\nregex = \"1\"\nonce_cell = \"1\"\nserde_derive = \"1\"\n\n[dev-dependencies]\ntempfile = \"3\"\ncriterion = \"0.5\"\n\n[build-dependencies]\ncc = \"1\"\n",
+        );
+        write(
+            root,
+            "src/main.rs",
+            "mod util;\nuse regex::Regex;\nuse util::helper;\nuse rand::Rng;\n\nfn main() {\n    let v = serde_json::json!({});\n}\n",
+        );
+        write(root, "src/util.rs", "use std::fmt;\npub fn helper() {}\n");
+        write(root, "tests/it.rs", "use demo_app::run;\nuse tempfile::tempdir;\n");
+
+        write(
+            root,
+            "web/package.json",
+            r#"{"name": "web", "dependencies": {"react": "18", "@acme/ui": "1", "left-pad": "1"}, "devDependencies": {"eslint": "9", "@types/react": "18"}}"#,
+        );
+        write(
+            root,
+            "web/src/app.tsx",
+            "import React from 'react';\nimport { Button } from '@acme/ui/button';\nimport fs from 'node:fs';\nimport path from 'path';\nimport { api } from './api';\nconst _ = require('lodash');\n",
+        );
+
+        write(
+            root,
+            "py/pyproject.toml",
+            "[project]\nname = \"tool\"\ndependencies = [\"requests[socks]>=2.31\", \"PyYAML\", \"click\"]\n\n[build-system]\nrequires = [\"setuptools\"]\n",
+        );
+        write(
+            root,
+            "py/tool/cli.py",
+            "import os, sys\nimport yaml\nfrom requests.adapters import HTTPAdapter\nfrom . import helpers\nfrom tool import config\nimport numpy as np\n",
+        );
+        write(root, "py/tool/config.py", "VALUE = 1\n");
+    }
+
+    #[test]
+    fn test_unused_and_undeclared_per_ecosystem() {
+        let dir = tempdir().unwrap();
+        fixture(dir.path());
+        let report = analyze_dependencies(dir.path(), None, &DepsConfig::default()).unwrap();
+        assert_eq!(report.manifests.len(), 3);
+
+        assert_eq!(
+            unused(&report, Ecosystem::Cargo),
+            vec![
+                ("once_cell", UnusedCategory::Unused),
+                ("cc", UnusedCategory::Build),
+                ("serde_derive", UnusedCategory::ProcMacro),
+                ("criterion", UnusedCategory::Dev),
+            ]
+        );
+        assert_eq!(undeclared(&report, Ecosystem::Cargo), vec!["rand"]);
+
+        assert_eq!(
+            unused(&report, Ecosystem::Npm),
+            vec![("left-pad", UnusedCategory::Unused), ("eslint", UnusedCategory::Dev)]
+        );
+        assert_eq!(undeclared(&report, Ecosystem::Npm), vec!["lodash"]);
+
+        assert_eq!(
+            unused(&report, Ecosystem::Python),
+            vec![("click", UnusedCategory::Unused), ("setuptools", UnusedCategory::Build)]
+        );
+        assert_eq!(undeclared(&report, Ecosystem::Python), vec!["numpy"]);
+        let numpy = report.undeclared.iter().find(|i| i.module == "numpy").unwrap();
+        assert!(numpy.file.ends_with("cli.py"));
+        assert_eq!(numpy.line, 6);
+    }
+
+    #[test]
+    fn test_config_aliases_map_package_to_module() {
+        let dir = tempdir().unwrap();
+        write(dir.path(), "pyproject.toml", "[project]\nname = \"x\"\ndependencies = [\"python-jose\"]\n");
+        write(dir.path(), "app.py", "from jose import jwt\n");
+
+        let report = analyze_dependencies(dir.path(), None, &DepsConfig::default()).unwrap();
+        assert_eq!(unused(&report, Ecosystem::Python), vec![("python-jose", UnusedCategory::Unused)]);
+        assert_eq!(undeclared(&report, Ecosystem::Python), vec!["jose"]);
+
+        let config = DepsConfig { aliases: HashMap::from([("python-jose".to_string(), vec!["jose".to_string()])]) };
+        let report = analyze_dependencies(dir.path(), None, &config).unwrap();
+        assert!(report.unused.is_empty() && report.undeclared.is_empty(), "{report:?}");
+    }
+
+    #[test]
+    fn test_nested_manifest_owns_its_files() {
+        let dir = tempdir().unwrap();
+        write(dir.path(), "Cargo.toml", "[workspace]\nmembers = [\"crates/a\"]\n");
+        write(dir.path(), "crates/a/Cargo.toml", "[package]\nname = \"a\"\n\n[dependencies]\nanyhow = \"1\"\n");
+        write(dir.path(), "crates/a/src/lib.rs", "pub fn f() -> anyhow::Result<()> { Ok(()) }\n");
+
+        let report = analyze_dependencies(dir.path(), None, &DepsConfig::default()).unwrap();
+        assert!(report.unused.is_empty(), "{:?}", report.unused);
+        assert_eq!(report.manifests[1].package.as_deref(), Some("a"));
+    }
+
+    #[test]
+    fn test_npm_package_names() {
+        assert_eq!(npm_package("@scope/pkg/sub/path").as_deref(), Some("@scope/pkg"));
+        assert_eq!(npm_package("lodash/fp").as_deref(), Some("lodash"));
+        assert_eq!(npm_package("./local"), None);
+        assert_eq!(npm_package("#internal"), None);
+        assert_eq!(npm_package("https://cdn.example.com/x.js"), None);
+        assert_eq!(requirement_name("requests[socks]>=2.31; python_version>'3.8'").as_deref(), Some("requests"));
+    }
+}
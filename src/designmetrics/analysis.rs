@@ -2,78 +2,53 @@
 //!
 //! Main analysis functions for calculating design quality metrics.
 
-use super::types::{DesignMetrics, ModuleMetrics};
-use super::extractors::{extract_dependencies, extract_classes_with_metrics, count_abstract_elements};
+use super::types::{ClassMetrics, DesignMetrics, ModuleMetrics};
+use super::extractors::{dependency_modules, extract_classes_with_metrics, count_abstract_elements};
+use crate::parser::ExtractionContext;
 use std::collections::{HashMap, HashSet};
-use std::path::{Path, PathBuf};
-use walkdir::WalkDir;
+use std::path::Path;
 
 pub fn analyze_design_metrics(
     path: &Path,
     extensions: Option<&[String]>,
     exclude: Option<&[String]>,
 ) -> Result<DesignMetrics, Box<dyn std::error::Error>> {
+    let context = ExtractionContext::build(path, extensions, exclude)?;
+    Ok(analyze_design_metrics_in(&context))
+}
+
+/// Design metrics over an already built extraction context
+pub fn analyze_design_metrics_in(context: &ExtractionContext) -> DesignMetrics {
     let mut metrics = DesignMetrics::new();
     let mut module_dependencies: HashMap<String, HashSet<String>> = HashMap::new();
 
-    let walker = WalkDir::new(path)
-        .into_iter()
-        .filter_entry(|e| {
-            if let Some(name) = e.file_name().to_str() {
-                if let Some(exclude_dirs) = exclude {
-                    for exclude_dir in exclude_dirs {
-                        if name == exclude_dir {
-                            return false;
-                        }
-                    }
-                }
-            }
-            true
-        })
-        .filter_map(|e| e.ok())
-        .filter(|e| e.file_type().is_file());
-
-    let files: Vec<PathBuf> = walker
-        .filter(|entry| {
-            let file_path = entry.path();
-            if let Some(exts) = extensions {
-                if let Some(ext) = file_path.extension().and_then(|s| s.to_str()) {
-                    exts.iter().any(|e| e == ext)
-                } else {
-                    false
-                }
-            } else {
-                true
-            }
-        })
-        .map(|e| e.path().to_path_buf())
-        .collect();
-
-    for file in &files {
-        let module_name = file.file_stem()
+    for file in context.files() {
+        let file_path = Path::new(&file.path);
+        let module_name = file_path.file_stem()
             .and_then(|s| s.to_str())
             .unwrap_or("unknown")
             .to_string();
 
-        let content = std::fs::read_to_string(file)?;
-        let mut module_metrics = ModuleMetrics::new(module_name.clone(), file.to_string_lossy().to_string());
+        let mut module_metrics = ModuleMetrics::new(module_name.clone(), file.path.clone());
 
-        let dependencies = extract_dependencies(&content, file);
+        let dependencies = dependency_modules(&file.imports);
         module_metrics.efferent_coupling = dependencies.len();
         module_metrics.dependencies = dependencies.clone();
         module_dependencies.insert(module_name.clone(), dependencies.into_iter().collect());
 
-        let classes = extract_classes_with_metrics(&content, file);
+        let classes = extract_classes_with_metrics(&file.content, file_path);
         module_metrics.classes = classes;
         module_metrics.calculate_cohesion();
 
-        let abstract_count = count_abstract_elements(&content, file);
+        let abstract_count = count_abstract_elements(&file.content, file_path);
         let total_classes = module_metrics.classes.len().max(1);
         module_metrics.abstractness = abstract_count as f64 / total_classes as f64;
 
         metrics.add_module(module_metrics);
     }
 
+    merge_partial_classes(&mut metrics);
+
     for (module_name, module_metrics) in metrics.modules.iter_mut() {
         let mut dependents = Vec::new();
         
@@ -91,5 +66,44 @@ pub fn analyze_design_metrics(
 
     metrics.calculate_overall_stats();
 
-    Ok(metrics)
+    metrics
+}
+
+/// Merge `partial` class declarations spread across files into a single entry
+///
+/// The merged class is reported in the module of its first contributing file
+/// (by path), with every contributing file listed in `source_files`.
+pub fn merge_partial_classes(metrics: &mut DesignMetrics) {
+    let mut module_names: Vec<(String, String)> = metrics.modules
+        .values()
+        .map(|m| (m.file_path.clone(), m.module_name.clone()))
+        .collect();
+    module_names.sort();
+
+    let mut merged: Vec<(String, ClassMetrics)> = Vec::new();
+    for (_, module_name) in &module_names {
+        let Some(module) = metrics.modules.get_mut(module_name) else {
+            continue;
+        };
+        let (partials, others): (Vec<ClassMetrics>, Vec<ClassMetrics>) =
+            module.classes.drain(..).partition(|c| c.partial);
+        module.classes = others;
+
+        for class in partials {
+            match merged.iter_mut().find(|(_, c)| c.class_name == class.class_name) {
+                Some((_, existing)) => existing.merge(class),
+                None => merged.push((module_name.clone(), class)),
+            }
+        }
+    }
+
+    for (owner, class) in merged {
+        if let Some(module) = metrics.modules.get_mut(&owner) {
+            module.classes.push(class);
+        }
+    }
+
+    for module in metrics.modules.values_mut() {
+        module.calculate_cohesion();
+    }
 }
@@ -3,38 +3,26 @@
 //! Functions to extract dependencies, classes, and metrics from source code.
 
 use super::types::ClassMetrics;
+use crate::parser::extract_imports;
 use std::collections::{HashMap, HashSet};
 use std::path::Path;
 
 pub fn extract_dependencies(content: &str, path: &Path) -> Vec<String> {
-    let mut dependencies = Vec::new();
-    let ext = path.extension().and_then(|s| s.to_str()).unwrap_or("");
+    dependency_modules(&extract_imports(content, &path.to_string_lossy()))
+}
 
-    let patterns = match ext {
-        "rs" => vec![r"use\s+([\w:]+)"],
-        "py" => vec![r"import\s+([\w.]+)", r"from\s+([\w.]+)\s+import"],
-        "js" | "ts" => vec![r#"import\s+.*\s+from\s+['\"]([^'\"]+)['\"]"#, r#"require\(['\"]([^'\"]+)['\"]\)"#],
-        "go" => vec![r#"import\s+"([^"]+)""#],
-        "java" | "kt" => vec![r"import\s+([\w.]+)"],
-        _ => vec![],
-    };
-
-    for pattern in patterns {
-        if let Ok(re) = regex::Regex::new(pattern) {
-            for line in content.lines() {
-                if let Some(caps) = re.captures(line) {
-                    if let Some(dep) = caps.get(1) {
-                        let dep_str = dep.as_str();
-                        let module = dep_str.split("::").next()
-                            .or_else(|| dep_str.split(".").next())
-                            .or_else(|| dep_str.split("/").last())
-                            .unwrap_or(dep_str);
-                        dependencies.push(module.to_string());
-                    }
-                }
-            }
-        }
-    }
+/// Reduce import paths to the sorted, deduplicated modules they come from
+pub fn dependency_modules(imports: &[String]) -> Vec<String> {
+    let mut dependencies: Vec<String> = imports
+        .iter()
+        .map(|dep_str| {
+            let module = dep_str.split("::").next()
+                .or_else(|| dep_str.split(".").next())
+                .or_else(|| dep_str.split("/").last())
+                .unwrap_or(dep_str);
+            module.to_string()
+        })
+        .collect();
 
     dependencies.sort();
     dependencies.dedup();
@@ -46,24 +34,28 @@ pub fn extract_classes_with_metrics(content: &str, path: &Path) -> Vec<ClassMetr
     let ext = path.extension().and_then(|s| s.to_str()).unwrap_or("");
 
     let class_pattern = match ext {
-        "rs" => r"(?:struct|enum|trait)\s+(\w+)",
-        "py" => r"class\s+(\w+)",
-        "js" | "ts" => r"class\s+(\w+)",
-        "go" => r"type\s+(\w+)\s+struct",
-        "java" | "kt" => r"(?:class|interface)\s+(\w+)",
+        "rs" => r"(?:struct|enum|trait)\s+(?P<name>\w+)",
+        "py" => r"class\s+(?P<name>\w+)",
+        "js" | "ts" => r"class\s+(?P<name>\w+)",
+        "go" => r"type\s+(?P<name>\w+)\s+struct",
+        "java" | "kt" => r"(?:class|interface)\s+(?P<name>\w+)",
+        "cs" => r"\b(?P<partial>partial\s+)?(?:class|interface|struct|record)\s+(?P<name>\w+)",
         _ => return classes,
     };
 
     if let Ok(class_re) = regex::Regex::new(class_pattern) {
         for cap in class_re.captures_iter(content) {
-            if let Some(class_name) = cap.get(1) {
+            if let Some(class_name) = cap.name("name") {
                 let mut class_metrics = ClassMetrics::new(class_name.as_str().to_string());
+                class_metrics.partial = cap.name("partial").is_some();
+                class_metrics.source_files = vec![path.to_string_lossy().to_string()];
 
-                let (methods, fields) = extract_class_members(content, class_name.as_str(), ext);
+                let body = class_body(content, class_name.end(), ext);
+                let (methods, fields) = extract_class_members(body, class_name.as_str(), ext);
                 class_metrics.methods = methods;
                 class_metrics.fields = fields;
 
-                class_metrics.method_field_usage = analyze_method_field_usage(content, &class_metrics.methods, &class_metrics.fields);
+                class_metrics.method_field_usage = analyze_method_field_usage(body, &class_metrics.methods, &class_metrics.fields);
                 
                 class_metrics.calculate_lcom();
                 classes.push(class_metrics);
@@ -74,6 +66,52 @@ pub fn extract_classes_with_metrics(content: &str, path: &Path) -> Vec<ClassMetr
     classes
 }
 
+/// The part of `content` holding the members of the class declared just before `decl_end`
+///
+/// Brace languages use the matching `{ ... }` block, Python the indented block.
+/// Rust and Go declare methods outside the type, so the whole file is used.
+pub fn class_body<'a>(content: &'a str, decl_end: usize, ext: &str) -> &'a str {
+    match ext {
+        "cs" | "java" | "kt" | "js" | "ts" => {
+            let rest = &content[decl_end..];
+            // A declaration ending in `;` before any `{` has no body (e.g. C# records)
+            let open = match rest.find(['{', ';']) {
+                Some(i) if rest.as_bytes()[i] == b'{' => decl_end + i,
+                _ => return "",
+            };
+            let mut depth = 0;
+            for (i, c) in content[open..].char_indices() {
+                match c {
+                    '{' => depth += 1,
+                    '}' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            return &content[open..open + i + 1];
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            &content[open..]
+        }
+        "py" => {
+            let line_start = content[..decl_end].rfind('\n').map(|i| i + 1).unwrap_or(0);
+            let indent = content[line_start..].len() - content[line_start..].trim_start().len();
+            let body_start = content[decl_end..].find('\n').map(|i| decl_end + i + 1).unwrap_or(content.len());
+            let mut body_end = body_start;
+            for line in content[body_start..].split_inclusive('\n') {
+                let trimmed = line.trim_start();
+                if !trimmed.is_empty() && line.len() - trimmed.len() <= indent {
+                    break;
+                }
+                body_end += line.len();
+            }
+            &content[body_start..body_end]
+        }
+        _ => content,
+    }
+}
+
 pub fn extract_class_members(content: &str, _class_name: &str, ext: &str) -> (Vec<String>, Vec<String>) {
     let mut methods = Vec::new();
     let mut fields = Vec::new();
@@ -83,6 +121,7 @@ pub fn extract_class_members(content: &str, _class_name: &str, ext: &str) -> (Ve
         "py" => r"def\s+(\w+)",
         "js" | "ts" => r"(?:async\s+)?(\w+)\s*\([^)]*\)\s*\{",
         "java" | "kt" => r"(?:public|private|protected)?\s*(?:static)?\s*\w+\s+(\w+)\s*\(",
+        "cs" => r"(?:public|private|protected|internal)(?:\s+(?:static|async|override|virtual|abstract|sealed|new|partial|extern))*\s+[\w<>\[\],.?]+\s+(\w+)\s*\(",
         _ => return (methods, fields),
     };
 
@@ -91,6 +130,7 @@ pub fn extract_class_members(content: &str, _class_name: &str, ext: &str) -> (Ve
         "py" => r"self\.(\w+)\s*=",
         "js" | "ts" => r"this\.(\w+)\s*=",
         "java" | "kt" => r"(?:private|public|protected)?\s*\w+\s+(\w+);",
+        "cs" => r"(?:private|public|protected|internal)(?:\s+(?:static|readonly|const|volatile))*\s+[\w<>\[\],.?]+\s+(\w+)\s*(?:;|=[^>]|\{\s*(?:get|set))",
         _ => return (methods, fields),
     };
 
@@ -139,6 +179,7 @@ pub fn count_abstract_elements(content: &str, path: &Path) -> usize {
         "py" => vec![r"class\s+\w+\(ABC\)", r"@abstractmethod"],
         "java" | "kt" => vec![r"abstract\s+class", r"interface\s+\w+"],
         "ts" => vec![r"interface\s+\w+", r"abstract\s+class"],
+        "cs" => vec![r"abstract\s+(?:partial\s+)?class", r"interface\s+\w+"],
         _ => vec![],
     };
 
@@ -8,7 +8,7 @@ pub mod extractors;
 pub mod reporting;
 
 pub use types::{DesignMetrics, ModuleMetrics, ClassMetrics, OverallStats};
-pub use analysis::analyze_design_metrics;
+pub use analysis::{analyze_design_metrics, analyze_design_metrics_in, merge_partial_classes};
 pub use reporting::print_design_metrics;
 
 #[cfg(test)]
@@ -84,4 +84,55 @@ mod tests {
         assert_eq!(critical.len(), 1);
         assert!(critical.contains(&"critical".to_string()));
     }
+
+    #[test]
+    fn test_partial_class_merged_across_files() {
+        let fixture = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/designmetrics/csharp_partial");
+        let metrics = analyze_design_metrics(&fixture, Some(&["cs".to_string()]), None).unwrap();
+
+        let orders: Vec<&ClassMetrics> = metrics.modules
+            .values()
+            .flat_map(|m| m.classes.iter())
+            .filter(|c| c.class_name == "OrderService")
+            .collect();
+        assert_eq!(orders.len(), 1);
+
+        let order = orders[0];
+        assert!(order.partial);
+        assert_eq!(order.source_files.len(), 2);
+        assert_eq!(order.methods.len(), 4);
+        assert_eq!(order.fields.len(), 3);
+    }
+
+    #[test]
+    fn test_multiple_top_level_classes_keep_their_own_members() {
+        let fixture = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/designmetrics/csharp_partial");
+        let metrics = analyze_design_metrics(&fixture, Some(&["cs".to_string()]), None).unwrap();
+
+        let module = metrics.modules.get("OrderService.Shipping").unwrap();
+        let receipt = module.classes.iter().find(|c| c.class_name == "Receipt").unwrap();
+        assert_eq!(receipt.methods, vec!["Print".to_string()]);
+        assert_eq!(receipt.fields, vec!["total".to_string()]);
+        assert!(!receipt.partial);
+    }
+
+    #[test]
+    fn test_class_metrics_merge() {
+        let mut a = ClassMetrics::new("Widget".to_string());
+        a.methods = vec!["Draw".to_string()];
+        a.fields = vec!["size".to_string()];
+        a.source_files = vec!["a.cs".to_string()];
+
+        let mut b = ClassMetrics::new("Widget".to_string());
+        b.methods = vec!["Draw".to_string(), "Resize".to_string()];
+        b.fields = vec!["color".to_string()];
+        b.source_files = vec!["b.cs".to_string()];
+
+        a.merge(b);
+        assert_eq!(a.methods, vec!["Draw", "Resize"]);
+        assert_eq!(a.fields, vec!["size", "color"]);
+        assert_eq!(a.source_files, vec!["a.cs", "b.cs"]);
+    }
 }
@@ -3,6 +3,7 @@
 //! Functions for displaying design metrics analysis results.
 
 use super::types::DesignMetrics;
+use crate::theme::{self, Glyph};
 use colored::*;
 
 pub fn print_design_metrics(metrics: &DesignMetrics, detailed: bool) {
@@ -17,14 +18,14 @@ pub fn print_design_metrics(metrics: &DesignMetrics, detailed: bool) {
     println!("  Average cohesion: {:.2}", metrics.overall_stats.avg_cohesion);
 
     if !metrics.overall_stats.highly_coupled_modules.is_empty() {
-        println!("\n{}", "⚠️  Highly Coupled Modules:".yellow().bold());
+        println!("\n{}", format!("{}  Highly Coupled Modules:", theme::glyph(Glyph::Warning)).yellow().bold());
         for module in &metrics.overall_stats.highly_coupled_modules {
             println!("  - {}", module);
         }
     }
 
     if !metrics.overall_stats.unstable_modules.is_empty() {
-        println!("\n{}", "⚠️  Unstable Modules (I > 0.7):".yellow().bold());
+        println!("\n{}", format!("{}  Unstable Modules (I > 0.7):", theme::glyph(Glyph::Warning)).yellow().bold());
         for module in &metrics.overall_stats.unstable_modules {
             if let Some(m) = metrics.modules.get(module) {
                 println!("  - {} (I = {:.2})", module, m.instability);
@@ -33,7 +34,7 @@ pub fn print_design_metrics(metrics: &DesignMetrics, detailed: bool) {
     }
 
     if !metrics.overall_stats.low_cohesion_modules.is_empty() {
-        println!("\n{}", "⚠️  Low Cohesion Modules:".yellow().bold());
+        println!("\n{}", format!("{}  Low Cohesion Modules:", theme::glyph(Glyph::Warning)).yellow().bold());
         for module in &metrics.overall_stats.low_cohesion_modules {
             if let Some(m) = metrics.modules.get(module) {
                 println!("  - {} (cohesion = {:.2})", module, m.cohesion);
@@ -43,7 +44,7 @@ pub fn print_design_metrics(metrics: &DesignMetrics, detailed: bool) {
 
     let critical = metrics.get_critical_modules();
     if !critical.is_empty() {
-        println!("\n{}", "🔴 Critical Modules (Ca > 5):".red().bold());
+        println!("\n{}", theme::heading(Glyph::Critical, "Critical Modules (Ca > 5):").red().bold());
         for module in &critical {
             if let Some(m) = metrics.modules.get(module) {
                 println!("  - {} (Ca = {})", module, m.afferent_coupling);
@@ -53,7 +54,7 @@ pub fn print_design_metrics(metrics: &DesignMetrics, detailed: bool) {
 
     let stable = metrics.get_stable_modules();
     if !stable.is_empty() {
-        println!("\n{}", "✅ Stable Modules (I < 0.3):".green().bold());
+        println!("\n{}", theme::heading(Glyph::Ok, "Stable Modules (I < 0.3):").green().bold());
         for module in &stable {
             if let Some(m) = metrics.modules.get(module) {
                 println!("  - {} (I = {:.2})", module, m.instability);
@@ -91,6 +92,9 @@ pub fn print_design_metrics(metrics: &DesignMetrics, detailed: bool) {
                 for class in &module.classes {
                     println!("    - {} (LCOM: {:.2}, methods: {}, fields: {})",
                         class.class_name, class.lcom, class.methods.len(), class.fields.len());
+                    if class.source_files.len() > 1 {
+                        println!("      partial, merged from: {}", class.source_files.join(", "));
+                    }
                 }
             }
         }
@@ -33,6 +33,12 @@ pub struct ClassMetrics {
     pub fields: Vec<String>,
     pub method_field_usage: HashMap<String, HashSet<String>>,
     pub lcom: f64,
+    /// Declared `partial` (C#), so other files may contribute members
+    #[serde(default)]
+    pub partial: bool,
+    /// Files the class is declared in; more than one for merged partial classes
+    #[serde(default)]
+    pub source_files: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -172,9 +178,34 @@ impl ClassMetrics {
             fields: Vec::new(),
             method_field_usage: HashMap::new(),
             lcom: 0.0,
+            partial: false,
+            source_files: Vec::new(),
         }
     }
 
+    /// Merge the members of another declaration of the same partial class
+    pub fn merge(&mut self, other: ClassMetrics) {
+        for method in other.methods {
+            if !self.methods.contains(&method) {
+                self.methods.push(method);
+            }
+        }
+        for field in other.fields {
+            if !self.fields.contains(&field) {
+                self.fields.push(field);
+            }
+        }
+        for (method, fields) in other.method_field_usage {
+            self.method_field_usage.entry(method).or_default().extend(fields);
+        }
+        for file in other.source_files {
+            if !self.source_files.contains(&file) {
+                self.source_files.push(file);
+            }
+        }
+        self.calculate_lcom();
+    }
+
     pub fn calculate_lcom(&mut self) {
         if self.methods.is_empty() || self.fields.is_empty() {
             self.lcom = 0.0;
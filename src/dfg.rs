@@ -5,6 +5,7 @@
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::path::Path;
+use crate::lines::split_lines;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DataFlowGraph {
@@ -199,7 +200,7 @@ pub fn build_dfg_from_source(content: &str, function_name: &str, file_path: &str
     let mut node_id = 0;
     let mut variable_map: HashMap<String, usize> = HashMap::new();
 
-    let lines: Vec<&str> = content.lines().collect();
+    let lines: Vec<&str> = split_lines(content).collect();
     let mut in_function = false;
     let mut brace_depth = 0;
 
@@ -0,0 +1,283 @@
+//! Grouping of pairwise duplicate matches into clusters
+//!
+//! A block copied into six files yields up to fifteen pairs; clustering reports
+//! it once with all six locations. Pairs sharing a location are joined with
+//! union-find, and overlapping spans in one file (the sliding windows of the
+//! same copy) count as a single occurrence.
+
+use super::ownership::KeepSuggestion;
+use super::types::{CloneType, EnhancedDuplicateBlock};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// One copy of a duplicated block
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct DuplicateOccurrence {
+    pub file: String,
+    pub line: usize,
+    /// Function containing the copy, with function granularity
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fn_name: Option<String>,
+}
+
+/// Every copy of the same code, grouped from pairwise matches
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateCluster {
+    /// Start of the longest matched block, as shown for pairs
+    pub representative_content: String,
+    /// Copies sorted by file and line
+    pub occurrences: Vec<DuplicateOccurrence>,
+    /// The loosest clone type among the pairwise matches
+    pub clone_type: CloneType,
+    /// The lowest pairwise similarity in the cluster
+    pub similarity: f64,
+    /// Lines in the longest copy
+    pub line_count: usize,
+    /// The copy most pairs suggested keeping, when ownership suggestions are enabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<KeepSuggestion>,
+}
+
+/// A matched span: file, first and last line
+type Span = (String, usize, usize);
+
+struct UnionFind {
+    parent: Vec<usize>,
+}
+
+impl UnionFind {
+    fn new(size: usize) -> Self {
+        Self { parent: (0..size).collect() }
+    }
+
+    fn find(&mut self, node: usize) -> usize {
+        let mut root = node;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+        let mut current = node;
+        while self.parent[current] != root {
+            let next = self.parent[current];
+            self.parent[current] = root;
+            current = next;
+        }
+        root
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a != b {
+            self.parent[b] = a;
+        }
+    }
+}
+
+/// Group pairwise duplicates into clusters, most copies first
+pub fn cluster_duplicates(duplicates: &[EnhancedDuplicateBlock]) -> Vec<DuplicateCluster> {
+    let mut spans: Vec<Span> = Vec::new();
+    let mut fn_names: Vec<Option<String>> = Vec::new();
+    let mut index: HashMap<Span, usize> = HashMap::new();
+    let mut node = |span: Span, fn_name: &Option<String>| {
+        *index.entry(span.clone()).or_insert_with(|| {
+            spans.push(span);
+            fn_names.push(fn_name.clone());
+            spans.len() - 1
+        })
+    };
+
+    let pairs: Vec<(usize, usize)> = duplicates
+        .iter()
+        .map(|dup| {
+            let last = dup.line_count.saturating_sub(1);
+            let a = node((dup.file1.clone(), dup.line1, dup.line1 + last), &dup.fn_name1);
+            let b = node((dup.file2.clone(), dup.line2, dup.line2 + last), &dup.fn_name2);
+            (a, b)
+        })
+        .collect();
+
+    let mut sets = UnionFind::new(spans.len());
+    for &(a, b) in &pairs {
+        sets.union(a, b);
+    }
+
+    // Overlapping windows in one file are the same copy
+    let mut by_file: Vec<usize> = (0..spans.len()).collect();
+    by_file.sort_by(|&a, &b| spans[a].cmp(&spans[b]));
+    let mut run: Option<(usize, usize)> = None;
+    for &i in &by_file {
+        let (file, start, end) = &spans[i];
+        match run {
+            Some((first, run_end)) if spans[first].0 == *file && *start <= run_end => {
+                sets.union(first, i);
+                run = Some((first, run_end.max(*end)));
+            }
+            _ => run = Some((i, *end)),
+        }
+    }
+
+    let mut members: HashMap<usize, Vec<usize>> = HashMap::new();
+    for &i in &by_file {
+        members.entry(sets.find(i)).or_default().push(i);
+    }
+    let mut pairs_of: HashMap<usize, Vec<&EnhancedDuplicateBlock>> = HashMap::new();
+    for (dup, &(a, _)) in duplicates.iter().zip(&pairs) {
+        pairs_of.entry(sets.find(a)).or_default().push(dup);
+    }
+
+    let mut clusters: Vec<DuplicateCluster> = members
+        .into_iter()
+        .filter_map(|(root, nodes)| {
+            let matches = pairs_of.remove(&root)?;
+            Some(build_cluster(&nodes, &spans, &fn_names, &matches))
+        })
+        .collect();
+
+    clusters.sort_by(|a, b| {
+        b.occurrences
+            .len()
+            .cmp(&a.occurrences.len())
+            .then(b.line_count.cmp(&a.line_count))
+            .then_with(|| a.occurrences.cmp(&b.occurrences))
+    });
+    clusters
+}
+
+/// Merge a component's spans into occurrences and summarize its pairs
+///
+/// `nodes` are sorted by span, so each file's spans are adjacent and in order.
+fn build_cluster(
+    nodes: &[usize],
+    spans: &[Span],
+    fn_names: &[Option<String>],
+    matches: &[&EnhancedDuplicateBlock],
+) -> DuplicateCluster {
+    let mut merged: Vec<(DuplicateOccurrence, usize)> = Vec::new();
+    for &i in nodes {
+        let (file, start, end) = &spans[i];
+        match merged.last_mut() {
+            Some((last, last_end)) if last.file == *file && *start <= *last_end => {
+                *last_end = (*last_end).max(*end);
+            }
+            _ => merged.push((
+                DuplicateOccurrence { file: file.clone(), line: *start, fn_name: fn_names[i].clone() },
+                *end,
+            )),
+        }
+    }
+
+    let line_count = merged.iter().map(|(o, end)| end - o.line + 1).max().unwrap_or(0);
+    let representative = matches.iter().max_by_key(|dup| dup.line_count).map_or_else(String::new, |dup| dup.content.clone());
+    let clone_type = matches.iter().map(|dup| dup.clone_type).max().unwrap_or(CloneType::Type1);
+    let similarity = matches.iter().map(|dup| dup.similarity).fold(f64::INFINITY, f64::min);
+    let suggestion = vote_keep(matches, &merged);
+
+    DuplicateCluster {
+        representative_content: representative,
+        occurrences: merged.into_iter().map(|(o, _)| o).collect(),
+        clone_type,
+        similarity: if similarity.is_finite() { similarity } else { 0.0 },
+        line_count,
+        suggestion,
+    }
+}
+
+/// The occurrence the pairs most often suggested keeping, as a cluster-wide suggestion
+fn vote_keep(matches: &[&EnhancedDuplicateBlock], merged: &[(DuplicateOccurrence, usize)]) -> Option<KeepSuggestion> {
+    let mut votes: HashMap<usize, (usize, &KeepSuggestion)> = HashMap::new();
+    for suggestion in matches.iter().filter_map(|dup| dup.suggestion.as_ref()) {
+        let line = suggestion.keep_line;
+        if let Some(n) = merged.iter().position(|(o, end)| o.file == suggestion.keep_file && o.line <= line && line <= *end) {
+            votes.entry(n).or_insert((0, suggestion)).0 += 1;
+        }
+    }
+
+    // Most votes wins, ties go to the earliest occurrence
+    let (n, (_, suggestion)) = votes.into_iter().max_by(|(a, (va, _)), (b, (vb, _))| va.cmp(vb).then(b.cmp(a)))?;
+    Some(KeepSuggestion {
+        keep_file: merged[n].0.file.clone(),
+        keep_line: merged[n].0.line,
+        replace_count: merged.len() - 1,
+        ..suggestion.clone()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pair(file1: &str, line1: usize, file2: &str, line2: usize, line_count: usize) -> EnhancedDuplicateBlock {
+        EnhancedDuplicateBlock {
+            file1: file1.to_string(),
+            line1,
+            file2: file2.to_string(),
+            line2,
+            content: "let total = 0;...".to_string(),
+            similarity: 1.0,
+            clone_type: CloneType::Type1,
+            token_similarity: 1.0,
+            structural_similarity: 1.0,
+            line_count,
+            fn_name1: None,
+            fn_name2: None,
+            suggestion: None,
+        }
+    }
+
+    fn locations(cluster: &DuplicateCluster) -> Vec<(&str, usize)> {
+        cluster.occurrences.iter().map(|o| (o.file.as_str(), o.line)).collect()
+    }
+
+    #[test]
+    fn test_pairs_of_one_block_form_one_cluster() {
+        let copies = [("a.rs", 3), ("b.rs", 10), ("c.rs", 10), ("d.rs", 40)];
+        let mut pairs = Vec::new();
+        for (i, (file1, line1)) in copies.iter().enumerate() {
+            for (file2, line2) in &copies[i + 1..] {
+                pairs.push(pair(file1, *line1, file2, *line2, 5));
+            }
+        }
+        pairs.push(pair("e.rs", 1, "f.rs", 1, 8));
+
+        let clusters = cluster_duplicates(&pairs);
+        assert_eq!(clusters.len(), 2, "{clusters:?}");
+        assert_eq!(locations(&clusters[0]), copies.to_vec());
+        assert_eq!(clusters[0].line_count, 5);
+        assert_eq!(locations(&clusters[1]), vec![("e.rs", 1), ("f.rs", 1)]);
+    }
+
+    #[test]
+    fn test_overlapping_windows_count_once() {
+        let mut second = pair("a.rs", 4, "b.rs", 21, 6);
+        second.similarity = 0.95;
+        second.clone_type = CloneType::Type2;
+        let pairs = vec![pair("a.rs", 1, "b.rs", 20, 6), second, pair("c.rs", 50, "d.rs", 1, 5)];
+
+        let clusters = cluster_duplicates(&pairs);
+        assert_eq!(clusters.len(), 2);
+        let windows = clusters.iter().find(|c| c.occurrences[0].file == "a.rs").unwrap();
+        assert_eq!(locations(windows), vec![("a.rs", 1), ("b.rs", 20)]);
+        assert_eq!(windows.line_count, 9);
+        assert_eq!(windows.clone_type, CloneType::Type2);
+        assert_eq!(windows.similarity, 0.95);
+    }
+
+    #[test]
+    fn test_cluster_keep_suggestion_is_the_majority() {
+        let suggest = |file: &str, line: usize| KeepSuggestion {
+            keep_file: file.to_string(),
+            keep_line: line,
+            replace_count: 1,
+            score: 1.0,
+            reasons: vec!["most referenced".to_string()],
+        };
+        let mut pairs = vec![pair("a.rs", 1, "b.rs", 1, 5), pair("a.rs", 1, "c.rs", 1, 5), pair("b.rs", 1, "c.rs", 1, 5)];
+        pairs[0].suggestion = Some(suggest("b.rs", 1));
+        pairs[1].suggestion = Some(suggest("c.rs", 1));
+        pairs[2].suggestion = Some(suggest("b.rs", 1));
+
+        let clusters = cluster_duplicates(&pairs);
+        let suggestion = clusters[0].suggestion.as_ref().unwrap();
+        assert_eq!((suggestion.keep_file.as_str(), suggestion.keep_line), ("b.rs", 1));
+        assert_eq!(suggestion.replace_count, 2);
+    }
+}
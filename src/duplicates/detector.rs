@@ -1,12 +1,16 @@
 //! Core duplicate detection logic with parallel processing
 
 use super::normalize::{calculate_hash, normalize_code, normalize_with_variables};
+use super::sections::{canonicalize_window, declaration_sections, section_index};
 use super::similarity::calculate_similarity;
-use super::types::{CodeBlock, DuplicateConfig, EnhancedDuplicateBlock};
-use crate::parser::read_file_content;
-use crate::search::list_files;
+use super::types::{CloneType, CodeBlock, DuplicateConfig, EnhancedDuplicateBlock};
+use super::types::Granularity;
+use crate::lines::{line_number_at, line_starts, split_lines};
+use crate::parser::{find_block, strip_comments_and_strings, ExtractionContext, FileExtraction};
+use crate::text::truncate_str_safe;
 use rayon::prelude::*;
 use std::collections::HashMap;
+use std::ops::Range;
 use std::path::Path;
 
 /// Extract code blocks from content with variable-length windows
@@ -15,61 +19,150 @@ pub fn extract_code_blocks(
     content: &str,
     config: &DuplicateConfig,
 ) -> Vec<CodeBlock> {
-    let lines: Vec<&str> = content.lines().collect();
+    let compared = compared_content(file, content, config);
+    let lines: Vec<&str> = split_lines(&compared).collect();
     let mut blocks = Vec::new();
+    let section_of = sections_of(file, content, config);
 
     // Variable-length sliding window
     for window_size in config.min_lines..=(config.min_lines * 3).min(lines.len()) {
         for i in 0..lines.len().saturating_sub(window_size) {
-            let block_lines: Vec<&str> = lines[i..i + window_size]
-                .iter()
-                .map(|l| l.trim())
-                .filter(|l| !l.is_empty())
-                .collect();
-
-            if block_lines.len() < config.min_lines {
-                continue;
+            if let Some(block) = build_block(file, &lines, i..i + window_size, &section_of, config) {
+                blocks.push(block);
             }
+        }
+    }
 
-            let content = block_lines.join("\n");
-            
-            // Skip if too many comments
-            if is_mostly_comments(&content) {
-                continue;
-            }
+    blocks
+}
 
-            let normalized = normalize_code(&content);
-            let normalized_with_vars = normalize_with_variables(&content);
-            
-            // Tokenize
-            let tokens: Vec<String> = normalized
-                .split_whitespace()
-                .map(|s| s.to_string())
-                .collect();
-
-            if tokens.len() < config.min_tokens {
-                continue;
-            }
+/// Extract one block per function, from its header to the end of its body
+///
+/// Returns `None` when the file's language has no reliable function
+/// extraction; callers fall back to [`extract_code_blocks`].
+pub fn extract_function_blocks(
+    file: &str,
+    content: &str,
+    functions: &[(String, usize)],
+    config: &DuplicateConfig,
+) -> Option<Vec<CodeBlock>> {
+    if !has_function_blocks(file) {
+        return None;
+    }
+    let compared = compared_content(file, content, config);
+    let lines: Vec<&str> = split_lines(&compared).collect();
+    let starts = line_starts(content);
+    let section_of = sections_of(file, content, config);
 
-            blocks.push(CodeBlock {
-                file: file.to_string(),
-                line_start: i + 1,
-                line_end: i + window_size,
-                content: content.clone(),
-                normalized,
-                tokens,
-                hash: calculate_hash(&content),
-                normalized_hash: calculate_hash(&normalized_with_vars),
-            });
-        }
+    let blocks = functions
+        .iter()
+        .filter_map(|(name, line)| {
+            let span = find_block(content, *starts.get(line.checked_sub(1)?)?, file)?;
+            let end_line = line_number_at(content, span.end);
+            let mut block = build_block(file, &lines, line - 1..end_line.min(lines.len()), &section_of, config)?;
+            block.fn_name = Some(name.clone());
+            Some(block)
+        })
+        .collect();
+    Some(blocks)
+}
+
+/// The text blocks are built from: `content` with comments and string contents
+/// blanked when `config.ignore_comments`, with the same lines either way
+fn compared_content<'a>(file: &str, content: &'a str, config: &DuplicateConfig) -> std::borrow::Cow<'a, str> {
+    if config.ignore_comments {
+        std::borrow::Cow::Owned(strip_comments_and_strings(content, file))
+    } else {
+        std::borrow::Cow::Borrowed(content)
     }
+}
 
-    blocks
+/// Whether [`find_block`] finds function bodies reliably for this file's language
+fn has_function_blocks(file: &str) -> bool {
+    const BLOCK_EXTENSIONS: &[&str] = &[
+        "rs", "py", "pyw", "pyi", "js", "jsx", "mjs", "cjs", "ts", "tsx", "go", "java", "kt", "kts",
+        "c", "h", "cpp", "cc", "cxx", "hpp", "cs", "swift", "scala", "php", "dart",
+    ];
+    let ext = Path::new(file).extension().and_then(|e| e.to_str()).unwrap_or("");
+    BLOCK_EXTENSIONS.contains(&ext.to_lowercase().as_str())
+}
+
+fn sections_of(file: &str, content: &str, config: &DuplicateConfig) -> HashMap<usize, usize> {
+    if config.order_insensitive_sections {
+        section_index(&declaration_sections(file, content))
+    } else {
+        HashMap::new()
+    }
+}
+
+/// Build a block from the 0-based line range `range`, or `None` if it is too small or mostly comments
+fn build_block(
+    file: &str,
+    lines: &[&str],
+    range: Range<usize>,
+    section_of: &HashMap<usize, usize>,
+    config: &DuplicateConfig,
+) -> Option<CodeBlock> {
+    let window: Vec<(usize, &str)> = lines[range.clone()]
+        .iter()
+        .enumerate()
+        .map(|(offset, l)| (range.start + offset, l.trim()))
+        .filter(|(_, l)| !l.is_empty())
+        .collect();
+
+    if window.len() < config.min_lines {
+        return None;
+    }
+
+    let block_lines: Vec<&str> = window.iter().map(|(_, l)| *l).collect();
+    let content = block_lines.join("\n");
+
+    // Skip if too many comments
+    if is_mostly_comments(&content) {
+        return None;
+    }
+
+    // Compare with declaration sections sorted, but keep the source order for display
+    let canonical = canonicalize_window(&window, section_of);
+    let reordered = canonical != block_lines;
+    let compared = if reordered { canonical.join("\n") } else { content.clone() };
+
+    let normalized = normalize_code(&compared);
+    let normalized_with_vars = normalize_with_variables(&compared);
+    let ordered_hash = if reordered {
+        calculate_hash(&normalize_code(&content))
+    } else {
+        calculate_hash(&normalized)
+    };
+
+    // Tokenize
+    let tokens: Vec<String> = normalized
+        .split_whitespace()
+        .map(|s| s.to_string())
+        .collect();
+
+    if tokens.len() < config.min_tokens {
+        return None;
+    }
+
+    // Report the lines that hold code, not blank or stripped lines at the edges
+    Some(CodeBlock {
+        file: file.to_string(),
+        line_start: window[0].0 + 1,
+        line_end: window[window.len() - 1].0 + 1,
+        hash: calculate_hash(&content),
+        content,
+        normalized,
+        tokens,
+        normalized_hash: calculate_hash(&normalized_with_vars),
+        ordered_hash,
+        fn_name: None,
+    })
 }
 
 /// Check if content is mostly comments
 fn is_mostly_comments(content: &str) -> bool {
-    let lines: Vec<&str> = content.lines().collect();
+    let lines: Vec<&str> = split_lines(content).collect();
     if lines.is_empty() {
         return false;
     }
@@ -232,12 +325,15 @@ fn create_duplicate(
         line1: block1.line_start,
         file2: block2.file.clone(),
         line2: block2.line_start,
-        content: block1.content.chars().take(100).collect::<String>() + "...",
+        content: truncate_str_safe(&block1.content, 100),
         similarity: metrics.overall_similarity,
         clone_type: metrics.clone_type,
         token_similarity: metrics.token_similarity,
         structural_similarity: metrics.structural_similarity,
         line_count: block1.line_end - block1.line_start + 1,
+        fn_name1: block1.fn_name.clone(),
+        fn_name2: block2.fn_name.clone(),
+        suggestion: None,
     }
 }
 
@@ -247,9 +343,34 @@ fn deduplicate_results(mut duplicates: Vec<EnhancedDuplicateBlock>) -> Vec<Enhan
         let key = format!("{}:{}:{}:{}", dup.file1, dup.line1, dup.file2, dup.line2);
         seen.insert(key)
     });
+
+    // A near-miss window that overlaps an exact copy but pairs its lines out of
+    // step is that copy again, shifted onto the lines around it
+    let exact: Vec<EnhancedDuplicateBlock> = duplicates
+        .iter()
+        .filter(|dup| dup.clone_type == CloneType::Type1)
+        .cloned()
+        .collect();
+    duplicates.retain(|dup| dup.clone_type == CloneType::Type1 || !exact.iter().any(|copy| shifted_onto(dup, copy)));
     duplicates
 }
 
+/// Whether `dup` pairs the same files as `copy`, intersects it on both sides
+/// and lines its two sides up differently
+fn shifted_onto(dup: &EnhancedDuplicateBlock, copy: &EnhancedDuplicateBlock) -> bool {
+    let (line1, line2) = if dup.file1 == copy.file1 && dup.file2 == copy.file2 {
+        (copy.line1, copy.line2)
+    } else if dup.file1 == copy.file2 && dup.file2 == copy.file1 {
+        (copy.line2, copy.line1)
+    } else {
+        return false;
+    };
+    let intersects = |start: usize, other: usize| start < other + copy.line_count && other < start + dup.line_count;
+    intersects(dup.line1, line1)
+        && intersects(dup.line2, line2)
+        && dup.line1 as isize - line1 as isize != dup.line2 as isize - line2 as isize
+}
+
 /// Main entry point for duplicate detection
 pub fn find_duplicates(
     path: &Path,
@@ -257,35 +378,43 @@ pub fn find_duplicates(
     exclude: Option<&[String]>,
     config: DuplicateConfig,
 ) -> Result<Vec<EnhancedDuplicateBlock>, Box<dyn std::error::Error>> {
-    let files = list_files(path, extensions, exclude)?;
-    
+    let context = ExtractionContext::build(path, extensions, exclude)?;
+    Ok(find_duplicates_in(&context, &config))
+}
+
+/// Duplicate detection over an already built extraction context
+pub fn find_duplicates_in(context: &ExtractionContext, config: &DuplicateConfig) -> Vec<EnhancedDuplicateBlock> {
+    let eligible = |file: &&FileExtraction| {
+        file.size <= config.max_file_size as u64 && should_process_file(&file.path, config)
+    };
+
+    let blocks_of = |file: &FileExtraction| {
+        let functions = match config.granularity {
+            Granularity::Function => extract_function_blocks(&file.path, &file.content, &file.functions, config),
+            Granularity::Window => None,
+        };
+        functions.unwrap_or_else(|| extract_code_blocks(&file.path, &file.content, config))
+    };
+
     // Extract blocks from all files
     let all_blocks: Vec<CodeBlock> = if config.use_parallel {
-        files
+        context
+            .files()
             .par_iter()
-            .filter(|file| {
-                file.size <= config.max_file_size as u64 && should_process_file(&file.path, &config)
-            })
-            .flat_map(|file| {
-                let content = read_file_content(&file.path);
-                extract_code_blocks(&file.path, &content, &config)
-            })
+            .filter(eligible)
+            .flat_map(blocks_of)
             .collect()
     } else {
-        files
+        context
+            .files()
             .iter()
-            .filter(|file| {
-                file.size <= config.max_file_size as u64 && should_process_file(&file.path, &config)
-            })
-            .flat_map(|file| {
-                let content = read_file_content(&file.path);
-                extract_code_blocks(&file.path, &content, &config)
-            })
+            .filter(eligible)
+            .flat_map(blocks_of)
             .collect()
     };
 
     // Find duplicates using hash-based indexing
-    Ok(find_duplicates_with_index(all_blocks, &config))
+    find_duplicates_with_index(all_blocks, config)
 }
 
 #[cfg(test)]
@@ -340,4 +469,97 @@ fn test2() {
         let blocks = extract_code_blocks("test.rs", content, &config);
         assert!(!blocks.is_empty());
     }
+
+    #[test]
+    fn test_shuffled_imports_only_match_when_order_insensitive() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/duplicates_order");
+        let context = ExtractionContext::build(&root, None, None).unwrap();
+        let import_duplicates = |order_insensitive_sections| {
+            let config = DuplicateConfig {
+                use_parallel: false,
+                order_insensitive_sections,
+                ..Default::default()
+            };
+            find_duplicates_in(&context, &config)
+                .into_iter()
+                .filter(|dup| dup.line1 == 1 && dup.line2 == 1)
+                .collect::<Vec<_>>()
+        };
+
+        assert!(import_duplicates(false).is_empty());
+
+        let found = import_duplicates(true);
+        assert!(!found.is_empty());
+        assert!(found.iter().all(|dup| dup.clone_type == crate::duplicates::CloneType::Type2));
+        assert!(found.iter().all(|dup| dup.similarity >= 0.99));
+    }
+
+    #[test]
+    fn test_function_granularity_reports_whole_functions() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/duplicates_functions");
+        let context = ExtractionContext::build(&root, None, None).unwrap();
+        let config = DuplicateConfig {
+            use_parallel: false,
+            granularity: Granularity::Function,
+            ..Default::default()
+        };
+
+        let found = find_duplicates_in(&context, &config);
+        assert_eq!(found.len(), 1, "{found:?}");
+        let dup = &found[0];
+        assert_eq!(dup.fn_name1.as_deref(), Some("totals_by_bucket"));
+        assert_eq!(dup.fn_name2.as_deref(), Some("totals_by_bucket"));
+        assert_eq!(dup.clone_type, crate::duplicates::CloneType::Type1);
+        assert_eq!(dup.line_count, 8);
+
+        // Window mode reports many overlapping windows of the same code instead
+        let windows = find_duplicates_in(&context, &DuplicateConfig { granularity: Granularity::Window, ..config });
+        assert!(windows.len() > 1);
+        assert!(windows.iter().all(|dup| dup.fn_name1.is_none()));
+    }
+
+    #[test]
+    fn test_license_headers_are_not_duplicates() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/duplicates_license");
+        let context = ExtractionContext::build(&root, None, None).unwrap();
+        let config = DuplicateConfig { use_parallel: false, suggest_keep: false, ..Default::default() };
+        // Line of each copy in config.rs and hashing.rs
+        let lines = |dup: &EnhancedDuplicateBlock| {
+            if dup.file1.ends_with("config.rs") { (dup.line1, dup.line2) } else { (dup.line2, dup.line1) }
+        };
+
+        let found = find_duplicates_in(&context, &config);
+        assert!(!found.is_empty(), "the shared checksum function is still a duplicate");
+        assert!(found.iter().map(lines).all(|(config_line, hashing_line)| config_line >= 19 && hashing_line >= 15), "{found:?}");
+        assert_eq!(found.iter().map(|dup| lines(dup).0).min(), Some(19));
+        assert_eq!(found.iter().map(|dup| lines(dup).1).min(), Some(15));
+
+        let with_comments = find_duplicates_in(&context, &DuplicateConfig { ignore_comments: false, ..config });
+        assert!(with_comments.iter().map(lines).any(|(config_line, _)| config_line < 14));
+    }
+
+    #[test]
+    fn test_function_blocks_fall_back_for_unsupported_languages() {
+        let config = DuplicateConfig::default();
+        let functions = vec![("f".to_string(), 1)];
+        assert!(extract_function_blocks("a.rb", "def f\n  1\nend\n", &functions, &config).is_none());
+        let blocks = extract_function_blocks("a.rs", "fn f() {\n    let a = 1;\n}\n", &functions, &config).unwrap();
+        assert!(blocks.is_empty());
+    }
+
+    #[test]
+    #[ignore = "benchmark-style: scans the repository's own sources; run with --ignored"]
+    fn test_parallel_matches_sequential() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+        let context = ExtractionContext::build(&root, None, None).unwrap();
+        let config = DuplicateConfig { min_lines: 3, ..Default::default() };
+        let run = |threads: usize| {
+            let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+            let started = std::time::Instant::now();
+            let found = pool.install(|| find_duplicates_in(&context, &config));
+            eprintln!("{threads} thread(s): {} duplicates in {:?}", found.len(), started.elapsed());
+            serde_json::to_string(&found).unwrap()
+        };
+        assert_eq!(run(1), run(8));
+    }
 }
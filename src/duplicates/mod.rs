@@ -8,15 +8,26 @@
 //! - `normalize`: Code normalization for better comparison
 //! - `similarity`: Multi-metric similarity calculation
 //! - `detector`: Core detection logic with parallel processing
+//! - `ownership`: Heuristics for which copy of a duplicate to keep
+//! - `sections`: Import runs and declaration lists that may be compared in any order
+//! - `cluster`: Grouping of pairwise matches into clusters of every copy
 
 mod types;
 mod normalize;
 mod similarity;
 mod detector;
+mod ownership;
+mod sections;
+mod cluster;
 
-pub use types::{CloneType, DuplicateConfig, EnhancedDuplicateBlock};
+pub use types::{CloneType, DuplicateConfig, EnhancedDuplicateBlock, Granularity, DEFAULT_MIN_LINES, DEFAULT_SIMILARITY};
+pub use cluster::{cluster_duplicates, DuplicateCluster, DuplicateOccurrence};
+pub use ownership::{suggest_keep, test_likeness, CopyCandidate, KeepSuggestion, OwnershipWeights};
 
+use crate::githistory::GitSearcher;
+use crate::parser::ExtractionContext;
 use crate::types::DuplicateBlock;
+use crate::theme::{self, Glyph};
 use colored::*;
 use std::path::Path;
 
@@ -29,7 +40,7 @@ pub fn detect_duplicates(
     similarity_threshold: f64,
 ) -> Result<(), Box<dyn std::error::Error>> {
     println!("{}", "Code Duplication Detection".cyan().bold());
-    println!("{}", "─".repeat(30).cyan());
+    println!("{}", theme::rule(30).cyan());
     println!();
 
     let config = DuplicateConfig {
@@ -38,7 +49,7 @@ pub fn detect_duplicates(
         ..Default::default()
     };
 
-    let duplicates = detector::find_duplicates(path, extensions, exclude, config)?;
+    let duplicates = find_duplicates_enhanced(path, extensions, exclude, config)?;
 
     print_enhanced_results(&duplicates);
     Ok(())
@@ -55,6 +66,7 @@ pub fn find_duplicates(
     let config = DuplicateConfig {
         min_lines,
         similarity_threshold,
+        suggest_keep: false,
         ..Default::default()
     };
 
@@ -77,15 +89,15 @@ pub fn detect_duplicates_enhanced(
     extensions: Option<&[String]>,
     exclude: Option<&[String]>,
     config: DuplicateConfig,
-) -> Result<(), Box<dyn std::error::Error>> {
+) -> Result<Vec<EnhancedDuplicateBlock>, Box<dyn std::error::Error>> {
     println!("{}", "Enhanced Code Duplication Detection".cyan().bold());
-    println!("{}", "─".repeat(40).cyan());
+    println!("{}", theme::rule(40).cyan());
     println!();
 
-    let duplicates = detector::find_duplicates(path, extensions, exclude, config)?;
+    let duplicates = find_duplicates_enhanced(path, extensions, exclude, config)?;
 
     print_enhanced_results(&duplicates);
-    Ok(())
+    Ok(duplicates)
 }
 
 /// Find duplicates with enhanced configuration
@@ -95,7 +107,95 @@ pub fn find_duplicates_enhanced(
     exclude: Option<&[String]>,
     config: DuplicateConfig,
 ) -> Result<Vec<EnhancedDuplicateBlock>, Box<dyn std::error::Error>> {
-    detector::find_duplicates(path, extensions, exclude, config)
+    let context = ExtractionContext::build(path, extensions, exclude)?;
+    Ok(find_duplicates_enhanced_in(&context, path, config))
+}
+
+/// Find duplicates grouped into clusters, one per duplicated block
+pub fn find_duplicate_clusters(
+    path: &Path,
+    extensions: Option<&[String]>,
+    exclude: Option<&[String]>,
+    config: DuplicateConfig,
+) -> Result<Vec<DuplicateCluster>, Box<dyn std::error::Error>> {
+    let duplicates = find_duplicates_enhanced(path, extensions, exclude, config)?;
+    Ok(cluster_duplicates(&duplicates))
+}
+
+/// Enhanced duplicate detection over an already built extraction context
+///
+/// `path` is only used to look up git history for keep suggestions.
+pub fn find_duplicates_enhanced_in(
+    context: &ExtractionContext,
+    path: &Path,
+    config: DuplicateConfig,
+) -> Vec<EnhancedDuplicateBlock> {
+    let mut duplicates = detector::find_duplicates_in(context, &config);
+    if config.suggest_keep && !duplicates.is_empty() {
+        annotate_ownership_in(&mut duplicates, context, path, &config.ownership_weights);
+    }
+    duplicates
+}
+
+/// Attach a keep suggestion to every duplicate
+///
+/// Inbound references use the same per-file counting as dead code detection;
+/// git history is consulted when `path` is inside a repository.
+pub fn annotate_ownership(
+    duplicates: &mut [EnhancedDuplicateBlock],
+    path: &Path,
+    extensions: Option<&[String]>,
+    exclude: Option<&[String]>,
+    weights: &OwnershipWeights,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let context = ExtractionContext::build(path, extensions, exclude)?;
+    annotate_ownership_in(duplicates, &context, path, weights);
+    Ok(())
+}
+
+/// [`annotate_ownership`] using an already built extraction context
+pub fn annotate_ownership_in(
+    duplicates: &mut [EnhancedDuplicateBlock],
+    context: &ExtractionContext,
+    path: &Path,
+    weights: &OwnershipWeights,
+) {
+    let references = context.reference_counts();
+    let git = GitSearcher::new(path).ok();
+
+    let candidate = |file: &str, line: usize, line_count: usize| {
+        let block_lines = line..line + line_count;
+        let inbound_refs = context
+            .get(file)
+            .map(|f| f.functions.as_slice())
+            .unwrap_or_default()
+            .iter()
+            .filter(|(_, def_line)| block_lines.contains(def_line))
+            // The defining file itself is one of the referencing files
+            .map(|(name, _)| references.get(name).copied().unwrap_or(0).saturating_sub(1))
+            .sum();
+
+        CopyCandidate {
+            file: file.to_string(),
+            line,
+            test_likeness: test_likeness(file),
+            inbound_refs,
+            introduced_at: git.as_ref().and_then(|g| g.line_timestamp(Path::new(file), line)),
+        }
+    };
+
+    for dup in duplicates.iter_mut() {
+        let copies = [
+            candidate(&dup.file1, dup.line1, dup.line_count),
+            candidate(&dup.file2, dup.line2, dup.line_count),
+        ];
+        dup.suggestion = suggest_keep(&copies, weights);
+    }
+
+    // Ownership falls back to test-likeness and references when git can't date lines
+    for warning in git.iter().flat_map(|g| g.warnings()) {
+        eprintln!("{} {}", "Warning:".yellow(), warning);
+    }
 }
 
 fn print_enhanced_results(duplicates: &[EnhancedDuplicateBlock]) {
@@ -104,21 +204,23 @@ fn print_enhanced_results(duplicates: &[EnhancedDuplicateBlock]) {
         return;
     }
 
+    let clusters = cluster_duplicates(duplicates);
+
     // Group by clone type
     let mut type1_count = 0;
     let mut type2_count = 0;
     let mut type3_count = 0;
     let mut type4_count = 0;
 
-    for dup in duplicates {
-        match dup.clone_type {
+    for cluster in &clusters {
+        match cluster.clone_type {
             CloneType::Type1 => type1_count += 1,
             CloneType::Type2 => type2_count += 1,
             CloneType::Type3 => type3_count += 1,
             CloneType::Type4 => type4_count += 1,
         }
 
-        let clone_badge = match dup.clone_type {
+        let clone_badge = match cluster.clone_type {
             CloneType::Type1 => "T1".red(),
             CloneType::Type2 => "T2".yellow(),
             CloneType::Type3 => "T3".blue(),
@@ -126,53 +228,63 @@ fn print_enhanced_results(duplicates: &[EnhancedDuplicateBlock]) {
         };
 
         println!(
-            "{} {} {:.0}% similar ({} lines)",
-            "🔄".dimmed(),
+            "{} {} {} copies, {:.0}% similar ({} lines)",
+            theme::glyph(Glyph::Duplicate).dimmed(),
             clone_badge,
-            dup.similarity * 100.0,
-            dup.line_count
-        );
-        println!(
-            "   {} {}:{}",
-            "→".dimmed(),
-            dup.file1.blue(),
-            dup.line1.to_string().yellow()
-        );
-        println!(
-            "   {} {}:{}",
-            "→".dimmed(),
-            dup.file2.blue(),
-            dup.line2.to_string().yellow()
-        );
-        println!(
-            "   {} Token: {:.0}% | Structural: {:.0}%",
-            "📊".dimmed(),
-            dup.token_similarity * 100.0,
-            dup.structural_similarity * 100.0
+            cluster.occurrences.len(),
+            cluster.similarity * 100.0,
+            cluster.line_count
         );
-        println!("   {}", dup.content.dimmed());
+        for occurrence in &cluster.occurrences {
+            let function = occurrence.fn_name.as_ref().map(|name| format!(" in {}", name.green())).unwrap_or_default();
+            println!(
+                "   {} {}:{}{}",
+                theme::arrow().dimmed(),
+                theme::link(occurrence.file.blue(), &occurrence.file, Some(occurrence.line)),
+                occurrence.line.to_string().yellow(),
+                function
+            );
+        }
+        println!("   {}", cluster.representative_content.dimmed());
+        if let Some(suggestion) = &cluster.suggestion {
+            let reasons = if suggestion.reasons.is_empty() {
+                "no clear winner, first copy".to_string()
+            } else {
+                suggestion.reasons.join(", ")
+            };
+            println!(
+                "   {} suggest keeping {}:{}, replacing {} other cop{} ({})",
+                theme::glyph(Glyph::Hint).dimmed(),
+                suggestion.keep_file.green(),
+                suggestion.keep_line,
+                suggestion.replace_count,
+                if suggestion.replace_count == 1 { "y" } else { "ies" },
+                reasons.dimmed()
+            );
+        }
         println!();
     }
 
-    println!("{}", "─".repeat(60).dimmed());
+    println!("{}", theme::rule(60).dimmed());
     println!(
-        "{} {} potential duplicates found",
-        "📈".dimmed(),
-        duplicates.len().to_string().yellow().bold()
+        "{} {} duplicate clusters ({} pairwise matches)",
+        theme::glyph(Glyph::Summary).dimmed(),
+        clusters.len().to_string().yellow().bold(),
+        duplicates.len()
     );
     
     println!("\n{}", "Clone Type Breakdown:".cyan());
     if type1_count > 0 {
-        println!("  {} Type-1 (Exact): {}", "•".dimmed(), type1_count.to_string().red());
+        println!("  {} Type-1 (Exact): {}", theme::bullet().dimmed(), type1_count.to_string().red());
     }
     if type2_count > 0 {
-        println!("  {} Type-2 (Renamed): {}", "•".dimmed(), type2_count.to_string().yellow());
+        println!("  {} Type-2 (Renamed): {}", theme::bullet().dimmed(), type2_count.to_string().yellow());
     }
     if type3_count > 0 {
-        println!("  {} Type-3 (Modified): {}", "•".dimmed(), type3_count.to_string().blue());
+        println!("  {} Type-3 (Modified): {}", theme::bullet().dimmed(), type3_count.to_string().blue());
     }
     if type4_count > 0 {
-        println!("  {} Type-4 (Semantic): {}", "•".dimmed(), type4_count.to_string().magenta());
+        println!("  {} Type-4 (Semantic): {}", theme::bullet().dimmed(), type4_count.to_string().magenta());
     }
 }
 
@@ -0,0 +1,204 @@
+//! Heuristics for choosing which copy of a duplicate to keep
+
+use serde::Serialize;
+
+/// Facts about one copy of a duplicated block
+#[derive(Debug, Clone, PartialEq)]
+pub struct CopyCandidate {
+    pub file: String,
+    pub line: usize,
+    /// 0.0 for production-looking paths, up to 1.0 for test/fixture/example paths
+    pub test_likeness: f64,
+    /// References to the names the copy defines, counted across the codebase
+    pub inbound_refs: usize,
+    /// Unix timestamp of the commit that introduced the copy, when git history is available
+    pub introduced_at: Option<i64>,
+}
+
+/// Relative weight of each factor in the keep score
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OwnershipWeights {
+    pub path: f64,
+    pub references: f64,
+    pub age: f64,
+}
+
+impl Default for OwnershipWeights {
+    fn default() -> Self {
+        Self {
+            path: 1.0,
+            references: 1.0,
+            age: 0.5,
+        }
+    }
+}
+
+/// Recommendation for which copy to keep
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct KeepSuggestion {
+    pub keep_file: String,
+    pub keep_line: usize,
+    pub replace_count: usize,
+    pub score: f64,
+    /// Factors in which the kept copy beats every other copy
+    pub reasons: Vec<String>,
+}
+
+/// Score each copy and suggest the one to keep
+///
+/// Each factor is normalized to 0..1 across the candidates before weighting, so
+/// a factor on which all copies agree never decides the outcome. Ties go to the
+/// first candidate.
+pub fn suggest_keep(candidates: &[CopyCandidate], weights: &OwnershipWeights) -> Option<KeepSuggestion> {
+    if candidates.len() < 2 {
+        return None;
+    }
+
+    let max_refs = candidates.iter().map(|c| c.inbound_refs).max().unwrap_or(0);
+    let times: Vec<i64> = candidates.iter().filter_map(|c| c.introduced_at).collect();
+    let oldest = times.iter().min().copied();
+    let newest = times.iter().max().copied();
+
+    let path_score = |c: &CopyCandidate| 1.0 - c.test_likeness.clamp(0.0, 1.0);
+    let ref_score = |c: &CopyCandidate| {
+        if max_refs == 0 {
+            0.0
+        } else {
+            c.inbound_refs as f64 / max_refs as f64
+        }
+    };
+    let age_score = |c: &CopyCandidate| match (c.introduced_at, oldest, newest) {
+        (Some(t), Some(old), Some(new)) if new > old => (new - t) as f64 / (new - old) as f64,
+        _ => 0.0,
+    };
+
+    let scores: Vec<f64> = candidates
+        .iter()
+        .map(|c| weights.path * path_score(c) + weights.references * ref_score(c) + weights.age * age_score(c))
+        .collect();
+
+    let (winner, score) = scores
+        .iter()
+        .enumerate()
+        .fold((0, f64::MIN), |best, (i, &s)| if s > best.1 { (i, s) } else { best });
+    let keep = &candidates[winner];
+    let others = || candidates.iter().enumerate().filter(move |(i, _)| *i != winner).map(|(_, c)| c);
+
+    let mut reasons = Vec::new();
+    if weights.path > 0.0 && others().all(|c| path_score(keep) > path_score(c)) {
+        reasons.push("least test-like path".to_string());
+    }
+    if weights.references > 0.0 && others().all(|c| keep.inbound_refs > c.inbound_refs) {
+        reasons.push(format!("most inbound references ({})", keep.inbound_refs));
+    }
+    if weights.age > 0.0
+        && keep.introduced_at.is_some()
+        && others().all(|c| c.introduced_at.is_none_or(|t| keep.introduced_at.unwrap() < t))
+    {
+        reasons.push("oldest in git history".to_string());
+    }
+
+    Some(KeepSuggestion {
+        keep_file: keep.file.clone(),
+        keep_line: keep.line,
+        replace_count: candidates.len() - 1,
+        score,
+        reasons,
+    })
+}
+
+/// How much a path looks like test, fixture, or example code (0.0 - 1.0)
+pub fn test_likeness(path: &str) -> f64 {
+    const MARKERS: [&str; 8] = ["test", "spec", "__tests__", "fixture", "mock", "example", "bench", "sample"];
+    let lower = path.to_lowercase().replace('\\', "/");
+    let hits = lower
+        .split('/')
+        .filter(|component| MARKERS.iter().any(|m| component.contains(m)))
+        .count();
+    (hits as f64 * 0.5).min(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(file: &str, refs: usize, introduced_at: Option<i64>) -> CopyCandidate {
+        CopyCandidate {
+            file: file.to_string(),
+            line: 1,
+            test_likeness: test_likeness(file),
+            inbound_refs: refs,
+            introduced_at,
+        }
+    }
+
+    #[test]
+    fn test_path_decides_winner() {
+        let copies = [
+            candidate("tests/helpers/util.rs", 3, None),
+            candidate("src/util.rs", 3, None),
+        ];
+        let suggestion = suggest_keep(&copies, &OwnershipWeights::default()).unwrap();
+        assert_eq!(suggestion.keep_file, "src/util.rs");
+        assert_eq!(suggestion.replace_count, 1);
+        assert_eq!(suggestion.reasons, vec!["least test-like path"]);
+    }
+
+    #[test]
+    fn test_references_decide_winner() {
+        let copies = [
+            candidate("src/a.rs", 1, None),
+            candidate("src/b.rs", 7, None),
+            candidate("src/c.rs", 2, None),
+        ];
+        let suggestion = suggest_keep(&copies, &OwnershipWeights::default()).unwrap();
+        assert_eq!(suggestion.keep_file, "src/b.rs");
+        assert_eq!(suggestion.replace_count, 2);
+        assert_eq!(suggestion.reasons, vec!["most inbound references (7)"]);
+    }
+
+    #[test]
+    fn test_age_decides_winner() {
+        let copies = [
+            candidate("src/new.rs", 0, Some(2_000)),
+            candidate("src/old.rs", 0, Some(1_000)),
+        ];
+        let suggestion = suggest_keep(&copies, &OwnershipWeights::default()).unwrap();
+        assert_eq!(suggestion.keep_file, "src/old.rs");
+        assert_eq!(suggestion.reasons, vec!["oldest in git history"]);
+    }
+
+    #[test]
+    fn test_weights_change_the_outcome() {
+        let copies = [
+            candidate("tests/util.rs", 10, None),
+            candidate("src/util.rs", 1, None),
+        ];
+        let by_refs = OwnershipWeights { path: 0.5, references: 2.0, age: 0.0 };
+        assert_eq!(suggest_keep(&copies, &by_refs).unwrap().keep_file, "tests/util.rs");
+
+        let by_path = OwnershipWeights { path: 2.0, references: 0.5, age: 0.0 };
+        assert_eq!(suggest_keep(&copies, &by_path).unwrap().keep_file, "src/util.rs");
+    }
+
+    #[test]
+    fn test_tie_keeps_first_and_gives_no_reasons() {
+        let copies = [candidate("src/a.rs", 0, None), candidate("src/b.rs", 0, None)];
+        let suggestion = suggest_keep(&copies, &OwnershipWeights::default()).unwrap();
+        assert_eq!(suggestion.keep_file, "src/a.rs");
+        assert!(suggestion.reasons.is_empty());
+    }
+
+    #[test]
+    fn test_single_copy_has_no_suggestion() {
+        assert!(suggest_keep(&[candidate("src/a.rs", 0, None)], &OwnershipWeights::default()).is_none());
+    }
+
+    #[test]
+    fn test_test_likeness() {
+        assert_eq!(test_likeness("src/util.rs"), 0.0);
+        assert_eq!(test_likeness("tests/util.rs"), 0.5);
+        assert_eq!(test_likeness("tests/fixtures/util.rs"), 1.0);
+        assert_eq!(test_likeness("src/util_test.go"), 0.5);
+    }
+}
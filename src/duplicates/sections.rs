@@ -0,0 +1,196 @@
+//! Order-insensitive declaration sections
+//!
+//! Import blocks and field/variant lists mean the same thing in any order, so
+//! with `order_insensitive_sections` their lines are sorted before comparison.
+//! Executable code is never reordered.
+
+use crate::language::get_language_by_extension;
+use crate::lines::{line_starts, split_lines};
+use crate::parser::find_block;
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Lines (0-based, as in [`split_lines`]) whose order doesn't matter, one group per section
+///
+/// A section is either a run of at least two consecutive single-line imports,
+/// or the body of a class-like block holding only field or variant
+/// declarations. Comment lines inside a section keep their place.
+pub fn declaration_sections(file_path: &str, content: &str) -> Vec<Vec<usize>> {
+    let Some(lang) = Path::new(file_path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .and_then(get_language_by_extension)
+    else {
+        return Vec::new();
+    };
+
+    let lines: Vec<&str> = split_lines(content).collect();
+    let starts = line_starts(content);
+    let mut sections = import_runs(&lines, lang.import_patterns);
+    if let Ok(class_re) = Regex::new(&format!(r"\b(?:{})", lang.class_patterns.join("|"))) {
+        for (idx, line) in lines.iter().enumerate() {
+            if class_re.is_match(line) && !is_comment(line.trim()) {
+                sections.extend(declaration_list(content, &lines, &starts, idx, file_path));
+            }
+        }
+    }
+    sections
+}
+
+/// Copy of `window` with the lines of each section sorted among themselves
+///
+/// `window` holds `(line index, trimmed line)` pairs; only section lines inside
+/// the window are sorted, so a window that cuts a section compares just its part.
+pub fn canonicalize_window<'a>(window: &[(usize, &'a str)], section_of: &HashMap<usize, usize>) -> Vec<&'a str> {
+    let mut canonical: Vec<&str> = window.iter().map(|(_, line)| *line).collect();
+    let mut positions: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (pos, (idx, _)) in window.iter().enumerate() {
+        if let Some(&section) = section_of.get(idx) {
+            positions.entry(section).or_default().push(pos);
+        }
+    }
+    for slots in positions.values().filter(|slots| slots.len() > 1) {
+        let mut values: Vec<&str> = slots.iter().map(|&pos| canonical[pos]).collect();
+        values.sort_unstable();
+        for (&pos, value) in slots.iter().zip(values) {
+            canonical[pos] = value;
+        }
+    }
+    canonical
+}
+
+/// Map each section line to the index of its section
+pub fn section_index(sections: &[Vec<usize>]) -> HashMap<usize, usize> {
+    sections
+        .iter()
+        .enumerate()
+        .flat_map(|(section, lines)| lines.iter().map(move |&idx| (idx, section)))
+        .collect()
+}
+
+fn import_runs(lines: &[&str], import_patterns: &[&str]) -> Vec<Vec<usize>> {
+    let Ok(import_re) = Regex::new(&format!(r"^(?:pub(?:\([^)]*\))?\s+)?(?:{})", import_patterns.join("|"))) else {
+        return Vec::new();
+    };
+    // Only whole single-line imports: no assignments, no statement spilling onto the next line
+    let is_import = |line: &str| {
+        import_re.is_match(line) && !line.contains('=') && !line.ends_with(['{', '(', '[', ',', '\\'])
+    };
+
+    let mut runs = Vec::new();
+    let mut current: Vec<usize> = Vec::new();
+    for (idx, line) in lines.iter().enumerate() {
+        let trimmed = line.trim();
+        if is_import(trimmed) {
+            current.push(idx);
+        } else if !is_comment(trimmed) {
+            // Blank lines and code end a run, comments don't
+            runs.push(std::mem::take(&mut current));
+        }
+    }
+    runs.push(current);
+    runs.retain(|run| run.len() > 1);
+    runs
+}
+
+fn declaration_list(content: &str, lines: &[&str], starts: &[usize], header: usize, file_path: &str) -> Option<Vec<usize>> {
+    let span = find_block(content, starts[header], file_path)?;
+
+    let mut section = Vec::new();
+    for (idx, line) in lines.iter().enumerate().skip(header) {
+        let line_start = starts[idx];
+        if line_start > span.end {
+            break;
+        }
+        if line_start >= span.start && line_start + line.len() <= span.end {
+            let trimmed = line.trim();
+            if is_declaration(trimmed) {
+                section.push(idx);
+            } else if !(trimmed.is_empty() || is_comment(trimmed)) {
+                return None;
+            }
+        }
+    }
+    (section.len() > 1).then_some(section)
+}
+
+fn is_comment(line: &str) -> bool {
+    line.starts_with("//")
+        || line.starts_with("/*")
+        || line.starts_with('*')
+        || (line.starts_with('#') && !line.starts_with("#["))
+}
+
+/// A field or variant declaration, never code with a body, a value or an attribute
+fn is_declaration(line: &str) -> bool {
+    static FIELD_RE: OnceLock<Regex> = OnceLock::new();
+    // `name: Type` without a separator (last Rust field, Python annotation),
+    // a Go field with an optional tag, or a bare enum variant
+    let field_re = FIELD_RE.get_or_init(|| {
+        Regex::new(r"^(?:(?:pub(?:\([^)]*\))?\s+)?[A-Za-z_]\w*\??\s*:\s*[^:\s].*|[A-Za-z_]\w*\s+[\w.*\[\]]+(?:\s+`[^`]*`)?|[A-Za-z_]\w*)$").unwrap()
+    });
+
+    if line.starts_with("#[") || line.starts_with('@') || line.contains(['{', '}', '=']) {
+        return false;
+    }
+    let code = line.split(" //").next().unwrap_or(line).trim_end();
+    code.ends_with([',', ';']) || field_re.is_match(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import_runs() {
+        let content = "use std::fmt;\nuse std::io;\n// grouped\nuse std::fs;\n\nuse regex::Regex;\nfn main() {}\n";
+        assert_eq!(declaration_sections("a.rs", content), vec![vec![0, 1, 3]]);
+
+        let content = "import os\nimport sys\nx = 1\nimport re\n";
+        assert_eq!(declaration_sections("a.py", content), vec![vec![0, 1]]);
+
+        // A multi-line import is left alone
+        let content = "use std::fmt;\nuse std::{\n    io,\n};\n";
+        assert!(declaration_sections("a.rs", content).is_empty());
+    }
+
+    #[test]
+    fn test_field_and_variant_lists() {
+        let content = "pub struct User {\n    pub id: u64,\n    // display name\n    pub name: String,\n    age: u32\n}\n";
+        assert_eq!(declaration_sections("a.rs", content), vec![vec![1, 3, 4]]);
+
+        let content = "enum Color {\n    Red,\n    Green(u8),\n    Blue\n}\n";
+        assert_eq!(declaration_sections("a.rs", content), vec![vec![1, 2, 3]]);
+
+        let content = "class Point:\n    x: int\n    y: int\n";
+        assert_eq!(declaration_sections("a.py", content), vec![vec![1, 2]]);
+    }
+
+    #[test]
+    fn test_code_is_never_a_section() {
+        let content = "impl User {\n    fn a() {}\n    fn b() {}\n}\n";
+        assert!(declaration_sections("a.rs", content).is_empty());
+
+        // Attributes belong to the field below them
+        let content = "struct Config {\n    #[serde(default)]\n    a: u32,\n    b: u32,\n}\n";
+        assert!(declaration_sections("a.rs", content).is_empty());
+
+        let content = "class Counter:\n    count: int\n    def bump(self):\n        self.count += 1\n";
+        assert!(declaration_sections("a.py", content).is_empty());
+    }
+
+    #[test]
+    fn test_canonicalize_window_sorts_only_section_lines() {
+        let lines = ["use b;", "use a;", "let x = 1;", "use d;", "use c;"];
+        let window: Vec<(usize, &str)> = lines.iter().copied().enumerate().collect();
+        let section_of = section_index(&[vec![0, 1], vec![3, 4]]);
+        assert_eq!(
+            canonicalize_window(&window, &section_of),
+            vec!["use a;", "use b;", "let x = 1;", "use c;", "use d;"]
+        );
+        // A window holding one line of a section leaves it unchanged
+        assert_eq!(canonicalize_window(&window[1..3], &section_of), vec!["use a;", "let x = 1;"]);
+    }
+}
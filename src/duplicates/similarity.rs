@@ -26,8 +26,8 @@ pub fn calculate_similarity(block1: &CodeBlock, block2: &CodeBlock) -> Similarit
         &block2.content,
         &block1.normalized,
         &block2.normalized,
-        block1.hash,
-        block2.hash,
+        block1.ordered_hash,
+        block2.ordered_hash,
         block1.normalized_hash,
         block2.normalized_hash,
     );
@@ -129,14 +129,15 @@ fn determine_clone_type(
     _content2: &str,
     normalized1: &str,
     normalized2: &str,
-    _hash1: u64,
-    _hash2: u64,
+    ordered_hash1: u64,
+    ordered_hash2: u64,
     norm_hash1: u64,
     norm_hash2: u64,
 ) -> CloneType {
-    // Type-1: Exact match after normalization (whitespace/comments removed)
+    // Type-1: Exact match after normalization (whitespace/comments removed);
+    // a match only after sorting declaration sections is a Type-2 clone
     if normalized1 == normalized2 {
-        return CloneType::Type1;
+        return if ordered_hash1 == ordered_hash2 { CloneType::Type1 } else { CloneType::Type2 };
     }
     
     // Type-2: Same structure with renamed variables
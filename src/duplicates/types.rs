@@ -1,9 +1,10 @@
 //! Type definitions for duplicate detection
 
+use super::ownership::{KeepSuggestion, OwnershipWeights};
 use serde::Serialize;
 
 /// Clone type classification
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
 pub enum CloneType {
     /// Type-1: Exact copies (except whitespace/comments)
     Type1,
@@ -15,6 +16,29 @@ pub enum CloneType {
     Type4,
 }
 
+/// Unit of code compared for duplication
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Granularity {
+    /// Sliding windows of `min_lines` to `3 * min_lines` lines
+    #[default]
+    Window,
+    /// Whole function bodies; files whose language has no reliable function
+    /// extraction fall back to windows
+    Function,
+}
+
+impl std::str::FromStr for Granularity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "window" => Ok(Self::Window),
+            "function" | "fn" => Ok(Self::Function),
+            other => Err(format!("Unknown granularity: {other} (expected: window, function)")),
+        }
+    }
+}
+
 /// Enhanced duplicate block with multi-metric similarity
 #[derive(Debug, Clone, Serialize)]
 pub struct EnhancedDuplicateBlock {
@@ -28,8 +52,23 @@ pub struct EnhancedDuplicateBlock {
     pub token_similarity: f64,
     pub structural_similarity: f64,
     pub line_count: usize,
+    /// Function containing the first copy, with function granularity
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fn_name1: Option<String>,
+    /// Function containing the second copy, with function granularity
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fn_name2: Option<String>,
+    /// Which copy to keep, when ownership suggestions are enabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<KeepSuggestion>,
 }
 
+/// Default for `duplicates --min-lines` and `[duplicates] min_lines`
+pub const DEFAULT_MIN_LINES: usize = 3;
+
+/// Default for `duplicates --similarity` and `[duplicates] similarity`
+pub const DEFAULT_SIMILARITY: f64 = 0.9;
+
 /// Configuration for duplicate detection
 #[derive(Debug, Clone)]
 pub struct DuplicateConfig {
@@ -50,6 +89,20 @@ pub struct DuplicateConfig {
     // Performance
     pub use_parallel: bool,
     pub max_file_size: usize,
+
+    // Ownership suggestions
+    pub suggest_keep: bool,
+    pub ownership_weights: OwnershipWeights,
+
+    /// Compare import runs and field/variant lists regardless of line order
+    pub order_insensitive_sections: bool,
+
+    /// Compare sliding windows or whole functions
+    pub granularity: Granularity,
+
+    /// Blank comments and string literal contents before comparing, so license
+    /// headers, docstrings and commented-out code don't count as duplicates
+    pub ignore_comments: bool,
 }
 
 impl Default for DuplicateConfig {
@@ -66,6 +119,11 @@ impl Default for DuplicateConfig {
             detect_type3: true,
             use_parallel: true,
             max_file_size: 1_000_000, // 1MB
+            suggest_keep: true,
+            ownership_weights: OwnershipWeights::default(),
+            order_insensitive_sections: false,
+            granularity: Granularity::Window,
+            ignore_comments: true,
         }
     }
 }
@@ -81,4 +139,9 @@ pub struct CodeBlock {
     pub tokens: Vec<String>,
     pub hash: u64,
     pub normalized_hash: u64,
+    /// Hash of the normalized lines in source order; only differs from a hash of
+    /// `normalized` when declaration sections were reordered for comparison
+    pub ordered_hash: u64,
+    /// Name of the function the block is, with function granularity
+    pub fn_name: Option<String>,
 }
@@ -0,0 +1,611 @@
+//! Embedding Search Module (experimental, `embeddings` feature)
+//!
+//! Embeds every indexed function with a local model and ranks them by cosine
+//! similarity to a natural-language query. Nothing touches the network: the
+//! model is a file on disk, loaded through the [`Embedder`] trait.
+//!
+//! Two backends are bundled, picked by [`load_embedder`]: [`TransformerEmbedder`]
+//! runs a sentence-transformer checkpoint (e.g. `all-MiniLM-L6-v2`) with
+//! candle, and [`StaticEmbedder`] averages per-token vectors from a
+//! word2vec/GloVe-style text file.
+//!
+//! Vectors are stored next to the persistent index (see [`vector_store_path`])
+//! and are only recomputed for functions whose text changed. Search is a brute
+//! force scan, so stores are capped at [`MAX_VECTORS`] functions.
+
+use crate::parser::extract_functions;
+use candle_core::{Device, Tensor};
+use candle_nn::VarBuilder;
+use candle_transformers::models::bert::{BertModel, Config as BertConfig, DTYPE};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use tokenizers::{Tokenizer, TruncationParams};
+
+/// Most functions a store may hold; every query compares against all of them
+pub const MAX_VECTORS: usize = 100_000;
+
+/// Lines of a function's body included in its embedded text
+pub const MAX_CHUNK_LINES: usize = 60;
+
+/// Turns text into a fixed-size vector
+pub trait Embedder {
+    /// Identifies the model; stored vectors from another model are discarded
+    fn model_id(&self) -> String;
+
+    fn dimensions(&self) -> usize;
+
+    fn embed(&self, text: &str) -> Vec<f32>;
+}
+
+/// Load the model at `path`: a sentence-transformer directory, or a word-vector file
+pub fn load_embedder(path: &Path) -> Result<Box<dyn Embedder>, Box<dyn std::error::Error>> {
+    if path.is_dir() {
+        Ok(Box::new(TransformerEmbedder::load(path)?))
+    } else {
+        Ok(Box::new(StaticEmbedder::load(path)?))
+    }
+}
+
+/// A BERT-family sentence-transformer run on the CPU, mean-pooled over its tokens
+///
+/// The model directory holds what a sentence-transformers checkpoint ships
+/// with: `config.json`, `tokenizer.json` and `model.safetensors`. Texts longer
+/// than the model's positions are truncated.
+pub struct TransformerEmbedder {
+    id: String,
+    dimensions: usize,
+    model: BertModel,
+    tokenizer: Tokenizer,
+}
+
+impl TransformerEmbedder {
+    pub fn load(dir: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let read = |name: &str| {
+            let path = dir.join(name);
+            fs::read(&path).map_err(|e| format!("Cannot read embedding model {}: {}", path.display(), e))
+        };
+        let config: BertConfig = serde_json::from_slice(&read("config.json")?)
+            .map_err(|e| format!("Embedding model config.json: {e}"))?;
+        let weights = read("model.safetensors")?;
+        let mut tokenizer = Tokenizer::from_file(dir.join("tokenizer.json"))
+            .map_err(|e| format!("Embedding model tokenizer.json: {e}"))?;
+        tokenizer.with_padding(None);
+        tokenizer
+            .with_truncation(Some(TruncationParams {
+                max_length: config.max_position_embeddings,
+                ..Default::default()
+            }))
+            .map_err(|e| format!("Embedding model tokenizer.json: {e}"))?;
+
+        let id = format!("bert:{}:{}", dir.display(), weights.len());
+        let weights = VarBuilder::from_buffered_safetensors(weights, DTYPE, &Device::Cpu)?;
+        let model = BertModel::load(weights, &config).map_err(|e| format!("Embedding model weights: {e}"))?;
+        Ok(Self { id, dimensions: config.hidden_size, model, tokenizer })
+    }
+
+    fn try_embed(&self, text: &str) -> Result<Vec<f32>, Box<dyn std::error::Error>> {
+        let encoding = self.tokenizer.encode(text, true).map_err(|e| e.to_string())?;
+        if encoding.get_ids().is_empty() {
+            return Err("No tokens to embed".into());
+        }
+        let device = &self.model.device;
+        let ids = Tensor::new(encoding.get_ids(), device)?.unsqueeze(0)?;
+        let type_ids = Tensor::new(encoding.get_type_ids(), device)?.unsqueeze(0)?;
+        let mask = Tensor::new(encoding.get_attention_mask(), device)?.unsqueeze(0)?;
+        let hidden = self.model.forward(&ids, &type_ids, Some(&mask))?;
+
+        // Average the token states the attention mask keeps
+        let mask = mask.to_dtype(DTYPE)?.unsqueeze(2)?;
+        let pooled = hidden.broadcast_mul(&mask)?.sum(1)?.broadcast_div(&mask.sum(1)?)?;
+        Ok(pooled.squeeze(0)?.to_vec1::<f32>()?)
+    }
+}
+
+impl Embedder for TransformerEmbedder {
+    fn model_id(&self) -> String {
+        self.id.clone()
+    }
+
+    fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    /// A text the model can't encode (such as one with no tokens) gets the
+    /// zero vector, which is similar to nothing
+    fn embed(&self, text: &str) -> Vec<f32> {
+        match self.try_embed(text) {
+            Ok(vector) => normalize(vector),
+            Err(_) => vec![0.0; self.dimensions],
+        }
+    }
+}
+
+/// Averages token vectors read from a word-vector text file
+///
+/// Each line is a token followed by its components (`parse 0.12 -0.4 ...`);
+/// a leading `count dimensions` header line, as word2vec writes, is skipped.
+/// Identifiers are split on `snake_case` and `camelCase` boundaries first, so
+/// `parseConfigFile` contributes `parse`, `config` and `file`.
+pub struct StaticEmbedder {
+    id: String,
+    dimensions: usize,
+    vectors: HashMap<String, Vec<f32>>,
+}
+
+impl StaticEmbedder {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read embedding model {}: {}", path.display(), e))?;
+        let mut embedder = Self::parse(&content)?;
+        embedder.id = format!("static:{}:{}", path.display(), content.len());
+        Ok(embedder)
+    }
+
+    fn parse(content: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut vectors = HashMap::new();
+        let mut dimensions = 0;
+        for (index, line) in content.lines().enumerate() {
+            let mut fields = line.split_whitespace();
+            let Some(token) = fields.next() else { continue };
+            let components: Vec<f32> = fields
+                .map(str::parse)
+                .collect::<Result<_, _>>()
+                .map_err(|e| format!("Embedding model line {}: {}", index + 1, e))?;
+            if index == 0 && components.len() == 1 && token.parse::<usize>().is_ok() {
+                continue;
+            }
+            if dimensions == 0 {
+                dimensions = components.len();
+            }
+            if components.len() != dimensions || dimensions == 0 {
+                return Err(format!(
+                    "Embedding model line {}: expected {} components, found {}",
+                    index + 1,
+                    dimensions,
+                    components.len()
+                )
+                .into());
+            }
+            vectors.insert(token.to_lowercase(), components);
+        }
+        if vectors.is_empty() {
+            return Err("Embedding model has no vectors".into());
+        }
+        Ok(Self { id: String::from("static"), dimensions, vectors })
+    }
+}
+
+impl Embedder for StaticEmbedder {
+    fn model_id(&self) -> String {
+        self.id.clone()
+    }
+
+    fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    fn embed(&self, text: &str) -> Vec<f32> {
+        let mut sum = vec![0.0; self.dimensions];
+        for token in tokenize(text) {
+            if let Some(vector) = self.vectors.get(&token) {
+                for (total, value) in sum.iter_mut().zip(vector) {
+                    *total += value;
+                }
+            }
+        }
+        normalize(sum)
+    }
+}
+
+/// Lowercased words of `text`, with identifiers split into their parts
+pub fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    for word in text.split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty()) {
+        let mut current = String::new();
+        let mut previous_lower = false;
+        for c in word.chars() {
+            if c.is_uppercase() && previous_lower && !current.is_empty() {
+                tokens.push(std::mem::take(&mut current));
+            }
+            previous_lower = c.is_lowercase() || c.is_ascii_digit();
+            current.extend(c.to_lowercase());
+        }
+        if !current.is_empty() {
+            tokens.push(current);
+        }
+    }
+    tokens
+}
+
+/// Scale to unit length, leaving the zero vector alone
+fn normalize(mut vector: Vec<f32>) -> Vec<f32> {
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|v| *v /= norm);
+    }
+    vector
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// A function's name, start line and the text that gets embedded
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionChunk {
+    pub name: String,
+    pub line: usize,
+    pub text: String,
+}
+
+/// Split `content` into one chunk per function, each running up to the next
+/// function (at most [`MAX_CHUNK_LINES`] lines)
+pub fn function_chunks(content: &str, file_path: &str) -> Vec<FunctionChunk> {
+    let mut functions = extract_functions(content, file_path);
+    functions.sort_by_key(|(_, line)| *line);
+    let lines: Vec<&str> = content.lines().collect();
+
+    functions
+        .iter()
+        .enumerate()
+        .map(|(i, (name, line))| {
+            let start = line - 1;
+            let next = functions.get(i + 1).map_or(lines.len(), |(_, next)| next - 1);
+            let end = next.max(start + 1).min(start + MAX_CHUNK_LINES).min(lines.len());
+            FunctionChunk {
+                name: name.clone(),
+                line: *line,
+                text: lines[start.min(end)..end].join("\n"),
+            }
+        })
+        .collect()
+}
+
+fn content_hash(text: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// One embedded function
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredVector {
+    pub path: String,
+    pub name: String,
+    pub line: usize,
+    pub hash: u64,
+    pub vector: Vec<f32>,
+}
+
+/// Embedded functions, persisted as JSON beside the index
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VectorStore {
+    pub model: String,
+    pub dimensions: usize,
+    pub entries: Vec<StoredVector>,
+}
+
+/// What [`VectorStore::update`] did
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct EmbedStats {
+    /// Functions embedded because they are new or their text changed
+    pub embedded: usize,
+    /// Functions whose stored vector was still valid
+    pub reused: usize,
+    /// Stored functions that no longer exist
+    pub removed: usize,
+}
+
+/// A function ranked against a query
+#[derive(Debug, Clone, Serialize)]
+pub struct SemanticHit {
+    pub path: String,
+    pub function: String,
+    pub line: usize,
+    pub score: f32,
+}
+
+/// Where the vectors for `index_file` are kept: `index.json` -> `index.vectors.json`
+pub fn vector_store_path(index_file: &Path) -> PathBuf {
+    index_file.with_extension("vectors.json")
+}
+
+impl VectorStore {
+    /// Load a store, or an empty one if the file doesn't exist yet
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Bring the store in line with the functions in `files`
+    ///
+    /// Functions keep their vector while their path, name and text are
+    /// unchanged; everything is re-embedded when the model differs.
+    pub fn update(&mut self, files: &[String], embedder: &dyn Embedder) -> Result<EmbedStats, Box<dyn std::error::Error>> {
+        let model = embedder.model_id();
+        if self.model != model || self.dimensions != embedder.dimensions() {
+            self.entries.clear();
+            self.model = model;
+            self.dimensions = embedder.dimensions();
+        }
+
+        // Stored functions by path and name; a name can occur more than once per file
+        let mut previous: HashMap<(String, String), Vec<StoredVector>> = HashMap::new();
+        for entry in self.entries.drain(..) {
+            previous.entry((entry.path.clone(), entry.name.clone())).or_default().push(entry);
+        }
+
+        let mut stats = EmbedStats::default();
+        let mut entries = Vec::new();
+        for path in files {
+            let Ok(content) = fs::read_to_string(path) else { continue };
+            for chunk in function_chunks(&content, path) {
+                let hash = content_hash(&chunk.text);
+                let stored = previous.get_mut(&(path.clone(), chunk.name.clone())).and_then(|stored| {
+                    let position = stored.iter().position(|entry| entry.hash == hash).unwrap_or(0);
+                    (!stored.is_empty()).then(|| stored.swap_remove(position))
+                });
+                let vector = match stored {
+                    Some(stored) if stored.hash == hash => {
+                        stats.reused += 1;
+                        stored.vector
+                    }
+                    _ => {
+                        stats.embedded += 1;
+                        embedder.embed(&chunk.text)
+                    }
+                };
+                entries.push(StoredVector { path: path.clone(), name: chunk.name, line: chunk.line, hash, vector });
+                if entries.len() > MAX_VECTORS {
+                    return Err(format!(
+                        "More than {MAX_VECTORS} functions to embed; narrow the search with --extensions or --exclude"
+                    )
+                    .into());
+                }
+            }
+        }
+        stats.removed = previous.values().map(Vec::len).sum();
+        self.entries = entries;
+        Ok(stats)
+    }
+
+    /// The `top_k` functions most similar to `query`, best first
+    pub fn search(&self, embedder: &dyn Embedder, query: &str, top_k: usize) -> Vec<SemanticHit> {
+        let query = embedder.embed(query);
+        let mut hits: Vec<SemanticHit> = self
+            .entries
+            .iter()
+            .map(|entry| SemanticHit {
+                path: entry.path.clone(),
+                function: entry.name.clone(),
+                line: entry.line,
+                score: dot(&query, &entry.vector),
+            })
+            .collect();
+        hits.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.path.cmp(&b.path))
+                .then_with(|| a.line.cmp(&b.line))
+        });
+        hits.truncate(top_k);
+        hits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use tempfile::TempDir;
+
+    /// Counts occurrences of a few topic words, one dimension each
+    struct FakeEmbedder {
+        calls: Cell<usize>,
+    }
+
+    const TOPICS: [&str; 4] = ["config", "validate", "render", "http"];
+
+    impl FakeEmbedder {
+        fn new() -> Self {
+            Self { calls: Cell::new(0) }
+        }
+    }
+
+    impl Embedder for FakeEmbedder {
+        fn model_id(&self) -> String {
+            "fake".to_string()
+        }
+
+        fn dimensions(&self) -> usize {
+            TOPICS.len()
+        }
+
+        fn embed(&self, text: &str) -> Vec<f32> {
+            self.calls.set(self.calls.get() + 1);
+            let tokens = tokenize(text);
+            normalize(TOPICS.iter().map(|t| tokens.iter().filter(|w| w == t).count() as f32).collect())
+        }
+    }
+
+    fn fixture() -> (TempDir, Vec<String>) {
+        let dir = TempDir::new().unwrap();
+        let config = dir.path().join("config.rs");
+        let view = dir.path().join("view.rs");
+        fs::write(
+            &config,
+            "fn load_config(path: &str) -> Config {\n    let config = read(path);\n    validate(&config);\n    config\n}\n\nfn validate(config: &Config) {\n    assert!(config.ok);\n}\n",
+        )
+        .unwrap();
+        fs::write(&view, "fn render_page(req: HttpRequest) -> String {\n    render(req.http_body())\n}\n").unwrap();
+        let files = vec![config.to_string_lossy().to_string(), view.to_string_lossy().to_string()];
+        (dir, files)
+    }
+
+    #[test]
+    fn test_tokenize_splits_identifiers() {
+        assert_eq!(tokenize("parseConfigFile(read_all)"), vec!["parse", "config", "file", "read", "all"]);
+        assert_eq!(tokenize("HTTPServer v2"), vec!["httpserver", "v2"]);
+    }
+
+    #[test]
+    fn test_function_chunks_run_to_next_function() {
+        let (_dir, files) = fixture();
+        let content = fs::read_to_string(&files[0]).unwrap();
+        let chunks = function_chunks(&content, &files[0]);
+        assert_eq!(chunks.len(), 2);
+        assert_eq!((chunks[0].name.as_str(), chunks[0].line), ("load_config", 1));
+        assert!(chunks[0].text.trim_end().ends_with('}'));
+        assert!(!chunks[0].text.contains("assert!"));
+        assert!(chunks[1].text.starts_with("fn validate"));
+    }
+
+    #[test]
+    fn test_search_ranks_by_similarity() {
+        let (_dir, files) = fixture();
+        let embedder = FakeEmbedder::new();
+        let mut store = VectorStore::default();
+        store.update(&files, &embedder).unwrap();
+
+        let hits = store.search(&embedder, "load and validate a config file", 3);
+        assert_eq!(hits.len(), 3);
+        assert!(["load_config", "validate"].contains(&hits[0].function.as_str()));
+        assert_eq!(hits[2].function, "render_page");
+        assert!(hits[0].score > hits[2].score);
+
+        let hits = store.search(&embedder, "render an http page", 1);
+        assert_eq!(hits[0].function, "render_page");
+        assert_eq!(hits[0].line, 1);
+    }
+
+    #[test]
+    fn test_update_only_embeds_changed_functions() {
+        let (_dir, files) = fixture();
+        let embedder = FakeEmbedder::new();
+        let mut store = VectorStore::default();
+
+        let first = store.update(&files, &embedder).unwrap();
+        assert_eq!(first, EmbedStats { embedded: 3, reused: 0, removed: 0 });
+
+        let second = store.update(&files, &embedder).unwrap();
+        assert_eq!(second, EmbedStats { embedded: 0, reused: 3, removed: 0 });
+        assert_eq!(embedder.calls.get(), 3);
+
+        fs::write(&files[1], "fn render_page(req: HttpRequest) -> String {\n    render(req.body())\n}\n").unwrap();
+        let third = store.update(&files, &embedder).unwrap();
+        assert_eq!(third, EmbedStats { embedded: 1, reused: 2, removed: 0 });
+
+        let fourth = store.update(&files[..1], &embedder).unwrap();
+        assert_eq!(fourth, EmbedStats { embedded: 0, reused: 2, removed: 1 });
+        assert_eq!(store.entries.len(), 2);
+    }
+
+    #[test]
+    fn test_store_round_trip_and_model_change() {
+        let (dir, files) = fixture();
+        let embedder = FakeEmbedder::new();
+        let mut store = VectorStore::default();
+        store.update(&files, &embedder).unwrap();
+
+        let path = vector_store_path(&dir.path().join(".codesearch/index.json"));
+        assert!(path.ends_with("index.vectors.json"));
+        store.save(&path).unwrap();
+        let mut loaded = VectorStore::load(&path).unwrap();
+        assert_eq!(loaded.entries.len(), 3);
+
+        let model = dir.path().join("vectors.txt");
+        fs::write(&model, "2 3\nconfig 1 0 0\nrender 0 1 0\n").unwrap();
+        let static_embedder = StaticEmbedder::load(&model).unwrap();
+        let stats = loaded.update(&files, &static_embedder).unwrap();
+        assert_eq!(stats.embedded, 3);
+        assert_eq!(loaded.dimensions, 3);
+    }
+
+    /// Write a randomly initialized two-layer BERT and a word-level tokenizer for `words`
+    fn tiny_transformer(dir: &Path, words: &[&str]) {
+        use candle_nn::VarMap;
+        use tokenizers::models::wordlevel::WordLevel;
+        use tokenizers::pre_tokenizers::whitespace::Whitespace;
+
+        let vocab: HashMap<String, u32> =
+            ["[UNK]"].iter().chain(words).enumerate().map(|(id, word)| (word.to_string(), id as u32)).collect();
+        let config = format!(
+            r#"{{"vocab_size": {}, "hidden_size": 8, "num_hidden_layers": 2, "num_attention_heads": 2,
+                "intermediate_size": 16, "hidden_act": "gelu", "hidden_dropout_prob": 0.0,
+                "max_position_embeddings": 16, "type_vocab_size": 2, "initializer_range": 0.02,
+                "layer_norm_eps": 1e-12, "pad_token_id": 0, "classifier_dropout": null, "model_type": "bert"}}"#,
+            vocab.len()
+        );
+        fs::write(dir.join("config.json"), &config).unwrap();
+
+        let model = WordLevel::builder().vocab(vocab.into_iter().collect()).unk_token("[UNK]".to_string()).build().unwrap();
+        let mut tokenizer = Tokenizer::new(model);
+        tokenizer.with_pre_tokenizer(Some(Whitespace {}));
+        tokenizer.save(dir.join("tokenizer.json"), false).unwrap();
+
+        let varmap = VarMap::new();
+        let config: BertConfig = serde_json::from_str(&config).unwrap();
+        BertModel::load(VarBuilder::from_varmap(&varmap, DTYPE, &Device::Cpu), &config).unwrap();
+        varmap.save(dir.join("model.safetensors")).unwrap();
+    }
+
+    #[test]
+    fn test_transformer_embedder_runs_a_local_model() {
+        let (dir, files) = fixture();
+        let model = dir.path().join("model");
+        fs::create_dir(&model).unwrap();
+        tiny_transformer(&model, &["fn", "load", "config", "render", "page", "http"]);
+
+        let embedder = load_embedder(&model).unwrap();
+        assert_eq!(embedder.dimensions(), 8);
+        assert!(embedder.model_id().starts_with("bert:"));
+
+        let vector = embedder.embed("load config");
+        assert_eq!(vector.len(), 8);
+        assert!((dot(&vector, &vector) - 1.0).abs() < 1e-4);
+        assert_eq!(vector, embedder.embed("load config"));
+        assert_ne!(vector, embedder.embed("render page"));
+        // Longer than the 16 positions the model has: truncated, not an error
+        assert_ne!(embedder.embed(&"http ".repeat(40)), vec![0.0; 8]);
+        assert_eq!(embedder.embed(""), vec![0.0; 8]);
+
+        let mut store = VectorStore::default();
+        let stats = store.update(&files, embedder.as_ref()).unwrap();
+        assert_eq!(stats.embedded, 3);
+        let hits = store.search(embedder.as_ref(), "render page", 3);
+        assert_eq!(hits.len(), 3);
+    }
+
+    #[test]
+    fn test_transformer_embedder_reports_missing_files() {
+        let dir = TempDir::new().unwrap();
+        let error = load_embedder(dir.path()).err().unwrap().to_string();
+        assert!(error.contains("config.json"), "{error}");
+    }
+
+    #[test]
+    fn test_static_embedder_parsing() {
+        let embedder = StaticEmbedder::parse("config 1 0\nRender 0 1\n").unwrap();
+        assert_eq!(embedder.dimensions(), 2);
+        assert_eq!(embedder.embed("renderView"), vec![0.0, 1.0]);
+        assert_eq!(embedder.embed("unknown words"), vec![0.0, 0.0]);
+
+        assert!(StaticEmbedder::parse("a 1 2\nb 1\n").is_err());
+        assert!(StaticEmbedder::parse("a x\n").is_err());
+        assert!(StaticEmbedder::parse("").is_err());
+    }
+}
@@ -0,0 +1,147 @@
+//! Text Encodings
+//!
+//! Source files are read as UTF-8, except that a UTF-16 byte order mark (as
+//! Visual Studio writes) has the file transcoded first, so those files are
+//! searched and analyzed instead of failing to decode and being skipped.
+//! Unpaired surrogates become U+FFFD. A UTF-8 byte order mark is dropped.
+
+use std::borrow::Cow;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Byte order mark of UTF-8 text
+pub const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+const UTF16_LE_BOM: &[u8] = b"\xFF\xFE";
+const UTF16_BE_BOM: &[u8] = b"\xFE\xFF";
+
+/// How file contents are decoded
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Encoding {
+    /// UTF-16 when the file starts with a UTF-16 byte order mark, UTF-8 otherwise
+    #[default]
+    Auto,
+    /// Always UTF-8; files that aren't valid UTF-8 are skipped
+    Utf8,
+    /// Always UTF-16, little-endian unless a byte order mark says otherwise
+    Utf16,
+}
+
+impl std::str::FromStr for Encoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "utf8" | "utf-8" => Ok(Self::Utf8),
+            "utf16" | "utf-16" => Ok(Self::Utf16),
+            other => Err(format!("Unknown encoding: {other} (expected: auto, utf8, utf16)")),
+        }
+    }
+}
+
+/// Byte order of UTF-16 text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteOrder {
+    Little,
+    Big,
+}
+
+/// Byte order to transcode content starting with `prefix` from, or `None` to read it as UTF-8
+pub fn utf16_order(prefix: &[u8], encoding: Encoding) -> Option<ByteOrder> {
+    let marked = if prefix.starts_with(UTF16_LE_BOM) {
+        Some(ByteOrder::Little)
+    } else if prefix.starts_with(UTF16_BE_BOM) {
+        Some(ByteOrder::Big)
+    } else {
+        None
+    };
+    match encoding {
+        Encoding::Auto => marked,
+        Encoding::Utf8 => None,
+        Encoding::Utf16 => Some(marked.unwrap_or(ByteOrder::Little)),
+    }
+}
+
+/// Transcode UTF-16 `bytes`, dropping a byte order mark
+///
+/// A trailing odd byte becomes U+FFFD, like an unpaired surrogate.
+pub fn decode_utf16(bytes: &[u8], order: ByteOrder) -> String {
+    let bytes = bytes
+        .strip_prefix(UTF16_LE_BOM)
+        .filter(|_| order == ByteOrder::Little)
+        .or_else(|| bytes.strip_prefix(UTF16_BE_BOM).filter(|_| order == ByteOrder::Big))
+        .unwrap_or(bytes);
+    let units = bytes.chunks_exact(2).map(|pair| match order {
+        ByteOrder::Little => u16::from_le_bytes([pair[0], pair[1]]),
+        ByteOrder::Big => u16::from_be_bytes([pair[0], pair[1]]),
+    });
+    let mut text: String = char::decode_utf16(units).map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER)).collect();
+    if bytes.len() % 2 == 1 {
+        text.push(char::REPLACEMENT_CHARACTER);
+    }
+    text
+}
+
+/// Decode file contents as `encoding` says
+///
+/// Fails with `InvalidData` when UTF-8 is expected and `bytes` aren't valid UTF-8.
+pub fn decode(bytes: &[u8], encoding: Encoding) -> io::Result<Cow<'_, str>> {
+    match utf16_order(bytes, encoding) {
+        Some(order) => Ok(Cow::Owned(decode_utf16(bytes, order))),
+        None => std::str::from_utf8(bytes.strip_prefix(UTF8_BOM).unwrap_or(bytes))
+            .map(Cow::Borrowed)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+    }
+}
+
+/// Read a whole file, decoding it as `encoding` says
+pub fn read_text(path: &Path, encoding: Encoding) -> io::Result<String> {
+    let bytes = fs::read(path)?;
+    let text = decode(&bytes, encoding)?.into_owned();
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utf16le(text: &str) -> Vec<u8> {
+        let mut bytes = UTF16_LE_BOM.to_vec();
+        bytes.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+        bytes
+    }
+
+    #[test]
+    fn test_decode_sniffs_byte_order_marks() {
+        assert_eq!(decode(&utf16le("héllo\r\nwörld"), Encoding::Auto).unwrap(), "héllo\r\nwörld");
+
+        let mut big = UTF16_BE_BOM.to_vec();
+        big.extend("fn main()".encode_utf16().flat_map(u16::to_be_bytes));
+        assert_eq!(decode(&big, Encoding::Auto).unwrap(), "fn main()");
+
+        assert_eq!(decode(b"\xEF\xBB\xBFplain", Encoding::Auto).unwrap(), "plain");
+        assert!(matches!(decode(b"plain", Encoding::Auto).unwrap(), Cow::Borrowed("plain")));
+    }
+
+    #[test]
+    fn test_forced_encodings() {
+        // Without a byte order mark, UTF-16 is taken as little-endian
+        let bare: Vec<u8> = "abc".encode_utf16().flat_map(u16::to_le_bytes).collect();
+        assert_eq!(decode(&bare, Encoding::Utf16).unwrap(), "abc");
+        assert_eq!(decode(&bare, Encoding::Auto).unwrap(), "a\0b\0c\0");
+
+        let err = decode(&utf16le("abc"), Encoding::Utf8).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        assert_eq!("UTF-16".parse::<Encoding>().unwrap(), Encoding::Utf16);
+        assert!("latin1".parse::<Encoding>().unwrap_err().contains("expected: auto, utf8, utf16"));
+    }
+
+    #[test]
+    fn test_decode_utf16_is_lossy() {
+        // An unpaired high surrogate, then a trailing odd byte
+        let bytes = [0xFF, 0xFE, b'a', 0, 0x00, 0xD8, b'b', 0, b'c'];
+        assert_eq!(decode_utf16(&bytes, ByteOrder::Little), "a\u{FFFD}b\u{FFFD}");
+    }
+}
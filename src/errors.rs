@@ -133,6 +133,80 @@ pub enum RemoteError {
     CloneFailed { repo: String },
 }
 
+/// Errors that can occur reading an index archive (`codesearch index --pack`)
+#[derive(Error, Debug)]
+pub enum ArchiveError {
+    /// The file doesn't start with the archive header
+    #[error("Not an index archive: {path}")]
+    NotAnArchive { path: PathBuf },
+
+    /// The archive was written by a newer, incompatible format
+    #[error("Unsupported index archive version {version} (expected {expected})")]
+    UnsupportedVersion { version: String, expected: u32 },
+
+    /// The payload doesn't match the stored checksum
+    #[error("Index archive is corrupted (checksum mismatch): {path}")]
+    ChecksumMismatch { path: PathBuf },
+
+    /// The archive was packed without `--include-contents`, so it can't be searched
+    #[error("Index archive has no file contents to search: {path} (pack it with --include-contents)")]
+    NoContents { path: PathBuf },
+}
+
+/// Errors that can occur loading a config file
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    /// The file can't be read
+    #[error("Cannot read config file {path}: {source}")]
+    Unreadable {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    /// The file isn't valid TOML, or has keys or values the config doesn't take
+    #[error("Invalid config file {path}: {message}")]
+    Invalid { path: PathBuf, message: String },
+}
+
+/// Process exit codes, listed in `codesearch --help`
+///
+/// Findings only change the exit code when a gate flag asks for it; errors
+/// always use [`ERROR`](exit_code::ERROR) so scripts can tell the two apart.
+pub mod exit_code {
+    /// `search --fail-on-matches` found matches
+    pub const MATCHES_FOUND: u8 = 1;
+    /// `complexity --max-complexity` was exceeded
+    pub const COMPLEXITY_EXCEEDED: u8 = 2;
+    /// `duplicates --max-duplicates` was exceeded
+    pub const DUPLICATES_EXCEEDED: u8 = 3;
+    /// `deadcode --fail-on-deadcode` found dead code
+    pub const DEAD_CODE_FOUND: u8 = 4;
+    /// `lint --fail-on` found a finding at or above the given level
+    pub const LINT_FAILED: u8 = 5;
+    /// `watch --fail-on-regression` saw a change make a file worse
+    pub const REGRESSION: u8 = 6;
+    /// The command failed: invalid pattern, missing path, bad arguments
+    pub const ERROR: u8 = 10;
+}
+
+/// A CI gate flag (`--fail-on-matches`, `--max-complexity`, ...) tripped
+///
+/// Returned once the command's output has been written; `main` exits with
+/// `code` instead of reporting an error.
+#[derive(Error, Debug)]
+#[error("{message}")]
+pub struct GateFailure {
+    pub code: u8,
+    pub message: String,
+}
+
+impl GateFailure {
+    pub fn new(code: u8, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+}
+
 // Conversion implementations for common error types
 
 impl From<std::io::Error> for SearchError {
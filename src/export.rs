@@ -1,28 +1,62 @@
 //! Export Module
 //!
-//! Provides functionality to export search results to various formats.
+//! Provides functionality to export search results to various formats,
+//! analysis reports as a self-contained HTML page, and analysis findings as
+//! SARIF 2.1.0 for code scanning tools.
 
-use crate::types::SearchResult;
+use crate::complexity::FileComplexityReport;
+use crate::deadcode::{Confidence, DeadCodeItem};
+use crate::duplicates::{CloneType, DuplicateCluster, EnhancedDuplicateBlock};
+use crate::lint::LintFinding;
+use crate::text::truncate_str_safe;
+use crate::types::{ComplexityMetrics, DuplicateBlock, SearchResult};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::fmt::Write as _;
 use std::fs::File;
 use std::io::Write;
+use std::path::Path;
 
-/// Export search results to a file (CSV or Markdown)
+/// Export search results to a file (CSV, Markdown, HTML, JUnit XML or text, by extension)
 pub fn export_results(
     results: &[SearchResult],
     path: &str,
     query: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    export_results_with(results, path, query, &ExportOptions::default())
+}
+
+/// Export search results like [`export_results`], with a choice of CSV and Markdown columns
+pub fn export_results_with(
+    results: &[SearchResult],
+    path: &str,
+    query: &str,
+    options: &ExportOptions,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let format = if path.ends_with(".csv") {
         ExportFormat::Csv
     } else if path.ends_with(".md") || path.ends_with(".markdown") {
         ExportFormat::Markdown
+    } else if is_html_path(path) {
+        ExportFormat::Html
+    } else if path.to_ascii_lowercase().ends_with(".xml") {
+        ExportFormat::Junit
     } else {
         ExportFormat::Text
     };
 
     match format {
-        ExportFormat::Csv => export_csv(results, path),
-        ExportFormat::Markdown => export_markdown(results, path, query),
+        ExportFormat::Csv => export_csv(results, path, options),
+        ExportFormat::Markdown => export_markdown(results, path, query, options),
+        ExportFormat::Html => {
+            let report = AnalysisBundle {
+                query: Some(query.to_string()),
+                search_results: Some(results.to_vec()),
+                ..AnalysisBundle::new("Search Results")
+            };
+            export_html(&report, path)
+        }
+        ExportFormat::Junit => export_junit(results, path, query),
         ExportFormat::Text => export_text(results, path, query),
     }
 }
@@ -30,25 +64,125 @@ pub fn export_results(
 enum ExportFormat {
     Csv,
     Markdown,
+    Html,
+    Junit,
     Text,
 }
 
-fn export_csv(results: &[SearchResult], path: &str) -> Result<(), Box<dyn std::error::Error>> {
+/// A column of the CSV and Markdown exports
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportColumn {
+    File,
+    Line,
+    Content,
+    Score,
+    Relevance,
+    /// Byte offsets of the matches in the content, as `start-end;start-end`
+    Matches,
+}
+
+impl ExportColumn {
+    pub const ALL: [ExportColumn; 6] = [
+        ExportColumn::File,
+        ExportColumn::Line,
+        ExportColumn::Content,
+        ExportColumn::Score,
+        ExportColumn::Relevance,
+        ExportColumn::Matches,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ExportColumn::File => "file",
+            ExportColumn::Line => "line",
+            ExportColumn::Content => "content",
+            ExportColumn::Score => "score",
+            ExportColumn::Relevance => "relevance",
+            ExportColumn::Matches => "matches",
+        }
+    }
+
+    fn header(self) -> &'static str {
+        match self {
+            ExportColumn::File => "File",
+            ExportColumn::Line => "Line",
+            ExportColumn::Content => "Content",
+            ExportColumn::Score => "Score",
+            ExportColumn::Relevance => "Relevance",
+            ExportColumn::Matches => "Matches",
+        }
+    }
+
+    fn value(self, result: &SearchResult) -> String {
+        match self {
+            ExportColumn::File => result.file.clone(),
+            ExportColumn::Line => result.line_number.to_string(),
+            ExportColumn::Content => result.content.clone(),
+            ExportColumn::Score => format!("{:.2}", result.score),
+            ExportColumn::Relevance => result.relevance.clone(),
+            ExportColumn::Matches => result
+                .matches
+                .iter()
+                .map(|m| format!("{}-{}", m.start, m.end))
+                .collect::<Vec<_>>()
+                .join(";"),
+        }
+    }
+}
+
+impl std::str::FromStr for ExportColumn {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ExportColumn::ALL
+            .into_iter()
+            .find(|column| column.name().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| {
+                let names: Vec<&str> = ExportColumn::ALL.iter().map(|c| c.name()).collect();
+                format!("Unknown export column '{s}' (expected one of: {})", names.join(", "))
+            })
+    }
+}
+
+/// Parse column names such as `["file", "line", "content"]`, rejecting repeats
+pub fn parse_export_columns(names: &[String]) -> Result<Vec<ExportColumn>, String> {
+    let mut columns = Vec::new();
+    for name in names {
+        let column: ExportColumn = name.parse()?;
+        if columns.contains(&column) {
+            return Err(format!("Export column '{}' is listed twice", column.name()));
+        }
+        columns.push(column);
+    }
+    if columns.is_empty() {
+        return Err("No export columns given".to_string());
+    }
+    Ok(columns)
+}
+
+/// How CSV and Markdown exports lay out results
+#[derive(Debug, Clone)]
+pub struct ExportOptions {
+    /// Columns in output order
+    pub columns: Vec<ExportColumn>,
+    /// Longest Markdown content cell in characters; longer lines end in `...`
+    pub max_width: Option<usize>,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self { columns: ExportColumn::ALL.to_vec(), max_width: None }
+    }
+}
+
+fn export_csv(results: &[SearchResult], path: &str, options: &ExportOptions) -> Result<(), Box<dyn std::error::Error>> {
     let file = File::create(path)?;
+    // The writer quotes fields with commas, quotes or newlines and doubles embedded quotes
     let mut wtr = csv::Writer::from_writer(file);
 
-    // Write header
-    wtr.write_record(["File", "Line", "Content", "Score", "Relevance"])?;
-
-    // Write results
+    wtr.write_record(options.columns.iter().map(|column| column.header()))?;
     for result in results {
-        wtr.write_record([
-            &result.file,
-            &result.line_number.to_string(),
-            &result.content,
-            &format!("{:.2}", result.score),
-            &result.relevance,
-        ])?;
+        wtr.write_record(options.columns.iter().map(|column| column.value(result)))?;
     }
 
     wtr.flush()?;
@@ -59,40 +193,80 @@ fn export_markdown(
     results: &[SearchResult],
     path: &str,
     query: &str,
+    options: &ExportOptions,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let mut file = File::create(path)?;
+    file.write_all(render_markdown(results, query, options).as_bytes())?;
+    Ok(())
+}
 
-    // Write header
-    writeln!(file, "# Search Results")?;
-    writeln!(file)?;
-    writeln!(file, "**Query:** `{}`", query)?;
-    writeln!(file, "**Total Results:** {}", results.len())?;
-    writeln!(file)?;
+fn render_markdown(results: &[SearchResult], query: &str, options: &ExportOptions) -> String {
+    let mut md = String::new();
+    let _ = writeln!(md, "# Search Results\n");
+    let _ = writeln!(md, "**Query:** <code>{}</code>", escape_markdown(query));
+    let _ = writeln!(md, "**Total Results:** {}\n", results.len());
 
-    // Group by file
-    let mut current_file = "";
-    for result in results {
-        if result.file != current_file {
-            writeln!(file)?;
-            writeln!(file, "## {}", result.file)?;
-            writeln!(file)?;
-            current_file = &result.file;
-        }
+    let headers: Vec<&str> = options.columns.iter().map(|column| column.header()).collect();
+    let _ = writeln!(md, "| {} |", headers.join(" | "));
+    let rules: Vec<&str> = options
+        .columns
+        .iter()
+        .map(|column| match column {
+            ExportColumn::Line | ExportColumn::Score => "---:",
+            _ => "---",
+        })
+        .collect();
+    let _ = writeln!(md, "| {} |", rules.join(" | "));
 
-        writeln!(
-            file,
-            "- **Line {}** (Score: {:.0}): `{}`",
-            result.line_number,
-            result.score,
-            result.content.trim()
-        )?;
+    for result in results {
+        let cells: Vec<String> = options
+            .columns
+            .iter()
+            .map(|&column| match column {
+                ExportColumn::Content => markdown_code_cell(&result.content, options.max_width),
+                _ => escape_markdown(&column.value(result)),
+            })
+            .collect();
+        let _ = writeln!(md, "| {} |", cells.join(" | "));
     }
 
-    writeln!(file)?;
-    writeln!(file, "---")?;
-    writeln!(file, "*Generated by codesearch*")?;
+    let _ = writeln!(md, "\n---");
+    let _ = writeln!(md, "*Generated by codesearch*");
+    md
+}
 
-    Ok(())
+/// Content as `<code>`, one line per `<br>`, each cut to `max_width` characters
+fn markdown_code_cell(content: &str, max_width: Option<usize>) -> String {
+    content
+        .lines()
+        .map(|line| {
+            let line = line.trim();
+            let line = match max_width {
+                Some(width) => truncate_str_safe(line, width),
+                None => line.to_string(),
+            };
+            format!("<code>{}</code>", escape_markdown(&line))
+        })
+        .collect::<Vec<_>>()
+        .join("<br>")
+}
+
+/// Backslash-escape what would end a table cell or start Markdown or HTML markup
+///
+/// `<code>` tags aren't code spans, so their text is escaped the same way.
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '|' | '&' | '~' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\r' | '\n' => escaped.push(' '),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
 }
 
 fn export_text(
@@ -120,6 +294,637 @@ fn export_text(
     Ok(())
 }
 
+/// Export search results as a JUnit XML test suite, for CI systems that annotate failing tests
+///
+/// Each file with matches is a test case with one `<failure>` per matching
+/// line, so forbidden patterns show up as failed tests. Without matches the
+/// suite holds a single passing test case rather than none.
+pub fn export_junit(results: &[SearchResult], path: &str, query: &str) -> Result<(), Box<dyn std::error::Error>> {
+    std::fs::write(path, render_junit(results, query))?;
+    Ok(())
+}
+
+fn render_junit(results: &[SearchResult], query: &str) -> String {
+    // Files in the order they were first reported
+    let mut files: Vec<(&str, Vec<&SearchResult>)> = Vec::new();
+    for result in results {
+        match files.iter_mut().find(|(file, _)| *file == result.file) {
+            Some((_, matches)) => matches.push(result),
+            None => files.push((&result.file, vec![result])),
+        }
+    }
+
+    let suite = escape_xml(&format!("codesearch: {query}"));
+    let tests = files.len().max(1);
+    let failures = files.len();
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(xml, "<testsuites name=\"codesearch\" tests=\"{tests}\" failures=\"{failures}\" errors=\"0\">");
+    let _ = writeln!(
+        xml,
+        "  <testsuite name=\"{suite}\" tests=\"{tests}\" failures=\"{failures}\" errors=\"0\" skipped=\"0\">"
+    );
+    if files.is_empty() {
+        let _ = writeln!(xml, "    <testcase name=\"no matches\" classname=\"{suite}\"/>");
+    }
+    for (file, matches) in &files {
+        let file = escape_xml(file);
+        let _ = writeln!(xml, "    <testcase name=\"{file}\" classname=\"{suite}\" file=\"{file}\">");
+        for result in matches {
+            let message = escape_xml(&format!("{}:{}: {}", result.file, result.line_number, result.content.trim()));
+            let _ = writeln!(
+                xml,
+                "      <failure type=\"match\" message=\"{message}\" file=\"{file}\" line=\"{}\">{message}</failure>",
+                result.line_number
+            );
+        }
+        let _ = writeln!(xml, "    </testcase>");
+    }
+    xml.push_str("  </testsuite>\n</testsuites>\n");
+    xml
+}
+
+/// Escape text for XML content and quoted attributes
+///
+/// Control characters that XML 1.0 can't represent at all, even escaped, are
+/// replaced; tabs and line breaks are kept as character references so they
+/// survive in attribute values.
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' => {
+                let _ = write!(escaped, "&#{};", c as u32);
+            }
+            '\0'..='\x1f' => escaped.push(char::REPLACEMENT_CHARACTER),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Whether an export path asks for HTML (`.html` or `.htm`, any case)
+pub fn is_html_path(path: &str) -> bool {
+    let lower = path.to_ascii_lowercase();
+    lower.ends_with(".html") || lower.ends_with(".htm")
+}
+
+/// What an HTML report shows: one section per analysis that ran
+///
+/// A `None` section is left out of the page; `Some` of an empty list is
+/// shown as a section saying nothing was found.
+#[derive(Debug, Clone, Default)]
+pub struct AnalysisBundle {
+    pub title: String,
+    /// The search query, shown under the title
+    pub query: Option<String>,
+    pub search_results: Option<Vec<SearchResult>>,
+    pub duplicate_clusters: Option<Vec<DuplicateCluster>>,
+    /// Listed as given; pass them most complex first
+    pub complexity: Option<Vec<FileComplexityReport>>,
+    pub dead_code: Option<Vec<DeadCodeItem>>,
+}
+
+impl AnalysisBundle {
+    pub fn new(title: &str) -> Self {
+        Self { title: title.to_string(), ..Default::default() }
+    }
+}
+
+/// Write `report` to `path` as a single HTML file with inline CSS and script
+pub fn export_html(report: &AnalysisBundle, path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    std::fs::write(path, render_html(report))?;
+    Ok(())
+}
+
+const HTML_STYLE: &str = r#"
+body { font-family: system-ui, sans-serif; margin: 2em; color: #222; }
+h1 { margin-bottom: 0.2em; }
+h2 { margin-top: 2em; border-bottom: 1px solid #ccc; }
+table { border-collapse: collapse; width: 100%; font-size: 0.9em; }
+th, td { border: 1px solid #ddd; padding: 4px 8px; text-align: left; vertical-align: top; }
+th { background: #f3f3f3; cursor: pointer; user-select: none; }
+th.asc::after { content: " \25B2"; }
+th.desc::after { content: " \25BC"; }
+tr:target { background: #fff3b0; }
+td.num { text-align: right; }
+code, pre { font-family: ui-monospace, monospace; white-space: pre-wrap; margin: 0; }
+.empty, .footer { color: #777; }
+.footer { margin-top: 3em; font-size: 0.8em; }
+"#;
+
+/// Sorts a table by the clicked column: numerically when both cells are
+/// numbers, by `data-sort` (or the text) otherwise; a second click reverses
+const HTML_SCRIPT: &str = r#"
+document.querySelectorAll("table.sortable th").forEach(function (th, col) {
+  th.addEventListener("click", function () {
+    var table = th.closest("table"), body = table.tBodies[0], desc = th.classList.contains("asc");
+    table.querySelectorAll("th").forEach(function (h) { h.classList.remove("asc", "desc"); });
+    th.classList.add(desc ? "desc" : "asc");
+    var key = function (row) { var cell = row.cells[col]; return (cell.dataset.sort || cell.textContent).trim(); };
+    var rows = Array.prototype.slice.call(body.rows);
+    rows.sort(function (a, b) {
+      var x = key(a), y = key(b), m = Number(x), n = Number(y);
+      var c = (x !== "" && y !== "" && !isNaN(m) && !isNaN(n)) ? m - n : x.localeCompare(y);
+      return desc ? -c : c;
+    });
+    rows.forEach(function (row) { body.appendChild(row); });
+  });
+});
+"#;
+
+/// Render `report` as a self-contained HTML page
+pub fn render_html(report: &AnalysisBundle) -> String {
+    let mut html = String::new();
+    let title = escape_html(&report.title);
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>{HTML_STYLE}</style>\n</head>\n<body>\n<h1>{title}</h1>\n"
+    );
+    if let Some(query) = &report.query {
+        let _ = writeln!(html, "<p>Query: <code>{}</code></p>", escape_html(query));
+    }
+
+    let mut anchors = Anchors::default();
+    if let Some(results) = &report.search_results {
+        let rows = results
+            .iter()
+            .map(|r| {
+                vec![
+                    location_cell(&mut anchors, "match", &r.file, r.line_number),
+                    number_cell(format!("{:.2}", r.score)),
+                    text_cell(&r.relevance),
+                    code_cell(r.content.trim()),
+                ]
+            })
+            .collect();
+        write_section(&mut html, "Search Results", &["Location", "Score", "Relevance", "Content"], rows);
+    }
+    if let Some(clusters) = &report.duplicate_clusters {
+        // One row per copy, so copies sort by file like the other sections
+        let mut rows = Vec::new();
+        for (i, cluster) in clusters.iter().enumerate() {
+            for occurrence in &cluster.occurrences {
+                rows.push(vec![
+                    location_cell(&mut anchors, "duplicate", &occurrence.file, occurrence.line),
+                    number_cell((i + 1).to_string()),
+                    text_cell(clone_type_label(cluster.clone_type)),
+                    number_cell(format!("{:.0}", cluster.similarity * 100.0)),
+                    number_cell(cluster.line_count.to_string()),
+                    code_cell(&cluster.representative_content),
+                ]);
+            }
+        }
+        let columns = ["Location", "Cluster", "Type", "Similarity %", "Lines", "Code"];
+        write_section(&mut html, "Duplicate Clusters", &columns, rows);
+    }
+    if let Some(reports) = &report.complexity {
+        let rows = reports
+            .iter()
+            .map(|r| {
+                vec![
+                    location_cell(&mut anchors, "complexity", &r.path, 1),
+                    number_cell(r.cyclomatic.to_string()),
+                    number_cell(r.cognitive.to_string()),
+                    number_cell(r.functions.to_string()),
+                    number_cell(r.max_nesting.to_string()),
+                    number_cell(r.lines.to_string()),
+                ]
+            })
+            .collect();
+        let columns = ["Location", "Cyclomatic", "Cognitive", "Functions", "Max nesting", "Lines"];
+        write_section(&mut html, "Top Complexity Files", &columns, rows);
+    }
+    if let Some(items) = &report.dead_code {
+        let rows = items
+            .iter()
+            .map(|item| {
+                vec![
+                    location_cell(&mut anchors, "dead", &item.file, item.line_number),
+                    text_cell(&item.item_type),
+                    code_cell(&item.name),
+                    text_cell(item.confidence.as_str()),
+                    text_cell(&item.reason),
+                ]
+            })
+            .collect();
+        write_section(&mut html, "Dead Code", &["Location", "Type", "Name", "Confidence", "Reason"], rows);
+    }
+
+    let _ = write!(
+        html,
+        "<p class=\"footer\">Generated by codesearch {}</p>\n<script>{HTML_SCRIPT}</script>\n</body>\n</html>\n",
+        env!("CARGO_PKG_VERSION")
+    );
+    html
+}
+
+/// A heading and a sortable table whose rows link to themselves
+fn write_section(html: &mut String, heading: &str, columns: &[&str], rows: Vec<Vec<Cell>>) {
+    let _ = writeln!(html, "<h2>{heading}</h2>");
+    if rows.is_empty() {
+        let _ = writeln!(html, "<p class=\"empty\">Nothing found.</p>");
+        return;
+    }
+    html.push_str("<table class=\"sortable\">\n<thead><tr>");
+    for column in columns {
+        let _ = write!(html, "<th>{}</th>", escape_html(column));
+    }
+    html.push_str("</tr></thead>\n<tbody>\n");
+    for cells in rows {
+        // The location cell names the row's anchor
+        let id = cells.iter().find_map(|cell| cell.anchor.as_deref()).unwrap_or_default();
+        let _ = write!(html, "<tr id=\"{id}\">");
+        for cell in &cells {
+            html.push_str(&cell.html);
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</tbody>\n</table>\n");
+}
+
+/// A rendered `<td>`, and the anchor of its row if it is a location
+struct Cell {
+    html: String,
+    anchor: Option<String>,
+}
+
+fn text_cell(text: &str) -> Cell {
+    Cell { html: format!("<td>{}</td>", escape_html(text)), anchor: None }
+}
+
+fn code_cell(code: &str) -> Cell {
+    Cell { html: format!("<td><pre><code>{}</code></pre></td>", escape_html(code)), anchor: None }
+}
+
+fn number_cell(number: String) -> Cell {
+    Cell { html: format!("<td class=\"num\">{number}</td>"), anchor: None }
+}
+
+/// A `file:line` link to its own row, sorting by file and then line
+fn location_cell(anchors: &mut Anchors, section: &str, file: &str, line: usize) -> Cell {
+    let anchor = anchors.next(section, file, line);
+    let location = escape_html(&format!("{file}:{line}"));
+    let sort_key = escape_html(&format!("{file}:{line:010}"));
+    Cell {
+        html: format!("<td data-sort=\"{sort_key}\"><a href=\"#{anchor}\">{location}</a></td>"),
+        anchor: Some(anchor),
+    }
+}
+
+/// Hands out unique element ids such as `match-src-main-rs-10`
+#[derive(Default)]
+struct Anchors {
+    used: HashSet<String>,
+}
+
+impl Anchors {
+    fn next(&mut self, section: &str, file: &str, line: usize) -> String {
+        let slug: String = file.chars().map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' }).collect();
+        let base = format!("{section}-{slug}-{line}");
+        let mut anchor = base.clone();
+        let mut n = 1;
+        while !self.used.insert(anchor.clone()) {
+            n += 1;
+            anchor = format!("{base}-{n}");
+        }
+        anchor
+    }
+}
+
+fn clone_type_label(clone_type: CloneType) -> &'static str {
+    match clone_type {
+        CloneType::Type1 => "Type-1",
+        CloneType::Type2 => "Type-2",
+        CloneType::Type3 => "Type-3",
+        CloneType::Type4 => "Type-4",
+    }
+}
+
+/// Escape text for use in HTML content and quoted attribute values
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;").replace('\'', "&#39;")
+}
+
+/// SARIF specification version written by [`export_sarif`]
+pub const SARIF_VERSION: &str = "2.1.0";
+
+/// JSON schema of the SARIF version written by [`export_sarif`]
+pub const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// Cyclomatic complexity above which a file is reported as a SARIF result
+pub const SARIF_COMPLEXITY_NOTE: u32 = 10;
+
+/// Cyclomatic complexity above which a complex file is reported as a warning
+pub const SARIF_COMPLEXITY_WARNING: u32 = 20;
+
+/// Rule that a SARIF result refers to, declared once per run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SarifRule {
+    pub id: String,
+    pub description: String,
+}
+
+/// A file and 1-based line range that a SARIF result points at
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SarifLocation {
+    pub file: String,
+    pub start_line: usize,
+    pub end_line: Option<usize>,
+}
+
+impl SarifLocation {
+    fn new(file: &str, start_line: usize) -> Self {
+        Self { file: file.to_string(), start_line, end_line: None }
+    }
+}
+
+/// An analysis finding that can be reported as a SARIF result
+pub trait SarifFinding {
+    fn rule(&self) -> SarifRule;
+    /// One of `error`, `warning` or `note`
+    fn level(&self) -> &'static str;
+    fn message(&self) -> String;
+    fn location(&self) -> SarifLocation;
+    /// Other places involved in the finding, such as the second copy of a duplicate
+    fn related_locations(&self) -> Vec<SarifLocation> {
+        Vec::new()
+    }
+}
+
+impl SarifFinding for DeadCodeItem {
+    fn rule(&self) -> SarifRule {
+        let kind: String = self
+            .item_type
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+            .collect();
+        SarifRule {
+            id: format!("dead-code/{kind}"),
+            description: format!("Potentially dead code: {}", self.item_type),
+        }
+    }
+
+    fn level(&self) -> &'static str {
+        match self.confidence {
+            Confidence::High => "warning",
+            Confidence::Medium | Confidence::Low => "note",
+        }
+    }
+
+    fn message(&self) -> String {
+        format!(
+            "{} '{}': {} ({} confidence)",
+            self.item_type,
+            self.name,
+            self.reason,
+            self.confidence.as_str()
+        )
+    }
+
+    fn location(&self) -> SarifLocation {
+        SarifLocation::new(&self.file, self.line_number)
+    }
+}
+
+impl SarifFinding for ComplexityMetrics {
+    fn rule(&self) -> SarifRule {
+        SarifRule {
+            id: "complexity/high-cyclomatic".to_string(),
+            description: "File with high cyclomatic complexity".to_string(),
+        }
+    }
+
+    fn level(&self) -> &'static str {
+        if self.cyclomatic_complexity > SARIF_COMPLEXITY_WARNING {
+            "warning"
+        } else {
+            "note"
+        }
+    }
+
+    fn message(&self) -> String {
+        format!(
+            "Cyclomatic complexity {} (cognitive {}, max nesting {}) across {} function(s)",
+            self.cyclomatic_complexity,
+            self.cognitive_complexity,
+            self.max_nesting_depth,
+            self.function_count
+        )
+    }
+
+    fn location(&self) -> SarifLocation {
+        SarifLocation::new(&self.file_path, 1)
+    }
+}
+
+impl SarifFinding for LintFinding {
+    fn rule(&self) -> SarifRule {
+        SarifRule {
+            id: format!("lint/{}", self.rule_id),
+            description: self.description.clone().unwrap_or_else(|| format!("Lint rule {} ({})", self.rule_id, self.kind)),
+        }
+    }
+
+    fn level(&self) -> &'static str {
+        self.level.as_str()
+    }
+
+    fn message(&self) -> String {
+        self.message.clone()
+    }
+
+    fn location(&self) -> SarifLocation {
+        SarifLocation::new(&self.file, self.line)
+    }
+}
+
+fn duplicate_rule() -> SarifRule {
+    SarifRule {
+        id: "duplicates/block".to_string(),
+        description: "Duplicated code block".to_string(),
+    }
+}
+
+fn duplicate_message(file2: &str, line2: usize, similarity: f64) -> String {
+    format!("Duplicate of {file2}:{line2} ({:.0}% similar)", similarity * 100.0)
+}
+
+impl SarifFinding for DuplicateBlock {
+    fn rule(&self) -> SarifRule {
+        duplicate_rule()
+    }
+
+    fn level(&self) -> &'static str {
+        "warning"
+    }
+
+    fn message(&self) -> String {
+        duplicate_message(&self.file2, self.line2, self.similarity)
+    }
+
+    fn location(&self) -> SarifLocation {
+        SarifLocation::new(&self.file1, self.line1)
+    }
+
+    fn related_locations(&self) -> Vec<SarifLocation> {
+        vec![SarifLocation::new(&self.file2, self.line2)]
+    }
+}
+
+impl SarifFinding for EnhancedDuplicateBlock {
+    fn rule(&self) -> SarifRule {
+        duplicate_rule()
+    }
+
+    fn level(&self) -> &'static str {
+        "warning"
+    }
+
+    fn message(&self) -> String {
+        duplicate_message(&self.file2, self.line2, self.similarity)
+    }
+
+    fn location(&self) -> SarifLocation {
+        let end_line = (self.line_count > 1).then(|| self.line1 + self.line_count - 1);
+        SarifLocation { end_line, ..SarifLocation::new(&self.file1, self.line1) }
+    }
+
+    fn related_locations(&self) -> Vec<SarifLocation> {
+        let end_line = (self.line_count > 1).then(|| self.line2 + self.line_count - 1);
+        vec![SarifLocation { end_line, ..SarifLocation::new(&self.file2, self.line2) }]
+    }
+}
+
+/// Build a SARIF 2.1.0 log with a single run holding one result per finding
+///
+/// Rules are declared once in `tool.driver.rules`, in order of first use, and
+/// results refer to them by `ruleId` and `ruleIndex`.
+pub fn to_sarif<T: SarifFinding>(findings: &[T]) -> Value {
+    let mut rules: Vec<SarifRule> = Vec::new();
+    let mut results = Vec::with_capacity(findings.len());
+
+    for finding in findings {
+        let rule = finding.rule();
+        let rule_index = match rules.iter().position(|r| r.id == rule.id) {
+            Some(index) => index,
+            None => {
+                rules.push(rule);
+                rules.len() - 1
+            }
+        };
+
+        let mut result = json!({
+            "ruleId": rules[rule_index].id,
+            "ruleIndex": rule_index,
+            "level": finding.level(),
+            "message": { "text": finding.message() },
+            "locations": [sarif_location(&finding.location())],
+        });
+        let related: Vec<Value> = finding
+            .related_locations()
+            .iter()
+            .enumerate()
+            .map(|(id, location)| {
+                let mut value = sarif_location(location);
+                value["id"] = json!(id);
+                value
+            })
+            .collect();
+        if !related.is_empty() {
+            result["relatedLocations"] = json!(related);
+        }
+        results.push(result);
+    }
+
+    let rules: Vec<Value> = rules
+        .iter()
+        .map(|rule| {
+            json!({
+                "id": rule.id,
+                "shortDescription": { "text": rule.description },
+            })
+        })
+        .collect();
+
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": SARIF_VERSION,
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "codesearch",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": "https://github.com/yingkitw/codesearch",
+                    "rules": rules,
+                }
+            },
+            "results": results,
+        }]
+    })
+}
+
+/// Write findings to `path` as a SARIF 2.1.0 log
+pub fn export_sarif<T: SarifFinding>(findings: &[T], path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut file = File::create(path)?;
+    serde_json::to_writer_pretty(&mut file, &to_sarif(findings))?;
+    writeln!(file)?;
+    Ok(())
+}
+
+fn sarif_location(location: &SarifLocation) -> Value {
+    let mut region = json!({ "startLine": location.start_line.max(1) });
+    if let Some(end_line) = location.end_line {
+        region["endLine"] = json!(end_line.max(location.start_line.max(1)));
+    }
+    let mut artifact = json!({ "uri": artifact_uri(&location.file) });
+    if !Path::new(&location.file).is_absolute() {
+        artifact["uriBaseId"] = json!("%SRCROOT%");
+    }
+    json!({
+        "physicalLocation": {
+            "artifactLocation": artifact,
+            "region": region,
+        }
+    })
+}
+
+/// Turn a reported path into a URI reference: forward slashes, no leading `./`,
+/// percent-encoded, and a `file://` URI when the path is absolute
+fn artifact_uri(file: &str) -> String {
+    let normalized = file.replace('\\', "/");
+    let mut path = normalized.as_str();
+    while let Some(rest) = path.strip_prefix("./") {
+        path = rest;
+    }
+
+    let mut uri = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                uri.push(byte as char)
+            }
+            // Keep Windows drive letters readable
+            b':' if Path::new(file).is_absolute() => uri.push(':'),
+            _ => uri.push_str(&format!("%{byte:02X}")),
+        }
+    }
+
+    if Path::new(file).is_absolute() {
+        if uri.starts_with('/') {
+            format!("file://{uri}")
+        } else {
+            format!("file:///{uri}")
+        }
+    } else {
+        uri
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -131,14 +936,21 @@ mod tests {
             SearchResult {
                 file: "test.rs".to_string(),
                 line_number: 10,
+                end_line_number: None,
                 content: "fn main() {}".to_string(),
                 matches: vec![Match {
                     start: 0,
                     end: 2,
                     text: "fn".to_string(),
+                    pattern_index: 0,
                 }],
                 score: 85.0,
                 relevance: "High".to_string(),
+                context_before: Vec::new(),
+                context_after: Vec::new(),
+                line_truncated: false,
+                content_offset: None,
+                annotations: Default::default(),
             },
         ]
     }
@@ -149,7 +961,7 @@ mod tests {
         let path = dir.path().join("results.csv");
         let results = create_test_results();
 
-        export_csv(&results, path.to_str().unwrap()).unwrap();
+        export_csv(&results, path.to_str().unwrap(), &ExportOptions::default()).unwrap();
 
         let content = std::fs::read_to_string(&path).unwrap();
         assert!(content.contains("File"));
@@ -162,13 +974,133 @@ mod tests {
         let path = dir.path().join("results.md");
         let results = create_test_results();
 
-        export_markdown(&results, path.to_str().unwrap(), "fn").unwrap();
+        export_markdown(&results, path.to_str().unwrap(), "fn", &ExportOptions::default()).unwrap();
 
         let content = std::fs::read_to_string(&path).unwrap();
         assert!(content.contains("# Search Results"));
         assert!(content.contains("test.rs"));
     }
 
+    fn tricky_results() -> Vec<SearchResult> {
+        let mut base = create_test_results().remove(0);
+        let mut results = Vec::new();
+        for (file, content) in [
+            ("a,b.rs", r#"let sep = ",";"#),
+            ("pipes.rs", "if a || b { x | y }"),
+            ("multi.rs", "let s = \"one\ntwo\";"),
+            ("html.md", "<script>alert(1)</script> `tick` \\| *bold*"),
+        ] {
+            base.file = file.to_string();
+            base.content = content.to_string();
+            results.push(base.clone());
+        }
+        results[1].matches.push(Match { start: 5, end: 7, text: "||".to_string(), pattern_index: 0 });
+        results
+    }
+
+    /// Cells of a Markdown table row, splitting like GFM: a `\` escapes the next character
+    fn markdown_cells(row: &str) -> Vec<String> {
+        let mut cells = vec![String::new()];
+        let mut chars = row.trim().trim_start_matches('|').chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => {
+                    cells.last_mut().unwrap().push(c);
+                    cells.last_mut().unwrap().extend(chars.next());
+                }
+                '|' => cells.push(String::new()),
+                _ => cells.last_mut().unwrap().push(c),
+            }
+        }
+        cells.pop();
+        cells
+    }
+
+    #[test]
+    fn test_export_csv_round_trips_every_field() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("results.csv");
+        let results = tricky_results();
+
+        export_results(&results, path.to_str().unwrap(), "q").unwrap();
+
+        let mut reader = csv::Reader::from_path(&path).unwrap();
+        assert_eq!(
+            reader.headers().unwrap().iter().collect::<Vec<_>>(),
+            vec!["File", "Line", "Content", "Score", "Relevance", "Matches"]
+        );
+        let records: Vec<csv::StringRecord> = reader.records().map(Result::unwrap).collect();
+        assert_eq!(records.len(), results.len());
+        for (record, result) in records.iter().zip(&results) {
+            assert_eq!(&record[0], result.file);
+            assert_eq!(&record[1], result.line_number.to_string());
+            assert_eq!(&record[2], result.content);
+            assert_eq!(&record[3], "85.00");
+            assert_eq!(&record[4], result.relevance);
+        }
+        assert_eq!(&records[0][5], "0-2");
+        assert_eq!(&records[1][5], "0-2;5-7");
+    }
+
+    #[test]
+    fn test_export_csv_selected_columns() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("results.csv");
+        let options = ExportOptions {
+            columns: parse_export_columns(&["content".to_string(), "FILE".to_string()]).unwrap(),
+            max_width: Some(5),
+        };
+
+        export_results_with(&tricky_results(), path.to_str().unwrap(), "q", &options).unwrap();
+
+        let mut reader = csv::Reader::from_path(&path).unwrap();
+        assert_eq!(reader.headers().unwrap().iter().collect::<Vec<_>>(), vec!["Content", "File"]);
+        let first = reader.records().next().unwrap().unwrap();
+        // The width only cuts Markdown cells
+        assert_eq!(&first[0], r#"let sep = ",";"#);
+        assert_eq!(&first[1], "a,b.rs");
+    }
+
+    #[test]
+    fn test_parse_export_columns_rejects_unknown_and_repeated() {
+        let names = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert!(parse_export_columns(&names(&["file", "bogus"])).unwrap_err().contains("bogus"));
+        assert!(parse_export_columns(&names(&["line", "line"])).unwrap_err().contains("twice"));
+        assert!(parse_export_columns(&[]).is_err());
+    }
+
+    #[test]
+    fn test_markdown_table_keeps_one_row_and_cell_per_field() {
+        let results = tricky_results();
+        let md = render_markdown(&results, "a|b", &ExportOptions::default());
+        let rows: Vec<&str> = md.lines().filter(|line| line.starts_with('|')).collect();
+        assert_eq!(rows.len(), results.len() + 2, "{md}");
+        for row in &rows {
+            assert_eq!(markdown_cells(row).len(), ExportColumn::ALL.len(), "{row}");
+        }
+        assert!(md.contains("<code>a\\|b</code>"), "{md}");
+
+        let cells = markdown_cells(rows[3]);
+        assert_eq!(cells[2].trim(), "<code>if a \\|\\| b { x \\| y }</code>");
+        assert_eq!(cells[5].trim(), "0-2;5-7");
+        let cells = markdown_cells(rows[4]);
+        assert_eq!(cells[2].trim(), "<code>let s = \"one</code><br><code>two\";</code>");
+        let cells = markdown_cells(rows[5]);
+        assert_eq!(cells[0].trim(), "html.md");
+        assert!(!cells[2].contains("<script>"), "{}", cells[2]);
+        assert!(cells[2].contains("\\`tick\\` \\\\\\| \\*bold\\*"), "{}", cells[2]);
+    }
+
+    #[test]
+    fn test_markdown_truncates_long_content() {
+        let options = ExportOptions { max_width: Some(10), ..ExportOptions::default() };
+        let md = render_markdown(&tricky_results(), "q", &options);
+        assert!(md.contains("<code>let sep...</code>"), "{md}");
+        assert!(md.contains("<code>two\";</code>"), "{md}");
+        // The cut doesn't separate a letter from its combining accent
+        assert_eq!(markdown_code_cell("cafe\u{301} au lait", Some(7)), "<code>caf...</code>");
+    }
+
     #[test]
     fn test_export_text() {
         let dir = tempdir().unwrap();
@@ -181,5 +1113,230 @@ mod tests {
         assert!(content.contains("Search Results"));
         assert!(content.contains("test.rs"));
     }
+
+    #[test]
+    fn test_render_html_tables_escape_code() {
+        let mut results = create_test_results();
+        results[0].content = "if a < b && s == \"<script>\" {}".to_string();
+        let report = AnalysisBundle {
+            query: Some("a < b".to_string()),
+            search_results: Some(results),
+            dead_code: Some(vec![DeadCodeItem {
+                file: "src/lib.rs".to_string(),
+                line_number: 7,
+                item_type: "function".to_string(),
+                name: "Vec<T>::unused".to_string(),
+                reason: "never called".to_string(),
+                confidence: Confidence::High,
+            }]),
+            duplicate_clusters: Some(Vec::new()),
+            ..AnalysisBundle::new("Report")
+        };
+        let html = render_html(&report);
+
+        assert!(html.contains("<td><pre><code>if a &lt; b &amp;&amp; s == &quot;&lt;script&gt;&quot; {}</code></pre></td>"), "{html}");
+        assert!(!html.contains("\"<script>"));
+        assert!(html.contains("<p>Query: <code>a &lt; b</code></p>"));
+        assert!(html.contains("<tr id=\"match-test-rs-10\"><td data-sort=\"test.rs:0000000010\"><a href=\"#match-test-rs-10\">test.rs:10</a></td>"), "{html}");
+        assert!(html.contains("<a href=\"#dead-src-lib-rs-7\">src/lib.rs:7</a></td><td>function</td><td><pre><code>Vec&lt;T&gt;::unused</code></pre></td><td>high</td>"), "{html}");
+        assert!(html.contains("<h2>Duplicate Clusters</h2>\n<p class=\"empty\">Nothing found.</p>"));
+        assert!(!html.contains("Top Complexity Files"));
+        // Self-contained: nothing is loaded from elsewhere
+        assert!(!html.contains("src=") && !html.contains("<link"));
+        assert_eq!(html.matches("<table class=\"sortable\">").count(), 2);
+    }
+
+    #[test]
+    fn test_export_results_writes_html_for_html_paths() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("results.HTML");
+
+        export_results(&create_test_results(), path.to_str().unwrap(), "fn").unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with("<!DOCTYPE html>"));
+        assert!(content.contains("<h2>Search Results</h2>"));
+        assert!(content.contains(">test.rs:10</a>"));
+    }
+
+    #[test]
+    fn test_export_junit_has_a_failure_per_match() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("results.xml");
+        let mut results = create_test_results();
+        results[0].content = "    let v = x.unwrap(); // a < b && \"c\" > 'd'\x01".to_string();
+        let mut second = results[0].clone();
+        second.line_number = 20;
+        let mut other = results[0].clone();
+        other.file = "src/a&b.rs".to_string();
+        results.extend([second, other]);
+
+        export_results(&results, path.to_str().unwrap(), "unwrap()").unwrap();
+
+        let xml = std::fs::read_to_string(&path).unwrap();
+        let doc = roxmltree::Document::parse(&xml).unwrap();
+        let suite = doc.descendants().find(|n| n.has_tag_name("testsuite")).unwrap();
+        assert_eq!(suite.attribute("name"), Some("codesearch: unwrap()"));
+        assert_eq!((suite.attribute("tests"), suite.attribute("failures")), (Some("2"), Some("2")));
+        let cases: Vec<_> = suite.children().filter(|n| n.has_tag_name("testcase")).collect();
+        let names: Vec<_> = cases.iter().map(|c| c.attribute("file").unwrap()).collect();
+        assert_eq!(names, vec!["test.rs", "src/a&b.rs"]);
+        let failures: Vec<_> = cases[0].children().filter(|n| n.has_tag_name("failure")).collect();
+        assert_eq!(failures.len(), 2);
+        assert_eq!(failures[1].attribute("line"), Some("20"));
+        assert_eq!(
+            failures[0].attribute("message"),
+            Some("test.rs:10: let v = x.unwrap(); // a < b && \"c\" > 'd'\u{FFFD}")
+        );
+        assert_eq!(failures[0].text(), failures[0].attribute("message"));
+        assert_eq!(doc.descendants().filter(|n| n.has_tag_name("failure")).count(), 3);
+    }
+
+    #[test]
+    fn test_export_junit_without_matches_passes() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("results.xml");
+
+        export_junit(&[], path.to_str().unwrap(), "dbg!").unwrap();
+
+        let xml = std::fs::read_to_string(&path).unwrap();
+        let doc = roxmltree::Document::parse(&xml).unwrap();
+        let cases: Vec<_> = doc.descendants().filter(|n| n.has_tag_name("testcase")).collect();
+        assert_eq!(cases.len(), 1);
+        assert_eq!(cases[0].children().filter(|n| n.is_element()).count(), 0);
+        assert_eq!(doc.root_element().attribute("failures"), Some("0"));
+    }
+
+    /// Check the parts of the SARIF 2.1.0 schema that consumers rely on, and
+    /// that every location resolves to an existing file relative to `root`
+    fn assert_valid_sarif(log: &Value, root: &Path) {
+        assert_eq!(log["version"], SARIF_VERSION);
+        assert!(log["$schema"].as_str().unwrap().contains("sarif-2.1.0"));
+        let runs = log["runs"].as_array().unwrap();
+        assert_eq!(runs.len(), 1);
+
+        let driver = &runs[0]["tool"]["driver"];
+        assert_eq!(driver["name"], "codesearch");
+        let rules = driver["rules"].as_array().unwrap();
+        let mut ids: Vec<&str> = rules.iter().map(|r| r["id"].as_str().unwrap()).collect();
+        assert!(rules.iter().all(|r| r["shortDescription"]["text"].is_string()));
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), rules.len(), "rules must be declared once");
+
+        for result in runs[0]["results"].as_array().unwrap() {
+            let index = result["ruleIndex"].as_u64().unwrap() as usize;
+            assert_eq!(rules[index]["id"], result["ruleId"]);
+            assert!(["error", "warning", "note", "none"].contains(&result["level"].as_str().unwrap()));
+            assert!(!result["message"]["text"].as_str().unwrap().is_empty());
+
+            let locations = result["locations"].as_array().unwrap();
+            assert!(!locations.is_empty());
+            let related = result["relatedLocations"].as_array().cloned().unwrap_or_default();
+            for location in locations.iter().chain(&related) {
+                let physical = &location["physicalLocation"];
+                assert!(physical["region"]["startLine"].as_u64().unwrap() >= 1);
+                let uri = percent_decode(physical["artifactLocation"]["uri"].as_str().unwrap());
+                let file = match uri.strip_prefix("file://") {
+                    Some(absolute) => Path::new(absolute).to_path_buf(),
+                    None => {
+                        assert_eq!(physical["artifactLocation"]["uriBaseId"], "%SRCROOT%");
+                        root.join(&uri)
+                    }
+                };
+                assert!(file.is_file(), "unresolvable location {uri}");
+            }
+        }
+    }
+
+    fn percent_decode(uri: &str) -> String {
+        let bytes = uri.as_bytes();
+        let mut decoded = Vec::new();
+        let mut i = 0;
+        while i < bytes.len() {
+            if bytes[i] == b'%' {
+                decoded.push(u8::from_str_radix(&uri[i + 1..i + 3], 16).unwrap());
+                i += 3;
+            } else {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+        String::from_utf8(decoded).unwrap()
+    }
+
+    fn write_sample_project(root: &Path) {
+        let body = "    let total = a + b;\n    let scaled = total * 2;\n    let shifted = scaled - 1;\n    println!(\"{}\", shifted);\n";
+        let mut branches = String::new();
+        for i in 0..25 {
+            branches.push_str(&format!("    if x == {i} {{ return {i}; }}\n"));
+        }
+        std::fs::create_dir(root.join("src")).unwrap();
+        std::fs::write(
+            root.join("src/lib.rs"),
+            format!("fn first(a: i32, b: i32) {{\n{body}}}\n\nfn second(a: i32, b: i32) {{\n{body}}}\n\nfn branchy(x: i32) -> i32 {{\n{branches}    0\n}}\n"),
+        )
+        .unwrap();
+        std::fs::write(root.join("src/other file.rs"), format!("fn third(a: i32, b: i32) {{\n{body}}}\n")).unwrap();
+    }
+
+    #[test]
+    fn test_sarif_dead_code_is_structurally_valid() {
+        let dir = tempdir().unwrap();
+        write_sample_project(dir.path());
+        let items = crate::deadcode::find_dead_code(dir.path(), None, None, &crate::deadcode::DeadCodeOptions::default()).unwrap();
+        assert!(!items.is_empty());
+
+        let log = to_sarif(&items);
+        assert_valid_sarif(&log, dir.path());
+        let results = log["runs"][0]["results"].as_array().unwrap();
+        assert_eq!(results.len(), items.len());
+        assert!(results.iter().any(|r| r["ruleId"] == "dead-code/function"));
+    }
+
+    #[test]
+    fn test_sarif_complexity_and_duplicates_are_structurally_valid() {
+        let dir = tempdir().unwrap();
+        write_sample_project(dir.path());
+
+        let metrics = crate::complexity::calculate_complexity(dir.path(), None, None).unwrap();
+        let complex: Vec<_> = metrics.into_iter().filter(|m| m.cyclomatic_complexity > SARIF_COMPLEXITY_NOTE).collect();
+        assert_eq!(complex.len(), 1);
+        let log = to_sarif(&complex);
+        assert_valid_sarif(&log, dir.path());
+        assert_eq!(log["runs"][0]["results"][0]["level"], "warning");
+
+        let config = crate::duplicates::DuplicateConfig { min_lines: 3, ..Default::default() };
+        let blocks = crate::duplicates::find_duplicates_enhanced(dir.path(), None, None, config).unwrap();
+        assert!(!blocks.is_empty());
+        let log = to_sarif(&blocks);
+        assert_valid_sarif(&log, dir.path());
+        assert_eq!(log["runs"][0]["tool"]["driver"]["rules"].as_array().unwrap().len(), 1);
+        assert!(log["runs"][0]["results"][0]["relatedLocations"].is_array());
+    }
+
+    #[test]
+    fn test_export_sarif_writes_relative_uris() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("report.sarif");
+        let items = vec![DeadCodeItem {
+            file: "./src/other file.rs".to_string(),
+            line_number: 0,
+            item_type: "class/struct".to_string(),
+            name: "Unused".to_string(),
+            reason: "Never referenced".to_string(),
+            confidence: Confidence::Low,
+        }];
+
+        export_sarif(&items, path.to_str().unwrap()).unwrap();
+
+        let log: Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        let result = &log["runs"][0]["results"][0];
+        assert_eq!(result["ruleId"], "dead-code/class-struct");
+        assert_eq!(result["level"], "note");
+        let physical = &result["locations"][0]["physicalLocation"];
+        assert_eq!(physical["artifactLocation"]["uri"], "src/other%20file.rs");
+        assert_eq!(physical["region"]["startLine"], 1);
+    }
 }
 
@@ -0,0 +1,189 @@
+//! Flag Rules
+//!
+//! Some `search` flag combinations can't all take effect: one flag silently
+//! overrides or disables another. [`SEARCH_FLAG_RULES`] lists them; each rule
+//! either warns, saying which flag won, or rejects a contradictory combination.
+//! Register new conflicts by adding a rule to the table.
+//!
+//! Combinations that make the query itself meaningless (`--fuzzy --literal`,
+//! `--word --fuzzy`, ...) are rejected by [`crate::search::Query::from_options`].
+
+use crate::errors::SearchError;
+
+/// What happens when a rule matches
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleSeverity {
+    /// One flag wins; the search runs and a warning is printed
+    Warn,
+    /// The flags contradict each other; the search is not run
+    Error,
+}
+
+/// The `search` flags the rules look at, as resolved from the command line
+#[derive(Debug, Clone, Default)]
+pub struct SearchFlags {
+    /// The query uses regex syntax beyond plain words
+    pub regex_syntax: bool,
+    pub fuzzy: bool,
+    pub literal: bool,
+    pub format_json: bool,
+    pub export: bool,
+    pub only_matching: bool,
+    pub no_filename: bool,
+    pub benchmark: bool,
+    pub vs_grep: bool,
+    pub rank: bool,
+    pub rank_group: bool,
+    pub sort_by: bool,
+    pub cache: bool,
+    /// Any of `--context`, `--before` and `--after`
+    pub context: bool,
+    pub multiline: bool,
+    pub files_from: bool,
+    pub index_archive: bool,
+}
+
+/// A known conflicting or ignored flag combination
+#[derive(Debug)]
+pub struct FlagRule {
+    /// The flags involved, as typed
+    pub flags: &'static str,
+    pub severity: RuleSeverity,
+    /// What happens instead, naming the flag that wins
+    pub message: &'static str,
+    pub applies: fn(&SearchFlags) -> bool,
+}
+
+/// Conflicts between `search` flags, in the order they are reported
+pub const SEARCH_FLAG_RULES: &[FlagRule] = &[
+    FlagRule {
+        flags: "--sort-by + --rank-group",
+        severity: RuleSeverity::Error,
+        message: "--sort-by and --rank-group ask for different result orders; pass only one",
+        applies: |f| f.sort_by && f.rank_group,
+    },
+    FlagRule {
+        flags: "--fuzzy + regex pattern",
+        severity: RuleSeverity::Warn,
+        message: "--fuzzy wins: the pattern is scored as plain text, its regex syntax is not interpreted",
+        applies: |f| f.fuzzy && !f.literal && f.regex_syntax,
+    },
+    FlagRule {
+        flags: "--export + --format json",
+        severity: RuleSeverity::Warn,
+        message: "--export wins over --format json: results go to the export file and no JSON is printed",
+        applies: |f| f.export && f.format_json && !f.only_matching,
+    },
+    FlagRule {
+        flags: "-o + --export",
+        severity: RuleSeverity::Warn,
+        message: "-o wins over --export: matches are printed and no export file is written",
+        applies: |f| f.only_matching && f.export,
+    },
+    FlagRule {
+        flags: "-o + --context",
+        severity: RuleSeverity::Warn,
+        message: "-o wins over --context/--before/--after: only the matched text is printed",
+        applies: |f| f.only_matching && f.context,
+    },
+    FlagRule {
+        flags: "--no-filename without -o",
+        severity: RuleSeverity::Warn,
+        message: "--no-filename only applies with -o; file names are still shown",
+        applies: |f| f.no_filename && !f.only_matching,
+    },
+    FlagRule {
+        flags: "--benchmark + -o --format json",
+        severity: RuleSeverity::Warn,
+        message: "-o --format json wins over --benchmark: the metrics are left out of the output",
+        applies: |f| f.benchmark && f.only_matching && f.format_json,
+    },
+    FlagRule {
+        flags: "--sort-by + --rank",
+        severity: RuleSeverity::Warn,
+        message: "--sort-by wins over --rank: results are in path order (scores are still computed)",
+        applies: |f| f.sort_by && f.rank && !f.rank_group,
+    },
+    FlagRule {
+        flags: "--cache + uncached search",
+        severity: RuleSeverity::Warn,
+        message: "--cache is ignored with context lines, --multiline, --files-from and --index-archive",
+        applies: |f| f.cache && (f.context || f.multiline || f.files_from || f.index_archive),
+    },
+    FlagRule {
+        flags: "--vs-grep + file list",
+        severity: RuleSeverity::Warn,
+        message: "--vs-grep is ignored with --files-from and --index-archive: there is no tree for grep to walk",
+        applies: |f| f.vs_grep && (f.files_from || f.index_archive),
+    },
+];
+
+/// Whether `pattern` uses regex syntax (`.` alone doesn't count, as it is common in plain text)
+pub fn has_regex_syntax(pattern: &str) -> bool {
+    pattern.chars().any(|c| matches!(c, '\\' | '^' | '$' | '*' | '+' | '?' | '(' | ')' | '[' | ']' | '{' | '}' | '|'))
+}
+
+/// Apply [`SEARCH_FLAG_RULES`], returning the warnings to print
+///
+/// The first matching error rule is returned as [`SearchError::InvalidOptions`].
+pub fn check_search_flags(flags: &SearchFlags) -> Result<Vec<&'static str>, SearchError> {
+    let matched: Vec<&FlagRule> = SEARCH_FLAG_RULES.iter().filter(|rule| (rule.applies)(flags)).collect();
+    if let Some(rule) = matched.iter().find(|rule| rule.severity == RuleSeverity::Error) {
+        return Err(SearchError::InvalidOptions { message: rule.message.to_string() });
+    }
+    Ok(matched.into_iter().map(|rule| rule.message).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A combination that trips each rule, keyed by the rule's `flags`
+    fn trigger(rule: &str) -> SearchFlags {
+        let flags = SearchFlags::default();
+        match rule {
+            "--sort-by + --rank-group" => SearchFlags { sort_by: true, rank: true, rank_group: true, ..flags },
+            "--fuzzy + regex pattern" => SearchFlags { fuzzy: true, regex_syntax: true, ..flags },
+            "--export + --format json" => SearchFlags { export: true, format_json: true, ..flags },
+            "-o + --export" => SearchFlags { only_matching: true, export: true, ..flags },
+            "-o + --context" => SearchFlags { only_matching: true, context: true, ..flags },
+            "--no-filename without -o" => SearchFlags { no_filename: true, ..flags },
+            "--benchmark + -o --format json" => SearchFlags { benchmark: true, only_matching: true, format_json: true, ..flags },
+            "--sort-by + --rank" => SearchFlags { sort_by: true, rank: true, ..flags },
+            "--cache + uncached search" => SearchFlags { cache: true, multiline: true, ..flags },
+            "--vs-grep + file list" => SearchFlags { vs_grep: true, files_from: true, ..flags },
+            other => panic!("no trigger for rule {other}"),
+        }
+    }
+
+    #[test]
+    fn test_every_rule_triggers() {
+        for rule in SEARCH_FLAG_RULES {
+            let flags = trigger(rule.flags);
+            assert!((rule.applies)(&flags), "{} doesn't apply to its trigger", rule.flags);
+            match (rule.severity, check_search_flags(&flags)) {
+                (RuleSeverity::Warn, Ok(warnings)) => assert!(warnings.contains(&rule.message), "{}", rule.flags),
+                (RuleSeverity::Error, Err(err)) => assert!(err.to_string().contains(rule.message), "{}", rule.flags),
+                (_, result) => panic!("{}: unexpected {result:?}", rule.flags),
+            }
+        }
+    }
+
+    #[test]
+    fn test_plain_flags_pass() {
+        assert_eq!(check_search_flags(&SearchFlags::default()).unwrap(), Vec::<&str>::new());
+        let flags = SearchFlags { rank: true, rank_group: true, format_json: true, regex_syntax: true, ..SearchFlags::default() };
+        assert!(check_search_flags(&flags).unwrap().is_empty());
+        // With --literal the query is rejected by `Query::from_options` instead
+        let flags = SearchFlags { fuzzy: true, literal: true, regex_syntax: true, ..SearchFlags::default() };
+        assert!(check_search_flags(&flags).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_regex_syntax() {
+        assert!(has_regex_syntax(r"fn \w+"));
+        assert!(has_regex_syntax("foo|bar"));
+        assert!(!has_regex_syntax("config.rs"));
+        assert!(!has_regex_syntax("hello world"));
+    }
+}
@@ -103,6 +103,30 @@ impl FileSystem for RealFileSystem {
     }
 }
 
+/// Replace the contents of `path` without leaving it half-written
+///
+/// Writes to a temporary file in the same directory, copies the original
+/// permissions over, then renames it into place. Commands that modify source
+/// files (e.g. `hygiene --fix`) go through this so an interrupted run leaves
+/// either the old or the new contents.
+pub fn write_atomically(path: &Path, contents: &[u8]) -> io::Result<()> {
+    use std::io::Write;
+
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let mut temp = tempfile::NamedTempFile::new_in(dir)?;
+    temp.write_all(contents)?;
+    match fs::metadata(path) {
+        Ok(metadata) => fs::set_permissions(temp.path(), metadata.permissions())?,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+    temp.persist(path).map_err(|e| e.error)?;
+    Ok(())
+}
+
 /// Mock file system for testing
 ///
 /// This implementation stores files in memory and allows testing without
@@ -230,6 +254,17 @@ mod tests {
         assert!(!fs.exists(Path::new("nonexistent.txt")));
     }
 
+    #[test]
+    fn test_write_atomically_replaces_contents() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file.txt");
+        fs::write(&path, "old").unwrap();
+
+        write_atomically(&path, b"new").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1, "temporary file left behind");
+    }
+
     #[test]
     fn test_mock_filesystem() {
         let mut fs = MockFileSystem::new();
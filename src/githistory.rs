@@ -1,10 +1,50 @@
 //! Git History Search Module
 //!
 //! Provides search capabilities across git history.
+//!
+//! Shallow clones and sparse or partial checkouts are expected (CI uses
+//! them): history ends at the shallow boundary and blobs may be missing from
+//! the object store. Those gaps never fail a command; they are recorded as
+//! warnings (see [`GitSearcher::warnings`]) and the affected commit or file is
+//! skipped.
 
-use git2::{Commit, DiffOptions, Repository};
+use git2::{Commit, DiffOptions, ErrorClass, ErrorCode, Oid, Repository, Tree};
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use crate::lines::split_lines;
+
+/// Why git data is unavailable
+#[derive(Error, Debug)]
+pub enum GitError {
+    /// No repository at or above the path
+    #[error("not a git repository: {path}")]
+    NotARepository { path: PathBuf },
+
+    /// The commit's parents were cut off by a shallow clone
+    #[error("history ends at {commit}: shallow clone")]
+    ShallowHistory { commit: String },
+
+    /// The file's blob isn't in the object store (sparse or partial checkout)
+    #[error("{path} is not in the object store (sparse or partial checkout)")]
+    MissingObject { path: String },
+
+    /// The file isn't inside the repository's working tree
+    #[error("{path} is outside the repository working tree")]
+    OutsideWorkdir { path: PathBuf },
+
+    #[error(transparent)]
+    Git(#[from] git2::Error),
+}
+
+impl GitError {
+    /// Whether a git2 error means an object is absent, as in shallow or partial clones
+    pub fn is_missing_object(err: &git2::Error) -> bool {
+        err.code() == ErrorCode::NotFound && matches!(err.class(), ErrorClass::Odb | ErrorClass::Object)
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GitSearchResult {
@@ -29,39 +69,110 @@ pub struct CommitInfo {
 
 pub struct GitSearcher {
     repo: Repository,
+    /// Commits whose parents a shallow clone left out
+    shallow_roots: HashSet<Oid>,
+    warnings: RefCell<Vec<String>>,
 }
 
 impl GitSearcher {
-    pub fn new(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
-        let repo = Repository::discover(path)?;
-        Ok(Self { repo })
+    pub fn new(path: &Path) -> Result<Self, GitError> {
+        let repo = Repository::discover(path)
+            .map_err(|_| GitError::NotARepository { path: path.to_path_buf() })?;
+        let shallow_roots = std::fs::read_to_string(repo.path().join("shallow"))
+            .map(|content| content.lines().filter_map(|line| Oid::from_str(line.trim()).ok()).collect())
+            .unwrap_or_default();
+        Ok(Self { repo, shallow_roots, warnings: RefCell::new(Vec::new()) })
     }
 
-    pub fn search_history(
-        &self,
-        pattern: &str,
-        max_commits: usize,
-    ) -> Result<Vec<GitSearchResult>, Box<dyn std::error::Error>> {
-        let mut results = Vec::new();
-        let regex = regex::Regex::new(pattern)?;
+    /// Whether the repository is a shallow clone
+    pub fn is_shallow(&self) -> bool {
+        self.repo.is_shallow() || !self.shallow_roots.is_empty()
+    }
+
+    /// Gaps hit so far (shallow history, missing objects), each reported once
+    pub fn warnings(&self) -> Vec<String> {
+        self.warnings.borrow().clone()
+    }
 
+    fn warn(&self, feature: &str, err: &GitError) {
+        let message = format!("{feature} unavailable: {err}");
+        let mut warnings = self.warnings.borrow_mut();
+        if !warnings.contains(&message) {
+            warnings.push(message);
+        }
+    }
+
+    /// The tree to diff `commit` against: its first parent's, or `None` for a root commit
+    fn parent_tree<'r>(&self, commit: &Commit<'r>) -> Result<Option<Tree<'r>>, GitError> {
+        let shallow = || GitError::ShallowHistory { commit: short_id(commit.id()) };
+        if self.shallow_roots.contains(&commit.id()) {
+            return Err(shallow());
+        }
+        if commit.parent_count() == 0 {
+            return Ok(None);
+        }
+        match commit.parent(0).and_then(|parent| parent.tree()) {
+            Ok(tree) => Ok(Some(tree)),
+            Err(e) if GitError::is_missing_object(&e) => Err(shallow()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Commits reachable from HEAD, newest first, stopping quietly where history is cut off
+    fn walk_commits(&self, max_commits: usize, feature: &str) -> Result<Vec<Commit<'_>>, GitError> {
         let mut revwalk = self.repo.revwalk()?;
         revwalk.push_head()?;
 
-        let mut commit_count = 0;
+        let mut commits = Vec::new();
         for oid in revwalk {
-            if commit_count >= max_commits {
+            if commits.len() >= max_commits {
                 break;
             }
+            let commit = match oid.and_then(|oid| self.repo.find_commit(oid)) {
+                Ok(commit) => commit,
+                Err(e) if GitError::is_missing_object(&e) => {
+                    let last = commits.last().map(|c: &Commit| short_id(c.id())).unwrap_or_default();
+                    self.warn(feature, &GitError::ShallowHistory { commit: last });
+                    break;
+                }
+                Err(e) => return Err(e.into()),
+            };
+            commits.push(commit);
+        }
+        Ok(commits)
+    }
 
-            let oid = oid?;
-            let commit = self.repo.find_commit(oid)?;
-            
-            if let Ok(commit_results) = self.search_commit(&commit, &regex) {
-                results.extend(commit_results);
-            }
+    /// `file_path` relative to the working tree, checked to be in HEAD's object store
+    fn tracked_blob_path(&self, file_path: &Path) -> Result<PathBuf, GitError> {
+        let relative = if file_path.is_absolute() || file_path.exists() {
+            let outside = || GitError::OutsideWorkdir { path: file_path.to_path_buf() };
+            let workdir = self.repo.workdir().ok_or_else(outside)?.canonicalize().map_err(|_| outside())?;
+            let absolute = file_path.canonicalize().map_err(|_| outside())?;
+            absolute.strip_prefix(&workdir).map_err(|_| outside())?.to_path_buf()
+        } else {
+            file_path.to_path_buf()
+        };
 
-            commit_count += 1;
+        let entry = self.repo.head()?.peel_to_tree()?.get_path(&relative)?;
+        if !self.repo.odb()?.exists(entry.id()) {
+            return Err(GitError::MissingObject { path: relative.to_string_lossy().to_string() });
+        }
+        Ok(relative)
+    }
+
+    pub fn search_history(
+        &self,
+        pattern: &str,
+        max_commits: usize,
+    ) -> Result<Vec<GitSearchResult>, Box<dyn std::error::Error>> {
+        let mut results = Vec::new();
+        let regex = regex::Regex::new(pattern)?;
+
+        for commit in self.walk_commits(max_commits, "history search")? {
+            match self.search_commit(&commit, &regex) {
+                Ok(commit_results) => results.extend(commit_results),
+                Err(e) => self.warn("history search", &e),
+            }
         }
 
         Ok(results)
@@ -71,15 +182,11 @@ impl GitSearcher {
         &self,
         commit: &Commit,
         regex: &regex::Regex,
-    ) -> Result<Vec<GitSearchResult>, Box<dyn std::error::Error>> {
+    ) -> Result<Vec<GitSearchResult>, GitError> {
         let mut results = Vec::new();
 
         let tree = commit.tree()?;
-        let parent_tree = if commit.parent_count() > 0 {
-            Some(commit.parent(0)?.tree()?)
-        } else {
-            None
-        };
+        let parent_tree = self.parent_tree(commit)?;
 
         let mut diff_opts = DiffOptions::new();
         let diff = self.repo.diff_tree_to_tree(
@@ -93,7 +200,7 @@ impl GitSearcher {
                 if let Some(path) = delta.new_file().path() {
                     if let Ok(blob) = tree.get_path(path).and_then(|entry| self.repo.find_blob(entry.id())) {
                         if let Ok(content) = std::str::from_utf8(blob.content()) {
-                            for (line_num, line) in content.lines().enumerate() {
+                            for (line_num, line) in split_lines(content).enumerate() {
                                 if regex.is_match(line) {
                                     results.push(GitSearchResult {
                                         commit_id: commit.id().to_string(),
@@ -124,19 +231,11 @@ impl GitSearcher {
         max_commits: usize,
     ) -> Result<Vec<CommitInfo>, Box<dyn std::error::Error>> {
         let mut commits = Vec::new();
-        let mut revwalk = self.repo.revwalk()?;
-        revwalk.push_head()?;
-
-        let mut count = 0;
-        for oid in revwalk {
-            if count >= max_commits {
-                break;
-            }
-
-            let oid = oid?;
-            let commit = self.repo.find_commit(oid)?;
-            
-            let files_changed = self.get_changed_files(&commit)?;
+        for commit in self.walk_commits(max_commits, "changed files")? {
+            let files_changed = self.get_changed_files(&commit).unwrap_or_else(|e| {
+                self.warn("changed files", &e);
+                Vec::new()
+            });
 
             commits.push(CommitInfo {
                 id: commit.id().to_string(),
@@ -146,22 +245,16 @@ impl GitSearcher {
                 message: commit.message().unwrap_or("").to_string(),
                 files_changed,
             });
-
-            count += 1;
         }
 
         Ok(commits)
     }
 
-    fn get_changed_files(&self, commit: &Commit) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    fn get_changed_files(&self, commit: &Commit) -> Result<Vec<String>, GitError> {
         let mut files = Vec::new();
 
         let tree = commit.tree()?;
-        let parent_tree = if commit.parent_count() > 0 {
-            Some(commit.parent(0)?.tree()?)
-        } else {
-            None
-        };
+        let parent_tree = self.parent_tree(commit)?;
 
         let mut diff_opts = DiffOptions::new();
         let diff = self.repo.diff_tree_to_tree(
@@ -222,23 +315,22 @@ impl GitSearcher {
     ) -> Result<Vec<GitSearchResult>, Box<dyn std::error::Error>> {
         let regex = regex::Regex::new(pattern)?;
         let mut results = Vec::new();
-        let mut revwalk = self.repo.revwalk()?;
-        revwalk.push_head()?;
-
-        let mut count = 0;
-        for oid in revwalk {
-            if count >= max_commits {
-                break;
-            }
 
-            let oid = oid?;
-            let commit = self.repo.find_commit(oid)?;
+        for commit in self.walk_commits(max_commits, "file history")? {
             let tree = commit.tree()?;
 
             if let Ok(entry) = tree.get_path(Path::new(file_path)) {
-                if let Ok(blob) = self.repo.find_blob(entry.id()) {
+                let blob = match self.repo.find_blob(entry.id()) {
+                    Ok(blob) => Some(blob),
+                    Err(e) if GitError::is_missing_object(&e) => {
+                        self.warn("file history", &GitError::MissingObject { path: file_path.to_string() });
+                        None
+                    }
+                    Err(e) => return Err(e.into()),
+                };
+                if let Some(blob) = blob {
                     if let Ok(content) = std::str::from_utf8(blob.content()) {
-                        for (line_num, line) in content.lines().enumerate() {
+                        for (line_num, line) in split_lines(content).enumerate() {
                             if regex.is_match(line) {
                                 results.push(GitSearchResult {
                                     commit_id: commit.id().to_string(),
@@ -254,8 +346,6 @@ impl GitSearcher {
                     }
                 }
             }
-
-            count += 1;
         }
 
         Ok(results)
@@ -266,12 +356,16 @@ impl GitSearcher {
         file_path: &str,
         line_number: usize,
     ) -> Result<CommitInfo, Box<dyn std::error::Error>> {
-        let blame = self.repo.blame_file(Path::new(file_path), None)?;
+        let relative = self.tracked_blob_path(Path::new(file_path))?;
+        let blame = self.repo.blame_file(&relative, None).map_err(GitError::from)?;
         
         if let Some(hunk) = blame.get_line(line_number) {
-            let commit = self.repo.find_commit(hunk.final_commit_id())?;
+            let commit = self.repo.find_commit(hunk.final_commit_id()).map_err(GitError::from)?;
             
-            let files_changed = self.get_changed_files(&commit)?;
+            let files_changed = self.get_changed_files(&commit).unwrap_or_else(|e| {
+                self.warn("changed files", &e);
+                Vec::new()
+            });
             
             Ok(CommitInfo {
                 id: commit.id().to_string(),
@@ -285,6 +379,38 @@ impl GitSearcher {
             Err("Line not found in blame".into())
         }
     }
+
+    /// Timestamp of the commit that last touched `line_number` of a working-tree file
+    ///
+    /// Accepts absolute paths or paths relative to the current directory.
+    /// Files missing from the object store are skipped with a warning; in a
+    /// shallow clone, lines older than the boundary date from the boundary commit.
+    pub fn line_timestamp(&self, file_path: &Path, line_number: usize) -> Option<i64> {
+        let relative = match self.tracked_blob_path(file_path) {
+            Ok(relative) => relative,
+            Err(e @ GitError::MissingObject { .. }) => {
+                self.warn("blame", &e);
+                return None;
+            }
+            Err(_) => return None,
+        };
+        let blame = match self.repo.blame_file(&relative, None) {
+            Ok(blame) => blame,
+            Err(e) => {
+                if GitError::is_missing_object(&e) {
+                    self.warn("blame", &GitError::MissingObject { path: relative.to_string_lossy().to_string() });
+                }
+                return None;
+            }
+        };
+        let hunk = blame.get_line(line_number)?;
+        let commit = self.repo.find_commit(hunk.final_commit_id()).ok()?;
+        Some(commit.time().seconds())
+    }
+}
+
+fn short_id(oid: Oid) -> String {
+    oid.to_string().chars().take(8).collect()
 }
 
 pub fn search_git_history(
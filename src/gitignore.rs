@@ -0,0 +1,198 @@
+//! Gitignore Module
+//!
+//! Skips paths matched by `.gitignore` files while walking a tree. The files of
+//! every directory from the repository root (the nearest ancestor with `.git`)
+//! down to the path are applied in order, so deeper and later rules win, and
+//! `!pattern` re-includes a path. As in git, nothing inside an ignored
+//! directory can be re-included, because the walk never enters it.
+
+use crate::lines::split_lines;
+use crate::pathglob::compile_glob;
+use globset::GlobMatcher;
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use walkdir::DirEntry;
+
+/// One line of a `.gitignore`
+#[derive(Debug, Clone)]
+struct Rule {
+    glob: GlobMatcher,
+    negated: bool,
+    dir_only: bool,
+}
+
+impl Rule {
+    fn parse(line: &str) -> Option<Self> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let (negated, pattern) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line.strip_prefix('\\').unwrap_or(line)),
+        };
+        let (dir_only, pattern) = match pattern.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, pattern),
+        };
+        if pattern.is_empty() {
+            return None;
+        }
+
+        // A slash anywhere but the end ties the pattern to the .gitignore's
+        // directory; otherwise it matches a name at any depth
+        let anchored = pattern.contains('/');
+        let pattern = pattern.trim_start_matches('/').replace('{', "[{]").replace('}', "[}]");
+        let glob = if anchored { pattern } else { format!("**/{pattern}") };
+        let glob = compile_glob(&glob).ok()?;
+        Some(Self { glob, negated, dir_only })
+    }
+
+    fn matches(&self, relative: &str, is_dir: bool) -> bool {
+        (is_dir || !self.dir_only) && self.glob.is_match(relative)
+    }
+}
+
+/// `.gitignore` rules for one walk, loaded lazily per directory
+#[derive(Debug)]
+pub struct GitIgnore {
+    /// Directory the walked paths are relative to
+    walk_dir: PathBuf,
+    /// Repository root, or the walked directory outside a repository
+    base: PathBuf,
+    /// `walk_dir` relative to `base`
+    prefix: PathBuf,
+    /// Rules of each directory's `.gitignore`, keyed by the directory relative to `base`
+    rules: HashMap<PathBuf, Vec<Rule>>,
+}
+
+impl GitIgnore {
+    /// Prepare to filter the paths of a walk starting at `root` (a directory or a file)
+    pub fn new(root: &Path) -> Self {
+        let walk_dir = if root.is_dir() {
+            root.to_path_buf()
+        } else {
+            root.parent().map(Path::to_path_buf).unwrap_or_default()
+        };
+        let dir = if walk_dir.as_os_str().is_empty() { Path::new(".") } else { walk_dir.as_path() };
+        let canonical = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+        let base = canonical
+            .ancestors()
+            .find(|ancestor| ancestor.join(".git").exists())
+            .unwrap_or(&canonical)
+            .to_path_buf();
+        let prefix = canonical.strip_prefix(&base).map(Path::to_path_buf).unwrap_or_default();
+
+        Self {
+            walk_dir,
+            base,
+            prefix,
+            rules: HashMap::new(),
+        }
+    }
+
+    /// Whether a walked entry is ignored; the walk root itself never is
+    pub fn is_ignored_entry(&mut self, entry: &DirEntry) -> bool {
+        entry.depth() > 0 && self.is_ignored(entry.path(), entry.file_type().is_dir())
+    }
+
+    /// Whether `path`, under the walked directory, is ignored
+    pub fn is_ignored(&mut self, path: &Path, is_dir: bool) -> bool {
+        let Ok(relative) = path.strip_prefix(&self.walk_dir) else {
+            return false;
+        };
+        let components: Vec<String> = self
+            .prefix
+            .join(relative)
+            .components()
+            .filter_map(|c| match c {
+                Component::Normal(name) => Some(name.to_string_lossy().into_owned()),
+                _ => None,
+            })
+            .collect();
+
+        let mut ignored = false;
+        let mut dir = PathBuf::new();
+        for (depth, name) in components.iter().enumerate() {
+            let below = components[depth..].join("/");
+            for rule in self.rules_for(&dir) {
+                if rule.matches(&below, is_dir) {
+                    ignored = !rule.negated;
+                }
+            }
+            dir.push(name);
+        }
+        ignored
+    }
+
+    fn rules_for(&mut self, dir: &Path) -> &[Rule] {
+        let base = &self.base;
+        self.rules.entry(dir.to_path_buf()).or_insert_with(|| {
+            std::fs::read_to_string(base.join(dir).join(".gitignore"))
+                .map(|content| split_lines(&content).filter_map(Rule::parse).collect())
+                .unwrap_or_default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(lines: &[&str]) -> Vec<Rule> {
+        lines.iter().filter_map(|l| Rule::parse(l)).collect()
+    }
+
+    fn ignored(rules: &[Rule], path: &str, is_dir: bool) -> bool {
+        rules.iter().fold(false, |ignored, rule| if rule.matches(path, is_dir) { !rule.negated } else { ignored })
+    }
+
+    #[test]
+    fn test_rule_parsing_skips_comments_and_blanks() {
+        assert_eq!(rules(&["", "# comment", "   ", "/"]).len(), 0);
+        let parsed = rules(&["\\#literal", "!keep.log", "build/"]);
+        assert!(parsed[0].glob.is_match("#literal"));
+        assert!(parsed[1].negated);
+        assert!(parsed[2].dir_only);
+    }
+
+    #[test]
+    fn test_anchored_and_unanchored_patterns() {
+        let r = rules(&["*.log", "/out", "docs/tmp", "cache/"]);
+        assert!(ignored(&r, "debug.log", false));
+        assert!(ignored(&r, "src/deep/debug.log", false));
+        assert!(ignored(&r, "out", true));
+        assert!(!ignored(&r, "src/out", true));
+        assert!(ignored(&r, "docs/tmp", false));
+        assert!(!ignored(&r, "src/docs/tmp", false));
+        assert!(ignored(&r, "src/cache", true));
+        assert!(!ignored(&r, "src/cache", false));
+    }
+
+    #[test]
+    fn test_later_rules_win_and_negation_reincludes() {
+        let r = rules(&["*.log", "!keep.log"]);
+        assert!(ignored(&r, "debug.log", false));
+        assert!(!ignored(&r, "keep.log", false));
+        let r = rules(&["!keep.log", "*.log"]);
+        assert!(ignored(&r, "keep.log", false));
+    }
+
+    #[test]
+    fn test_nested_gitignore_and_missing_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join(".gitignore"), "*.tmp\n").unwrap();
+        std::fs::write(dir.path().join("src/.gitignore"), "!keep.tmp\nlocal.rs\n").unwrap();
+
+        let mut gitignore = GitIgnore::new(dir.path());
+        assert!(gitignore.is_ignored(&dir.path().join("a.tmp"), false));
+        assert!(gitignore.is_ignored(&dir.path().join("src/b.tmp"), false));
+        assert!(!gitignore.is_ignored(&dir.path().join("src/keep.tmp"), false));
+        assert!(gitignore.is_ignored(&dir.path().join("src/local.rs"), false));
+        assert!(!gitignore.is_ignored(&dir.path().join("local.rs"), false));
+
+        let empty = tempfile::TempDir::new().unwrap();
+        assert!(!GitIgnore::new(empty.path()).is_ignored(&empty.path().join("a.rs"), false));
+    }
+}
@@ -0,0 +1,457 @@
+//! File Hygiene Module
+//!
+//! Reports line-ending style, UTF-8 BOMs, trailing whitespace and missing final
+//! newlines, the usual sources of diff noise, and optionally fixes them.
+//!
+//! Files are scanned in fixed-size blocks, so large files are never held in
+//! memory. Every LF is a line break, with a preceding CR making it CRLF; a lone
+//! CR is ignored. A CRLF inside a string literal in an otherwise LF file is
+//! therefore reported as `mixed`; telling it apart would need a parser per language.
+
+use crate::options::has_extension;
+use crate::theme;
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+const BOM: &[u8] = b"\xEF\xBB\xBF";
+const BLOCK_SIZE: usize = 64 * 1024;
+
+/// Line-ending style of a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LineEndings {
+    /// No line breaks at all
+    None,
+    Lf,
+    Crlf,
+    Mixed,
+}
+
+/// Hygiene findings for a single file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileHygiene {
+    pub file: String,
+    pub line_endings: LineEndings,
+    pub lf_lines: usize,
+    pub crlf_lines: usize,
+    pub has_bom: bool,
+    pub trailing_whitespace_lines: usize,
+    pub final_newline: bool,
+}
+
+impl FileHygiene {
+    /// Rough count of offending lines, used to rank the worst files
+    pub fn issue_score(&self) -> usize {
+        self.lf_lines.min(self.crlf_lines)
+            + self.trailing_whitespace_lines
+            + usize::from(self.has_bom)
+            + usize::from(!self.final_newline)
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.issue_score() == 0
+    }
+}
+
+/// Per-category file counts
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HygieneSummary {
+    pub files_scanned: usize,
+    pub lf: usize,
+    pub crlf: usize,
+    pub mixed: usize,
+    pub with_bom: usize,
+    pub with_trailing_whitespace: usize,
+    pub missing_final_newline: usize,
+}
+
+/// Files with at least one issue, worst first, and the summary over all scanned files
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HygieneReport {
+    pub summary: HygieneSummary,
+    pub files: Vec<FileHygiene>,
+}
+
+impl HygieneReport {
+    pub fn from_files(scanned: Vec<FileHygiene>) -> Self {
+        let mut summary = HygieneSummary {
+            files_scanned: scanned.len(),
+            ..HygieneSummary::default()
+        };
+        for file in &scanned {
+            match file.line_endings {
+                LineEndings::Lf => summary.lf += 1,
+                LineEndings::Crlf => summary.crlf += 1,
+                LineEndings::Mixed => summary.mixed += 1,
+                LineEndings::None => {}
+            }
+            summary.with_bom += usize::from(file.has_bom);
+            summary.with_trailing_whitespace += usize::from(file.trailing_whitespace_lines > 0);
+            summary.missing_final_newline += usize::from(!file.final_newline);
+        }
+
+        let mut files: Vec<FileHygiene> = scanned.into_iter().filter(|f| !f.is_clean()).collect();
+        files.sort_by(|a, b| b.issue_score().cmp(&a.issue_score()).then_with(|| a.file.cmp(&b.file)));
+        Self { summary, files }
+    }
+}
+
+/// Streaming scanner state, fed one block at a time
+#[derive(Default)]
+struct Scanner {
+    offset: usize,
+    has_bom: bool,
+    binary: bool,
+    lf_lines: usize,
+    crlf_lines: usize,
+    trailing_whitespace_lines: usize,
+    prev_cr: bool,
+    last_is_whitespace: bool,
+    last_byte: Option<u8>,
+}
+
+impl Scanner {
+    fn feed(&mut self, block: &[u8]) {
+        for &byte in block {
+            // Only a BOM at the very start counts; it's split over blocks only for tiny reads
+            if self.offset < BOM.len() {
+                if self.offset == 0 {
+                    self.has_bom = byte == BOM[0];
+                } else {
+                    self.has_bom &= byte == BOM[self.offset];
+                }
+            }
+            self.offset += 1;
+
+            match byte {
+                b'\n' => {
+                    if self.prev_cr {
+                        self.crlf_lines += 1;
+                    } else {
+                        self.lf_lines += 1;
+                    }
+                    if self.last_is_whitespace {
+                        self.trailing_whitespace_lines += 1;
+                    }
+                    self.last_is_whitespace = false;
+                }
+                // Decided by the next byte: line ending before LF, content otherwise
+                b'\r' => {}
+                b' ' | b'\t' => self.last_is_whitespace = true,
+                0 => self.binary = true,
+                _ => self.last_is_whitespace = false,
+            }
+            if self.prev_cr && byte != b'\n' {
+                self.last_is_whitespace = matches!(byte, b' ' | b'\t');
+            }
+            self.prev_cr = byte == b'\r';
+            self.last_byte = Some(byte);
+        }
+    }
+
+    fn finish(mut self, file: String) -> FileHygiene {
+        if self.last_is_whitespace && self.last_byte != Some(b'\n') {
+            self.trailing_whitespace_lines += 1;
+        }
+        let line_endings = match (self.lf_lines, self.crlf_lines) {
+            (0, 0) => LineEndings::None,
+            (_, 0) => LineEndings::Lf,
+            (0, _) => LineEndings::Crlf,
+            _ => LineEndings::Mixed,
+        };
+        FileHygiene {
+            file,
+            line_endings,
+            lf_lines: self.lf_lines,
+            crlf_lines: self.crlf_lines,
+            has_bom: self.has_bom && self.offset >= BOM.len(),
+            trailing_whitespace_lines: self.trailing_whitespace_lines,
+            // An empty file has nothing to terminate
+            final_newline: self.last_byte.is_none_or(|b| b == b'\n'),
+        }
+    }
+}
+
+/// Scan `reader` block by block; `None` for binary content (a NUL byte)
+pub fn scan_reader(file: &str, mut reader: impl Read) -> io::Result<Option<FileHygiene>> {
+    let mut scanner = Scanner::default();
+    let mut block = vec![0u8; BLOCK_SIZE];
+    loop {
+        let read = match reader.read(&mut block) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        scanner.feed(&block[..read]);
+        if scanner.binary {
+            return Ok(None);
+        }
+    }
+    Ok(Some(scanner.finish(file.to_string())))
+}
+
+/// Files under `path`, without reading them (`list_files` counts lines up front)
+fn walk_files(path: &Path, extensions: Option<&[String]>, exclude: Option<&[String]>) -> Vec<PathBuf> {
+    WalkDir::new(path)
+        .into_iter()
+        .filter_entry(|e| {
+            let name = e.file_name().to_string_lossy();
+            !exclude.is_some_and(|dirs| dirs.iter().any(|d| *d == name))
+        })
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter(|e| extensions.is_none_or(|exts| has_extension(e.path(), exts)))
+        .map(|e| e.into_path())
+        .collect()
+}
+
+/// Scan every file under `path`, with paths reported relative to it
+pub fn scan_path(
+    path: &Path,
+    extensions: Option<&[String]>,
+    exclude: Option<&[String]>,
+) -> Result<HygieneReport, Box<dyn std::error::Error>> {
+    use rayon::prelude::*;
+
+    if !path.exists() {
+        return Err(format!("Path not found: {}", path.display()).into());
+    }
+    let scanned: Vec<FileHygiene> = walk_files(path, extensions, exclude)
+        .par_iter()
+        .filter_map(|file_path| {
+            let relative = file_path
+                .strip_prefix(path)
+                .ok()
+                .filter(|p| !p.as_os_str().is_empty())
+                .unwrap_or(file_path)
+                .to_string_lossy()
+                .replace('\\', "/");
+            let reader = File::open(file_path).ok()?;
+            scan_reader(&relative, reader).ok().flatten()
+        })
+        .collect();
+    Ok(HygieneReport::from_files(scanned))
+}
+
+/// Normalize line endings to the file's majority style (LF on a tie) and strip
+/// trailing spaces and tabs; `None` when nothing changes
+///
+/// The BOM and a missing final newline are left alone. Trailing whitespace
+/// inside multi-line string literals is stripped too.
+pub fn fix_content(content: &[u8]) -> Option<Vec<u8>> {
+    let crlf = content.windows(2).filter(|w| w == b"\r\n").count();
+    let lf = content.iter().filter(|&&b| b == b'\n').count() - crlf;
+    let eol: &[u8] = if crlf > lf { b"\r\n" } else { b"\n" };
+
+    let mut fixed = Vec::with_capacity(content.len());
+    let mut lines = content.split(|&b| b == b'\n').peekable();
+    while let Some(line) = lines.next() {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        let end = line.iter().rposition(|b| !matches!(b, b' ' | b'\t')).map_or(0, |i| i + 1);
+        fixed.extend_from_slice(&line[..end]);
+        if lines.peek().is_some() {
+            fixed.extend_from_slice(eol);
+        }
+    }
+    (fixed != content).then_some(fixed)
+}
+
+/// Fix every file in `report` with mixed line endings or trailing whitespace
+///
+/// Writes go through [`crate::fs::write_atomically`]. Returns the number of files changed.
+pub fn fix_files(root: &Path, report: &HygieneReport) -> Result<usize, Box<dyn std::error::Error>> {
+    let mut changed = 0;
+    for file in &report.files {
+        if file.line_endings != LineEndings::Mixed && file.trailing_whitespace_lines == 0 {
+            continue;
+        }
+        let path = if root.is_file() { root.to_path_buf() } else { root.join(&file.file) };
+        let content = std::fs::read(&path)?;
+        if let Some(fixed) = fix_content(&content) {
+            crate::fs::write_atomically(&path, &fixed)?;
+            changed += 1;
+        }
+    }
+    Ok(changed)
+}
+
+/// Print the summary and up to `top` of the worst files
+pub fn print_report(report: &HygieneReport, top: usize) {
+    println!("{}", "File Hygiene".cyan().bold());
+    println!("{}", theme::rule(30).cyan());
+    println!();
+    print_summary(&report.summary);
+
+    if report.files.is_empty() {
+        println!();
+        println!("{}", "No line-ending or whitespace issues found.".green());
+        return;
+    }
+
+    println!();
+    println!("{}", "Worst offenders".yellow().bold());
+    for file in report.files.iter().take(top) {
+        let mut issues = Vec::new();
+        if file.line_endings == LineEndings::Mixed {
+            issues.push(format!("mixed endings ({} LF, {} CRLF)", file.lf_lines, file.crlf_lines));
+        }
+        if file.has_bom {
+            issues.push("BOM".to_string());
+        }
+        if file.trailing_whitespace_lines > 0 {
+            issues.push(format!("{} line(s) with trailing whitespace", file.trailing_whitespace_lines));
+        }
+        if !file.final_newline {
+            issues.push("no final newline".to_string());
+        }
+        println!("  {} {}: {}", theme::bullet().dimmed(), file.file.bold(), issues.join(", "));
+    }
+    if report.files.len() > top {
+        println!("  {}", format!("... and {} more file(s)", report.files.len() - top).dimmed());
+    }
+}
+
+/// Print the per-category counts (also used by `analyze`)
+pub fn print_summary(summary: &HygieneSummary) {
+    println!("  Files scanned: {}", summary.files_scanned.to_string().green());
+    println!(
+        "  Line endings: {} LF, {} CRLF, {} mixed",
+        summary.lf.to_string().green(),
+        summary.crlf.to_string().yellow(),
+        summary.mixed.to_string().red()
+    );
+    println!("  UTF-8 BOM: {}", summary.with_bom.to_string().yellow());
+    println!("  Trailing whitespace: {}", summary.with_trailing_whitespace.to_string().yellow());
+    println!("  Missing final newline: {}", summary.missing_final_newline.to_string().yellow());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixture_dir() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/hygiene")
+    }
+
+    fn scan(content: &[u8]) -> FileHygiene {
+        scan_reader("test", content).unwrap().unwrap()
+    }
+
+    /// Reader that hands out one byte per call, to exercise block boundaries
+    struct OneByte<'a>(&'a [u8]);
+
+    impl Read for OneByte<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            match self.0.split_first() {
+                Some((&b, rest)) if !buf.is_empty() => {
+                    buf[0] = b;
+                    self.0 = rest;
+                    Ok(1)
+                }
+                _ => Ok(0),
+            }
+        }
+    }
+
+    #[test]
+    fn test_line_ending_styles() {
+        assert_eq!(scan(b"a\nb\n").line_endings, LineEndings::Lf);
+        assert_eq!(scan(b"a\r\nb\r\n").line_endings, LineEndings::Crlf);
+        assert_eq!(scan(b"a\r\nb\n").line_endings, LineEndings::Mixed);
+        assert_eq!(scan(b"no newline").line_endings, LineEndings::None);
+        // A lone CR isn't a line break
+        assert_eq!(scan(b"a\rb\n").line_endings, LineEndings::Lf);
+    }
+
+    #[test]
+    fn test_trailing_whitespace_and_final_newline() {
+        let file = scan(b"a \nb\t\r\nc\nd  ");
+        assert_eq!(file.trailing_whitespace_lines, 3);
+        assert!(!file.final_newline);
+
+        let file = scan(b"a\r \nb\n");
+        assert_eq!(file.trailing_whitespace_lines, 1);
+        assert!(file.final_newline);
+        assert!(scan(b"").final_newline);
+    }
+
+    #[test]
+    fn test_bom_detection() {
+        assert!(scan(b"\xEF\xBB\xBFfn main() {}\n").has_bom);
+        assert!(!scan(b"fn main() {}\n").has_bom);
+        assert!(!scan(b"\xEF\xBB").has_bom);
+    }
+
+    #[test]
+    fn test_streaming_matches_whole_buffer() {
+        let content = b"\xEF\xBB\xBFa \r\nb\nc\t\r\n";
+        let whole = scan(content);
+        let streamed = scan_reader("test", OneByte(content)).unwrap().unwrap();
+        assert_eq!(whole, streamed);
+        assert_eq!(streamed.line_endings, LineEndings::Mixed);
+        assert!(streamed.has_bom);
+        assert_eq!(streamed.trailing_whitespace_lines, 2);
+    }
+
+    #[test]
+    fn test_binary_files_skipped() {
+        assert_eq!(scan_reader("bin", &b"\x7fELF\x00\x01"[..]).unwrap(), None);
+    }
+
+    #[test]
+    fn test_scan_fixtures() {
+        let report = scan_path(&fixture_dir(), None, None).unwrap();
+        let find = |name: &str| report.files.iter().find(|f| f.file == name);
+
+        assert_eq!(report.summary.files_scanned, 4);
+        assert_eq!(report.summary.lf, 1);
+        assert_eq!(report.summary.crlf, 2);
+        assert_eq!(report.summary.mixed, 1);
+        assert_eq!(report.summary.with_bom, 1);
+        assert!(find("clean.rs").is_none());
+
+        let mixed = find("mixed.py").unwrap();
+        assert_eq!(mixed.line_endings, LineEndings::Mixed);
+        assert_eq!(mixed.trailing_whitespace_lines, 2);
+        assert!(!mixed.final_newline);
+        assert_eq!(report.files[0].file, "mixed.py", "worst offender first");
+
+        let bom = find("bom.rs").unwrap();
+        assert!(bom.has_bom);
+        assert_eq!(bom.line_endings, LineEndings::Crlf);
+        // Consistent CRLF on its own isn't an issue
+        assert!(find("crlf.js").is_none());
+    }
+
+    #[test]
+    fn test_fix_content() {
+        assert_eq!(fix_content(b"a \r\nb\r\nc\n").unwrap(), b"a\r\nb\r\nc\r\n");
+        assert_eq!(fix_content(b"a\nb\r\n").unwrap(), b"a\nb\n");
+        assert_eq!(fix_content(b"\xEF\xBB\xBFx\t\ny").unwrap(), b"\xEF\xBB\xBFx\ny");
+        assert_eq!(fix_content(b"clean\n"), None);
+    }
+
+    #[test]
+    fn test_fix_files_rewrites_only_fixable_files() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["clean.rs", "mixed.py", "bom.rs", "crlf.js"] {
+            std::fs::copy(fixture_dir().join(name), dir.path().join(name)).unwrap();
+        }
+
+        let report = scan_path(dir.path(), None, None).unwrap();
+        assert_eq!(fix_files(dir.path(), &report).unwrap(), 1);
+
+        let rescanned = scan_path(dir.path(), None, None).unwrap();
+        assert_eq!(rescanned.summary.mixed, 0);
+        assert_eq!(rescanned.summary.with_trailing_whitespace, 0);
+        // Not touched by --fix
+        assert_eq!(rescanned.summary.with_bom, 1);
+        assert_eq!(rescanned.summary.missing_final_newline, 1);
+    }
+}
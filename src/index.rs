@@ -1,7 +1,11 @@
 //! Incremental Indexing Module
 //!
 //! Provides incremental indexing for large codebases with persistent storage.
+//! Each entry keeps the file's trigrams, so searches given an index can skip
+//! files that can't contain the query without opening them.
 
+use crate::lines::split_lines;
+use crate::options::has_extension;
 use dashmap::DashMap;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -20,14 +24,25 @@ pub struct IndexEntry {
     pub functions: Vec<String>,
     pub classes: Vec<String>,
     pub imports: Vec<String>,
+    /// Sorted, distinct trigrams of the content, ASCII case folded (see [`trigrams`])
+    #[serde(default)]
+    pub trigrams: Vec<u32>,
 }
 
-#[derive(Debug)]
 pub struct CodeIndex {
     entries: Arc<DashMap<String, IndexEntry>>,
     index_path: PathBuf,
 }
 
+impl std::fmt::Debug for CodeIndex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CodeIndex")
+            .field("index_path", &self.index_path)
+            .field("entries", &self.entries.len())
+            .finish()
+    }
+}
+
 impl CodeIndex {
     pub fn new(index_path: PathBuf) -> Self {
         let entries = Arc::new(DashMap::new());
@@ -46,20 +61,26 @@ impl CodeIndex {
     }
 
     pub fn index_file(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        self.index_file_change(path).map(|_| ())
+    }
+
+    fn index_file_change(&self, path: &Path) -> Result<FileChange, Box<dyn std::error::Error>> {
         let metadata = fs::metadata(path)?;
         let modified = metadata.modified()?;
         let size = metadata.len();
         
         let path_str = path.to_string_lossy().to_string();
         
-        if let Some(entry) = self.entries.get(&path_str) {
-            if entry.modified == modified && entry.size == size {
-                return Ok(());
+        let change = match self.entries.get(&path_str) {
+            Some(entry) if entry.modified == modified && entry.size == size => {
+                return Ok(FileChange::Unchanged);
             }
-        }
+            Some(_) => FileChange::Updated,
+            None => FileChange::Added,
+        };
         
         let content = fs::read_to_string(path)?;
-        let lines = content.lines().count();
+        let lines = split_lines(&content).count();
         
         let functions = extract_functions(&content, path);
         let classes = extract_classes(&content, path);
@@ -73,45 +94,15 @@ impl CodeIndex {
             functions,
             classes,
             imports,
+            trigrams: trigrams(content.as_bytes()),
         };
         
         self.entries.insert(path_str, entry);
-        Ok(())
+        Ok(change)
     }
 
     pub fn index_directory(&self, path: &Path, extensions: Option<&[String]>, exclude: Option<&[String]>) -> Result<(), Box<dyn std::error::Error>> {
-        let walker = WalkDir::new(path)
-            .into_iter()
-            .filter_entry(|e| {
-                if let Some(name) = e.file_name().to_str() {
-                    if let Some(exclude_dirs) = exclude {
-                        for exclude_dir in exclude_dirs {
-                            if name == exclude_dir {
-                                return false;
-                            }
-                        }
-                    }
-                }
-                true
-            })
-            .filter_map(|e| e.ok())
-            .filter(|e| e.file_type().is_file());
-
-        let files: Vec<PathBuf> = walker
-            .filter(|entry| {
-                let file_path = entry.path();
-                if let Some(exts) = extensions {
-                    if let Some(ext) = file_path.extension().and_then(|s| s.to_str()) {
-                        exts.iter().any(|e| e == ext)
-                    } else {
-                        false
-                    }
-                } else {
-                    true
-                }
-            })
-            .map(|e| e.path().to_path_buf())
-            .collect();
+        let files = collect_files(path, extensions, exclude);
 
         use rayon::prelude::*;
         files.par_iter().for_each(|file| {
@@ -121,6 +112,45 @@ impl CodeIndex {
         Ok(())
     }
 
+    /// Re-index a directory, dropping entries under it whose files no longer exist
+    pub fn refresh_directory(&self, path: &Path, extensions: Option<&[String]>, exclude: Option<&[String]>) -> Result<RefreshStats, Box<dyn std::error::Error>> {
+        let files = collect_files(path, extensions, exclude);
+
+        use rayon::prelude::*;
+        let changes: Vec<FileChange> = files
+            .par_iter()
+            .filter_map(|file| self.index_file_change(file).ok())
+            .collect();
+
+        let mut stats = RefreshStats::default();
+        for change in changes {
+            match change {
+                FileChange::Added => stats.added += 1,
+                FileChange::Updated => stats.updated += 1,
+                FileChange::Unchanged => {}
+            }
+        }
+
+        let root = path.to_string_lossy().to_string();
+        let stale: Vec<String> = self.entries
+            .iter()
+            .filter(|e| e.key().starts_with(&root) && !Path::new(e.key()).exists())
+            .map(|e| e.key().clone())
+            .collect();
+        for key in stale {
+            if self.remove(&key) {
+                stats.removed += 1;
+            }
+        }
+
+        Ok(stats)
+    }
+
+    /// Remove a file from the index, returning whether it was indexed
+    pub fn remove(&self, path: &str) -> bool {
+        self.entries.remove(path).is_some()
+    }
+
     pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
         let entries_map: HashMap<String, IndexEntry> = self.entries
             .iter()
@@ -137,6 +167,37 @@ impl CodeIndex {
         Ok(())
     }
 
+    /// Whether `path` may contain text with all of `required` trigrams
+    ///
+    /// Only answers `false` when the file's entry is current: files that are
+    /// missing from the index, or whose mtime or size changed since they were
+    /// indexed, may contain anything.
+    pub fn may_contain(&self, path: &Path, required: &[u32]) -> bool {
+        let Some(entry) = self.entries.get(path.to_string_lossy().as_ref()) else {
+            return true;
+        };
+        let current = fs::metadata(path)
+            .and_then(|m| Ok(m.modified()? == entry.modified && m.len() == entry.size))
+            .unwrap_or(false);
+        // Entries written before trigrams were stored have none
+        if !current || (entry.trigrams.is_empty() && entry.size >= 3) {
+            return true;
+        }
+        required.iter().all(|t| entry.trigrams.binary_search(t).is_ok())
+    }
+
+    /// Whether the index has no entries, e.g. because it was never built
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Paths of all indexed files, sorted
+    pub fn paths(&self) -> Vec<String> {
+        let mut paths: Vec<String> = self.entries.iter().map(|e| e.key().clone()).collect();
+        paths.sort();
+        paths
+    }
+
     pub fn get(&self, path: &str) -> Option<IndexEntry> {
         self.entries.get(path).map(|e| e.value().clone())
     }
@@ -200,6 +261,65 @@ pub struct IndexStats {
     pub total_classes: usize,
 }
 
+/// Files added, updated, and removed by [`CodeIndex::refresh_directory`]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RefreshStats {
+    pub added: usize,
+    pub updated: usize,
+    pub removed: usize,
+}
+
+/// Sorted, distinct byte trigrams of `bytes` with ASCII letters lowercased
+///
+/// Used both for file content and for the text a query requires, so a
+/// case-insensitive query is ruled out exactly when its folded text is absent.
+pub fn trigrams(bytes: &[u8]) -> Vec<u32> {
+    let mut found: Vec<u32> = bytes
+        .windows(3)
+        .map(|w| {
+            let [a, b, c] = [w[0], w[1], w[2]].map(|b| b.to_ascii_lowercase() as u32);
+            (a << 16) | (b << 8) | c
+        })
+        .collect();
+    found.sort_unstable();
+    found.dedup();
+    found
+}
+
+enum FileChange {
+    Added,
+    Updated,
+    Unchanged,
+}
+
+fn collect_files(path: &Path, extensions: Option<&[String]>, exclude: Option<&[String]>) -> Vec<PathBuf> {
+    WalkDir::new(path)
+        .into_iter()
+        .filter_entry(|e| {
+            if let Some(name) = e.file_name().to_str() {
+                if let Some(exclude_dirs) = exclude {
+                    for exclude_dir in exclude_dirs {
+                        if name == exclude_dir {
+                            return false;
+                        }
+                    }
+                }
+            }
+            true
+        })
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter(|entry| {
+            let file_path = entry.path();
+            match extensions {
+                Some(exts) => has_extension(file_path, exts),
+                None => true,
+            }
+        })
+        .map(|e| e.path().to_path_buf())
+        .collect()
+}
+
 fn extract_functions(content: &str, path: &Path) -> Vec<String> {
     let mut functions = Vec::new();
     let ext = path.extension().and_then(|s| s.to_str()).unwrap_or("");
@@ -215,7 +335,7 @@ fn extract_functions(content: &str, path: &Path) -> Vec<String> {
     
     for pattern in patterns {
         if let Ok(re) = regex::Regex::new(pattern) {
-            for line in content.lines() {
+            for line in split_lines(content) {
                 if let Some(caps) = re.captures(line) {
                     if let Some(name) = caps.get(1) {
                         functions.push(name.as_str().to_string());
@@ -243,7 +363,7 @@ fn extract_classes(content: &str, path: &Path) -> Vec<String> {
     
     for pattern in patterns {
         if let Ok(re) = regex::Regex::new(pattern) {
-            for line in content.lines() {
+            for line in split_lines(content) {
                 if let Some(caps) = re.captures(line) {
                     if let Some(name) = caps.get(1) {
                         classes.push(name.as_str().to_string());
@@ -271,7 +391,7 @@ fn extract_imports(content: &str, path: &Path) -> Vec<String> {
     
     for pattern in patterns {
         if let Ok(re) = regex::Regex::new(pattern) {
-            for line in content.lines() {
+            for line in split_lines(content) {
                 if let Some(caps) = re.captures(line) {
                     if let Some(name) = caps.get(1) {
                         imports.push(name.as_str().to_string());
@@ -337,4 +457,63 @@ mod tests {
         assert!(classes.contains(&"Config".to_string()));
         assert!(classes.contains(&"Status".to_string()));
     }
+
+    #[test]
+    fn test_may_contain_uses_current_trigrams_only() {
+        let dir = tempdir().unwrap();
+        let index = CodeIndex::new(dir.path().join("index.json"));
+        let file = dir.path().join("a.rs");
+        fs::write(&file, "fn Alpha() {}").unwrap();
+        index.index_file(&file).unwrap();
+
+        assert!(index.may_contain(&file, &trigrams(b"alpha")));
+        assert!(!index.may_contain(&file, &trigrams(b"beta")));
+        // Files that aren't indexed may contain anything
+        assert!(index.may_contain(&dir.path().join("b.rs"), &trigrams(b"beta")));
+
+        // A changed size invalidates the entry until the file is indexed again
+        fs::write(&file, "fn Alpha() {} fn beta() {}").unwrap();
+        assert!(index.may_contain(&file, &trigrams(b"beta")));
+        index.index_file(&file).unwrap();
+        assert!(!index.may_contain(&file, &trigrams(b"gamma")));
+    }
+
+    #[test]
+    fn test_trigrams_survive_save_and_load() {
+        let dir = tempdir().unwrap();
+        let index_path = dir.path().join("index.json");
+        let file = dir.path().join("a.rs");
+        fs::write(&file, "needle").unwrap();
+        let index = CodeIndex::new(index_path.clone());
+        index.index_file(&file).unwrap();
+        index.save().unwrap();
+
+        let loaded = CodeIndex::new(index_path);
+        assert_eq!(loaded.get(&file.to_string_lossy()).unwrap().trigrams, trigrams(b"needle"));
+        assert!(!loaded.may_contain(&file, &trigrams(b"haystack")));
+    }
+
+    #[test]
+    fn test_refresh_directory_reports_changes() {
+        let dir = tempdir().unwrap();
+        let index = CodeIndex::new(dir.path().join("index.json"));
+        let src = dir.path().join("src");
+        fs::create_dir(&src).unwrap();
+        fs::write(src.join("a.rs"), "fn a() {}").unwrap();
+        fs::write(src.join("b.rs"), "fn b() {}").unwrap();
+
+        let stats = index.refresh_directory(&src, None, None).unwrap();
+        assert_eq!(stats, RefreshStats { added: 2, updated: 0, removed: 0 });
+
+        let stats = index.refresh_directory(&src, None, None).unwrap();
+        assert_eq!(stats, RefreshStats::default());
+
+        fs::write(src.join("a.rs"), "fn a() {}\nfn a2() {}").unwrap();
+        fs::remove_file(src.join("b.rs")).unwrap();
+        fs::write(src.join("c.rs"), "fn c() {}").unwrap();
+
+        let stats = index.refresh_directory(&src, None, None).unwrap();
+        assert_eq!(stats, RefreshStats { added: 1, updated: 1, removed: 1 });
+        assert!(index.get(&src.join("b.rs").to_string_lossy()).is_none());
+    }
 }
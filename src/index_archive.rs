@@ -0,0 +1,283 @@
+//! Index Archives
+//!
+//! `codesearch index --pack` writes the index of a tree, and with
+//! `--include-contents` every file's contents, to one self-contained file.
+//! `codesearch search --index-archive` searches it on another machine without
+//! the original tree, reporting paths relative to the packed root.
+//!
+//! The file starts with a `CSIDX <version>` line and a `fnv1a64 <hex>` line
+//! holding the checksum of the rest, a JSON payload. Contents are stored as
+//! indices into one table of the distinct lines of all files, which shrinks
+//! source trees considerably (blank lines, braces and imports repeat a lot)
+//! and restores every file byte for byte.
+
+use crate::errors::ArchiveError;
+use crate::index::{CodeIndex, IndexEntry};
+use crate::search::search_contents_outcome;
+use crate::types::{SearchOptions, SearchOutcome};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Format version written in the header; archives with another version are rejected
+pub const ARCHIVE_VERSION: u32 = 1;
+
+const MAGIC: &str = "CSIDX";
+const CHECKSUM_ALGORITHM: &str = "fnv1a64";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ArchivePayload {
+    root: String,
+    created: DateTime<Utc>,
+    files: Vec<ArchivedFile>,
+    /// Distinct lines of all stored contents, referenced by [`ArchivedFile::contents`]
+    lines: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ArchivedFile {
+    /// The index entry, with `path` relative to the packed root
+    entry: IndexEntry,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    contents: Option<Vec<u32>>,
+}
+
+/// What [`pack_index`] wrote
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PackStats {
+    pub files: usize,
+    /// Files whose contents are stored
+    pub files_with_contents: usize,
+    /// Size of the stored contents before packing
+    pub content_bytes: u64,
+    /// Size of the archive file
+    pub archive_bytes: u64,
+}
+
+/// Write the entries of `index` under `root` to a single archive at `out`
+///
+/// Entries indexed under another spelling of the root (e.g. absolute instead of
+/// relative) are left out. With `include_contents`, each file is read from disk
+/// now; files that can no longer be read are packed without contents.
+pub fn pack_index(
+    index: &CodeIndex,
+    root: &Path,
+    include_contents: bool,
+    out: &Path,
+) -> Result<PackStats, Box<dyn std::error::Error>> {
+    let mut stats = PackStats::default();
+    let mut files = Vec::new();
+    let mut lines: Vec<String> = Vec::new();
+    let mut line_ids: HashMap<String, u32> = HashMap::new();
+
+    for path in index.paths() {
+        let (Some(mut entry), Some(relative)) = (index.get(&path), member_path(root, &path)) else {
+            continue;
+        };
+        entry.path = relative;
+        let contents: Option<Vec<u32>> = include_contents.then(|| fs::read_to_string(&path).ok()).flatten().map(|text| {
+            stats.files_with_contents += 1;
+            stats.content_bytes += text.len() as u64;
+            text.split('\n')
+                .map(|line| match line_ids.get(line) {
+                    Some(&id) => id,
+                    None => {
+                        let id = lines.len() as u32;
+                        line_ids.insert(line.to_string(), id);
+                        lines.push(line.to_string());
+                        id
+                    }
+                })
+                .collect()
+        });
+        files.push(ArchivedFile { entry, contents });
+    }
+    stats.files = files.len();
+
+    let payload = serde_json::to_vec(&ArchivePayload {
+        root: root.display().to_string(),
+        created: Utc::now(),
+        files,
+        lines,
+    })?;
+    let mut data = format!("{MAGIC} {ARCHIVE_VERSION}\n{CHECKSUM_ALGORITHM} {:016x}\n", fnv1a64(&payload)).into_bytes();
+    data.extend_from_slice(&payload);
+
+    if let Some(parent) = out.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(out, &data)?;
+    stats.archive_bytes = data.len() as u64;
+    Ok(stats)
+}
+
+/// An index archive loaded into memory
+#[derive(Debug)]
+pub struct IndexArchive {
+    path: PathBuf,
+    payload: ArchivePayload,
+}
+
+impl IndexArchive {
+    /// Read an archive, checking its header and checksum
+    pub fn open(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let data = fs::read(path)?;
+        let not_an_archive = || ArchiveError::NotAnArchive { path: path.to_path_buf() };
+
+        let (header, rest) = split_line(&data).ok_or_else(not_an_archive)?;
+        let version = header.strip_prefix(&format!("{MAGIC} ")).ok_or_else(not_an_archive)?;
+        if version != ARCHIVE_VERSION.to_string() {
+            return Err(ArchiveError::UnsupportedVersion { version: version.to_string(), expected: ARCHIVE_VERSION }.into());
+        }
+        let (checksum, payload) = split_line(rest).ok_or_else(not_an_archive)?;
+        let checksum = checksum.strip_prefix(&format!("{CHECKSUM_ALGORITHM} ")).ok_or_else(not_an_archive)?;
+        if u64::from_str_radix(checksum, 16).ok() != Some(fnv1a64(payload)) {
+            return Err(ArchiveError::ChecksumMismatch { path: path.to_path_buf() }.into());
+        }
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            payload: serde_json::from_slice(payload)?,
+        })
+    }
+
+    /// The root the archive was packed from, as given on the packing machine
+    pub fn root(&self) -> &str {
+        &self.payload.root
+    }
+
+    pub fn created(&self) -> DateTime<Utc> {
+        self.payload.created
+    }
+
+    /// Index entries, with paths relative to the packed root
+    pub fn entries(&self) -> impl Iterator<Item = &IndexEntry> {
+        self.payload.files.iter().map(|file| &file.entry)
+    }
+
+    /// Whether file contents were packed, so the archive can be searched
+    pub fn has_contents(&self) -> bool {
+        self.payload.files.iter().any(|file| file.contents.is_some())
+    }
+
+    /// `(relative path, contents)` of every file packed with its contents, in path order
+    pub fn members(&self) -> Vec<(String, String)> {
+        self.payload
+            .files
+            .iter()
+            .filter_map(|file| {
+                let ids = file.contents.as_ref()?;
+                let text = ids
+                    .iter()
+                    .map(|&id| self.payload.lines.get(id as usize).map_or("", String::as_str))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                Some((file.entry.path.clone(), text))
+            })
+            .collect()
+    }
+
+    /// Search the packed contents like [`crate::search::search_code_outcome`] searches a tree
+    pub fn search(&self, query: &str, options: &SearchOptions) -> Result<SearchOutcome, Box<dyn std::error::Error>> {
+        if !self.has_contents() {
+            return Err(ArchiveError::NoContents { path: self.path.clone() }.into());
+        }
+        search_contents_outcome(query, &self.members(), options)
+    }
+}
+
+/// `path` relative to `root`, with `/` separators; `None` if it isn't under `root`
+fn member_path(root: &Path, path: &str) -> Option<String> {
+    let path = Path::new(path);
+    let relative = path.strip_prefix(root).ok()?;
+    // A single indexed file is its own root
+    let relative = if relative.as_os_str().is_empty() { Path::new(path.file_name()?) } else { relative };
+    Some(
+        relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/"),
+    )
+}
+
+/// The first line of `data` as text, and the bytes after it
+fn split_line(data: &[u8]) -> Option<(&str, &[u8])> {
+    let end = data.iter().position(|&b| b == b'\n')?;
+    Some((std::str::from_utf8(&data[..end]).ok()?, &data[end + 1..]))
+}
+
+/// 64-bit FNV-1a, stable across platforms and Rust versions (unlike `DefaultHasher`)
+fn fnv1a64(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| (hash ^ b as u64).wrapping_mul(0x0100_0000_01b3))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn pack_workspace(include_contents: bool) -> (tempfile::TempDir, PathBuf, PackStats) {
+        let out_dir = tempdir().unwrap();
+        let workspace = tempdir().unwrap();
+        let root = workspace.path();
+        fs::create_dir_all(root.join("src/net")).unwrap();
+        fs::write(root.join("src/lib.rs"), "mod net;\n\npub fn connect(url: &str) {\n    net::open(url);\n}\n").unwrap();
+        fs::write(root.join("src/net/mod.rs"), "pub fn open(url: &str) {\r\n    println!(\"{url}\");\r\n}\r\n").unwrap();
+        fs::write(root.join("README.md"), "# Demo\n").unwrap();
+
+        let index = CodeIndex::new(out_dir.path().join("index.json"));
+        index.index_directory(root, Some(&["rs".to_string()]), None).unwrap();
+        let archive = out_dir.path().join("out.csidx");
+        let stats = pack_index(&index, root, include_contents, &archive).unwrap();
+        // Everything below runs without the tree
+        drop(workspace);
+        (out_dir, archive, stats)
+    }
+
+    #[test]
+    fn test_search_archive_without_tree() {
+        let (_dir, path, stats) = pack_workspace(true);
+        assert_eq!((stats.files, stats.files_with_contents), (2, 2));
+        assert!(stats.archive_bytes > 0);
+
+        let archive = IndexArchive::open(&path).unwrap();
+        let members = archive.members();
+        let paths: Vec<&str> = members.iter().map(|(path, _)| path.as_str()).collect();
+        assert_eq!(paths, vec!["src/lib.rs", "src/net/mod.rs"]);
+        assert_eq!(members[1].1, "pub fn open(url: &str) {\r\n    println!(\"{url}\");\r\n}\r\n");
+
+        let outcome = archive.search(r"open\(", &SearchOptions::default()).unwrap();
+        let mut found: Vec<(&str, usize)> = outcome.results.iter().map(|r| (r.file.as_str(), r.line_number)).collect();
+        found.sort();
+        assert_eq!(found, vec![("src/lib.rs", 4), ("src/net/mod.rs", 1)]);
+        assert!(archive.entries().any(|entry| entry.functions.contains(&"connect".to_string())));
+    }
+
+    #[test]
+    fn test_archive_without_contents_cannot_be_searched() {
+        let (_dir, path, stats) = pack_workspace(false);
+        assert_eq!((stats.files, stats.files_with_contents, stats.content_bytes), (2, 0, 0));
+
+        let archive = IndexArchive::open(&path).unwrap();
+        assert_eq!(archive.entries().count(), 2);
+        let err = archive.search("open", &SearchOptions::default()).unwrap_err();
+        assert!(err.to_string().contains("--include-contents"), "{err}");
+    }
+
+    #[test]
+    fn test_corrupted_archive_is_rejected() {
+        let (_dir, path, _) = pack_workspace(true);
+        let mut data = fs::read(&path).unwrap();
+        let last = data.len() - 2;
+        data[last] ^= 0x20;
+        fs::write(&path, &data).unwrap();
+        let err = IndexArchive::open(&path).unwrap_err();
+        assert!(err.to_string().contains("checksum mismatch"), "{err}");
+
+        fs::write(&path, "{}").unwrap();
+        assert!(IndexArchive::open(&path).unwrap_err().to_string().contains("Not an index archive"));
+    }
+}
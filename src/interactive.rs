@@ -1,15 +1,43 @@
 //! Interactive Mode Module
 //!
 //! Provides an interactive REPL for code searching and analysis.
+//!
+//! Every accepted command is recorded with a timestamp; `save-session <file>`
+//! writes them out as a plain-text script (one command per line, `#`
+//! comments) that `codesearch interactive --replay <file>` runs again.
+//!
+//! The query history and the toggles outlive the session: they are saved to
+//! `~/.codesearch/interactive.json` on exit and loaded on the next start.
+//! Replayed sessions neither load nor save them, so a replay always starts
+//! from the same state.
 
-use crate::{analysis, circular, complexity, deadcode, duplicates, export};
+use crate::{analysis, bookmarks, circular, clipboard, complexity, deadcode, duplicates, export};
+use crate::options::normalize_extensions;
 use crate::search::search_code;
 use crate::search::print_results;
 use crate::search::print_search_stats;
 use crate::types::{SearchOptions, SearchResult};
+use crate::theme::{self, Glyph};
+use chrono::{DateTime, SecondsFormat, Utc};
 use colored::*;
-use std::io::{self, Write};
-use std::path::Path;
+use regex::RegexBuilder;
+use rustyline::completion::{Completer, FilenameCompleter, Pair};
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::{DefaultHistory, History};
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+/// Where history and settings persist between sessions, relative to the home directory
+pub const STATE_FILE: &str = ".codesearch/interactive.json";
+
+/// Most queries the persisted history keeps
+pub const HISTORY_LIMIT: usize = 100;
 
 /// Run interactive search mode
 pub fn run(
@@ -17,7 +45,48 @@ pub fn run(
     extensions: Option<&[String]>,
     exclude: Option<&[String]>,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    println!("{}", "🎮 Interactive Search Mode".cyan().bold());
+    let session = match InteractiveState::default_path() {
+        Some(state_path) => Session::with_state(path, extensions, exclude, state_path),
+        None => Session::new(path, extensions, exclude),
+    };
+    run_session(session)
+}
+
+/// Replay a session script saved with `save-session`
+///
+/// Without `replay_to` every step runs and the session ends; with it, steps
+/// up to and including that one run and the REPL continues from there.
+pub fn replay(
+    path: &Path,
+    extensions: Option<&[String]>,
+    exclude: Option<&[String]>,
+    script: &Path,
+    replay_to: Option<usize>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let text = fs::read_to_string(script)
+        .map_err(|e| format!("Cannot read session script {}: {e}", script.display()))?;
+    let steps = parse_session_script(&text);
+    let count = match replay_to {
+        Some(step) if step == 0 || step > steps.len() => {
+            return Err(format!("--replay-to {step}: {} has steps 1-{}", script.display(), steps.len()).into());
+        }
+        Some(step) => step,
+        None => steps.len(),
+    };
+
+    let mut session = Session::new(path, extensions, exclude);
+    let finished = session.replay(&steps[..count], true)?;
+    match replay_to {
+        Some(_) if finished => run_session(session),
+        _ => {
+            println!("{}", format!("Replayed {count} step(s) from {}", script.display()).green());
+            Ok(())
+        }
+    }
+}
+
+fn run_session(mut session: Session) -> Result<(), Box<dyn std::error::Error>> {
+    println!("{}", theme::heading(Glyph::Search, "Interactive Search Mode").cyan().bold());
     println!(
         "{}",
         "Type 'help' for commands and shortcuts, 'quit' to exit"
@@ -26,301 +95,740 @@ pub fn run(
     );
     println!();
 
-    let mut last_query: Option<String> = None;
-    let mut current_extensions = extensions.map(|exts| exts.to_vec());
-    let mut current_exclude = exclude.map(|excl| excl.to_vec());
-    let mut fuzzy_mode = false;
-    let mut case_insensitive = true;
-    let mut ranking_mode = false;
-    let mut semantic_mode = false;
-    let mut last_results: Vec<SearchResult> = Vec::new();
+    let result = if io::stdin().is_terminal() && io::stdout().is_terminal() {
+        run_editor(&mut session)
+    } else {
+        run_piped(&mut session)
+    };
+
+    session.persist();
+    result
+}
+
+fn prompt(session: &Session) -> String {
+    format!("{} {} ", session.settings.mode_indicator().blue(), "codesearch>".green().bold())
+}
+
+/// Read commands with line editing, history on the arrow keys and tab completion
+///
+/// Ctrl-C drops the line being typed; Ctrl-D on an empty line ends the session.
+fn run_editor(session: &mut Session) -> Result<(), Box<dyn std::error::Error>> {
+    let mut editor: Editor<CommandHelper, DefaultHistory> = Editor::new()?;
+    editor.set_helper(Some(CommandHelper::default()));
+    for query in &session.history {
+        editor.add_history_entry(query.as_str())?;
+    }
 
     loop {
-        let mode_indicator = format!(
-            "[{}{}{}{}]",
-            if fuzzy_mode { "F" } else { "-" },
-            if case_insensitive { "I" } else { "-" },
-            if ranking_mode { "R" } else { "-" },
-            if semantic_mode { "S" } else { "-" }
-        );
+        match editor.readline(&prompt(session)) {
+            Ok(line) => {
+                if run_editor_line(session, editor.history_mut(), &line)? == Outcome::Quit {
+                    break;
+                }
+            }
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => {
+                println!();
+                break;
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(())
+}
 
-        print!("{} {} ", mode_indicator.blue(), "codesearch>".green().bold());
+/// Run one line read by the editor, keeping the editor's up-arrow history in step
+///
+/// The line joins `history`, and `history clear` empties it along with the session's.
+fn run_editor_line<H: History>(session: &mut Session, history: &mut H, line: &str) -> rustyline::Result<Outcome> {
+    if !line.trim().is_empty() {
+        history.add(line.trim())?;
+    }
+    let outcome = run_line(session, line);
+    if std::mem::take(&mut session.history_cleared) {
+        history.clear()?;
+    }
+    Ok(outcome)
+}
+
+/// Read commands a line at a time from piped input, until it ends
+fn run_piped(session: &mut Session) -> Result<(), Box<dyn std::error::Error>> {
+    loop {
+        print!("{}", prompt(session));
         io::stdout().flush()?;
 
         let mut input = String::new();
-        io::stdin().read_line(&mut input)?;
-        let input = input.trim();
+        if io::stdin().read_line(&mut input)? == 0 {
+            break;
+        }
+        if run_line(session, input.trim()) == Outcome::Quit {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Run one line typed at the prompt, printing an error instead of ending the session
+fn run_line(session: &mut Session, line: &str) -> Outcome {
+    session.execute(line).unwrap_or_else(|e| {
+        println!("{}", format!("Error: {e}").red());
+        Outcome::Ignored
+    })
+}
+
+/// Commands tab completion offers for the first word of a line
+const COMMANDS: &[&str] = &[
+    "analyze", "case", "circular", "clear", "complexity", "copy", "deadcode", "duplicates", "exclude", "exit",
+    "export", "ext", "fuzzy", "help", "history", "keep", "languages", "mark", "quit", "rank", "save-session",
+    "semantic", "status",
+];
+
+/// Commands whose argument completes as a file path
+const PATH_COMMANDS: &[&str] = &["exclude", "export", "save-session"];
+
+/// Tab completion for the prompt: command names, then paths after [`PATH_COMMANDS`]
+#[derive(Default)]
+struct CommandHelper {
+    files: FilenameCompleter,
+}
+
+impl CommandHelper {
+    fn complete_line(&self, line: &str, pos: usize) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let before = &line[..pos];
+        let indent = before.len() - before.trim_start().len();
+        match before.trim_start().split_once(char::is_whitespace) {
+            None => {
+                let prefix = &before[indent..];
+                let candidates = COMMANDS
+                    .iter()
+                    .filter(|command| command.starts_with(prefix))
+                    .map(|command| Pair { display: command.to_string(), replacement: format!("{command} ") })
+                    .collect();
+                Ok((indent, candidates))
+            }
+            Some((command, _)) if PATH_COMMANDS.contains(&command) => self.files.complete_path(line, pos),
+            Some(_) => Ok((pos, Vec::new())),
+        }
+    }
+}
+
+impl Completer for CommandHelper {
+    type Candidate = Pair;
+
+    fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> rustyline::Result<(usize, Vec<Pair>)> {
+        self.complete_line(line, pos)
+    }
+}
+
+impl Hinter for CommandHelper {
+    type Hint = String;
+}
+
+impl Highlighter for CommandHelper {}
+
+impl Validator for CommandHelper {}
+
+impl Helper for CommandHelper {}
+
+/// Settings changed by the toggles, `ext` and `exclude`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionSettings {
+    pub extensions: Option<Vec<String>>,
+    pub exclude: Option<Vec<String>>,
+    pub fuzzy: bool,
+    pub case_insensitive: bool,
+    pub ranking: bool,
+    pub semantic: bool,
+}
 
-        if input.is_empty() {
-            continue;
+impl Default for SessionSettings {
+    fn default() -> Self {
+        Self {
+            extensions: None,
+            exclude: None,
+            fuzzy: false,
+            case_insensitive: true,
+            ranking: false,
+            semantic: false,
         }
+    }
+}
+
+impl SessionSettings {
+    /// Prompt prefix such as `[-I--]`, one letter per enabled toggle
+    fn mode_indicator(&self) -> String {
+        format!(
+            "[{}{}{}{}]",
+            if self.fuzzy { "F" } else { "-" },
+            if self.case_insensitive { "I" } else { "-" },
+            if self.ranking { "R" } else { "-" },
+            if self.semantic { "S" } else { "-" }
+        )
+    }
+
+    fn search_options(&self) -> SearchOptions {
+        SearchOptions::builder()
+            .extensions(self.extensions.clone())
+            .ignore_case(self.case_insensitive)
+            .fuzzy(self.fuzzy)
+            .max_results(20)
+            .exclude(self.exclude.clone())
+            .rank(self.ranking)
+            .semantic(self.semantic)
+            .build()
+    }
+}
+
+/// History and settings kept between interactive sessions
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InteractiveState {
+    /// Queries, oldest first
+    pub history: Vec<String>,
+    pub settings: SessionSettings,
+}
+
+impl InteractiveState {
+    /// [`STATE_FILE`] in the home directory (`HOME`, or `USERPROFILE` on Windows)
+    pub fn default_path() -> Option<PathBuf> {
+        let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"))?;
+        (!home.is_empty()).then(|| Path::new(&home).join(STATE_FILE))
+    }
+
+    /// Load the state from `path`; a missing or unreadable file gives the defaults
+    pub fn load(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    /// Write the state to `path`
+    ///
+    /// The file is written under a name of its own and renamed into place, so
+    /// sessions exiting at the same time never leave it half written; the
+    /// last one to exit wins.
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let temp = path.with_extension(format!("{}.tmp", std::process::id()));
+        fs::write(&temp, serde_json::to_string_pretty(self)?)?;
+        fs::rename(&temp, path)?;
+        Ok(())
+    }
+}
+
+/// Append `query` to `history` unless it repeats the last entry, keeping the newest [`HISTORY_LIMIT`]
+pub fn push_history(history: &mut Vec<String>, query: &str) {
+    if history.last().is_some_and(|last| last == query) {
+        return;
+    }
+    history.push(query.to_string());
+    if history.len() > HISTORY_LIMIT {
+        history.drain(..history.len() - HISTORY_LIMIT);
+    }
+}
+
+/// What a command did, which decides whether it is recorded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// Nothing to replay: help, status, a usage message, an invalid selection
+    Ignored,
+    /// Ran and may have changed the session
+    Done,
+    /// Ran and left this many results
+    Results(usize),
+    Quit,
+}
+
+/// One recorded command
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionStep {
+    pub at: DateTime<Utc>,
+    pub command: String,
+    /// Result count left by the command, for commands that search or filter
+    pub results: Option<usize>,
+}
+
+/// The state of an interactive session, and the commands that built it
+pub struct Session {
+    path: PathBuf,
+    pub settings: SessionSettings,
+    pub last_query: Option<String>,
+    pub last_results: Vec<SearchResult>,
+    pub steps: Vec<SessionStep>,
+    /// Queries of this and earlier sessions, oldest first
+    pub history: Vec<String>,
+    /// Where history and settings are saved on exit; `None` keeps them in memory
+    state_path: Option<PathBuf>,
+    /// Set by `history clear` until the line editor has dropped its copy too
+    history_cleared: bool,
+}
+
+impl Session {
+    pub fn new(path: &Path, extensions: Option<&[String]>, exclude: Option<&[String]>) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            settings: SessionSettings {
+                extensions: extensions.map(|exts| exts.to_vec()),
+                exclude: exclude.map(|excl| excl.to_vec()),
+                ..SessionSettings::default()
+            },
+            last_query: None,
+            last_results: Vec::new(),
+            steps: Vec::new(),
+            history: Vec::new(),
+            state_path: None,
+            history_cleared: false,
+        }
+    }
+
+    /// A session resuming the history and settings saved in `state_path`
+    ///
+    /// `extensions` and `exclude`, when given, replace the saved ones.
+    pub fn with_state(
+        path: &Path,
+        extensions: Option<&[String]>,
+        exclude: Option<&[String]>,
+        state_path: PathBuf,
+    ) -> Self {
+        let state = InteractiveState::load(&state_path);
+        let mut session = Self::new(path, extensions, exclude);
+        session.settings = SessionSettings {
+            extensions: session.settings.extensions.or(state.settings.extensions),
+            exclude: session.settings.exclude.or(state.settings.exclude),
+            ..state.settings
+        };
+        session.last_query = state.history.last().cloned();
+        session.history = state.history;
+        session.state_path = Some(state_path);
+        session
+    }
+
+    /// Save the history and settings, if the session has somewhere to keep them
+    pub fn persist(&self) {
+        let Some(path) = &self.state_path else { return };
+        let state = InteractiveState { history: self.history.clone(), settings: self.settings.clone() };
+        if let Err(e) = state.save(path) {
+            eprintln!("{}", format!("Could not save interactive history to {}: {e}", path.display()).yellow());
+        }
+    }
+
+    /// Run one command line, recording it if it was accepted
+    pub fn execute(&mut self, input: &str) -> Result<Outcome, Box<dyn std::error::Error>> {
+        let outcome = self.dispatch(input.trim())?;
+        let results = match outcome {
+            Outcome::Results(count) => Some(count),
+            Outcome::Done => None,
+            Outcome::Ignored | Outcome::Quit => return Ok(outcome),
+        };
+        self.steps.push(SessionStep { at: Utc::now(), command: input.trim().to_string(), results });
+        Ok(outcome)
+    }
+
+    /// Run `steps` in order, printing each and the number of results it left
+    ///
+    /// Returns false if a step quit the session.
+    pub fn replay(&mut self, steps: &[String], verbose: bool) -> Result<bool, Box<dyn std::error::Error>> {
+        for (i, step) in steps.iter().enumerate() {
+            if verbose {
+                println!("{}", format!("[{}/{}] {step}", i + 1, steps.len()).cyan().bold());
+            }
+            let outcome = self.execute(step).map_err(|e| format!("Step {} ({step}) failed: {e}", i + 1))?;
+            match outcome {
+                Outcome::Quit => return Ok(false),
+                Outcome::Results(count) if verbose => println!("{}", format!("=> {count} result(s)").dimmed()),
+                _ => {}
+            }
+        }
+        Ok(true)
+    }
+
+    /// The recorded steps as a script for `--replay`
+    pub fn to_script(&self) -> String {
+        let mut script = format!(
+            "# codesearch interactive session, saved {}\n# Recorded in {}; replay with: codesearch interactive <path> --replay <this file>\n# One command per line; lines starting with # are comments, a leading \\ is dropped\n",
+            Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+            self.path.display()
+        );
+        for (i, step) in self.steps.iter().enumerate() {
+            let at = step.at.to_rfc3339_opts(SecondsFormat::Secs, true);
+            match step.results {
+                Some(count) => script.push_str(&format!("\n# step {}, {at}: {count} result(s)\n", i + 1)),
+                None => script.push_str(&format!("\n# step {}, {at}\n", i + 1)),
+            }
+            // Keep queries such as `#include` from reading as comments
+            if step.command.starts_with('#') || step.command.starts_with('\\') {
+                script.push('\\');
+            }
+            script.push_str(&step.command);
+            script.push('\n');
+        }
+        script
+    }
+
+    fn search(&mut self, query: &str) -> Result<usize, Box<dyn std::error::Error>> {
+        let results = search_code(query, &self.path, &self.settings.search_options())?;
+        self.last_results = results.clone();
+        if results.is_empty() {
+            println!("{}", "No matches found.".dimmed());
+        } else {
+            print_results(&results, true, self.settings.ranking);
+            print_search_stats(&results, query);
+        }
+        Ok(results.len())
+    }
+
+    fn dispatch(&mut self, input: &str) -> Result<Outcome, Box<dyn std::error::Error>> {
+        let path = self.path.clone();
+        let path = path.as_path();
+        let settings = &mut self.settings;
 
         let parts: Vec<&str> = input.split_whitespace().collect();
         if parts.is_empty() {
-            continue;
+            return Ok(Outcome::Ignored);
         }
 
-        match parts[0] {
+        let outcome = match parts[0] {
             "quit" | "exit" | "q" | ":q" => {
-                println!("{}", "👋 Goodbye!".green().italic());
-                break;
+                println!("{}", theme::heading(Glyph::Goodbye, "Goodbye!").green().italic());
+                Outcome::Quit
             }
             "help" | "h" | "?" => {
                 print_help();
+                Outcome::Ignored
             }
             "/f" | ":f" | "fuzzy" => {
-                fuzzy_mode = !fuzzy_mode;
-                println!(
-                    "Fuzzy mode: {}",
-                    if fuzzy_mode {
-                        "ON".green()
-                    } else {
-                        "OFF".dimmed()
-                    }
-                );
+                settings.fuzzy = !settings.fuzzy;
+                println!("Fuzzy mode: {}", on_off(settings.fuzzy));
+                Outcome::Done
             }
             "/i" | ":i" | "case" => {
-                case_insensitive = !case_insensitive;
-                println!(
-                    "Case insensitive: {}",
-                    if case_insensitive {
-                        "ON".green()
-                    } else {
-                        "OFF".dimmed()
-                    }
-                );
+                settings.case_insensitive = !settings.case_insensitive;
+                println!("Case insensitive: {}", on_off(settings.case_insensitive));
+                Outcome::Done
             }
             "/r" | ":r" | "rank" => {
-                ranking_mode = !ranking_mode;
-                println!(
-                    "Ranking mode: {}",
-                    if ranking_mode {
-                        "ON".green()
-                    } else {
-                        "OFF".dimmed()
-                    }
-                );
+                settings.ranking = !settings.ranking;
+                println!("Ranking mode: {}", on_off(settings.ranking));
+                Outcome::Done
             }
             "/s" | ":s" | "semantic" => {
-                semantic_mode = !semantic_mode;
-                println!(
-                    "Semantic search: {}",
-                    if semantic_mode {
-                        "ON".green()
-                    } else {
-                        "OFF".dimmed()
-                    }
-                );
+                settings.semantic = !settings.semantic;
+                println!("Semantic search: {}", on_off(settings.semantic));
+                Outcome::Done
             }
             "!!" | "repeat" => {
-                if let Some(ref query) = last_query {
+                if let Some(query) = self.last_query.clone() {
                     println!("{}", format!("Repeating: {}", query).blue());
-                    let options = SearchOptions {
-                        extensions: current_extensions.clone(),
-                        ignore_case: case_insensitive,
-                        fuzzy: fuzzy_mode,
-                        fuzzy_threshold: 0.6,
-                        max_results: 20,
-                        exclude: current_exclude.clone(),
-                        rank: ranking_mode,
-                        cache: false,
-                        semantic: semantic_mode,
-                        benchmark: false,
-                        vs_grep: false,
-                    };
-                    let results = search_code(query, path, &options)?;
-                    last_results = results.clone();
-                    if results.is_empty() {
-                        println!("{}", "No matches found.".dimmed());
-                    } else {
-                        print_results(&results, true, ranking_mode);
-                        print_search_stats(&results, query);
-                    }
+                    Outcome::Results(self.search(&query)?)
                 } else {
                     println!("{}", "No previous search.".dimmed());
+                    Outcome::Ignored
                 }
             }
             "ext" | "extensions" => {
                 if parts.len() > 1 {
-                    let new_exts: Vec<String> = parts[1..].iter().map(|s| s.to_string()).collect();
-                    current_extensions = Some(new_exts.clone());
-                    println!(
-                        "{}",
-                        format!("Extensions set: {}", new_exts.join(", ")).green()
-                    );
+                    match normalize_extensions(&parts[1..]) {
+                        Ok(new_exts) => {
+                            println!(
+                                "{}",
+                                format!("Extensions set: {}", new_exts.join(", ")).green()
+                            );
+                            settings.extensions = Some(new_exts);
+                            Outcome::Done
+                        }
+                        Err(e) => {
+                            println!("{}", e.to_string().red());
+                            Outcome::Ignored
+                        }
+                    }
                 } else {
-                    match &current_extensions {
+                    match &settings.extensions {
                         Some(exts) => {
                             println!("Current extensions: {}", exts.join(", ").blue())
                         }
                         None => println!("No extensions filter (searching all files)"),
                     }
+                    Outcome::Ignored
+                }
+            }
+            "exclude" if parts.len() > 1 && is_selection(&parts[1..]) => {
+                match parse_selection(&parts[1..], self.last_results.len()) {
+                    Ok(indices) => {
+                        let dropped = indices.len();
+                        self.last_results = without_indices(&self.last_results, &indices);
+                        println!("{}", format!("Dropped {dropped} result(s)").green());
+                        print_current_set(&self.last_results, self.last_query.as_deref().unwrap_or(""));
+                        Outcome::Results(self.last_results.len())
+                    }
+                    Err(e) => {
+                        println!("{}", e.red());
+                        Outcome::Ignored
+                    }
+                }
+            }
+            "keep" => {
+                if parts.len() < 2 {
+                    println!("{}", "Usage: keep <pattern>  (filter the last results by a regex)".dimmed());
+                    Outcome::Ignored
+                } else {
+                    let pattern = input[parts[0].len()..].trim();
+                    match RegexBuilder::new(pattern).case_insensitive(settings.case_insensitive).build() {
+                        Ok(re) => {
+                            self.last_results.retain(|r| re.is_match(&r.content) || re.is_match(&r.file));
+                            print_current_set(&self.last_results, self.last_query.as_deref().unwrap_or(""));
+                            Outcome::Results(self.last_results.len())
+                        }
+                        Err(e) => {
+                            println!("{}", format!("Invalid pattern: {e}").red());
+                            Outcome::Ignored
+                        }
+                    }
                 }
             }
             "exclude" => {
                 if parts.len() > 1 {
                     let new_excl: Vec<String> = parts[1..].iter().map(|s| s.to_string()).collect();
-                    current_exclude = Some(new_excl.clone());
+                    settings.exclude = Some(new_excl.clone());
                     println!(
                         "{}",
                         format!("Exclude directories: {}", new_excl.join(", ")).green()
                     );
+                    Outcome::Done
                 } else {
-                    match &current_exclude {
+                    match &settings.exclude {
                         Some(excl) => {
                             println!("Excluded directories: {}", excl.join(", ").yellow())
                         }
                         None => println!("No excluded directories"),
                     }
+                    Outcome::Ignored
                 }
             }
             "clear" | "cls" => {
                 print!("\x1B[2J\x1B[1;1H");
                 io::stdout().flush()?;
+                Outcome::Ignored
             }
             "status" | "settings" => {
                 println!("{}", "Current Settings:".cyan().bold());
-                println!(
-                    "  Fuzzy mode:       {}",
-                    if fuzzy_mode {
-                        "ON".green()
-                    } else {
-                        "OFF".dimmed()
-                    }
-                );
-                println!(
-                    "  Case insensitive: {}",
-                    if case_insensitive {
-                        "ON".green()
-                    } else {
-                        "OFF".dimmed()
+                println!("  Fuzzy mode:       {}", on_off(settings.fuzzy));
+                println!("  Case insensitive: {}", on_off(settings.case_insensitive));
+                println!("  Ranking mode:     {}", on_off(settings.ranking));
+                println!("  Semantic search:  {}", on_off(settings.semantic));
+                match &settings.extensions {
+                    Some(exts) => println!("  Extensions:       {}", exts.join(", ").blue()),
+                    None => println!("  Extensions:       {}", "all".blue()),
+                }
+                println!("  Recorded steps:   {}", self.steps.len().to_string().blue());
+                Outcome::Ignored
+            }
+            "history" => {
+                if parts.get(1) == Some(&"clear") {
+                    self.history.clear();
+                    self.history_cleared = true;
+                    if let Some(path) = &self.state_path {
+                        match fs::remove_file(path) {
+                            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e.into()),
+                            _ => {}
+                        }
                     }
-                );
-                println!(
-                    "  Ranking mode:     {}",
-                    if ranking_mode {
-                        "ON".green()
-                    } else {
-                        "OFF".dimmed()
+                    println!("{}", "History cleared.".green());
+                } else if self.history.is_empty() {
+                    println!("{}", "No history yet.".dimmed());
+                } else {
+                    for (i, query) in self.history.iter().enumerate() {
+                        println!("  {} {query}", format!("{:>3}", i + 1).dimmed());
                     }
-                );
-                println!(
-                    "  Semantic search:  {}",
-                    if semantic_mode {
-                        "ON".green()
-                    } else {
-                        "OFF".dimmed()
+                }
+                Outcome::Ignored
+            }
+            "save-session" => {
+                match parts.get(1) {
+                    Some(file) => {
+                        fs::write(file, self.to_script())?;
+                        println!("{}", format!("{} step(s) saved to: {file}", self.steps.len()).green());
                     }
-                );
-                match &current_extensions {
-                    Some(exts) => println!("  Extensions:       {}", exts.join(", ").blue()),
-                    None => println!("  Extensions:       {}", "all".blue()),
+                    None => println!("{}", "Usage: save-session <file>".dimmed()),
                 }
+                Outcome::Ignored
             }
             "export" => {
                 if parts.len() > 1 {
                     let export_path = parts[1];
-                    if last_results.is_empty() {
-                        println!("{}", "No results to export. Run a search first.".dimmed());
+                    let selected = if parts.len() > 2 {
+                        parse_selection(&parts[2..], self.last_results.len())
+                            .map(|indices| indices.iter().map(|&i| self.last_results[i].clone()).collect())
                     } else {
-                        let query = last_query.as_deref().unwrap_or("");
-                        export::export_results(&last_results, export_path, query)?;
+                        Ok(self.last_results.clone())
+                    };
+                    match selected {
+                        Ok(selected) if selected.is_empty() => {
+                            println!("{}", "No results to export. Run a search first.".dimmed());
+                            Outcome::Ignored
+                        }
+                        Ok(selected) => {
+                            let query = self.last_query.as_deref().unwrap_or("");
+                            export::export_results(&selected, export_path, query)?;
+                            println!(
+                                "{}",
+                                format!("{} result(s) exported to: {}", selected.len(), export_path).green()
+                            );
+                            Outcome::Done
+                        }
+                        Err(e) => {
+                            println!("{}", e.red());
+                            Outcome::Ignored
+                        }
+                    }
+                } else {
+                    println!("{}", "Usage: export <filename.csv|.md> [1,3,7-12]".dimmed());
+                    Outcome::Ignored
+                }
+            }
+            "mark" => {
+                match parts.get(1).and_then(|n| n.parse::<usize>().ok()) {
+                    Some(n) if (1..=self.last_results.len()).contains(&n) => {
+                        let result = &self.last_results[n - 1];
+                        let label = parts[2..].join(" ");
+                        let mut store = bookmarks::BookmarkStore::load_default()?;
+                        store.add(&result.file, result.line_number, &label)?;
+                        store.save()?;
                         println!(
                             "{}",
-                            format!("Results exported to: {}", export_path).green()
+                            format!("Bookmarked {}:{}", result.file, result.line_number).green()
                         );
+                        Outcome::Done
                     }
-                } else {
-                    println!("{}", "Usage: export <filename.csv|.md>".dimmed());
+                    _ => {
+                        println!(
+                            "{}",
+                            "Usage: mark <n> [label]  (n = result number from the last search)".dimmed()
+                        );
+                        Outcome::Ignored
+                    }
+                }
+            }
+            "copy" | "yank" => {
+                match clipboard::parse_copy_args(&parts[1..]) {
+                    Some(selection) => match clipboard::format_copy_text(&self.last_results, selection) {
+                        Some(text) => match clipboard::copy_to_clipboard(&text) {
+                            Ok(()) => println!("{} {}", "Copied to clipboard:".green(), text),
+                            Err(e) => {
+                                println!("{}", format!("Clipboard unavailable ({e}); copy the text below:").yellow());
+                                println!("{text}");
+                            }
+                        },
+                        None => println!(
+                            "{}",
+                            format!("No result to copy (last search returned {}).", self.last_results.len()).dimmed()
+                        ),
+                    },
+                    None => println!(
+                        "{}",
+                        "Usage: copy <n> | copy <n> line | copy all  (n = result number from the last search)".dimmed()
+                    ),
                 }
+                // Copying doesn't change the session
+                Outcome::Ignored
             }
             "analyze" => {
                 analysis::analyze_codebase(
                     path,
-                    current_extensions.as_deref(),
-                    current_exclude.as_deref(),
+                    settings.extensions.as_deref(),
+                    settings.exclude.as_deref(),
                 )?;
+                Outcome::Done
             }
             "complexity" => {
                 complexity::analyze_complexity(
                     path,
-                    current_extensions.as_deref(),
-                    current_exclude.as_deref(),
+                    settings.extensions.as_deref(),
+                    settings.exclude.as_deref(),
                     None,
                     true,
                 )?;
+                Outcome::Done
             }
             "duplicates" | "dups" => {
                 duplicates::detect_duplicates(
                     path,
-                    current_extensions.as_deref(),
-                    current_exclude.as_deref(),
+                    settings.extensions.as_deref(),
+                    settings.exclude.as_deref(),
                     3,
                     0.9,
                 )?;
+                Outcome::Done
             }
             "deadcode" | "dead" => {
                 deadcode::detect_dead_code(
                     path,
-                    current_extensions.as_deref(),
-                    current_exclude.as_deref(),
+                    settings.extensions.as_deref(),
+                    settings.exclude.as_deref(),
+                    &deadcode::DeadCodeOptions::default(),
                 )?;
+                Outcome::Done
             }
             "circular" | "cycle" | "cycles" => {
                 circular::detect_circular_calls(
                     path,
-                    current_extensions.as_deref(),
-                    current_exclude.as_deref(),
+                    settings.extensions.as_deref(),
+                    settings.exclude.as_deref(),
                 )?;
+                Outcome::Done
             }
             "languages" | "langs" => {
                 analysis::list_supported_languages()?;
+                Outcome::Ignored
             }
             // Default: perform search
             _ => {
                 let query = input;
-                last_query = Some(query.to_string());
-
-                let options = SearchOptions {
-                    extensions: current_extensions.clone(),
-                    ignore_case: case_insensitive,
-                    fuzzy: fuzzy_mode,
-                    fuzzy_threshold: 0.6,
-                    max_results: 20,
-                    exclude: current_exclude.clone(),
-                    rank: ranking_mode,
-                    cache: false,
-                    semantic: semantic_mode,
-                    benchmark: false,
-                    vs_grep: false,
-                };
-                let results = search_code(query, path, &options)?;
-
-                last_results = results.clone();
-
-                if results.is_empty() {
-                    println!("{}", "No matches found.".dimmed());
-                } else {
-                    print_results(&results, true, ranking_mode);
-                    print_search_stats(&results, query);
-                }
+                self.last_query = Some(query.to_string());
+                push_history(&mut self.history, query);
+                let count = self.search(query)?;
                 println!();
+                Outcome::Results(count)
             }
-        }
+        };
+        Ok(outcome)
     }
+}
 
-    Ok(())
+fn on_off(on: bool) -> ColoredString {
+    if on {
+        "ON".green()
+    } else {
+        "OFF".dimmed()
+    }
+}
+
+/// The commands of a session script, in order
+///
+/// Blank lines and `#` comments are skipped, and one leading `\` is dropped
+/// (it protects commands that start with `#` or `\`).
+pub fn parse_session_script(text: &str) -> Vec<String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| line.strip_prefix('\\').unwrap_or(line).to_string())
+        .collect()
 }
 
 fn print_help() {
-    println!("{}", "📖 Interactive Search Commands".cyan().bold());
-    println!("{}", "─".repeat(35).cyan());
+    println!("{}", theme::heading(Glyph::Help, "Interactive Search Commands").cyan().bold());
+    println!("{}", theme::rule(35).cyan());
     println!();
     println!("{}", "Search:".yellow().bold());
     println!("  <pattern>  - Search for text pattern");
     println!("  !!         - Repeat last search");
+    println!("  history    - List earlier searches (kept between sessions)");
+    println!("  history clear - Forget them");
     println!();
-    println!("{}", "⚡ Toggles:".yellow().bold());
+    println!("{}", theme::heading(Glyph::Toggles, "Toggles:").yellow().bold());
     println!("  /f         - Toggle fuzzy search");
     println!("  /i         - Toggle case insensitivity");
     println!("  /r         - Toggle relevance ranking");
@@ -328,8 +836,9 @@ fn print_help() {
     println!();
     println!("{}", "Config:".yellow().bold());
     println!("  ext <e1 e2>    - Set file extensions");
-    println!("  exclude <d1>   - Set exclude directories");
+    println!("  exclude <d1>   - Set exclude directories (numbers drop results instead)");
     println!("  status         - Show current settings");
+    println!("  save-session <file> - Save this session's commands as a script for --replay");
     println!();
     println!("{}", "Analysis:".yellow().bold());
     println!("  analyze    - Codebase metrics");
@@ -339,11 +848,333 @@ fn print_help() {
     println!("  circular   - Circular call detection");
     println!("  languages  - Supported languages");
     println!();
+    println!("{}", "Results:".yellow().bold());
+    println!("  export <file> [sel]  - Export results, or only a selection like 1,3,7-12");
+    println!("  exclude <n> [n..]    - Drop results by number (e.g. exclude 4 5, exclude 2-6)");
+    println!("  keep <pattern>       - Keep only results whose line or path matches a regex");
+    println!();
     println!("{}", "Other:".yellow().bold());
-    println!("  export     - Export results");
+    println!("  mark <n> [label] - Bookmark the n-th result of the last search");
+    println!("  copy <n>         - Copy file:line of the n-th result to the clipboard");
+    println!("  copy <n> line    - Copy the n-th result's line content");
+    println!("  copy all         - Copy file:line of every result");
     println!("  clear      - Clear screen");
     println!("  help       - This help");
     println!("  quit       - Exit");
     println!();
 }
 
+/// Print the current result set numbered from 1, followed by its statistics
+fn print_current_set(results: &[SearchResult], query: &str) {
+    if results.is_empty() {
+        println!("{}", "No results left.".dimmed());
+        return;
+    }
+    for (i, r) in results.iter().enumerate() {
+        println!(
+            "  {} {}:{} {}",
+            format!("[{}]", i + 1).blue(),
+            r.file.green(),
+            r.line_number.to_string().yellow(),
+            r.content.trim()
+        );
+    }
+    print_search_stats(results, query);
+}
+
+/// Whether every argument looks like a result selection (`3`, `7-12`, `1,3`)
+pub fn is_selection(args: &[&str]) -> bool {
+    args.iter()
+        .flat_map(|a| a.split(','))
+        .filter(|t| !t.is_empty())
+        .all(|t| t.chars().all(|c| c.is_ascii_digit() || c == '-') && t.chars().any(|c| c.is_ascii_digit()))
+}
+
+/// Parse a 1-based result selection into sorted, de-duplicated 0-based indices
+///
+/// Arguments may be comma- or space-separated numbers and inclusive ranges
+/// (`1,3 7-12`). Unparsable tokens, reversed ranges and numbers outside
+/// `1..=len` are errors, listing every offending token.
+pub fn parse_selection(args: &[&str], len: usize) -> Result<Vec<usize>, String> {
+    let mut indices = Vec::new();
+    let mut invalid = Vec::new();
+    let mut out_of_range = Vec::new();
+
+    for token in args.iter().flat_map(|a| a.split(',')).map(str::trim).filter(|t| !t.is_empty()) {
+        let range = match token.split_once('-') {
+            Some((start, end)) => start.parse::<usize>().ok().zip(end.parse::<usize>().ok()),
+            None => token.parse::<usize>().ok().map(|n| (n, n)),
+        };
+        match range {
+            Some((start, end)) if start <= end => {
+                if start == 0 || end > len {
+                    out_of_range.push(token.to_string());
+                } else {
+                    indices.extend(start - 1..end);
+                }
+            }
+            _ => invalid.push(token.to_string()),
+        }
+    }
+
+    if !invalid.is_empty() {
+        return Err(format!("Invalid selection: {} (use numbers and ranges like 1,3,7-12)", invalid.join(", ")));
+    }
+    if !out_of_range.is_empty() {
+        return Err(format!(
+            "Out of range: {} (the last results are numbered 1-{len})",
+            out_of_range.join(", ")
+        ));
+    }
+    if indices.is_empty() {
+        return Err("Empty selection".to_string());
+    }
+    indices.sort_unstable();
+    indices.dedup();
+    Ok(indices)
+}
+
+/// The results not at the given 0-based indices, in their original order
+pub fn without_indices(results: &[SearchResult], indices: &[usize]) -> Vec<SearchResult> {
+    results
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| indices.binary_search(i).is_err())
+        .map(|(_, r)| r.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(line_number: usize) -> SearchResult {
+        SearchResult {
+            file: "a.rs".to_string(),
+            line_number,
+            end_line_number: None,
+            content: String::new(),
+            matches: Vec::new(),
+            score: 0.0,
+            relevance: String::new(),
+            context_before: Vec::new(),
+            context_after: Vec::new(),
+            line_truncated: false,
+            content_offset: None,
+            annotations: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_parse_selection_numbers_and_ranges() {
+        assert_eq!(parse_selection(&["1,3,7-9"], 12), Ok(vec![0, 2, 6, 7, 8]));
+        assert_eq!(parse_selection(&["4", "5"], 5), Ok(vec![3, 4]));
+        assert_eq!(parse_selection(&["2-2"], 2), Ok(vec![1]));
+    }
+
+    #[test]
+    fn test_parse_selection_dedupes_and_sorts() {
+        assert_eq!(parse_selection(&["5,1-3", "2", "3,1"], 5), Ok(vec![0, 1, 2, 4]));
+    }
+
+    #[test]
+    fn test_parse_selection_out_of_bounds() {
+        let err = parse_selection(&["0,2,6-8"], 5).unwrap_err();
+        assert!(err.contains("0"), "{err}");
+        assert!(err.contains("6-8"), "{err}");
+        assert!(!err.contains(" 2"), "{err}");
+        assert!(parse_selection(&["1"], 0).is_err());
+    }
+
+    #[test]
+    fn test_parse_selection_invalid_tokens() {
+        let err = parse_selection(&["1,x,5-3,2-"], 10).unwrap_err();
+        assert!(err.starts_with("Invalid selection"), "{err}");
+        assert!(err.contains("x") && err.contains("5-3") && err.contains("2-"), "{err}");
+        assert!(parse_selection(&[","], 10).is_err());
+    }
+
+    #[test]
+    fn test_is_selection() {
+        assert!(is_selection(&["4", "5"]));
+        assert!(is_selection(&["1,3,7-12"]));
+        assert!(!is_selection(&["target", "node_modules"]));
+        assert!(!is_selection(&["build-2"]));
+        assert!(!is_selection(&["-"]));
+    }
+
+    #[test]
+    fn test_without_indices_keeps_order() {
+        let results: Vec<SearchResult> = (1..=5).map(result).collect();
+        let kept = without_indices(&results, &[1, 3]);
+        let lines: Vec<usize> = kept.iter().map(|r| r.line_number).collect();
+        assert_eq!(lines, vec![1, 3, 5]);
+    }
+
+    fn workspace() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("lib.rs"), "fn parse() {}\nfn Parse_all() {}\n// parse later\n").unwrap();
+        std::fs::write(dir.path().join("notes.py"), "def parse():\n    pass\n").unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_session_records_only_replayable_commands() {
+        let dir = workspace();
+        let mut session = Session::new(dir.path(), None, None);
+        for command in ["help", "ext rs", "status", "/i", "keep", "parse", "exclude 9", "quit"] {
+            session.execute(command).unwrap();
+        }
+        let commands: Vec<&str> = session.steps.iter().map(|s| s.command.as_str()).collect();
+        assert_eq!(commands, vec!["ext rs", "/i", "parse"]);
+        assert_eq!(session.steps[2].results, Some(2));
+    }
+
+    #[test]
+    fn test_session_script_roundtrip() {
+        let dir = workspace();
+        let mut session = Session::new(dir.path(), None, None);
+        for command in ["ext rs", "/i", "parse", "/i", "parse", "keep fn", "exclude 1", "#include"] {
+            session.execute(command).unwrap();
+        }
+        let script = session.to_script();
+        assert!(script.contains("# step 6, "), "{script}");
+        assert!(script.contains("\n\\#include\n"), "{script}");
+
+        let steps = parse_session_script(&script);
+        assert_eq!(steps.len(), 8);
+        assert_eq!(steps[7], "#include");
+
+        let mut replayed = Session::new(dir.path(), None, None);
+        assert!(replayed.replay(&steps, false).unwrap());
+        assert_eq!(replayed.settings, session.settings);
+        assert_eq!(replayed.last_query, session.last_query);
+        assert_eq!(replayed.last_results.len(), session.last_results.len());
+        let counts = |s: &Session| s.steps.iter().map(|step| step.results).collect::<Vec<_>>();
+        assert_eq!(counts(&replayed), counts(&session));
+
+        // Stopping after the fifth step leaves the case-insensitive search in place
+        let mut partial = Session::new(dir.path(), None, None);
+        partial.replay(&steps[..5], false).unwrap();
+        assert_eq!(partial.last_results.len(), 3);
+        assert!(partial.settings.case_insensitive);
+    }
+
+    #[test]
+    fn test_push_history_skips_repeats_and_keeps_the_newest() {
+        let mut history = Vec::new();
+        for query in ["a", "a", "b", "a", "a"] {
+            push_history(&mut history, query);
+        }
+        assert_eq!(history, vec!["a", "b", "a"]);
+
+        for i in 0..HISTORY_LIMIT + 5 {
+            push_history(&mut history, &format!("q{i}"));
+        }
+        assert_eq!(history.len(), HISTORY_LIMIT);
+        assert_eq!(history[0], "q5");
+        assert_eq!(history.last().map(String::as_str), Some(format!("q{}", HISTORY_LIMIT + 4).as_str()));
+    }
+
+    #[test]
+    fn test_state_round_trips_through_the_home_directory() {
+        let dir = workspace();
+        let home = tempfile::tempdir().unwrap();
+        let state_path = home.path().join(STATE_FILE);
+
+        let mut session = Session::with_state(dir.path(), None, None, state_path.clone());
+        for command in ["/f", "/r", "ext rs py", "exclude target", "parse", "parse", "fn"] {
+            session.execute(command).unwrap();
+        }
+        session.persist();
+        assert!(state_path.exists());
+        // Only the renamed file is left behind
+        assert_eq!(fs::read_dir(state_path.parent().unwrap()).unwrap().count(), 1);
+
+        let resumed = Session::with_state(dir.path(), None, None, state_path.clone());
+        assert_eq!(resumed.settings, session.settings);
+        assert!(resumed.settings.fuzzy && resumed.settings.ranking);
+        assert_eq!(resumed.history, vec!["parse", "fn"]);
+        assert_eq!(resumed.last_query.as_deref(), Some("fn"));
+
+        // Extensions on the command line win over the saved ones
+        let rs = vec!["rs".to_string()];
+        let overridden = Session::with_state(dir.path(), Some(&rs), None, state_path.clone());
+        assert_eq!(overridden.settings.extensions, Some(rs));
+        assert_eq!(overridden.settings.exclude, Some(vec!["target".to_string()]));
+    }
+
+    #[test]
+    fn test_history_clear_removes_the_state_file() {
+        let dir = workspace();
+        let home = tempfile::tempdir().unwrap();
+        let state_path = home.path().join(STATE_FILE);
+        let mut session = Session::with_state(dir.path(), None, None, state_path.clone());
+        session.execute("parse").unwrap();
+        session.persist();
+
+        assert_eq!(session.execute("history clear").unwrap(), Outcome::Ignored);
+        assert!(session.history.is_empty());
+        assert!(!state_path.exists());
+        // Clearing again, with no file, is fine
+        session.execute("history clear").unwrap();
+    }
+
+    #[test]
+    fn test_history_clear_empties_the_editor_history() {
+        let dir = workspace();
+        let mut session = Session::new(dir.path(), None, None);
+        let mut history = DefaultHistory::new();
+        run_editor_line(&mut session, &mut history, "parse").unwrap();
+        run_editor_line(&mut session, &mut history, "status").unwrap();
+        assert_eq!(history.len(), 2);
+
+        run_editor_line(&mut session, &mut history, "history clear").unwrap();
+        assert!(history.is_empty());
+        assert!(session.history.is_empty());
+
+        // Lines after the clear are remembered again
+        run_editor_line(&mut session, &mut history, "parse").unwrap();
+        assert_eq!(history.len(), 1);
+    }
+
+    #[test]
+    fn test_state_load_tolerates_missing_and_corrupt_files() {
+        let home = tempfile::tempdir().unwrap();
+        let path = home.path().join(STATE_FILE);
+        assert_eq!(InteractiveState::load(&path), InteractiveState::default());
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "{ not json").unwrap();
+        assert_eq!(InteractiveState::load(&path), InteractiveState::default());
+        // Fields missing from an older file take their defaults
+        fs::write(&path, r#"{"history": ["x"], "settings": {"fuzzy": true}}"#).unwrap();
+        let state = InteractiveState::load(&path);
+        assert_eq!(state.history, vec!["x"]);
+        assert!(state.settings.fuzzy && state.settings.case_insensitive);
+    }
+
+    #[test]
+    fn test_completion_offers_commands_then_paths() {
+        let helper = CommandHelper::default();
+        let names = |(start, pairs): (usize, Vec<Pair>)| {
+            (start, pairs.into_iter().map(|pair| pair.replacement).collect::<Vec<_>>())
+        };
+
+        assert_eq!(names(helper.complete_line("ex", 2).unwrap()), (0, vec![
+            "exclude ".to_string(),
+            "exit ".to_string(),
+            "export ".to_string(),
+            "ext ".to_string(),
+        ]));
+        assert_eq!(names(helper.complete_line("  sav", 5).unwrap()), (2, vec!["save-session ".to_string()]));
+        // Searches and other arguments aren't completed
+        assert_eq!(names(helper.complete_line("keep fo", 7).unwrap()), (7, Vec::new()));
+
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("report.md"), "").unwrap();
+        let line = format!("export {}/rep", dir.path().display());
+        let (start, pairs) = names(helper.complete_line(&line, line.len()).unwrap());
+        assert_eq!(start, "export ".len());
+        assert_eq!(pairs, vec![format!("{}/report.md", dir.path().display())]);
+    }
+}
@@ -2,7 +2,24 @@
 //!
 //! Comprehensive list of 48+ supported programming languages with their patterns.
 
-use super::types::LanguageInfo;
+use super::types::{ComplexityThresholds, LanguageInfo};
+
+// Per-function complexity limits, by how much branching a language's code usually carries
+
+/// Statically typed general-purpose languages
+const COMPILED: ComplexityThresholds = ComplexityThresholds::new(10, 15, 60);
+/// Dynamic languages, where dispatch on values often replaces types
+const DYNAMIC: ComplexityThresholds = ComplexityThresholds::new(12, 18, 50);
+/// Shell scripts and build recipes
+const SCRIPT: ComplexityThresholds = ComplexityThresholds::new(8, 12, 40);
+/// Functional languages, which favor small functions and pattern matching
+const FUNCTIONAL: ComplexityThresholds = ComplexityThresholds::new(8, 12, 30);
+/// Query languages: long statements are normal, branching is not
+const QUERY: ComplexityThresholds = ComplexityThresholds::new(5, 8, 80);
+/// Configuration, markup and schemas, which should hardly branch at all
+const DECLARATIVE: ComplexityThresholds = ComplexityThresholds::new(3, 5, 200);
+/// Assembly, where routines are long and jumps are spelled out
+const LOW_LEVEL: ComplexityThresholds = ComplexityThresholds::new(10, 15, 120);
 
 /// All supported languages with their patterns
 pub fn get_supported_languages() -> Vec<LanguageInfo> {
@@ -15,6 +32,7 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
             class_patterns: &[r"struct\s+\w+", r"enum\s+\w+", r"trait\s+\w+", r"impl\s+\w+"],
             comment_patterns: &[r"//", r"/\*", r"///", r"//!"],
             import_patterns: &[r"use\s+", r"mod\s+", r"extern\s+crate"],
+            default_thresholds: COMPILED,
         },
         // Python
         LanguageInfo {
@@ -24,6 +42,7 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
             class_patterns: &[r"class\s+\w+"],
             comment_patterns: &[r"#", r"'''", r#"""""#],
             import_patterns: &[r"import\s+", r"from\s+\w+\s+import"],
+            default_thresholds: DYNAMIC,
         },
         // JavaScript
         LanguageInfo {
@@ -33,6 +52,7 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
             class_patterns: &[r"class\s+\w+"],
             comment_patterns: &[r"//", r"/\*"],
             import_patterns: &[r"import\s+", r"require\s*\(", r"export\s+"],
+            default_thresholds: DYNAMIC,
         },
         // TypeScript
         LanguageInfo {
@@ -42,6 +62,7 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
             class_patterns: &[r"class\s+\w+", r"interface\s+\w+", r"type\s+\w+\s*="],
             comment_patterns: &[r"//", r"/\*"],
             import_patterns: &[r"import\s+", r"export\s+"],
+            default_thresholds: DYNAMIC,
         },
         // Go
         LanguageInfo {
@@ -51,6 +72,7 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
             class_patterns: &[r"type\s+\w+\s+struct", r"type\s+\w+\s+interface"],
             comment_patterns: &[r"//", r"/\*"],
             import_patterns: &[r"import\s+", r"package\s+"],
+            default_thresholds: COMPILED,
         },
         // Java
         LanguageInfo {
@@ -60,6 +82,7 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
             class_patterns: &[r"class\s+\w+", r"interface\s+\w+", r"enum\s+\w+", r"@interface\s+\w+"],
             comment_patterns: &[r"//", r"/\*", r"/\*\*"],
             import_patterns: &[r"import\s+", r"package\s+"],
+            default_thresholds: COMPILED,
         },
         // Kotlin
         LanguageInfo {
@@ -69,6 +92,7 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
             class_patterns: &[r"class\s+\w+", r"object\s+\w+", r"interface\s+\w+", r"data\s+class\s+\w+", r"sealed\s+class\s+\w+"],
             comment_patterns: &[r"//", r"/\*"],
             import_patterns: &[r"import\s+", r"package\s+"],
+            default_thresholds: COMPILED,
         },
         // Swift
         LanguageInfo {
@@ -78,6 +102,7 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
             class_patterns: &[r"class\s+\w+", r"struct\s+\w+", r"enum\s+\w+", r"protocol\s+\w+", r"extension\s+\w+"],
             comment_patterns: &[r"//", r"/\*"],
             import_patterns: &[r"import\s+"],
+            default_thresholds: COMPILED,
         },
         // C
         LanguageInfo {
@@ -87,6 +112,7 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
             class_patterns: &[r"struct\s+\w+", r"enum\s+\w+", r"typedef\s+"],
             comment_patterns: &[r"//", r"/\*"],
             import_patterns: &[r"#include\s+", r"#define\s+"],
+            default_thresholds: COMPILED,
         },
         // C++
         LanguageInfo {
@@ -96,6 +122,7 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
             class_patterns: &[r"class\s+\w+", r"struct\s+\w+", r"enum\s+(class\s+)?\w+", r"namespace\s+\w+"],
             comment_patterns: &[r"//", r"/\*"],
             import_patterns: &[r"#include\s+", r"using\s+namespace", r"using\s+\w+"],
+            default_thresholds: COMPILED,
         },
         // C#
         LanguageInfo {
@@ -105,6 +132,7 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
             class_patterns: &[r"class\s+\w+", r"interface\s+\w+", r"struct\s+\w+", r"enum\s+\w+", r"record\s+\w+"],
             comment_patterns: &[r"//", r"/\*", r"///"],
             import_patterns: &[r"using\s+", r"namespace\s+"],
+            default_thresholds: COMPILED,
         },
         // PHP
         LanguageInfo {
@@ -114,6 +142,7 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
             class_patterns: &[r"class\s+\w+", r"interface\s+\w+", r"trait\s+\w+"],
             comment_patterns: &[r"//", r"#", r"/\*"],
             import_patterns: &[r"use\s+", r"namespace\s+", r"require", r"include"],
+            default_thresholds: DYNAMIC,
         },
         // Ruby
         LanguageInfo {
@@ -123,6 +152,7 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
             class_patterns: &[r"class\s+\w+", r"module\s+\w+"],
             comment_patterns: &[r"#", r"=begin"],
             import_patterns: &[r"require\s+", r"require_relative\s+", r"include\s+"],
+            default_thresholds: DYNAMIC,
         },
         // Scala
         LanguageInfo {
@@ -132,6 +162,7 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
             class_patterns: &[r"class\s+\w+", r"object\s+\w+", r"trait\s+\w+", r"case\s+class\s+\w+"],
             comment_patterns: &[r"//", r"/\*"],
             import_patterns: &[r"import\s+", r"package\s+"],
+            default_thresholds: COMPILED,
         },
         // Perl
         LanguageInfo {
@@ -141,6 +172,7 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
             class_patterns: &[r"package\s+\w+"],
             comment_patterns: &[r"#", r"=pod"],
             import_patterns: &[r"use\s+", r"require\s+"],
+            default_thresholds: DYNAMIC,
         },
         // Lua
         LanguageInfo {
@@ -150,6 +182,7 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
             class_patterns: &[],
             comment_patterns: &[r"--", r"--\[\["],
             import_patterns: &[r"require\s*\("],
+            default_thresholds: DYNAMIC,
         },
         // Shell/Bash
         LanguageInfo {
@@ -159,6 +192,7 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
             class_patterns: &[],
             comment_patterns: &[r"#"],
             import_patterns: &[r"source\s+", r"\.\s+"],
+            default_thresholds: SCRIPT,
         },
         // PowerShell
         LanguageInfo {
@@ -168,6 +202,7 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
             class_patterns: &[r"class\s+\w+"],
             comment_patterns: &[r"#", r"<#"],
             import_patterns: &[r"Import-Module", r"using\s+module"],
+            default_thresholds: DYNAMIC,
         },
         // R
         LanguageInfo {
@@ -177,6 +212,7 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
             class_patterns: &[r"setClass\s*\(", r"setRefClass\s*\("],
             comment_patterns: &[r"#"],
             import_patterns: &[r"library\s*\(", r"require\s*\(", r"source\s*\("],
+            default_thresholds: DYNAMIC,
         },
         // Julia
         LanguageInfo {
@@ -186,6 +222,7 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
             class_patterns: &[r"struct\s+\w+", r"mutable\s+struct\s+\w+", r"abstract\s+type\s+\w+"],
             comment_patterns: &[r"#", r"#="],
             import_patterns: &[r"using\s+", r"import\s+", r"include\s*\("],
+            default_thresholds: DYNAMIC,
         },
         // Haskell
         LanguageInfo {
@@ -195,6 +232,7 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
             class_patterns: &[r"data\s+\w+", r"newtype\s+\w+", r"class\s+\w+", r"instance\s+\w+"],
             comment_patterns: &[r"--", r"\{-"],
             import_patterns: &[r"import\s+", r"module\s+"],
+            default_thresholds: FUNCTIONAL,
         },
         // Elixir
         LanguageInfo {
@@ -204,6 +242,7 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
             class_patterns: &[r"defmodule\s+\w+", r"defprotocol\s+\w+", r"defimpl\s+\w+"],
             comment_patterns: &[r"#"],
             import_patterns: &[r"import\s+", r"use\s+", r"require\s+", r"alias\s+"],
+            default_thresholds: FUNCTIONAL,
         },
         // Erlang
         LanguageInfo {
@@ -213,6 +252,7 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
             class_patterns: &[r"-module\s*\("],
             comment_patterns: &[r"%"],
             import_patterns: &[r"-import\s*\(", r"-include"],
+            default_thresholds: FUNCTIONAL,
         },
         // Clojure
         LanguageInfo {
@@ -222,6 +262,7 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
             class_patterns: &[r"\(defrecord\s+\w+", r"\(deftype\s+\w+", r"\(defprotocol\s+\w+"],
             comment_patterns: &[r";"],
             import_patterns: &[r"\(ns\s+", r"\(require\s+", r"\(use\s+", r"\(import\s+"],
+            default_thresholds: FUNCTIONAL,
         },
         // Dart
         LanguageInfo {
@@ -231,6 +272,7 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
             class_patterns: &[r"class\s+\w+", r"mixin\s+\w+", r"extension\s+\w+"],
             comment_patterns: &[r"//", r"/\*", r"///"],
             import_patterns: &[r#"import\s+'"#, r#"export\s+'"#, r"part\s+"],
+            default_thresholds: COMPILED,
         },
         // Objective-C
         LanguageInfo {
@@ -240,6 +282,7 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
             class_patterns: &[r"@interface\s+\w+", r"@implementation\s+\w+", r"@protocol\s+\w+"],
             comment_patterns: &[r"//", r"/\*"],
             import_patterns: &[r"#import\s+", r"#include\s+", r"@import\s+"],
+            default_thresholds: COMPILED,
         },
         // Groovy
         LanguageInfo {
@@ -249,6 +292,7 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
             class_patterns: &[r"class\s+\w+", r"interface\s+\w+", r"trait\s+\w+"],
             comment_patterns: &[r"//", r"/\*"],
             import_patterns: &[r"import\s+", r"package\s+"],
+            default_thresholds: COMPILED,
         },
         // SQL
         LanguageInfo {
@@ -258,6 +302,7 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
             class_patterns: &[r"CREATE\s+TABLE\s+\w+", r"CREATE\s+VIEW\s+\w+", r"CREATE\s+INDEX\s+\w+"],
             comment_patterns: &[r"--", r"/\*"],
             import_patterns: &[],
+            default_thresholds: QUERY,
         },
         // YAML/Config
         LanguageInfo {
@@ -267,6 +312,7 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
             class_patterns: &[],
             comment_patterns: &[r"#"],
             import_patterns: &[],
+            default_thresholds: DECLARATIVE,
         },
         // TOML
         LanguageInfo {
@@ -276,6 +322,7 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
             class_patterns: &[r"\[[\w\.]+\]"],
             comment_patterns: &[r"#"],
             import_patterns: &[],
+            default_thresholds: DECLARATIVE,
         },
         // JSON
         LanguageInfo {
@@ -285,6 +332,7 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
             class_patterns: &[],
             comment_patterns: &[],
             import_patterns: &[],
+            default_thresholds: DECLARATIVE,
         },
         // XML/HTML
         LanguageInfo {
@@ -294,6 +342,7 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
             class_patterns: &[r"<\w+[^>]*>"],
             comment_patterns: &[r"<!--"],
             import_patterns: &[r"<link", r"<script", r"<import"],
+            default_thresholds: DECLARATIVE,
         },
         // CSS/SCSS/LESS
         LanguageInfo {
@@ -303,6 +352,7 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
             class_patterns: &[r"\.\w+\s*\{", r"#\w+\s*\{", r"@media\s+"],
             comment_patterns: &[r"/\*", r"//"],
             import_patterns: &[r"@import\s+", r"@use\s+"],
+            default_thresholds: DECLARATIVE,
         },
         // Markdown
         LanguageInfo {
@@ -312,6 +362,7 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
             class_patterns: &[r"^#+\s+"],
             comment_patterns: &[],
             import_patterns: &[],
+            default_thresholds: DECLARATIVE,
         },
         // Zig
         LanguageInfo {
@@ -321,6 +372,7 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
             class_patterns: &[r"const\s+\w+\s*=\s*struct", r"const\s+\w+\s*=\s*enum"],
             comment_patterns: &[r"//"],
             import_patterns: &[r"@import\s*\("],
+            default_thresholds: COMPILED,
         },
         // V
         LanguageInfo {
@@ -330,6 +382,7 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
             class_patterns: &[r"struct\s+\w+", r"interface\s+\w+"],
             comment_patterns: &[r"//", r"/\*"],
             import_patterns: &[r"import\s+", r"module\s+"],
+            default_thresholds: COMPILED,
         },
         // Nim
         LanguageInfo {
@@ -339,6 +392,7 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
             class_patterns: &[r"type\s+\w+", r"object\s+\w+"],
             comment_patterns: &[r"#", r"#\["],
             import_patterns: &[r"import\s+", r"from\s+\w+\s+import", r"include\s+"],
+            default_thresholds: COMPILED,
         },
         // Crystal
         LanguageInfo {
@@ -348,6 +402,7 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
             class_patterns: &[r"class\s+\w+", r"struct\s+\w+", r"module\s+\w+"],
             comment_patterns: &[r"#"],
             import_patterns: &[r"require\s+"],
+            default_thresholds: COMPILED,
         },
         // OCaml
         LanguageInfo {
@@ -357,6 +412,7 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
             class_patterns: &[r"type\s+\w+", r"module\s+\w+", r"class\s+\w+"],
             comment_patterns: &[r"\(\*"],
             import_patterns: &[r"open\s+", r"include\s+"],
+            default_thresholds: FUNCTIONAL,
         },
         // F#
         LanguageInfo {
@@ -366,6 +422,7 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
             class_patterns: &[r"type\s+\w+", r"module\s+\w+"],
             comment_patterns: &[r"//", r"\(\*"],
             import_patterns: &[r"open\s+", r"#load\s+"],
+            default_thresholds: FUNCTIONAL,
         },
         // Assembly
         LanguageInfo {
@@ -375,6 +432,7 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
             class_patterns: &[r"\.section\s+", r"\.text", r"\.data"],
             comment_patterns: &[r";", r"#", r"//"],
             import_patterns: &[r"\.include\s+", r"%include\s+"],
+            default_thresholds: LOW_LEVEL,
         },
         // Makefile
         LanguageInfo {
@@ -384,6 +442,7 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
             class_patterns: &[],
             comment_patterns: &[r"#"],
             import_patterns: &[r"include\s+"],
+            default_thresholds: SCRIPT,
         },
         // Docker
         LanguageInfo {
@@ -393,6 +452,7 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
             class_patterns: &[r"FROM\s+", r"WORKDIR\s+"],
             comment_patterns: &[r"#"],
             import_patterns: &[r"COPY\s+", r"ADD\s+"],
+            default_thresholds: SCRIPT,
         },
         // Terraform/HCL
         LanguageInfo {
@@ -402,6 +462,7 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
             class_patterns: &[r#"resource\s+""#, r#"module\s+""#, r#"variable\s+""#, r#"output\s+""#],
             comment_patterns: &[r"#", r"//", r"/\*"],
             import_patterns: &[r"source\s*="],
+            default_thresholds: DECLARATIVE,
         },
         // Protocol Buffers
         LanguageInfo {
@@ -411,6 +472,7 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
             class_patterns: &[r"message\s+\w+", r"service\s+\w+", r"enum\s+\w+"],
             comment_patterns: &[r"//", r"/\*"],
             import_patterns: &[r#"import\s+""#],
+            default_thresholds: DECLARATIVE,
         },
         // GraphQL
         LanguageInfo {
@@ -420,6 +482,7 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
             class_patterns: &[r"type\s+\w+", r"interface\s+\w+", r"input\s+\w+", r"enum\s+\w+"],
             comment_patterns: &[r"#"],
             import_patterns: &[],
+            default_thresholds: QUERY,
         },
         // Solidity (Smart Contracts)
         LanguageInfo {
@@ -429,6 +492,7 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
             class_patterns: &[r"contract\s+\w+", r"interface\s+\w+", r"library\s+\w+", r"struct\s+\w+"],
             comment_patterns: &[r"//", r"/\*", r"///"],
             import_patterns: &[r"import\s+"],
+            default_thresholds: COMPILED,
         },
         // WebAssembly Text
         LanguageInfo {
@@ -438,6 +502,7 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
             class_patterns: &[r"\(module", r"\(type\s+\$\w+"],
             comment_patterns: &[r";;"],
             import_patterns: &[r"\(import\s+"],
+            default_thresholds: LOW_LEVEL,
         },
     ]
 }
@@ -0,0 +1,212 @@
+//! Embedded Language Regions
+//!
+//! Finds code of one language written inside a file of another: `<script>` and
+//! `<style>` blocks in HTML, and fenced code blocks in Markdown. Metrics and
+//! analysis attribute those lines to the embedded language instead of the host.
+
+use super::definitions::get_supported_languages;
+use crate::lines::split_lines;
+use regex::Regex;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// A block of code in one language embedded in a file of another
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmbeddedRegion {
+    /// Canonical extension of the embedded language (e.g. `js`, `css`, `py`)
+    pub extension: String,
+    /// 0-based index of the region's first line in the host file
+    pub start_line: usize,
+    /// The region's lines, each terminated by `\n`
+    pub content: String,
+}
+
+impl EmbeddedRegion {
+    /// Number of host lines the region covers
+    pub fn line_count(&self) -> usize {
+        self.content.matches('\n').count()
+    }
+
+    /// Host line indices (0-based) the region covers
+    pub fn lines(&self) -> std::ops::Range<usize> {
+        self.start_line..self.start_line + self.line_count()
+    }
+}
+
+/// Embedded regions of `content`, in file order
+///
+/// Only whole lines between an opening and closing tag or fence are taken, so
+/// a one-line `<script>...</script>` stays with the host.
+pub fn extract_embedded_regions(file_path: &str, content: &str) -> Vec<EmbeddedRegion> {
+    let ext = Path::new(file_path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    match ext.as_str() {
+        "html" | "htm" | "xhtml" => html_regions(content),
+        "md" | "markdown" | "mdown" | "mkd" => markdown_regions(content),
+        _ => Vec::new(),
+    }
+}
+
+/// `content` with the lines of `regions` emptied, for measuring the host language alone
+///
+/// The line count is unchanged, so line numbers still match the original.
+pub fn blank_regions(content: &str, regions: &[EmbeddedRegion]) -> String {
+    let mut host = String::with_capacity(content.len());
+    for (idx, line) in split_lines(content).enumerate() {
+        if !regions.iter().any(|r| r.lines().contains(&idx)) {
+            host.push_str(line);
+        }
+        host.push('\n');
+    }
+    host
+}
+
+fn html_regions(content: &str) -> Vec<EmbeddedRegion> {
+    static OPEN_RE: OnceLock<Regex> = OnceLock::new();
+    static ATTR_RE: OnceLock<Regex> = OnceLock::new();
+    let open_re = OPEN_RE.get_or_init(|| Regex::new(r"(?i)<(script|style)\b([^>]*)>").unwrap());
+    let attr_re = ATTR_RE.get_or_init(|| Regex::new(r#"(?i)\b(type|lang)\s*=\s*["']?([^"'\s>]+)"#).unwrap());
+
+    let mut regions = Vec::new();
+    // Open block: closing tag, language (None for non-code such as JSON or templates), body
+    let mut open: Option<(String, Option<&str>, EmbeddedRegion)> = None;
+    for (idx, line) in split_lines(content).enumerate() {
+        if let Some((close_tag, ext, mut region)) = open.take() {
+            if line.to_lowercase().contains(&close_tag) {
+                if ext.is_some() && !region.content.trim().is_empty() {
+                    regions.push(region);
+                }
+            } else {
+                region.content.push_str(line);
+                region.content.push('\n');
+                open = Some((close_tag, ext, region));
+            }
+            continue;
+        }
+
+        let Some(caps) = open_re.captures(line) else { continue };
+        let tag = caps[1].to_lowercase();
+        let close_tag = format!("</{tag}");
+        let rest = &line[caps.get(0).map_or(line.len(), |m| m.end())..];
+        if rest.to_lowercase().contains(&close_tag) {
+            continue;
+        }
+
+        let mut ext = if tag == "style" { Some("css") } else { Some("js") };
+        for attr in attr_re.captures_iter(&caps[2]) {
+            let value = attr[2].to_lowercase();
+            ext = match value.as_str() {
+                "ts" | "typescript" | "text/typescript" | "application/typescript" => Some("ts"),
+                "module" | "text/javascript" | "application/javascript" | "js" | "javascript" => ext,
+                "css" | "text/css" => Some("css"),
+                _ => None,
+            };
+        }
+        let region = EmbeddedRegion {
+            extension: ext.unwrap_or_default().to_string(),
+            start_line: idx + 1,
+            content: String::new(),
+        };
+        open = Some((close_tag, ext, region));
+    }
+    regions
+}
+
+fn markdown_regions(content: &str) -> Vec<EmbeddedRegion> {
+    let mut regions = Vec::new();
+    // Open fence: its marker, the language if known, and the body so far
+    let mut open: Option<(String, Option<String>, EmbeddedRegion)> = None;
+    for (idx, line) in split_lines(content).enumerate() {
+        let trimmed = line.trim();
+        if let Some((fence, ext, mut region)) = open.take() {
+            if trimmed.starts_with(&fence) && trimmed.trim_start_matches(&fence[..1]).is_empty() {
+                if ext.is_some() && !region.content.trim().is_empty() {
+                    regions.push(region);
+                }
+            } else {
+                region.content.push_str(line);
+                region.content.push('\n');
+                open = Some((fence, ext, region));
+            }
+            continue;
+        }
+
+        let marker = if trimmed.starts_with("```") { '`' } else if trimmed.starts_with("~~~") { '~' } else { continue };
+        let fence: String = trimmed.chars().take_while(|&c| c == marker).collect();
+        let info = trimmed[fence.len()..].trim_matches(|c: char| c.is_whitespace() || c == '{' || c == '}' || c == '.');
+        let ext = info.split([' ', ',']).next().and_then(fence_extension);
+        let region = EmbeddedRegion {
+            extension: ext.clone().unwrap_or_default(),
+            start_line: idx + 1,
+            content: String::new(),
+        };
+        open = Some((fence, ext, region));
+    }
+
+    // An unterminated fence runs to the end of the file
+    if let Some((_, Some(_), region)) = open.filter(|(_, _, region)| !region.content.trim().is_empty()) {
+        regions.push(region);
+    }
+    regions
+}
+
+/// Canonical extension for a fence info string such as `rust`, `py` or `JavaScript`
+fn fence_extension(info: &str) -> Option<String> {
+    let info = info.to_lowercase();
+    let info = match info.as_str() {
+        "" => return None,
+        "shell" | "console" => "sh",
+        "golang" => "go",
+        "c++" => "cpp",
+        "c#" | "csharp" => "cs",
+        other => other,
+    };
+    get_supported_languages()
+        .into_iter()
+        .find(|lang| lang.name.eq_ignore_ascii_case(info) || lang.extensions.iter().any(|e| e.eq_ignore_ascii_case(info)))
+        .and_then(|lang| lang.extensions.first().map(|e| e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_html_script_and_style_blocks() {
+        let content = "<html>\n<style>\nbody { margin: 0; }\n</style>\n<script type=\"module\">\nfunction a() {}\nfunction b() {}\n</SCRIPT>\n</html>\n";
+        let regions = extract_embedded_regions("page.html", content);
+        assert_eq!(regions.len(), 2);
+        assert_eq!((regions[0].extension.as_str(), regions[0].lines()), ("css", 2..3));
+        assert_eq!((regions[1].extension.as_str(), regions[1].lines()), ("js", 5..7));
+        assert_eq!(regions[1].content, "function a() {}\nfunction b() {}\n");
+    }
+
+    #[test]
+    fn test_html_skips_inline_and_non_code_blocks() {
+        let content = "<script src=\"app.js\"></script>\n<script>run()</script>\n<script type=\"application/json\">\n{\"a\": 1}\n</script>\n<script lang=\"ts\">\nlet x: number = 1;\n</script>\n";
+        let regions = extract_embedded_regions("page.htm", content);
+        assert_eq!(regions.len(), 1);
+        assert_eq!((regions[0].extension.as_str(), regions[0].start_line), ("ts", 6));
+    }
+
+    #[test]
+    fn test_markdown_fences() {
+        let content = "# Title\n```rust\nfn main() {}\n```\n\n~~~ Python\ndef f():\n    pass\n~~~\n```\nplain\n```\n````javascript\nconst a = 1;\n";
+        let regions = extract_embedded_regions("README.md", content);
+        let summary: Vec<(&str, std::ops::Range<usize>)> = regions.iter().map(|r| (r.extension.as_str(), r.lines())).collect();
+        assert_eq!(summary, vec![("rs", 2..3), ("py", 6..8), ("js", 13..14)]);
+    }
+
+    #[test]
+    fn test_blank_regions_keeps_line_numbers() {
+        let content = "<p>\n<script>\nlet a = 1;\nlet b = 2;\n</script>\n<p>";
+        let regions = extract_embedded_regions("a.html", content);
+        let host = blank_regions(content, &regions);
+        assert_eq!(host, "<p>\n<script>\n\n\n</script>\n<p>\n");
+        assert_eq!(split_lines(&host).count(), split_lines(content).count());
+        assert!(extract_embedded_regions("a.rs", content).is_empty());
+    }
+}
@@ -5,10 +5,12 @@
 pub mod types;
 pub mod definitions;
 pub mod utilities;
+pub mod injection;
 
-pub use types::LanguageInfo;
+pub use types::{ComplexityThresholds, LanguageInfo};
 pub use definitions::get_supported_languages;
-pub use utilities::{get_language_by_extension, get_all_supported_extensions, get_language_name};
+pub use utilities::{find_language, get_all_supported_extensions, get_language_by_extension, get_language_name, language_extension, language_override, overridden_extensions, set_language_overrides};
+pub use injection::{blank_regions, extract_embedded_regions, EmbeddedRegion};
 
 #[cfg(test)]
 mod tests {
@@ -40,6 +42,21 @@ mod tests {
         assert_eq!(name, "Unknown");
     }
 
+    #[test]
+    fn test_language_overrides_take_precedence() {
+        assert!(set_language_overrides([("zzphp", "PHP"), ("zzpy", "nope")]).is_err());
+        assert_eq!(language_override("zzphp"), None);
+
+        set_language_overrides([(".ZZPHP", "php")]).unwrap();
+        assert_eq!(get_language_by_extension("zzphp").unwrap().name, "PHP");
+        assert_eq!(get_language_name("lib/helpers.zzphp"), "PHP");
+        assert_eq!(language_extension("zzphp"), "php");
+        assert_eq!(language_extension("rs"), "rs");
+        assert_eq!(overridden_extensions("PHP"), vec!["zzphp".to_string()]);
+        set_language_overrides([]).unwrap();
+        assert!(get_language_by_extension("zzphp").is_none());
+    }
+
     #[test]
     fn test_supported_languages_count() {
         let langs = get_supported_languages();
@@ -2,6 +2,8 @@
 //!
 //! Data structures for language metadata.
 
+use serde::{Deserialize, Serialize};
+
 /// Supported programming language with its metadata
 #[derive(Debug, Clone)]
 pub struct LanguageInfo {
@@ -12,4 +14,25 @@ pub struct LanguageInfo {
     pub comment_patterns: &'static [&'static str],
     #[allow(dead_code)]
     pub import_patterns: &'static [&'static str],
+    /// Per-function limits above which the complexity and refactoring reports flag a function
+    pub default_thresholds: ComplexityThresholds,
+}
+
+/// Per-function complexity limits; a function above any of them is flagged
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ComplexityThresholds {
+    pub cyclomatic: u32,
+    pub cognitive: u32,
+    /// Lines from the signature to the closing brace
+    pub function_length: usize,
+}
+
+impl ComplexityThresholds {
+    /// Limits for files in no known language
+    pub const FALLBACK: Self = Self::new(10, 15, 60);
+
+    pub const fn new(cyclomatic: u32, cognitive: u32, function_length: usize) -> Self {
+        Self { cyclomatic, cognitive, function_length }
+    }
 }
@@ -4,16 +4,72 @@
 
 use super::types::LanguageInfo;
 use super::definitions::get_supported_languages;
+use std::collections::BTreeMap;
 use std::path::Path;
+use std::sync::RwLock;
+
+/// Extension (lowercase, without the dot) to language name, from `[language.overrides]`
+static OVERRIDES: RwLock<BTreeMap<String, &'static str>> = RwLock::new(BTreeMap::new());
 
 /// Get language info by file extension
+///
+/// Extensions mapped by [`set_language_overrides`] resolve to their language
+/// before the built-in table is consulted.
 pub fn get_language_by_extension(ext: &str) -> Option<LanguageInfo> {
     let ext_lower = ext.to_lowercase();
+    if let Some(name) = language_override(&ext_lower) {
+        return find_language(name);
+    }
     get_supported_languages()
         .into_iter()
         .find(|lang| lang.extensions.iter().any(|e| e.to_lowercase() == ext_lower))
 }
 
+/// Get a supported language by name, ignoring case
+pub fn find_language(name: &str) -> Option<LanguageInfo> {
+    get_supported_languages().into_iter().find(|lang| lang.name.eq_ignore_ascii_case(name))
+}
+
+/// Map extensions to languages for the rest of the process, replacing earlier overrides
+///
+/// Keys are extensions with or without the leading dot, values language names
+/// as [`find_language`] takes them. Returns the first name that isn't a
+/// supported language, leaving the overrides unchanged.
+pub fn set_language_overrides<'a>(overrides: impl IntoIterator<Item = (&'a str, &'a str)>) -> Result<(), String> {
+    let mut resolved = BTreeMap::new();
+    for (ext, name) in overrides {
+        let language = find_language(name).ok_or_else(|| name.to_string())?;
+        resolved.insert(ext.trim_start_matches('.').to_lowercase(), language.name);
+    }
+    if let Ok(mut current) = OVERRIDES.write() {
+        *current = resolved;
+    }
+    Ok(())
+}
+
+/// The language name `[language.overrides]` gives `ext`, if any
+pub fn language_override(ext: &str) -> Option<&'static str> {
+    let overrides = OVERRIDES.read().ok()?;
+    overrides.get(&ext.to_lowercase()).copied()
+}
+
+/// Extensions `[language.overrides]` maps to the language named `name`, sorted
+pub fn overridden_extensions(name: &str) -> Vec<String> {
+    let Ok(overrides) = OVERRIDES.read() else { return Vec::new() };
+    overrides.iter().filter(|(_, language)| language.eq_ignore_ascii_case(name)).map(|(ext, _)| ext.clone()).collect()
+}
+
+/// The extension language-specific code should treat `ext` as
+///
+/// An overridden extension stands in for its language's first built-in one
+/// (`inc` mapped to PHP reads as `php`); any other is returned unchanged.
+pub fn language_extension(ext: &str) -> &str {
+    match language_override(ext).and_then(find_language) {
+        Some(language) => language.extensions[0],
+        None => ext,
+    }
+}
+
 /// Get all supported file extensions
 #[allow(dead_code)]
 pub fn get_all_supported_extensions() -> Vec<&'static str> {
@@ -0,0 +1,121 @@
+//! Legacy Search API
+//!
+//! The positional `search_code` signature from before [`SearchOptions`], kept
+//! for one release so embedders can migrate at their own pace. Each call is
+//! forwarded to [`crate::search::search_code`]. The first call in a process
+//! logs a warning through the `log` crate; the `compat` feature silences the
+//! compile-time deprecation for one more release.
+
+use crate::types::{SearchOptions, SearchResult};
+use std::path::Path;
+use std::sync::Once;
+
+/// Where the legacy signature's replacement is described
+pub const MIGRATION_NOTES: &str = "https://github.com/yingkitw/codesearch/blob/main/MIGRATION.md";
+
+static NOTICE: Once = Once::new();
+
+/// Search with the pre-[`SearchOptions`] positional arguments
+///
+/// Options that didn't exist then keep their [`SearchOptions::default`] values.
+#[cfg_attr(
+    not(feature = "compat"),
+    deprecated(
+        since = "0.1.6",
+        note = "build a `SearchOptions` and call `codesearch::search_code(query, path, &options)`; see MIGRATION.md"
+    )
+)]
+#[allow(clippy::too_many_arguments)]
+pub fn search_code(
+    query: &str,
+    path: &Path,
+    extensions: Option<&[String]>,
+    ignore_case: bool,
+    fuzzy: bool,
+    fuzzy_threshold: f64,
+    max_results: usize,
+    exclude: Option<&[String]>,
+    rank: bool,
+    cache: bool,
+    semantic: bool,
+    benchmark: bool,
+    vs_grep: bool,
+) -> Result<Vec<SearchResult>, Box<dyn std::error::Error>> {
+    NOTICE.call_once(|| {
+        log::warn!(
+            "codesearch::legacy::search_code is deprecated and will be removed in the next release; \
+             pass a SearchOptions to codesearch::search_code instead ({MIGRATION_NOTES})"
+        );
+    });
+
+    let options = SearchOptions {
+        extensions: extensions.map(<[String]>::to_vec),
+        ignore_case,
+        fuzzy,
+        fuzzy_threshold,
+        max_results,
+        exclude: exclude.map(<[String]>::to_vec),
+        rank,
+        cache,
+        semantic,
+        benchmark,
+        vs_grep,
+        ..SearchOptions::default()
+    };
+    crate::search::search_code(query, path, &options)
+}
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_legacy_matches_options_api() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("lib.rs"), "fn parse_config() {}\nfn Parse() { parse_config(); }\n").unwrap();
+        fs::write(dir.path().join("app.py"), "def parse_config():\n    return PARSE\n").unwrap();
+        fs::create_dir(dir.path().join("vendor")).unwrap();
+        fs::write(dir.path().join("vendor/dep.rs"), "fn parse() {}\n").unwrap();
+
+        let rs = ["rs".to_string()];
+        let vendor = ["vendor".to_string()];
+        for extensions in [None, Some(&rs[..])] {
+            for exclude in [None, Some(&vendor[..])] {
+                for (ignore_case, fuzzy, rank, semantic) in [
+                    (false, false, false, false),
+                    (true, false, false, false),
+                    (false, true, false, false),
+                    (false, false, true, false),
+                    (true, false, true, false),
+                    (false, false, false, true),
+                ] {
+                    for max_results in [1, 10] {
+                        let query = if fuzzy { "prsconfig" } else { "parse" };
+                        let legacy = search_code(query, dir.path(), extensions, ignore_case, fuzzy, 0.5, max_results, exclude, rank, false, semantic, false, false).unwrap();
+
+                        let options = SearchOptions {
+                            extensions: extensions.map(<[String]>::to_vec),
+                            exclude: exclude.map(<[String]>::to_vec),
+                            ignore_case,
+                            fuzzy,
+                            fuzzy_threshold: 0.5,
+                            max_results,
+                            rank,
+                            semantic,
+                            ..SearchOptions::default()
+                        };
+                        let current = crate::search::search_code(query, dir.path(), &options).unwrap();
+
+                        let context = format!("{extensions:?} {exclude:?} case={ignore_case} fuzzy={fuzzy} rank={rank} semantic={semantic} max={max_results}");
+                        assert!(!current.is_empty(), "{context}");
+                        assert_eq!(legacy, current, "{context}");
+                        assert!(NOTICE.is_completed());
+                    }
+                }
+            }
+        }
+    }
+}
@@ -11,14 +11,21 @@
 //! - MCP server integration
 
 pub mod analysis;
+pub mod apidiff;
+pub mod badge;
 pub mod ast;
+pub mod bookmarks;
 pub mod cache;
 pub mod callgraph;
+pub mod callgraph_metrics;
 pub mod cfg;
 pub mod cli;
 pub mod commands;
+pub mod config;
 pub mod codemetrics;
+pub mod cognitive;
 pub mod circular;
+pub mod clipboard;
 #[cfg(test)]
 mod circular_tests;
 pub mod complexity;
@@ -26,42 +33,66 @@ pub mod complexity;
 mod complexity_tests;
 pub mod deadcode;
 pub mod depgraph;
+pub mod deps;
 pub mod designmetrics;
 pub mod dfg;
 pub mod duplicates;
+#[cfg(feature = "embeddings")]
+pub mod embeddings;
+pub mod encoding;
 pub mod cache_lru;
 pub mod errors;
 pub mod export;
+pub mod flag_rules;
 pub mod fs;
 pub mod githistory;
+pub mod gitignore;
 pub mod graphs;
+pub mod hygiene;
 pub mod index;
+pub mod index_archive;
 pub mod interactive;
 pub mod language;
+pub mod legacy;
+pub mod lines;
+pub mod lint;
 #[cfg(feature = "mcp")]
 pub mod mcp;
 pub mod memopt;
+pub mod options;
 pub mod parser;
+pub mod pathglob;
 pub mod pdg;
+pub mod privacy;
+pub mod profiles;
 pub mod remote;
+pub mod report;
+pub mod schema;
 pub mod search;
+pub mod text;
+pub mod theme;
+pub mod timings;
 pub mod traits;
 #[cfg(test)]
 mod search_tests;
 pub mod types;
+pub mod warm;
+pub mod watch_analysis;
 pub mod watcher;
 
 // Re-export commonly used items at the crate root
-pub use search::{list_files, print_results, print_search_stats, search_code};
-pub use types::{ComplexityMetrics, DuplicateBlock, FileInfo, Match, RefactorSuggestion, SearchResult};
-pub use analysis::analyze_codebase;
+pub use search::{list_files, print_results, print_search_stats, search_code, search_code_streaming};
+pub use types::{ComplexityMetrics, DirectoryMatches, DuplicateBlock, FileInfo, FunctionComplexity, Match, MatchHistogram, RefactorSuggestion, SearchOptions, SearchOptionsBuilder, SearchResult, SearchStats};
+pub use analysis::{analyze_codebase, compute_codebase_stats, CodebaseStats, LanguageStats};
 pub use ast::{analyze_file, AstAnalysis, AstParser, FunctionInfo, ClassInfo};
-pub use callgraph::{build_call_graph, CallGraph, CallNode};
+pub use callgraph::{build_call_graph, build_call_graph_in, CallGraph, CallNode};
+pub use callgraph_metrics::{compute_metrics, CallGraphMetrics, FunctionMetrics};
 pub use cfg::{analyze_file_cfg, build_cfg_from_source, ControlFlowGraph, BasicBlock};
-pub use codemetrics::{analyze_file_metrics, analyze_project_metrics, print_metrics_report, FileMetrics, ProjectMetrics};
-pub use complexity::{calculate_file_complexity, calculate_cyclomatic_complexity, calculate_cognitive_complexity};
+pub use codemetrics::{analyze_file_metrics, analyze_project_metrics, analyze_project_metrics_timed, print_metrics_report, FileMetrics, ProjectMetrics, SubLanguageMetrics};
+pub use complexity::{calculate_file_complexity, calculate_cyclomatic_complexity, calculate_cognitive_complexity, complexity_reports, FileComplexityReport};
+pub use cognitive::cognitive_complexity;
 pub use circular::{detect_circular_calls, find_circular_calls, CircularCall};
-pub use deadcode::{detect_dead_code, find_dead_code, DeadCodeItem};
+pub use deadcode::{detect_dead_code, find_dead_code, find_dead_code_in, find_dead_code_reachable_in, DeadCodeItem, DeadCodeOptions};
 pub use depgraph::{build_dependency_graph, DependencyGraph, DependencyNode};
 pub use designmetrics::{analyze_design_metrics, print_design_metrics, DesignMetrics, ModuleMetrics};
 pub use dfg::{analyze_file_dfg, build_dfg_from_source, DataFlowGraph, DfgNode};
@@ -69,8 +100,10 @@ pub use duplicates::{detect_duplicates, find_duplicates};
 pub use githistory::{search_git_history, GitSearcher, GitSearchResult, CommitInfo};
 pub use graphs::{GraphAnalyzer, GraphAnalysisResult, GraphType};
 pub use index::{CodeIndex, IndexEntry, IndexStats};
+pub use index_archive::{pack_index, IndexArchive, PackStats};
 pub use language::{get_supported_languages, LanguageInfo};
 pub use memopt::{FileReader, StreamingSearcher};
+pub use parser::{ExtractionContext, FileExtraction};
 pub use pdg::{analyze_file_pdg, build_pdg_from_source, ProgramDependencyGraph};
 pub use remote::{search_remote_repository, RemoteSearcher, RemoteSearchResult};
 pub use watcher::{start_watching, FileWatcher};
@@ -0,0 +1,175 @@
+//! Line splitting for `\n`, `\r\n` and bare `\r` line endings
+//!
+//! `str::lines` and `BufRead::lines` only know `\n` and `\r\n`, so a file saved
+//! with classic Mac endings comes out as one line, and a stray `\r` before
+//! `\n` (in mixed files) stays in the content. Search and the analysis modules
+//! split and number lines through here instead, so they all agree.
+
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::io::{self, BufRead};
+
+/// Iterator over the lines of a string, without their terminators
+///
+/// A terminator at the very end doesn't start another line, as with `str::lines`.
+#[derive(Debug, Clone)]
+pub struct Lines<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Iterator for Lines<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        if self.rest.is_empty() {
+            return None;
+        }
+        let (line, rest) = match self.rest.find(['\n', '\r']) {
+            Some(end) => {
+                let terminator = if self.rest[end..].starts_with("\r\n") { 2 } else { 1 };
+                (&self.rest[..end], &self.rest[end + terminator..])
+            }
+            None => (self.rest, ""),
+        };
+        self.rest = rest;
+        Some(line)
+    }
+}
+
+/// Split `content` on `\n`, `\r\n` and `\r`
+pub fn split_lines(content: &str) -> Lines<'_> {
+    Lines { rest: content }
+}
+
+/// Number of lines in `content`, as [`split_lines`] counts them
+pub fn line_count(content: &str) -> usize {
+    split_lines(content).count()
+}
+
+/// `content` with every `\r\n` and bare `\r` turned into `\n`
+///
+/// For analyses that match across lines, so a CRLF file gives the same text
+/// (and line numbers) as its LF copy. Borrows when there is nothing to change.
+pub fn normalize_line_endings(content: &str) -> Cow<'_, str> {
+    if !content.contains('\r') {
+        return Cow::Borrowed(content);
+    }
+    Cow::Owned(content.replace("\r\n", "\n").replace('\r', "\n"))
+}
+
+/// Byte offset at which each line of `content` starts
+pub fn line_starts(content: &str) -> Vec<usize> {
+    let mut starts = Vec::new();
+    let mut offset = 0;
+    for line in split_lines(content) {
+        starts.push(offset);
+        offset += line.len();
+        offset += if content[offset..].starts_with("\r\n") { 2 } else { 1 };
+    }
+    starts
+}
+
+/// 1-based number of the line holding byte `offset`
+pub fn line_number_at(content: &str, offset: usize) -> usize {
+    let prefix = content.as_bytes()[..offset.min(content.len())].iter();
+    let mut line = 1;
+    let mut previous = 0u8;
+    for &byte in prefix {
+        // The `\n` of a `\r\n` was already counted at the `\r`
+        if byte == b'\r' || (byte == b'\n' && previous != b'\r') {
+            line += 1;
+        }
+        previous = byte;
+    }
+    line
+}
+
+/// Streaming counterpart of [`split_lines`] for buffered readers
+///
+/// Fails with `InvalidData` on invalid UTF-8, like `BufRead::lines`.
+pub struct LineReader<R> {
+    reader: R,
+    buf: Vec<u8>,
+    pending: VecDeque<String>,
+}
+
+/// Read the lines of `reader`, splitting on `\n`, `\r\n` and `\r`
+pub fn read_lines<R: BufRead>(reader: R) -> LineReader<R> {
+    LineReader {
+        reader,
+        buf: Vec::new(),
+        pending: VecDeque::new(),
+    }
+}
+
+impl<R: BufRead> Iterator for LineReader<R> {
+    type Item = io::Result<String>;
+
+    fn next(&mut self) -> Option<io::Result<String>> {
+        if let Some(line) = self.pending.pop_front() {
+            return Some(Ok(line));
+        }
+
+        // A chunk ends at `\n` or end of input, so a `\r\n` is never split
+        // across chunks; bare `\r` lines are split out of the chunk
+        self.buf.clear();
+        match self.reader.read_until(b'\n', &mut self.buf) {
+            Ok(0) => None,
+            Ok(_) => match std::str::from_utf8(&self.buf) {
+                Ok(chunk) => {
+                    self.pending.extend(split_lines(chunk).map(str::to_string));
+                    self.pending.pop_front().map(Ok)
+                }
+                Err(e) => Some(Err(io::Error::new(io::ErrorKind::InvalidData, e))),
+            },
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_lines_handles_every_ending() {
+        for content in ["a\nb\n\nc", "a\r\nb\r\n\r\nc", "a\rb\r\rc", "a\r\nb\r\n\rc\n"] {
+            assert_eq!(split_lines(content).collect::<Vec<_>>(), vec!["a", "b", "", "c"], "{content:?}");
+            assert_eq!(line_count(content), 4, "{content:?}");
+        }
+        assert_eq!(split_lines("").count(), 0);
+        assert_eq!(split_lines("\n").collect::<Vec<_>>(), vec![""]);
+        assert_eq!(split_lines("a\r").collect::<Vec<_>>(), vec!["a"]);
+    }
+
+    #[test]
+    fn test_normalize_line_endings() {
+        assert_eq!(normalize_line_endings("a\r\nb\rc\n"), "a\nb\nc\n");
+        assert!(matches!(normalize_line_endings("a\nb"), Cow::Borrowed("a\nb")));
+    }
+
+    #[test]
+    fn test_line_starts_and_numbers_agree() {
+        let content = "ab\r\ncd\ref\ngh";
+        let starts = line_starts(content);
+        assert_eq!(starts, vec![0, 4, 7, 10]);
+        for (idx, &start) in starts.iter().enumerate() {
+            assert_eq!(line_number_at(content, start), idx + 1);
+        }
+        assert_eq!(line_number_at(content, 5), 2);
+        assert_eq!(line_number_at(content, 100), 4);
+    }
+
+    #[test]
+    fn test_read_lines_matches_split_lines() {
+        let content = "one\r\ntwo\rthree\r\rfive\nsix\r";
+        let read: Vec<String> = read_lines(content.as_bytes()).collect::<io::Result<_>>().unwrap();
+        assert_eq!(read, split_lines(content).collect::<Vec<_>>());
+        assert_eq!(read, vec!["one", "two", "three", "", "five", "six"]);
+
+        let invalid: &[u8] = b"ok\n\xff\n";
+        let mut lines = read_lines(invalid);
+        assert_eq!(lines.next().unwrap().unwrap(), "ok");
+        assert_eq!(lines.next().unwrap().unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,705 @@
+//! Lint Rules Engine
+//!
+//! `codesearch lint` checks a tree against the rules of a TOML rules file:
+//!
+//! ```toml
+//! [[rule]]
+//! id = "no-dbg"
+//! kind = "regex"
+//! pattern = 'dbg!\('
+//! level = "error"
+//!
+//! [[rule]]
+//! id = "cognitive-25"
+//! kind = "metric"
+//! metric = "cognitive"
+//! max = 25
+//! scope = "function"
+//!
+//! [[rule]]
+//! id = "no-regressions"
+//! kind = "metric_regression"
+//! metric = "cognitive"
+//! max_increase = 2
+//! ```
+//!
+//! Each rule kind has an evaluator, built by the factory a [`RuleRegistry`]
+//! maps the kind to; every evaluator reports [`LintFinding`]s, so all rule
+//! kinds share the text, JSON and SARIF output and the `--fail-on` gate.
+//! Regression rules compare against a [`LintBaseline`] written earlier with
+//! `lint --write-baseline`.
+
+use crate::complexity::{calculate_file_complexity, calculate_function_complexities, function_length};
+use crate::encoding::{read_text, Encoding};
+use crate::lines::split_lines;
+use crate::parser::get_file_extension;
+use crate::search::list_files;
+use crate::theme;
+use crate::types::FunctionComplexity;
+use colored::*;
+use regex::Regex;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Arc, OnceLock};
+
+/// Rules file read by `lint` when `--rules` isn't given
+pub const DEFAULT_RULES_FILE: &str = ".codesearch/lint.toml";
+
+/// How serious a finding is, as in SARIF
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintLevel {
+    Note,
+    #[default]
+    Warning,
+    Error,
+}
+
+impl LintLevel {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Note => "note",
+            Self::Warning => "warning",
+            Self::Error => "error",
+        }
+    }
+}
+
+impl FromStr for LintLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "note" => Ok(Self::Note),
+            "warning" => Ok(Self::Warning),
+            "error" => Ok(Self::Error),
+            other => Err(format!("Unknown level: {other} (expected: note, warning, error)")),
+        }
+    }
+}
+
+/// One `[[rule]]` entry of a rules file
+///
+/// `id`, `kind`, `level` and `description` are common to every kind; the
+/// other keys are the kind's parameters, checked by its evaluator factory.
+#[derive(Debug, Clone, Deserialize)]
+pub struct LintRule {
+    pub id: String,
+    pub kind: String,
+    #[serde(default)]
+    pub level: LintLevel,
+    /// What the rule is for, shown as the SARIF rule description
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(flatten)]
+    pub params: toml::Table,
+}
+
+impl LintRule {
+    /// The kind-specific keys of the rule as `T`
+    pub fn params<T: DeserializeOwned>(&self) -> Result<T, String> {
+        toml::Value::Table(self.params.clone()).try_into().map_err(|e| format!("Rule {}: {e}", self.id))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RulesFile {
+    #[serde(default)]
+    rule: Vec<LintRule>,
+}
+
+/// Read the rules of a TOML rules file
+pub fn load_rules(path: &Path) -> Result<Vec<LintRule>, Box<dyn std::error::Error>> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("Cannot read rules file {}: {e}", path.display()))?;
+    parse_rules(&text).map_err(|e| format!("Invalid rules file {}: {e}", path.display()).into())
+}
+
+fn parse_rules(text: &str) -> Result<Vec<LintRule>, toml::de::Error> {
+    Ok(toml::from_str::<RulesFile>(text)?.rule)
+}
+
+/// A rule violation in a file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LintFinding {
+    pub rule_id: String,
+    pub kind: String,
+    pub level: LintLevel,
+    /// Path relative to the linted directory
+    pub file: String,
+    pub line: usize,
+    pub message: String,
+    /// The rule's description, if it has one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// A violation as reported by an evaluator, before the rule's id and level are attached
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleHit {
+    pub line: usize,
+    pub message: String,
+}
+
+/// Checks one rule against a file
+pub trait RuleEvaluator: Send + Sync {
+    fn evaluate(&self, file: &LintFile) -> Vec<RuleHit>;
+}
+
+/// What evaluator factories may need besides the rule itself
+#[derive(Debug, Clone, Default)]
+pub struct LintSetup {
+    pub baseline: Option<Arc<LintBaseline>>,
+}
+
+/// Builds the evaluator of a rule, or explains why the rule is invalid
+pub type EvaluatorFactory = fn(&LintRule, &LintSetup) -> Result<Box<dyn RuleEvaluator>, String>;
+
+/// Maps rule kinds to the factories building their evaluators
+///
+/// [`RuleRegistry::default`] knows the built-in kinds: `regex`, `metric`
+/// (also `metric_threshold`) and `metric_regression`.
+pub struct RuleRegistry {
+    factories: BTreeMap<String, EvaluatorFactory>,
+}
+
+impl RuleRegistry {
+    /// A registry without any rule kinds
+    pub fn empty() -> Self {
+        Self { factories: BTreeMap::new() }
+    }
+
+    /// Handle rules of `kind` with `factory`, replacing any earlier factory for it
+    pub fn register(&mut self, kind: &str, factory: EvaluatorFactory) {
+        self.factories.insert(kind.to_string(), factory);
+    }
+
+    /// The registered kinds, sorted
+    pub fn kinds(&self) -> impl Iterator<Item = &str> {
+        self.factories.keys().map(String::as_str)
+    }
+
+    /// Build the evaluator of `rule`
+    pub fn build(&self, rule: &LintRule, setup: &LintSetup) -> Result<Box<dyn RuleEvaluator>, String> {
+        let factory = self.factories.get(&rule.kind).ok_or_else(|| {
+            format!(
+                "Rule {}: unknown kind {} (expected: {})",
+                rule.id,
+                rule.kind,
+                self.kinds().collect::<Vec<_>>().join(", ")
+            )
+        })?;
+        factory(rule, setup)
+    }
+}
+
+impl Default for RuleRegistry {
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        registry.register("regex", RegexEvaluator::build);
+        registry.register("metric", MetricEvaluator::build);
+        registry.register("metric_threshold", MetricEvaluator::build);
+        registry.register("metric_regression", RegressionEvaluator::build);
+        registry
+    }
+}
+
+/// A file being linted; its metrics are computed the first time a rule asks
+pub struct LintFile {
+    /// Path relative to the linted directory, as reported and as keyed in baselines
+    pub path: String,
+    pub content: String,
+    functions: OnceLock<Vec<FunctionComplexity>>,
+    metrics: OnceLock<MetricValues>,
+}
+
+impl LintFile {
+    pub fn new(path: &str, content: String) -> Self {
+        Self { path: path.to_string(), content, functions: OnceLock::new(), metrics: OnceLock::new() }
+    }
+
+    pub fn extension(&self) -> &str {
+        get_file_extension(&self.path)
+    }
+
+    /// Metrics of each function in the file
+    pub fn functions(&self) -> &[FunctionComplexity] {
+        self.functions.get_or_init(|| calculate_function_complexities(&self.content, self.extension()))
+    }
+
+    /// Metrics of the whole file
+    pub fn metrics(&self) -> MetricValues {
+        *self.metrics.get_or_init(|| {
+            let metrics = calculate_file_complexity(&self.path, &self.content);
+            MetricValues {
+                cyclomatic: metrics.cyclomatic_complexity,
+                cognitive: metrics.cognitive_complexity,
+                nesting: metrics.max_nesting_depth,
+                lines: metrics.lines_of_code as u32,
+            }
+        })
+    }
+}
+
+/// A metric that `metric` and `metric_regression` rules can limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Metric {
+    Cyclomatic,
+    Cognitive,
+    Nesting,
+    /// Lines of a function, or non-blank lines of a file
+    Lines,
+}
+
+impl fmt::Display for Metric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Cyclomatic => "cyclomatic complexity",
+            Self::Cognitive => "cognitive complexity",
+            Self::Nesting => "nesting depth",
+            Self::Lines => "line count",
+        })
+    }
+}
+
+/// Whether a metric rule applies to each function or to whole files
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MetricScope {
+    #[default]
+    Function,
+    File,
+}
+
+/// The metrics of a function or file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MetricValues {
+    pub cyclomatic: u32,
+    pub cognitive: u32,
+    pub nesting: u32,
+    pub lines: u32,
+}
+
+impl MetricValues {
+    pub fn of_function(function: &FunctionComplexity) -> Self {
+        Self {
+            cyclomatic: function.cyclomatic_complexity,
+            cognitive: function.cognitive_complexity,
+            nesting: function.max_nesting_depth,
+            lines: function_length(function) as u32,
+        }
+    }
+
+    pub fn get(&self, metric: Metric) -> u32 {
+        match metric {
+            Metric::Cyclomatic => self.cyclomatic,
+            Metric::Cognitive => self.cognitive,
+            Metric::Nesting => self.nesting,
+            Metric::Lines => self.lines,
+        }
+    }
+}
+
+/// Metrics recorded by `lint --write-baseline`, for `metric_regression` rules
+///
+/// Functions are keyed `path::name`, so moving a function within its file
+/// doesn't lose its baseline; of several same-named functions in a file, the
+/// first is recorded.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LintBaseline {
+    #[serde(default)]
+    pub files: BTreeMap<String, MetricValues>,
+    #[serde(default)]
+    pub functions: BTreeMap<String, MetricValues>,
+}
+
+impl LintBaseline {
+    /// Record the metrics of `file` and its functions
+    pub fn record(&mut self, file: &LintFile) {
+        self.files.insert(file.path.clone(), file.metrics());
+        for function in file.functions() {
+            self.functions
+                .entry(function_key(&file.path, &function.name))
+                .or_insert_with(|| MetricValues::of_function(function));
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("Cannot read baseline {}: {e}", path.display()))?;
+        serde_json::from_str(&text).map_err(|e| format!("Invalid baseline {}: {e}", path.display()).into())
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)? + "\n")?;
+        Ok(())
+    }
+}
+
+fn function_key(path: &str, name: &str) -> String {
+    format!("{path}::{name}")
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RegexParams {
+    pattern: String,
+}
+
+/// `regex` rules: one finding per line matching `pattern`
+struct RegexEvaluator {
+    regex: Regex,
+}
+
+impl RegexEvaluator {
+    fn build(rule: &LintRule, _setup: &LintSetup) -> Result<Box<dyn RuleEvaluator>, String> {
+        let params: RegexParams = rule.params()?;
+        let regex = Regex::new(&params.pattern).map_err(|e| format!("Rule {}: {e}", rule.id))?;
+        Ok(Box::new(Self { regex }))
+    }
+}
+
+impl RuleEvaluator for RegexEvaluator {
+    fn evaluate(&self, file: &LintFile) -> Vec<RuleHit> {
+        split_lines(&file.content)
+            .enumerate()
+            .filter(|(_, line)| self.regex.is_match(line))
+            .map(|(i, _)| RuleHit { line: i + 1, message: format!("matches `{}`", self.regex.as_str()) })
+            .collect()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MetricParams {
+    metric: Metric,
+    max: u32,
+    #[serde(default)]
+    scope: MetricScope,
+}
+
+/// `metric` rules: one finding per function (or file) with `metric` above `max`
+struct MetricEvaluator {
+    params: MetricParams,
+}
+
+impl MetricEvaluator {
+    fn build(rule: &LintRule, _setup: &LintSetup) -> Result<Box<dyn RuleEvaluator>, String> {
+        Ok(Box::new(Self { params: rule.params()? }))
+    }
+}
+
+impl RuleEvaluator for MetricEvaluator {
+    fn evaluate(&self, file: &LintFile) -> Vec<RuleHit> {
+        let MetricParams { metric, max, scope } = self.params;
+        match scope {
+            MetricScope::Function => file
+                .functions()
+                .iter()
+                .filter_map(|function| {
+                    let value = MetricValues::of_function(function).get(metric);
+                    (value > max).then(|| RuleHit {
+                        line: function.start_line,
+                        message: format!("{metric} of `{}` is {value} (max {max})", function.name),
+                    })
+                })
+                .collect(),
+            MetricScope::File => {
+                let value = file.metrics().get(metric);
+                if value > max {
+                    vec![RuleHit { line: 1, message: format!("{metric} of the file is {value} (max {max})") }]
+                } else {
+                    Vec::new()
+                }
+            }
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RegressionParams {
+    metric: Metric,
+    #[serde(default)]
+    max_increase: u32,
+    #[serde(default)]
+    scope: MetricScope,
+}
+
+/// `metric_regression` rules: one finding per function (or file) whose
+/// `metric` rose by more than `max_increase` over the baseline
+///
+/// Functions and files missing from the baseline are new, not regressions.
+struct RegressionEvaluator {
+    params: RegressionParams,
+    baseline: Arc<LintBaseline>,
+}
+
+impl RegressionEvaluator {
+    fn build(rule: &LintRule, setup: &LintSetup) -> Result<Box<dyn RuleEvaluator>, String> {
+        let params = rule.params()?;
+        let baseline = setup.baseline.clone().ok_or_else(|| format!("Rule {}: metric_regression rules need --baseline", rule.id))?;
+        Ok(Box::new(Self { params, baseline }))
+    }
+
+    fn check(&self, before: Option<&MetricValues>, now: u32, what: &str) -> Option<String> {
+        let RegressionParams { metric, max_increase, .. } = self.params;
+        let before = before?.get(metric);
+        (now > before.saturating_add(max_increase))
+            .then(|| format!("{metric} of {what} rose from {before} to {now} (+{}, max +{max_increase})", now - before))
+    }
+}
+
+impl RuleEvaluator for RegressionEvaluator {
+    fn evaluate(&self, file: &LintFile) -> Vec<RuleHit> {
+        match self.params.scope {
+            MetricScope::Function => {
+                let mut seen = HashMap::new();
+                file.functions()
+                    .iter()
+                    // Only the first of same-named functions is in the baseline
+                    .filter(|function| seen.insert(function.name.as_str(), ()).is_none())
+                    .filter_map(|function| {
+                        let before = self.baseline.functions.get(&function_key(&file.path, &function.name));
+                        let now = MetricValues::of_function(function).get(self.params.metric);
+                        let message = self.check(before, now, &format!("`{}`", function.name))?;
+                        Some(RuleHit { line: function.start_line, message })
+                    })
+                    .collect()
+            }
+            MetricScope::File => {
+                let now = file.metrics().get(self.params.metric);
+                self.check(self.baseline.files.get(&file.path), now, "the file")
+                    .map(|message| vec![RuleHit { line: 1, message }])
+                    .unwrap_or_default()
+            }
+        }
+    }
+}
+
+/// A set of rules with their evaluators, ready to check files
+pub struct Linter {
+    rules: Vec<(LintRule, Box<dyn RuleEvaluator>)>,
+}
+
+impl Linter {
+    /// Build the evaluator of every rule, failing on the first invalid one
+    pub fn new(rules: Vec<LintRule>, registry: &RuleRegistry, setup: &LintSetup) -> Result<Self, String> {
+        let mut seen = HashMap::new();
+        for rule in &rules {
+            if seen.insert(rule.id.as_str(), ()).is_some() {
+                return Err(format!("Rule id {} is used twice", rule.id));
+            }
+        }
+        let rules = rules
+            .into_iter()
+            .map(|rule| registry.build(&rule, setup).map(|evaluator| (rule, evaluator)))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { rules })
+    }
+
+    /// Findings of every rule in `file`, by line and then rule order
+    pub fn lint_file(&self, file: &LintFile) -> Vec<LintFinding> {
+        let mut findings: Vec<LintFinding> = self
+            .rules
+            .iter()
+            .flat_map(|(rule, evaluator)| {
+                evaluator.evaluate(file).into_iter().map(|hit| LintFinding {
+                    rule_id: rule.id.clone(),
+                    kind: rule.kind.clone(),
+                    level: rule.level,
+                    file: file.path.clone(),
+                    line: hit.line,
+                    message: hit.message,
+                    description: rule.description.clone(),
+                })
+            })
+            .collect();
+        findings.sort_by_key(|f| f.line);
+        findings
+    }
+}
+
+/// The readable files under `path`, with paths relative to it
+pub fn lint_files(
+    path: &Path,
+    extensions: Option<&[String]>,
+    exclude: Option<&[String]>,
+) -> Result<Vec<LintFile>, Box<dyn std::error::Error>> {
+    use rayon::prelude::*;
+
+    let files = list_files(path, extensions, exclude)?;
+    let mut files: Vec<LintFile> = files
+        .par_iter()
+        .filter_map(|f| {
+            let content = read_text(Path::new(&f.path), Encoding::Auto).ok()?;
+            let relative = Path::new(&f.path)
+                .strip_prefix(path)
+                .map(|p| p.to_string_lossy().replace('\\', "/"))
+                .unwrap_or_else(|_| f.path.clone());
+            Some(LintFile::new(&relative, content))
+        })
+        .collect();
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
+
+/// Lint every file under `path`, returning findings by file and line
+pub fn lint_path(
+    path: &Path,
+    extensions: Option<&[String]>,
+    exclude: Option<&[String]>,
+    linter: &Linter,
+) -> Result<Vec<LintFinding>, Box<dyn std::error::Error>> {
+    use rayon::prelude::*;
+
+    let files = lint_files(path, extensions, exclude)?;
+    Ok(files.par_iter().flat_map(|file| linter.lint_file(file)).collect())
+}
+
+/// Baseline of the current metrics of every file under `path`
+pub fn baseline_path(
+    path: &Path,
+    extensions: Option<&[String]>,
+    exclude: Option<&[String]>,
+) -> Result<LintBaseline, Box<dyn std::error::Error>> {
+    let mut baseline = LintBaseline::default();
+    for file in lint_files(path, extensions, exclude)? {
+        baseline.record(&file);
+    }
+    Ok(baseline)
+}
+
+/// JSON document body: findings plus per-level counts
+pub fn findings_to_json(findings: &[LintFinding]) -> serde_json::Value {
+    let count = |level| findings.iter().filter(|f| f.level == level).count();
+    serde_json::json!({
+        "findings": findings,
+        "summary": {
+            "total": findings.len(),
+            "error": count(LintLevel::Error),
+            "warning": count(LintLevel::Warning),
+            "note": count(LintLevel::Note),
+        },
+    })
+}
+
+/// Print findings grouped by file, the text output of the `lint` command
+pub fn print_findings(findings: &[LintFinding]) {
+    println!("{}", "Lint".cyan().bold());
+    println!("{}", theme::rule(30).cyan());
+    println!();
+
+    if findings.is_empty() {
+        println!("{}", "No findings.".green());
+        return;
+    }
+
+    let mut current_file: Option<&str> = None;
+    for finding in findings {
+        if current_file != Some(finding.file.as_str()) {
+            println!("{}", finding.file.bold());
+            current_file = Some(finding.file.as_str());
+        }
+        let level = match finding.level {
+            LintLevel::Error => finding.level.as_str().red().bold(),
+            LintLevel::Warning => finding.level.as_str().yellow(),
+            LintLevel::Note => finding.level.as_str().dimmed(),
+        };
+        println!(
+            "  {}:{} {} [{}] {}",
+            finding.file.dimmed(),
+            finding.line.to_string().yellow(),
+            level,
+            finding.rule_id.cyan(),
+            finding.message
+        );
+    }
+    println!();
+    println!("{} finding(s)", findings.len().to_string().yellow());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RULES: &str = r#"
+[[rule]]
+id = "no-todo"
+kind = "regex"
+pattern = "TODO"
+level = "note"
+
+[[rule]]
+id = "cognitive-3"
+kind = "metric"
+metric = "cognitive"
+max = 3
+
+[[rule]]
+id = "no-regressions"
+kind = "metric_regression"
+metric = "cognitive"
+max_increase = 1
+level = "error"
+"#;
+
+    const BEFORE: &str = "fn parse(x: i32) -> i32 {\n    if x > 0 { 1 } else { 0 }\n}\n";
+    const AFTER: &str = "// TODO: simplify\nfn parse(x: i32) -> i32 {\n    if x > 0 {\n        if x > 10 {\n            if x > 100 { return 3; }\n            return 2;\n        }\n        1\n    } else { 0 }\n}\n\nfn fresh(x: i32) -> i32 {\n    if x > 0 { if x > 1 { if x > 2 { 3 } else { 2 } } else { 1 } } else { 0 }\n}\n";
+
+    fn linter(baseline: Option<LintBaseline>) -> Result<Linter, String> {
+        let setup = LintSetup { baseline: baseline.map(Arc::new) };
+        Linter::new(parse_rules(RULES).unwrap(), &RuleRegistry::default(), &setup)
+    }
+
+    #[test]
+    fn test_one_rule_of_each_kind() {
+        let mut baseline = LintBaseline::default();
+        baseline.record(&LintFile::new("src/parse.rs", BEFORE.to_string()));
+        let findings = linter(Some(baseline)).unwrap().lint_file(&LintFile::new("src/parse.rs", AFTER.to_string()));
+
+        let found: Vec<(&str, usize)> = findings.iter().map(|f| (f.rule_id.as_str(), f.line)).collect();
+        // `fresh` is over the threshold but not in the baseline, so it's no regression
+        assert_eq!(found, vec![("no-todo", 1), ("cognitive-3", 2), ("no-regressions", 2), ("cognitive-3", 12)]);
+        assert_eq!(findings[2].level, LintLevel::Error);
+        assert_eq!(findings[2].message, "cognitive complexity of `parse` rose from 2 to 7 (+5, max +1)");
+    }
+
+    #[test]
+    fn test_invalid_rules_are_rejected() {
+        let err = |rules: &str, setup: &LintSetup| {
+            Linter::new(parse_rules(rules).unwrap(), &RuleRegistry::default(), setup).err().unwrap()
+        };
+        assert!(linter(None).err().unwrap().contains("need --baseline"));
+        let setup = LintSetup::default();
+        assert!(err("[[rule]]\nid = \"x\"\nkind = \"magic\"\n", &setup).contains("unknown kind magic"));
+        assert!(err("[[rule]]\nid = \"x\"\nkind = \"metric\"\nmetric = \"cognitive\"\nmaz = 3\n", &setup).contains("maz"));
+        assert!(err("[[rule]]\nid = \"x\"\nkind = \"regex\"\npattern = \"(\"\n", &setup).starts_with("Rule x:"));
+        let twice = "[[rule]]\nid = \"x\"\nkind = \"regex\"\npattern = \"a\"\n".repeat(2);
+        assert!(err(&twice, &setup).contains("used twice"));
+    }
+
+    #[test]
+    fn test_registry_accepts_custom_kinds() {
+        struct EveryFile;
+        impl RuleEvaluator for EveryFile {
+            fn evaluate(&self, _file: &LintFile) -> Vec<RuleHit> {
+                vec![RuleHit { line: 1, message: "seen".to_string() }]
+            }
+        }
+        let mut registry = RuleRegistry::empty();
+        registry.register("every_file", |_, _| Ok(Box::new(EveryFile)));
+        let rules = parse_rules("rule = [{ id = \"seen\", kind = \"every_file\" }]").unwrap();
+        let linter = Linter::new(rules, &registry, &LintSetup::default()).unwrap();
+
+        let findings = linter.lint_file(&LintFile::new("a.py", String::new()));
+        assert_eq!(findings.len(), 1);
+        assert_eq!((findings[0].kind.as_str(), findings[0].level), ("every_file", LintLevel::Warning));
+    }
+}
@@ -2,20 +2,150 @@
 //!
 //! This is the main entry point that orchestrates all modules.
 
-use clap::Parser;
+use clap::{CommandFactory, FromArgMatches, Parser};
 use colored::*;
 
 // Use library modules
-use codesearch::cli::{Cli, Commands, get_default_exclude_dirs};
-use codesearch::{analysis, circular, complexity, deadcode, duplicates, export, interactive};
+use codesearch::cli::{BookmarkAction, CacheAction, Cli, Commands, ConfigAction, get_default_exclude_dirs};
+use codesearch::config::Config;
+use codesearch::errors::{exit_code, GateFailure};
+use codesearch::{analysis, bookmarks, cache, circular, complexity, deadcode, duplicates, export, interactive};
+use codesearch::export::SarifFinding;
+use codesearch::flag_rules::{check_search_flags, has_regex_syntax, SearchFlags};
 #[cfg(feature = "mcp")]
 use codesearch::mcp;
-use codesearch::search::{list_files, print_results, print_search_stats, search_code};
-use codesearch::types::SearchOptions;
+use codesearch::search::{build_matcher, chrome_enabled, list_files_with, load_file_list, only_matching_texts, print_grep_comparison, print_limit_status, print_only_matching, print_results, print_results_annotated, print_search_metrics, print_search_stats, group_results_by_file, search_code_streaming, search_files_outcome, search_path_or_glob_outcome, sort_results_by_path};
+use codesearch::schema::{OnlyMatchingResult, SchemaVersion, SearchJsonStream, SearchOutput, WithMeta, only_matching_to_json, search_output_to_json, to_versioned_json};
+use codesearch::theme::{self, Glyph};
+use codesearch::timings::{Phase, Timings};
+use codesearch::options::has_extension;
+use codesearch::encoding::Encoding;
+use codesearch::types::{ContextLines, LimitReached, RankGroup, RegexEngine, SCHEMA_VERSION, SearchOptions, SearchStats, SortKey};
+use codesearch::pathglob::check_search_path;
+use codesearch::index::CodeIndex;
+use codesearch::index_archive::{pack_index, IndexArchive};
+use codesearch::analysis::format_size;
+use std::io::{BufWriter, IsTerminal};
+use std::ops::ControlFlow;
+use std::path::Path;
+use std::process::ExitCode;
+use std::sync::Arc;
 
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let cli = Cli::parse();
+/// Print how a search query will be interpreted, to stderr
+fn print_query_explanation(query: &str, options: &SearchOptions, profile: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    use codesearch::search::{plan_query, Query};
+
+    let plan = plan_query(&Query::from_options(query, options)?);
+    eprintln!("{}", "Query explanation:".cyan().bold());
+    eprintln!("  Query: {}", query.yellow());
+    eprintln!("  Profile: {}", profile.unwrap_or("none (flags and defaults only)"));
+    for line in plan.describe() {
+        eprintln!("  {line}");
+    }
+    eprintln!();
+    Ok(())
+}
+
+/// Write findings as SARIF to `export_path`, or print them when no path is given
+fn write_sarif<T: SarifFinding>(findings: &[T], export_path: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    match export_path {
+        Some(path) => {
+            export::export_sarif(findings, path)?;
+            eprintln!("{}", format!("Results exported to: {path}").green());
+        }
+        None => println!("{}", serde_json::to_string_pretty(&export::to_sarif(findings))?),
+    }
+    Ok(())
+}
+
+/// Write `report` as HTML to `path`
+fn write_html(report: &export::AnalysisBundle, path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    export::export_html(report, path)?;
+    eprintln!("{}", format!("Results exported to: {path}").green());
+    Ok(())
+}
+
+/// Search `path`, writing results to stdout as JSON as they are found
+///
+/// Returns the number of results written.
+fn stream_search_json(query: &str, path: &Path, options: &SearchOptions, version: SchemaVersion) -> Result<usize, Box<dyn std::error::Error>> {
+    check_search_path(path)?;
+    let mut stream = SearchJsonStream::begin(BufWriter::new(std::io::stdout().lock()), query, version)?;
+    let mut write_error = None;
+    let summary = search_code_streaming(query, path, options, |result| match stream.push(&result) {
+        Ok(()) => ControlFlow::Continue(()),
+        Err(err) => {
+            write_error = Some(err);
+            ControlFlow::Break(())
+        }
+    })?;
+    if let Some(err) = write_error {
+        return Err(err.into());
+    }
+    let limit_reached = LimitReached { per_file: summary.per_file_limit, ..LimitReached::default() };
+    let written = stream.count();
+    stream.finish(SearchOutput { limit_reached, ..SearchOutput::new(query, Vec::new()) })?;
+    Ok(written)
+}
+
+/// Write a complete search document to stdout as compact JSON, one result per line
+fn write_search_json(output: SearchOutput, version: SchemaVersion) -> Result<(), Box<dyn std::error::Error>> {
+    let mut stream = SearchJsonStream::begin(BufWriter::new(std::io::stdout().lock()), &output.query, version)?;
+    for result in &output.results {
+        stream.push(result)?;
+    }
+    stream.finish(output)?;
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => match e.downcast_ref::<GateFailure>() {
+            Some(gate) => {
+                eprintln!("{} {}", "Gate failed:".red().bold(), gate.message);
+                ExitCode::from(gate.code)
+            }
+            None => {
+                eprintln!("Error: {e}");
+                ExitCode::from(exit_code::ERROR)
+            }
+        },
+    }
+}
+
+fn run() -> Result<(), Box<dyn std::error::Error>> {
+    // Usage errors exit with `exit_code::ERROR` too, not clap's 2, which is a gate code here
+    let exit_on_usage_error = |e: clap::Error| -> ! {
+        let _ = e.print();
+        std::process::exit(if e.use_stderr() { exit_code::ERROR.into() } else { 0 });
+    };
+    let matches = Cli::command().try_get_matches().unwrap_or_else(|e| exit_on_usage_error(e));
+    let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| exit_on_usage_error(e));
+    cli.normalize_extensions()?;
+    let config = Config::resolve(cli.config.as_deref(), &cli.target_path(&matches))?;
+    config.activate();
+    cli.apply_config(&matches, &config);
+    let profile = cli.apply_profile(&matches, &config.profiles)?;
+    if profile.as_ref().and_then(|p| p.color) == Some(false) {
+        colored::control::set_override(false);
+    }
+    let output_config = &config.output;
+    let hyperlinks: theme::HyperlinkMode = match cli.hyperlinks.as_deref().or(output_config.hyperlinks.as_deref()) {
+        Some(mode) => mode.parse()?,
+        None => theme::HyperlinkMode::default(),
+    };
+    theme::init(cli.no_emoji, hyperlinks, output_config);
+    if let Some(color_theme) = &output_config.theme {
+        theme::set_color_theme(color_theme.parse()?);
+    }
+    if let Some(jobs) = cli.jobs {
+        if jobs == 0 {
+            return Err("--jobs must be at least 1".into());
+        }
+        rayon::ThreadPoolBuilder::new().num_threads(jobs).build_global()?;
+    }
 
     // Handle simple search without subcommand: codesearch <query> [path]
     if cli.command.is_none() {
@@ -50,27 +180,30 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 final_exclude.extend(user_exclude);
             }
             
-            let options = SearchOptions {
-                extensions: cli.extensions,
-                ignore_case: cli.ignore_case,
-                fuzzy: cli.fuzzy,
-                fuzzy_threshold: 0.6,
-                max_results: cli.max_results,
-                exclude: Some(final_exclude),
-                rank: false,
-                cache: false,
-                semantic: false,
-                benchmark: false,
-                vs_grep: false,
-            };
+            let options = SearchOptions::builder()
+                .extensions(cli.extensions)
+                .ignore_case(cli.ignore_case)
+                .fuzzy(cli.fuzzy)
+                .max_results(cli.max_results)
+                .max_total(cli.max_total)
+                .exclude(final_exclude)
+                .gitignore(config.search.respect_gitignore())
+                .build();
             
-            let results = search_code(&query, &cli.path, &options)?;
+            let outcome = search_path_or_glob_outcome(&query, &cli.path, &options)?;
+            let results = &outcome.results;
+            let show_chrome = chrome_enabled(false);
             
             if results.is_empty() {
-                println!("{}", "No matches found.".dimmed());
+                if show_chrome {
+                    eprintln!("{}", "No matches found.".dimmed());
+                }
             } else {
-                print_results(&results, true, false);
-            print_search_stats(&results, &query);
+                print_results(results, true, false);
+                if show_chrome {
+                    print_search_stats(results, &query);
+                    print_limit_status(&outcome, &options);
+                }
             }
             return Ok(());
         } else {
@@ -87,19 +220,79 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             ignore_case,
             no_line_numbers,
             max_results,
+            max_total,
             format,
+            only_matching,
+            replace_output,
+            no_filename,
+            width,
+            wrap,
             stats,
             fuzzy,
             fuzzy_threshold,
+            literal,
+            word,
+            context,
+            before,
+            after,
+            multiline,
+            multiline_max_bytes,
             exclude,
             rank,
+            rank_group,
+            sort_by,
             cache,
+            use_index,
+            index_file,
+            index_archive,
             semantic,
+            explain,
+            engine,
+            encoding,
             benchmark,
             vs_grep,
             no_auto_exclude,
+            no_gitignore,
+            files_from,
+            no_filter_list,
             export: export_path,
+            export_columns,
+            export_width,
+            show_bookmarks,
+            schema_version,
+            fail_on_matches,
         }) => {
+            let schema_version: SchemaVersion = schema_version.parse()?;
+            codesearch::search::layout::set_layout(width.map(usize::from), wrap);
+            let export_options = export::ExportOptions {
+                columns: match &export_columns {
+                    Some(names) => export::parse_export_columns(names)?,
+                    None => export::ExportColumn::ALL.to_vec(),
+                },
+                max_width: export_width,
+            };
+            let flags = SearchFlags {
+                regex_syntax: has_regex_syntax(&query),
+                fuzzy,
+                literal,
+                format_json: matches!(format.as_str(), "json" | "json-pretty"),
+                export: export_path.is_some(),
+                only_matching,
+                no_filename,
+                benchmark,
+                vs_grep,
+                rank,
+                rank_group: rank_group.is_some(),
+                sort_by: sort_by.is_some(),
+                cache,
+                context: context.is_some() || before.is_some() || after.is_some(),
+                multiline,
+                files_from: files_from.is_some(),
+                index_archive: index_archive.is_some(),
+            };
+            for warning in check_search_flags(&flags)? {
+                eprintln!("{} {warning}", "Warning:".yellow().bold());
+            }
             let final_exclude = if no_auto_exclude {
                 exclude
                 } else {
@@ -110,46 +303,185 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 Some(auto_exclude)
             };
             
-            let options = SearchOptions {
-                extensions,
-                ignore_case,
-                fuzzy,
-                fuzzy_threshold,
-                max_results,
-                exclude: final_exclude,
-                rank,
-                cache,
-                semantic,
-                benchmark,
-                vs_grep,
-            };
+            let options = SearchOptions::builder()
+                .extensions(extensions)
+                .ignore_case(ignore_case)
+                .fuzzy(fuzzy)
+                .fuzzy_threshold(fuzzy_threshold)
+                .literal(literal)
+                .word_boundary(word)
+                .max_results(max_results)
+                .max_total(max_total)
+                .exclude(final_exclude)
+                .gitignore(!no_gitignore && config.search.respect_gitignore())
+                .rank(rank)
+                .cache(cache)
+                .semantic(semantic)
+                .benchmark(benchmark)
+                .vs_grep(vs_grep)
+                .engine(engine.parse::<RegexEngine>()?)
+                .encoding(encoding.parse::<Encoding>()?)
+                .context(ContextLines::new(
+                    before.or(context).unwrap_or(0),
+                    after.or(context).unwrap_or(0),
+                ))
+                .multiline(multiline)
+                .multiline_max_bytes(multiline_max_bytes)
+                .index(use_index.then(|| Arc::new(CodeIndex::new(index_file.clone()))))
+                .build();
             
-            let results = search_code(&query, &path, &options)?;
+            let json_output = matches!(format.as_str(), "json" | "json-pretty");
+            // Stats, banners and notices go to stderr, and only when someone will see them
+            let show_chrome = !json_output && chrome_enabled(stats);
+
+            if show_chrome && options.index.as_ref().is_some_and(|index| index.is_empty()) {
+                eprintln!(
+                    "{} no index at {}; run `codesearch index` first",
+                    "Warning:".yellow().bold(),
+                    index_file.display()
+                );
+            }
+
+            if explain && show_chrome {
+                print_query_explanation(&query, &options, cli.profile.as_deref())?;
+            }
+
+            // Without ranking, sorting, caps or summaries, nothing needs the whole result set
+            let streamable = format == "json"
+                && !only_matching
+                && export_path.is_none()
+                && files_from.is_none()
+                && index_archive.is_none()
+                && !rank
+                && sort_by.is_none()
+                && max_total.is_none()
+                && !stats
+                && !benchmark
+                && !vs_grep
+                && !cache;
+            if streamable {
+                let matched = stream_search_json(&query, &path, &options, schema_version)?;
+                if fail_on_matches && matched > 0 {
+                    return Err(GateFailure::new(exit_code::MATCHES_FOUND, format!("{matched} matching line(s)")).into());
+                }
+                return Ok(());
+            }
+
+            let (mut outcome, missing_files) = match (&index_archive, &files_from) {
+                (Some(archive), _) => (IndexArchive::open(archive)?.search(&query, &options)?, Vec::new()),
+                (None, Some(source)) => {
+                    let list = load_file_list(source, Path::new("."))?;
+                    let outcome = search_files_outcome(&query, &list.files, &options, !no_filter_list)?;
+                    (outcome, list.missing)
+                }
+                (None, None) => (search_path_or_glob_outcome(&query, &path, &options)?, Vec::new()),
+            };
+            if show_chrome && !missing_files.is_empty() {
+                eprintln!("{}", format!("{} listed file(s) not found:", missing_files.len()).yellow());
+                for missing in &missing_files {
+                    eprintln!("  {}", missing.display().to_string().dimmed());
+                }
+            }
+            let limit_reached = outcome.limit_reached;
+            let metrics = std::mem::take(&mut outcome.metrics);
+            let grep_comparison = outcome.grep_comparison.take();
+            let mut results = std::mem::take(&mut outcome.results);
+            let matched = results.len();
 
-            if let Some(path) = export_path {
-                export::export_results(&results, &path, &query)?;
-                println!("{}", format!("Results exported to: {path}").green());
+            if show_chrome {
+                if benchmark {
+                    print_search_metrics(&metrics, cache);
+                }
+                if let Some(comparison) = &grep_comparison {
+                    print_grep_comparison(comparison, &metrics);
+                }
+            }
+            let rank_group = match rank_group {
+                Some(group) => group.parse()?,
+                None if !json_output && std::io::stdout().is_terminal() => RankGroup::File,
+                None => RankGroup::None,
+            };
+            match sort_by {
+                Some(SortKey::Path) => sort_results_by_path(&mut results),
+                None if rank && rank_group == RankGroup::File => results = group_results_by_file(results),
+                None => {}
+            }
+
+            let template_matcher = match &replace_output {
+                Some(_) => Some(build_matcher(&query, &options)?),
+                None => None,
+            };
+            let template = template_matcher.as_ref().zip(replace_output.as_deref());
+
+            if only_matching {
+                if json_output {
+                    let reduced = results
+                        .iter()
+                        .map(|r| OnlyMatchingResult {
+                            file: r.file.clone(),
+                            line_number: r.line_number,
+                            matches: only_matching_texts(r, template),
+                        })
+                        .collect();
+                    println!("{}", only_matching_to_json(&query, reduced, schema_version)?);
+                } else {
+                    print_only_matching(&results, !no_filename, !no_line_numbers, template);
+                }
+            } else if let Some(path) = export_path {
+                export::export_results_with(&results, &path, &query, &export_options)?;
+                if show_chrome {
+                    eprintln!("{}", format!("Results exported to: {path}").green());
+                }
             } else {
                 match format.as_str() {
-                "json" => {
-                    let json = serde_json::to_string_pretty(&results)?;
-                    println!("{json}");
+                "json" | "json-pretty" => {
+                    let output = SearchOutput {
+                        limit_reached,
+                        stats: stats.then(|| SearchStats::from_results(&results)),
+                        metrics: benchmark.then_some(metrics),
+                        grep_comparison,
+                        missing_files: missing_files.iter().map(|p| p.display().to_string()).collect(),
+                        ..SearchOutput::new(&query, results)
+                    };
+                    if format == "json" {
+                        write_search_json(output, schema_version)?;
+                    } else {
+                        println!("{}", search_output_to_json(&output, schema_version)?);
+                    }
                 }
                 _ => {
                         if results.is_empty() {
-                            println!("{}", "No matches found.".dimmed());
+                            if show_chrome {
+                                eprintln!("{}", "No matches found.".dimmed());
+                            }
                         } else {
-                            print_results(&results, !no_line_numbers, rank);
+                            if show_bookmarks {
+                                let store = bookmarks::BookmarkStore::load_default()?;
+                                print_results_annotated(&results, !no_line_numbers, rank, |r| {
+                                    store.label_for(&r.file, r.line_number).map(|label| {
+                                        if label.is_empty() { "bookmarked".to_string() } else { label.to_string() }
+                                    })
+                                });
+                            } else {
+                                print_results(&results, !no_line_numbers, rank);
+                            }
                             if stats {
                         print_search_stats(&results, &query);
+                    }
+                            if show_chrome {
+                        print_limit_status(&outcome, &options);
                     }
                 }
             }
         }
         }
+            if fail_on_matches && matched > 0 {
+                return Err(GateFailure::new(exit_code::MATCHES_FOUND, format!("{matched} matching line(s)")).into());
+            }
         }
-        Some(Commands::Files { path, extensions, exclude }) => {
-            let files = list_files(&path, extensions.as_deref(), exclude.as_deref())?;
+        Some(Commands::Files { path, extensions, exclude, no_gitignore }) => {
+            let gitignore = !no_gitignore && config.search.respect_gitignore();
+            let files = list_files_with(&path, extensions.as_deref(), exclude.as_deref(), gitignore)?;
             match extensions {
                 Some(_) => {
                     for file in files {
@@ -157,57 +489,234 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                     }
                 }
                 None => {
-                    let json = serde_json::to_string_pretty(&files)?;
+                    let json = serde_json::to_string_pretty(&serde_json::json!({
+                        "schema_version": SCHEMA_VERSION,
+                        "files": files,
+                    }))?;
                     println!("{json}");
                 }
             }
         }
-        Some(Commands::Interactive { path, extensions, exclude }) => {
-            interactive::run(&path, extensions.as_deref(), exclude.as_deref())?;
-        }
-        Some(Commands::Analyze { path, extensions, exclude }) => {
-            analysis::analyze_codebase(&path, extensions.as_deref(), exclude.as_deref())?;
+        Some(Commands::Interactive { path, extensions, exclude, replay, replay_to }) => match replay {
+            Some(script) => interactive::replay(&path, extensions.as_deref(), exclude.as_deref(), &script, replay_to)?,
+            None => interactive::run(&path, extensions.as_deref(), exclude.as_deref())?,
+        },
+        Some(Commands::Analyze { path, extensions, exclude, format, schema_version }) => {
+            if format == "json" {
+                let schema_version: SchemaVersion = schema_version.parse()?;
+                let stats = analysis::compute_codebase_stats(&path, extensions.as_deref(), exclude.as_deref())?;
+                println!("{}", to_versioned_json(&stats, schema_version)?);
+            } else {
+                analysis::analyze_codebase(&path, extensions.as_deref(), exclude.as_deref())?;
+            }
         }
-        Some(Commands::Complexity { path, extensions, exclude, threshold, sort }) => {
-            complexity::analyze_complexity(&path, extensions.as_deref(), exclude.as_deref(), threshold, sort)?;
+        Some(Commands::Complexity { path, extensions, exclude, threshold, sort, format, export, per_function, top, max_complexity }) => {
+            // (location, cyclomatic complexity) of everything analyzed, for --max-complexity
+            let analyzed: Vec<(String, u32)> = if per_function {
+                if format != "text" {
+                    return Err(format!("{} output is per file; drop --per-function", format.to_uppercase()).into());
+                }
+                complexity::analyze_function_complexity(&path, extensions.as_deref(), exclude.as_deref(), threshold, top, &config.complexity)?
+                    .into_iter()
+                    .map(|(file, f)| (format!("{file}:{} {}", f.start_line, f.name), f.cyclomatic_complexity))
+                    .collect()
+            } else if let Some(html_path) = export.as_deref().filter(|p| export::is_html_path(p)) {
+                let mut reports = complexity::complexity_reports(&path, extensions.as_deref(), exclude.as_deref())?;
+                let analyzed = reports.iter().map(|r| (r.path.clone(), r.cyclomatic)).collect();
+                complexity::filter_complexity_reports(&mut reports, threshold, true);
+                let report = export::AnalysisBundle { complexity: Some(reports), ..export::AnalysisBundle::new("Complexity Report") };
+                write_html(&report, html_path)?;
+                analyzed
+            } else if format == "sarif" || export.is_some() {
+                let mut metrics = complexity::calculate_complexity(&path, extensions.as_deref(), exclude.as_deref())?;
+                let analyzed = metrics.iter().map(|m| (m.file_path.clone(), m.cyclomatic_complexity)).collect();
+                match threshold {
+                    Some(thresh) => metrics.retain(|m| m.cyclomatic_complexity >= thresh),
+                    None => metrics.retain(|m| m.cyclomatic_complexity > export::SARIF_COMPLEXITY_NOTE),
+                }
+                metrics.sort_by(|a, b| b.cyclomatic_complexity.cmp(&a.cyclomatic_complexity));
+                write_sarif(&metrics, export.as_deref())?;
+                analyzed
+            } else {
+                let mut reports = complexity::complexity_reports(&path, extensions.as_deref(), exclude.as_deref())?;
+                let analyzed = reports.iter().map(|r| (r.path.clone(), r.cyclomatic)).collect();
+                complexity::filter_complexity_reports(&mut reports, threshold, sort);
+                match format.as_str() {
+                    "json" => {
+                        let output = serde_json::json!({ "files": reports, "total_files": reports.len() });
+                        println!("{}", to_versioned_json(&output, SchemaVersion::current())?);
+                    }
+                    "text" => complexity::print_complexity_reports(&reports),
+                    other => return Err(format!("Unknown format: {other} (expected: text, json, sarif)").into()),
+                }
+                analyzed
+            };
+            if let Some(max) = max_complexity {
+                let over: Vec<&(String, u32)> = analyzed.iter().filter(|(_, cc)| *cc > max).collect();
+                if let Some((worst, cc)) = over.iter().max_by_key(|(_, cc)| *cc) {
+                    return Err(GateFailure::new(
+                        exit_code::COMPLEXITY_EXCEEDED,
+                        format!("{} over --max-complexity {max} (worst: {worst}, {cc})", over.len()),
+                    )
+                    .into());
+                }
+            }
         }
-        Some(Commands::DesignMetrics { path, extensions, exclude, detailed, format }) => {
+        Some(Commands::DesignMetrics { path, extensions, exclude, detailed, format, schema_version }) => {
             use codesearch::designmetrics::{analyze_design_metrics, print_design_metrics};
             
             println!("{}", "Analyzing design metrics...".cyan().bold());
             let metrics = analyze_design_metrics(&path, extensions.as_deref(), exclude.as_deref())?;
             
             if format == "json" {
-                println!("{}", serde_json::to_string_pretty(&metrics)?);
+                let schema_version: SchemaVersion = schema_version.parse()?;
+                println!("{}", to_versioned_json(&metrics, schema_version)?);
             } else {
                 print_design_metrics(&metrics, detailed);
             }
         }
-        Some(Commands::Metrics { path, extensions, exclude, detailed, format }) => {
-            use codesearch::codemetrics::{analyze_project_metrics, print_metrics_report};
+        Some(Commands::Metrics { path, extensions, exclude, detailed, format, schema_version, verbose }) => {
+            use codesearch::codemetrics::{analyze_project_metrics_timed, print_metrics_report};
             
+            let timings = if verbose { Timings::enabled() } else { Timings::disabled() };
             println!("{}", "Analyzing comprehensive code metrics...".cyan().bold());
-            let metrics = analyze_project_metrics(&path, extensions.as_deref(), exclude.as_deref())?;
+            let metrics = analyze_project_metrics_timed(&path, extensions.as_deref(), exclude.as_deref(), &timings)?;
             
             if format == "json" {
-                println!("{}", serde_json::to_string_pretty(&metrics)?);
+                let schema_version: SchemaVersion = schema_version.parse()?;
+                let output = timings.time(Phase::Render, || to_versioned_json(&WithMeta::new(&metrics, timings.meta()), schema_version))?;
+                println!("{output}");
             } else {
-                print_metrics_report(&metrics, detailed);
+                timings.time(Phase::Render, || print_metrics_report(&metrics, detailed));
+            }
+            timings.print_table();
+        }
+        Some(Commands::Duplicates { path, extensions, exclude, min_lines, similarity, order_insensitive, granularity, format, export, max_duplicates }) => {
+            let config = duplicates::DuplicateConfig {
+                min_lines,
+                similarity_threshold: similarity,
+                order_insensitive_sections: order_insensitive,
+                granularity: granularity.parse()?,
+                ignore_comments: config.duplicates.ignore_comments,
+                ..Default::default()
+            };
+            let found = if let Some(html_path) = export.as_deref().filter(|p| export::is_html_path(p)) {
+                let found = duplicates::find_duplicates_enhanced(&path, extensions.as_deref(), exclude.as_deref(), config)?;
+                let clusters = duplicates::cluster_duplicates(&found);
+                let report = export::AnalysisBundle { duplicate_clusters: Some(clusters), ..export::AnalysisBundle::new("Duplicate Code Report") };
+                write_html(&report, html_path)?;
+                found
+            } else if format == "sarif" || export.is_some() {
+                let found = duplicates::find_duplicates_enhanced(&path, extensions.as_deref(), exclude.as_deref(), config)?;
+                write_sarif(&found, export.as_deref())?;
+                found
+            } else if format == "json" {
+                let found = duplicates::find_duplicates_enhanced(&path, extensions.as_deref(), exclude.as_deref(), config)?;
+                let clusters = duplicates::cluster_duplicates(&found);
+                let output = serde_json::json!({
+                    "duplicates": found,
+                    "total_duplicates": found.len(),
+                    "clusters": clusters,
+                    "total_clusters": clusters.len(),
+                });
+                println!("{}", to_versioned_json(&output, SchemaVersion::current())?);
+                found
+            } else {
+                duplicates::detect_duplicates_enhanced(&path, extensions.as_deref(), exclude.as_deref(), config)?
+            };
+            let clusters = duplicates::cluster_duplicates(&found).len();
+            if let Some(max) = max_duplicates.filter(|&max| clusters > max) {
+                return Err(GateFailure::new(
+                    exit_code::DUPLICATES_EXCEEDED,
+                    format!("{clusters} duplicate clusters, more than --max-duplicates {max}"),
+                )
+                .into());
             }
         }
-        Some(Commands::Duplicates { path, extensions, exclude, min_lines, similarity }) => {
-            duplicates::detect_duplicates(&path, extensions.as_deref(), exclude.as_deref(), min_lines, similarity)?;
+        Some(Commands::Deadcode { path, extensions, exclude, reachability, entry_point, include_public, format, quiet, export, fail_on_deadcode }) => {
+            let options = deadcode::DeadCodeOptions { include_public };
+            let html = export.as_deref().filter(|p| export::is_html_path(p));
+            let sarif = html.is_none() && (format == "sarif" || export.is_some());
+            let text = html.is_none() && !sarif && format == "text";
+            if text {
+                deadcode::print_dead_code_header();
+            }
+            let items = if reachability || entry_point.is_some() {
+                use codesearch::deadcode::{EntryPoint, DEFAULT_ENTRY_POINTS};
+                use codesearch::ExtractionContext;
+
+                let mut specs = config.deadcode.entry_points.clone();
+                specs.extend(entry_point.unwrap_or_default());
+                if specs.is_empty() {
+                    specs = DEFAULT_ENTRY_POINTS.iter().map(|s| s.to_string()).collect();
+                }
+                let entry_points = EntryPoint::parse_all(&specs)?;
+                let context = ExtractionContext::build(&path, extensions.as_deref(), exclude.as_deref())?;
+                let (items, reachability) = deadcode::find_dead_code_reachable_in(&context, &path, &entry_points, &options);
+                if text && !context.is_empty() {
+                    deadcode::print_reachability(&reachability, &entry_points);
+                }
+                items
+            } else {
+                deadcode::find_dead_code(&path, extensions.as_deref(), exclude.as_deref(), &options)?
+            };
+            if let Some(html_path) = html {
+                let report = export::AnalysisBundle { dead_code: Some(items.clone()), ..export::AnalysisBundle::new("Dead Code Report") };
+                write_html(&report, html_path)?;
+            } else {
+                match format.as_str() {
+                    _ if sarif => write_sarif(&items, export.as_deref())?,
+                    "json" => println!("{}", to_versioned_json(&deadcode::DeadCodeReport::new(items.clone()), SchemaVersion::current())?),
+                    "text" => deadcode::print_dead_code_results(&items, quiet),
+                    other => return Err(format!("Unknown format: {other} (expected: text, json, sarif)").into()),
+                }
+            }
+            if fail_on_deadcode && !items.is_empty() {
+                return Err(GateFailure::new(exit_code::DEAD_CODE_FOUND, format!("{} dead code item(s)", items.len())).into());
+            }
         }
-        Some(Commands::Deadcode { path, extensions, exclude }) => {
-            deadcode::detect_dead_code(&path, extensions.as_deref(), exclude.as_deref())?;
+        Some(Commands::Refactor { path, extensions, exclude, high_priority, disable, only }) => {
+            let mut disabled = if only.is_empty() { config.refactor.disabled_types.clone() } else { Vec::new() };
+            disabled.extend(disable);
+            let enabled = analysis::enabled_suggestion_types(&only, &disabled);
+            analysis::suggest_refactoring(&path, extensions.as_deref(), exclude.as_deref(), high_priority, &config.complexity, &enabled)?;
+        }
+        Some(Commands::Badge { metric, path, extensions, exclude, output, format, cached }) => {
+            use codesearch::badge::{self, Badge, HistoryEntry};
+
+            let history = Path::new(badge::HISTORY_FILE);
+            let value = if cached {
+                match badge::latest_history(history, metric, &path)? {
+                    Some(entry) => entry.value,
+                    None => {
+                        return Err(format!("No {} recorded for {} in {}; run without --cached first", metric.label(), path.display(), badge::HISTORY_FILE).into());
+                    }
+                }
+            } else {
+                let mut final_exclude = get_default_exclude_dirs();
+                final_exclude.extend(exclude.unwrap_or_default());
+                let value = badge::compute_metric(metric, &path, extensions.as_deref(), Some(&final_exclude))?;
+                badge::record_history(history, &HistoryEntry::now(&path, metric, value))?;
+                value
+            };
+            let badge = Badge::new(metric, value, config.badge.bands(metric));
+            let rendered = match format.as_str() {
+                "svg" => badge::render_svg(&badge),
+                "json" => serde_json::to_string_pretty(&badge)? + "\n",
+                other => return Err(format!("Unknown format: {other} (expected: svg, json)").into()),
+            };
+            match output {
+                Some(file) => {
+                    std::fs::write(&file, rendered)?;
+                    println!("{}", format!("Wrote {} badge ({}, {}) to {}", metric.label(), badge.message, badge.color, file.display()).green());
+                }
+                None => print!("{rendered}"),
+            }
         }
         Some(Commands::Circular { path, extensions, exclude }) => {
             circular::detect_circular_calls(&path, extensions.as_deref(), exclude.as_deref())?;
         }
-        Some(Commands::Index { path, extensions, exclude, index_file }) => {
-            use codesearch::index::CodeIndex;
-            use std::sync::Arc;
-            
+        Some(Commands::Index { path, extensions, exclude, index_file, pack, include_contents }) => {
             println!("{}", "Building code index...".cyan().bold());
             let index = Arc::new(CodeIndex::new(index_file.clone()));
             index.index_directory(&path, extensions.as_deref(), exclude.as_deref())?;
@@ -220,15 +729,72 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             println!("  Total functions: {}", stats.total_functions);
             println!("  Total classes: {}", stats.total_classes);
             println!("\n{}", format!("Index saved to: {}", index_file.display()).green());
+
+            if let Some(archive) = pack {
+                let packed = pack_index(&index, &path, include_contents, &archive)?;
+                println!("\n{}", format!("Archive written to: {}", archive.display()).green());
+                println!("  Files: {}", packed.files);
+                if include_contents {
+                    println!("  Contents: {} files, {}", packed.files_with_contents, format_size(packed.content_bytes));
+                }
+                println!("  Archive size: {}", format_size(packed.archive_bytes));
+            }
         }
-        Some(Commands::Watch { path, extensions, index_file }) => {
-            use codesearch::index::CodeIndex;
+        Some(Commands::Warm { queries_file, path, extensions, exclude, index_file, parallel }) => {
+            use codesearch::warm::{print_warm_summary, read_queries, warm_queries};
+
+            let queries = read_queries(&queries_file)?;
+            let mut final_exclude = get_default_exclude_dirs();
+            final_exclude.extend(exclude.unwrap_or_default());
+
+            let index = Arc::new(CodeIndex::new(index_file));
+            index.refresh_directory(&path, extensions.as_deref(), Some(&final_exclude))?;
+            index.save()?;
+
+            // The same options as `search QUERY PATH --cache`, so those searches hit
+            let options = SearchOptions::builder()
+                .extensions(extensions)
+                .exclude(Some(final_exclude))
+                .gitignore(config.search.respect_gitignore())
+                .cache(true)
+                .index(Some(index))
+                .build();
+            let outcomes = warm_queries(&queries, &path, &options, parallel);
+            print_warm_summary(&outcomes);
+
+            let failed = outcomes.iter().filter(|o| o.error.is_some()).count();
+            if failed > 0 {
+                return Err(format!("{failed} of {} queries failed", outcomes.len()).into());
+            }
+        }
+        Some(Commands::Watch { path, extensions, index_file, analyze, debounce_ms, bell, fail_on_regression, duration }) => {
             use codesearch::watcher::start_watching;
-            use std::sync::Arc;
             
             println!("{}", "Starting file watcher...".cyan().bold());
             let index = Arc::new(CodeIndex::new(index_file));
-            start_watching(path, index, extensions)?;
+            if let Some(analyses) = analyze {
+                use codesearch::privacy::SensitiveNames;
+                use codesearch::watch_analysis::{watch_with_analysis, WatchAnalysis, WatchAnalysisOptions, WatchAnalyzer};
+                use std::time::Duration;
+
+                let analyses = analyses
+                    .iter()
+                    .map(|a| a.parse::<WatchAnalysis>())
+                    .collect::<Result<Vec<_>, _>>()?;
+                let names = SensitiveNames::from_config(&config.privacy, &[]);
+                let analyzer = WatchAnalyzer::new(analyses, extensions, get_default_exclude_dirs(), names);
+                let options = WatchAnalysisOptions {
+                    debounce: Duration::from_millis(debounce_ms),
+                    duration: duration.map(Duration::from_secs),
+                    bell,
+                };
+                let regressions = watch_with_analysis(&path, &index, analyzer, &options)?;
+                if fail_on_regression && regressions > 0 {
+                    return Err(GateFailure::new(exit_code::REGRESSION, format!("{regressions} change(s) made files worse")).into());
+                }
+            } else {
+                start_watching(path, index, extensions)?;
+            }
         }
         Some(Commands::Ast { path, extensions, format }) => {
             use codesearch::ast::analyze_file;
@@ -240,7 +806,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 let analysis = analyze_file(&path)?;
                 
                 if format == "json" {
-                    println!("{}", serde_json::to_string_pretty(&analysis)?);
+                    println!("{}", to_versioned_json(&analysis, SchemaVersion::current())?);
                 } else {
                     println!("\n{}", "Functions:".green().bold());
                     for func in &analysis.functions {
@@ -268,9 +834,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 
                 for entry in walker {
                     let file_path = entry.path();
-                    if let Some(ext) = file_path.extension().and_then(|s| s.to_str()) {
+                    if file_path.extension().is_some() {
                         if let Some(exts) = &extensions {
-                            if !exts.iter().any(|e| e == ext) {
+                            if !has_extension(file_path, exts) {
                                 continue;
                             }
                         }
@@ -297,7 +863,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 
                 for cfg in &cfgs {
                     if format == "json" {
-                        println!("{}", serde_json::to_string_pretty(&cfg)?);
+                        println!("{}", to_versioned_json(&cfg, SchemaVersion::current())?);
                     } else if format == "dot" {
                         println!("{}", cfg.to_dot());
                     } else {
@@ -308,7 +874,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                         
                         let unreachable = cfg.find_unreachable_blocks();
                         if !unreachable.is_empty() {
-                            println!("  {} Unreachable blocks: {:?}", "⚠️".yellow(), unreachable);
+                            println!("  {} Unreachable blocks: {:?}", theme::glyph(Glyph::Warning).yellow(), unreachable);
                         }
                         
                         let loops = cfg.find_loops();
@@ -319,7 +885,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                     
                     if let Some(export_path) = &export {
                         let output = if format == "json" {
-                            serde_json::to_string_pretty(&cfg)?
+                            to_versioned_json(&cfg, SchemaVersion::current())?
                         } else {
                             cfg.to_dot()
                         };
@@ -339,7 +905,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 
                 for dfg in &dfgs {
                     if format == "json" {
-                        println!("{}", serde_json::to_string_pretty(&dfg)?);
+                        println!("{}", to_versioned_json(&dfg, SchemaVersion::current())?);
                     } else if format == "dot" {
                         println!("{}", dfg.to_dot());
                     } else {
@@ -349,7 +915,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                         
                         let unused = dfg.find_unused_variables();
                         if !unused.is_empty() {
-                            println!("  {} Unused variables: {:?}", "⚠️".yellow(), unused);
+                            println!("  {} Unused variables: {:?}", theme::glyph(Glyph::Warning).yellow(), unused);
                         }
                         
                         let redundant = dfg.find_redundant_computations();
@@ -360,7 +926,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                     
                     if let Some(export_path) = &export {
                         let output = if format == "json" {
-                            serde_json::to_string_pretty(&dfg)?
+                            to_versioned_json(&dfg, SchemaVersion::current())?
                         } else {
                             dfg.to_dot()
                         };
@@ -372,18 +938,26 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
         Some(Commands::Callgraph { path, extensions, exclude, format, recursive_only, dead_only }) => {
             use codesearch::callgraph::build_call_graph;
+            use codesearch::callgraph_metrics::{adjacency, compute_metrics, print_metrics, CallGraphReport};
             
             println!("{}", "Building Call Graph...".cyan().bold());
             let graph = build_call_graph(&path, extensions.as_deref(), exclude.as_deref())?;
+            let metrics = compute_metrics(&adjacency(&graph));
             
             if format == "json" {
-                println!("{}", serde_json::to_string_pretty(&graph)?);
+                let report = CallGraphReport { graph: &graph, metrics };
+                println!("{}", to_versioned_json(&report, SchemaVersion::current())?);
             } else if format == "dot" {
                 println!("{}", graph.to_dot());
             } else {
                 println!("\n{}", "Call Graph Analysis:".green().bold());
                 println!("  Functions: {}", graph.nodes.len());
                 println!("  Function calls: {}", graph.edges.len());
+                println!("  Max call depth: {}", metrics.max_depth);
+
+                if !recursive_only && !dead_only {
+                    print_metrics(&metrics, 10);
+                }
                 
                 if recursive_only || !dead_only {
                     let recursive = graph.find_recursive_functions();
@@ -416,7 +990,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 
                 for pdg in &pdgs {
                     if format == "json" {
-                        println!("{}", serde_json::to_string_pretty(&pdg)?);
+                        println!("{}", to_versioned_json(&pdg, SchemaVersion::current())?);
                     } else if format == "dot" {
                         println!("{}", pdg.to_dot());
                     } else {
@@ -443,7 +1017,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                     
                     if let Some(export_path) = &export {
                         let output = if format == "json" {
-                            serde_json::to_string_pretty(&pdg)?
+                            to_versioned_json(&pdg, SchemaVersion::current())?
                         } else {
                             pdg.to_dot()
                         };
@@ -463,14 +1037,14 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             
             for result in &results {
                 if format == "json" {
-                    println!("{}", serde_json::to_string_pretty(&result)?);
+                    println!("{}", to_versioned_json(&result, SchemaVersion::current())?);
                 } else {
                     println!("\n{}", format!("{:?} Analysis:", result.graph_type).green().bold());
                     println!("  Nodes: {}", result.summary.node_count);
                     println!("  Edges: {}", result.summary.edge_count);
                     println!("\n{}", "Key Findings:".cyan());
                     for finding in &result.summary.key_findings {
-                        println!("  • {finding}");
+                        println!("  {} {finding}", theme::bullet());
                     }
                 }
                 
@@ -513,7 +1087,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                     }
                 }
             } else if format == "json" {
-                println!("{}", serde_json::to_string_pretty(&graph)?);
+                println!("{}", to_versioned_json(&graph, SchemaVersion::current())?);
             } else if format == "dot" {
                 println!("{}", graph.to_dot());
             } else {
@@ -535,6 +1109,129 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 }
             }
         }
+        Some(Commands::SemanticSearch { query, path, extensions, exclude, top_k, model, index_file, format }) => {
+            let mut final_exclude = get_default_exclude_dirs();
+            final_exclude.extend(exclude.unwrap_or_default());
+
+            #[cfg(feature = "embeddings")]
+            {
+                use codesearch::embeddings::{load_embedder, vector_store_path, VectorStore};
+
+                let model = model
+                    .or(config.semantic.model.clone())
+                    .ok_or("No embedding model: pass --model or set `model` under [semantic] in the config")?;
+                let embedder = load_embedder(&model)?;
+
+                let index = CodeIndex::new(index_file.clone());
+                index.refresh_directory(&path, extensions.as_deref(), Some(&final_exclude))?;
+                index.save()?;
+                let root = path.to_string_lossy().to_string();
+                let files: Vec<String> = index.paths().into_iter().filter(|p| p.starts_with(&root)).collect();
+
+                let store_path = vector_store_path(&index_file);
+                let mut store = VectorStore::load(&store_path)?;
+                let stats = store.update(&files, embedder.as_ref())?;
+                store.save(&store_path)?;
+                let hits = store.search(embedder.as_ref(), &query, top_k);
+
+                if format == "json" {
+                    let output = serde_json::json!({ "query": query, "hits": hits, "stats": stats });
+                    println!("{}", to_versioned_json(&output, SchemaVersion::current())?);
+                } else {
+                    if chrome_enabled(false) {
+                        eprintln!(
+                            "{}",
+                            format!("Embedded {} function(s), reused {}, dropped {}", stats.embedded, stats.reused, stats.removed).dimmed()
+                        );
+                    }
+                    if hits.is_empty() {
+                        println!("{}", "No functions indexed.".dimmed());
+                    }
+                    for hit in &hits {
+                        println!(
+                            "{:.3}  {}:{}  {}",
+                            hit.score,
+                            hit.path.blue(),
+                            hit.line.to_string().yellow(),
+                            hit.function.green()
+                        );
+                    }
+                }
+            }
+
+            #[cfg(not(feature = "embeddings"))]
+            {
+                let _ = (model, index_file);
+                eprintln!(
+                    "{}",
+                    "Embedding search not enabled (build with --features embeddings); using keyword --semantic search.".yellow()
+                );
+                let options = SearchOptions::builder()
+                    .extensions(extensions)
+                    .semantic(true)
+                    .max_total(top_k)
+                    .exclude(final_exclude)
+                    .gitignore(config.search.respect_gitignore())
+                    .rank(true)
+                    .build();
+                let results = codesearch::search::search_code(&query, &path, &options)?;
+                if format == "json" {
+                    println!("{}", to_versioned_json(&results, SchemaVersion::current())?);
+                } else if results.is_empty() {
+                    eprintln!("{}", "No matches found.".dimmed());
+                } else {
+                    print_results(&results, true, true);
+                }
+            }
+        }
+        Some(Commands::Dependents { file, path, extensions, exclude, transitive, depth, format }) => {
+            use codesearch::depgraph::{build_dependency_graph, print_dependents};
+
+            if !file.is_file() {
+                return Err(codesearch::errors::SearchError::FileNotFound { path: file }.into());
+            }
+            if depth == Some(0) {
+                return Err("--depth must be at least 1".into());
+            }
+            let graph = build_dependency_graph(&path, extensions.as_deref(), exclude.as_deref())?;
+            let target = graph.find_node(&file).ok_or_else(|| {
+                format!(
+                    "{} is not among the files analyzed under {} (check --extensions and --exclude)",
+                    file.display(),
+                    path.display()
+                )
+            })?;
+            let dependents = graph.dependents_of(target, if transitive { depth } else { Some(1) });
+
+            match format.as_str() {
+                "json" => println!("{}", to_versioned_json(&dependents, SchemaVersion::current())?),
+                "dot" => print!("{}", dependents.to_dot()),
+                "text" => print_dependents(&dependents),
+                other => return Err(format!("Unknown format: {other} (expected: text, json, dot)").into()),
+            }
+        }
+        Some(Commands::Deps { path, exclude, unused, format }) => {
+            use codesearch::deps::{analyze_dependencies, print_manifests, print_unused_report};
+
+            let report = analyze_dependencies(&path, exclude.as_deref(), &config.deps)?;
+            match format.as_str() {
+                "json" => println!("{}", to_versioned_json(&report, SchemaVersion::current())?),
+                "text" if unused => print_unused_report(&report),
+                "text" => print_manifests(&report),
+                other => return Err(format!("Unknown format: {other} (expected: text, json)").into()),
+            }
+        }
+        Some(Commands::ApiDiff { range, path, extensions, exclude, format }) => {
+            use codesearch::apidiff::{api_diff, print_api_diff, render_markdown};
+
+            let diff = api_diff(&path, &range, extensions.as_deref(), exclude.as_deref())?;
+            match format.as_str() {
+                "json" => println!("{}", to_versioned_json(&diff, SchemaVersion::current())?),
+                "markdown" => print!("{}", render_markdown(&diff)),
+                "text" => print_api_diff(&diff),
+                other => return Err(format!("Unknown format: {other} (expected: text, json, markdown)").into()),
+            }
+        }
         Some(Commands::GitHistory { query, path, max_commits, author, message, file }) => {
             use codesearch::githistory::GitSearcher;
             
@@ -570,6 +1267,95 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                     println!("  {}", result.content);
                 }
             }
+            for warning in searcher.warnings() {
+                eprintln!("{} {}", "Warning:".yellow(), warning);
+            }
+        }
+        Some(Commands::Bookmarks { action }) => {
+            let mut store = bookmarks::BookmarkStore::load_default()?;
+            match action {
+                BookmarkAction::Add { location, label } => {
+                    let (file, line) = bookmarks::parse_location(&location)?;
+                    store.add(&file, line, &label)?;
+                    store.save()?;
+                    println!("{}", format!("Bookmarked {file}:{line}").green());
+                }
+                BookmarkAction::List => {
+                    bookmarks::print_bookmarks(&store);
+                }
+                BookmarkAction::Remove { target } => {
+                    let removed = match target.parse::<usize>() {
+                        Ok(position) => store.remove_at(position).is_some(),
+                        Err(_) => {
+                            let (file, line) = bookmarks::parse_location(&target)?;
+                            store.remove(&file, line)
+                        }
+                    };
+                    if removed {
+                        store.save()?;
+                        println!("{}", format!("Removed bookmark {target}").green());
+                    } else {
+                        println!("{}", format!("No bookmark matching {target}").yellow());
+                    }
+                }
+                BookmarkAction::Clear => {
+                    store.clear();
+                    store.save()?;
+                    println!("{}", "All bookmarks cleared.".green());
+                }
+            }
+        }
+        Some(Commands::Cache { action }) => {
+            let search_cache = cache::SearchCache::load(Path::new(cache::DEFAULT_CACHE_FILE));
+            match action {
+                CacheAction::Clear => {
+                    let cleared = search_cache.stats()["result_entries"];
+                    match std::fs::remove_file(cache::DEFAULT_CACHE_FILE) {
+                        Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+                        _ => {}
+                    }
+                    println!("{}", format!("Cleared {cleared} cached search(es).").green());
+                }
+                CacheAction::Stats { format } => {
+                    let size = std::fs::metadata(cache::DEFAULT_CACHE_FILE).map_or(0, |m| m.len());
+                    let stats = search_cache.stats();
+                    match format.as_str() {
+                        "json" => {
+                            let mut json = serde_json::to_value(&stats)?;
+                            json["file"] = cache::DEFAULT_CACHE_FILE.into();
+                            json["size_bytes"] = size.into();
+                            println!("{}", serde_json::to_string_pretty(&json)?);
+                        }
+                        "text" => {
+                            println!("{}", format!("Search cache: {}", cache::DEFAULT_CACHE_FILE).cyan().bold());
+                            println!("  Cached searches: {}", stats["result_entries"].to_string().green());
+                            println!("  Cached lines:    {}", stats["content_entries"]);
+                            println!("  Size on disk:    {}", format_size(size));
+                            if stats["invalidated_by_version"] > 0 {
+                                println!("  Dropped as written by another version: {}", stats["invalidated_by_version"]);
+                            }
+                        }
+                        other => return Err(format!("Unknown format: {other} (expected: text, json)").into()),
+                    }
+                }
+            }
+        }
+        Some(Commands::Config { action: ConfigAction::Show { format, .. } }) => {
+            match format.as_str() {
+                "toml" => {
+                    match &config.source {
+                        Some(path) => println!("# Effective configuration: {} over the defaults", path.display()),
+                        None => println!("# Effective configuration: no config file, the defaults"),
+                    }
+                    print!("{}", toml::to_string(&config)?);
+                }
+                "json" => {
+                    let mut json = serde_json::to_value(&config)?;
+                    json["source"] = config.source.as_ref().map(|path| path.display().to_string()).into();
+                    println!("{}", serde_json::to_string_pretty(&json)?);
+                }
+                other => return Err(format!("Unknown format: {other} (expected: toml, json)").into()),
+            }
         }
         Some(Commands::Remote { query, repo, extensions, token, github, language, max_results }) => {
             use codesearch::remote::RemoteSearcher;
@@ -598,21 +1384,121 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 return Ok(());
             }
         }
-        Some(Commands::Languages) => {
-            analysis::list_supported_languages()?;
+        Some(Commands::Report { path, extensions, exclude, per_directory, depth, export_dir, format, verbose }) => {
+            use codesearch::parser::ExtractionContext;
+            use codesearch::report::{collect_report_inputs, export_pages, render_markdown, summarize_directories};
+
+            let timings = if verbose { Timings::enabled() } else { Timings::disabled() };
+            let depth = if per_directory { depth } else { 0 };
+            let context = ExtractionContext::build_cached_timed(&path, extensions.as_deref(), exclude.as_deref(), &timings)?;
+            let inputs = collect_report_inputs(&path, &context, extensions.as_deref(), exclude.as_deref(), depth, &timings)?;
+            let summaries = timings.time(Phase::Aggregate, || summarize_directories(&inputs, depth));
+
+            if let Some(dir) = export_dir {
+                let written = timings.time(Phase::Render, || export_pages(&summaries, &dir))?;
+                println!("{}", format!("Wrote {} report page(s) to {}", written.len(), dir.display()).green());
+            } else if format == "json" {
+                let output = serde_json::json!({ "depth": depth, "directories": summaries });
+                let output = timings.time(Phase::Render, || to_versioned_json(&WithMeta::new(output, timings.meta()), SchemaVersion::current()))?;
+                println!("{output}");
+            } else {
+                let markdown: String = timings.time(Phase::Render, || summaries.iter().map(render_markdown).collect());
+                println!("# Report: {}\n", path.display());
+                print!("{markdown}");
+            }
+            timings.print_table();
+        }
+        Some(Commands::PrivacyScan { path, extensions, exclude, sensitive, format }) => {
+            use codesearch::privacy::{SensitiveNames, findings_to_json, findings_to_sarif, print_findings, scan_path};
+
+            let names = SensitiveNames::from_config(&config.privacy, sensitive.as_deref().unwrap_or_default());
+            let findings = scan_path(&path, extensions.as_deref(), exclude.as_deref(), &names)?;
+
+            match format.as_str() {
+                "json" => println!("{}", to_versioned_json(&findings_to_json(&findings), SchemaVersion::current())?),
+                "sarif" => println!("{}", serde_json::to_string_pretty(&findings_to_sarif(&findings))?),
+                _ => print_findings(&findings),
+            }
+        }
+        Some(Commands::Hygiene { path, extensions, exclude, top, fix, format }) => {
+            use codesearch::hygiene::{fix_files, print_report, scan_path};
+
+            let mut report = scan_path(&path, extensions.as_deref(), exclude.as_deref())?;
+            if fix {
+                let changed = fix_files(&path, &report)?;
+                eprintln!("{} {} file(s)", "Fixed".green().bold(), changed);
+                report = scan_path(&path, extensions.as_deref(), exclude.as_deref())?;
+            }
+
+            if format == "json" {
+                println!("{}", to_versioned_json(&report, SchemaVersion::current())?);
+            } else {
+                print_report(&report, top);
+            }
+        }
+        Some(Commands::Lint { path, extensions, exclude, rules, baseline, write_baseline, format, export, fail_on }) => {
+            use codesearch::lint::{baseline_path, findings_to_json, lint_path, load_rules, print_findings, LintBaseline, LintLevel, LintSetup, Linter, RuleRegistry};
+
+            if let Some(file) = write_baseline {
+                let baseline = baseline_path(&path, extensions.as_deref(), exclude.as_deref())?;
+                baseline.save(&file)?;
+                eprintln!(
+                    "{} {} file(s), {} function(s) to {}",
+                    "Baseline recorded:".green(),
+                    baseline.files.len(),
+                    baseline.functions.len(),
+                    file.display()
+                );
+                return Ok(());
+            }
+            // Check the arguments before linting, not after
+            let fail_on: Option<LintLevel> = fail_on.as_deref().map(str::parse).transpose()?;
+            let sarif = format == "sarif" || export.is_some();
+            if !sarif && format != "json" && format != "text" {
+                return Err(format!("Unknown format: {format} (expected: text, json, sarif)").into());
+            }
+
+            let setup = LintSetup { baseline: baseline.as_deref().map(LintBaseline::load).transpose()?.map(Arc::new) };
+            let linter = Linter::new(load_rules(&rules)?, &RuleRegistry::default(), &setup)?;
+            let findings = lint_path(&path, extensions.as_deref(), exclude.as_deref(), &linter)?;
+            match format.as_str() {
+                _ if sarif => write_sarif(&findings, export.as_deref())?,
+                "json" => println!("{}", to_versioned_json(&findings_to_json(&findings), SchemaVersion::current())?),
+                _ => print_findings(&findings),
+            }
+            if let Some(level) = fail_on {
+                let failing = findings.iter().filter(|f| f.level >= level).count();
+                if failing > 0 {
+                    return Err(GateFailure::new(exit_code::LINT_FAILED, format!("{failing} finding(s) at {} or above", level.as_str())).into());
+                }
+            }
+        }
+        Some(Commands::Languages { format }) => {
+            if format == "json" {
+                println!("{}", serde_json::to_string_pretty(&analysis::language_table(&config.complexity))?);
+            } else {
+                analysis::list_supported_languages()?;
+            }
         }
-        Some(Commands::McpServer) => {
+        Some(Commands::McpServer { max_concurrent, call_timeout, max_result_bytes }) => {
             #[cfg(feature = "mcp")]
             {
+                use codesearch::mcp::state::ServerLimits;
                 use tokio::runtime::Runtime;
+                let limits = ServerLimits {
+                    max_concurrent,
+                    call_timeout: std::time::Duration::from_secs(call_timeout),
+                    max_result_bytes,
+                };
                 let rt = Runtime::new()?;
-                rt.block_on(mcp::start_mcp_server())?;
+                rt.block_on(mcp::start_mcp_server(limits))?;
             }
             #[cfg(not(feature = "mcp"))]
             {
+                let _ = (max_concurrent, call_timeout, max_result_bytes);
                 eprintln!("MCP server support not enabled. Build with: cargo build --features mcp");
                 eprintln!("Or add to Cargo.toml: [features] default = [\"mcp\"]");
-                std::process::exit(1);
+                std::process::exit(exit_code::ERROR.into());
             }
         }
         None => {
@@ -3,23 +3,24 @@
 //! Provides MCP server functionality for code search operations.
 //! 
 //! This module is organized into sub-modules for better maintainability:
-//! - `schemas`: JSON Schema implementations for MCP types
 //! - `params`: Parameter structures for MCP tools
 //! - `tools`: Tool implementation functions
+//! - `state`: Shared server state (uptime, index, in-flight calls)
 
-#[cfg(feature = "mcp")]
-mod schemas;
 #[cfg(feature = "mcp")]
 mod params;
 #[cfg(feature = "mcp")]
 mod tools;
+#[cfg(feature = "mcp")]
+pub mod state;
 
 #[cfg(feature = "mcp")]
 pub use params::*;
 
 #[cfg(feature = "mcp")]
 use rmcp::{
-    tool, tool_router, ServerHandler,
+    tool, tool_handler, tool_router, ServerHandler,
+    model::{Implementation, ServerCapabilities, ServerInfo},
     handler::server::tool::ToolRouter,
     handler::server::wrapper::{Parameters, Json},
     transport::io::stdio,
@@ -27,30 +28,37 @@ use rmcp::{
 };
 
 #[cfg(feature = "mcp")]
-use crate::types::{FileInfo, SearchResult};
+use state::{ServerLimits, ServerState, DEFAULT_INDEX_FILE, SHUTDOWN_DEADLINE};
+#[cfg(feature = "mcp")]
+use std::path::PathBuf;
+#[cfg(feature = "mcp")]
+use std::sync::Arc;
 
 #[cfg(feature = "mcp")]
 #[derive(Debug, Clone)]
 pub struct CodeSearchMcpService {
     tool_router: ToolRouter<Self>,
+    state: Arc<ServerState>,
 }
 
 #[cfg(feature = "mcp")]
 #[tool_router]
 impl CodeSearchMcpService {
-    pub fn new() -> Self {
+    pub fn new(state: Arc<ServerState>) -> Self {
         Self {
             tool_router: Self::tool_router(),
+            state,
         }
     }
 
     /// Search for text patterns in code files with advanced options
-    #[tool(description = "Search for text patterns in code files with advanced options like fuzzy matching, regex, and filtering")]
+    #[tool(description = "Search for text patterns in code files with advanced options like fuzzy matching, regex, and filtering. max_results caps matches per file; max_total caps the whole result list after ranking. kind selects regex (default), literal, fuzzy or semantic matching. Results over the server's size budget come back as {results, truncation}")]
     pub async fn search_code(
         &self,
         params: Parameters<SearchCodeParams>,
-    ) -> Json<Vec<SearchResult>> {
-        tools::search_code_tool(params).await
+    ) -> Json<serde_json::Value> {
+        let _call = self.state.begin_call();
+        Json(self.state.run_limited(move || tools::search_code_tool(params).0).await)
     }
 
     /// List all searchable files in a directory
@@ -58,17 +66,19 @@ impl CodeSearchMcpService {
     pub async fn list_files(
         &self,
         params: Parameters<ListFilesParams>,
-    ) -> Json<Vec<FileInfo>> {
-        tools::list_files_tool(params).await
+    ) -> Json<serde_json::Value> {
+        let _call = self.state.begin_call();
+        Json(self.state.run_limited(move || tools::list_files_tool(params).0).await)
     }
 
     /// Analyze codebase metrics and statistics
-    #[tool(description = "Analyze codebase metrics and statistics. Returns JSON with file counts, line counts, and code patterns")]
+    #[tool(description = "Analyze codebase metrics and statistics. Returns JSON with file, line and size totals, a per-language breakdown, function/class/comment counts and the largest files")]
     pub async fn analyze_codebase(
         &self,
         params: Parameters<AnalyzeCodebaseParams>,
     ) -> Json<serde_json::Value> {
-        tools::analyze_codebase_tool(params).await
+        let _call = self.state.begin_call();
+        Json(self.state.run_limited(move || tools::analyze_codebase_tool(params).0).await)
     }
 
     /// Detect code complexity issues
@@ -77,16 +87,18 @@ impl CodeSearchMcpService {
         &self,
         params: Parameters<ComplexityParams>,
     ) -> Json<serde_json::Value> {
-        tools::detect_complexity_tool(params).await
+        let _call = self.state.begin_call();
+        Json(self.state.run_limited(move || tools::detect_complexity_tool(params).0).await)
     }
 
     /// Detect duplicate code blocks
-    #[tool(description = "Detect duplicate code blocks. Returns pairs of similar code sections")]
+    #[tool(description = "Detect duplicate code blocks. Returns pairs of similar code sections, or with cluster=true one cluster per duplicated block listing every copy")]
     pub async fn detect_duplicates(
         &self,
         params: Parameters<DuplicatesParams>,
     ) -> Json<serde_json::Value> {
-        tools::detect_duplicates_tool(params).await
+        let _call = self.state.begin_call();
+        Json(self.state.run_limited(move || tools::detect_duplicates_tool(params).0).await)
     }
 
     /// Detect dead code
@@ -95,7 +107,8 @@ impl CodeSearchMcpService {
         &self,
         params: Parameters<DeadcodeParams>,
     ) -> Json<serde_json::Value> {
-        tools::detect_deadcode_tool(params).await
+        let _call = self.state.begin_call();
+        Json(self.state.run_limited(move || tools::detect_deadcode_tool(params).0).await)
     }
 
     /// Detect circular dependencies
@@ -104,22 +117,99 @@ impl CodeSearchMcpService {
         &self,
         params: Parameters<CircularParams>,
     ) -> Json<serde_json::Value> {
-        tools::detect_circular_tool(params).await
+        let _call = self.state.begin_call();
+        Json(self.state.run_limited(move || tools::detect_circular_tool(params).0).await)
+    }
+
+    /// Report server health and capabilities
+    #[tool(description = "Report server version, uptime, allowed roots, enabled features, resource limits, in-flight calls, and index/cache statistics")]
+    pub async fn server_info(&self) -> Json<serde_json::Value> {
+        let _call = self.state.begin_call();
+        tools::server_info_tool(&self.state)
+    }
+
+    /// Incrementally re-index a root directory
+    #[tool(description = "Incrementally re-index a directory. Returns counts of files added, updated, and removed")]
+    pub async fn refresh_index(
+        &self,
+        params: Parameters<RefreshIndexParams>,
+    ) -> Json<serde_json::Value> {
+        let _call = self.state.begin_call();
+        let state = self.state.clone();
+        Json(self.state.run_limited(move || tools::refresh_index_tool(&state, params).0).await)
     }
 }
 
 #[cfg(feature = "mcp")]
+#[tool_handler]
 impl ServerHandler for CodeSearchMcpService {
-    fn get_tool_router(&self) -> &ToolRouter<Self> {
-        &self.tool_router
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            server_info: Implementation::from_build_env(),
+            ..Default::default()
+        }
     }
 }
 
 /// Start the MCP server
+///
+/// Runs until the client disconnects or SIGINT/SIGTERM is received. On a signal,
+/// in-flight tool calls get up to [`SHUTDOWN_DEADLINE`] to finish, then the rest
+/// are cancelled and the index is flushed to disk. Every tool call except
+/// `server_info` runs within `limits`.
 #[cfg(feature = "mcp")]
-pub async fn start_mcp_server() -> Result<(), Box<dyn std::error::Error>> {
-    let service = CodeSearchMcpService::new();
+pub async fn start_mcp_server(limits: ServerLimits) -> Result<(), Box<dyn std::error::Error>> {
+    let state = Arc::new(ServerState::with_limits(
+        vec![std::env::current_dir()?],
+        PathBuf::from(DEFAULT_INDEX_FILE),
+        limits,
+    ));
+    let service = CodeSearchMcpService::new(state.clone());
     let transport = stdio();
-    serve_server(service, transport).await?;
+    let running = serve_server(service, transport).await?;
+
+    let cancel = running.cancellation_token();
+    let shutdown_state = state.clone();
+    tokio::spawn(async move {
+        shutdown_signal().await;
+        if !shutdown_state.drain(SHUTDOWN_DEADLINE).await {
+            eprintln!(
+                "Shutdown deadline reached with {} tool call(s) still running",
+                shutdown_state.in_flight()
+            );
+        }
+        shutdown_state.cancel_calls();
+        cancel.cancel();
+    });
+
+    running.waiting().await?;
+    state.flush()?;
     Ok(())
 }
+
+/// Resolve when SIGINT or (on Unix) SIGTERM is received
+#[cfg(feature = "mcp")]
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(_) => std::future::pending::<()>().await,
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
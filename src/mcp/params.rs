@@ -11,7 +11,7 @@ pub struct SearchCodeParams {
     #[serde(default)]
     pub path: Option<String>,
     /// File extensions to include (e.g., ["rs", "py", "js"])
-    #[serde(default)]
+    #[serde(default, deserialize_with = "crate::options::deserialize_extensions")]
     pub extensions: Option<Vec<String>>,
     /// Case-insensitive search
     #[serde(default)]
@@ -19,12 +19,21 @@ pub struct SearchCodeParams {
     /// Enable fuzzy search (handles typos and variations)
     #[serde(default)]
     pub fuzzy: Option<bool>,
-    /// Fuzzy search threshold (0.0 = exact match, 1.0 = very loose)
+    /// Minimum fuzzy similarity, 0.0-1.0: higher is stricter (0.0 = any match, 1.0 = the query verbatim)
     #[serde(default)]
     pub fuzzy_threshold: Option<f64>,
-    /// Maximum number of results per file
+    /// How to interpret the query: regex (default), literal, fuzzy or semantic; literal and semantic can't be combined with `fuzzy: true`
+    #[serde(default)]
+    pub kind: Option<QueryKindParam>,
+    /// Only match the query as a whole word
+    #[serde(default)]
+    pub word_boundary: Option<bool>,
+    /// Maximum number of matching lines per file (default: 10); the total across files can exceed this
     #[serde(default)]
     pub max_results: Option<usize>,
+    /// Maximum number of results overall, applied after ranking (default: unlimited)
+    #[serde(default)]
+    pub max_total: Option<usize>,
     /// Exclude directories (e.g., ["target", "node_modules"])
     #[serde(default)]
     pub exclude: Option<Vec<String>>,
@@ -33,13 +42,23 @@ pub struct SearchCodeParams {
     pub rank: Option<bool>,
 }
 
+/// Query interpretation accepted by `search_code`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum QueryKindParam {
+    Regex,
+    Literal,
+    Fuzzy,
+    Semantic,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ListFilesParams {
     /// Directory to scan (default: current directory)
     #[serde(default)]
     pub path: Option<String>,
     /// File extensions to include (e.g., ["rs", "py", "js"])
-    #[serde(default)]
+    #[serde(default, deserialize_with = "crate::options::deserialize_extensions")]
     pub extensions: Option<Vec<String>>,
     /// Exclude directories (e.g., ["target", "node_modules"])
     #[serde(default)]
@@ -52,7 +71,7 @@ pub struct AnalyzeCodebaseParams {
     #[serde(default)]
     pub path: Option<String>,
     /// File extensions to include (e.g., ["rs", "py", "js"])
-    #[serde(default)]
+    #[serde(default, deserialize_with = "crate::options::deserialize_extensions")]
     pub extensions: Option<Vec<String>>,
     /// Exclude directories (e.g., ["target", "node_modules"])
     #[serde(default)]
@@ -65,7 +84,7 @@ pub struct ComplexityParams {
     #[serde(default)]
     pub path: Option<String>,
     /// File extensions to include (e.g., ["rs", "py", "js"])
-    #[serde(default)]
+    #[serde(default, deserialize_with = "crate::options::deserialize_extensions")]
     pub extensions: Option<Vec<String>>,
     /// Exclude directories (e.g., ["target", "node_modules"])
     #[serde(default)]
@@ -84,7 +103,7 @@ pub struct DuplicatesParams {
     #[serde(default)]
     pub path: Option<String>,
     /// File extensions to include (e.g., ["rs", "py", "js"])
-    #[serde(default)]
+    #[serde(default, deserialize_with = "crate::options::deserialize_extensions")]
     pub extensions: Option<Vec<String>>,
     /// Exclude directories (e.g., ["target", "node_modules"])
     #[serde(default)]
@@ -95,6 +114,9 @@ pub struct DuplicatesParams {
     /// Similarity threshold (0.0-1.0, default: 0.9)
     #[serde(default)]
     pub similarity: Option<f64>,
+    /// Group every copy of a block into one cluster instead of returning pairs
+    #[serde(default)]
+    pub cluster: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -103,7 +125,7 @@ pub struct DeadcodeParams {
     #[serde(default)]
     pub path: Option<String>,
     /// File extensions to include (e.g., ["rs", "py", "js"])
-    #[serde(default)]
+    #[serde(default, deserialize_with = "crate::options::deserialize_extensions")]
     pub extensions: Option<Vec<String>>,
     /// Exclude directories (e.g., ["target", "node_modules"])
     #[serde(default)]
@@ -116,9 +138,16 @@ pub struct CircularParams {
     #[serde(default)]
     pub path: Option<String>,
     /// File extensions to include (e.g., ["rs", "py", "js"])
-    #[serde(default)]
+    #[serde(default, deserialize_with = "crate::options::deserialize_extensions")]
     pub extensions: Option<Vec<String>>,
     /// Exclude directories (e.g., ["target", "node_modules"])
     #[serde(default)]
     pub exclude: Option<Vec<String>>,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RefreshIndexParams {
+    /// Directory to re-index (default: current directory)
+    #[serde(default)]
+    pub path: Option<String>,
+}
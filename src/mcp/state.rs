@@ -0,0 +1,468 @@
+//! Shared state for a long-running MCP server
+
+use crate::cache::get_search_cache;
+use crate::index::{CodeIndex, IndexStats, RefreshStats};
+use crate::types::SCHEMA_VERSION;
+use serde::{Serialize, Serializer};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+use tokio_util::sync::CancellationToken;
+
+/// Default location of the index maintained by the server
+pub const DEFAULT_INDEX_FILE: &str = ".codesearch/index.json";
+
+/// How long shutdown waits for in-flight tool calls before giving up
+pub const SHUTDOWN_DEADLINE: Duration = Duration::from_secs(10);
+
+/// Resource limits applied to every tool call except `server_info`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ServerLimits {
+    /// Tool calls executing at once; further calls wait for a slot
+    pub max_concurrent: usize,
+    /// Wall time per call, including the wait for a slot
+    #[serde(rename = "call_timeout_ms", serialize_with = "serialize_millis")]
+    pub call_timeout: Duration,
+    /// Serialized size above which a result is truncated
+    pub max_result_bytes: usize,
+}
+
+impl Default for ServerLimits {
+    fn default() -> Self {
+        Self {
+            max_concurrent: 4,
+            call_timeout: Duration::from_secs(60),
+            max_result_bytes: 1_000_000,
+        }
+    }
+}
+
+fn serialize_millis<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u128(duration.as_millis())
+}
+
+/// State shared by all tool calls for the lifetime of the server
+#[derive(Debug)]
+pub struct ServerState {
+    started_at: Instant,
+    roots: Vec<PathBuf>,
+    index: CodeIndex,
+    in_flight: AtomicUsize,
+    limits: ServerLimits,
+    slots: Arc<Semaphore>,
+    /// Parent of every call's token; cancelled on shutdown
+    cancel: CancellationToken,
+}
+
+/// Response of the `server_info` tool
+#[derive(Debug, Clone, Serialize)]
+pub struct ServerInfo {
+    pub schema_version: String,
+    pub name: String,
+    pub version: String,
+    pub uptime_secs: u64,
+    pub allowed_roots: Vec<String>,
+    pub features: HashMap<String, bool>,
+    pub in_flight_calls: usize,
+    /// Calls holding an execution slot (the rest of `in_flight_calls` are waiting)
+    pub running_calls: usize,
+    pub limits: ServerLimits,
+    pub index: IndexStats,
+    pub cache: HashMap<String, usize>,
+}
+
+/// Response of the `refresh_index` tool
+#[derive(Debug, Clone, Serialize)]
+pub struct RefreshReport {
+    pub schema_version: String,
+    pub path: String,
+    #[serde(flatten)]
+    pub stats: RefreshStats,
+    pub duration_ms: u128,
+}
+
+/// Marks a tool call as in flight until dropped
+pub struct CallGuard<'a> {
+    counter: &'a AtomicUsize,
+}
+
+impl Drop for CallGuard<'_> {
+    fn drop(&mut self) {
+        self.counter.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl ServerState {
+    /// Create server state rooted at `roots`, backed by the index at `index_path`
+    pub fn new(roots: Vec<PathBuf>, index_path: PathBuf) -> Self {
+        Self::with_limits(roots, index_path, ServerLimits::default())
+    }
+
+    /// [`ServerState::new`] with explicit resource limits
+    pub fn with_limits(roots: Vec<PathBuf>, index_path: PathBuf, limits: ServerLimits) -> Self {
+        Self {
+            started_at: Instant::now(),
+            roots,
+            index: CodeIndex::new(index_path),
+            in_flight: AtomicUsize::new(0),
+            limits,
+            slots: Arc::new(Semaphore::new(limits.max_concurrent.max(1))),
+            cancel: CancellationToken::new(),
+        }
+    }
+
+    pub fn limits(&self) -> ServerLimits {
+        self.limits
+    }
+
+    /// Run `call` on a blocking thread within the server's limits
+    ///
+    /// The call waits for one of `max_concurrent` slots, and has `call_timeout`
+    /// for waiting plus running. A call that times out, or is cancelled by
+    /// shutdown, gets a structured error; its work can't be interrupted, so it
+    /// keeps its slot until it actually finishes. Results larger than
+    /// `max_result_bytes` are truncated with a `truncation` marker.
+    pub async fn run_limited<T, F>(&self, call: F) -> serde_json::Value
+    where
+        T: Serialize + Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let token = self.cancel.child_token();
+        let slots = self.slots.clone();
+        let run = async move {
+            let permit = slots.acquire_owned().await.ok()?;
+            tokio::task::spawn_blocking(move || {
+                let _permit = permit;
+                serde_json::to_value(call()).unwrap_or_default()
+            })
+            .await
+            .ok()
+        };
+
+        let outcome = tokio::select! {
+            value = run => value.ok_or("Tool call failed before producing a result"),
+            _ = tokio::time::sleep(self.limits.call_timeout) => {
+                token.cancel();
+                return limit_error(
+                    "timeout",
+                    format!("Tool call exceeded its {}ms time budget", self.limits.call_timeout.as_millis()),
+                );
+            }
+            _ = token.cancelled() => Err("Tool call cancelled by server shutdown"),
+        };
+        match outcome {
+            Ok(value) => truncate_to_budget(value, self.limits.max_result_bytes),
+            Err(message) => limit_error("cancelled", message.to_string()),
+        }
+    }
+
+    /// Cancel every waiting and running call
+    pub fn cancel_calls(&self) {
+        self.cancel.cancel();
+    }
+
+    /// Register a tool call; the call counts as in flight while the guard lives
+    pub fn begin_call(&self) -> CallGuard<'_> {
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        CallGuard {
+            counter: &self.in_flight,
+        }
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::SeqCst)
+    }
+
+    /// Version, uptime, roots, features, and index/cache statistics
+    pub fn info(&self) -> ServerInfo {
+        let features = [
+            ("mcp", cfg!(feature = "mcp")),
+            ("ast", true),
+            ("tui", false),
+        ]
+        .into_iter()
+        .map(|(name, enabled)| (name.to_string(), enabled))
+        .collect();
+
+        ServerInfo {
+            schema_version: SCHEMA_VERSION.to_string(),
+            name: env!("CARGO_PKG_NAME").to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            uptime_secs: self.started_at.elapsed().as_secs(),
+            allowed_roots: self.roots.iter().map(|r| r.display().to_string()).collect(),
+            features,
+            // The server_info call itself is in flight
+            in_flight_calls: self.in_flight().saturating_sub(1),
+            running_calls: self.limits.max_concurrent.max(1) - self.slots.available_permits(),
+            limits: self.limits,
+            index: self.index.get_stats(),
+            cache: get_search_cache().stats(),
+        }
+    }
+
+    /// Incrementally re-index `path` and report what changed
+    pub fn refresh_index(&self, path: &Path) -> Result<RefreshReport, Box<dyn std::error::Error>> {
+        let start = Instant::now();
+        let stats = self.index.refresh_directory(path, None, None)?;
+        Ok(RefreshReport {
+            schema_version: SCHEMA_VERSION.to_string(),
+            path: path.display().to_string(),
+            stats,
+            duration_ms: start.elapsed().as_millis(),
+        })
+    }
+
+    /// Wait until no tool calls are in flight, or the deadline passes
+    ///
+    /// Returns whether all calls completed.
+    pub async fn drain(&self, deadline: Duration) -> bool {
+        let start = Instant::now();
+        while self.in_flight() > 0 {
+            if start.elapsed() >= deadline {
+                return false;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        true
+    }
+
+    /// Persist the index to disk
+    pub fn flush(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.index.save()
+    }
+}
+
+fn limit_error(limit: &str, message: String) -> serde_json::Value {
+    serde_json::json!({
+        "schema_version": SCHEMA_VERSION,
+        "error": message,
+        "limit": limit,
+    })
+}
+
+/// Cut `value` down to `max_bytes` of JSON, recording what was dropped
+///
+/// Arrays keep their leading items and are wrapped as `{"results": [...]}`;
+/// objects have their largest array field cut. A result with nothing to cut
+/// is replaced by an error rather than returned partially.
+pub fn truncate_to_budget(value: serde_json::Value, max_bytes: usize) -> serde_json::Value {
+    let size = json_size(&value);
+    if size <= max_bytes {
+        return value;
+    }
+
+    let (mut object, field) = match value {
+        serde_json::Value::Array(items) => {
+            let mut object = serde_json::Map::new();
+            object.insert("results".to_string(), serde_json::Value::Array(items));
+            (object, Some("results".to_string()))
+        }
+        serde_json::Value::Object(object) => {
+            let field = object
+                .iter()
+                .filter(|(_, v)| v.is_array())
+                .max_by_key(|(_, v)| json_size(v))
+                .map(|(k, _)| k.clone());
+            (object, field)
+        }
+        _ => (serde_json::Map::new(), None),
+    };
+    let Some(field) = field else {
+        return limit_error("result_size", format!("Result of {size} bytes exceeds the {max_bytes} byte budget"));
+    };
+
+    let items = match object.remove(&field) {
+        Some(serde_json::Value::Array(items)) => items,
+        _ => Vec::new(),
+    };
+    let total = items.len();
+    // Room left for the items once the rest of the document and the marker are in
+    let overhead = json_size(&serde_json::Value::Object(object.clone())) + 160 + field.len();
+    let mut budget = max_bytes.saturating_sub(overhead);
+    let kept: Vec<serde_json::Value> = items
+        .into_iter()
+        .take_while(|item| {
+            let item_size = json_size(item) + 1;
+            let fits = item_size <= budget;
+            budget = budget.saturating_sub(item_size);
+            fits
+        })
+        .collect();
+
+    object.insert(
+        "truncation".to_string(),
+        serde_json::json!({
+            "field": field,
+            "kept": kept.len(),
+            "total": total,
+            "max_result_bytes": max_bytes,
+        }),
+    );
+    object.insert(field, serde_json::Value::Array(kept));
+    object
+        .entry("schema_version")
+        .or_insert_with(|| SCHEMA_VERSION.into());
+    serde_json::Value::Object(object)
+}
+
+fn json_size(value: &serde_json::Value) -> usize {
+    serde_json::to_vec(value).map_or(0, |bytes| bytes.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_server_info_shape() {
+        let dir = tempdir().unwrap();
+        let state = ServerState::new(vec![dir.path().to_path_buf()], dir.path().join("index.json"));
+        let _call = state.begin_call();
+
+        let info = serde_json::to_value(state.info()).unwrap();
+        assert_eq!(info["schema_version"], SCHEMA_VERSION);
+        assert_eq!(info["version"], env!("CARGO_PKG_VERSION"));
+        assert!(info["uptime_secs"].is_u64());
+        assert_eq!(info["allowed_roots"].as_array().unwrap().len(), 1);
+        assert_eq!(info["features"]["mcp"], true);
+        assert_eq!(info["in_flight_calls"], 0);
+        assert_eq!(info["running_calls"], 0);
+        assert_eq!(info["limits"]["max_concurrent"], 4);
+        assert_eq!(info["limits"]["call_timeout_ms"], 60_000);
+        assert!(info["index"]["total_files"].is_u64());
+        assert!(info["cache"].is_object());
+    }
+
+    #[test]
+    fn test_refresh_index_picks_up_new_file() {
+        let dir = tempdir().unwrap();
+        let src = dir.path().join("src");
+        fs::create_dir(&src).unwrap();
+        fs::write(src.join("a.rs"), "fn a() {}").unwrap();
+
+        let state = ServerState::new(vec![dir.path().to_path_buf()], dir.path().join("index.json"));
+        assert_eq!(state.refresh_index(&src).unwrap().stats.added, 1);
+
+        fs::write(src.join("b.rs"), "fn b() {}").unwrap();
+        let report = state.refresh_index(&src).unwrap();
+        assert_eq!(report.stats.added, 1);
+        assert_eq!(report.stats.updated, 0);
+        assert_eq!(state.info().index.total_files, 2);
+    }
+
+    #[test]
+    fn test_call_guard_tracks_in_flight_calls() {
+        let dir = tempdir().unwrap();
+        let state = ServerState::new(Vec::new(), dir.path().join("index.json"));
+        {
+            let _a = state.begin_call();
+            let _b = state.begin_call();
+            assert_eq!(state.in_flight(), 2);
+        }
+        assert_eq!(state.in_flight(), 0);
+    }
+
+    #[test]
+    fn test_flush_writes_index() {
+        let dir = tempdir().unwrap();
+        let index_path = dir.path().join(".codesearch/index.json");
+        let state = ServerState::new(Vec::new(), index_path.clone());
+        state.flush().unwrap();
+        assert!(index_path.exists());
+    }
+
+    fn limited_state(dir: &Path, max_concurrent: usize, call_timeout: Duration, max_result_bytes: usize) -> Arc<ServerState> {
+        let limits = ServerLimits { max_concurrent, call_timeout, max_result_bytes };
+        Arc::new(ServerState::with_limits(Vec::new(), dir.join("index.json"), limits))
+    }
+
+    /// A tool call that sleeps, recording how many calls ran at the same time
+    fn slow_call(running: Arc<AtomicUsize>, peak: Arc<AtomicUsize>, sleep: Duration) -> impl FnOnce() -> usize + Send + 'static {
+        move || {
+            let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+            peak.fetch_max(now, Ordering::SeqCst);
+            std::thread::sleep(sleep);
+            running.fetch_sub(1, Ordering::SeqCst);
+            now
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_calls_are_capped() {
+        let dir = tempdir().unwrap();
+        let state = limited_state(dir.path(), 2, Duration::from_secs(10), 1_000_000);
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        let calls: Vec<_> = (0..6)
+            .map(|_| {
+                let state = state.clone();
+                let call = slow_call(running.clone(), peak.clone(), Duration::from_millis(100));
+                tokio::spawn(async move { state.run_limited(call).await })
+            })
+            .collect();
+        for call in calls {
+            assert!(call.await.unwrap().is_u64());
+        }
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+        assert_eq!(state.info().running_calls, 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_slow_call_times_out_and_keeps_its_slot() {
+        let dir = tempdir().unwrap();
+        let state = limited_state(dir.path(), 1, Duration::from_millis(100), 1_000_000);
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        let start = Instant::now();
+        let value = state.run_limited(slow_call(running.clone(), peak.clone(), Duration::from_millis(600))).await;
+        assert!(start.elapsed() < Duration::from_millis(500));
+        assert_eq!(value["limit"], "timeout");
+        assert!(value["error"].as_str().unwrap().contains("100ms"));
+
+        // The abandoned call still runs, so the next one can't get a slot in time
+        let value = state.run_limited(|| 1).await;
+        assert_eq!(value["limit"], "timeout");
+        assert_eq!(state.info().running_calls, 1);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_calls_return_an_error() {
+        let dir = tempdir().unwrap();
+        let state = limited_state(dir.path(), 1, Duration::from_secs(10), 1_000_000);
+        state.cancel_calls();
+        let value = state.run_limited(|| 1).await;
+        assert_eq!(value["limit"], "cancelled");
+    }
+
+    #[test]
+    fn test_truncate_to_budget() {
+        let items: Vec<String> = (0..100).map(|i| format!("result number {i}")).collect();
+        let value = serde_json::to_value(&items).unwrap();
+        assert_eq!(truncate_to_budget(value.clone(), 100_000), value);
+
+        let truncated = truncate_to_budget(value, 600);
+        assert!(serde_json::to_vec(&truncated).unwrap().len() <= 600);
+        assert_eq!(truncated["truncation"]["field"], "results");
+        assert_eq!(truncated["truncation"]["total"], 100);
+        let kept = truncated["results"].as_array().unwrap().len();
+        assert!(kept > 0 && kept < 100);
+        assert_eq!(truncated["truncation"]["kept"], kept);
+        assert_eq!(truncated["results"][0], "result number 0");
+
+        // Objects have their largest array cut and keep everything else
+        let report = serde_json::json!({ "schema_version": SCHEMA_VERSION, "total_items": 100, "dead_code": items });
+        let truncated = truncate_to_budget(report, 600);
+        assert_eq!(truncated["truncation"]["field"], "dead_code");
+        assert_eq!(truncated["total_items"], 100);
+
+        let value = serde_json::json!({ "text": "x".repeat(1000) });
+        assert_eq!(truncate_to_budget(value, 100)["limit"], "result_size");
+    }
+}
@@ -1,36 +1,40 @@
 //! MCP tool implementations
 
 use super::params::*;
+use super::state::ServerState;
 use crate::search::{list_files, search_code};
-use crate::types::{FileInfo, SearchOptions, SearchResult};
-use crate::{circular, complexity, deadcode, duplicates};
+use crate::types::{FileInfo, SCHEMA_VERSION, SearchOptions, SearchResult, DEFAULT_FUZZY_THRESHOLD, DEFAULT_MAX_RESULTS};
+use crate::schema::Versioned;
+use crate::{analysis, circular, complexity, deadcode, duplicates};
 use rmcp::handler::server::wrapper::{Json, Parameters};
 use std::path::PathBuf;
 
 /// Search for text patterns in code files
-pub async fn search_code_tool(params: Parameters<SearchCodeParams>) -> Json<Vec<SearchResult>> {
+pub fn search_code_tool(params: Parameters<SearchCodeParams>) -> Json<Vec<SearchResult>> {
     let params = params.0;
     let path_buf = PathBuf::from(params.path.as_deref().unwrap_or("."));
     
-    let options = SearchOptions {
-        extensions: params.extensions,
-        ignore_case: params.ignore_case.unwrap_or(false),
-        fuzzy: params.fuzzy.unwrap_or(false),
-        fuzzy_threshold: params.fuzzy_threshold.unwrap_or(0.6),
-        max_results: params.max_results.unwrap_or(10),
-        exclude: params.exclude,
-        rank: params.rank.unwrap_or(false),
-        cache: false,
-        semantic: false,
-        benchmark: false,
-        vs_grep: false,
-    };
+    // `kind` sets the matching flag; literal or semantic with `fuzzy: true` fails the query mapping
+    let kind = params.kind.unwrap_or(QueryKindParam::Regex);
+    let options = SearchOptions::builder()
+        .extensions(params.extensions)
+        .ignore_case(params.ignore_case.unwrap_or(false))
+        .fuzzy(params.fuzzy.unwrap_or(false) || kind == QueryKindParam::Fuzzy)
+        .fuzzy_threshold(params.fuzzy_threshold.unwrap_or(DEFAULT_FUZZY_THRESHOLD))
+        .literal(kind == QueryKindParam::Literal)
+        .word_boundary(params.word_boundary.unwrap_or(false))
+        .max_results(params.max_results.unwrap_or(DEFAULT_MAX_RESULTS))
+        .max_total(params.max_total)
+        .exclude(params.exclude)
+        .rank(params.rank.unwrap_or(false))
+        .semantic(kind == QueryKindParam::Semantic)
+        .build();
     
     Json(search_code(&params.query, &path_buf, &options).unwrap_or_default())
 }
 
 /// List all searchable files in a directory
-pub async fn list_files_tool(params: Parameters<ListFilesParams>) -> Json<Vec<FileInfo>> {
+pub fn list_files_tool(params: Parameters<ListFilesParams>) -> Json<Vec<FileInfo>> {
     let params = params.0;
     let path_buf = PathBuf::from(params.path.as_deref().unwrap_or("."));
     
@@ -42,100 +46,23 @@ pub async fn list_files_tool(params: Parameters<ListFilesParams>) -> Json<Vec<Fi
 }
 
 /// Analyze codebase metrics and statistics
-pub async fn analyze_codebase_tool(params: Parameters<AnalyzeCodebaseParams>) -> Json<serde_json::Value> {
+///
+/// Returns the same document as `codesearch analyze --format json`.
+pub fn analyze_codebase_tool(params: Parameters<AnalyzeCodebaseParams>) -> Json<serde_json::Value> {
     let params = params.0;
     let path_buf = PathBuf::from(params.path.as_deref().unwrap_or("."));
-    
-    let files = list_files(
+
+    let stats = analysis::compute_codebase_stats(
         &path_buf,
         params.extensions.as_deref(),
         params.exclude.as_deref(),
     ).unwrap_or_default();
-    
-    if files.is_empty() {
-        return Json(serde_json::json!({
-            "message": "No files found to analyze",
-            "files": []
-        }));
-    }
 
-    // Calculate statistics
-    let total_files = files.len();
-    let total_lines: usize = files.iter().map(|f| f.lines).sum();
-    let total_size: u64 = files.iter().map(|f| f.size).sum();
-    
-    // File type breakdown
-    let mut ext_counts: std::collections::HashMap<String, (usize, usize, u64)> = std::collections::HashMap::new();
-    for file in &files {
-        let ext = if let Some(ext) = std::path::Path::new(&file.path).extension() {
-            ext.to_string_lossy().to_string()
-        } else {
-            "no extension".to_string()
-        };
-        
-        let entry = ext_counts.entry(ext).or_insert((0, 0, 0));
-        entry.0 += 1;
-        entry.1 += file.lines;
-        entry.2 += file.size;
-    }
-
-    // Code patterns analysis
-    let patterns = vec![
-        ("functions", r"fn\s+\w+|function\s+\w+|def\s+\w+"),
-        ("classes", r"class\s+\w+"),
-        ("comments", r"//|#|/\*|<!--"),
-        ("todo", r"TODO|FIXME|HACK|XXX"),
-        ("imports", r"^import|^use|^#include|^require"),
-    ];
-
-    let mut pattern_counts = std::collections::HashMap::new();
-    for (name, pattern) in patterns {
-        if let Ok(regex) = regex::Regex::new(pattern) {
-            let mut total_matches = 0;
-            
-            for file in &files {
-                if let Ok(content) = std::fs::read_to_string(&file.path) {
-                    for line in content.lines() {
-                        if regex.is_match(line) {
-                            total_matches += 1;
-                        }
-                    }
-                }
-            }
-            pattern_counts.insert(name, total_matches);
-        }
-    }
-
-    // Largest files
-    let mut file_vec = files.clone();
-    file_vec.sort_by(|a, b| b.lines.cmp(&a.lines));
-    let largest_files: Vec<_> = file_vec.iter().take(5).map(|f| {
-        serde_json::json!({
-            "path": f.path,
-            "lines": f.lines,
-            "size": f.size
-        })
-    }).collect();
-
-    Json(serde_json::json!({
-        "total_files": total_files,
-        "total_lines": total_lines,
-        "total_size": total_size,
-        "file_types": ext_counts.iter().map(|(ext, (count, lines, size))| {
-            serde_json::json!({
-                "extension": ext,
-                "files": count,
-                "lines": lines,
-                "size": size
-            })
-        }).collect::<Vec<_>>(),
-        "patterns": pattern_counts,
-        "largest_files": largest_files
-    }))
+    Json(serde_json::to_value(Versioned::new(stats)).unwrap_or_default())
 }
 
 /// Detect code complexity issues
-pub async fn detect_complexity_tool(params: Parameters<ComplexityParams>) -> Json<serde_json::Value> {
+pub fn detect_complexity_tool(params: Parameters<ComplexityParams>) -> Json<serde_json::Value> {
     let params = params.0;
     let path_buf = PathBuf::from(params.path.as_deref().unwrap_or("."));
     
@@ -158,15 +85,36 @@ pub async fn detect_complexity_tool(params: Parameters<ComplexityParams>) -> Jso
     }
     
     Json(serde_json::json!({
+        "schema_version": SCHEMA_VERSION,
         "metrics": filtered_metrics,
         "total_files": filtered_metrics.len()
     }))
 }
 
 /// Detect duplicate code blocks
-pub async fn detect_duplicates_tool(params: Parameters<DuplicatesParams>) -> Json<serde_json::Value> {
+pub fn detect_duplicates_tool(params: Parameters<DuplicatesParams>) -> Json<serde_json::Value> {
     let params = params.0;
     let path_buf = PathBuf::from(params.path.as_deref().unwrap_or("."));
+
+    if params.cluster.unwrap_or(false) {
+        let config = duplicates::DuplicateConfig {
+            min_lines: params.min_lines.unwrap_or(5),
+            similarity_threshold: params.similarity.unwrap_or(0.9),
+            suggest_keep: false,
+            ..Default::default()
+        };
+        let clusters = duplicates::find_duplicate_clusters(
+            &path_buf,
+            params.extensions.as_deref(),
+            params.exclude.as_deref(),
+            config,
+        ).unwrap_or_default();
+        return Json(serde_json::json!({
+            "schema_version": SCHEMA_VERSION,
+            "clusters": clusters,
+            "total_clusters": clusters.len()
+        }));
+    }
     
     let duplicates = duplicates::find_duplicates(
         &path_buf,
@@ -177,13 +125,14 @@ pub async fn detect_duplicates_tool(params: Parameters<DuplicatesParams>) -> Jso
     ).unwrap_or_default();
     
     Json(serde_json::json!({
+        "schema_version": SCHEMA_VERSION,
         "duplicates": duplicates,
         "total_duplicates": duplicates.len()
     }))
 }
 
 /// Detect dead code
-pub async fn detect_deadcode_tool(params: Parameters<DeadcodeParams>) -> Json<serde_json::Value> {
+pub fn detect_deadcode_tool(params: Parameters<DeadcodeParams>) -> Json<serde_json::Value> {
     let params = params.0;
     let path_buf = PathBuf::from(params.path.as_deref().unwrap_or("."));
     
@@ -191,27 +140,45 @@ pub async fn detect_deadcode_tool(params: Parameters<DeadcodeParams>) -> Json<se
         &path_buf,
         params.extensions.as_deref(),
         params.exclude.as_deref(),
+        &deadcode::DeadCodeOptions::default(),
     ).unwrap_or_default();
-    
-    Json(serde_json::json!({
-        "dead_code": dead_code,
-        "total_items": dead_code.len()
-    }))
+
+    Json(serde_json::to_value(Versioned::new(deadcode::DeadCodeReport::new(dead_code))).unwrap_or_default())
 }
 
 /// Detect circular dependencies
-pub async fn detect_circular_tool(params: Parameters<CircularParams>) -> Json<serde_json::Value> {
+pub fn detect_circular_tool(params: Parameters<CircularParams>) -> Json<serde_json::Value> {
     let params = params.0;
     let path_buf = PathBuf::from(params.path.as_deref().unwrap_or("."));
     
-    let cycles = circular::find_circular_dependencies(
+    let cycles = circular::find_circular_calls(
         &path_buf,
         params.extensions.as_deref(),
         params.exclude.as_deref(),
     ).unwrap_or_default();
     
     Json(serde_json::json!({
+        "schema_version": SCHEMA_VERSION,
         "cycles": cycles,
         "total_cycles": cycles.len()
     }))
 }
+
+/// Report server version, uptime, roots, features, and index/cache statistics
+pub fn server_info_tool(state: &ServerState) -> Json<serde_json::Value> {
+    Json(serde_json::to_value(state.info()).unwrap_or_default())
+}
+
+/// Incrementally re-index a root and report files added/updated/removed
+pub fn refresh_index_tool(state: &ServerState, params: Parameters<RefreshIndexParams>) -> Json<serde_json::Value> {
+    let params = params.0;
+    let path_buf = PathBuf::from(params.path.as_deref().unwrap_or("."));
+
+    match state.refresh_index(&path_buf) {
+        Ok(report) => Json(serde_json::to_value(report).unwrap_or_default()),
+        Err(e) => Json(serde_json::json!({
+            "schema_version": SCHEMA_VERSION,
+            "error": e.to_string()
+        })),
+    }
+}
@@ -6,6 +6,7 @@ use memmap2::Mmap;
 use std::fs::File;
 use std::io::BufReader;
 use std::path::Path;
+use crate::lines::{read_lines, split_lines};
 
 const LARGE_FILE_THRESHOLD: u64 = 10 * 1024 * 1024; // 10 MB
 
@@ -30,11 +31,11 @@ impl FileReader {
     pub fn read_lines(&mut self) -> Box<dyn Iterator<Item = String> + '_> {
         match self {
             FileReader::Buffered(reader) => {
-                Box::new(std::io::BufRead::lines(reader).filter_map(|line| line.ok()))
+                Box::new(read_lines(reader).filter_map(|line| line.ok()))
             }
             FileReader::Mapped(mmap) => {
                 let content = String::from_utf8_lossy(mmap);
-                Box::new(content.lines().map(|s| s.to_string()).collect::<Vec<_>>().into_iter())
+                Box::new(split_lines(&content).map(|s| s.to_string()).collect::<Vec<_>>().into_iter())
             }
         }
     }
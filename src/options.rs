@@ -0,0 +1,186 @@
+//! Shared Option Handling
+//!
+//! Normalization of user-supplied option values that are accepted in several
+//! places (CLI flags, config files, MCP parameters).
+
+use crate::errors::SearchError;
+use serde::{Deserialize, Deserializer};
+use std::path::Path;
+
+/// Normalize a list of file extensions
+///
+/// Each entry may itself be comma-separated. Entries are trimmed, stripped of
+/// leading dots and lowercased, and duplicates are dropped keeping the first
+/// occurrence, so `[".rs", "RS", " rs", "py"]` becomes `["rs", "py"]`. Empty
+/// entries and entries containing a path separator are rejected, listing every
+/// offending token.
+pub fn normalize_extensions<S: AsRef<str>>(raw: &[S]) -> Result<Vec<String>, SearchError> {
+    let mut normalized: Vec<String> = Vec::new();
+    let mut invalid = Vec::new();
+
+    for token in raw.iter().flat_map(|entry| entry.as_ref().split(',')) {
+        let ext = token.trim().trim_start_matches('.').to_lowercase();
+        if ext.is_empty() || ext.contains(['/', '\\']) || ext.contains(char::is_whitespace) {
+            invalid.push(format!("{:?}", token.trim()));
+        } else if !normalized.contains(&ext) {
+            normalized.push(ext);
+        }
+    }
+
+    if invalid.is_empty() {
+        Ok(normalized)
+    } else {
+        Err(SearchError::InvalidOptions {
+            message: format!(
+                "invalid extension(s) {} (expected names like rs or .py)",
+                invalid.join(", ")
+            ),
+        })
+    }
+}
+
+/// Normalize an optional extension list in place
+pub fn normalize_extension_option(extensions: &mut Option<Vec<String>>) -> Result<(), SearchError> {
+    if let Some(raw) = extensions.take() {
+        *extensions = Some(normalize_extensions(&raw)?);
+    }
+    Ok(())
+}
+
+/// Serde helper for extension list fields: `#[serde(default, deserialize_with = "...")]`
+pub fn deserialize_extensions<'de, D>(deserializer: D) -> Result<Option<Vec<String>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let raw: Option<Vec<String>> = Option::deserialize(deserializer)?;
+    raw.map(|r| normalize_extensions(&r))
+        .transpose()
+        .map_err(serde::de::Error::custom)
+}
+
+/// Whether `path` has one of `extensions`, compared case-insensitively
+///
+/// Multi-part extensions such as `d.ts` match against the end of the file name.
+/// Tolerates un-normalized entries (a leading dot, upper case) from library callers.
+pub fn has_extension(path: &Path, extensions: &[String]) -> bool {
+    let Some(name) = path.file_name().and_then(|s| s.to_str()) else {
+        return false;
+    };
+    let name = name.to_lowercase();
+    extensions.iter().any(|e| {
+        let ext = e.trim().trim_start_matches('.').to_lowercase();
+        !ext.is_empty()
+            && name.len() > ext.len() + 1
+            && name.ends_with(&ext)
+            && name[..name.len() - ext.len()].ends_with('.')
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn normalize(raw: &[&str]) -> Vec<String> {
+        normalize_extensions(raw).unwrap()
+    }
+
+    #[test]
+    fn test_strips_dots_and_whitespace() {
+        assert_eq!(normalize(&[".rs", " py ", "..js"]), vec!["rs", "py", "js"]);
+    }
+
+    #[test]
+    fn test_lowercases() {
+        assert_eq!(normalize(&["RS", "Py", "TSX"]), vec!["rs", "py", "tsx"]);
+    }
+
+    #[test]
+    fn test_dedupes_keeping_first_occurrence() {
+        assert_eq!(normalize(&[".rs", "RS", " rs", "py", "rs"]), vec!["rs", "py"]);
+    }
+
+    #[test]
+    fn test_splits_comma_separated_entries() {
+        assert_eq!(normalize(&[".rs,RS, rs", "py"]), vec!["rs", "py"]);
+    }
+
+    #[test]
+    fn test_keeps_multi_part_extensions() {
+        assert_eq!(normalize(&[".d.ts", "tar.gz"]), vec!["d.ts", "tar.gz"]);
+    }
+
+    #[test]
+    fn test_empty_list_is_empty() {
+        assert!(normalize(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_rejects_empty_entries() {
+        let err = normalize_extensions(&["rs", " ", "."]).unwrap_err().to_string();
+        assert!(err.contains(r#""""#), "{err}");
+        assert!(err.contains(r#"".""#), "{err}");
+    }
+
+    #[test]
+    fn test_rejects_path_separators_listing_every_token() {
+        let err = normalize_extensions(&["src/rs", "py", "a\\b"]).unwrap_err().to_string();
+        assert!(err.contains("src/rs"), "{err}");
+        assert!(err.contains(r"a\\b"), "{err}");
+        assert!(!err.contains(r#""py""#), "{err}");
+    }
+
+    #[test]
+    fn test_rejects_inner_whitespace() {
+        assert!(normalize_extensions(&["r s"]).is_err());
+    }
+
+    #[test]
+    fn test_normalize_option_in_place() {
+        let mut exts = Some(vec![".RS".to_string()]);
+        normalize_extension_option(&mut exts).unwrap();
+        assert_eq!(exts, Some(vec!["rs".to_string()]));
+
+        let mut none = None;
+        normalize_extension_option(&mut none).unwrap();
+        assert_eq!(none, None);
+    }
+
+    #[test]
+    fn test_deserialize_extensions() {
+        #[derive(Deserialize)]
+        struct Params {
+            #[serde(default, deserialize_with = "deserialize_extensions")]
+            extensions: Option<Vec<String>>,
+        }
+
+        let params: Params = serde_json::from_str(r#"{"extensions": [".RS", "rs", "py"]}"#).unwrap();
+        assert_eq!(params.extensions, Some(vec!["rs".to_string(), "py".to_string()]));
+
+        let params: Params = serde_json::from_str("{}").unwrap();
+        assert_eq!(params.extensions, None);
+
+        assert!(serde_json::from_str::<Params>(r#"{"extensions": ["src/"]}"#).is_err());
+    }
+
+    #[test]
+    fn test_has_extension_is_case_insensitive() {
+        let exts = vec!["rs".to_string()];
+        assert!(has_extension(Path::new("src/main.rs"), &exts));
+        assert!(has_extension(Path::new("LEGACY.RS"), &exts));
+        assert!(!has_extension(Path::new("main.py"), &exts));
+        assert!(!has_extension(Path::new("Makefile"), &exts));
+    }
+
+    #[test]
+    fn test_has_extension_multi_part() {
+        let exts = vec!["d.ts".to_string()];
+        assert!(has_extension(Path::new("types/index.d.ts"), &exts));
+        assert!(!has_extension(Path::new("index.ts"), &exts));
+        assert!(!has_extension(Path::new(".d.ts"), &exts));
+    }
+
+    #[test]
+    fn test_has_extension_tolerates_unnormalized_entries() {
+        assert!(has_extension(Path::new("main.rs"), &[".RS".to_string()]));
+    }
+}
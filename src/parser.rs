@@ -2,24 +2,51 @@
 //!
 //! Provides shared utilities for parsing code files, extracting functions, and common operations.
 //! This module ensures DRY, KISS, and SOC principles.
+//!
+//! [`ExtractionContext`] runs the extraction once per analysis run so dead code,
+//! duplicates, call graph and design metrics analyses can share it.
 
-use crate::language::get_language_by_extension;
+use crate::encoding::{read_text, Encoding};
+use crate::language::{get_language_by_extension, language_extension};
+use crate::lines::{normalize_line_endings, split_lines};
+use crate::options::has_extension;
+use crate::timings::{Phase, Timings};
+use rayon::prelude::*;
 use regex::Regex;
-use std::collections::HashSet;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use walkdir::WalkDir;
+
+/// Version byte written in front of a persisted [`ExtractionContext`]
+pub const EXTRACTION_FORMAT_VERSION: u8 = 3;
+
+/// Where analyses persist the extraction context, relative to the analyzed root
+pub const DEFAULT_EXTRACTION_CACHE: &str = ".codesearch/extraction.json";
 
 /// Read file content, returning empty string on error
+///
+/// UTF-16 files with a byte order mark are transcoded, and line endings are
+/// normalized to `\n`, so a CRLF file analyzes the same as its LF copy.
 pub fn read_file_content(file_path: &str) -> String {
-    fs::read_to_string(file_path).unwrap_or_default()
+    match read_text(Path::new(file_path), Encoding::Auto) {
+        Ok(content) => normalize_line_endings(&content).into_owned(),
+        Err(_) => String::new(),
+    }
 }
 
-/// Extract file extension from path
+/// Extract file extension from path, as language-specific code should see it
+///
+/// An extension mapped in `[language.overrides]` comes back as its language's
+/// own (see [`language_extension`]), so `.inc` files mapped to PHP read as `php`.
 pub fn get_file_extension(file_path: &str) -> &str {
-    Path::new(file_path)
+    let ext = Path::new(file_path)
         .extension()
         .and_then(|e| e.to_str())
-        .unwrap_or("")
+        .unwrap_or("");
+    language_extension(ext)
 }
 
 /// Check if a name is a keyword or builtin function
@@ -39,11 +66,27 @@ pub fn is_keyword_or_builtin(name: &str) -> bool {
     )
 }
 
-/// Extract identifier from regex captures (first capture group)
+/// Extract identifier from regex captures
+///
+/// Uses the first capture group when it holds an identifier; otherwise, since
+/// most language patterns (`fn\s+\w+`) have no group, the last identifier in
+/// the whole match that is not a keyword.
 pub fn extract_identifier_from_match(caps: &regex::Captures) -> Option<String> {
-    caps.get(1)
-        .map(|m| m.as_str().to_string())
-        .filter(|name| !is_keyword_or_builtin(name))
+    let is_identifier = |s: &str| {
+        s.chars().next().is_some_and(|c| c.is_alphabetic() || c == '_')
+            && s.chars().all(|c| c.is_alphanumeric() || c == '_')
+    };
+
+    let wanted = |s: &str| is_identifier(s) && !is_keyword_or_builtin(s);
+    if let Some(group) = caps.get(1).map(|m| m.as_str().trim()).filter(|group| wanted(group)) {
+        return Some(group.to_string());
+    }
+
+    caps.get(0)?
+        .as_str()
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .rfind(|word| wanted(word))
+        .map(str::to_string)
 }
 
 /// Extract function definitions from content using language patterns
@@ -57,10 +100,13 @@ pub fn extract_functions(
     if let Some(lang) = get_language_by_extension(ext) {
         for pattern in lang.function_patterns {
             if let Ok(re) = Regex::new(pattern) {
-                for (line_num, line) in content.lines().enumerate() {
+                for (line_num, line) in split_lines(content).enumerate() {
                     if let Some(caps) = re.captures(line) {
                         if let Some(name) = extract_identifier_from_match(&caps) {
-                            functions.push((name, line_num + 1));
+                            let definition = (name, line_num + 1);
+                            if !functions.contains(&definition) {
+                                functions.push(definition);
+                            }
                         }
                     }
                 }
@@ -82,10 +128,13 @@ pub fn extract_classes(
     if let Some(lang) = get_language_by_extension(ext) {
         for pattern in lang.class_patterns {
             if let Ok(re) = Regex::new(pattern) {
-                for (line_num, line) in content.lines().enumerate() {
+                for (line_num, line) in split_lines(content).enumerate() {
                     if let Some(caps) = re.captures(line) {
                         if let Some(name) = extract_identifier_from_match(&caps) {
-                            classes.push((name, line_num + 1));
+                            let definition = (name, line_num + 1);
+                            if !classes.contains(&definition) {
+                                classes.push(definition);
+                            }
                         }
                     }
                 }
@@ -113,27 +162,704 @@ pub fn extract_function_calls(line: &str) -> HashSet<String> {
     calls
 }
 
+/// Whether `c` can start an identifier: `_` or a letter of any script
+fn is_identifier_start(c: char) -> bool {
+    c == '_' || c.is_alphabetic()
+}
+
+/// Whether `c` can continue an identifier: `_`, a letter or a digit of any script
+fn is_identifier_continue(c: char) -> bool {
+    c == '_' || c.is_alphanumeric()
+}
+
+/// Identifiers in `content` with their byte offsets, in one pass and without a regex
+///
+/// An identifier is a whole run of letters, digits and `_` that doesn't start
+/// with a digit, so `id` is never found inside `identifier`, `my_id` or `0x1d`.
+pub fn identifiers(content: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut chars = content.char_indices().peekable();
+    std::iter::from_fn(move || {
+        loop {
+            let (start, first) = chars.next()?;
+            if !is_identifier_continue(first) {
+                continue;
+            }
+            let mut end = start + first.len_utf8();
+            while let Some(&(offset, c)) = chars.peek() {
+                if !is_identifier_continue(c) {
+                    break;
+                }
+                end = offset + c.len_utf8();
+                chars.next();
+            }
+            // Numbers (`10`, `0x1f`, `2e5`) are skipped whole
+            if is_identifier_start(first) {
+                return Some((start, &content[start..end]));
+            }
+        }
+    })
+}
+
 /// Extract all identifier references from content
 pub fn extract_identifier_references(content: &str) -> HashSet<String> {
-    let identifier_re = Regex::new(r"\b([a-zA-Z_][a-zA-Z0-9_]*)\b").unwrap();
-    let mut references = HashSet::new();
+    identifiers(content)
+        .map(|(_, name)| name)
+        .filter(|name| !is_keyword_or_builtin(name))
+        .map(str::to_string)
+        .collect()
+}
 
-    for cap in identifier_re.captures_iter(content) {
-        if let Some(name) = cap.get(1) {
-            let ref_name = name.as_str().to_string();
-            if !is_keyword_or_builtin(&ref_name) {
-                references.insert(ref_name);
+/// Count how often each identifier occurs in content, keywords included
+///
+/// One pass over the file; afterwards any number of names can be looked up
+/// without scanning it again. Callers that want references only filter with
+/// [`is_keyword_or_builtin`].
+pub fn count_identifiers(content: &str) -> HashMap<String, usize> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for (_, name) in identifiers(content) {
+        match counts.get_mut(name) {
+            Some(count) => *count += 1,
+            None => {
+                counts.insert(name.to_string(), 1);
+            }
+        }
+    }
+    counts
+}
+
+/// Parts of a file [`count_identifiers_excluding`] leaves out
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SkipRegions {
+    /// String and char literals
+    pub strings: bool,
+    pub comments: bool,
+}
+
+/// Like [`count_identifiers`], not counting identifiers inside the regions `skip` names
+///
+/// Literals and comments are recognized as in [`mask_literals`].
+pub fn count_identifiers_excluding(content: &str, file_path: &str, skip: SkipRegions) -> HashMap<String, usize> {
+    if skip == SkipRegions::default() {
+        return count_identifiers(content);
+    }
+    count_identifiers(&mask_regions(content, file_path, skip))
+}
+
+/// Names `content` may invoke dynamically
+///
+/// Covers identifier-like words inside string literals (`getattr(obj, "name")`,
+/// `globals()["name"]`, `#[serde(rename = "name")]`, route and event handler
+/// strings, `obj["name"]()`), plus symbols passed to Ruby's `send`, `public_send`,
+/// `method`, `respond_to?` and `define_method`. Single-quoted strings aren't
+/// scanned in Rust, where `'` starts lifetimes and chars.
+pub fn extract_dynamic_names(content: &str, file_path: &str) -> HashSet<String> {
+    static DOUBLE_QUOTED_RE: OnceLock<Regex> = OnceLock::new();
+    static ANY_QUOTED_RE: OnceLock<Regex> = OnceLock::new();
+    static RUBY_SYMBOL_RE: OnceLock<Regex> = OnceLock::new();
+    static WORD_RE: OnceLock<Regex> = OnceLock::new();
+    let strings = if get_file_extension(file_path) == "rs" {
+        DOUBLE_QUOTED_RE.get_or_init(|| Regex::new(r#""(?:[^"\\\n]|\\.)*""#).unwrap())
+    } else {
+        ANY_QUOTED_RE.get_or_init(|| Regex::new(r#""(?:[^"\\\n]|\\.)*"|'(?:[^'\\\n]|\\.)*'|`[^`]*`"#).unwrap())
+    };
+    let word_re = WORD_RE.get_or_init(|| Regex::new(r"[a-zA-Z_][a-zA-Z0-9_]*").unwrap());
+
+    let mut names: HashSet<String> = strings
+        .find_iter(content)
+        .flat_map(|literal| word_re.find_iter(literal.as_str()))
+        .map(|word| word.as_str().to_string())
+        .collect();
+    if get_file_extension(file_path) == "rb" {
+        let symbol_re = RUBY_SYMBOL_RE.get_or_init(|| {
+            Regex::new(r"\b(?:send|public_send|__send__|method|respond_to\?|define_method)\(?\s*:([a-zA-Z_]\w*)").unwrap()
+        });
+        names.extend(symbol_re.captures_iter(content).map(|caps| caps[1].to_string()));
+    }
+    names
+}
+
+/// Extract imported paths (`std::io::Write`, `os.path`, `./util`) in order of appearance
+pub fn extract_imports(content: &str, file_path: &str) -> Vec<String> {
+    let patterns: &[&str] = match get_file_extension(file_path) {
+        "rs" => &[r"use\s+([\w:]+)"],
+        "py" => &[r"^\s*import\s+([\w.]+)", r"^\s*from\s+([\w.]+)\s+import"],
+        "js" | "ts" => &[r#"import\s+.*\s+from\s+['\"]([^'\"]+)['\"]"#, r#"require\(['\"]([^'\"]+)['\"]\)"#],
+        "go" => &[r#"import\s+"([^"]+)""#],
+        "java" | "kt" => &[r"import\s+([\w.]+)"],
+        "cs" => &[r"^\s*using\s+(?:static\s+)?([\w.]+)\s*;"],
+        _ => &[],
+    };
+
+    let mut imports = Vec::new();
+    for pattern in patterns {
+        if let Ok(re) = Regex::new(pattern) {
+            for line in split_lines(content) {
+                if let Some(import) = re.captures(line).and_then(|caps| caps.get(1)) {
+                    imports.push(import.as_str().to_string());
+                }
+            }
+        }
+    }
+    imports
+}
+
+/// Stable 64-bit FNV-1a hash of file content, used to reuse persisted extractions
+pub fn content_hash(content: &str) -> u64 {
+    content.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Byte range of a block body, excluding its delimiters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockSpan {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Find the body of the block whose header starts at byte `from`
+///
+/// For brace languages the body opens at the first `{` outside parentheses,
+/// brackets, strings and comments; a `;` before it means a declaration without
+/// a body. For Python the body is the indented suite after the header's `:`, or
+/// the rest of the line for a one-line definition.
+pub fn find_block(content: &str, from: usize, file_path: &str) -> Option<BlockSpan> {
+    match get_file_extension(file_path) {
+        "py" | "pyw" | "pyi" => find_indented_block(content, from),
+        ext => find_braced_block(content, from, ext == "rs"),
+    }
+}
+
+fn find_braced_block(content: &str, from: usize, rust: bool) -> Option<BlockSpan> {
+    let bytes = content.as_bytes();
+    let mut depth = 0usize;
+    let mut braces = 0usize;
+    let mut body_start = None;
+    let mut i = from;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                i = content[i..].find('\n').map_or(bytes.len(), |n| i + n);
+                continue;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = content[i + 2..].find("*/").map_or(bytes.len(), |n| i + n + 4);
+                continue;
+            }
+            b'"' | b'`' => {
+                i = string_end(bytes, i);
+                continue;
             }
+            b'\'' if !rust => {
+                i = string_end(bytes, i);
+                continue;
+            }
+            // A char literal; otherwise a lifetime, which needs no skipping
+            b'\'' if rust_char_literal_end(content, i).is_some() => {
+                i = rust_char_literal_end(content, i).unwrap_or(i + 1);
+                continue;
+            }
+            b'(' | b'[' if body_start.is_none() => depth += 1,
+            b')' | b']' if body_start.is_none() => depth = depth.saturating_sub(1),
+            b';' if body_start.is_none() && depth == 0 => return None,
+            b'{' if body_start.is_none() && depth == 0 => {
+                body_start = Some(i + 1);
+                braces = 1;
+            }
+            b'{' if body_start.is_some() => braces += 1,
+            b'}' if body_start.is_some() => {
+                braces -= 1;
+                if braces == 0 {
+                    return body_start.map(|start| BlockSpan { start, end: i });
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+fn find_indented_block(content: &str, from: usize) -> Option<BlockSpan> {
+    let bytes = content.as_bytes();
+    let mut depth = 0usize;
+    let mut i = from;
+    let colon = loop {
+        match *bytes.get(i)? {
+            b'#' => {
+                i = content[i..].find('\n').map_or(bytes.len(), |n| i + n);
+                continue;
+            }
+            b'"' | b'\'' => {
+                i = string_end(bytes, i);
+                continue;
+            }
+            b'(' | b'[' | b'{' => depth += 1,
+            b')' | b']' | b'}' => depth = depth.saturating_sub(1),
+            b':' if depth == 0 => break i,
+            _ => {}
+        }
+        i += 1;
+    };
+
+    let line_start = content[..from].rfind('\n').map_or(0, |n| n + 1);
+    let indent = indentation(&content[line_start..]);
+    let line_end = content[colon..].find('\n').map_or(content.len(), |n| colon + n);
+    let rest = content[colon + 1..line_end].trim();
+    if !rest.is_empty() && !rest.starts_with('#') {
+        return Some(BlockSpan { start: colon + 1, end: line_end });
+    }
+
+    let start = (line_end + 1).min(content.len());
+    let mut end = start;
+    let mut offset = start;
+    for line in content[start..].split_inclusive('\n') {
+        if !line.trim().is_empty() {
+            if indentation(line) <= indent {
+                break;
+            }
+            end = offset + line.trim_end_matches(['\r', '\n']).len();
+        }
+        offset += line.len();
+    }
+    (end > start).then_some(BlockSpan { start, end })
+}
+
+/// Replace the contents of string literals and comments with spaces
+///
+/// Offsets and line breaks are kept, so positions in the result map back to
+/// `content`; keywords and braces inside literals no longer count as code.
+pub fn mask_literals(content: &str, file_path: &str) -> String {
+    mask_regions(content, file_path, SkipRegions { strings: true, comments: true })
+}
+
+/// Like [`mask_literals`], masking only the regions `mask` names
+///
+/// Literals and comments are still recognized either way, so a quote inside
+/// a comment doesn't start a string.
+pub fn mask_regions(content: &str, file_path: &str, mask: SkipRegions) -> String {
+    mask_source(content, get_file_extension(file_path), mask, false)
+}
+
+/// Blank out comments and the contents of string literals, keeping every line break
+///
+/// Unlike [`mask_literals`] the quotes stay, so `f("a")` and `f()` still
+/// differ; line numbers in the result match `content`.
+pub fn strip_comments_and_strings(content: &str, file_path: &str) -> String {
+    strip_comments_and_strings_for(content, get_file_extension(file_path))
+}
+
+/// Like [`strip_comments_and_strings`], for content in the language of extension `ext`
+pub fn strip_comments_and_strings_for(content: &str, ext: &str) -> String {
+    mask_source(content, ext, SkipRegions { strings: true, comments: true }, true)
+}
+
+/// Block comments by their `comment_patterns` entry: pattern, opener, closer
+///
+/// Python's triple-quoted strings are listed as comments, so docstrings go too.
+const BLOCK_COMMENTS: &[(&str, &str, &str)] = &[
+    (r"/\*\*", "/**", "*/"),
+    (r"/\*", "/*", "*/"),
+    (r"'''", "'''", "'''"),
+    (r#"""""#, "\"\"\"", "\"\"\""),
+    (r"<!--", "<!--", "-->"),
+    (r"\{-", "{-", "-}"),
+    (r"\(\*", "(*", "*)"),
+    (r"=begin", "=begin", "=end"),
+    (r"=pod", "=pod", "=cut"),
+    (r"--\[\[", "--[[", "]]"),
+    (r"<#", "<#", "#>"),
+    (r"#=", "#=", "=#"),
+    (r"#\[", "#[", "]#"),
+];
+
+/// Blank the regions `mask` names, keeping offsets and line breaks
+///
+/// Comment markers come from the language's `comment_patterns`; in files of
+/// unknown languages only string literals are recognized. With `keep_quotes`
+/// a literal's delimiters stay and only its contents are blanked.
+fn mask_source(content: &str, ext: &str, mask: SkipRegions, keep_quotes: bool) -> String {
+    let mut blocks: Vec<(&str, &str)> = Vec::new();
+    let mut line_markers: Vec<String> = Vec::new();
+    for pattern in get_language_by_extension(ext).map_or(&[][..], |lang| lang.comment_patterns) {
+        match BLOCK_COMMENTS.iter().find(|(p, _, _)| p == pattern) {
+            Some(&(_, open, close)) => blocks.push((open, close)),
+            None => line_markers.push(pattern.replace('\\', "")),
+        }
+    }
+    // Longest first, so `/**` wins over `/*` and `#[` over `#`
+    blocks.sort_by_key(|(open, _)| std::cmp::Reverse(open.len()));
+    line_markers.retain(|marker| !marker.is_empty());
+    line_markers.sort_by_key(|marker| std::cmp::Reverse(marker.len()));
+    let rust = ext == "rs";
+
+    let bytes = content.as_bytes();
+    let mut masked = bytes.to_vec();
+    let mut i = 0;
+    while i < bytes.len() {
+        let rest = &bytes[i..];
+        let (end, region) = if let Some((open, close)) = blocks.iter().find(|(open, _)| rest.starts_with(open.as_bytes())) {
+            let end = content[i + open.len()..].find(close).map_or(bytes.len(), |n| i + open.len() + n + close.len());
+            (end, mask.comments.then_some(i..end))
+        } else if line_markers.iter().any(|marker| rest.starts_with(marker.as_bytes())) {
+            let end = content[i..].find('\n').map_or(bytes.len(), |n| i + n);
+            (end, mask.comments.then_some(i..end))
+        } else if matches!(bytes[i], b'"' | b'`') || (bytes[i] == b'\'' && !rust) {
+            let end = string_end(bytes, i);
+            let close = if end > i + 1 && bytes[end - 1] == bytes[i] { end - 1 } else { end };
+            (end, mask.strings.then_some(if keep_quotes { i + 1..close } else { i..end }))
+        } else if bytes[i] == b'\'' {
+            // A Rust char literal; otherwise a lifetime, which is code
+            match rust_char_literal_end(content, i) {
+                Some(end) => (end, mask.strings.then_some(if keep_quotes { i + 1..end - 1 } else { i..end })),
+                None => (i + 1, None),
+            }
+        } else {
+            (i + 1, None)
+        };
+        for byte in &mut masked[region.unwrap_or(0..0)] {
+            if *byte != b'\n' && *byte != b'\r' {
+                *byte = b' ';
+            }
+        }
+        i = end;
+    }
+    // Only whole ASCII-delimited runs were replaced, so the result is still UTF-8
+    String::from_utf8(masked).unwrap_or_else(|_| content.to_string())
+}
+
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start_matches([' ', '\t']).len()
+}
+
+/// Index just past the string literal opening at `start`
+///
+/// `"` and `'` strings end at the line break when unterminated, so a stray
+/// apostrophe can't swallow the rest of the file; backtick strings may span lines.
+fn string_end(bytes: &[u8], start: usize) -> usize {
+    let quote = bytes[start];
+    let mut i = start + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' if bytes.get(i + 1).is_some_and(|&next| next != b'\n') => i += 2,
+            b'\n' if quote != b'`' => return i,
+            b if b == quote => return i + 1,
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
+
+/// End of the Rust char literal at `start` (`'x'`, `'\n'`, `'\u{1F600}'`), if it is one
+fn rust_char_literal_end(content: &str, start: usize) -> Option<usize> {
+    let rest = &content[start + 1..];
+    if rest.starts_with('\\') {
+        return rest.char_indices().skip(2).take(10).find(|&(_, c)| c == '\'').map(|(n, _)| start + n + 2);
+    }
+    let c = rest.chars().next()?;
+    rest[c.len_utf8()..].starts_with('\'').then(|| start + c.len_utf8() + 2)
+}
+
+/// Everything extracted from one file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileExtraction {
+    pub path: String,
+    pub size: u64,
+    pub content_hash: u64,
+    /// Function definitions as (name, line)
+    pub functions: Vec<(String, usize)>,
+    /// Class/struct definitions as (name, line)
+    pub classes: Vec<(String, usize)>,
+    /// Occurrences of each identifier in this file, keywords included (see [`count_identifiers`])
+    pub identifiers: HashMap<String, usize>,
+    /// Names that may be invoked dynamically: words inside string literals, and
+    /// symbols passed to Ruby's `send`/`method`-style calls
+    #[serde(default)]
+    pub dynamic_names: HashSet<String>,
+    pub imports: Vec<String>,
+    /// Language the file was extracted as, `None` if unknown; a file whose
+    /// extension `[language.overrides]` has since remapped is extracted again
+    #[serde(default)]
+    pub language: Option<String>,
+    /// File content; not persisted, it is re-read to check the hash anyway
+    #[serde(skip)]
+    pub content: String,
+}
+
+impl FileExtraction {
+    /// Whole-word occurrences of `name` in this file, looked up without rescanning it
+    pub fn occurrences(&self, name: &str) -> usize {
+        self.identifiers.get(name).copied().unwrap_or(0)
+    }
+
+    pub fn extract(path: &str, content: String) -> Self {
+        Self {
+            path: path.to_string(),
+            size: content.len() as u64,
+            content_hash: content_hash(&content),
+            functions: extract_functions(&content, path),
+            classes: extract_classes(&content, path),
+            identifiers: count_identifiers(&content),
+            dynamic_names: extract_dynamic_names(&content, path),
+            imports: extract_imports(&content, path),
+            language: file_language(path),
+            content,
+        }
+    }
+}
+
+/// Name of the language `path` is in, honoring `[language.overrides]`
+fn file_language(path: &str) -> Option<String> {
+    get_language_by_extension(get_file_extension(path)).map(|language| language.name.to_string())
+}
+
+/// Per-file extraction results shared by the analyses of one run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExtractionContext {
+    /// Sorted by path
+    files: Vec<FileExtraction>,
+}
+
+impl ExtractionContext {
+    /// Read and extract every matching file under `path`
+    pub fn build(
+        path: &Path,
+        extensions: Option<&[String]>,
+        exclude: Option<&[String]>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::build_reusing(path, extensions, exclude, None)
+    }
+
+    /// Like [`build`](Self::build), reusing entries of `previous` whose content hash is unchanged
+    pub fn build_reusing(
+        path: &Path,
+        extensions: Option<&[String]>,
+        exclude: Option<&[String]>,
+        previous: Option<&ExtractionContext>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::build_reusing_timed(path, extensions, exclude, previous, &Timings::disabled())
+    }
+
+    /// Like [`build_reusing`](Self::build_reusing), recording the walk, read and extract phases
+    pub fn build_reusing_timed(
+        path: &Path,
+        extensions: Option<&[String]>,
+        exclude: Option<&[String]>,
+        previous: Option<&ExtractionContext>,
+        timings: &Timings,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let files: Vec<FileExtraction> = timings
+            .time(Phase::Walk, || walk_source_files(path, extensions, exclude))
+            .par_iter()
+            .map(|file| {
+                let path = file.to_string_lossy().to_string();
+                let content = timings.time(Phase::Read, || read_file_content(&path));
+                timings.time(Phase::Extract, || match previous.and_then(|p| p.get(&path)) {
+                    Some(cached) if cached.content_hash == content_hash(&content) && cached.language == file_language(&path) => FileExtraction {
+                        content,
+                        ..cached.clone()
+                    },
+                    _ => FileExtraction::extract(&path, content),
+                })
+            })
+            .collect();
+        Ok(timings.time(Phase::Extract, || Self::from_extractions(files)))
+    }
+
+    /// Build a context from in-memory (path, content) pairs
+    pub fn from_sources<P: Into<String>, C: Into<String>>(sources: impl IntoIterator<Item = (P, C)>) -> Self {
+        Self::from_extractions(
+            sources
+                .into_iter()
+                .map(|(path, content)| {
+                    let path: String = path.into();
+                    FileExtraction::extract(&path, content.into())
+                })
+                .collect(),
+        )
+    }
+
+    fn from_extractions(mut files: Vec<FileExtraction>) -> Self {
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        Self { files }
+    }
+
+    pub fn files(&self) -> &[FileExtraction] {
+        &self.files
+    }
+
+    pub fn get(&self, path: &str) -> Option<&FileExtraction> {
+        self.files
+            .binary_search_by(|f| f.path.as_str().cmp(path))
+            .ok()
+            .map(|i| &self.files[i])
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Number of files mentioning each identifier
+    ///
+    /// An identifier used several times in one file counts once, matching
+    /// [`crate::deadcode::count_references`]. Files are counted in parallel
+    /// shards whose maps are summed, so the result doesn't depend on the split.
+    pub fn reference_counts(&self) -> HashMap<String, usize> {
+        self.files
+            .par_iter()
+            .fold(HashMap::new, |mut counts: HashMap<String, usize>, file| {
+                for name in file.identifiers.keys().filter(|name| !is_keyword_or_builtin(name)) {
+                    *counts.entry(name.clone()).or_insert(0) += 1;
+                }
+                counts
+            })
+            .reduce(HashMap::new, |mut merged, shard| {
+                for (name, count) in shard {
+                    *merged.entry(name).or_insert(0) += count;
+                }
+                merged
+            })
+    }
+
+    /// Names that any file may invoke dynamically (see [`extract_dynamic_names`])
+    pub fn dynamic_names(&self) -> HashSet<&str> {
+        self.files
+            .iter()
+            .flat_map(|file| file.dynamic_names.iter().map(String::as_str))
+            .collect()
+    }
+
+    /// Serialize, prefixed with [`EXTRACTION_FORMAT_VERSION`]
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let mut bytes = vec![EXTRACTION_FORMAT_VERSION];
+        bytes.extend(serde_json::to_vec(self)?);
+        Ok(bytes)
+    }
+
+    /// Restore a context written by [`to_bytes`](Self::to_bytes)
+    ///
+    /// Returns `None` for another format version or unparsable data. Restored
+    /// entries have no content; pass them to [`build_reusing`](Self::build_reusing).
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        match bytes.split_first() {
+            Some((&EXTRACTION_FORMAT_VERSION, body)) => serde_json::from_slice(body).ok(),
+            _ => None,
+        }
+    }
+
+    /// Load a persisted context, or `None` if missing or stale
+    pub fn load(cache_file: &Path) -> Option<Self> {
+        Self::from_bytes(&fs::read(cache_file).ok()?)
+    }
+
+    pub fn save(&self, cache_file: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(parent) = cache_file.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(cache_file, self.to_bytes()?)?;
+        Ok(())
+    }
+
+    /// Build for directory `path`, reusing and then updating `path/.codesearch/extraction.json`
+    ///
+    /// Cache failures never fail the analysis.
+    pub fn build_cached(
+        path: &Path,
+        extensions: Option<&[String]>,
+        exclude: Option<&[String]>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::build_cached_timed(path, extensions, exclude, &Timings::disabled())
+    }
+
+    /// Like [`build_cached`](Self::build_cached), recording the walk, read and extract phases
+    ///
+    /// Loading and saving the cache count as reading.
+    pub fn build_cached_timed(
+        path: &Path,
+        extensions: Option<&[String]>,
+        exclude: Option<&[String]>,
+        timings: &Timings,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        if !path.is_dir() {
+            return Self::build_reusing_timed(path, extensions, exclude, None, timings);
         }
+        let cache_file = path.join(DEFAULT_EXTRACTION_CACHE);
+        let previous = timings.time(Phase::Read, || Self::load(&cache_file));
+        let context = Self::build_reusing_timed(path, extensions, exclude, previous.as_ref(), timings)?;
+        let _ = timings.time(Phase::Read, || context.save(&cache_file));
+        Ok(context)
     }
+}
 
-    references
+fn walk_source_files(path: &Path, extensions: Option<&[String]>, exclude: Option<&[String]>) -> Vec<PathBuf> {
+    WalkDir::new(path)
+        .into_iter()
+        .filter_entry(|e| {
+            let name = e.file_name().to_str().unwrap_or("");
+            // Never extract our own cache and index files
+            let own_data = e.depth() > 0 && e.file_type().is_dir() && name == ".codesearch";
+            !own_data && !exclude.is_some_and(|dirs| dirs.iter().any(|d| d == name))
+        })
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter(|e| extensions.is_none_or(|exts| has_extension(e.path(), exts)))
+        .map(|e| e.into_path())
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_extract_dynamic_names() {
+        let py = "handler = getattr(module, \"on_save\")\nglobals()['on_load']()\n";
+        let names = extract_dynamic_names(py, "app.py");
+        assert!(names.contains("on_save") && names.contains("on_load"));
+        assert!(!names.contains("getattr") && !names.contains("handler"));
+
+        // Lifetimes aren't strings
+        let rs = "fn f<'a>(x: &'a str) {}\n#[serde(rename = \"kind\")]\n";
+        let names = extract_dynamic_names(rs, "lib.rs");
+        assert_eq!(names, HashSet::from(["kind".to_string()]));
+
+        let rb = "obj.send(:archive)\nrespond_to? :restore\nsend :purge\n";
+        let names = extract_dynamic_names(rb, "model.rb");
+        assert!(names.contains("archive") && names.contains("restore") && names.contains("purge"));
+    }
+
+    #[test]
+    fn test_mask_literals_keeps_offsets() {
+        let rs = "let s = \"{ if }\"; // if {\nfn f<'a>(c: char) -> bool { c == '{' }\n";
+        let masked = mask_literals(rs, "lib.rs");
+        assert_eq!(masked.len(), rs.len());
+        assert_eq!(masked.lines().count(), 2);
+        assert!(!masked.contains("if"));
+        assert_eq!(masked.matches('{').count(), 1);
+        assert!(masked.contains("<'a>"));
+
+        let py = "x = '# not a comment'  # if y:\n";
+        assert_eq!(mask_literals(py, "a.py").trim(), "x =");
+    }
+
+    #[test]
+    fn test_strip_comments_and_strings_keeps_lines() {
+        let code = "/*\n * Licensed under MIT\n */\n/// Docs\nfn greet() {\n    println!(\"hi // there\"); // wave\n}\n";
+        let stripped = strip_comments_and_strings(code, "lib.rs");
+        assert_eq!(stripped.lines().count(), code.lines().count());
+        assert_eq!(stripped.lines().nth(4), Some("fn greet() {"));
+        assert_eq!(stripped.lines().nth(5).map(str::trim_end), Some("    println!(\"           \");"));
+        assert!(stripped.lines().take(4).all(|line| line.trim().is_empty()));
+
+        let python = "def f():\n    \"\"\"Docstring\n    more\"\"\"\n    return 'x'  # note\n";
+        let stripped = strip_comments_and_strings(python, "a.py");
+        assert_eq!(stripped.lines().count(), 4);
+        assert!(stripped.lines().skip(1).take(2).all(|line| line.trim().is_empty()));
+        assert_eq!(stripped.lines().nth(3).map(str::trim_end), Some("    return ' '"));
+
+        assert_eq!(strip_comments_and_strings("// kept", "notes.unknownext"), "// kept");
+    }
+
     #[test]
     fn test_read_file_content() {
         let content = read_file_content("nonexistent.rs");
@@ -163,6 +889,159 @@ mod tests {
         assert!(!calls.contains("let"));
     }
 
+    #[test]
+    fn test_extract_functions_without_capture_groups() {
+        let functions = extract_functions("pub fn helper() {}\nasync fn fetch() {}\n", "lib.rs");
+        assert_eq!(functions, vec![("helper".to_string(), 1), ("fetch".to_string(), 2)]);
+
+        let functions = extract_functions("const load = async (url) => {\n};\n", "app.js");
+        assert!(functions.contains(&("load".to_string(), 1)));
+
+        let classes = extract_classes("class Parser:\n    pass\n", "p.py");
+        assert_eq!(classes, vec![("Parser".to_string(), 1)]);
+    }
+
+    #[test]
+    fn test_find_braced_block() {
+        let content = "fn f(x: [u8; 2]) -> char {\n    if x[0] == 1 { '{' } else { '}' }\n}\nfn g();\n";
+        let span = find_block(content, 0, "a.rs").unwrap();
+        assert_eq!(&content[span.start..span.end], "\n    if x[0] == 1 { '{' } else { '}' }\n");
+
+        let g = content.find("fn g").unwrap();
+        assert_eq!(find_block(content, g, "a.rs"), None);
+
+        let content = "function f({ a, b } = {}) {\n  // }\n  return \"}\" + a;\n}\n";
+        let span = find_block(content, 0, "a.js").unwrap();
+        assert!(content[span.start..span.end].contains("return \"}\" + a;"));
+        assert_eq!(span.end, content.len() - 2);
+    }
+
+    #[test]
+    fn test_find_indented_block() {
+        let content = "def f(a: int = 1) -> dict[str, int]:\n    x = a\n\n    return x\ny = 2\n";
+        let span = find_block(content, 0, "a.py").unwrap();
+        assert_eq!(&content[span.start..span.end], "    x = a\n\n    return x");
+
+        let content = "class A:\n    def g(self, b): return b\n    def h(self): pass\n";
+        let g = content.find("def g").unwrap();
+        let span = find_block(content, g, "a.py").unwrap();
+        assert_eq!(content[span.start..span.end].trim(), "return b");
+    }
+
+    #[test]
+    fn test_count_identifiers() {
+        let counts = count_identifiers("let x = 10; let y = x + 5;");
+        assert_eq!(counts["x"], 2);
+        assert_eq!(counts["y"], 1);
+        assert_eq!(counts["let"], 2);
+        assert!(!counts.contains_key("10"));
+    }
+
+    #[test]
+    fn test_identifiers_are_whole_words() {
+        let content = "id = identifier(my_id, 0x1d, 2id) + id;\nlet größe = naïve_id + 名前;";
+        let found: Vec<(usize, &str)> = identifiers(content).collect();
+        assert_eq!(found[..4], [(0, "id"), (5, "identifier"), (16, "my_id"), (36, "id")]);
+        let names: Vec<&str> = found.iter().map(|(_, name)| *name).collect();
+        assert_eq!(names[4..], ["let", "größe", "naïve_id", "名前"]);
+        for (offset, name) in found {
+            assert!(content[offset..].starts_with(name));
+        }
+
+        let counts = count_identifiers(content);
+        assert_eq!(counts["id"], 2);
+        assert!(!counts.contains_key("x1d") && !counts.contains_key("d"));
+    }
+
+    #[test]
+    fn test_count_identifiers_excluding_strings_and_comments() {
+        let content = "let target = 1; // target again\nlog(\"target\", target);\n";
+        assert_eq!(count_identifiers(content)["target"], 4);
+        let count = |strings, comments| {
+            count_identifiers_excluding(content, "a.rs", SkipRegions { strings, comments }).get("target").copied()
+        };
+        assert_eq!(count(false, false), Some(4));
+        assert_eq!(count(true, false), Some(3));
+        assert_eq!(count(false, true), Some(3));
+        assert_eq!(count(true, true), Some(2));
+
+        let python = "name = 1  # name\nprint('name')\n";
+        let counts = count_identifiers_excluding(python, "a.py", SkipRegions { strings: true, comments: true });
+        assert_eq!(counts["name"], 1);
+    }
+
+    #[test]
+    fn test_extract_imports() {
+        let imports = extract_imports("use std::io::Write;\nuse crate::parser;\n", "lib.rs");
+        assert_eq!(imports, vec!["std::io::Write", "crate::parser"]);
+        assert_eq!(extract_imports("from os import path\n", "a.py"), vec!["os"]);
+        assert!(extract_imports("use x;", "notes.txt").is_empty());
+    }
+
+    #[test]
+    fn test_python_from_import_yields_only_the_module() {
+        let imports = extract_imports("import os.path\nfrom a.b import c\n    from .util import helper\n", "a.py");
+        assert_eq!(imports, vec!["os.path", "a.b", ".util"]);
+    }
+
+    #[test]
+    fn test_context_extracts_each_file_once() {
+        let context = ExtractionContext::from_sources([
+            ("b.rs", "fn helper() {}\nstruct Thing;\n"),
+            ("a.rs", "use std::io;\nfn main() { helper(); helper(); }\n"),
+        ]);
+        let paths: Vec<&str> = context.files().iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["a.rs", "b.rs"]);
+
+        let b = context.get("b.rs").unwrap();
+        assert_eq!(b.functions, vec![("helper".to_string(), 1)]);
+        assert_eq!(b.classes, vec![("Thing".to_string(), 2)]);
+        assert_eq!(context.get("a.rs").unwrap().identifiers["helper"], 2);
+        assert_eq!(context.get("a.rs").unwrap().imports, vec!["std::io"]);
+        // Counted per file, not per occurrence
+        assert_eq!(context.reference_counts()["helper"], 2);
+        assert!(context.get("c.rs").is_none());
+    }
+
+    #[test]
+    fn test_content_hash_is_stable() {
+        assert_eq!(content_hash(""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(content_hash("fn main() {}"), content_hash("fn main() {}"));
+        assert_ne!(content_hash("fn a() {}"), content_hash("fn b() {}"));
+    }
+
+    #[test]
+    fn test_persisted_context_is_reused_when_unchanged() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.rs"), "fn alpha() {}\n").unwrap();
+        fs::write(dir.path().join("b.rs"), "fn beta() {}\n").unwrap();
+        let cache_file = dir.path().join(DEFAULT_EXTRACTION_CACHE);
+
+        let first = ExtractionContext::build_cached(dir.path(), Some(&["rs".to_string()]), None).unwrap();
+        assert_eq!(first.files().len(), 2);
+
+        // Tamper with the cached entry for a.rs: it is reused only while the hash matches
+        let mut cached = ExtractionContext::load(&cache_file).unwrap();
+        assert!(cached.files.iter().all(|f| f.content.is_empty()));
+        cached.files[0].functions = vec![("from_cache".to_string(), 1)];
+        fs::write(dir.path().join("b.rs"), "fn gamma() {}\n").unwrap();
+
+        let second = ExtractionContext::build_reusing(dir.path(), Some(&["rs".to_string()]), None, Some(&cached)).unwrap();
+        assert_eq!(second.files()[0].functions[0].0, "from_cache");
+        assert_eq!(second.files()[0].content, "fn alpha() {}\n");
+        assert_eq!(second.files()[1].functions[0].0, "gamma");
+    }
+
+    #[test]
+    fn test_stale_format_is_ignored() {
+        let context = ExtractionContext::from_sources([("a.rs", "fn a() {}")]);
+        let mut bytes = context.to_bytes().unwrap();
+        assert!(ExtractionContext::from_bytes(&bytes).is_some());
+        bytes[0] = EXTRACTION_FORMAT_VERSION + 1;
+        assert!(ExtractionContext::from_bytes(&bytes).is_none());
+        assert!(ExtractionContext::from_bytes(b"").is_none());
+    }
+
     #[test]
     fn test_extract_identifier_references() {
         let content = "let x = 10; let y = x + 5;";
@@ -0,0 +1,210 @@
+//! Path Glob Module
+//!
+//! Resolves the search path argument. A glob pattern the shell left
+//! unexpanded (e.g. a quoted `"src/**/*.rs"`) becomes one walk root, its
+//! leading literal directories, plus a filter on the walked paths; paths that
+//! don't exist are rejected.
+
+use globset::{GlobBuilder, GlobMatcher};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Whether `s` contains glob metacharacters
+pub fn has_glob_meta(s: &str) -> bool {
+    s.contains(['*', '?', '[', '{'])
+}
+
+/// Compile a glob pattern over `/`-separated paths
+///
+/// Supports `*` (within one directory), `**`, `?`, `[...]` (with `[!...]`
+/// negation) and `{a,b}` alternation.
+pub fn compile_glob(pattern: &str) -> Result<GlobMatcher, globset::Error> {
+    Ok(GlobBuilder::new(pattern).literal_separator(true).build()?.compile_matcher())
+}
+
+/// A glob search path: the directory to walk and the pattern walked files must match
+#[derive(Debug, Clone)]
+pub struct PathGlob {
+    pub root: PathBuf,
+    matcher: GlobMatcher,
+}
+
+impl PathGlob {
+    /// The glob `path` stands for, or `None` if it exists or has no glob characters
+    ///
+    /// An existing path is taken literally, even if its name contains glob characters.
+    pub fn from_path(path: &Path) -> Option<Result<Self, globset::Error>> {
+        let pattern = path.to_string_lossy().replace('\\', "/");
+        if path.exists() || !has_glob_meta(&pattern) {
+            return None;
+        }
+        let pattern = pattern.strip_prefix("./").unwrap_or(&pattern).to_string();
+        let base = literal_prefix(&pattern);
+        let root = if base.as_os_str().is_empty() { PathBuf::from(".") } else { base };
+        Some(compile_glob(&pattern).map(|matcher| Self { root, matcher }))
+    }
+
+    /// Whether `path`, as the walk from [`root`](Self::root) yields it, matches
+    pub fn matches(&self, path: &Path) -> bool {
+        let path = path.to_string_lossy().replace('\\', "/");
+        self.matcher.is_match(path.strip_prefix("./").unwrap_or(&path))
+    }
+}
+
+/// Check that `path` names something to search
+///
+/// A nonexistent path with glob characters must match at least one file;
+/// any other nonexistent path is an error.
+pub fn check_search_path(path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let glob = match PathGlob::from_path(path) {
+        Some(glob) => glob?,
+        None if path.exists() => return Ok(()),
+        None => return Err(format!("Path does not exist: {}", path.display()).into()),
+    };
+    let any_match = WalkDir::new(&glob.root)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .any(|e| e.file_type().is_file() && glob.matches(e.path()));
+    if !any_match {
+        return Err(format!(
+            "No files match the pattern '{}'. Check the pattern, or pass an existing directory with -e to filter by extension.",
+            path.display()
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Leading directory components of `pattern` that contain no glob characters
+fn literal_prefix(pattern: &str) -> PathBuf {
+    let mut components: Vec<&str> = pattern
+        .split('/')
+        .take_while(|c| !has_glob_meta(c))
+        .collect();
+    // The last component is only a directory if the pattern continues past it
+    if components.len() == pattern.split('/').count() {
+        components.pop();
+    }
+    PathBuf::from(components.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_has_glob_meta() {
+        assert!(has_glob_meta("src/*.rs"));
+        assert!(has_glob_meta("src/**"));
+        assert!(has_glob_meta("file?.rs"));
+        assert!(has_glob_meta("{a,b}.rs"));
+        assert!(has_glob_meta("[ab].rs"));
+        assert!(!has_glob_meta("src/main.rs"));
+    }
+
+    #[test]
+    fn test_single_star_does_not_cross_directories() {
+        let glob = compile_glob("src/*.rs").unwrap();
+        assert!(glob.is_match("src/main.rs"));
+        assert!(!glob.is_match("src/search/core.rs"));
+        assert!(!glob.is_match("src/main.py"));
+    }
+
+    #[test]
+    fn test_double_star_matches_any_depth() {
+        let glob = compile_glob("src/**/*.rs").unwrap();
+        assert!(glob.is_match("src/main.rs"));
+        assert!(glob.is_match("src/search/core.rs"));
+        assert!(glob.is_match("src/a/b/c.rs"));
+        assert!(!glob.is_match("tests/a.rs"));
+
+        let glob = compile_glob("src/**").unwrap();
+        assert!(glob.is_match("src/a/b/c.rs"));
+    }
+
+    #[test]
+    fn test_question_mark_matches_one_char() {
+        let glob = compile_glob("file?.rs").unwrap();
+        assert!(glob.is_match("file1.rs"));
+        assert!(!glob.is_match("file10.rs"));
+        assert!(!glob.is_match("file/.rs"));
+    }
+
+    #[test]
+    fn test_brace_alternation() {
+        let glob = compile_glob("src/*.{rs,py}").unwrap();
+        assert!(glob.is_match("src/a.rs"));
+        assert!(glob.is_match("src/b.py"));
+        assert!(!glob.is_match("src/c.js"));
+    }
+
+    #[test]
+    fn test_character_classes() {
+        let glob = compile_glob("[ab].rs").unwrap();
+        assert!(glob.is_match("a.rs"));
+        assert!(!glob.is_match("c.rs"));
+
+        let glob = compile_glob("[!ab].rs").unwrap();
+        assert!(glob.is_match("c.rs"));
+        assert!(!glob.is_match("a.rs"));
+    }
+
+    #[test]
+    fn test_literal_prefix() {
+        assert_eq!(literal_prefix("src/**/*.rs"), PathBuf::from("src"));
+        assert_eq!(literal_prefix("src/search/*.rs"), PathBuf::from("src/search"));
+        assert_eq!(literal_prefix("*.rs"), PathBuf::new());
+    }
+
+    #[test]
+    fn test_path_glob_walks_the_literal_prefix() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("src/nested")).unwrap();
+        fs::write(root.join("src/a.rs"), "").unwrap();
+        fs::write(root.join("src/nested/b.rs"), "").unwrap();
+        fs::write(root.join("src/c.py"), "").unwrap();
+
+        let glob = PathGlob::from_path(&root.join("src/**/*.rs")).unwrap().unwrap();
+        assert_eq!(glob.root, root.join("src"));
+        assert!(glob.matches(&root.join("src/a.rs")));
+        assert!(glob.matches(&root.join("src/nested/b.rs")));
+        assert!(!glob.matches(&root.join("src/c.py")));
+
+        let glob = PathGlob::from_path(&root.join("src/*.{rs,py}")).unwrap().unwrap();
+        assert!(glob.matches(&root.join("src/c.py")));
+        assert!(!glob.matches(&root.join("src/nested/b.rs")));
+
+        let relative = PathGlob::from_path(Path::new("./*.nomatch")).unwrap().unwrap();
+        assert_eq!(relative.root, PathBuf::from("."));
+        assert!(relative.matches(Path::new("./a.nomatch")));
+        assert!(check_search_path(&root.join("src/**/*.rs")).is_ok());
+    }
+
+    #[test]
+    fn test_existing_directory_with_brackets_is_literal() {
+        let dir = tempdir().unwrap();
+        let literal = dir.path().join("[id]");
+        fs::create_dir(&literal).unwrap();
+        fs::write(literal.join("page.tsx"), "").unwrap();
+
+        assert!(PathGlob::from_path(&literal).is_none());
+        assert!(check_search_path(&literal).is_ok());
+    }
+
+    #[test]
+    fn test_nonexistent_plain_path_is_an_error() {
+        let err = check_search_path(Path::new("definitely/not/here")).unwrap_err();
+        assert!(err.to_string().contains("does not exist"));
+    }
+
+    #[test]
+    fn test_glob_without_matches_is_an_error() {
+        let dir = tempdir().unwrap();
+        let pattern = dir.path().join("*.nomatch");
+        let err = check_search_path(&pattern).unwrap_err();
+        assert!(err.to_string().contains("No files match"));
+    }
+}
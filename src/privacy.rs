@@ -0,0 +1,927 @@
+//! Privacy Scan
+//!
+//! Finds logging and print calls that interpolate identifiers whose names
+//! suggest personal or secret data (email, ssn, password, token, card, ...).
+//!
+//! Detection is lexical: per-language call patterns locate the calls, the
+//! argument list is tokenized (string placeholders included), and each
+//! identifier path is split into snake/camel words and compared with the
+//! sensitive-name list. A finding is raised to high severity when the
+//! identifier is a parameter of the enclosing function.
+//!
+//! A `codesearch-ignore` comment (optionally `codesearch-ignore: privacy`) on
+//! the call's line or the line above suppresses it.
+
+use crate::config::PrivacyConfig;
+use crate::lines::split_lines;
+use crate::parser::{extract_functions, get_file_extension};
+use crate::search::list_files;
+use crate::text::truncate_str_safe;
+use crate::theme;
+use colored::*;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Names reported unless `[privacy] disable_builtins` is set
+pub const DEFAULT_SENSITIVE_NAMES: &[&str] = &[
+    "email", "ssn", "password", "passwd", "pwd", "token", "secret", "api_key", "card", "cvv",
+    "phone", "dob", "passport", "iban",
+];
+
+/// Comment marker that suppresses a finding on the same or the next line
+pub const SUPPRESSION_MARKER: &str = "codesearch-ignore";
+
+/// SARIF rule id for findings
+pub const RULE_ID: &str = "privacy/sensitive-log";
+
+/// Words after a sensitive word that turn it into metadata (`token_count`, `password.length`)
+const BENIGN_QUALIFIERS: &[&str] = &[
+    "count", "counts", "len", "length", "size", "type", "kind", "limit", "max", "min", "idx",
+    "index", "field", "column", "label", "prompt", "regex", "pattern", "policy", "required",
+    "valid", "validated", "verified", "enabled", "expiry", "expires", "expiration", "ttl", "url",
+    "endpoint",
+];
+
+/// Words before a sensitive word that do the same (`num_tokens`, `has_password`)
+const BENIGN_PREFIXES: &[&str] = &["num", "n", "max", "min", "total", "has", "is", "no"];
+
+/// Calls whose arguments are not logged verbatim (`len(token)`, `mask(email)`)
+const SANITIZER_WORDS: &[&str] = &[
+    "len", "length", "count", "size", "mask", "masked", "redact", "redacted", "hash", "hashed",
+    "obfuscate", "sanitize", "anonymize", "empty", "some", "none",
+];
+
+/// Characters of call text kept in a finding
+const MAX_CALL_CHARS: usize = 160;
+
+/// Longest argument list considered, in bytes
+const MAX_ARGUMENT_BYTES: usize = 4096;
+
+/// How likely a finding leaks caller-supplied data
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Medium,
+    High,
+}
+
+impl Severity {
+    /// SARIF result level
+    pub fn sarif_level(&self) -> &'static str {
+        match self {
+            Severity::High => "error",
+            Severity::Medium => "warning",
+        }
+    }
+}
+
+/// A logging call that interpolates a sensitive-looking identifier
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PrivacyFinding {
+    pub file: String,
+    pub line: usize,
+    /// The call, whitespace-collapsed and shortened
+    pub call: String,
+    /// The identifier path as written, e.g. `user.email`
+    pub identifier: String,
+    /// The sensitive name it matched
+    pub matched: String,
+    pub severity: Severity,
+    /// Whether the identifier's root is a parameter of the enclosing function
+    pub from_parameter: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub function: Option<String>,
+}
+
+/// The sensitive-name list, split into words for matching
+#[derive(Debug, Clone)]
+pub struct SensitiveNames {
+    names: Vec<(String, Vec<String>)>,
+    allow: HashSet<String>,
+}
+
+impl Default for SensitiveNames {
+    fn default() -> Self {
+        Self::new(DEFAULT_SENSITIVE_NAMES.iter().copied(), std::iter::empty::<&str>())
+    }
+}
+
+impl SensitiveNames {
+    /// Build from names (any case style) and identifiers that are never reported
+    pub fn new<N: AsRef<str>, A: AsRef<str>>(
+        names: impl IntoIterator<Item = N>,
+        allow: impl IntoIterator<Item = A>,
+    ) -> Self {
+        let mut parsed: Vec<(String, Vec<String>)> = Vec::new();
+        for name in names {
+            let words = split_words(name.as_ref());
+            if !words.is_empty() && !parsed.iter().any(|(_, w)| *w == words) {
+                parsed.push((words.join("_"), words));
+            }
+        }
+        Self {
+            names: parsed,
+            allow: allow.into_iter().map(|a| a.as_ref().to_string()).collect(),
+        }
+    }
+
+    /// The builtin list plus `[privacy]` config and extra names from the command line
+    pub fn from_config(config: &PrivacyConfig, extra: &[String]) -> Self {
+        let builtins = if config.disable_builtins { &[][..] } else { DEFAULT_SENSITIVE_NAMES };
+        let names = builtins
+            .iter()
+            .map(|s| s.to_string())
+            .chain(config.sensitive_names.iter().cloned())
+            .chain(extra.iter().cloned());
+        Self::new(names, &config.allow)
+    }
+
+    /// The sensitive name an identifier path such as `user.emailAddress` matches
+    ///
+    /// Matching is on whole words, so `tokenizer` does not match `token`, and a
+    /// benign qualifier (`token_count`, `password.length`) cancels the match.
+    pub fn match_identifier(&self, path: &str) -> Option<&str> {
+        if self.allow.contains(path) {
+            return None;
+        }
+        let segments: Vec<Vec<String>> = path.split('.').map(split_words).collect();
+
+        for (index, words) in segments.iter().enumerate() {
+            if self.allow.contains(&words.join("_")) {
+                continue;
+            }
+            for (name, name_words) in &self.names {
+                let Some(start) = find_word_sequence(words, name_words) else {
+                    continue;
+                };
+                let end = start + name_words.len();
+                let qualifier = words.get(end).or_else(|| segments.get(index + 1).and_then(|s| s.first()));
+                let prefix = start.checked_sub(1).map(|i| &words[i]);
+                let benign = qualifier.is_some_and(|w| BENIGN_QUALIFIERS.contains(&w.as_str()))
+                    || prefix.is_some_and(|w| BENIGN_PREFIXES.contains(&w.as_str()));
+                if !benign {
+                    return Some(name.as_str());
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Position of `needle` (allowing a plural last word) inside `words`
+fn find_word_sequence(words: &[String], needle: &[String]) -> Option<usize> {
+    if needle.is_empty() || words.len() < needle.len() {
+        return None;
+    }
+    (0..=words.len() - needle.len()).find(|&start| {
+        needle.iter().enumerate().all(|(i, n)| {
+            let w = &words[start + i];
+            w == n || (i == needle.len() - 1 && w.strip_suffix('s') == Some(n.as_str()))
+        })
+    })
+}
+
+/// Split an identifier into lowercase words on `_`, `-` and camelCase boundaries
+///
+/// `userSSN` becomes `["user", "ssn"]` and `APIKey` becomes `["api", "key"]`.
+pub fn split_words(identifier: &str) -> Vec<String> {
+    let chars: Vec<char> = identifier.chars().collect();
+    let mut words = Vec::new();
+    let mut current = String::new();
+
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        if c.is_uppercase() && !current.is_empty() {
+            let prev = chars[i - 1];
+            let next_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            if prev.is_lowercase() || prev.is_ascii_digit() || (prev.is_uppercase() && next_lower) {
+                words.push(std::mem::take(&mut current));
+            }
+        }
+        current.extend(c.to_lowercase());
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+/// Regex locating logging/print calls up to their opening parenthesis
+pub fn log_call_pattern(ext: &str) -> Option<&'static Regex> {
+    static RUST: OnceLock<Regex> = OnceLock::new();
+    static PYTHON: OnceLock<Regex> = OnceLock::new();
+    static JAVASCRIPT: OnceLock<Regex> = OnceLock::new();
+    static JVM: OnceLock<Regex> = OnceLock::new();
+    static GO: OnceLock<Regex> = OnceLock::new();
+
+    let (cell, pattern) = match ext.to_lowercase().as_str() {
+        "rs" => (
+            &RUST,
+            r"\b(?:(?:log|tracing)::)?(?:println|print|eprintln|eprint|format|panic|trace|debug|info|warn|error)!\s*\(",
+        ),
+        "py" | "pyw" => (
+            &PYTHON,
+            r"\b(?:print|(?:self\.)?(?:logging|logger|log|LOGGER|LOG)\.(?:debug|info|warning|warn|error|exception|critical|log))\s*\(",
+        ),
+        "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" => (
+            &JAVASCRIPT,
+            r"\b(?:console\.(?:log|info|warn|error|debug|trace)|(?:this\.)?(?:logger|log)\.(?:debug|info|warn|error|trace|fatal))\s*\(",
+        ),
+        "java" | "kt" | "kts" | "scala" | "cs" => (
+            &JVM,
+            r"\b(?:System\.(?:out|err)\.print(?:ln|f)?|(?:this\.)?(?:log|logger|LOG|LOGGER|Log)\.(?:debug|info|warn|warning|error|trace|fatal)|println|Console\.Write(?:Line)?)\s*\(",
+        ),
+        "go" => (
+            &GO,
+            r"\b(?:fmt\.(?:Print|Printf|Println|Sprintf|Errorf|Fprintf|Fprintln)|log\.(?:Print|Printf|Println|Fatal|Fatalf|Panic|Panicf)|(?:logger|slog)\.(?:Debug|Info|Warn|Error)\w*)\s*\(",
+        ),
+        _ => return None,
+    };
+    Some(cell.get_or_init(|| Regex::new(pattern).expect("valid log call pattern")))
+}
+
+/// The text between the parenthesis at `open` and its matching close, skipping strings
+///
+/// For Rust (`ext == "rs"`) a `'` followed by an identifier and no closing `'`
+/// is a lifetime, not a character literal.
+pub fn call_arguments<'a>(text: &'a str, open: usize, ext: &str) -> Option<&'a str> {
+    let bytes = text.as_bytes();
+    if bytes.get(open) != Some(&b'(') {
+        return None;
+    }
+    let limit = (open + MAX_ARGUMENT_BYTES).min(bytes.len());
+    let mut depth = 0usize;
+    let mut quote: Option<u8> = None;
+    let mut i = open;
+
+    while i < limit {
+        let b = bytes[i];
+        match quote {
+            Some(_) if b == b'\\' => i += 1,
+            Some(q) if b == q => quote = None,
+            Some(_) => {}
+            None => match b {
+                b'"' | b'`' => quote = Some(b),
+                b'\'' if !(ext == "rs" && is_lifetime(&bytes[i + 1..])) => quote = Some(b),
+                b'(' | b'[' | b'{' => depth += 1,
+                b')' | b']' | b'}' => {
+                    depth = depth.saturating_sub(1);
+                    if depth == 0 {
+                        return text.get(open + 1..i);
+                    }
+                }
+                _ => {}
+            },
+        }
+        i += 1;
+    }
+    None
+}
+
+fn is_lifetime(after_quote: &[u8]) -> bool {
+    let ident_len = after_quote
+        .iter()
+        .take_while(|b| b.is_ascii_alphanumeric() || **b == b'_')
+        .count();
+    ident_len > 0 && after_quote.get(ident_len) != Some(&b'\'')
+}
+
+/// Identifier paths read by an argument list, including string placeholders
+///
+/// Names called as functions are skipped, as is everything inside a call to a
+/// sanitizer (`len(token)`, `mask(email)`, `password.len()`). Placeholders are
+/// `${...}` in backtick strings and, for Rust and Python, `{...}` in any string.
+pub fn interpolated_identifiers(args: &str, ext: &str) -> Vec<String> {
+    let braces_interpolate = matches!(ext, "rs" | "py" | "pyw");
+    let mut found = Vec::new();
+    collect_identifiers(args, braces_interpolate, &mut found);
+    let mut seen = HashSet::new();
+    found.retain(|path| seen.insert(path.clone()));
+    found
+}
+
+fn is_sanitizer(name: &str) -> bool {
+    split_words(name).iter().any(|w| SANITIZER_WORDS.contains(&w.as_str()))
+}
+
+fn collect_identifiers(text: &str, braces_interpolate: bool, found: &mut Vec<String>) {
+    let chars: Vec<char> = text.chars().collect();
+    // Callee name (empty for plain brackets) of every open group
+    let mut groups: Vec<String> = Vec::new();
+    let mut pending_call: Option<String> = None;
+    let mut i = 0;
+
+    let sanitized = |groups: &[String]| groups.iter().any(|g| is_sanitizer(g));
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if c == '"' || c == '\'' || c == '`' {
+            let (content, next) = read_string(&chars, i, 0);
+            if !sanitized(&groups) {
+                for expr in placeholders(&content, c == '`', braces_interpolate) {
+                    collect_identifiers(&expr, braces_interpolate, found);
+                }
+            }
+            i = next;
+            continue;
+        }
+
+        if c.is_alphabetic() || c == '_' {
+            let (path, next) = read_path(&chars, i);
+            i = next;
+
+            // String prefixes: f"...", r"...", b"...", r#"..."#
+            let is_prefix = path.len() <= 2 && path.chars().all(|p| "fFrRbBuU".contains(p));
+            let mut hashes = 0;
+            while is_prefix && chars.get(i + hashes) == Some(&'#') {
+                hashes += 1;
+            }
+            if is_prefix && matches!(chars.get(i + hashes), Some('"') | Some('\'')) {
+                let (content, next) = read_string(&chars, i + hashes, hashes);
+                if !sanitized(&groups) {
+                    for expr in placeholders(&content, false, braces_interpolate) {
+                        collect_identifiers(&expr, braces_interpolate, found);
+                    }
+                }
+                i = next;
+                continue;
+            }
+
+            let mut j = i;
+            while chars.get(j).is_some_and(|c| c.is_whitespace()) {
+                j += 1;
+            }
+            let is_call = chars.get(j) == Some(&'(')
+                || (chars.get(j) == Some(&'!') && chars.get(j + 1) == Some(&'('));
+            if is_call {
+                let (receiver, method) = match path.rsplit_once('.') {
+                    Some((receiver, method)) => (Some(receiver), method),
+                    None => (None, path.as_str()),
+                };
+                if let Some(receiver) = receiver.filter(|_| !is_sanitizer(method) && !sanitized(&groups)) {
+                    found.push(receiver.to_string());
+                }
+                pending_call = Some(method.to_string());
+            } else if !sanitized(&groups) {
+                found.push(path);
+            }
+            continue;
+        }
+
+        if c.is_ascii_digit() {
+            while chars.get(i).is_some_and(|c| c.is_alphanumeric() || *c == '_' || *c == '.') {
+                i += 1;
+            }
+            continue;
+        }
+
+        match c {
+            '(' => groups.push(pending_call.take().unwrap_or_default()),
+            '[' | '{' => groups.push(String::new()),
+            ')' | ']' | '}' => {
+                groups.pop();
+            }
+            c if c == '!' || c.is_whitespace() => {}
+            _ => pending_call = None,
+        }
+        i += 1;
+    }
+}
+
+/// Read `a.b?.c` starting at `start`; returns the dotted path and the index after it
+fn read_path(chars: &[char], start: usize) -> (String, usize) {
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+    let mut path = String::new();
+    let mut i = start;
+    loop {
+        while chars.get(i).is_some_and(|c| is_ident(*c)) {
+            path.push(chars[i]);
+            i += 1;
+        }
+        let mut j = i;
+        if chars.get(j) == Some(&'?') {
+            j += 1;
+        }
+        if chars.get(j) == Some(&'.') && chars.get(j + 1).is_some_and(|c| c.is_alphabetic() || *c == '_') {
+            path.push('.');
+            i = j + 1;
+        } else {
+            return (path, i);
+        }
+    }
+}
+
+/// Read a string literal opening at `start`; returns its content and the index after it
+///
+/// `hashes` is the number of `#` around a Rust raw string, which also disables escapes.
+fn read_string(chars: &[char], start: usize, hashes: usize) -> (String, usize) {
+    let quote = chars[start];
+    let mut content = String::new();
+    let mut i = start + 1;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '\\' && hashes == 0 {
+            content.push(c);
+            if let Some(&next) = chars.get(i + 1) {
+                content.push(next);
+            }
+            i += 2;
+            continue;
+        }
+        if c == quote && (0..hashes).all(|h| chars.get(i + 1 + h) == Some(&'#')) {
+            return (content, i + 1 + hashes);
+        }
+        content.push(c);
+        i += 1;
+    }
+    (content, i)
+}
+
+/// Expressions inside the placeholders of a string's content
+fn placeholders(content: &str, template: bool, braces_interpolate: bool) -> Vec<String> {
+    let chars: Vec<char> = content.chars().collect();
+    let mut exprs = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let opens = chars[i] == '{' && chars.get(i + 1) != Some(&'{');
+        let dollar = i > 0 && chars[i - 1] == '$';
+        if chars[i] == '{' && chars.get(i + 1) == Some(&'{') {
+            i += 2;
+            continue;
+        }
+        if opens && ((template && dollar) || (!template && braces_interpolate)) {
+            let mut depth = 0;
+            let mut end = i;
+            for (j, &c) in chars.iter().enumerate().skip(i) {
+                match c {
+                    '{' => depth += 1,
+                    '}' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            end = j;
+                            break;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            if end > i {
+                let inner: String = chars[i + 1..end].iter().collect();
+                // Format specs and conversions: {x:?}, {x!r}, {x=}
+                let expr = if template {
+                    inner.as_str()
+                } else {
+                    inner.split([':', '!', '=']).next().unwrap_or("")
+                };
+                exprs.push(expr.to_string());
+                i = end + 1;
+                continue;
+            }
+        }
+        i += 1;
+    }
+    exprs
+}
+
+/// Parameter names in a parenthesized parameter list (without the parentheses)
+///
+/// Handles `name: Type` (Rust, Python, TS), defaults, `*args`/`**kwargs`,
+/// `Type name` (Java, C#), `name Type` (Go) and destructuring patterns.
+pub fn parameter_names(params: &str, ext: &str) -> Vec<String> {
+    let ident = |s: &str| -> Vec<String> {
+        s.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .filter(|w| w.chars().next().is_some_and(|c| c.is_alphabetic() || c == '_'))
+            .filter(|w| !matches!(*w, "mut" | "ref" | "self" | "this" | "final" | "const" | "let"))
+            .map(str::to_string)
+            .collect()
+    };
+
+    split_top_level(params)
+        .into_iter()
+        .flat_map(|piece| {
+            let piece = piece.trim();
+            if piece.starts_with('{') || piece.starts_with('[') {
+                // Destructured bindings each count: `{ email, name }`
+                let inner = piece.split(['=']).next().unwrap_or("");
+                return ident(inner.trim_start_matches(['{', '[']).split(['}', ']']).next().unwrap_or(""))
+                    .into_iter()
+                    .collect::<Vec<_>>();
+            }
+            let binding = match piece.split_once(':') {
+                Some((name, _)) => name,
+                None => piece.split('=').next().unwrap_or(""),
+            };
+            let words = ident(binding);
+            let name = if ext == "go" || piece.contains(':') { words.first() } else { words.last() };
+            name.cloned().into_iter().collect()
+        })
+        .collect()
+}
+
+fn split_top_level(text: &str) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut depth = 0i32;
+    let mut start = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '(' | '[' | '{' | '<' => depth += 1,
+            ')' | ']' | '}' | '>' => depth -= 1,
+            ',' if depth == 0 => {
+                pieces.push(&text[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    pieces.push(&text[start..]);
+    pieces
+}
+
+/// Whether `line` carries a suppression comment covering this scan
+pub fn suppresses(line: &str) -> bool {
+    let Some((_, rest)) = line.split_once(SUPPRESSION_MARKER) else {
+        return false;
+    };
+    let rules: Vec<&str> = rest
+        .split(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_'))
+        .filter(|w| !w.is_empty())
+        .collect();
+    rules.is_empty() || rules.contains(&"privacy")
+}
+
+/// Scan one file's content
+pub fn scan_source(file: &str, content: &str, names: &SensitiveNames) -> Vec<PrivacyFinding> {
+    let ext = get_file_extension(file).to_lowercase();
+    let Some(pattern) = log_call_pattern(&ext) else {
+        return Vec::new();
+    };
+    let lines: Vec<&str> = split_lines(content).collect();
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(content.match_indices('\n').map(|(i, _)| i + 1))
+        .collect();
+    let functions = extract_functions(content, file);
+
+    let mut findings: Vec<PrivacyFinding> = Vec::new();
+    for m in pattern.find_iter(content) {
+        let line_index = line_starts.partition_point(|&s| s <= m.start()) - 1;
+        let prefix = &content[line_starts[line_index]..m.start()];
+        let trimmed = prefix.trim_start();
+        if prefix.contains("//") || trimmed.starts_with('#') || trimmed.starts_with('*') || trimmed.starts_with("/*") {
+            continue;
+        }
+        // `obj.print(` is a method, not the builtin
+        if prefix.ends_with('.') && !m.as_str().contains('.') {
+            continue;
+        }
+        let suppressed = lines.get(line_index).is_some_and(|l| suppresses(l))
+            || line_index.checked_sub(1).and_then(|i| lines.get(i)).is_some_and(|l| suppresses(l));
+        if suppressed {
+            continue;
+        }
+        let Some(args) = call_arguments(content, m.end() - 1, &ext) else {
+            continue;
+        };
+
+        let line = line_index + 1;
+        let enclosing = functions.iter().filter(|(_, l)| *l <= line).max_by_key(|(_, l)| *l);
+        let parameters = enclosing
+            .map(|(name, l)| function_parameters(content, &line_starts, *l, name, &ext))
+            .unwrap_or_default();
+        let call = shorten_call(&content[m.start()..m.end() + args.len() + 1]);
+
+        for identifier in interpolated_identifiers(args, &ext) {
+            let Some(matched) = names.match_identifier(&identifier) else {
+                continue;
+            };
+            if findings.iter().any(|f| f.line == line && f.identifier == identifier) {
+                continue;
+            }
+            let root = identifier
+                .split('.')
+                .find(|s| !matches!(*s, "self" | "this"))
+                .unwrap_or(&identifier);
+            let from_parameter = parameters.iter().any(|p| p == root);
+            findings.push(PrivacyFinding {
+                file: file.to_string(),
+                line,
+                call: call.clone(),
+                identifier: identifier.clone(),
+                matched: matched.to_string(),
+                severity: if from_parameter { Severity::High } else { Severity::Medium },
+                from_parameter,
+                function: enclosing.map(|(name, _)| name.clone()),
+            });
+        }
+    }
+    findings
+}
+
+/// Parameters of the function `name` declared on 1-based `line`
+fn function_parameters(content: &str, line_starts: &[usize], line: usize, name: &str, ext: &str) -> Vec<String> {
+    let Some(&start) = line_starts.get(line - 1) else {
+        return Vec::new();
+    };
+    let declaration = &content[start..];
+    let Some(name_at) = declaration.find(name) else {
+        return Vec::new();
+    };
+    let Some(open) = declaration[name_at..].find('(').map(|i| start + name_at + i) else {
+        return Vec::new();
+    };
+    call_arguments(content, open, ext)
+        .map(|params| parameter_names(params, ext))
+        .unwrap_or_default()
+}
+
+fn shorten_call(call: &str) -> String {
+    let collapsed = call.split_whitespace().collect::<Vec<_>>().join(" ");
+    truncate_str_safe(&collapsed, MAX_CALL_CHARS)
+}
+
+/// Scan every file under `path`; paths in findings are relative to `path`
+pub fn scan_path(
+    path: &Path,
+    extensions: Option<&[String]>,
+    exclude: Option<&[String]>,
+    names: &SensitiveNames,
+) -> Result<Vec<PrivacyFinding>, Box<dyn std::error::Error>> {
+    use rayon::prelude::*;
+
+    let files = list_files(path, extensions, exclude)?;
+    let mut findings: Vec<PrivacyFinding> = files
+        .par_iter()
+        .filter(|f| log_call_pattern(get_file_extension(&f.path)).is_some())
+        .flat_map(|f| {
+            let content = std::fs::read_to_string(&f.path).unwrap_or_default();
+            let relative = Path::new(&f.path)
+                .strip_prefix(path)
+                .map(|p| p.to_string_lossy().replace('\\', "/"))
+                .unwrap_or_else(|_| f.path.clone());
+            scan_source(&relative, &content, names)
+        })
+        .collect();
+    findings.sort_by(|a, b| a.file.cmp(&b.file).then(a.line.cmp(&b.line)));
+    Ok(findings)
+}
+
+/// JSON document body: findings plus per-severity counts
+pub fn findings_to_json(findings: &[PrivacyFinding]) -> serde_json::Value {
+    let high = findings.iter().filter(|f| f.severity == Severity::High).count();
+    serde_json::json!({
+        "findings": findings,
+        "summary": { "total": findings.len(), "high": high, "medium": findings.len() - high },
+    })
+}
+
+/// SARIF 2.1.0 log with one result per finding
+pub fn findings_to_sarif(findings: &[PrivacyFinding]) -> serde_json::Value {
+    let results: Vec<serde_json::Value> = findings
+        .iter()
+        .map(|f| {
+            serde_json::json!({
+                "ruleId": RULE_ID,
+                "level": f.severity.sarif_level(),
+                "message": {
+                    "text": format!("`{}` (matches \"{}\") is passed to a logging call: {}", f.identifier, f.matched, f.call),
+                },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": f.file },
+                        "region": { "startLine": f.line },
+                    },
+                }],
+            })
+        })
+        .collect();
+
+    serde_json::json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "codesearch",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": "https://github.com/yingkitw/codesearch",
+                    "rules": [{
+                        "id": RULE_ID,
+                        "name": "SensitiveValueLogged",
+                        "shortDescription": { "text": "Sensitive-looking identifier passed to a logging or print call" },
+                        "help": { "text": format!("Remove or redact the value, or add a `{SUPPRESSION_MARKER}: privacy` comment if it is safe.") },
+                    }],
+                },
+            },
+            "results": results,
+        }],
+    })
+}
+
+/// Print findings grouped by file
+pub fn print_findings(findings: &[PrivacyFinding]) {
+    println!("{}", "Privacy Scan".cyan().bold());
+    println!("{}", theme::rule(30).cyan());
+    println!();
+
+    if findings.is_empty() {
+        println!("{}", "No sensitive values found in logging calls.".green());
+        return;
+    }
+
+    let mut current_file: Option<&str> = None;
+    for finding in findings {
+        if current_file != Some(finding.file.as_str()) {
+            println!("{}", finding.file.bold());
+            current_file = Some(finding.file.as_str());
+        }
+        let badge = match finding.severity {
+            Severity::High => "[HIGH]".red().bold(),
+            Severity::Medium => "[MEDIUM]".yellow(),
+        };
+        println!(
+            "  {}:{} {} {} (matches \"{}\"{})",
+            finding.file.dimmed(),
+            finding.line.to_string().yellow(),
+            badge,
+            finding.identifier.bold(),
+            finding.matched,
+            if finding.from_parameter { ", function parameter" } else { "" }
+        );
+        println!("      {}", finding.call.dimmed());
+    }
+
+    let high = findings.iter().filter(|f| f.severity == Severity::High).count();
+    println!();
+    println!(
+        "Found {} finding(s): {} high, {} medium",
+        findings.len(),
+        high,
+        findings.len() - high
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixture_dir() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/privacy")
+    }
+
+    fn identifiers(findings: &[PrivacyFinding]) -> Vec<&str> {
+        findings.iter().map(|f| f.identifier.as_str()).collect()
+    }
+
+    #[test]
+    fn test_split_words() {
+        assert_eq!(split_words("user_email"), vec!["user", "email"]);
+        assert_eq!(split_words("userSSN"), vec!["user", "ssn"]);
+        assert_eq!(split_words("APIKey"), vec!["api", "key"]);
+        assert_eq!(split_words("creditCardNumber"), vec!["credit", "card", "number"]);
+        assert_eq!(split_words("__token__"), vec!["token"]);
+    }
+
+    #[test]
+    fn test_match_identifier_whole_words() {
+        let names = SensitiveNames::default();
+        assert_eq!(names.match_identifier("user_email"), Some("email"));
+        assert_eq!(names.match_identifier("user.emailAddress"), Some("email"));
+        assert_eq!(names.match_identifier("apiKey"), Some("api_key"));
+        assert_eq!(names.match_identifier("auth_tokens"), Some("token"));
+        assert_eq!(names.match_identifier("tokenizer"), None);
+        assert_eq!(names.match_identifier("discard"), None);
+    }
+
+    #[test]
+    fn test_match_identifier_benign_lookalikes() {
+        let names = SensitiveNames::default();
+        assert_eq!(names.match_identifier("token_count"), None);
+        assert_eq!(names.match_identifier("password.length"), None);
+        assert_eq!(names.match_identifier("num_tokens"), None);
+        assert_eq!(names.match_identifier("has_password"), None);
+        assert_eq!(names.match_identifier("emailVerified"), None);
+    }
+
+    #[test]
+    fn test_configured_names_and_allow_list() {
+        let config = PrivacyConfig {
+            disable_builtins: true,
+            sensitive_names: vec!["salary".to_string()],
+            allow: vec!["public_salary".to_string()],
+        };
+        let names = SensitiveNames::from_config(&config, &["dateOfBirth".to_string()]);
+        assert_eq!(names.match_identifier("email"), None);
+        assert_eq!(names.match_identifier("monthlySalary"), Some("salary"));
+        assert_eq!(names.match_identifier("date_of_birth"), Some("date_of_birth"));
+        assert_eq!(names.match_identifier("public_salary"), None);
+    }
+
+    #[test]
+    fn test_interpolated_identifiers_rust() {
+        let ids = interpolated_identifiers(r#""user {email} logged in with {:?}", user.password"#, "rs");
+        assert_eq!(ids, vec!["email", "user.password"]);
+        let ids = interpolated_identifiers(r#""len {}", password.len()"#, "rs");
+        assert!(ids.is_empty(), "{ids:?}");
+        let ids = interpolated_identifiers(r#"r"{{literal}} {token}""#, "rs");
+        assert_eq!(ids, vec!["token"]);
+    }
+
+    #[test]
+    fn test_interpolated_identifiers_python_and_js() {
+        let ids = interpolated_identifiers(r#"f"signup {user.email!r} {len(token)}""#, "py");
+        assert_eq!(ids, vec!["user.email"]);
+        let ids = interpolated_identifiers(r#"`card ${card.last4} for ${mask(ssn)}`, "{email}""#, "js");
+        assert_eq!(ids, vec!["card.last4"]);
+    }
+
+    #[test]
+    fn test_parameter_names() {
+        assert_eq!(parameter_names("&self, email: &str, mut token: String", "rs"), vec!["email", "token"]);
+        assert_eq!(parameter_names("self, email, password=None, *args, **kwargs", "py"), vec!["email", "password", "args", "kwargs"]);
+        assert_eq!(parameter_names("{ email, name }, token = ''", "js"), vec!["email", "name", "token"]);
+        assert_eq!(parameter_names("String email, final int count", "java"), vec!["email", "count"]);
+        assert_eq!(parameter_names("email string, n int", "go"), vec!["email", "n"]);
+    }
+
+    #[test]
+    fn test_suppresses() {
+        assert!(suppresses("log::info!(\"{token}\"); // codesearch-ignore"));
+        assert!(suppresses("# codesearch-ignore: privacy"));
+        assert!(suppresses("/* codesearch-ignore privacy */"));
+        assert!(!suppresses("// codesearch-ignore: deadcode"));
+        assert!(!suppresses("println!(\"{token}\");"));
+    }
+
+    #[test]
+    fn test_severity_raised_for_parameters() {
+        let source = "fn login(email: &str) {\n    let token = issue();\n    log::info!(\"login {email} {token}\");\n}\n";
+        let findings = scan_source("auth.rs", source, &SensitiveNames::default());
+        assert_eq!(identifiers(&findings), vec!["email", "token"]);
+        assert_eq!(findings[0].severity, Severity::High);
+        assert!(findings[0].from_parameter);
+        assert_eq!(findings[0].function.as_deref(), Some("login"));
+        assert_eq!(findings[1].severity, Severity::Medium);
+        assert_eq!(findings[0].line, 3);
+    }
+
+    #[test]
+    fn test_multiline_calls_and_comments() {
+        let source = "fn f() {\n    // println!(\"{password}\");\n    println!(\n        \"{}\",\n        secret\n    );\n}\n";
+        let findings = scan_source("a.rs", source, &SensitiveNames::default());
+        assert_eq!(identifiers(&findings), vec!["secret"]);
+        assert_eq!(findings[0].line, 3);
+    }
+
+    #[test]
+    fn test_fixture_true_positives() {
+        let findings = scan_path(&fixture_dir(), None, None, &SensitiveNames::default()).unwrap();
+        let leaks: Vec<(&str, usize, &str, Severity)> = findings
+            .iter()
+            .map(|f| (f.file.as_str(), f.line, f.identifier.as_str(), f.severity))
+            .collect();
+        assert_eq!(
+            leaks,
+            vec![
+                ("leaky.js", 2, "user.email", Severity::High),
+                ("leaky.js", 3, "apiKey", Severity::Medium),
+                ("leaky.py", 5, "password", Severity::High),
+                ("leaky.py", 6, "ssn", Severity::Medium),
+                ("leaky.rs", 3, "card_number", Severity::High),
+                ("leaky.rs", 5, "session_token", Severity::Medium),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fixture_benign_lookalikes_are_not_reported() {
+        let findings = scan_path(&fixture_dir(), None, None, &SensitiveNames::default()).unwrap();
+        assert!(findings.iter().all(|f| !f.file.starts_with("benign")), "{findings:?}");
+    }
+
+    #[test]
+    fn test_json_and_sarif_output() {
+        let findings = scan_source("a.py", "def f(email):\n    print(f\"{email}\")\n", &SensitiveNames::default());
+        let json = findings_to_json(&findings);
+        assert_eq!(json["summary"]["high"], 1);
+        assert_eq!(json["findings"][0]["severity"], "high");
+
+        let sarif = findings_to_sarif(&findings);
+        assert_eq!(sarif["version"], "2.1.0");
+        let run = &sarif["runs"][0];
+        assert_eq!(run["tool"]["driver"]["rules"][0]["id"], RULE_ID);
+        let result = &run["results"][0];
+        assert_eq!(result["ruleId"], RULE_ID);
+        assert_eq!(result["level"], "error");
+        assert_eq!(result["locations"][0]["physicalLocation"]["artifactLocation"]["uri"], "a.py");
+        assert_eq!(result["locations"][0]["physicalLocation"]["region"]["startLine"], 2);
+    }
+}
@@ -0,0 +1,141 @@
+//! Profiles
+//!
+//! Named bundles of option values, selected with `--profile`. The built-in
+//! `quick`, `thorough` and `ci` profiles cover the common invocations, and
+//! config files can add their own under `[profiles.<name>]`. A profile only
+//! fills in options the command line leaves unset, so explicit flags win.
+
+use crate::errors::SearchError;
+use crate::types::SortKey;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Names of the built-in profiles
+pub const BUILTIN_PROFILES: [&str; 3] = ["quick", "thorough", "ci"];
+
+/// Option values a profile sets; `None` leaves the command's own default
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Profile {
+    /// Match queries without regex syntax as plain text
+    pub literal: Option<bool>,
+    /// Skip common build directories (target, node_modules, ...)
+    pub auto_exclude: Option<bool>,
+    /// Skip paths matched by `.gitignore` files
+    pub gitignore: Option<bool>,
+    /// Maximum matching lines per file
+    pub max_results: Option<usize>,
+    /// Maximum results in total
+    pub max_total: Option<usize>,
+    pub multiline: Option<bool>,
+    /// With `multiline`, skip files larger than this many bytes
+    pub multiline_max_bytes: Option<u64>,
+    pub rank: Option<bool>,
+    /// Result order, e.g. `path` for output that is the same on every run
+    pub sort_by: Option<SortKey>,
+    /// Output format of the commands that have `--format`
+    pub format: Option<String>,
+    /// Print only summaries, not each finding
+    pub quiet: Option<bool>,
+    /// Colored output (`false` turns colors off everywhere)
+    pub color: Option<bool>,
+    /// Exit with the command's gate code when it finds anything
+    /// (`search --fail-on-matches`, `deadcode --fail-on-deadcode`)
+    pub fail_on_findings: Option<bool>,
+}
+
+impl Profile {
+    /// The built-in profile called `name`
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "quick" => Some(Self {
+                literal: Some(true),
+                auto_exclude: Some(true),
+                max_results: Some(5),
+                rank: Some(false),
+                ..Self::default()
+            }),
+            // Every file whose content is text: no build-directory or
+            // `.gitignore` skipping, binary files are still left out when read
+            "thorough" => Some(Self {
+                auto_exclude: Some(false),
+                gitignore: Some(false),
+                max_results: Some(1000),
+                multiline: Some(true),
+                multiline_max_bytes: Some(64 * 1024 * 1024),
+                rank: Some(true),
+                ..Self::default()
+            }),
+            "ci" => Some(Self {
+                sort_by: Some(SortKey::Path),
+                format: Some("json".to_string()),
+                quiet: Some(true),
+                color: Some(false),
+                fail_on_findings: Some(true),
+                ..Self::default()
+            }),
+            _ => None,
+        }
+    }
+
+    /// The profile called `name`: one from the config's `[profiles]`, or else a built-in
+    pub fn resolve(name: &str, configured: &HashMap<String, Profile>) -> Result<Self, SearchError> {
+        configured
+            .get(name)
+            .cloned()
+            .or_else(|| Self::builtin(name))
+            .ok_or_else(|| {
+                let mut known: Vec<&str> = BUILTIN_PROFILES.to_vec();
+                let mut custom: Vec<&str> = configured.keys().map(String::as_str).collect();
+                custom.sort_unstable();
+                known.extend(custom);
+                SearchError::InvalidOptions {
+                    message: format!("unknown profile {name:?} (expected one of: {})", known.join(", ")),
+                }
+            })
+    }
+}
+
+/// Fill `field` from a profile value unless the flag was given explicitly
+pub fn fill<T: Clone>(field: &mut T, value: Option<&T>, explicit: bool) {
+    if let (Some(value), false) = (value, explicit) {
+        *field = value.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_builtin_resolves() {
+        for name in BUILTIN_PROFILES {
+            assert_eq!(Profile::resolve(name, &HashMap::new()).unwrap(), Profile::builtin(name).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_configured_profile_shadows_builtin() {
+        let custom = Profile { max_results: Some(2), ..Profile::default() };
+        let configured = HashMap::from([("quick".to_string(), custom.clone())]);
+        assert_eq!(Profile::resolve("quick", &configured).unwrap(), custom);
+    }
+
+    #[test]
+    fn test_unknown_profile_lists_the_known_ones() {
+        let configured = HashMap::from([("myteam".to_string(), Profile::default())]);
+        let err = Profile::resolve("fast", &configured).unwrap_err().to_string();
+        assert!(err.contains("quick, thorough, ci, myteam"), "{err}");
+    }
+
+    #[test]
+    fn test_fill_keeps_explicit_values() {
+        let mut value = 3;
+        fill(&mut value, Some(&5), true);
+        assert_eq!(value, 3);
+        fill(&mut value, Some(&5), false);
+        assert_eq!(value, 5);
+        fill(&mut value, None, false);
+        assert_eq!(value, 5);
+    }
+}
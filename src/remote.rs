@@ -72,19 +72,11 @@ impl RemoteSearcher {
         use crate::search::search_code;
         use crate::types::SearchOptions;
         
-        let options = SearchOptions {
-            extensions: extensions.map(|e| e.to_vec()),
-            ignore_case: false,
-            fuzzy: false,
-            fuzzy_threshold: 0.6,
-            max_results: 100,
-            exclude: Some(vec!["target".to_string(), "node_modules".to_string(), ".git".to_string()]),
-            rank: false,
-            cache: false,
-            semantic: false,
-            benchmark: false,
-            vs_grep: false,
-        };
+        let options = SearchOptions::builder()
+            .extensions(extensions.map(|e| e.to_vec()))
+            .max_results(100)
+            .exclude(vec!["target".to_string(), "node_modules".to_string(), ".git".to_string()])
+            .build();
         
         let search_results = search_code(pattern, path, &options)?;
 
@@ -0,0 +1,526 @@
+//! Report Module
+//!
+//! Onboarding report that rolls the metrics and dead-code analyses up per
+//! directory, together with each directory's README summary and CODEOWNERS owners.
+
+use crate::codemetrics::{analyze_project_metrics_timed, ProjectMetrics};
+use crate::deadcode::{find_dead_code_in, DeadCodeItem, DeadCodeOptions};
+use crate::language::get_language_name;
+use crate::parser::ExtractionContext;
+use crate::pathglob::compile_glob;
+use crate::timings::{Phase, Timings};
+use crate::theme::{self, Glyph};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Key used for files directly under the report root
+pub const ROOT_DIRECTORY: &str = ".";
+
+/// Number of most complex files listed per directory
+const TOP_COMPLEX_FILES: usize = 5;
+
+/// README file names, checked case-insensitively
+const README_NAMES: [&str; 4] = ["readme.md", "readme", "readme.rst", "readme.txt"];
+
+/// CODEOWNERS locations, relative to the report root
+pub const CODEOWNERS_PATHS: [&str; 3] = ["CODEOWNERS", ".github/CODEOWNERS", "docs/CODEOWNERS"];
+
+/// The facts about one file the report needs
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileFacts {
+    /// Path relative to the report root, `/`-separated
+    pub path: String,
+    pub language: String,
+    pub sloc: usize,
+    pub cyclomatic: usize,
+}
+
+/// One CODEOWNERS line
+#[derive(Debug, Clone, PartialEq)]
+pub struct OwnerRule {
+    pub pattern: String,
+    pub owners: Vec<String>,
+}
+
+/// Everything the rollup is computed from
+#[derive(Debug, Clone, Default)]
+pub struct ReportInputs {
+    pub files: Vec<FileFacts>,
+    /// Dead-code findings with paths relative to the report root
+    pub dead_code: Vec<DeadCodeItem>,
+    /// First README paragraph, keyed by directory relative to the root
+    pub readmes: BTreeMap<String, String>,
+    pub codeowners: Vec<OwnerRule>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LanguageStat {
+    pub language: String,
+    pub files: usize,
+    pub sloc: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ComplexFile {
+    pub path: String,
+    pub cyclomatic: usize,
+}
+
+/// Summary of one directory
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DirectorySummary {
+    pub directory: String,
+    pub files: usize,
+    pub sloc: usize,
+    pub languages: Vec<LanguageStat>,
+    pub top_complex_files: Vec<ComplexFile>,
+    pub dead_code_count: usize,
+    pub todo_count: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub readme_summary: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub owners: Vec<String>,
+}
+
+/// The directory a root-relative file path is grouped under at `depth`
+///
+/// Depth 0 groups everything under [`ROOT_DIRECTORY`]; files shallower than
+/// `depth` are grouped under their own parent directory.
+pub fn directory_key(path: &str, depth: usize) -> String {
+    let components: Vec<&str> = path.split('/').filter(|c| !c.is_empty() && *c != ".").collect();
+    let dirs = &components[..components.len().saturating_sub(1)];
+    let take = depth.min(dirs.len());
+    if take == 0 {
+        ROOT_DIRECTORY.to_string()
+    } else {
+        dirs[..take].join("/")
+    }
+}
+
+/// Roll the inputs up into one summary per directory, sorted by directory
+pub fn summarize_directories(inputs: &ReportInputs, depth: usize) -> Vec<DirectorySummary> {
+    let mut groups: BTreeMap<String, Vec<&FileFacts>> = BTreeMap::new();
+    for file in &inputs.files {
+        groups.entry(directory_key(&file.path, depth)).or_default().push(file);
+    }
+
+    let mut dead_code: BTreeMap<String, (usize, usize)> = BTreeMap::new();
+    for item in &inputs.dead_code {
+        let counts = dead_code.entry(directory_key(&item.file, depth)).or_default();
+        if item.item_type == "todo" {
+            counts.1 += 1;
+        } else {
+            counts.0 += 1;
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|(directory, files)| {
+            let mut languages: BTreeMap<&str, (usize, usize)> = BTreeMap::new();
+            for file in &files {
+                let stat = languages.entry(file.language.as_str()).or_default();
+                stat.0 += 1;
+                stat.1 += file.sloc;
+            }
+            let mut languages: Vec<LanguageStat> = languages
+                .into_iter()
+                .map(|(language, (files, sloc))| LanguageStat {
+                    language: language.to_string(),
+                    files,
+                    sloc,
+                })
+                .collect();
+            languages.sort_by(|a, b| b.sloc.cmp(&a.sloc).then_with(|| a.language.cmp(&b.language)));
+
+            let mut top_complex_files: Vec<ComplexFile> = files
+                .iter()
+                .map(|f| ComplexFile {
+                    path: f.path.clone(),
+                    cyclomatic: f.cyclomatic,
+                })
+                .collect();
+            top_complex_files.sort_by(|a, b| b.cyclomatic.cmp(&a.cyclomatic).then_with(|| a.path.cmp(&b.path)));
+            top_complex_files.truncate(TOP_COMPLEX_FILES);
+
+            let (dead_code_count, todo_count) = dead_code.get(&directory).copied().unwrap_or_default();
+
+            DirectorySummary {
+                files: files.len(),
+                sloc: files.iter().map(|f| f.sloc).sum(),
+                languages,
+                top_complex_files,
+                dead_code_count,
+                todo_count,
+                readme_summary: inputs.readmes.get(&directory).cloned(),
+                owners: owners_for(&inputs.codeowners, &directory),
+                directory,
+            }
+        })
+        .collect()
+}
+
+/// First prose paragraph of a README, skipping headings, badges and blank lines
+pub fn first_paragraph(readme: &str) -> Option<String> {
+    let mut paragraph: Vec<&str> = Vec::new();
+    for line in readme.lines().map(str::trim) {
+        let skippable = line.is_empty()
+            || line.starts_with('#')
+            || line.starts_with("[![")
+            || line.starts_with("![")
+            || line.starts_with('<')
+            || line.chars().all(|c| c == '=' || c == '-');
+        if skippable {
+            if paragraph.is_empty() {
+                continue;
+            }
+            break;
+        }
+        paragraph.push(line);
+    }
+    (!paragraph.is_empty()).then(|| paragraph.join(" "))
+}
+
+/// Parse CODEOWNERS content, ignoring comments and lines without owners
+pub fn parse_codeowners(content: &str) -> Vec<OwnerRule> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let pattern = parts.next()?.to_string();
+            let owners: Vec<String> = parts.take_while(|p| !p.starts_with('#')).map(String::from).collect();
+            (!owners.is_empty()).then_some(OwnerRule { pattern, owners })
+        })
+        .collect()
+}
+
+/// Parse the CODEOWNERS file of `root`, the first of [`CODEOWNERS_PATHS`] that exists
+pub fn read_codeowners(root: &Path) -> Option<Vec<OwnerRule>> {
+    CODEOWNERS_PATHS
+        .iter()
+        .find_map(|p| std::fs::read_to_string(root.join(p)).ok())
+        .map(|content| parse_codeowners(&content))
+}
+
+/// Owners of a directory: the last rule matching the directory or one of its ancestors
+///
+/// Works the same for a file path, which rules can also match by name (`*.rs`).
+pub fn owners_for(rules: &[OwnerRule], directory: &str) -> Vec<String> {
+    let candidates: Vec<String> = if directory == ROOT_DIRECTORY {
+        Vec::new()
+    } else {
+        let parts: Vec<&str> = directory.split('/').collect();
+        (1..=parts.len()).map(|n| parts[..n].join("/")).collect()
+    };
+
+    rules
+        .iter()
+        .rev()
+        .find(|rule| {
+            if rule.pattern == "*" || (rule.pattern == "/*" && directory == ROOT_DIRECTORY) {
+                return true;
+            }
+            let anchored = rule.pattern.starts_with('/') || rule.pattern.trim_end_matches('/').contains('/');
+            let pattern = rule.pattern.trim_start_matches('/').trim_end_matches('/');
+            let pattern = if anchored { pattern.to_string() } else { format!("**/{pattern}") };
+            match compile_glob(&pattern) {
+                Ok(glob) => candidates.iter().any(|dir| glob.is_match(dir)),
+                Err(_) => false,
+            }
+        })
+        .map(|rule| rule.owners.clone())
+        .unwrap_or_default()
+}
+
+/// Run the analyses and gather READMEs and CODEOWNERS for `root`
+///
+/// `context` must have been built for the same root, extensions and excludes.
+pub fn collect_report_inputs(
+    root: &Path,
+    context: &ExtractionContext,
+    extensions: Option<&[String]>,
+    exclude: Option<&[String]>,
+    depth: usize,
+    timings: &Timings,
+) -> Result<ReportInputs, Box<dyn std::error::Error>> {
+    let metrics = analyze_project_metrics_timed(root, extensions, exclude, timings)?;
+    let files = timings.time(Phase::Aggregate, || file_facts(root, &metrics));
+
+    let dead_code = timings.time(Phase::Compare, || {
+        find_dead_code_in(context, &DeadCodeOptions::default())
+            .into_iter()
+            .map(|mut item| {
+                item.file = relative_path(root, Path::new(&item.file));
+                item
+            })
+            .collect()
+    });
+
+    let mut readmes = BTreeMap::new();
+    for file in &files {
+        let directory = directory_key(&file.path, depth);
+        if readmes.contains_key(&directory) {
+            continue;
+        }
+        let dir_path = if directory == ROOT_DIRECTORY { root.to_path_buf() } else { root.join(&directory) };
+        if let Some(summary) = timings.time(Phase::Read, || find_readme(&dir_path)).and_then(|text| first_paragraph(&text)) {
+            readmes.insert(directory, summary);
+        }
+    }
+
+    let codeowners = timings.time(Phase::Read, || read_codeowners(root)).unwrap_or_default();
+
+    Ok(ReportInputs {
+        files,
+        dead_code,
+        readmes,
+        codeowners,
+    })
+}
+
+fn file_facts(root: &Path, metrics: &ProjectMetrics) -> Vec<FileFacts> {
+    metrics
+        .files
+        .iter()
+        .map(|f| FileFacts {
+            path: relative_path(root, Path::new(&f.file_path)),
+            language: get_language_name(&f.file_path),
+            sloc: f.size.source_lines,
+            cyclomatic: f.complexity.cyclomatic_complexity,
+        })
+        .collect()
+}
+
+/// `path` relative to `root`, `/`-separated; unchanged if it isn't under `root`
+pub(crate) fn relative_path(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+fn find_readme(dir: &Path) -> Option<String> {
+    let entries = std::fs::read_dir(dir).ok()?;
+    let mut names: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.is_file())
+        .collect();
+    names.sort();
+    README_NAMES.iter().find_map(|wanted| {
+        names
+            .iter()
+            .find(|p| p.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.eq_ignore_ascii_case(wanted)))
+            .and_then(|p| std::fs::read_to_string(p).ok())
+    })
+}
+
+/// Render one directory summary as a Markdown section
+pub fn render_markdown(summary: &DirectorySummary) -> String {
+    let mut out = format!("## {}\n\n", summary.directory);
+
+    if let Some(readme) = &summary.readme_summary {
+        out.push_str(&format!("> {readme}\n\n"));
+    }
+    if !summary.owners.is_empty() {
+        out.push_str(&format!("**Owners:** {}\n\n", summary.owners.join(", ")));
+    }
+
+    let sep = theme::glyph(Glyph::Separator);
+    out.push_str(&format!(
+        "**Files:** {} {sep} **SLOC:** {} {sep} **Dead code:** {} {sep} **TODOs:** {}\n\n",
+        summary.files, summary.sloc, summary.dead_code_count, summary.todo_count
+    ));
+
+    out.push_str("| Language | Files | SLOC |\n|---|---:|---:|\n");
+    for lang in &summary.languages {
+        out.push_str(&format!("| {} | {} | {} |\n", lang.language, lang.files, lang.sloc));
+    }
+    out.push('\n');
+
+    if !summary.top_complex_files.is_empty() {
+        out.push_str("**Most complex files:**\n\n");
+        for file in &summary.top_complex_files {
+            out.push_str(&format!("- `{}` (cyclomatic {})\n", file.path, file.cyclomatic));
+        }
+        out.push('\n');
+    }
+
+    out
+}
+
+/// File name used for a directory's page in `--export-dir` mode
+pub fn export_file_name(directory: &str) -> String {
+    if directory == ROOT_DIRECTORY {
+        "_root.md".to_string()
+    } else {
+        format!("{}.md", directory.replace('/', "__"))
+    }
+}
+
+/// Write one Markdown page per directory into `dir`, returning the paths written
+pub fn export_pages(summaries: &[DirectorySummary], dir: &Path) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    std::fs::create_dir_all(dir)?;
+    let mut written = Vec::new();
+    for summary in summaries {
+        let path = dir.join(export_file_name(&summary.directory));
+        std::fs::write(&path, format!("# Report: {}\n\n{}", summary.directory, render_markdown(summary)))?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deadcode::Confidence;
+
+
+    fn facts(path: &str, language: &str, sloc: usize, cyclomatic: usize) -> FileFacts {
+        FileFacts {
+            path: path.to_string(),
+            language: language.to_string(),
+            sloc,
+            cyclomatic,
+        }
+    }
+
+    fn item(file: &str, item_type: &str) -> DeadCodeItem {
+        DeadCodeItem {
+            file: file.to_string(),
+            line_number: 1,
+            item_type: item_type.to_string(),
+            name: "x".to_string(),
+            reason: String::new(),
+            confidence: Confidence::High,
+        }
+    }
+
+    #[test]
+    fn test_directory_key() {
+        assert_eq!(directory_key("src/search/core.rs", 1), "src");
+        assert_eq!(directory_key("src/search/core.rs", 2), "src/search");
+        assert_eq!(directory_key("src/search/core.rs", 5), "src/search");
+        assert_eq!(directory_key("main.rs", 1), ROOT_DIRECTORY);
+        assert_eq!(directory_key("src/main.rs", 0), ROOT_DIRECTORY);
+        assert_eq!(directory_key("./src/main.rs", 1), "src");
+    }
+
+    #[test]
+    fn test_summarize_groups_by_prefix() {
+        let inputs = ReportInputs {
+            files: vec![
+                facts("api/handler.rs", "Rust", 40, 9),
+                facts("api/routes.rs", "Rust", 10, 2),
+                facts("api/gen/client.py", "Python", 30, 4),
+                facts("main.rs", "Rust", 5, 1),
+            ],
+            dead_code: vec![item("api/handler.rs", "todo"), item("api/routes.rs", "function"), item("main.rs", "todo")],
+            readmes: BTreeMap::from([("api".to_string(), "HTTP layer.".to_string())]),
+            codeowners: parse_codeowners("* @all\n/api/ @api-team\n"),
+        };
+
+        let summaries = summarize_directories(&inputs, 1);
+        assert_eq!(summaries.len(), 2);
+
+        let root = &summaries[0];
+        assert_eq!(root.directory, ROOT_DIRECTORY);
+        assert_eq!(root.todo_count, 1);
+        assert_eq!(root.owners, vec!["@all"]);
+        assert_eq!(root.readme_summary, None);
+
+        let api = &summaries[1];
+        assert_eq!(api.directory, "api");
+        assert_eq!(api.files, 3);
+        assert_eq!(api.sloc, 80);
+        assert_eq!(api.languages[0], LanguageStat { language: "Rust".to_string(), files: 2, sloc: 50 });
+        assert_eq!(api.top_complex_files[0].path, "api/handler.rs");
+        assert_eq!(api.dead_code_count, 1);
+        assert_eq!(api.todo_count, 1);
+        assert_eq!(api.readme_summary.as_deref(), Some("HTTP layer."));
+        assert_eq!(api.owners, vec!["@api-team"]);
+    }
+
+    #[test]
+    fn test_missing_readme_and_codeowners_are_omitted() {
+        let inputs = ReportInputs {
+            files: vec![facts("core/engine.py", "Python", 12, 3)],
+            ..Default::default()
+        };
+        let summaries = summarize_directories(&inputs, 1);
+        assert_eq!(summaries[0].readme_summary, None);
+        assert!(summaries[0].owners.is_empty());
+
+        let json = serde_json::to_value(&summaries[0]).unwrap();
+        assert!(json.get("readme_summary").is_none());
+        assert!(json.get("owners").is_none());
+
+        let markdown = render_markdown(&summaries[0]);
+        assert!(!markdown.contains("Owners"));
+        assert!(!markdown.contains("> "));
+    }
+
+    #[test]
+    fn test_first_paragraph() {
+        let readme = "# Title\n[![ci](badge.svg)](ci)\n\nFirst line\nsecond line.\n\nNext paragraph.";
+        assert_eq!(first_paragraph(readme).as_deref(), Some("First line second line."));
+        assert_eq!(first_paragraph("# Only a heading\n"), None);
+        assert_eq!(first_paragraph("Title\n=====\n\nBody.").as_deref(), Some("Title"));
+    }
+
+    #[test]
+    fn test_codeowners_last_match_wins() {
+        let rules = parse_codeowners("# comment\n*       @all\nsrc/    @src-team\n/src/search/ @search-team # inline\ndocs\n");
+        assert_eq!(rules.len(), 3);
+        assert_eq!(owners_for(&rules, "src/search"), vec!["@search-team"]);
+        assert_eq!(owners_for(&rules, "src/index"), vec!["@src-team"]);
+        assert_eq!(owners_for(&rules, "tests"), vec!["@all"]);
+        assert_eq!(owners_for(&[], "src"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_unanchored_pattern_matches_at_any_depth() {
+        let rules = parse_codeowners("fixtures/ @qa\n");
+        assert_eq!(owners_for(&rules, "tests/fixtures"), vec!["@qa"]);
+        assert_eq!(owners_for(&rules, "tests/fixtures/schema"), vec!["@qa"]);
+        assert!(owners_for(&rules, "tests").is_empty());
+    }
+
+    #[test]
+    fn test_report_on_fixture_tree() {
+        let exts = vec!["rs".to_string(), "py".to_string()];
+        let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/report");
+        let context = ExtractionContext::build(&fixture, Some(&exts), None).unwrap();
+        let inputs = collect_report_inputs(&fixture, &context, Some(&exts), None, 1, &Timings::disabled()).unwrap();
+        let summaries = summarize_directories(&inputs, 1);
+
+        let names: Vec<&str> = summaries.iter().map(|s| s.directory.as_str()).collect();
+        assert_eq!(names, vec!["api", "core"]);
+
+        let api = &summaries[0];
+        assert_eq!(api.readme_summary.as_deref(), Some("Handles incoming HTTP requests and routes them to the core."));
+        assert_eq!(api.owners, vec!["@api-team"]);
+        assert!(api.todo_count >= 1);
+        assert_eq!(api.languages[0].language, "Rust");
+
+        let core = &summaries[1];
+        assert_eq!(core.readme_summary, None);
+        assert_eq!(core.owners, vec!["@platform"]);
+        assert_eq!(core.languages[0].language, "Python");
+    }
+
+    #[test]
+    fn test_export_pages() {
+        let dir = tempfile::tempdir().unwrap();
+        let inputs = ReportInputs {
+            files: vec![facts("a/b/x.rs", "Rust", 1, 1), facts("y.rs", "Rust", 1, 1)],
+            ..Default::default()
+        };
+        let written = export_pages(&summarize_directories(&inputs, 2), dir.path()).unwrap();
+        let names: Vec<String> = written.iter().map(|p| p.file_name().unwrap().to_string_lossy().to_string()).collect();
+        assert_eq!(names, vec!["_root.md", "a__b.md"]);
+    }
+}
@@ -0,0 +1,456 @@
+//! Output Schema Module
+//!
+//! Versioned envelopes for the JSON documents emitted by the CLI, plus explicit
+//! conversions to and from older shapes for the `--schema-version` flag.
+
+use crate::types::{GrepComparison, LimitReached, SearchMetrics, SearchOutcome, SearchResult, SearchStats, SCHEMA_VERSION};
+use crate::timings::Meta;
+use serde::{Deserialize, Serialize};
+use std::io::{self, Write};
+use std::str::FromStr;
+
+/// Schema versions that can be requested with `--schema-version`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaVersion {
+    /// Pre-versioning shape: bare documents without a `schema_version` field
+    V0,
+    /// Current shape (see [`SCHEMA_VERSION`])
+    V1,
+}
+
+impl SchemaVersion {
+    /// The schema version emitted when no flag is given
+    pub fn current() -> Self {
+        SchemaVersion::V1
+    }
+}
+
+impl FromStr for SchemaVersion {
+    type Err = String;
+
+    /// Parse a semver string, matching on the major component only
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let major = s.trim().trim_start_matches('v').split('.').next().unwrap_or("");
+        match major {
+            "0" => Ok(SchemaVersion::V0),
+            "1" => Ok(SchemaVersion::V1),
+            _ => Err(format!(
+                "Unsupported schema version: {s} (supported: 0, {SCHEMA_VERSION})"
+            )),
+        }
+    }
+}
+
+/// Top-level document wrapper carrying the schema version
+///
+/// The wrapped data is flattened into the document, so `T` must serialize as a
+/// struct or map. List-shaped documents use a dedicated wrapper such as
+/// [`SearchOutput`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Versioned<T> {
+    pub schema_version: String,
+    #[serde(flatten)]
+    pub data: T,
+}
+
+impl<T> Versioned<T> {
+    /// Wrap a document in the current schema version
+    pub fn new(data: T) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION.to_string(),
+            data,
+        }
+    }
+
+    /// Convert to the v0 shape (the bare document)
+    pub fn into_v0(self) -> T {
+        self.data
+    }
+}
+
+/// Document with an optional `meta` section, such as `--verbose` phase timings (schema 1.11+)
+///
+/// Like [`Versioned`], the wrapped data is flattened and must serialize as a
+/// struct or map.
+#[derive(Debug, Clone, Serialize)]
+pub struct WithMeta<T> {
+    #[serde(flatten)]
+    pub data: T,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Meta>,
+}
+
+impl<T> WithMeta<T> {
+    pub fn new(data: T, meta: Option<Meta>) -> Self {
+        Self { data, meta }
+    }
+}
+
+/// Search results document (schema 1.x)
+///
+/// Schema 0 emitted the results as a bare JSON array.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchOutput {
+    pub schema_version: String,
+    pub query: String,
+    pub total_results: usize,
+    /// Which result limits truncated `results` (schema 1.4+)
+    #[serde(default)]
+    pub limit_reached: LimitReached,
+    /// Result summary, present with `--stats` (schema 1.6+)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stats: Option<SearchStats>,
+    /// Performance metrics, present with `--benchmark` (schema 1.6+)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metrics: Option<SearchMetrics>,
+    /// grep timing, present with `--vs-grep` when grep could be run (schema 1.6+)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grep_comparison: Option<GrepComparison>,
+    /// Paths from `--files-from` that weren't found (schema 1.15+)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub missing_files: Vec<String>,
+    pub results: Vec<SearchResult>,
+}
+
+impl SearchOutput {
+    /// Create a search document in the current schema version
+    pub fn new(query: &str, results: Vec<SearchResult>) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION.to_string(),
+            query: query.to_string(),
+            total_results: results.len(),
+            limit_reached: LimitReached::default(),
+            stats: None,
+            metrics: None,
+            grep_comparison: None,
+            missing_files: Vec::new(),
+            results,
+        }
+    }
+
+    /// Create a search document carrying the outcome's limit flags
+    pub fn from_outcome(query: &str, outcome: SearchOutcome) -> Self {
+        Self {
+            limit_reached: outcome.limit_reached,
+            ..Self::new(query, outcome.results)
+        }
+    }
+
+    /// Convert from the v0 shape (a bare array of results)
+    pub fn from_v0(query: &str, results: Vec<SearchResult>) -> Self {
+        Self::new(query, results)
+    }
+
+    /// Convert to the v0 shape (a bare array of results)
+    pub fn into_v0(self) -> Vec<SearchResult> {
+        self.results
+    }
+}
+
+/// A result reduced to its matched text, for `--only-matching`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OnlyMatchingResult {
+    pub file: String,
+    pub line_number: usize,
+    pub matches: Vec<String>,
+}
+
+/// `--only-matching` results document (schema 1.2+)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OnlyMatchingOutput {
+    pub schema_version: String,
+    pub query: String,
+    pub total_results: usize,
+    pub results: Vec<OnlyMatchingResult>,
+}
+
+/// Render `--only-matching` results as pretty JSON in the requested schema version
+///
+/// Schema 0 has no dedicated shape, so it gets the bare array of results.
+pub fn only_matching_to_json(
+    query: &str,
+    results: Vec<OnlyMatchingResult>,
+    version: SchemaVersion,
+) -> Result<String, serde_json::Error> {
+    match version {
+        SchemaVersion::V0 => serde_json::to_string_pretty(&results),
+        SchemaVersion::V1 => serde_json::to_string_pretty(&OnlyMatchingOutput {
+            schema_version: SCHEMA_VERSION.to_string(),
+            query: query.to_string(),
+            total_results: results.len(),
+            results,
+        }),
+    }
+}
+
+/// Render search results as pretty JSON in the requested schema version
+pub fn search_results_to_json(
+    query: &str,
+    results: &[SearchResult],
+    version: SchemaVersion,
+) -> Result<String, serde_json::Error> {
+    match version {
+        SchemaVersion::V0 => serde_json::to_string_pretty(results),
+        SchemaVersion::V1 => serde_json::to_string_pretty(&SearchOutput::new(query, results.to_vec())),
+    }
+}
+
+/// Render a search outcome, including its `limit_reached` flags, in the requested schema version
+///
+/// Schema 0 has no place for the flags and gets the bare array of results.
+pub fn search_outcome_to_json(
+    query: &str,
+    outcome: &SearchOutcome,
+    version: SchemaVersion,
+) -> Result<String, serde_json::Error> {
+    match version {
+        SchemaVersion::V0 => serde_json::to_string_pretty(&outcome.results),
+        SchemaVersion::V1 => serde_json::to_string_pretty(&SearchOutput::from_outcome(query, outcome.clone())),
+    }
+}
+
+/// Render a search document in the requested schema version
+///
+/// Schema 0 has no place for limits, stats or metrics and gets the bare array of results.
+pub fn search_output_to_json(output: &SearchOutput, version: SchemaVersion) -> Result<String, serde_json::Error> {
+    match version {
+        SchemaVersion::V0 => serde_json::to_string_pretty(&output.results),
+        SchemaVersion::V1 => serde_json::to_string_pretty(output),
+    }
+}
+
+/// Writes a compact search document one result at a time (`--format json`)
+///
+/// Results are serialized as they arrive instead of building the whole document
+/// first. The object starts with `schema_version` and `query`, then the
+/// `results` array, and ends with the fields only known once the search is done
+/// (`total_results`, `limit_reached`, `stats`, ...): the same fields as
+/// [`SearchOutput`], which it parses into. Schema 0 writes the bare array.
+pub struct SearchJsonStream<W: Write> {
+    writer: W,
+    version: SchemaVersion,
+    count: usize,
+}
+
+impl<W: Write> SearchJsonStream<W> {
+    /// Write the document up to the opening bracket of `results`
+    pub fn begin(mut writer: W, query: &str, version: SchemaVersion) -> io::Result<Self> {
+        if version == SchemaVersion::V1 {
+            write!(writer, "{{\"schema_version\":")?;
+            serde_json::to_writer(&mut writer, SCHEMA_VERSION)?;
+            write!(writer, ",\"query\":")?;
+            serde_json::to_writer(&mut writer, query)?;
+            write!(writer, ",\"results\":")?;
+        }
+        writer.write_all(b"[")?;
+        Ok(Self { writer, version, count: 0 })
+    }
+
+    /// Append one result, on a line of its own
+    pub fn push(&mut self, result: &SearchResult) -> io::Result<()> {
+        self.writer.write_all(if self.count == 0 { b"\n" } else { b",\n" })?;
+        serde_json::to_writer(&mut self.writer, result)?;
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Results written so far
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Close `results` and write the fields of `trailer` that come after it
+    ///
+    /// `trailer.results` is ignored and `total_results` is the number of results
+    /// pushed. Returns the writer, flushed.
+    pub fn finish(mut self, trailer: SearchOutput) -> io::Result<W> {
+        self.writer.write_all(if self.count == 0 { b"]" } else { b"\n]" })?;
+        if self.version == SchemaVersion::V1 {
+            let trailer = SearchOutput {
+                total_results: self.count,
+                results: Vec::new(),
+                ..trailer
+            };
+            if let serde_json::Value::Object(fields) = serde_json::to_value(&trailer)? {
+                for (key, value) in fields.iter().filter(|(key, _)| !matches!(key.as_str(), "schema_version" | "query" | "results")) {
+                    self.writer.write_all(b",")?;
+                    serde_json::to_writer(&mut self.writer, key)?;
+                    self.writer.write_all(b":")?;
+                    serde_json::to_writer(&mut self.writer, value)?;
+                }
+            }
+            self.writer.write_all(b"}")?;
+        }
+        self.writer.write_all(b"\n")?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Render a struct-shaped document as pretty JSON in the requested schema version
+pub fn to_versioned_json<T: Serialize>(
+    data: &T,
+    version: SchemaVersion,
+) -> Result<String, serde_json::Error> {
+    match version {
+        SchemaVersion::V0 => serde_json::to_string_pretty(data),
+        SchemaVersion::V1 => serde_json::to_string_pretty(&Versioned::new(data)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEARCH_V0_FIXTURE: &str = include_str!("../tests/fixtures/schema/search_results_v0.json");
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Summary {
+        total_files: usize,
+        total_lines: usize,
+    }
+
+    #[test]
+    fn test_parse_schema_version() {
+        assert_eq!("0".parse::<SchemaVersion>(), Ok(SchemaVersion::V0));
+        assert_eq!("0.9.1".parse::<SchemaVersion>(), Ok(SchemaVersion::V0));
+        assert_eq!("1".parse::<SchemaVersion>(), Ok(SchemaVersion::V1));
+        assert_eq!(SCHEMA_VERSION.parse::<SchemaVersion>(), Ok(SchemaVersion::current()));
+        assert!("2.0.0".parse::<SchemaVersion>().is_err());
+    }
+
+    #[test]
+    fn test_search_v0_fixture_round_trip() {
+        let legacy: Vec<SearchResult> = serde_json::from_str(SEARCH_V0_FIXTURE).unwrap();
+        assert_eq!(legacy.len(), 2);
+
+        let output = SearchOutput::from_v0("fn", legacy.clone());
+        assert_eq!(output.schema_version, SCHEMA_VERSION);
+        assert_eq!(output.total_results, 2);
+
+        let json = serde_json::to_string(&output).unwrap();
+        let parsed: SearchOutput = serde_json::from_str(&json).unwrap();
+        let round_tripped = parsed.into_v0();
+
+        assert_eq!(
+            serde_json::to_value(&legacy).unwrap(),
+            serde_json::to_value(&round_tripped).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_search_results_to_json_v0_matches_fixture_shape() {
+        let legacy: Vec<SearchResult> = serde_json::from_str(SEARCH_V0_FIXTURE).unwrap();
+        let json = search_results_to_json("fn", &legacy, SchemaVersion::V0).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let fixture: serde_json::Value = serde_json::from_str(SEARCH_V0_FIXTURE).unwrap();
+        assert_eq!(value, fixture);
+    }
+
+    #[test]
+    fn test_search_outcome_json_carries_limit_flags() {
+        let legacy: Vec<SearchResult> = serde_json::from_str(SEARCH_V0_FIXTURE).unwrap();
+        let outcome = SearchOutcome {
+            results: legacy,
+            limit_reached: LimitReached { per_file: false, total: true },
+            ..Default::default()
+        };
+
+        let json = search_outcome_to_json("fn", &outcome, SchemaVersion::V1).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["limit_reached"], serde_json::json!({ "per_file": false, "total": true }));
+        assert_eq!(value["total_results"], 2);
+
+        // Documents from before 1.4 have no flags
+        let mut older = value.clone();
+        older.as_object_mut().unwrap().remove("limit_reached");
+        let parsed: SearchOutput = serde_json::from_value(older).unwrap();
+        assert_eq!(parsed.limit_reached, LimitReached::default());
+
+        let v0 = search_outcome_to_json("fn", &outcome, SchemaVersion::V0).unwrap();
+        assert!(!v0.contains("limit_reached"));
+    }
+
+    #[test]
+    fn test_search_output_embeds_stats_and_metrics_only_when_set() {
+        let legacy: Vec<SearchResult> = serde_json::from_str(SEARCH_V0_FIXTURE).unwrap();
+        let plain = SearchOutput::new("fn", legacy.clone());
+        let value: serde_json::Value = serde_json::from_str(&search_output_to_json(&plain, SchemaVersion::V1).unwrap()).unwrap();
+        assert!(value.get("stats").is_none());
+        assert!(value.get("metrics").is_none());
+
+        let output = SearchOutput {
+            stats: Some(SearchStats::from_results(&legacy)),
+            metrics: Some(SearchMetrics { files_processed: 4, ..Default::default() }),
+            ..SearchOutput::new("fn", legacy)
+        };
+        let value: serde_json::Value = serde_json::from_str(&search_output_to_json(&output, SchemaVersion::V1).unwrap()).unwrap();
+        assert_eq!(value["stats"]["total_matches"], output.stats.as_ref().unwrap().total_matches);
+        assert_eq!(value["metrics"]["files_processed"], 4);
+        assert!(value.get("grep_comparison").is_none());
+
+        let parsed: SearchOutput = serde_json::from_value(value).unwrap();
+        assert_eq!(parsed.metrics, output.metrics);
+
+        let v0: serde_json::Value = serde_json::from_str(&search_output_to_json(&output, SchemaVersion::V0).unwrap()).unwrap();
+        assert!(v0.is_array());
+    }
+
+    #[test]
+    fn test_streamed_json_parses_as_search_output() {
+        let legacy: Vec<SearchResult> = serde_json::from_str(SEARCH_V0_FIXTURE).unwrap();
+        let query = "fn \"main\"\\s";
+        let mut stream = SearchJsonStream::begin(Vec::new(), query, SchemaVersion::V1).unwrap();
+        for i in 0..100_000 {
+            let mut result = legacy[i % legacy.len()].clone();
+            result.line_number = i + 1;
+            stream.push(&result).unwrap();
+        }
+        let trailer = SearchOutput {
+            limit_reached: LimitReached { per_file: true, total: false },
+            stats: Some(SearchStats::from_results(&legacy)),
+            ..SearchOutput::new(query, Vec::new())
+        };
+        let bytes = stream.finish(trailer).unwrap();
+
+        let parsed: SearchOutput = serde_json::from_str(std::str::from_utf8(&bytes).unwrap()).unwrap();
+        assert_eq!(parsed.schema_version, SCHEMA_VERSION);
+        assert_eq!(parsed.query, query);
+        assert_eq!(parsed.total_results, 100_000);
+        assert_eq!(parsed.results.len(), 100_000);
+        assert_eq!(parsed.results[99_999].line_number, 100_000);
+        assert!(parsed.limit_reached.per_file);
+        assert!(parsed.stats.is_some());
+    }
+
+    #[test]
+    fn test_streamed_json_without_results() {
+        let bytes = SearchJsonStream::begin(Vec::new(), "x", SchemaVersion::V0).unwrap().finish(SearchOutput::new("x", Vec::new())).unwrap();
+        assert_eq!(String::from_utf8(bytes).unwrap(), "[]\n");
+
+        let bytes = SearchJsonStream::begin(Vec::new(), "x", SchemaVersion::V1).unwrap().finish(SearchOutput::new("x", Vec::new())).unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(value["total_results"], 0);
+        assert_eq!(value["results"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn test_versioned_round_trip() {
+        let summary = Summary { total_files: 3, total_lines: 120 };
+        let json = to_versioned_json(&summary, SchemaVersion::V1).unwrap();
+        assert!(json.contains("\"schema_version\""));
+
+        let parsed: Versioned<Summary> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.schema_version, SCHEMA_VERSION);
+        assert_eq!(parsed.into_v0(), summary);
+    }
+
+    #[test]
+    fn test_versioned_v0_has_no_version_field() {
+        let summary = Summary { total_files: 1, total_lines: 10 };
+        let json = to_versioned_json(&summary, SchemaVersion::V0).unwrap();
+        assert!(!json.contains("schema_version"));
+        let parsed: Summary = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, summary);
+    }
+}
@@ -2,36 +2,101 @@
 //!
 //! Main search implementation with parallel processing and caching.
 
-use crate::cache::get_search_cache;
-use crate::types::{SearchMetrics, SearchOptions, SearchResult};
-use super::fuzzy::search_in_file_parallel;
-use super::semantic::enhance_query_semantically;
-use super::utilities::compare_with_grep;
-use regex::Regex;
+use crate::cache::{fingerprint_files, get_search_cache};
+use crate::config::Config;
+use crate::encoding::{read_text, Encoding};
+use crate::gitignore::GitIgnore;
+use crate::index::trigrams;
+use crate::lines::split_lines;
+use crate::options::has_extension;
+use crate::pathglob::{check_search_path, PathGlob};
+use crate::types::{LimitReached, SearchMetrics, SearchOptions, SearchOutcome, SearchResult, StreamSummary};
+use super::fuzzy::{search_in_file_limited, search_reader_limited};
+use super::multiline::{search_in_file_multiline, search_text_multiline};
+use super::pattern::PatternMatcher;
+use super::processors::apply_post_processors;
+use super::query::{Query, QueryKind, QueryPlan};
+use super::semantic::SynonymTable;
+use super::utilities::{grep_comparison, print_grep_comparison, print_search_metrics};
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::ControlFlow;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, mpsc};
 use std::time::Instant;
 use walkdir::WalkDir;
 
+/// Maximum number of walked paths buffered ahead of the search workers
+const WALK_CHANNEL_CAPACITY: usize = 1024;
+
+/// Maximum number of per-file result batches buffered ahead of a streaming callback
+const RESULT_CHANNEL_CAPACITY: usize = 256;
+
 /// Main search function with support for fuzzy, regex, semantic, and cached searches
+///
+/// Directory walking and matching overlap, so files are searched in whatever order
+/// the workers pick them up. Results within a single file are always in line order,
+/// but the order of files may vary between runs. Use `options.rank` to sort by score
+/// or [`sort_results_by_path`] for a deterministic file order.
+///
+/// `options.max_results` caps matching lines (multiline spans with `options.multiline`) per file; `options.max_total` caps the
+/// whole result list (see [`search_code_outcome`]).
+///
+/// With `options.benchmark` or `options.vs_grep`, the metrics are printed to stderr.
 pub fn search_code(
     query: &str,
     path: &Path,
     options: &SearchOptions,
 ) -> Result<Vec<SearchResult>, Box<dyn std::error::Error>> {
+    let outcome = search_code_outcome(query, path, options)?;
+    if options.benchmark {
+        print_search_metrics(&outcome.metrics, options.cache);
+    }
+    if let Some(comparison) = &outcome.grep_comparison {
+        print_grep_comparison(comparison, &outcome.metrics);
+    }
+    Ok(outcome.results)
+}
+
+/// Like [`search_code`], also reporting which result limits truncated the output
+///
+/// `options.post_processors` run last, over the results that are returned.
+///
+/// `max_total` is applied after ranking, so with `options.rank` the best-scored
+/// results are kept. Without ranking, a `max_total` search is sorted by path
+/// first, so the kept results don't depend on which files finished first; the
+/// tree is then walked in path order, and files past those results are skipped.
+pub fn search_code_outcome(
+    query: &str,
+    path: &Path,
+    options: &SearchOptions,
+) -> Result<SearchOutcome, Box<dyn std::error::Error>> {
     let start_time = Instant::now();
-    let mut results = Vec::new();
+    // The cache holds single-line matches without their context lines
+    let use_cache = options.cache && options.context.is_empty() && !options.multiline;
 
-    let (cache_hits, cache_misses) = if options.cache {
+    // Stat-only pass over the files the search would read; a change to any of them misses
+    // (the cache's own directory excluded, or saving the cache would invalidate it)
+    let fingerprint = use_cache.then(|| {
+        fingerprint_files(
+            walk_search_files(path, options, false)
+                .filter(|file| !file.components().any(|c| c.as_os_str() == ".codesearch")),
+        )
+    });
+    let (cache_hits, cache_misses) = if let Some(fingerprint) = fingerprint {
         let search_cache = get_search_cache();
-        let extensions_slice = options.extensions.as_ref().map(|v| v.as_slice());
-        let cache_key = search_cache.get_cache_key(query, &path.to_string_lossy(), extensions_slice, options.fuzzy);
-        if let Some(cached_results) = search_cache.get(&cache_key) {
-            if options.benchmark {
-                use colored::*;
-                println!("{}", "Cache hit! Returning cached results instantly.".green().bold());
-            }
-            return Ok(cached_results);
+        let cache_key = search_cache.get_cache_key(query, &path.to_string_lossy(), options);
+        if let Some(cached_results) = search_cache.get_fresh(&cache_key, fingerprint) {
+            // Cached results are stored before the total cap; per-file truncation isn't recorded
+            let mut outcome = finish_results(cached_results, options, LimitReached::default());
+            outcome.metrics = SearchMetrics {
+                search_time_ms: start_time.elapsed().as_millis(),
+                parallel_workers: rayon::current_num_threads(),
+                cache_hits: 1,
+                ..SearchMetrics::default()
+            };
+            return Ok(outcome);
         } else {
             (0, 1)
         }
@@ -39,27 +104,462 @@ pub fn search_code(
         (0, 0)
     };
 
-    let enhanced_query = if options.semantic {
-        enhance_query_semantically(query)
+    // Unranked, `max_total` keeps a prefix in path order: walk in that order, so
+    // files past the point where the prefix is complete can be skipped
+    let budget = options.max_total.filter(|_| !options.rank).map(TotalBudget::new);
+    let files = walk_search_files(path, options, budget.is_some());
+    let mut results = Vec::new();
+    let summary = stream_search(files, query, options, budget.as_ref(), |result| {
+        results.push(result);
+        ControlFlow::Continue(())
+    })?;
+    let total_files = summary.files_processed;
+
+    if options.rank {
+        sort_results_by_score(&mut results);
+    }
+
+    let elapsed = start_time.elapsed();
+    let metrics = SearchMetrics {
+        files_processed: total_files,
+        total_lines_scanned: 0,
+        search_time_ms: elapsed.as_millis(),
+        parallel_workers: rayon::current_num_threads(),
+        cache_hits,
+        cache_misses,
+        files_ruled_out_by_index: summary.files_ruled_out,
+    };
+
+    let grep_comparison = if options.vs_grep {
+        let extensions_slice = options.extensions.as_ref().map(|v| v.as_slice());
+        grep_comparison(query, &path.to_string_lossy(), extensions_slice, &metrics)
     } else {
-        query.to_string()
+        None
     };
 
-    let regex = if options.fuzzy {
-        if options.ignore_case {
-            Regex::new(&format!("(?i).*{}.*", regex::escape(&enhanced_query)))?
-        } else {
-            Regex::new(&format!(".*{}.*", regex::escape(&enhanced_query)))?
+    if let Some(fingerprint) = fingerprint {
+        let search_cache = get_search_cache();
+        let cache_key = search_cache.get_cache_key(query, &path.to_string_lossy(), options);
+        search_cache.set_fresh(cache_key, results.clone(), fingerprint);
+        // The cache only speeds up later runs; failing to persist it doesn't fail the search
+        let _ = search_cache.save();
+    }
+
+    let limit_reached = LimitReached {
+        per_file: summary.per_file_limit,
+        total: false,
+    };
+    Ok(SearchOutcome {
+        metrics,
+        grep_comparison,
+        files_skipped: summary.files_skipped,
+        ..finish_results(results, options, limit_reached)
+    })
+}
+
+/// Search like [`search_code`], handing each result to `on_result` as soon as its file is searched
+///
+/// Files are searched on the rayon pool while the tree is still being walked;
+/// each file's results are sent over a bounded channel and delivered in line
+/// order, but files arrive in whatever order the workers finish them. The
+/// callback runs on the calling thread, so it may hold non-`Sync` state or
+/// forward into another channel. Returning [`ControlFlow::Break`] stops the walk
+/// and the workers; results of files already in flight are discarded.
+///
+/// Ranking and `max_total` need the whole result set, so `options.rank` and
+/// `options.max_total` are not applied here: scores are still computed, and the
+/// caller can buffer and sort, or stop after enough results. [`search_code`]
+/// does exactly that on top of this function. The cache is not consulted.
+/// `options.post_processors` run on each result before `on_result` gets it.
+pub fn search_code_streaming<F>(
+    query: &str,
+    path: &Path,
+    options: &SearchOptions,
+    mut on_result: F,
+) -> Result<StreamSummary, Box<dyn std::error::Error>>
+where
+    F: FnMut(SearchResult) -> ControlFlow<()>,
+{
+    stream_search(walk_search_files(path, options, false), query, options, None, |mut result| {
+        for processor in &options.post_processors {
+            processor.process(&mut result);
         }
-    } else if options.ignore_case {
-        Regex::new(&format!("(?i){}", &enhanced_query))?
+        on_result(result)
+    })
+}
+
+/// Search exactly `files`, skipping the directory walk
+///
+/// Used for `--files-from`. With `filter`, `options.extensions` and
+/// `options.multiline_max_bytes` still apply to the listed files; without it,
+/// every listed file is searched in full. Exclusions and `.gitignore` are not
+/// consulted, and neither the cache nor `vs_grep` are used.
+pub fn search_files_outcome(
+    query: &str,
+    files: &[PathBuf],
+    options: &SearchOptions,
+    filter: bool,
+) -> Result<SearchOutcome, Box<dyn std::error::Error>> {
+    let start_time = Instant::now();
+    let unfiltered;
+    let options = if filter {
+        options
     } else {
-        Regex::new(&enhanced_query)?
+        unfiltered = SearchOptions {
+            multiline_max_bytes: u64::MAX,
+            ..options.clone()
+        };
+        &unfiltered
     };
+    let files = files.iter().filter(|file| match options.extensions.as_deref() {
+        Some(exts) if filter => has_extension(file, exts),
+        _ => true,
+    });
 
-    let walker = WalkDir::new(path)
+    let mut results = Vec::new();
+    let summary = stream_search(files.cloned(), query, options, None, |result| {
+        results.push(result);
+        ControlFlow::Continue(())
+    })?;
+
+    if options.rank {
+        sort_results_by_score(&mut results);
+    }
+
+    let metrics = SearchMetrics {
+        files_processed: summary.files_processed,
+        search_time_ms: start_time.elapsed().as_millis(),
+        parallel_workers: rayon::current_num_threads(),
+        files_ruled_out_by_index: summary.files_ruled_out,
+        ..SearchMetrics::default()
+    };
+    let limit_reached = LimitReached {
+        per_file: summary.per_file_limit,
+        total: false,
+    };
+    Ok(SearchOutcome {
+        metrics,
+        ..finish_results(results, options, limit_reached)
+    })
+}
+
+/// Search `(path, contents)` pairs held in memory, e.g. the members of an index archive
+///
+/// Matching is the same as for files on disk; `path` is what the results
+/// report. `options.extensions` and `options.multiline_max_bytes` filter the
+/// members, while exclusions, `.gitignore`, the index, the cache and `vs_grep`
+/// don't apply.
+pub fn search_contents_outcome(
+    query: &str,
+    members: &[(String, String)],
+    options: &SearchOptions,
+) -> Result<SearchOutcome, Box<dyn std::error::Error>> {
+    use rayon::prelude::*;
+
+    let start_time = Instant::now();
+    let typed_query = Query::from_options(query, options)?;
+    let fuzzy_threshold = typed_query.fuzzy_threshold();
+    let regex = Arc::new(plan_query(&typed_query).compile(options.engine)?);
+
+    let searched: Vec<(Vec<SearchResult>, bool)> = members
+        .par_iter()
+        .filter(|(path, _)| options.extensions.as_deref().is_none_or(|exts| has_extension(Path::new(path), exts)))
+        .filter(|(_, text)| !options.multiline || text.len() as u64 <= options.multiline_max_bytes)
+        .map(|(path, text)| {
+            let path = Path::new(path);
+            if options.multiline {
+                search_text_multiline(text, path, &regex, query, options.max_results, options.ranking(), options.context)
+            } else {
+                search_reader_limited(text.as_bytes(), path, &regex, fuzzy_threshold.is_some(), fuzzy_threshold.unwrap_or_default(), query, options.max_results, options.ranking(), options.context)
+                    .unwrap_or_default()
+            }
+        })
+        .collect();
+
+    let files_processed = searched.len();
+    let per_file_limit = searched.iter().any(|(_, truncated)| *truncated);
+    let mut results: Vec<SearchResult> = searched.into_iter().flat_map(|(results, _)| results).collect();
+    if options.rank {
+        sort_results_by_score(&mut results);
+    }
+
+    let metrics = SearchMetrics {
+        files_processed,
+        search_time_ms: start_time.elapsed().as_millis(),
+        parallel_workers: rayon::current_num_threads(),
+        ..SearchMetrics::default()
+    };
+    let limit_reached = LimitReached {
+        per_file: per_file_limit,
+        total: false,
+    };
+    Ok(SearchOutcome {
+        metrics,
+        ..finish_results(results, options, limit_reached)
+    })
+}
+
+/// Search the files produced by `files` on the rayon pool, streaming results to `on_result`
+///
+/// With a `budget`, `files` must come in path order; files are skipped once the
+/// files before them hold more than `max_total` results.
+fn stream_search<I, F>(
+    files: I,
+    query: &str,
+    options: &SearchOptions,
+    budget: Option<&TotalBudget>,
+    mut on_result: F,
+) -> Result<StreamSummary, Box<dyn std::error::Error>>
+where
+    I: Iterator<Item = PathBuf> + Send,
+    F: FnMut(SearchResult) -> ControlFlow<()>,
+{
+    let typed_query = Query::from_options(query, options)?;
+    let fuzzy_threshold = typed_query.fuzzy_threshold();
+    let regex = Arc::new(plan_query(&typed_query).compile(options.engine)?);
+    // Trigrams every matching file contains; shorter text can't rule anything out
+    let prefilter = options
+        .index
+        .as_deref()
+        .zip(typed_query.required_literal().filter(|text| text.len() >= 3))
+        .map(|(code_index, text)| (code_index, trigrams(text.as_bytes())));
+    let files_seen = AtomicUsize::new(0);
+    let files_ruled_out = AtomicUsize::new(0);
+    let per_file_limit = AtomicBool::new(false);
+    let stop = AtomicBool::new(false);
+    let files_skipped = AtomicBool::new(false);
+    let (sender, receiver) = mpsc::sync_channel::<(usize, PathBuf)>(WALK_CHANNEL_CAPACITY);
+    let (result_sender, result_receiver) = mpsc::sync_channel::<Vec<SearchResult>>(RESULT_CHANNEL_CAPACITY);
+
+    // Walk on a dedicated thread so matching starts with the first file found;
+    // the bounded channels keep memory flat regardless of tree size.
+    use rayon::prelude::*;
+    let stopped = std::thread::scope(|scope| {
+        let (stop, files_skipped) = (&stop, &files_skipped);
+        scope.spawn(move || {
+            for (index, file_path) in files.enumerate() {
+                if budget.is_some_and(|b| b.skips(index)) {
+                    files_skipped.store(true, Ordering::Relaxed);
+                    break;
+                }
+                if stop.load(Ordering::Relaxed) || sender.send((index, file_path)).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let (files_seen, files_ruled_out, per_file_limit, regex, prefilter) =
+            (&files_seen, &files_ruled_out, &per_file_limit, &regex, &prefilter);
+        scope.spawn(move || {
+            receiver.into_iter().par_bridge().for_each_with(result_sender, |results, (index, file_path)| {
+                // Keep draining the walk after a stop, so the walker isn't left blocked
+                if stop.load(Ordering::Relaxed) {
+                    return;
+                }
+                if budget.is_some_and(|b| b.skips(index)) {
+                    files_skipped.store(true, Ordering::Relaxed);
+                    return;
+                }
+                if prefilter.as_ref().is_some_and(|(code_index, required)| !code_index.may_contain(&file_path, required)) {
+                    files_ruled_out.fetch_add(1, Ordering::Relaxed);
+                    if let Some(budget) = budget {
+                        budget.record(index, 0);
+                    }
+                    return;
+                }
+                files_seen.fetch_add(1, Ordering::Relaxed);
+                let searched = if options.multiline {
+                    search_in_file_multiline(&file_path, regex, query, options.max_results, options.ranking(), options.context, options.multiline_max_bytes, options.encoding)
+                } else {
+                    search_in_file_limited(&file_path, regex, fuzzy_threshold.is_some(), fuzzy_threshold.unwrap_or_default(), query, options.max_results, options.ranking(), options.context, options.encoding)
+                };
+                let (file_results, truncated) = searched.unwrap_or_default();
+                if let Some(budget) = budget {
+                    budget.record(index, file_results.len());
+                }
+                if truncated {
+                    per_file_limit.store(true, Ordering::Relaxed);
+                }
+                if !file_results.is_empty() && results.send(file_results).is_err() {
+                    stop.store(true, Ordering::Relaxed);
+                }
+            });
+        });
+
+        let mut stopped = false;
+        for result in result_receiver.iter().flatten() {
+            if on_result(result).is_break() {
+                stopped = true;
+                stop.store(true, Ordering::Relaxed);
+                break;
+            }
+        }
+        // Dropping the receiver fails pending sends, unblocking the workers
+        drop(result_receiver);
+        stopped
+    });
+
+    Ok(StreamSummary {
+        files_processed: files_seen.into_inner(),
+        per_file_limit: per_file_limit.into_inner(),
+        stopped,
+        files_skipped: files_skipped.into_inner(),
+        files_ruled_out: files_ruled_out.into_inner(),
+    })
+}
+
+/// Results found per walked file, to tell when the first `max_total` in path order are known
+struct TotalBudget {
+    max_total: usize,
+    /// Walk index from which files can be skipped
+    cutoff: AtomicUsize,
+    progress: Mutex<BudgetProgress>,
+}
+
+#[derive(Default)]
+struct BudgetProgress {
+    /// Result counts of finished files that aren't yet part of the prefix
+    pending: BTreeMap<usize, usize>,
+    /// Files `0..next` are all finished
+    next: usize,
+    /// Results in files `0..next`
+    found: usize,
+}
+
+impl TotalBudget {
+    fn new(max_total: usize) -> Self {
+        Self {
+            max_total,
+            cutoff: AtomicUsize::new(usize::MAX),
+            progress: Mutex::new(BudgetProgress::default()),
+        }
+    }
+
+    fn skips(&self, index: usize) -> bool {
+        index >= self.cutoff.load(Ordering::Relaxed)
+    }
+
+    /// Record that the file at walk position `index` had `count` results
+    fn record(&self, index: usize, count: usize) {
+        let mut progress = self.progress.lock().unwrap_or_else(|e| e.into_inner());
+        progress.pending.insert(index, count);
+        loop {
+            let next = progress.next;
+            let Some(count) = progress.pending.remove(&next) else { break };
+            progress.next += 1;
+            progress.found += count;
+        }
+        // Exceeding the cap (not just reaching it) is what makes `limit_reached.total` certain
+        if progress.found > self.max_total {
+            self.cutoff.store(progress.next, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Apply `options.max_total` to results that are already ranked (if `options.rank`)
+///
+/// Repeated `(file, line)` results are dropped first (see [`dedup_results`]),
+/// ranked or not, so the cap counts distinct lines.
+pub fn apply_total_limit(
+    mut results: Vec<SearchResult>,
+    options: &SearchOptions,
+    mut limit_reached: LimitReached,
+) -> SearchOutcome {
+    dedup_results(&mut results);
+    let mut truncated = 0;
+    if let Some(max_total) = options.max_total {
+        if !options.rank {
+            sort_results_by_path(&mut results);
+        }
+        if results.len() > max_total {
+            truncated = results.len() - max_total;
+            results.truncate(max_total);
+            limit_reached.total = true;
+        }
+    }
+    SearchOutcome {
+        results,
+        limit_reached,
+        truncated,
+        ..SearchOutcome::default()
+    }
+}
+
+/// [`apply_total_limit`], then `options.post_processors` over the results kept
+fn finish_results(results: Vec<SearchResult>, options: &SearchOptions, limit_reached: LimitReached) -> SearchOutcome {
+    let mut outcome = apply_total_limit(results, options, limit_reached);
+    apply_post_processors(&mut outcome.results, options);
+    outcome
+}
+
+/// Compile the pattern `search_code` matches lines against
+///
+/// Maps `options` onto a [`Query`] (rejecting conflicting flags) and compiles its plan.
+pub fn build_matcher(query: &str, options: &SearchOptions) -> Result<PatternMatcher, Box<dyn std::error::Error>> {
+    let query = Query::from_options(query, options)?;
+    Ok(plan_query(&query).compile(options.engine)?)
+}
+
+/// Plan `query`, expanding semantic queries with the configured synonym groups
+pub fn plan_query(query: &Query) -> QueryPlan {
+    let synonyms = match query.kind {
+        QueryKind::Semantic => SynonymTable::from_config(&Config::load().semantic),
+        _ => SynonymTable::default(),
+    };
+    query.plan(&synonyms)
+}
+
+/// Search `path`, which may be a glob pattern the shell didn't expand
+///
+/// A glob is walked from its leading literal directories with the usual
+/// excludes and `.gitignore` rules, searching only the files it matches.
+/// Fails instead of returning no results when the path doesn't exist, or when
+/// a glob pattern matches no files.
+pub fn search_path_or_glob(
+    query: &str,
+    path: &Path,
+    options: &SearchOptions,
+) -> Result<Vec<SearchResult>, Box<dyn std::error::Error>> {
+    let outcome = search_path_or_glob_outcome(query, path, options)?;
+    if options.benchmark {
+        print_search_metrics(&outcome.metrics, options.cache);
+    }
+    if let Some(comparison) = &outcome.grep_comparison {
+        print_grep_comparison(comparison, &outcome.metrics);
+    }
+    Ok(outcome.results)
+}
+
+/// Like [`search_path_or_glob`], also reporting which result limits truncated the output
+pub fn search_path_or_glob_outcome(
+    query: &str,
+    path: &Path,
+    options: &SearchOptions,
+) -> Result<SearchOutcome, Box<dyn std::error::Error>> {
+    check_search_path(path)?;
+    search_code_outcome(query, path, options)
+}
+
+/// Lazily walk `path`, yielding files that pass the exclude and extension filters
+///
+/// A glob pattern that names no existing path is walked from its leading
+/// literal directories, yielding only the files it matches. With `sorted`,
+/// files come in the order [`sort_results_by_path`] puts them.
+fn walk_search_files<'a>(
+    path: &'a Path,
+    options: &'a SearchOptions,
+    sorted: bool,
+) -> impl Iterator<Item = PathBuf> + 'a {
+    let glob = PathGlob::from_path(path).and_then(Result::ok);
+    let root = glob.as_ref().map_or(path, |glob| glob.root.as_path());
+    let mut gitignore = options.gitignore.then(|| GitIgnore::new(root));
+    let mut walker = WalkDir::new(root);
+    if sorted {
+        walker = walker.sort_by_file_name();
+    }
+    walker
         .into_iter()
-        .filter_entry(|e| {
+        .filter_entry(move |e| {
             if let Some(name) = e.file_name().to_str() {
                 if let Some(ref exclude_dirs) = options.exclude {
                     for exclude_dir in exclude_dirs {
@@ -69,92 +569,102 @@ pub fn search_code(
                     }
                 }
             }
-            true
+            !gitignore.as_mut().is_some_and(|g| g.is_ignored_entry(e))
         })
         .filter_map(|e| e.ok())
-        .filter(|e| e.file_type().is_file());
-
-    let files: Vec<PathBuf> = walker
+        .filter(|e| e.file_type().is_file())
+        .filter(move |e| glob.as_ref().is_none_or(|glob| glob.matches(e.path())))
         .filter(|entry| {
             let file_path = entry.path();
-            if let Some(ref exts) = options.extensions {
-                if let Some(ext) = file_path.extension().and_then(|s| s.to_str()) {
-                    exts.iter().any(|e| e == ext)
-                } else {
-                    false
-                }
-            } else {
-                true
+            match options.extensions.as_deref() {
+                Some(exts) => has_extension(file_path, exts),
+                None => true,
             }
         })
-        .map(|e| e.path().to_path_buf())
-        .collect();
-
-    let total_files = files.len();
-    let regex = Arc::new(regex);
-
-    use rayon::prelude::*;
-    let file_results: Vec<SearchResult> = files
-        .par_iter()
-        .filter_map(|file_path| {
-            search_in_file_parallel(file_path, &regex, options.fuzzy, options.fuzzy_threshold, query, options.max_results, options.rank).ok()
-        })
-        .flatten()
-        .collect();
-
-    results.extend(file_results);
+        .map(|e| e.into_path())
+}
 
-    if options.rank {
-        results.sort_by(|a, b| {
-            b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal)
-        });
-    }
+/// Sort results by file path, keeping line order within each file
+///
+/// Paths compare component by component, so a directory's files come right
+/// after the directory itself, as in a walk sorted by file name.
+pub fn sort_results_by_path(results: &mut [SearchResult]) {
+    results.sort_by(|a, b| Path::new(&a.file).cmp(Path::new(&b.file)).then(a.line_number.cmp(&b.line_number)));
+}
 
-    let elapsed = start_time.elapsed();
-    let total_matches: usize = results.iter().map(|r| r.matches.len()).sum();
-    let metrics = SearchMetrics {
-        files_processed: total_files,
-        total_lines_scanned: 0,
-        search_time_ms: elapsed.as_millis(),
-        parallel_workers: rayon::current_num_threads(),
-        cache_hits,
-        cache_misses,
-    };
+/// Sort results by score, best first; equal scores go by file path, then line
+///
+/// The order is total, so a ranked search lists its results the same way on
+/// every run regardless of which worker finished first.
+pub fn sort_results_by_score(results: &mut [SearchResult]) {
+    results.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| Path::new(&a.file).cmp(Path::new(&b.file)))
+            .then(a.line_number.cmp(&b.line_number))
+    });
+}
 
-    if options.benchmark {
-        use colored::*;
-        println!("\n{}", "Performance Metrics:".cyan().bold());
-        println!("  Files searched: {}", metrics.files_processed);
-        println!("  Total matches: {total_matches}");
-        println!("  Search time: {}ms", metrics.search_time_ms);
-        println!("  Parallel workers: {}", metrics.parallel_workers);
-        if options.cache {
-            println!("  Cache hits: {}", metrics.cache_hits);
-            println!("  Cache misses: {}", metrics.cache_misses);
+/// Drop results repeating an earlier result's `(file, line)`
+///
+/// The surviving result stays at the first one's position but is whichever of
+/// the two scored higher (the earlier on a tie), so ranked results keep their order.
+pub fn dedup_results(results: &mut Vec<SearchResult>) {
+    let mut seen: HashMap<(String, usize), usize> = HashMap::new();
+    let mut kept: Vec<SearchResult> = Vec::with_capacity(results.len());
+    for result in results.drain(..) {
+        match seen.entry((result.file.clone(), result.line_number)) {
+            Entry::Occupied(entry) => {
+                let earlier = &mut kept[*entry.get()];
+                if result.score > earlier.score {
+                    *earlier = result;
+                }
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(kept.len());
+                kept.push(result);
+            }
         }
     }
+    *results = kept;
+}
 
-    if options.vs_grep {
-        let extensions_slice = options.extensions.as_ref().map(|v| v.as_slice());
-        compare_with_grep(query, &path.to_string_lossy(), extensions_slice, &metrics);
+/// Group ranked results by file: files by their best score, then each file's results by line
+///
+/// Files with equal best scores are ordered by path.
+pub fn group_results_by_file(mut results: Vec<SearchResult>) -> Vec<SearchResult> {
+    let mut best: HashMap<String, f64> = HashMap::new();
+    for result in &results {
+        let score = best.entry(result.file.clone()).or_insert(result.score);
+        *score = score.max(result.score);
     }
-
-    if options.cache && !results.is_empty() {
-        let search_cache = get_search_cache();
-        let extensions_slice = options.extensions.as_ref().map(|v| v.as_slice());
-        let cache_key = search_cache.get_cache_key(query, &path.to_string_lossy(), extensions_slice, options.fuzzy);
-        search_cache.set(cache_key, results.clone());
-    }
-
-    Ok(results)
+    results.sort_by(|a, b| {
+        best[&b.file]
+            .partial_cmp(&best[&a.file])
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| Path::new(&a.file).cmp(Path::new(&b.file)))
+            .then(a.line_number.cmp(&b.line_number))
+    });
+    results
 }
 
-/// List all searchable files in a directory
+/// List all searchable files in a directory, skipping paths ignored by `.gitignore`
 pub fn list_files(
     path: &Path,
     extensions: Option<&[String]>,
     exclude: Option<&[String]>,
 ) -> Result<Vec<crate::types::FileInfo>, Box<dyn std::error::Error>> {
+    list_files_with(path, extensions, exclude, true)
+}
+
+/// Like [`list_files`], with `.gitignore` handling chosen by `gitignore`
+pub fn list_files_with(
+    path: &Path,
+    extensions: Option<&[String]>,
+    exclude: Option<&[String]>,
+    gitignore: bool,
+) -> Result<Vec<crate::types::FileInfo>, Box<dyn std::error::Error>> {
+    let mut gitignore = gitignore.then(|| GitIgnore::new(path));
     let walker = WalkDir::new(path)
         .into_iter()
         .filter_entry(|e| {
@@ -167,7 +677,7 @@ pub fn list_files(
                     }
                 }
             }
-            true
+            !gitignore.as_mut().is_some_and(|g| g.is_ignored_entry(e))
         })
         .filter_map(|e| e.ok())
         .filter(|e| e.file_type().is_file());
@@ -175,22 +685,17 @@ pub fn list_files(
     let files: Vec<crate::types::FileInfo> = walker
         .filter(|entry| {
             let file_path = entry.path();
-            if let Some(exts) = extensions {
-                if let Some(ext) = file_path.extension().and_then(|s| s.to_str()) {
-                    exts.iter().any(|e| e == ext)
-                } else {
-                    false
-                }
-            } else {
-                true
+            match extensions {
+                Some(exts) => has_extension(file_path, exts),
+                None => true,
             }
         })
         .map(|entry| {
             let path = entry.path();
             let metadata = std::fs::metadata(path).ok();
             let size = metadata.as_ref().map(|m| m.len()).unwrap_or(0);
-            let lines = if let Ok(content) = std::fs::read_to_string(path) {
-                content.lines().count()
+            let lines = if let Ok(content) = read_text(path, Encoding::Auto) {
+                split_lines(&content).count()
             } else {
                 0
             };
@@ -205,3 +710,30 @@ pub fn list_files(
 
     Ok(files)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_cuts_off_after_the_prefix_exceeds_max_total() {
+        let budget = TotalBudget::new(4);
+        // File 1 finishes first; nothing is known until file 0 does
+        budget.record(1, 3);
+        budget.record(2, 0);
+        assert!(!budget.skips(5));
+
+        budget.record(0, 2);
+        assert!(!budget.skips(2));
+        assert!(budget.skips(3));
+    }
+
+    #[test]
+    fn test_budget_reaching_max_total_exactly_keeps_searching() {
+        let budget = TotalBudget::new(4);
+        budget.record(0, 4);
+        assert!(!budget.skips(1));
+        budget.record(1, 1);
+        assert!(budget.skips(2));
+    }
+}
@@ -0,0 +1,79 @@
+//! File Lists
+//!
+//! Reads explicit lists of files to search, such as the output of
+//! `git diff --name-only`, for `search --files-from`.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+/// Files named by a list, split into those found and those that aren't files
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileList {
+    pub files: Vec<PathBuf>,
+    /// Listed paths that don't exist or aren't regular files
+    pub missing: Vec<PathBuf>,
+}
+
+/// Read a file list, one path per line, resolving relative paths against `base`
+///
+/// Blank lines and lines starting with `#` are ignored, and surrounding
+/// whitespace is trimmed. Listed paths are kept in order, duplicates included.
+pub fn read_file_list(reader: impl BufRead, base: &Path) -> io::Result<FileList> {
+    let mut list = FileList::default();
+    for line in reader.lines() {
+        let line = line?;
+        let entry = line.trim();
+        if entry.is_empty() || entry.starts_with('#') {
+            continue;
+        }
+        let path = base.join(entry);
+        if path.is_file() {
+            list.files.push(path);
+        } else {
+            list.missing.push(path);
+        }
+    }
+    Ok(list)
+}
+
+/// Read the file list at `source`, or from stdin when it is `-`
+pub fn load_file_list(source: &str, base: &Path) -> Result<FileList, Box<dyn std::error::Error>> {
+    let list = if source == "-" {
+        read_file_list(io::stdin().lock(), base)
+    } else {
+        let file = File::open(source).map_err(|e| format!("Cannot read file list {source}: {e}"))?;
+        read_file_list(BufReader::new(file), base)
+    };
+    Ok(list?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_comments_blank_lines_and_missing_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.rs"), "").unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/b.py"), "").unwrap();
+
+        let input = "# changed files\na.rs\n\n  src/b.py  \r\ngone.rs\nsrc\n";
+        let list = read_file_list(input.as_bytes(), dir.path()).unwrap();
+        assert_eq!(list.files, vec![dir.path().join("a.rs"), dir.path().join("src/b.py")]);
+        // Directories aren't searched either
+        assert_eq!(list.missing, vec![dir.path().join("gone.rs"), dir.path().join("src")]);
+    }
+
+    #[test]
+    fn test_absolute_paths_ignore_base() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("a.rs");
+        std::fs::write(&file, "").unwrap();
+
+        let input = format!("{}\n", file.display());
+        let list = read_file_list(input.as_bytes(), Path::new("/nonexistent")).unwrap();
+        assert_eq!(list.files, vec![file]);
+    }
+}
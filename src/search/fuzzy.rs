@@ -2,102 +2,290 @@
 //!
 //! Provides fuzzy matching and relevance scoring for search results.
 
-use crate::types::{Match, SearchResult};
+use crate::language::language_extension;
+use crate::types::{ContextLines, Match, ScoreWeights, SearchResult, ELLIPSIS, LONG_LINE_BYTES, LONG_LINE_WINDOW};
+use super::pattern::PatternMatcher;
+use super::pure::fuzzy_similarity;
 use fuzzy_matcher::skim::SkimMatcherV2;
 use fuzzy_matcher::FuzzyMatcher;
-use regex::Regex;
+use crate::encoding::{decode_utf16, utf16_order, Encoding, UTF8_BOM};
+use crate::lines::read_lines;
+use crate::text::{prefix_end, suffix_start};
+use std::collections::VecDeque;
 use std::fs;
-use std::io::{BufRead, BufReader};
-use std::path::Path;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Component, Path};
 use std::sync::Arc;
 
 /// Search within a single file using parallel processing
+///
+/// With `rank`, results are scored with those weights (see [`calculate_relevance_score`]).
+#[allow(clippy::too_many_arguments)]
 pub fn search_in_file_parallel(
     file_path: &Path,
-    regex: &Arc<Regex>,
+    regex: &Arc<PatternMatcher>,
     fuzzy: bool,
     fuzzy_threshold: f64,
     query: &str,
     max_results: usize,
-    rank: bool,
+    rank: Option<&ScoreWeights>,
+    context: ContextLines,
+    encoding: Encoding,
 ) -> Result<Vec<SearchResult>, Box<dyn std::error::Error>> {
-    let file = fs::File::open(file_path)?;
-    let reader = BufReader::new(file);
-    let mut results = Vec::new();
+    search_in_file_limited(file_path, regex, fuzzy, fuzzy_threshold, query, max_results, rank, context, encoding)
+        .map(|(results, _)| results)
+}
+
+/// Like [`search_in_file_parallel`], also reporting whether `max_results` cut off further matches
+///
+/// The file is read until one match past the limit is found, so the flag is exact.
+/// A matching line is one result, with every occurrence on it in `matches`.
+/// Context lines are attached to the match they surround and don't count
+/// against `max_results`; where two matches are close, each line is reported once.
+///
+/// Lines over [`LONG_LINE_BYTES`] (minified code) are reported as a window around
+/// their first match and flagged `line_truncated`; long context lines are cut short.
+///
+/// UTF-8 files are streamed; a file `encoding` says is UTF-16 is transcoded
+/// in memory first, so its match offsets are into the transcoded lines.
+#[allow(clippy::too_many_arguments)]
+pub fn search_in_file_limited(
+    file_path: &Path,
+    regex: &Arc<PatternMatcher>,
+    fuzzy: bool,
+    fuzzy_threshold: f64,
+    query: &str,
+    max_results: usize,
+    rank: Option<&ScoreWeights>,
+    context: ContextLines,
+    encoding: Encoding,
+) -> Result<(Vec<SearchResult>, bool), Box<dyn std::error::Error>> {
+    let mut reader = BufReader::new(fs::File::open(file_path)?);
+    let prefix = reader.fill_buf()?;
+    if let Some(order) = utf16_order(prefix, encoding) {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        let text = decode_utf16(&bytes, order);
+        return search_reader_limited(text.as_bytes(), file_path, regex, fuzzy, fuzzy_threshold, query, max_results, rank, context);
+    }
+    if prefix.starts_with(UTF8_BOM) {
+        reader.consume(UTF8_BOM.len());
+    }
+    search_reader_limited(reader, file_path, regex, fuzzy, fuzzy_threshold, query, max_results, rank, context)
+}
+
+/// Like [`search_in_file_limited`], reading the lines from `reader`
+///
+/// `file_path` is only reported in the results and used for ranking, so the
+/// contents can come from memory, e.g. an index archive.
+#[allow(clippy::too_many_arguments)]
+pub fn search_reader_limited<R: BufRead>(
+    reader: R,
+    file_path: &Path,
+    regex: &Arc<PatternMatcher>,
+    fuzzy: bool,
+    fuzzy_threshold: f64,
+    query: &str,
+    max_results: usize,
+    rank: Option<&ScoreWeights>,
+    context: ContextLines,
+) -> Result<(Vec<SearchResult>, bool), Box<dyn std::error::Error>> {
+    let mut results: Vec<SearchResult> = Vec::new();
+    let mut truncated = false;
     let mut line_count = 0;
     let matcher = SkimMatcherV2::default();
+    let best_score = matcher.fuzzy_match(query, query).unwrap_or(0);
+    let query_chars = query.chars().count();
 
-    for line in reader.lines() {
+    // Lines since the last reported one, kept for the next match's leading context;
+    // a line goes either here or into the previous match's trailing context, never both
+    let mut before: VecDeque<String> = VecDeque::with_capacity(context.before);
+    let mut after_left = 0;
+
+    for line in read_lines(reader) {
         line_count += 1;
         let line = line?;
 
-        if results.len() >= max_results {
-            break;
-        }
+        let hit = if fuzzy {
+            let similar = |(score, indices): &(i64, Vec<usize>)| {
+                let span = match (indices.first(), indices.last()) {
+                    (Some(first), Some(last)) => last - first + 1,
+                    _ => 0,
+                };
+                fuzzy_similarity(*score, best_score, query_chars, span) >= fuzzy_threshold
+            };
+            matcher.fuzzy_indices(&line, query).filter(similar).map(|(score, indices)| {
+                let mut matches = Vec::new();
 
-        if fuzzy {
-            if let Some((score, indices)) = matcher.fuzzy_indices(&line, query) {
-                if score as f64 >= fuzzy_threshold {
-                    let mut matches = Vec::new();
-
-                    for &idx in &indices {
-                        if matches.is_empty() || idx >= matches.last().map(|m: &Match| m.end).unwrap_or(0) {
-                            matches.push(Match {
-                                start: idx,
-                                end: idx + 1,
-                                text: line.chars().nth(idx).map(|c| c.to_string()).unwrap_or_default(),
-                            });
-                        }
+                // The matcher reports char indices; matches carry byte offsets
+                let chars: Vec<(usize, char)> = line.char_indices().collect();
+                for &idx in &indices {
+                    let Some(&(start, c)) = chars.get(idx) else { continue };
+                    if matches.is_empty() || start >= matches.last().map(|m: &Match| m.end).unwrap_or(0) {
+                        matches.push(Match {
+                            start,
+                            end: start + c.len_utf8(),
+                            text: c.to_string(),
+                            pattern_index: 0,
+                        });
                     }
-
-                    let (score_val, relevance) = if rank {
-                        let s = calculate_relevance_score(&line, query, line_count, file_path, true, Some(score));
-                        let r = if s >= 80.0 { "Very High" } else if s >= 60.0 { "High" } else if s >= 40.0 { "Medium" } else { "Low" };
-                        (s, r.to_string())
-                    } else {
-                        (score as f64, "Medium".to_string())
-                    };
-
-                    results.push(SearchResult {
-                        file: file_path.to_string_lossy().to_string(),
-                        line_number: line_count,
-                        content: line.clone(),
-                        matches,
-                        score: score_val,
-                        relevance,
-                    });
                 }
+
+                let (score_val, relevance) = if let Some(weights) = rank {
+                    let s = calculate_relevance_score(&line, query, line_count, file_path, true, Some(score), weights);
+                    let r = if s >= 80.0 { "Very High" } else if s >= 60.0 { "High" } else if s >= 40.0 { "Medium" } else { "Low" };
+                    (s, r.to_string())
+                } else {
+                    (score as f64, "Medium".to_string())
+                };
+                (matches, score_val, relevance)
+            })
+        } else if let Some(first) = regex.find(&line) {
+            // Every occurrence is recorded; an empty match (e.g. `^`) only when there is nothing else
+            let mut spans: Vec<(usize, usize)> = regex.find_all(&line).into_iter().filter(|(s, e)| e > s).collect();
+            if spans.is_empty() {
+                spans.push(first);
             }
-        } else if let Some(mat) = regex.find_iter(&line).next() {
-            let (score_val, relevance) = if rank {
-                let s = calculate_relevance_score(&line, query, line_count, file_path, false, None);
+
+            let (score_val, relevance) = if let Some(weights) = rank {
+                let s = calculate_relevance_score(&line, query, line_count, file_path, false, None, weights);
                 let r = if s >= 80.0 { "Very High" } else if s >= 60.0 { "High" } else if s >= 40.0 { "Medium" } else { "Low" };
                 (s, r.to_string())
             } else {
                 (50.0, "Medium".to_string())
             };
 
-            let matches = vec![Match {
-                start: mat.start(),
-                end: mat.end(),
-                text: mat.as_str().to_string(),
-            }];
-
-            results.push(SearchResult {
-                file: file_path.to_string_lossy().to_string(),
-                line_number: line_count,
-                content: line.clone(),
-                matches,
-                score: score_val,
-                relevance,
-            });
+            let matches = spans
+                .into_iter()
+                .map(|(start, end)| Match {
+                    start,
+                    end,
+                    text: line[start..end].to_string(),
+                    pattern_index: regex.pattern_index(&line, start),
+                })
+                .collect();
+            Some((matches, score_val, relevance))
+        } else {
+            None
+        };
+
+        let Some((matches, score, relevance)) = hit else {
+            if after_left > 0 {
+                if let Some(last) = results.last_mut() {
+                    last.context_after.push(clip_long_line(line));
+                }
+                after_left -= 1;
+            } else if context.before > 0 {
+                if before.len() == context.before {
+                    before.pop_front();
+                }
+                before.push_back(clip_long_line(line));
+            }
+            continue;
+        };
+
+        if results.len() >= max_results {
+            truncated = true;
+            break;
         }
+        let (content, content_offset, matches) = if line.len() > LONG_LINE_BYTES {
+            let first = matches.first().map_or((0, 0), |m| (m.start, m.end));
+            let (window, offset) = segment_long_line(&line, first);
+            let matches = matches.into_iter().map(clip_match_text).collect();
+            (window, Some(offset), matches)
+        } else {
+            (line, None, matches)
+        };
+        results.push(SearchResult {
+            file: file_path.to_string_lossy().to_string(),
+            line_number: line_count,
+            end_line_number: None,
+            content,
+            matches,
+            score,
+            relevance,
+            context_before: before.drain(..).collect(),
+            context_after: Vec::new(),
+            line_truncated: content_offset.is_some(),
+            content_offset,
+            annotations: Default::default(),
+        });
+        after_left = context.after;
     }
 
-    Ok(results)
+    Ok((results, truncated))
+}
+
+/// Name defined after the first `keyword` in `line`, e.g. `parse` in `pub fn parse(`
+fn defined_name<'a>(line: &'a str, keyword: &str) -> Option<&'a str> {
+    let rest = &line[line.find(keyword)? + keyword.len()..];
+    let rest = rest.trim_start();
+    let end = rest.find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(rest.len());
+    (end > 0).then(|| &rest[..end])
+}
+
+/// Cut a long line down to its first match and [`LONG_LINE_WINDOW`] characters either side
+///
+/// Returns the window, with `…` where the line was cut, and the byte offset in
+/// `line` where it starts. A match longer than the window is cut as well. Cuts
+/// fall between grapheme clusters, so the window can come out a little shorter.
+fn segment_long_line(line: &str, (start, end): (usize, usize)) -> (String, usize) {
+    let from = suffix_start(&line[..start], LONG_LINE_WINDOW);
+    let end = end.min(advance(line, start, LONG_LINE_WINDOW));
+    let to = advance(line, end, LONG_LINE_WINDOW);
+
+    let mut window = String::with_capacity(to - from + 2 * ELLIPSIS.len());
+    if from > 0 {
+        window.push_str(ELLIPSIS);
+    }
+    window.push_str(&line[from..to]);
+    if to < line.len() {
+        window.push_str(ELLIPSIS);
+    }
+    (window, from)
+}
+
+/// Byte offset at most `chars` characters past `from`, between grapheme clusters, or the end of `line`
+fn advance(line: &str, from: usize, chars: usize) -> usize {
+    from + prefix_end(&line[from..], chars)
 }
 
+/// Keep at most [`LONG_LINE_WINDOW`] characters of a match's text; offsets are unchanged
+fn clip_match_text(mut m: Match) -> Match {
+    let end = prefix_end(&m.text, LONG_LINE_WINDOW);
+    m.text.truncate(end);
+    m
+}
+
+/// Cut a context line over [`LONG_LINE_BYTES`] to its first [`LONG_LINE_WINDOW`] characters
+fn clip_long_line(line: String) -> String {
+    if line.len() <= LONG_LINE_BYTES {
+        return line;
+    }
+    let end = prefix_end(&line, LONG_LINE_WINDOW);
+    format!("{}{ELLIPSIS}", &line[..end])
+}
+
+/// Width of the score's tiebreaker, below the one decimal place scores are shown with
+const TIEBREAK_RANGE: f64 = 0.01;
+
+/// Trimmed line length (in characters) past which the tiebreaker no longer tells lines apart
+const TIEBREAK_MAX_CHARS: usize = 1000;
+
+/// Keywords that start a definition, each followed by the defined name
+const DEFINITION_KEYWORDS: [&str; 7] = ["fn ", "def ", "function ", "class ", "struct ", "impl ", "trait "];
+
 /// Calculate relevance score for a search result
+///
+/// `file_path` is the path as reported in the results, relative to where the
+/// search ran when its path was relative; the query found in its file stem or
+/// in a directory name along it (ignoring case) boosts the score, as does a
+/// definition of exactly the query. How much each boost adds is `weights`.
+///
+/// Besides the coarse boosts, shorter lines get a bonus of up to
+/// [`TIEBREAK_RANGE`], so lines with the same boosts rarely tie and the
+/// tighter match ranks first. The bonus never moves a score across a
+/// relevance band, and the total stays within 0 to 100.
 pub fn calculate_relevance_score(
     line: &str,
     query: &str,
@@ -105,11 +293,12 @@ pub fn calculate_relevance_score(
     file_path: &Path,
     _is_fuzzy: bool,
     fuzzy_score: Option<i64>,
+    weights: &ScoreWeights,
 ) -> f64 {
     let mut score = 50.0;
 
     if line.contains(query) {
-        score += 30.0;
+        score += weights.content_match;
     }
 
     if let Some(fs) = fuzzy_score {
@@ -117,24 +306,41 @@ pub fn calculate_relevance_score(
     }
 
     if line_number < 100 {
-        score += 5.0;
+        score += weights.early_line;
     }
 
     if let Some(ext) = file_path.extension().and_then(|e| e.to_str()) {
-        match ext {
-            "rs" | "py" | "js" | "ts" => score += 10.0,
-            "md" | "txt" => score += 5.0,
+        match language_extension(ext) {
+            "rs" | "py" | "js" | "ts" => score += weights.source_file,
+            "md" | "txt" => score += weights.text_file,
             _ => {}
         }
     }
 
-    let definition_patterns = ["fn ", "def ", "function ", "class ", "struct ", "impl ", "trait "];
-    for pattern in &definition_patterns {
-        if line.contains(pattern) {
-            score += 15.0;
-            break;
+    if let Some(keyword) = DEFINITION_KEYWORDS.iter().find(|keyword| line.contains(*keyword)) {
+        score += weights.definition;
+        if defined_name(line, keyword) == Some(query) {
+            score += weights.definition_name;
+        }
+    }
+
+    let query_lower = query.to_lowercase();
+    if !query_lower.is_empty() {
+        let mentions = |name: &std::ffi::OsStr| name.to_string_lossy().to_lowercase().contains(&query_lower);
+        if file_path.file_stem().is_some_and(mentions) {
+            score += weights.file_stem;
+        }
+        let in_directory = file_path
+            .parent()
+            .into_iter()
+            .flat_map(Path::components)
+            .any(|c| matches!(c, Component::Normal(name) if mentions(name)));
+        if in_directory {
+            score += weights.directory;
         }
     }
 
-    score.clamp(0.0, 100.0)
+    let length = line.trim().chars().count().min(TIEBREAK_MAX_CHARS);
+    let tiebreak = TIEBREAK_RANGE * (TIEBREAK_MAX_CHARS - length) as f64 / TIEBREAK_MAX_CHARS as f64;
+    score.clamp(0.0, 100.0 - TIEBREAK_RANGE) + tiebreak
 }
@@ -0,0 +1,220 @@
+//! Result Line Layout
+//!
+//! Fits result lines to the terminal so rows stay aligned: the `line:` gutter
+//! has a fixed width and content past the right edge is cut with an
+//! ellipsis, or wrapped under itself with `--wrap`. A cut never hides the
+//! first match; when it would, the row becomes a window around the match, as
+//! with long minified lines. Output that isn't a terminal is left as is
+//! unless `--width` asks for a layout.
+
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// `--width`, or 0 to use the terminal's
+static WIDTH: AtomicUsize = AtomicUsize::new(0);
+static WRAP: AtomicBool = AtomicBool::new(false);
+
+/// Fewest columns left for content, however wide the gutter
+pub const MIN_CONTENT_WIDTH: usize = 20;
+
+/// Columns a tab is expanded to in laid-out lines
+pub const TAB_WIDTH: usize = 4;
+
+/// Set the `--width` override and whether to wrap instead of cutting, for the rest of the process
+pub fn set_layout(width: Option<usize>, wrap: bool) {
+    WIDTH.store(width.unwrap_or(0), Ordering::Relaxed);
+    WRAP.store(wrap, Ordering::Relaxed);
+}
+
+/// Columns to lay results out in: `--width`, else the terminal's when stdout is one
+///
+/// `None` means lines are printed whole.
+pub fn output_width() -> Option<usize> {
+    match WIDTH.load(Ordering::Relaxed) {
+        0 if std::io::stdout().is_terminal() => {
+            terminal_size::terminal_size().map(|(width, _)| width.0 as usize).filter(|&w| w > 0)
+        }
+        0 => None,
+        width => Some(width),
+    }
+}
+
+/// Whether overflowing lines wrap instead of being cut
+pub fn wrap_enabled() -> bool {
+    WRAP.load(Ordering::Relaxed)
+}
+
+/// One printed row of a line: a byte range of it and whether it was cut on either side
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Row {
+    pub start: usize,
+    pub end: usize,
+    /// Text before `start` was left out, so the row starts with an ellipsis
+    pub cut_before: bool,
+    /// Text after `end` was left out, so the row ends with an ellipsis
+    pub cut_after: bool,
+}
+
+/// Split `line` into the rows that fit `width` columns after a `prefix_width`-column gutter
+///
+/// Widths are in characters, so tabs should be expanded first (see
+/// [`expand_tabs`]). `spans` are byte ranges of the matches in `line`.
+///
+/// Without `wrap` there is one row, cut where it overflows with room for an
+/// ellipsis of `ellipsis_width` columns. If that would cut off the first match,
+/// the row is a window starting a quarter of its width before the match
+/// instead. With `wrap` the rows cover the whole line, breaking after a space
+/// in the second half of a row where there is one; rows after the first are
+/// meant to be indented by the gutter width.
+pub fn layout_line(
+    prefix_width: usize,
+    line: &str,
+    spans: &[(usize, usize)],
+    width: usize,
+    wrap: bool,
+    ellipsis_width: usize,
+) -> Vec<Row> {
+    let room = width.saturating_sub(prefix_width).max(MIN_CONTENT_WIDTH);
+    // Byte offset of every character, and of the end of the line
+    let offsets: Vec<usize> = line.char_indices().map(|(i, _)| i).chain([line.len()]).collect();
+    let chars = offsets.len() - 1;
+    if chars <= room {
+        return vec![Row { start: 0, end: line.len(), cut_before: false, cut_after: false }];
+    }
+    if wrap {
+        return wrap_rows(line, &offsets, room);
+    }
+
+    let char_at = |byte: usize| offsets.partition_point(|&offset| offset < byte);
+    let head = room.saturating_sub(ellipsis_width).max(1);
+    let first_match = spans.iter().filter(|&&(start, end)| start < end).min();
+    let row = match first_match {
+        Some(&(start, end)) if char_at(end) > head => {
+            let inner = room.saturating_sub(2 * ellipsis_width).max(1);
+            let from = char_at(start).saturating_sub(inner / 4);
+            if from + inner >= chars {
+                // The window reaches the end, so only the start is cut
+                let from = chars - head;
+                Row { start: offsets[from], end: line.len(), cut_before: true, cut_after: false }
+            } else {
+                Row { start: offsets[from], end: offsets[from + inner], cut_before: from > 0, cut_after: true }
+            }
+        }
+        _ => Row { start: 0, end: offsets[head], cut_before: false, cut_after: true },
+    };
+    vec![row]
+}
+
+/// Rows of at most `room` characters, breaking after the last space past half a row
+fn wrap_rows(line: &str, offsets: &[usize], room: usize) -> Vec<Row> {
+    let chars = offsets.len() - 1;
+    let mut rows = Vec::new();
+    let mut from = 0;
+    while from < chars {
+        let mut to = (from + room).min(chars);
+        if to < chars {
+            let space = (from + room / 2..to).rev().find(|&i| line[offsets[i]..].starts_with(char::is_whitespace));
+            if let Some(space) = space {
+                to = space + 1;
+            }
+        }
+        rows.push(Row { start: offsets[from], end: offsets[to], cut_before: false, cut_after: false });
+        from = to;
+    }
+    rows
+}
+
+/// `line` with each tab replaced by [`TAB_WIDTH`] spaces, and `spans` moved to match
+pub fn expand_tabs(line: &str, spans: &[(usize, usize, usize)]) -> (String, Vec<(usize, usize, usize)>) {
+    if !line.contains('\t') {
+        return (line.to_string(), spans.to_vec());
+    }
+    let shift = |byte: usize| byte + line[..byte.min(line.len())].matches('\t').count() * (TAB_WIDTH - 1);
+    let expanded = line.replace('\t', &" ".repeat(TAB_WIDTH));
+    let spans = spans.iter().map(|&(start, end, tag)| (shift(start), shift(end), tag)).collect();
+    (expanded, spans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text<'a>(line: &'a str, row: &Row) -> &'a str {
+        &line[row.start..row.end]
+    }
+
+    #[test]
+    fn test_short_line_is_one_whole_row() {
+        let rows = layout_line(8, "fn main() {}", &[(3, 7)], 80, false, 1);
+        assert_eq!(rows, vec![Row { start: 0, end: 12, cut_before: false, cut_after: false }]);
+    }
+
+    #[test]
+    fn test_long_line_is_cut_with_room_for_the_ellipsis() {
+        let line = "x".repeat(100);
+        let rows = layout_line(10, &line, &[(0, 1)], 40, false, 1);
+        assert_eq!(rows, vec![Row { start: 0, end: 29, cut_before: false, cut_after: true }]);
+    }
+
+    #[test]
+    fn test_match_past_the_edge_gets_a_window() {
+        let line = format!("{}needle{}", "a".repeat(60), "b".repeat(60));
+        let rows = layout_line(0, &line, &[(60, 66)], 30, false, 1);
+        assert_eq!(rows.len(), 1);
+        let row = rows[0];
+        assert!(row.cut_before && row.cut_after);
+        // 28 columns between the ellipses, 7 of them before the match
+        assert_eq!(text(&line, &row), format!("{}needle{}", "a".repeat(7), "b".repeat(15)));
+    }
+
+    #[test]
+    fn test_match_near_the_end_cuts_only_the_start() {
+        let line = format!("{}needle", "a".repeat(60));
+        let rows = layout_line(0, &line, &[(60, 66)], 30, false, 3);
+        assert_eq!(rows.len(), 1);
+        assert!(rows[0].cut_before && !rows[0].cut_after);
+        assert_eq!(text(&line, &rows[0]), format!("{}needle", "a".repeat(21)));
+    }
+
+    #[test]
+    fn test_window_keeps_multibyte_characters_whole() {
+        let line = format!("{}needle{}", "\u{e9}".repeat(50), "\u{e9}".repeat(50));
+        let start = line.find("needle").unwrap();
+        let rows = layout_line(0, &line, &[(start, start + 6)], 24, false, 1);
+        let row = text(&line, &rows[0]);
+        assert!(row.contains("needle"), "{row}");
+        assert_eq!(row.chars().count(), 22);
+    }
+
+    #[test]
+    fn test_narrow_terminal_still_leaves_room_for_content() {
+        let line = "y".repeat(50);
+        let rows = layout_line(30, &line, &[], 32, false, 1);
+        assert_eq!(rows[0].end, MIN_CONTENT_WIDTH - 1);
+    }
+
+    #[test]
+    fn test_wrap_breaks_after_spaces() {
+        let line = "let total = first_value + second_value + third_value;";
+        let rows = layout_line(0, line, &[], 20, true, 1);
+        let texts: Vec<&str> = rows.iter().map(|row| text(line, row)).collect();
+        assert_eq!(texts, vec!["let total = ", "first_value + ", "second_value + ", "third_value;"]);
+        assert!(rows.iter().all(|row| !row.cut_before && !row.cut_after));
+    }
+
+    #[test]
+    fn test_wrap_hard_breaks_long_words() {
+        let line = "z".repeat(45);
+        let rows = layout_line(5, &line, &[], 25, true, 1);
+        let lengths: Vec<usize> = rows.iter().map(|row| row.end - row.start).collect();
+        assert_eq!(lengths, vec![20, 20, 5]);
+    }
+
+    #[test]
+    fn test_expand_tabs_moves_spans() {
+        let (line, spans) = expand_tabs("\t\tfoo\tbar", &[(2, 5, 0), (6, 9, 1)]);
+        assert_eq!(line, "        foo    bar");
+        assert_eq!(spans, vec![(8, 11, 0), (15, 18, 1)]);
+        assert_eq!(&line[15..18], "bar");
+    }
+}
@@ -4,21 +4,33 @@
 
 pub mod core;
 pub mod engine;
+pub mod filelist;
 pub mod fuzzy;
+pub mod layout;
+pub mod multiline;
+pub mod pattern;
+pub mod processors;
 pub mod pure;
+pub mod query;
 pub mod semantic;
 pub mod utilities;
 
-pub use core::{search_code, list_files};
+pub use core::{apply_total_limit, build_matcher, plan_query, search_code, search_code_outcome, search_code_streaming, search_contents_outcome, search_files_outcome, search_path_or_glob, search_path_or_glob_outcome, list_files, list_files_with, dedup_results, group_results_by_file, sort_results_by_path, sort_results_by_score};
 pub use engine::DefaultSearchEngine;
-pub use fuzzy::{search_in_file_parallel, calculate_relevance_score};
-pub use semantic::enhance_query_semantically;
-pub use utilities::{compare_with_grep, print_results, print_search_stats};
+pub use filelist::{load_file_list, read_file_list, FileList};
+pub use fuzzy::{search_in_file_limited, search_in_file_parallel, search_reader_limited, calculate_relevance_score};
+pub use multiline::{search_in_file_multiline, search_text_multiline};
+pub use pattern::{compile_pattern, PatternError, PatternMatcher, UnsupportedConstruct};
+pub use processors::{apply_post_processors, CodeOwnersAnnotator, ResultProcessor};
+pub use query::{CaseMode, Query, QueryKind, QueryPlan};
+pub use semantic::{enhance_query_semantically, expand_query, QueryExpansion, SynonymTable};
+pub use utilities::{annotation_suffix, chrome_enabled, compare_with_grep, grep_comparison, highlight_ranges, highlight_result, highlight_tagged_ranges, only_matching_texts, print_grep_comparison, print_only_matching, print_results, print_limit_status, print_results_annotated, print_search_metrics, print_search_stats};
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::SearchOptions;
+    use crate::types::{ContextLines, LimitReached, RegexEngine, ScoreWeights, SearchOptions, SearchResult, DEFAULT_MULTILINE_MAX_BYTES};
+    use crate::encoding::Encoding;
     use std::path::Path;
 
     #[test]
@@ -39,11 +51,47 @@ mod tests {
             Path::new("test.rs"),
             false,
             None,
+            &ScoreWeights::default(),
         );
         assert!(score > 0.0);
         assert!(score <= 100.0);
     }
 
+    #[test]
+    fn test_relevance_score_boosts_query_in_directory_and_file_stem() {
+        let weights = ScoreWeights::default();
+        let line = "// hand the tokens to the parser";
+        let score = |path: &str| calculate_relevance_score(line, "parser", 150, Path::new(path), false, None, &weights);
+
+        let in_parser_dir = score("parser/mod.rs");
+        let in_parser_file = score("src/Parser.rs");
+        let elsewhere = score("utils/misc.rs");
+        assert!(in_parser_dir > elsewhere, "{in_parser_dir} vs {elsewhere}");
+        assert!(in_parser_file > in_parser_dir, "{in_parser_file} vs {in_parser_dir}");
+    }
+
+    #[test]
+    fn test_relevance_score_boosts_definition_of_the_query() {
+        // Without the content and file type boosts the defaults don't saturate at 100
+        let weights = ScoreWeights { content_match: 0.0, source_file: 0.0, ..ScoreWeights::default() };
+        let score = |line: &str| calculate_relevance_score(line, "parse", 150, Path::new("src/lib.rs"), false, None, &weights);
+
+        let definition = score("pub fn parse(input: &str) -> Ast {");
+        let other_definition = score("pub fn parse_all(input: &str) -> Vec<Ast> {");
+        let call = score("let ast = parse(input);");
+        assert!(definition > other_definition, "{definition} vs {other_definition}");
+        assert!(other_definition > call, "{other_definition} vs {call}");
+    }
+
+    #[test]
+    fn test_relevance_score_zero_weights_disable_path_boosts() {
+        let weights = ScoreWeights { file_stem: 0.0, directory: 0.0, ..ScoreWeights::default() };
+        let line = "// hand the tokens to the parser";
+        let score = |path: &str| calculate_relevance_score(line, "parser", 150, Path::new(path), false, None, &weights);
+
+        assert_eq!(score("parser/parser.rs"), score("utils/misc.rs"));
+    }
+
     #[test]
     fn test_search_code_basic() {
         use std::fs;
@@ -58,13 +106,25 @@ mod tests {
             ignore_case: true,
             fuzzy: false,
             fuzzy_threshold: 0.6,
+            literal: false,
+            word_boundary: false,
             max_results: 10,
+            max_total: None,
             exclude: None,
+            gitignore: true,
             rank: false,
             cache: false,
             semantic: false,
             benchmark: false,
             vs_grep: false,
+            engine: RegexEngine::Default,
+            context: ContextLines::default(),
+            multiline: false,
+            multiline_max_bytes: DEFAULT_MULTILINE_MAX_BYTES,
+            encoding: Encoding::Auto,
+            index: None,
+            score_weights: Default::default(),
+            post_processors: Vec::new(),
         };
         let results = search_code("test", dir.path(), &options);
 
@@ -102,13 +162,25 @@ mod tests {
             ignore_case: true,
             fuzzy: false,
             fuzzy_threshold: 0.6,
+            literal: false,
+            word_boundary: false,
             max_results: 10,
+            max_total: None,
             exclude: None,
+            gitignore: true,
             rank: false,
             cache: false,
             semantic: false,
             benchmark: false,
             vs_grep: false,
+            engine: RegexEngine::Default,
+            context: ContextLines::default(),
+            multiline: false,
+            multiline_max_bytes: DEFAULT_MULTILINE_MAX_BYTES,
+            encoding: Encoding::Auto,
+            index: None,
+            score_weights: Default::default(),
+            post_processors: Vec::new(),
         };
         let results = search_code("test", dir.path(), &options);
 
@@ -116,4 +188,302 @@ mod tests {
         let results = results.unwrap();
         assert_eq!(results.len(), 1);
     }
+
+    #[test]
+    fn test_search_code_reports_unsupported_regex_syntax() {
+        use std::fs;
+        use tempfile::tempdir;
+
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("main.rs"), "fn main() {}").unwrap();
+
+        let err = search_code("(?<=fn )main", dir.path(), &SearchOptions::default()).unwrap_err();
+        let err = err.downcast_ref::<PatternError>().expect("pattern error");
+        assert_eq!(err.construct, Some(UnsupportedConstruct::Lookaround));
+    }
+
+    #[test]
+    fn test_only_matching_reports_every_match_on_a_line() {
+        use std::fs;
+        use tempfile::tempdir;
+
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("deps.toml"), "a = \"1.2.3\", b = \"4.5.6\"\nname = \"x\"\n").unwrap();
+
+        let options = SearchOptions::default();
+        let results = search_code(r"\d+\.\d+\.\d+", dir.path(), &options).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(only_matching_texts(&results[0], None), vec!["1.2.3", "4.5.6"]);
+    }
+
+    #[test]
+    fn test_every_match_on_a_line_is_recorded() {
+        use std::fs;
+        use tempfile::tempdir;
+
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("lib.rs"), "let total = total + total;\nlet other = 1;\n").unwrap();
+
+        let results = search_code("total", dir.path(), &SearchOptions::default()).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].matches.len(), 3);
+        let starts: Vec<usize> = results[0].matches.iter().map(|m| m.start).collect();
+        assert_eq!(starts, vec![4, 12, 20]);
+
+        let stats = crate::types::SearchStats::from_results(&results);
+        assert_eq!((stats.total_matches, stats.total_occurrences), (1, 3));
+    }
+
+    #[test]
+    fn test_only_matching_output_template() {
+        use std::fs;
+        use tempfile::tempdir;
+
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("urls.txt"), "see https://example.com/a and http://test.org/b\n").unwrap();
+
+        let query = r"https?://([\w.]+)/\w+";
+        let options = SearchOptions::default();
+        let results = search_code(query, dir.path(), &options).unwrap();
+        let matcher = build_matcher(query, &options).unwrap();
+
+        let hosts = only_matching_texts(&results[0], Some((&matcher, "$1")));
+        assert_eq!(hosts, vec!["example.com", "test.org"]);
+    }
+
+    #[test]
+    fn test_search_code_streams_many_files() {
+        use std::fs;
+        use tempfile::tempdir;
+
+        let dir = tempdir().unwrap();
+        for d in 0..5 {
+            let sub = dir.path().join(format!("dir{d}"));
+            fs::create_dir(&sub).unwrap();
+            for f in 0..300 {
+                fs::write(sub.join(format!("f{f}.rs")), "fn needle() {}\nlet x = 1;\n").unwrap();
+            }
+        }
+
+        let results = search_code("needle", dir.path(), &SearchOptions::default()).unwrap();
+        assert_eq!(results.len(), 1500);
+    }
+
+    #[test]
+    fn test_sort_results_by_path_is_deterministic() {
+        use std::fs;
+        use tempfile::tempdir;
+
+        let dir = tempdir().unwrap();
+        for name in ["c.rs", "a.rs", "b.rs"] {
+            fs::write(dir.path().join(name), "fn one() {}\nfn two() {}\n").unwrap();
+        }
+
+        let mut results = search_code("fn", dir.path(), &SearchOptions::default()).unwrap();
+        sort_results_by_path(&mut results);
+
+        let order: Vec<(String, usize)> = results
+            .iter()
+            .map(|r| (Path::new(&r.file).file_name().unwrap().to_string_lossy().to_string(), r.line_number))
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                ("a.rs".to_string(), 1),
+                ("a.rs".to_string(), 2),
+                ("b.rs".to_string(), 1),
+                ("b.rs".to_string(), 2),
+                ("c.rs".to_string(), 1),
+                ("c.rs".to_string(), 2),
+            ]
+        );
+    }
+
+    fn limits_fixture() -> tempfile::TempDir {
+        use std::fs;
+
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("c.rs"), "fn needle() {}\nlet needle = 1;\nneedle();\n").unwrap();
+        fs::write(dir.path().join("a.rs"), "// needle\nfn needle_helper() {}\nlet x = needle;\n").unwrap();
+        fs::write(dir.path().join("b.rs"), "needle\nneedle\nneedle\n").unwrap();
+        dir
+    }
+
+    fn file_names(results: &[SearchResult]) -> Vec<String> {
+        results
+            .iter()
+            .map(|r| Path::new(&r.file).file_name().unwrap().to_string_lossy().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_limits_not_reached_when_under_both_caps() {
+        let dir = limits_fixture();
+        let options = SearchOptions::default().with_max_results(10).with_max_total(100);
+        let outcome = search_code_outcome("needle", dir.path(), &options).unwrap();
+        assert_eq!(outcome.results.len(), 9);
+        assert!(!outcome.limit_reached.any());
+    }
+
+    #[test]
+    fn test_per_file_limit_sets_only_per_file_flag() {
+        let dir = limits_fixture();
+        let options = SearchOptions::default().with_max_results(2);
+        let outcome = search_code_outcome("needle", dir.path(), &options).unwrap();
+        assert_eq!(outcome.results.len(), 6);
+        assert_eq!(outcome.limit_reached, LimitReached { per_file: true, total: false });
+    }
+
+    #[test]
+    fn test_total_limit_without_rank_keeps_path_order_prefix() {
+        let dir = limits_fixture();
+        let options = SearchOptions::default().with_max_total(4);
+        let outcome = search_code_outcome("needle", dir.path(), &options).unwrap();
+        assert_eq!(file_names(&outcome.results), vec!["a.rs", "a.rs", "a.rs", "b.rs"]);
+        assert_eq!(outcome.limit_reached, LimitReached { per_file: false, total: true });
+    }
+
+    #[test]
+    fn test_total_limit_with_rank_keeps_best_scores() {
+        let dir = limits_fixture();
+        let ranked = SearchOptions::default().with_rank(true);
+        let all = search_code_outcome("needle", dir.path(), &ranked).unwrap().results;
+
+        let capped = search_code_outcome("needle", dir.path(), &ranked.clone().with_max_total(3)).unwrap();
+        assert_eq!(capped.results.len(), 3);
+        assert!(capped.limit_reached.total);
+        let kept_min = capped.results.iter().map(|r| r.score).fold(f64::INFINITY, f64::min);
+        let best: Vec<f64> = all.iter().take(3).map(|r| r.score).collect();
+        assert_eq!(capped.results.iter().map(|r| r.score).collect::<Vec<_>>(), best);
+        assert!(all[3..].iter().all(|r| r.score <= kept_min));
+    }
+
+    #[test]
+    fn test_both_limits_together() {
+        let dir = limits_fixture();
+        for rank in [false, true] {
+            let options = SearchOptions::default()
+                .with_max_results(1)
+                .with_max_total(2)
+                .with_rank(rank);
+            let outcome = search_code_outcome("needle", dir.path(), &options).unwrap();
+            assert_eq!(outcome.results.len(), 2, "rank={rank}");
+            assert_eq!(outcome.limit_reached, LimitReached { per_file: true, total: true }, "rank={rank}");
+            let names = file_names(&outcome.results);
+            assert_ne!(names[0], names[1], "per-file cap applies before the total cap");
+        }
+    }
+
+    #[test]
+    fn test_total_limit_reports_how_many_results_were_dropped() {
+        let dir = limits_fixture();
+        let ranked = SearchOptions::default().with_rank(true).with_max_total(3);
+        let outcome = search_code_outcome("needle", dir.path(), &ranked).unwrap();
+        assert_eq!(outcome.truncated, 6);
+        assert!(!outcome.files_skipped);
+
+        // Unranked searches may skip files, so the count is a lower bound
+        let outcome = search_code_outcome("needle", dir.path(), &SearchOptions::default().with_max_total(4)).unwrap();
+        assert!(outcome.truncated >= 2);
+    }
+
+    #[test]
+    fn test_total_limit_without_rank_skips_files_past_the_kept_prefix() {
+        use std::fs;
+
+        let dir = tempfile::tempdir().unwrap();
+        // `f000/` sorts before `f000.rs` component-wise, though not as a string
+        fs::create_dir(dir.path().join("f000")).unwrap();
+        fs::write(dir.path().join("f000/inner.rs"), "needle\n").unwrap();
+        for i in 0..500 {
+            fs::write(dir.path().join(format!("f{i:03}.rs")), "needle\n").unwrap();
+        }
+
+        let outcome = search_code_outcome("needle", dir.path(), &SearchOptions::default().with_max_total(4)).unwrap();
+        assert_eq!(file_names(&outcome.results), vec!["inner.rs", "f000.rs", "f001.rs", "f002.rs"]);
+        assert!(outcome.limit_reached.total);
+        assert!(outcome.files_skipped);
+        assert!(outcome.metrics.files_processed < 501);
+
+        let mut all = search_code("needle", dir.path(), &SearchOptions::default()).unwrap();
+        sort_results_by_path(&mut all);
+        assert_eq!(file_names(&all[..2]), vec!["inner.rs", "f000.rs"]);
+    }
+
+    #[test]
+    fn test_total_limit_equal_to_count_is_not_reported() {
+        let dir = limits_fixture();
+        let options = SearchOptions::default().with_max_total(9);
+        let outcome = search_code_outcome("needle", dir.path(), &options).unwrap();
+        assert_eq!(outcome.results.len(), 9);
+        assert!(!outcome.limit_reached.total);
+    }
+
+    fn scored(file: &str, line_number: usize, score: f64) -> SearchResult {
+        SearchResult {
+            file: file.to_string(),
+            line_number,
+            end_line_number: None,
+            content: "needle".to_string(),
+            matches: Vec::new(),
+            score,
+            relevance: "Medium".to_string(),
+            context_before: Vec::new(),
+            context_after: Vec::new(),
+            line_truncated: false,
+            content_offset: None,
+            annotations: Default::default(),
+        }
+    }
+
+    fn locations(results: &[SearchResult]) -> Vec<(&str, usize)> {
+        results.iter().map(|r| (r.file.as_str(), r.line_number)).collect()
+    }
+
+    #[test]
+    fn test_group_results_by_file_orders_files_by_best_score() {
+        let ranked = vec![
+            scored("b.rs", 40, 90.0),
+            scored("a.rs", 2, 80.0),
+            scored("c.rs", 7, 70.0),
+            scored("b.rs", 3, 60.0),
+            scored("a.rs", 1, 50.0),
+            scored("b.rs", 12, 10.0),
+        ];
+        let grouped = group_results_by_file(ranked);
+        assert_eq!(
+            locations(&grouped),
+            vec![("b.rs", 3), ("b.rs", 12), ("b.rs", 40), ("a.rs", 1), ("a.rs", 2), ("c.rs", 7)]
+        );
+    }
+
+    #[test]
+    fn test_group_results_by_file_breaks_score_ties_by_path() {
+        let grouped = group_results_by_file(vec![scored("z.rs", 9, 50.0), scored("m.rs", 4, 50.0), scored("z.rs", 1, 20.0)]);
+        assert_eq!(locations(&grouped), vec![("m.rs", 4), ("z.rs", 1), ("z.rs", 9)]);
+        assert!(group_results_by_file(Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn test_sort_results_by_score_breaks_ties_by_path_then_line() {
+        let mut results = vec![scored("b.rs", 2, 50.0), scored("a.rs", 9, 50.0), scored("c.rs", 1, 70.0), scored("a.rs", 3, 50.0)];
+        sort_results_by_score(&mut results);
+        assert_eq!(locations(&results), vec![("c.rs", 1), ("a.rs", 3), ("a.rs", 9), ("b.rs", 2)]);
+    }
+
+    #[test]
+    fn test_dedup_results_keeps_higher_score_in_first_position() {
+        let mut results = vec![scored("a.rs", 1, 40.0), scored("b.rs", 2, 60.0), scored("a.rs", 1, 90.0), scored("b.rs", 2, 10.0)];
+        dedup_results(&mut results);
+        assert_eq!(locations(&results), vec![("a.rs", 1), ("b.rs", 2)]);
+        assert_eq!(results.iter().map(|r| r.score).collect::<Vec<_>>(), vec![90.0, 60.0]);
+    }
+
+    #[test]
+    fn test_rank_group_parses_cli_values() {
+        use crate::types::RankGroup;
+        assert_eq!("file".parse::<RankGroup>(), Ok(RankGroup::File));
+        assert_eq!("None".parse::<RankGroup>(), Ok(RankGroup::None));
+        assert!("line".parse::<RankGroup>().is_err());
+    }
 }
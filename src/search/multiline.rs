@@ -0,0 +1,188 @@
+//! Multiline Search
+//!
+//! Matches the pattern against whole files so it can span lines, then maps each
+//! match back to the lines it covers. Matches that start on a line already
+//! covered by the previous match are reported with it, so no line is reported
+//! twice.
+
+use crate::encoding::{read_text, Encoding};
+use crate::lines::{line_starts, split_lines};
+use crate::types::{ContextLines, Match, ScoreWeights, SearchResult};
+use super::fuzzy::calculate_relevance_score;
+use super::pattern::PatternMatcher;
+use std::fs;
+use std::path::Path;
+
+/// Search a whole file, reporting up to `max_results` spans of lines
+///
+/// Files larger than `max_bytes` are skipped. The flag is set when
+/// `max_results` cut off further matches.
+#[allow(clippy::too_many_arguments)]
+pub fn search_in_file_multiline(
+    file_path: &Path,
+    regex: &PatternMatcher,
+    query: &str,
+    max_results: usize,
+    rank: Option<&ScoreWeights>,
+    context: ContextLines,
+    max_bytes: u64,
+    encoding: Encoding,
+) -> Result<(Vec<SearchResult>, bool), Box<dyn std::error::Error>> {
+    if fs::metadata(file_path)?.len() > max_bytes {
+        return Ok((Vec::new(), false));
+    }
+    let text = read_text(file_path, encoding)?;
+    Ok(search_text_multiline(&text, file_path, regex, query, max_results, rank, context))
+}
+
+/// Like [`search_in_file_multiline`], over contents already in memory
+///
+/// `file_path` is only reported in the results and used for ranking.
+pub fn search_text_multiline(
+    text: &str,
+    file_path: &Path,
+    regex: &PatternMatcher,
+    query: &str,
+    max_results: usize,
+    rank: Option<&ScoreWeights>,
+    context: ContextLines,
+) -> (Vec<SearchResult>, bool) {
+    let lines: Vec<&str> = split_lines(text).collect();
+    let starts = line_starts(text);
+    let line_of = |offset: usize| starts.partition_point(|&start| start <= offset).saturating_sub(1);
+
+    // First and last line (0-based) of each reported span, with the matches in it
+    type Group = (usize, usize, Vec<(usize, usize)>);
+    let mut groups: Vec<Group> = Vec::new();
+    let mut truncated = false;
+    for (start, end) in regex.find_all(text) {
+        if end == start {
+            continue;
+        }
+        let (first, last) = (line_of(start), line_of(end - 1));
+        let full = groups.len() >= max_results;
+        match groups.last_mut() {
+            Some((_, group_last, spans)) if first <= *group_last => {
+                *group_last = (*group_last).max(last);
+                spans.push((start, end));
+            }
+            _ if full => {
+                truncated = true;
+                break;
+            }
+            _ => groups.push((first, last, vec![(start, end)])),
+        }
+    }
+
+    let mut results = Vec::with_capacity(groups.len());
+    // First line not yet reported, so context lines are reported once
+    let mut next_free = 0;
+    for (idx, (first, last, spans)) in groups.iter().enumerate() {
+        let (first, last) = (*first, *last);
+        let content = lines[first..=last].join("\n");
+
+        // Byte offset in the file -> offset in `content`, whose lines end in a single `\n`
+        let to_content = |offset: usize, line: usize| {
+            let column = (offset - starts[line]).min(lines[line].len());
+            lines[first..line].iter().map(|l| l.len() + 1).sum::<usize>() + column
+        };
+        let matches = spans
+            .iter()
+            .map(|&(offset, end)| {
+                let start = to_content(offset, line_of(offset));
+                let end = to_content(end, line_of(end - 1)).max(start);
+                Match {
+                    start,
+                    end,
+                    text: content[start..end].to_string(),
+                    pattern_index: regex.pattern_index(text, offset),
+                }
+            })
+            .collect();
+
+        let (score, relevance) = if let Some(weights) = rank {
+            let s = calculate_relevance_score(&content, query, first + 1, file_path, false, None, weights);
+            let r = if s >= 80.0 { "Very High" } else if s >= 60.0 { "High" } else if s >= 40.0 { "Medium" } else { "Low" };
+            (s, r.to_string())
+        } else {
+            (50.0, "Medium".to_string())
+        };
+
+        let before_start = first.saturating_sub(context.before).max(next_free);
+        let after_end = groups
+            .get(idx + 1)
+            .map_or(lines.len(), |(next_first, _, _)| *next_first)
+            .min(last + 1 + context.after)
+            .min(lines.len());
+        next_free = after_end;
+
+        results.push(SearchResult {
+            file: file_path.to_string_lossy().to_string(),
+            line_number: first + 1,
+            end_line_number: (last > first).then_some(last + 1),
+            content,
+            matches,
+            score,
+            relevance,
+            context_before: lines[before_start..first].iter().map(|l| l.to_string()).collect(),
+            context_after: lines[last + 1..after_end].iter().map(|l| l.to_string()).collect(),
+            line_truncated: false,
+            content_offset: None,
+            annotations: Default::default(),
+        });
+    }
+
+    (results, truncated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::pattern::compile_pattern;
+    use crate::types::RegexEngine;
+
+    fn search(content: &str, pattern: &str, max_results: usize, context: ContextLines) -> (Vec<SearchResult>, bool) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.rs");
+        fs::write(&path, content).unwrap();
+        let regex = compile_pattern(&format!("(?ms){pattern}"), RegexEngine::Default).unwrap();
+        search_in_file_multiline(&path, &regex, pattern, max_results, None, context, u64::MAX, Encoding::Auto).unwrap()
+    }
+
+    #[test]
+    fn test_match_spanning_lines() {
+        let content = "struct A;\nimpl A {\n    fn new() {\n        Self\n    }\n}\n";
+        let (results, truncated) = search(content, r"fn new\(\)\s*\{\s*Self", 10, ContextLines::default());
+        assert!(!truncated);
+        assert_eq!(results.len(), 1);
+        assert_eq!((results[0].line_number, results[0].end_line_number), (3, Some(4)));
+        assert_eq!(results[0].content, "    fn new() {\n        Self");
+        assert_eq!(results[0].matches[0].text, "fn new() {\n        Self");
+    }
+
+    #[test]
+    fn test_single_line_matches_and_crlf() {
+        let (results, _) = search("let a = 1;\r\nlet b = 2;\r\n", r"let \w", 10, ContextLines::default());
+        assert_eq!(results.len(), 2);
+        assert_eq!((results[1].line_number, results[1].end_line_number), (2, None));
+        assert_eq!(results[1].content, "let b = 2;");
+
+        // A match ending in a line break doesn't claim the next line
+        let (results, _) = search("a {\r\n}\r\n", r"\{\s*", 10, ContextLines::default());
+        assert_eq!((results[0].line_number, results[0].end_line_number), (1, None));
+        assert_eq!(results[0].matches[0].text, "{");
+    }
+
+    #[test]
+    fn test_max_results_and_context() {
+        let content = "x\nfn a() {\n}\ny\nfn b() {\n}\nz\nfn c() {\n}\n";
+        let (results, truncated) = search(content, r"fn \w\(\) \{\n\}", 2, ContextLines::new(1, 1));
+        assert!(truncated);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].context_before, vec!["x"]);
+        assert_eq!(results[0].context_after, vec!["y"]);
+        // `y` was already reported after the first match
+        assert!(results[1].context_before.is_empty());
+        assert_eq!(results[1].end_line_number, Some(6));
+    }
+}
@@ -0,0 +1,354 @@
+//! Pattern Compilation
+//!
+//! Compiles the search query into a matcher for the selected regex engine and
+//! turns compile failures into errors that say which construct is unsupported.
+
+use crate::types::RegexEngine;
+use regex::Regex;
+use std::fmt;
+
+/// A compiled search pattern
+///
+/// When the query is an alternation (`foo|bar`), each top-level alternative is
+/// also compiled on its own (see [`with_branches`](Self::with_branches)), so a
+/// match can be attributed to the alternative that produced it.
+#[derive(Debug, Clone)]
+pub struct PatternMatcher {
+    regex: CompiledRegex,
+    branches: Vec<CompiledRegex>,
+}
+
+#[derive(Debug, Clone)]
+enum CompiledRegex {
+    Default(Regex),
+    #[cfg(feature = "fancy")]
+    Fancy(fancy_regex::Regex),
+}
+
+impl CompiledRegex {
+    fn find_at(&self, text: &str, start: usize) -> Option<(usize, usize)> {
+        match self {
+            Self::Default(re) => re.find_at(text, start).map(|m| (m.start(), m.end())),
+            // A match that exceeds the backtracking limit counts as no match
+            #[cfg(feature = "fancy")]
+            Self::Fancy(re) => re.find_from_pos(text, start).ok().flatten().map(|m| (m.start(), m.end())),
+        }
+    }
+}
+
+impl PatternMatcher {
+    /// Byte range of the first match in `text`
+    pub fn find(&self, text: &str) -> Option<(usize, usize)> {
+        self.regex.find_at(text, 0)
+    }
+
+    /// Byte ranges of every non-overlapping match in `text`
+    pub fn find_all(&self, text: &str) -> Vec<(usize, usize)> {
+        match &self.regex {
+            CompiledRegex::Default(re) => re.find_iter(text).map(|m| (m.start(), m.end())).collect(),
+            #[cfg(feature = "fancy")]
+            CompiledRegex::Fancy(re) => re
+                .find_iter(text)
+                .filter_map(|m| m.ok())
+                .map(|m| (m.start(), m.end()))
+                .collect(),
+        }
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        self.find(text).is_some()
+    }
+
+    /// Expand a capture-group template (`$1`, `${name}`) for the match at or after `start`
+    pub fn expand_at(&self, text: &str, start: usize, template: &str) -> Option<String> {
+        match &self.regex {
+            CompiledRegex::Default(re) => {
+                let caps = re.captures_at(text, start)?;
+                let mut expanded = String::new();
+                caps.expand(template, &mut expanded);
+                Some(expanded)
+            }
+            #[cfg(feature = "fancy")]
+            CompiledRegex::Fancy(re) => {
+                let caps = re.captures_from_pos(text, start).ok()??;
+                Some(fancy_regex::Expander::default().expansion(template, &caps))
+            }
+        }
+    }
+
+    /// Also compile each of `branches`, the pattern's top-level alternatives, for attribution
+    ///
+    /// If any alternative doesn't compile on its own, matches aren't attributed
+    /// and [`pattern_index`](Self::pattern_index) is always 0.
+    pub fn with_branches(mut self, branches: &[String], engine: RegexEngine) -> Self {
+        self.branches = branches
+            .iter()
+            .map(|branch| compile_pattern(branch, engine).map(|matcher| matcher.regex))
+            .collect::<Result<_, _>>()
+            .unwrap_or_default();
+        self
+    }
+
+    /// Number of alternatives matches are attributed to; 1 without branches
+    pub fn pattern_count(&self) -> usize {
+        self.branches.len().max(1)
+    }
+
+    /// Index of the alternative that produced the match starting at byte `start` of `text`
+    ///
+    /// Like the whole pattern, this picks the first alternative that matches
+    /// there. 0 when the pattern has no branches or none matches at `start`.
+    pub fn pattern_index(&self, text: &str, start: usize) -> usize {
+        self.branches
+            .iter()
+            .position(|branch| branch.find_at(text, start).is_some_and(|(s, _)| s == start))
+            .unwrap_or(0)
+    }
+}
+
+/// Regex syntax the default engine deliberately does not support
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnsupportedConstruct {
+    Lookaround,
+    Backreference,
+    PossessiveQuantifier,
+}
+
+impl UnsupportedConstruct {
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::Lookaround => "lookahead/lookbehind assertions ((?=...), (?!...), (?<=...), (?<!...))",
+            Self::Backreference => "backreferences (\\1, \\k<name>)",
+            Self::PossessiveQuantifier => "possessive quantifiers (*+, ++, ?+)",
+        }
+    }
+}
+
+/// Failure to compile a search pattern
+#[derive(Debug)]
+pub struct PatternError {
+    pub pattern: String,
+    pub engine: RegexEngine,
+    /// Set when the pattern failed because of syntax another engine supports
+    pub construct: Option<UnsupportedConstruct>,
+    pub message: String,
+}
+
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.construct {
+            Some(construct) => {
+                writeln!(
+                    f,
+                    "Pattern '{}' uses {}, which the default regex engine does not support.",
+                    self.pattern,
+                    construct.description()
+                )?;
+                if cfg!(feature = "fancy") {
+                    write!(f, "Hint: rerun with --engine fancy, or rewrite the pattern without them.")
+                } else {
+                    write!(
+                        f,
+                        "Hint: rebuild with --features fancy and rerun with --engine fancy, or rewrite the pattern without them."
+                    )
+                }
+            }
+            None => write!(f, "Invalid regex pattern '{}': {}", self.pattern, self.message),
+        }
+    }
+}
+
+impl std::error::Error for PatternError {}
+
+/// Compile `pattern` with the requested engine
+pub fn compile_pattern(pattern: &str, engine: RegexEngine) -> Result<PatternMatcher, PatternError> {
+    match engine {
+        RegexEngine::Default => Regex::new(pattern)
+            .map(|re| PatternMatcher { regex: CompiledRegex::Default(re), branches: Vec::new() })
+            .map_err(|e| PatternError {
+                pattern: pattern.to_string(),
+                engine,
+                construct: classify_unsupported(pattern),
+                message: e.to_string(),
+            }),
+        RegexEngine::Fancy => compile_fancy(pattern),
+    }
+}
+
+#[cfg(feature = "fancy")]
+fn compile_fancy(pattern: &str) -> Result<PatternMatcher, PatternError> {
+    fancy_regex::Regex::new(pattern)
+        .map(|re| PatternMatcher { regex: CompiledRegex::Fancy(re), branches: Vec::new() })
+        .map_err(|e| PatternError {
+            pattern: pattern.to_string(),
+            engine: RegexEngine::Fancy,
+            construct: None,
+            message: e.to_string(),
+        })
+}
+
+#[cfg(not(feature = "fancy"))]
+fn compile_fancy(pattern: &str) -> Result<PatternMatcher, PatternError> {
+    Err(PatternError {
+        pattern: pattern.to_string(),
+        engine: RegexEngine::Fancy,
+        construct: None,
+        message: "the fancy engine is not available in this build (rebuild with --features fancy)".to_string(),
+    })
+}
+
+/// Find the first construct in `pattern` that only a backtracking engine supports
+///
+/// Escapes and character classes are skipped, so `\(?=` or `[*+]` are not reported.
+pub fn classify_unsupported(pattern: &str) -> Option<UnsupportedConstruct> {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut in_class = false;
+    let mut after_quantifier = false;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '\\' {
+            match chars.get(i + 1) {
+                Some(d) if !in_class && ('1'..='9').contains(d) => return Some(UnsupportedConstruct::Backreference),
+                Some('k') if !in_class && chars.get(i + 2) == Some(&'<') => {
+                    return Some(UnsupportedConstruct::Backreference);
+                }
+                _ => {}
+            }
+            after_quantifier = false;
+            i += 2;
+            continue;
+        }
+
+        if in_class {
+            in_class = c != ']';
+            i += 1;
+            continue;
+        }
+
+        match c {
+            '[' => {
+                in_class = true;
+                // A leading `]` (or `^]`) is a literal member of the class
+                if chars.get(i + 1) == Some(&'^') {
+                    i += 1;
+                }
+                if chars.get(i + 1) == Some(&']') {
+                    i += 1;
+                }
+            }
+            '(' if chars.get(i + 1) == Some(&'?') => {
+                let lookaround = matches!(
+                    (chars.get(i + 2), chars.get(i + 3)),
+                    (Some('=' | '!'), _) | (Some('<'), Some('=' | '!'))
+                );
+                if lookaround {
+                    return Some(UnsupportedConstruct::Lookaround);
+                }
+            }
+            '+' if after_quantifier => return Some(UnsupportedConstruct::PossessiveQuantifier),
+            '*' | '+' | '?' | '}' => {
+                after_quantifier = true;
+                i += 1;
+                continue;
+            }
+            _ => {}
+        }
+        after_quantifier = false;
+        i += 1;
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookbehind_fails_helpfully_on_default_engine() {
+        let err = compile_pattern(r"(?<=fn )main", RegexEngine::Default).unwrap_err();
+        assert_eq!(err.construct, Some(UnsupportedConstruct::Lookaround));
+        let message = err.to_string();
+        assert!(message.contains("lookahead/lookbehind"));
+        assert!(message.contains("--engine fancy"));
+    }
+
+    #[test]
+    fn test_plain_syntax_errors_are_not_classified() {
+        let err = compile_pattern("fn (main", RegexEngine::Default).unwrap_err();
+        assert_eq!(err.construct, None);
+        assert!(err.to_string().starts_with("Invalid regex pattern"));
+    }
+
+    #[test]
+    fn test_classify_unsupported() {
+        assert_eq!(classify_unsupported(r"foo(?=bar)"), Some(UnsupportedConstruct::Lookaround));
+        assert_eq!(classify_unsupported(r"foo(?!bar)"), Some(UnsupportedConstruct::Lookaround));
+        assert_eq!(classify_unsupported(r"(?<!x)y"), Some(UnsupportedConstruct::Lookaround));
+        assert_eq!(classify_unsupported(r"(\w+) \1"), Some(UnsupportedConstruct::Backreference));
+        assert_eq!(classify_unsupported(r"(?<w>\w+) \k<w>"), Some(UnsupportedConstruct::Backreference));
+        assert_eq!(classify_unsupported(r"a++b"), Some(UnsupportedConstruct::PossessiveQuantifier));
+        assert_eq!(classify_unsupported(r"a{2}+"), Some(UnsupportedConstruct::PossessiveQuantifier));
+    }
+
+    #[test]
+    fn test_classify_ignores_escapes_and_classes() {
+        assert_eq!(classify_unsupported(r"\(?=x"), None);
+        assert_eq!(classify_unsupported(r"[*+]+"), None);
+        assert_eq!(classify_unsupported(r"\++"), None);
+        assert_eq!(classify_unsupported(r"(?<name>\w+)"), None);
+        assert_eq!(classify_unsupported(r"a+?b"), None);
+    }
+
+    #[test]
+    fn test_default_engine_matches() {
+        let matcher = compile_pattern(r"fn \w+", RegexEngine::Default).unwrap();
+        assert_eq!(matcher.find("pub fn main() {}"), Some((4, 11)));
+        assert!(!matcher.is_match("let x = 1;"));
+    }
+
+    #[test]
+    fn test_find_all_and_expand_at() {
+        let matcher = compile_pattern(r"v(\d+)\.(\d+)", RegexEngine::Default).unwrap();
+        let line = "upgrade v1.2 to v3.4";
+        assert_eq!(matcher.find_all(line), vec![(8, 12), (16, 20)]);
+        assert_eq!(matcher.expand_at(line, 16, "$1-$2").as_deref(), Some("3-4"));
+    }
+
+    #[test]
+    fn test_pattern_index_picks_the_first_branch_matching_at_the_start() {
+        let branches = ["fn".to_string(), "fn main".to_string(), r"\d+".to_string()];
+        let matcher = compile_pattern(r"fn|fn main|\d+", RegexEngine::Default).unwrap().with_branches(&branches, RegexEngine::Default);
+        assert_eq!(matcher.pattern_count(), 3);
+        let line = "fn main() -> 42";
+        assert_eq!(matcher.find_all(line), vec![(0, 2), (13, 15)]);
+        assert_eq!(matcher.pattern_index(line, 0), 0);
+        assert_eq!(matcher.pattern_index(line, 13), 2);
+
+        // Without branches, or when one doesn't compile alone, everything is pattern 0
+        let plain = compile_pattern(r"\d+", RegexEngine::Default).unwrap();
+        assert_eq!((plain.pattern_count(), plain.pattern_index(line, 13)), (1, 0));
+        let broken = plain.with_branches(&["(".to_string(), "x".to_string()], RegexEngine::Default);
+        assert_eq!(broken.pattern_count(), 1);
+    }
+
+    #[cfg(not(feature = "fancy"))]
+    #[test]
+    fn test_fancy_engine_unavailable_without_feature() {
+        let err = compile_pattern(r"(?<=fn )main", RegexEngine::Fancy).unwrap_err();
+        assert!(err.to_string().contains("--features fancy"));
+    }
+
+    #[cfg(feature = "fancy")]
+    #[test]
+    fn test_lookbehind_succeeds_with_fancy_engine() {
+        let matcher = compile_pattern(r"(?<=fn )main", RegexEngine::Fancy).unwrap();
+        assert_eq!(matcher.find("fn main() {}"), Some((3, 7)));
+        assert!(!matcher.is_match("let main = 1;"));
+
+        let matcher = compile_pattern(r"(\w+) \1", RegexEngine::Fancy).unwrap();
+        assert!(matcher.is_match("the the"));
+    }
+}
@@ -0,0 +1,66 @@
+//! Result Processors
+//!
+//! Hooks that library users register in [`SearchOptions::post_processors`] to
+//! change or annotate results after ranking and the `max_total` cap, before
+//! they are printed or exported. [`CodeOwnersAnnotator`] is the built-in one.
+
+use crate::report::{owners_for, read_codeowners, relative_path, OwnerRule};
+use crate::types::{SearchOptions, SearchResult};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// A step run over every search result before output
+///
+/// Processors usually add entries to [`SearchResult::annotations`], which the
+/// text output shows after the line and JSON includes as is. They run on the
+/// calling thread, in the order they were registered, after ranking, so they
+/// see the final order but shouldn't rely on changing it.
+pub trait ResultProcessor: fmt::Debug + Send + Sync {
+    fn process(&self, result: &mut SearchResult);
+}
+
+/// Run `options.post_processors` over `results`, each processor over every result in turn
+pub fn apply_post_processors(results: &mut [SearchResult], options: &SearchOptions) {
+    for processor in &options.post_processors {
+        for result in results.iter_mut() {
+            processor.process(result);
+        }
+    }
+}
+
+/// Annotates each result with the owners CODEOWNERS assigns its file
+///
+/// The owners go under [`CodeOwnersAnnotator::KEY`], space-separated as in
+/// CODEOWNERS; files no rule matches are left alone. Result paths are taken
+/// relative to `root`, so the search path should be inside it.
+#[derive(Debug, Clone)]
+pub struct CodeOwnersAnnotator {
+    root: PathBuf,
+    rules: Vec<OwnerRule>,
+}
+
+impl CodeOwnersAnnotator {
+    /// Annotation key the owners are stored under
+    pub const KEY: &'static str = "owners";
+
+    pub fn new(root: impl Into<PathBuf>, rules: Vec<OwnerRule>) -> Self {
+        Self { root: root.into(), rules }
+    }
+
+    /// Read the CODEOWNERS file of `root` (also looked for in `.github/` and `docs/`)
+    ///
+    /// Returns `None` when there is none.
+    pub fn discover(root: &Path) -> Option<Self> {
+        read_codeowners(root).map(|rules| Self::new(root, rules))
+    }
+}
+
+impl ResultProcessor for CodeOwnersAnnotator {
+    fn process(&self, result: &mut SearchResult) {
+        let file = relative_path(&self.root, Path::new(&result.file));
+        let owners = owners_for(&self.rules, &file);
+        if !owners.is_empty() {
+            result.annotations.insert(Self::KEY.to_string(), owners.join(" "));
+        }
+    }
+}
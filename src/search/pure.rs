@@ -130,6 +130,7 @@ pub fn extract_matches_pure(_line: &str, start: usize, end: usize, text: &str) -
         start,
         end,
         text: text.to_string(),
+        pattern_index: 0,
     }
 }
 
@@ -142,6 +143,23 @@ pub fn fuzzy_match_quality(score: i64, query_length: usize, line_length: usize)
     (normalized * length_penalty).clamp(0.0, 1.0)
 }
 
+/// Similarity of a fuzzy match, from 0.0 (barely a match) to 1.0 (the query verbatim)
+///
+/// Skim scores grow with the query's length, so `score` is first divided by
+/// `best_score`, what the query scores against itself. That ratio stays high
+/// for letters scattered over word starts (`c_a_l_c` for `calc`), so it is
+/// combined with how compact the match is: `query_chars` over the chars
+/// spanned from the first matched char to the last. The geometric mean of the
+/// two keeps abbreviations (`usrmngr` for `UserManager`) around 0.7.
+pub fn fuzzy_similarity(score: i64, best_score: i64, query_chars: usize, span_chars: usize) -> f64 {
+    if best_score <= 0 || span_chars == 0 {
+        return 0.0;
+    }
+    let score_ratio = (score as f64 / best_score as f64).clamp(0.0, 1.0);
+    let compactness = (query_chars as f64 / span_chars as f64).clamp(0.0, 1.0);
+    (score_ratio * compactness).sqrt()
+}
+
 /// Check if a line should be included based on filters (pure function)
 pub fn should_include_line(
     line: &str,
@@ -194,6 +212,16 @@ mod tests {
         assert!(quality > 0.0 && quality <= 1.0);
     }
 
+    #[test]
+    fn test_fuzzy_similarity() {
+        assert_eq!(fuzzy_similarity(51, 51, 2, 2), 1.0);
+        // Scores above the query's own are capped
+        assert_eq!(fuzzy_similarity(60, 51, 2, 2), 1.0);
+        assert!((fuzzy_similarity(50, 100, 4, 8) - 0.5).abs() < 1e-9);
+        assert_eq!(fuzzy_similarity(10, 0, 2, 2), 0.0);
+        assert_eq!(fuzzy_similarity(10, 10, 2, 0), 0.0);
+    }
+
     #[test]
     fn test_should_include_line() {
         assert!(should_include_line("test line", 5, 100, &[]));
@@ -0,0 +1,502 @@
+//! Typed Queries
+//!
+//! Maps the loose search flags (`--fuzzy`, `--semantic`, `--literal`, ...) onto a
+//! single [`Query`], rejecting combinations that have no sensible meaning, and
+//! plans the pattern the engine actually compiles.
+
+use crate::errors::SearchError;
+use crate::types::{RegexEngine, SearchOptions};
+use super::pattern::{compile_pattern, PatternError, PatternMatcher};
+use super::semantic::{expand_query, QueryExpansion, SynonymTable};
+
+/// How the query text is interpreted
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QueryKind {
+    /// The query is a regular expression
+    Regex,
+    /// The query is matched as plain text
+    Literal,
+    /// Lines are scored against the query; those at or above `threshold` match
+    Fuzzy { threshold: f64 },
+    /// Query words with a synonym group are expanded before matching
+    Semantic,
+}
+
+impl QueryKind {
+    /// Lowercase name, as accepted by the MCP `kind` parameter
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Regex => "regex",
+            Self::Literal => "literal",
+            Self::Fuzzy { .. } => "fuzzy",
+            Self::Semantic => "semantic",
+        }
+    }
+}
+
+/// Whether letter case matters when matching
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CaseMode {
+    #[default]
+    Sensitive,
+    Insensitive,
+}
+
+/// A validated search query
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    pub pattern: String,
+    pub kind: QueryKind,
+    pub case: CaseMode,
+    /// Only match the query as a whole word
+    pub word_boundary: bool,
+    /// Match against whole files: `.` matches newlines and `^`/`$` match at line breaks
+    pub multiline: bool,
+}
+
+/// The pattern a [`Query`] compiles to, and how it was derived
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryPlan {
+    pub query: Query,
+    /// Applied synonym groups, for semantic queries
+    pub expansion: Option<QueryExpansion>,
+    pub effective_pattern: String,
+    /// Each top-level alternative of a regex query, planned like the whole
+    /// pattern; empty unless there are at least two
+    pub branch_patterns: Vec<String>,
+}
+
+fn conflict(message: &str) -> SearchError {
+    SearchError::InvalidOptions { message: message.to_string() }
+}
+
+impl Query {
+    /// A case-sensitive query without word boundaries
+    pub fn new(pattern: impl Into<String>, kind: QueryKind) -> Self {
+        Self {
+            pattern: pattern.into(),
+            kind,
+            case: CaseMode::Sensitive,
+            word_boundary: false,
+            multiline: false,
+        }
+    }
+
+    /// Map the search flags in `options` onto a query
+    ///
+    /// At most one of `fuzzy`, `semantic` and `literal` may be set; none selects a
+    /// regex query. Word boundaries can't be combined with fuzzy matching, which
+    /// scores whole lines rather than matching a span.
+    pub fn from_options(pattern: &str, options: &SearchOptions) -> Result<Self, SearchError> {
+        let kind = match (options.fuzzy, options.semantic, options.literal) {
+            (false, false, false) => QueryKind::Regex,
+            (true, false, false) => QueryKind::Fuzzy { threshold: options.fuzzy_threshold },
+            (false, true, false) => QueryKind::Semantic,
+            (false, false, true) => QueryKind::Literal,
+            (true, true, _) => {
+                return Err(conflict("--fuzzy and --semantic can't be combined: semantic expansion produces a regex, which fuzzy matching would treat as text"));
+            }
+            (true, false, true) => {
+                return Err(conflict("--fuzzy and --literal can't be combined: fuzzy search already treats the query as text"));
+            }
+            (false, true, true) => {
+                return Err(conflict("--semantic and --literal can't be combined: semantic expansion produces a regex"));
+            }
+        };
+        if let QueryKind::Fuzzy { threshold } = kind
+            && !(0.0..=1.0).contains(&threshold)
+        {
+            return Err(SearchError::InvalidOptions {
+                message: format!("--fuzzy-threshold must be between 0.0 (any match) and 1.0 (the query verbatim), got {threshold}"),
+            });
+        }
+        if options.word_boundary && matches!(kind, QueryKind::Fuzzy { .. }) {
+            return Err(conflict("--word and --fuzzy can't be combined: fuzzy search scores whole lines"));
+        }
+        if options.multiline && matches!(kind, QueryKind::Fuzzy { .. }) {
+            return Err(conflict("--multiline and --fuzzy can't be combined: fuzzy search scores whole lines"));
+        }
+
+        Ok(Self {
+            pattern: pattern.to_string(),
+            kind,
+            case: if options.ignore_case { CaseMode::Insensitive } else { CaseMode::Sensitive },
+            word_boundary: options.word_boundary,
+            multiline: options.multiline,
+        })
+    }
+
+    /// Score threshold for fuzzy queries
+    pub fn fuzzy_threshold(&self) -> Option<f64> {
+        match self.kind {
+            QueryKind::Fuzzy { threshold } => Some(threshold),
+            _ => None,
+        }
+    }
+
+    /// Text that every match must contain, used to rule files out without reading them
+    ///
+    /// Fuzzy and semantic queries have none. For regexes this is the longest run
+    /// of plain characters that can't be skipped; patterns with alternation or
+    /// groups aren't analyzed. Case-insensitive queries only qualify when the
+    /// text is ASCII, since the index folds ASCII case only.
+    pub fn required_literal(&self) -> Option<String> {
+        let literal = match self.kind {
+            QueryKind::Literal => self.pattern.clone(),
+            QueryKind::Regex => regex_required_literal(&self.pattern)?,
+            QueryKind::Fuzzy { .. } | QueryKind::Semantic => return None,
+        };
+        if self.case == CaseMode::Insensitive && !literal.is_ascii() {
+            return None;
+        }
+        Some(literal)
+    }
+
+    /// Derive the pattern to compile, expanding semantic queries with `synonyms`
+    pub fn plan(&self, synonyms: &SynonymTable) -> QueryPlan {
+        let (body, expansion) = match self.kind {
+            QueryKind::Regex => (self.pattern.clone(), None),
+            QueryKind::Literal => (regex::escape(&self.pattern), None),
+            QueryKind::Fuzzy { .. } => (format!(".*{}.*", regex::escape(&self.pattern)), None),
+            QueryKind::Semantic => {
+                let expansion = expand_query(&self.pattern, synonyms);
+                (expansion.pattern.clone(), Some(expansion))
+            }
+        };
+        let branch_patterns = match self.kind {
+            QueryKind::Regex => {
+                let branches = top_level_branches(&self.pattern);
+                if branches.len() > 1 { branches.iter().map(|branch| self.wrap(branch)).collect() } else { Vec::new() }
+            }
+            _ => Vec::new(),
+        };
+
+        QueryPlan {
+            query: self.clone(),
+            expansion,
+            effective_pattern: self.wrap(&body),
+            branch_patterns,
+        }
+    }
+
+    /// `body` with the word boundaries and inline flags the query asks for
+    fn wrap(&self, body: &str) -> String {
+        let body = if self.word_boundary { format!(r"\b(?:{body})\b") } else { body.to_string() };
+        let flags = match (self.case, self.multiline) {
+            (CaseMode::Insensitive, true) => "(?ims)",
+            (CaseMode::Insensitive, false) => "(?i)",
+            (CaseMode::Sensitive, true) => "(?ms)",
+            (CaseMode::Sensitive, false) => "",
+        };
+        format!("{flags}{body}")
+    }
+}
+
+impl QueryPlan {
+    pub fn compile(&self, engine: RegexEngine) -> Result<PatternMatcher, PatternError> {
+        Ok(compile_pattern(&self.effective_pattern, engine)?.with_branches(&self.branch_patterns, engine))
+    }
+
+    /// Human-readable lines for `--explain`
+    pub fn describe(&self) -> Vec<String> {
+        let kind = match self.query.kind {
+            QueryKind::Fuzzy { threshold } => format!("fuzzy (threshold {threshold})"),
+            kind => kind.name().to_string(),
+        };
+        let case = match self.query.case {
+            CaseMode::Sensitive => "sensitive",
+            CaseMode::Insensitive => "insensitive",
+        };
+        let mut lines = vec![
+            format!("Kind: {kind}"),
+            format!("Case: {case}"),
+            format!("Whole word: {}", if self.query.word_boundary { "yes" } else { "no" }),
+            format!("Multiline: {}", if self.query.multiline { "yes" } else { "no" }),
+        ];
+
+        match &self.expansion {
+            None => lines.push("Semantic expansion: off (enable with --semantic)".to_string()),
+            Some(expansion) if expansion.applied.is_empty() => {
+                lines.push("Semantic expansion: no synonym groups matched".to_string());
+            }
+            Some(expansion) => lines.extend(
+                expansion
+                    .applied
+                    .iter()
+                    .map(|applied| format!("Expanded '{}' -> {}", applied.term, applied.pattern)),
+            ),
+        }
+        lines.push(format!("Effective pattern: {}", self.effective_pattern));
+        lines
+    }
+}
+
+/// The top-level alternatives of `pattern`: `a|b(c|d)` is `a` and `b(c|d)`
+///
+/// Escapes, groups and character classes are skipped over. Inline flags that
+/// open the pattern, like `(?i)`, apply to every alternative, so they are
+/// repeated on each.
+fn top_level_branches(pattern: &str) -> Vec<String> {
+    let flags = pattern
+        .strip_prefix("(?")
+        .and_then(|rest| rest.find(')').filter(|&end| rest[..end].chars().all(|c| c.is_ascii_alphabetic() || c == '-')))
+        .map_or("", |end| &pattern[..end + 3]);
+    let body = &pattern[flags.len()..];
+
+    let mut branches = Vec::new();
+    let mut depth = 0usize;
+    // Classes nest, e.g. `[a[:digit:]]`
+    let mut class_depth = 0usize;
+    let mut branch_start = 0;
+    let mut chars = body.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '[' => {
+                class_depth += 1;
+                // A leading `]` (or `^]`) is a literal member of the class
+                chars.next_if(|&(_, c)| c == '^');
+                chars.next_if(|&(_, c)| c == ']');
+            }
+            ']' if class_depth > 0 => class_depth -= 1,
+            _ if class_depth > 0 => {}
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            '|' if depth == 0 => {
+                branches.push(format!("{flags}{}", &body[branch_start..i]));
+                branch_start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    branches.push(format!("{flags}{}", &body[branch_start..]));
+    branches
+}
+
+/// Longest run of characters every match of `pattern` contains, if it is simple enough to tell
+fn regex_required_literal(pattern: &str) -> Option<String> {
+    if pattern.contains('|') || pattern.contains('(') {
+        return None;
+    }
+
+    let mut runs: Vec<String> = Vec::new();
+    let mut run = String::new();
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next()? {
+                // Escapes with arguments, e.g. `\x41` or `\p{L}`
+                'x' | 'u' | 'U' | 'p' | 'P' => return None,
+                // Classes, anchors and control characters
+                e if e.is_ascii_alphanumeric() => runs.push(std::mem::take(&mut run)),
+                e => run.push(e),
+            },
+            // The previous character may not appear at all
+            '?' | '*' => {
+                run.pop();
+                runs.push(std::mem::take(&mut run));
+            }
+            '{' => {
+                run.pop();
+                runs.push(std::mem::take(&mut run));
+                chars.by_ref().find(|&c| c == '}')?;
+            }
+            '[' => {
+                runs.push(std::mem::take(&mut run));
+                let mut first = true;
+                loop {
+                    match chars.next()? {
+                        '\\' => {
+                            chars.next()?;
+                        }
+                        ']' if !first => break,
+                        _ => {}
+                    }
+                    first = false;
+                }
+            }
+            '+' | '.' | '^' | '$' => runs.push(std::mem::take(&mut run)),
+            c => run.push(c),
+        }
+    }
+    runs.push(run);
+    runs.into_iter().filter(|r| !r.is_empty()).max_by_key(|r| r.chars().count())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Copy)]
+    struct Flags {
+        fuzzy: bool,
+        semantic: bool,
+        literal: bool,
+        word: bool,
+        ignore_case: bool,
+    }
+
+    fn options(flags: Flags) -> SearchOptions {
+        SearchOptions {
+            fuzzy: flags.fuzzy,
+            fuzzy_threshold: 0.5,
+            semantic: flags.semantic,
+            literal: flags.literal,
+            word_boundary: flags.word,
+            ignore_case: flags.ignore_case,
+            ..SearchOptions::default()
+        }
+    }
+
+    #[test]
+    fn test_flag_mapping_table() {
+        // (fuzzy, semantic, literal, word) -> kind name, or the conflicting flags
+        type Row = (bool, bool, bool, bool, Result<&'static str, &'static str>);
+        let table: [Row; 16] = [
+            (false, false, false, false, Ok("regex")),
+            (false, false, false, true, Ok("regex")),
+            (true, false, false, false, Ok("fuzzy")),
+            (true, false, false, true, Err("--word and --fuzzy")),
+            (false, true, false, false, Ok("semantic")),
+            (false, true, false, true, Ok("semantic")),
+            (false, false, true, false, Ok("literal")),
+            (false, false, true, true, Ok("literal")),
+            (true, true, false, false, Err("--fuzzy and --semantic")),
+            (true, true, false, true, Err("--fuzzy and --semantic")),
+            (true, false, true, false, Err("--fuzzy and --literal")),
+            (true, false, true, true, Err("--fuzzy and --literal")),
+            (false, true, true, false, Err("--semantic and --literal")),
+            (false, true, true, true, Err("--semantic and --literal")),
+            (true, true, true, false, Err("--fuzzy and --semantic")),
+            (true, true, true, true, Err("--fuzzy and --semantic")),
+        ];
+
+        for (fuzzy, semantic, literal, word, expected) in table {
+            for ignore_case in [false, true] {
+                let flags = Flags { fuzzy, semantic, literal, word, ignore_case };
+                let mapped = Query::from_options("needle", &options(flags));
+                let context = format!("fuzzy={fuzzy} semantic={semantic} literal={literal} word={word} ignore_case={ignore_case}");
+                match (mapped, expected) {
+                    (Ok(query), Ok(kind)) => {
+                        assert_eq!(query.kind.name(), kind, "{context}");
+                        assert_eq!(query.word_boundary, word, "{context}");
+                        let case = if ignore_case { CaseMode::Insensitive } else { CaseMode::Sensitive };
+                        assert_eq!(query.case, case, "{context}");
+                    }
+                    (Err(err), Err(flags)) => assert!(err.to_string().contains(flags), "{context}: {err}"),
+                    (mapped, expected) => panic!("{context}: got {mapped:?}, expected {expected:?}"),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_fuzzy_keeps_threshold() {
+        let query = Query::from_options("needle", &SearchOptions::default().with_fuzzy(true).with_fuzzy_threshold(0.3)).unwrap();
+        assert_eq!(query.kind, QueryKind::Fuzzy { threshold: 0.3 });
+        assert_eq!(query.fuzzy_threshold(), Some(0.3));
+        assert_eq!(Query::new("needle", QueryKind::Regex).fuzzy_threshold(), None);
+    }
+
+    #[test]
+    fn test_fuzzy_threshold_out_of_range() {
+        for threshold in [-0.1, 1.5, 60.0, f64::NAN] {
+            let err = Query::from_options("needle", &SearchOptions::default().with_fuzzy(true).with_fuzzy_threshold(threshold)).unwrap_err();
+            assert!(err.to_string().contains("between 0.0 (any match) and 1.0"), "{threshold}: {err}");
+        }
+        // Only checked for fuzzy queries
+        assert!(Query::from_options("needle", &SearchOptions::default().with_fuzzy_threshold(60.0)).is_ok());
+    }
+
+    #[test]
+    fn test_effective_patterns() {
+        let synonyms = SynonymTable::builtin();
+        let plan = |query: Query| query.plan(&synonyms).effective_pattern;
+
+        assert_eq!(plan(Query::new("a.b", QueryKind::Regex)), "a.b");
+        assert_eq!(plan(Query::new("a.b", QueryKind::Literal)), r"a\.b");
+        assert_eq!(plan(Query::new("a.b", QueryKind::Fuzzy { threshold: 0.6 })), r".*a\.b.*");
+        assert_eq!(plan(Query::new("error", QueryKind::Semantic)), "(error|exception|panic|fail|throw)");
+
+        let mut query = Query::new("a.b", QueryKind::Literal);
+        query.case = CaseMode::Insensitive;
+        query.word_boundary = true;
+        assert_eq!(plan(query), r"(?i)\b(?:a\.b)\b");
+
+        let mut query = Query::new(r"fn new\(\)\s*\{", QueryKind::Regex);
+        query.multiline = true;
+        assert_eq!(plan(query), r"(?ms)fn new\(\)\s*\{");
+    }
+
+    #[test]
+    fn test_multiline_rejects_fuzzy() {
+        let options = SearchOptions::default().with_multiline(true);
+        assert!(Query::from_options("a.b", &options).unwrap().multiline);
+        let err = Query::from_options("a.b", &options.with_fuzzy(true)).unwrap_err();
+        assert!(err.to_string().contains("--multiline and --fuzzy"));
+    }
+
+    #[test]
+    fn test_literal_and_word_boundary_matching() {
+        let synonyms = SynonymTable::builtin();
+        let literal = Query::new("a.b", QueryKind::Literal).plan(&synonyms).compile(RegexEngine::Default).unwrap();
+        assert!(literal.is_match("x = a.b;"));
+        assert!(!literal.is_match("x = axb;"));
+
+        let mut word = Query::new("test", QueryKind::Regex);
+        word.word_boundary = true;
+        let word = word.plan(&synonyms).compile(RegexEngine::Default).unwrap();
+        assert!(word.is_match("fn test() {}"));
+        assert!(!word.is_match("fn testing() {}"));
+    }
+
+    #[test]
+    fn test_describe_mentions_kind_and_expansion() {
+        let synonyms = SynonymTable::builtin();
+        let lines = Query::new("error", QueryKind::Semantic).plan(&synonyms).describe();
+        assert!(lines.contains(&"Kind: semantic".to_string()));
+        assert!(lines.iter().any(|l| l.starts_with("Expanded 'error'")));
+
+        let lines = Query::new("x", QueryKind::Regex).plan(&synonyms).describe();
+        assert!(lines.iter().any(|l| l.contains("enable with --semantic")));
+        assert_eq!(lines.last().unwrap(), "Effective pattern: x");
+    }
+
+    #[test]
+    fn test_branch_patterns_split_top_level_alternation() {
+        assert_eq!(top_level_branches("TODO|FIXME"), vec!["TODO", "FIXME"]);
+        assert_eq!(top_level_branches(r"a(b|c)|d\|e|[|x]"), vec!["a(b|c)", r"d\|e", "[|x]"]);
+        assert_eq!(top_level_branches("[]|]|[^]|]|[a[:digit:]|]"), vec!["[]|]", "[^]|]", "[a[:digit:]|]"]);
+        assert_eq!(top_level_branches("(?i)a|b"), vec!["(?i)a", "(?i)b"]);
+        assert_eq!(top_level_branches("(?:a|b)"), vec!["(?:a|b)"]);
+
+        let mut query = Query::new("foo|bar", QueryKind::Regex);
+        query.case = CaseMode::Insensitive;
+        query.word_boundary = true;
+        let plan = query.plan(&SynonymTable::default());
+        assert_eq!(plan.branch_patterns, vec![r"(?i)\b(?:foo)\b", r"(?i)\b(?:bar)\b"]);
+        assert!(Query::new("foo", QueryKind::Regex).plan(&SynonymTable::default()).branch_patterns.is_empty());
+        assert!(Query::new("a|b", QueryKind::Literal).plan(&SynonymTable::default()).branch_patterns.is_empty());
+    }
+
+    #[test]
+    fn test_required_literal() {
+        let literal = |pattern: &str| Query::new(pattern, QueryKind::Regex).required_literal();
+        assert_eq!(literal("needle"), Some("needle".to_string()));
+        assert_eq!(literal(r"fn\s+main_loop"), Some("main_loop".to_string()));
+        assert_eq!(literal(r"colou?r_value"), Some("r_value".to_string()));
+        assert_eq!(literal(r"[a-z]+_handler\.rs"), Some("_handler.rs".to_string()));
+        assert_eq!(literal(r"ab{2,3}cdef"), Some("cdef".to_string()));
+        assert_eq!(literal("foo|bar"), None);
+        assert_eq!(literal("(?i)needle"), None);
+        assert_eq!(literal(r"\x41BC"), None);
+
+        assert_eq!(Query::new("a.b(", QueryKind::Literal).required_literal(), Some("a.b(".to_string()));
+        assert_eq!(Query::new("needle", QueryKind::Fuzzy { threshold: 0.6 }).required_literal(), None);
+        let mut insensitive = Query::new("café", QueryKind::Literal);
+        insensitive.case = CaseMode::Insensitive;
+        assert_eq!(insensitive.required_literal(), None);
+    }
+}
@@ -2,32 +2,217 @@
 //!
 //! Enhances search queries with semantic patterns for better code understanding.
 
+use crate::config::SemanticConfig;
+use regex::Regex;
+
+/// Built-in concept patterns, keyed by the query word that triggers them
+const BUILTIN_PATTERNS: [(&str, &str); 15] = [
+    ("function", r"(function|def|fn|func|method|procedure)"),
+    ("class", r"(class|struct|interface|trait|type)"),
+    ("variable", r"(let|var|const|val|mut)"),
+    ("loop", r"(for|while|do|foreach|map|filter)"),
+    ("condition", r"(if|else|switch|case|when|match)"),
+    ("error", r"(error|exception|panic|fail|throw)"),
+    ("test", r"(test|spec|it|describe|assert)"),
+    ("import", r"(import|use|require|include|from)"),
+    ("return", r"(return|yield|emit)"),
+    ("async", r"(async|await|promise|future)"),
+    ("comment", r"(//|#|/\*)"),
+    ("string", r#"(".*"|'.*'|`.*`)"#),
+    ("number", r"\d+"),
+    ("boolean", r"(true|false|True|False|TRUE|FALSE)"),
+    ("null", r"(null|nil|None|undefined)"),
+];
+
+/// Synonym groups used for semantic query expansion
+///
+/// Each entry maps a lowercase trigger word to the regex it expands to.
+#[derive(Debug, Clone, Default)]
+pub struct SynonymTable {
+    entries: Vec<(String, String)>,
+}
+
+/// A single word of the query that was expanded
+#[derive(Debug, Clone, PartialEq)]
+pub struct AppliedExpansion {
+    pub term: String,
+    pub pattern: String,
+}
+
+/// Result of expanding a query
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryExpansion {
+    pub pattern: String,
+    pub applied: Vec<AppliedExpansion>,
+}
+
+impl SynonymTable {
+    /// The built-in concept table
+    pub fn builtin() -> Self {
+        Self {
+            entries: BUILTIN_PATTERNS
+                .iter()
+                .map(|(k, p)| (k.to_string(), p.to_string()))
+                .collect(),
+        }
+    }
+
+    /// Build the table from config: custom groups merged over the built-ins
+    ///
+    /// A custom group with the same trigger word as a built-in replaces it.
+    pub fn from_config(config: &SemanticConfig) -> Self {
+        let mut table = if config.disable_builtins {
+            Self::default()
+        } else {
+            Self::builtin()
+        };
+
+        let mut custom: Vec<_> = config.synonyms.iter().collect();
+        custom.sort_by(|a, b| a.0.cmp(b.0));
+        for (term, synonyms) in custom {
+            table.insert_group(term, synonyms);
+        }
+        table
+    }
+
+    /// Add a synonym group, replacing any existing entry for `term`
+    pub fn insert_group(&mut self, term: &str, synonyms: &[String]) {
+        let term = term.to_lowercase();
+        let alternatives: Vec<String> = std::iter::once(term.as_str())
+            .chain(synonyms.iter().map(|s| s.as_str()))
+            .map(regex::escape)
+            .collect();
+        let pattern = format!("({})", alternatives.join("|"));
+
+        match self.entries.iter_mut().find(|(k, _)| *k == term) {
+            Some(entry) => entry.1 = pattern,
+            None => self.entries.push((term, pattern)),
+        }
+    }
+
+    /// Pattern for a trigger word, if present
+    pub fn get(&self, term: &str) -> Option<&str> {
+        let term = term.to_lowercase();
+        self.entries
+            .iter()
+            .find(|(k, _)| *k == term)
+            .map(|(_, p)| p.as_str())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Expand every whole word of `query` that has a synonym group
+///
+/// Words are matched case-insensitively and only as whole words, so `testing`
+/// does not trigger the `test` group. Expanded text is not expanded again.
+pub fn expand_query(query: &str, table: &SynonymTable) -> QueryExpansion {
+    let word_re = Regex::new(r"\b\w+\b").expect("valid word regex");
+    let mut pattern = String::with_capacity(query.len());
+    let mut applied = Vec::new();
+    let mut last = 0;
+
+    for word in word_re.find_iter(query) {
+        if let Some(expansion) = table.get(word.as_str()) {
+            pattern.push_str(&query[last..word.start()]);
+            pattern.push_str(expansion);
+            last = word.end();
+            applied.push(AppliedExpansion {
+                term: word.as_str().to_string(),
+                pattern: expansion.to_string(),
+            });
+        }
+    }
+    pattern.push_str(&query[last..]);
+
+    QueryExpansion { pattern, applied }
+}
+
 /// Enhance a query with semantic patterns
 pub fn enhance_query_semantically(query: &str) -> String {
-    let semantic_patterns = [
-        ("function", r"(function|def|fn|func|method|procedure)"),
-        ("class", r"(class|struct|interface|trait|type)"),
-        ("variable", r"(let|var|const|val|mut)"),
-        ("loop", r"(for|while|do|foreach|map|filter)"),
-        ("condition", r"(if|else|switch|case|when|match)"),
-        ("error", r"(error|exception|panic|fail|throw)"),
-        ("test", r"(test|spec|it|describe|assert)"),
-        ("import", r"(import|use|require|include|from)"),
-        ("return", r"(return|yield|emit)"),
-        ("async", r"(async|await|promise|future)"),
-        ("comment", r"(//|#|/\*)"),
-        ("string", r#"(".*"|'.*'|`.*`)"#),
-        ("number", r"\d+"),
-        ("boolean", r"(true|false|True|False|TRUE|FALSE)"),
-        ("null", r"(null|nil|None|undefined)"),
-    ];
-
-    let query_lower = query.to_lowercase();
-    for (keyword, pattern) in &semantic_patterns {
-        if query_lower.contains(keyword) {
-            return pattern.to_string();
+    expand_query(query, &SynonymTable::builtin()).pattern
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn config(synonyms: &[(&str, &[&str])], disable_builtins: bool) -> SemanticConfig {
+        SemanticConfig {
+            disable_builtins,
+            synonyms: synonyms
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.iter().map(|s| s.to_string()).collect()))
+                .collect::<HashMap<_, _>>(),
+            model: None,
         }
     }
 
-    query.to_string()
+    #[test]
+    fn test_custom_groups_are_merged_with_builtins() {
+        let table = SynonymTable::from_config(&config(&[("order", &["booking", "purchase"])], false));
+
+        let expansion = expand_query("order", &table);
+        assert_eq!(expansion.pattern, "(order|booking|purchase)");
+
+        let expansion = expand_query("function", &table);
+        assert!(expansion.pattern.contains("fn"));
+    }
+
+    #[test]
+    fn test_expands_multiple_words_and_keeps_the_rest() {
+        let table = SynonymTable::from_config(&config(
+            &[("order", &["booking"]), ("user", &["account", "member"])],
+            true,
+        ));
+
+        let expansion = expand_query("User places order now", &table);
+        assert_eq!(expansion.pattern, "(user|account|member) places (order|booking) now");
+        assert_eq!(expansion.applied.len(), 2);
+        assert_eq!(expansion.applied[0].term, "User");
+    }
+
+    #[test]
+    fn test_whole_word_matching_only() {
+        let table = SynonymTable::builtin();
+        assert_eq!(expand_query("testing", &table).pattern, "testing");
+        assert_eq!(expand_query("reorder_list", &table).pattern, "reorder_list");
+        assert!(expand_query("testing", &table).applied.is_empty());
+    }
+
+    #[test]
+    fn test_custom_group_overrides_overlapping_builtin() {
+        let table = SynonymTable::from_config(&config(&[("error", &["fault"])], false));
+        assert_eq!(expand_query("error", &table).pattern, "(error|fault)");
+    }
+
+    #[test]
+    fn test_overlapping_terms_are_not_expanded_twice() {
+        let table = SynonymTable::from_config(&config(
+            &[("order", &["purchase"]), ("purchase", &["buy"])],
+            true,
+        ));
+        let expansion = expand_query("order", &table);
+        assert_eq!(expansion.pattern, "(order|purchase)");
+        assert_eq!(expansion.applied.len(), 1);
+    }
+
+    #[test]
+    fn test_disable_builtins() {
+        let table = SynonymTable::from_config(&config(&[("order", &["booking"])], true));
+        assert_eq!(expand_query("function", &table).pattern, "function");
+        assert!(table.get("class").is_none());
+
+        let empty = SynonymTable::from_config(&config(&[], true));
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn test_synonyms_are_regex_escaped() {
+        let table = SynonymTable::from_config(&config(&[("price", &["cost.usd"])], true));
+        assert_eq!(expand_query("price", &table).pattern, r"(price|cost\.usd)");
+    }
 }
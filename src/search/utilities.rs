@@ -2,12 +2,23 @@
 //!
 //! Helper functions for search operations including grep comparison and output formatting.
 
-use crate::types::{SearchMetrics, SearchResult};
+use crate::types::{GrepComparison, SearchMetrics, SearchOptions, SearchOutcome, SearchResult, SearchStats, ELLIPSIS};
+use super::layout;
+use super::pattern::PatternMatcher;
+use crate::theme::{self, ColorTheme, Glyph};
 use colored::*;
+use std::io::IsTerminal;
 use std::time::Instant;
 
-/// Compare search performance with grep
-pub fn compare_with_grep(query: &str, path: &str, extensions: Option<&[String]>, metrics: &SearchMetrics) {
+/// Run the same search with `grep -rn` and time it
+///
+/// Returns `None` when grep can't be run.
+pub fn grep_comparison(
+    query: &str,
+    path: &str,
+    extensions: Option<&[String]>,
+    metrics: &SearchMetrics,
+) -> Option<GrepComparison> {
     use std::process::Command;
 
     let start_time = Instant::now();
@@ -21,83 +32,523 @@ pub fn compare_with_grep(query: &str, path: &str, extensions: Option<&[String]>,
         }
     }
     
-    let output = grep_cmd.output();
+    let output = grep_cmd.output().ok()?;
     let grep_time = start_time.elapsed();
-    
-    println!("\n{}", "Grep Comparison:".cyan().bold());
-    println!("  codesearch time: {}ms", metrics.search_time_ms);
-    println!("  grep time: {}ms", grep_time.as_millis());
-    
-    if let Ok(result) = output {
-        let grep_matches = String::from_utf8_lossy(&result.stdout).lines().count();
-        println!("  codesearch files: {}", metrics.files_processed);
-        println!("  grep matches: {grep_matches}");
-        
-        let speedup = grep_time.as_millis() as f64 / metrics.search_time_ms as f64;
-        if speedup > 1.0 {
-            println!("  {} {:.2}x faster than grep", "codesearch is".green().bold(), speedup);
-        } else if speedup < 1.0 {
-            println!("  {} {:.2}x slower than grep", "codesearch is".yellow(), 1.0 / speedup);
-        } else {
-            println!("  Similar performance");
-        }
+    let grep_matches = String::from_utf8_lossy(&output.stdout).lines().count();
+
+    Some(GrepComparison {
+        grep_time_ms: grep_time.as_millis(),
+        grep_matches,
+        speedup: grep_time.as_millis() as f64 / metrics.search_time_ms.max(1) as f64,
+    })
+}
+
+/// Compare search performance with grep, printing the comparison to stderr
+pub fn compare_with_grep(query: &str, path: &str, extensions: Option<&[String]>, metrics: &SearchMetrics) {
+    match grep_comparison(query, path, extensions, metrics) {
+        Some(comparison) => print_grep_comparison(&comparison, metrics),
+        None => eprintln!("{}", "Grep comparison unavailable: could not run grep".yellow()),
+    }
+}
+
+/// Print a grep comparison to stderr
+pub fn print_grep_comparison(comparison: &GrepComparison, metrics: &SearchMetrics) {
+    eprintln!("\n{}", "Grep Comparison:".cyan().bold());
+    eprintln!("  codesearch time: {}ms", metrics.search_time_ms);
+    eprintln!("  grep time: {}ms", comparison.grep_time_ms);
+    eprintln!("  codesearch files: {}", metrics.files_processed);
+    eprintln!("  grep matches: {}", comparison.grep_matches);
+
+    let speedup = comparison.speedup;
+    if speedup > 1.0 {
+        eprintln!("  {} {:.2}x faster than grep", "codesearch is".green().bold(), speedup);
+    } else if speedup < 1.0 {
+        eprintln!("  {} {:.2}x slower than grep", "codesearch is".yellow(), 1.0 / speedup);
+    } else {
+        eprintln!("  Similar performance");
     }
 }
 
+/// Print `--benchmark` metrics to stderr
+pub fn print_search_metrics(metrics: &SearchMetrics, cache: bool) {
+    if metrics.cache_hits > 0 && metrics.files_processed == 0 {
+        eprintln!("{}", "Cache hit! Returning cached results instantly.".green().bold());
+    }
+    eprintln!("\n{}", "Performance Metrics:".cyan().bold());
+    eprintln!("  Files searched: {}", metrics.files_processed);
+    if metrics.files_ruled_out_by_index > 0 {
+        eprintln!("  Files ruled out by index: {}", metrics.files_ruled_out_by_index);
+    }
+    eprintln!("  Search time: {}ms", metrics.search_time_ms);
+    eprintln!("  Parallel workers: {}", metrics.parallel_workers);
+    if cache {
+        eprintln!("  Cache hits: {}", metrics.cache_hits);
+        eprintln!("  Cache misses: {}", metrics.cache_misses);
+    }
+}
+
+/// Whether to print non-result output (stats, banners, notices) to stderr
+///
+/// It is shown when stdout is a terminal, or anywhere when `--stats` was given,
+/// so piped output stays clean by default.
+pub fn chrome_enabled(stats_requested: bool) -> bool {
+    stats_requested || std::io::stdout().is_terminal()
+}
+
 /// Print search results with optional line numbers and ranking
 pub fn print_results(results: &[SearchResult], show_line_numbers: bool, show_ranking: bool) {
+    print_results_annotated(results, show_line_numbers, show_ranking, |_| None);
+}
+
+/// Print search results, appending the annotation returned for each line (e.g. a bookmark label)
+pub fn print_results_annotated<F>(
+    results: &[SearchResult],
+    show_line_numbers: bool,
+    show_ranking: bool,
+    annotate: F,
+) where
+    F: Fn(&SearchResult) -> Option<String>,
+{
     if results.is_empty() {
         return;
     }
 
+    let color = colored::control::SHOULD_COLORIZE.should_colorize();
+    let fit = layout::output_width().map(|width| Fit::new(width, results, show_line_numbers));
+    let number_width = fit.as_ref().map_or(0, |fit| fit.number_width);
     let mut current_file = String::new();
+    // Last line printed in the current file, to separate non-adjacent context groups
+    let mut last_printed: Option<usize> = None;
     
     for result in results {
         if result.file != current_file {
             current_file = result.file.clone();
-            println!("\n{}", current_file.green().bold());
+            last_printed = None;
+            println!("\n{}", theme::link(current_file.green().bold(), &current_file, None));
+        }
+
+        let has_context = !result.context_before.is_empty() || !result.context_after.is_empty();
+        let first_line = result.line_number.saturating_sub(result.context_before.len());
+        if has_context && last_printed.is_some_and(|last| first_line > last + 1) {
+            println!("  {}", "--".dimmed());
+        }
+        for (offset, line) in result.context_before.iter().enumerate() {
+            print_context_line(first_line + offset, line, show_line_numbers, fit.as_ref());
         }
-        
-        let line_prefix = if show_line_numbers {
-            format!("{}:", result.line_number).blue().to_string()
-        } else {
-            String::new()
-        };
         
         let ranking_suffix = if show_ranking {
-            format!(" [score: {:.1}]", result.score).yellow().to_string()
+            format!(" [score: {:.1}]", result.score)
         } else {
             String::new()
         };
-        
-        let mut highlighted_content = result.content.clone();
-        for mat in result.matches.iter().rev() {
-            if mat.end <= highlighted_content.len() {
-                let before = &highlighted_content[..mat.start];
-                let matched = &highlighted_content[mat.start..mat.end];
-                let after = &highlighted_content[mat.end..];
-                highlighted_content = format!("{}{}{}", before, matched.red().bold(), after);
+        let processed = annotation_suffix(result);
+        let annotation = annotate(result)
+            .map(|note| format!(" {} {note}", theme::glyph(Glyph::Bookmark)))
+            .unwrap_or_default();
+        let suffix_width = [&ranking_suffix, &processed, &annotation].iter().map(|s| s.chars().count()).sum();
+        let suffix = format!(
+            "{}{}{}",
+            if ranking_suffix.is_empty() { ranking_suffix } else { ranking_suffix.yellow().to_string() },
+            if processed.is_empty() { processed } else { processed.dimmed().to_string() },
+            if annotation.is_empty() { annotation } else { annotation.magenta().to_string() }
+        );
+
+        // Lines after the first belong to a multiline match
+        let content_lines = match &fit {
+            Some(fit) => fit.content_rows(result, show_line_numbers, suffix_width, color),
+            None => highlight_result(result, theme::color_theme(), color).split('\n').map(|line| vec![line.to_string()]).collect(),
+        };
+        for (offset, rows) in content_lines.iter().enumerate() {
+            let line_number = result.line_number + offset;
+            for (row_index, row) in rows.iter().enumerate() {
+                let prefix = match (show_line_numbers, row_index) {
+                    (false, _) => String::new(),
+                    (true, 0) if offset == 0 => theme::link(
+                        format!("{line_number:>number_width$}:").blue(),
+                        &result.file,
+                        Some(line_number),
+                    ),
+                    (true, 0) => format!("{line_number:>number_width$}:").blue().to_string(),
+                    // Wrapped rows hang under the content
+                    (true, _) => " ".repeat(number_width + 1),
+                };
+                let suffix = if offset == 0 && row_index + 1 == rows.len() { suffix.as_str() } else { "" };
+                println!("  {prefix}{row}{suffix}");
             }
         }
-        
-        println!("  {line_prefix}{highlighted_content}{ranking_suffix}");
+
+        let last_line = result.end_line_number.unwrap_or(result.line_number);
+        for (offset, line) in result.context_after.iter().enumerate() {
+            print_context_line(last_line + 1 + offset, line, show_line_numbers, fit.as_ref());
+        }
+        last_printed = Some(last_line + result.context_after.len());
+    }
+}
+
+/// How result lines are fitted to the terminal (see [`layout`])
+struct Fit {
+    width: usize,
+    wrap: bool,
+    /// Digits of the largest line number printed, so the gutter has one width
+    number_width: usize,
+    ellipsis: &'static str,
+}
+
+impl Fit {
+    fn new(width: usize, results: &[SearchResult], show_line_numbers: bool) -> Self {
+        let last_line = results
+            .iter()
+            .map(|r| r.end_line_number.unwrap_or(r.line_number) + r.context_after.len())
+            .max()
+            .unwrap_or(0);
+        Self {
+            width,
+            wrap: layout::wrap_enabled(),
+            number_width: if show_line_numbers { last_line.to_string().len() } else { 0 },
+            ellipsis: if theme::is_ascii() { "..." } else { ELLIPSIS },
+        }
+    }
+
+    /// Columns before the content: the indent, then the line number and its `:` or `-`
+    fn prefix_width(&self, show_line_numbers: bool) -> usize {
+        2 + if show_line_numbers { self.number_width + 1 } else { 0 }
+    }
+
+    /// Rows of each line of a result's content, highlighted; the first line leaves `suffix_width` columns free
+    fn content_rows(&self, result: &SearchResult, show_line_numbers: bool, suffix_width: usize, color: bool) -> Vec<Vec<String>> {
+        let spans: Vec<(usize, usize, usize)> = result
+            .matches
+            .iter()
+            .filter_map(|m| result.content_range(m).map(|(start, end)| (start, end, m.pattern_index)))
+            .collect();
+        let theme = theme::color_theme();
+        let mut base = 0;
+        let mut lines = Vec::new();
+        for (index, line) in result.content.split('\n').enumerate() {
+            let line_spans: Vec<(usize, usize, usize)> = spans
+                .iter()
+                .filter(|&&(start, end, _)| start < base + line.len() && end > base)
+                .map(|&(start, end, tag)| (start.max(base) - base, end.min(base + line.len()) - base, tag))
+                .collect();
+            let reserved = if index == 0 { suffix_width } else { 0 };
+            lines.push(self.rows(line, &line_spans, show_line_numbers, reserved, |part, tag| theme.paint_match(part, tag, color)));
+            base += line.len() + 1;
+        }
+        lines
+    }
+
+    /// `line` cut or wrapped into rows, with `paint` applied to the spans in each
+    fn rows(
+        &self,
+        line: &str,
+        spans: &[(usize, usize, usize)],
+        show_line_numbers: bool,
+        reserved: usize,
+        paint: impl Fn(&str, usize) -> String,
+    ) -> Vec<String> {
+        let (line, spans) = layout::expand_tabs(line, spans);
+        let bounds: Vec<(usize, usize)> = spans.iter().map(|&(start, end, _)| (start, end)).collect();
+        let width = self.width.saturating_sub(reserved);
+        let rows = layout::layout_line(self.prefix_width(show_line_numbers), &line, &bounds, width, self.wrap, self.ellipsis.chars().count());
+        rows.iter()
+            .map(|row| {
+                let row_spans: Vec<(usize, usize, usize)> = spans
+                    .iter()
+                    .filter(|&&(start, end, _)| start < row.end && end > row.start)
+                    .map(|&(start, end, tag)| (start.max(row.start) - row.start, end.min(row.end) - row.start, tag))
+                    .collect();
+                let text = highlight_tagged_ranges(&line[row.start..row.end], &row_spans, &paint);
+                let before = if row.cut_before { self.ellipsis.dimmed().to_string() } else { String::new() };
+                let after = if row.cut_after { self.ellipsis.dimmed().to_string() } else { String::new() };
+                format!("{before}{text}{after}")
+            })
+            .collect()
+    }
+}
+
+/// A result's [`annotations`](SearchResult::annotations) as ` [key: value]` pairs, in key order
+///
+/// Empty when there are none; the text output prints it dimmed after the line.
+pub fn annotation_suffix(result: &SearchResult) -> String {
+    result.annotations.iter().map(|(key, value)| format!(" [{key}: {value}]")).collect()
+}
+
+/// `content` with each byte range passed through `paint`
+///
+/// Ranges are byte offsets into `content` itself and may come in any order;
+/// overlapping or touching ones are painted as one. Each line of a multiline
+/// range is painted separately so the gutter stays uncolored. If any range is
+/// out of bounds or splits a character, `content` is returned unpainted.
+pub fn highlight_ranges(content: &str, ranges: &[(usize, usize)], paint: impl Fn(&str) -> String) -> String {
+    let tagged: Vec<(usize, usize, usize)> = ranges.iter().map(|&(start, end)| (start, end, 0)).collect();
+    highlight_tagged_ranges(content, &tagged, |part, _| paint(part))
+}
+
+/// Like [`highlight_ranges`], with a tag on each range that `paint` gets along with the text
+///
+/// Touching ranges are only painted as one when their tags are equal;
+/// overlapping ones always are, with the tag of the one starting first.
+pub fn highlight_tagged_ranges(
+    content: &str,
+    ranges: &[(usize, usize, usize)],
+    paint: impl Fn(&str, usize) -> String,
+) -> String {
+    if ranges.iter().any(|&(start, end, _)| start > end || content.get(start..end).is_none()) {
+        return content.to_string();
+    }
+    let mut ranges: Vec<(usize, usize, usize)> = ranges.iter().copied().filter(|&(start, end, _)| start < end).collect();
+    ranges.sort_unstable();
+    let mut merged: Vec<(usize, usize, usize)> = Vec::with_capacity(ranges.len());
+    for (start, end, tag) in ranges {
+        match merged.last_mut() {
+            Some(last) if start < last.1 || (start == last.1 && tag == last.2) => last.1 = last.1.max(end),
+            _ => merged.push((start, end, tag)),
+        }
+    }
+
+    let mut out = String::with_capacity(content.len());
+    let mut copied = 0;
+    for (start, end, tag) in merged {
+        out.push_str(&content[copied..start]);
+        let parts: Vec<String> = content[start..end].split('\n').map(|part| paint(part, tag)).collect();
+        out.push_str(&parts.join("\n"));
+        copied = end;
+    }
+    out.push_str(&content[copied..]);
+    out
+}
+
+/// A result's content with each match highlighted in its alternative's `theme` color
+///
+/// With `color` false the content comes back as is.
+pub fn highlight_result(result: &SearchResult, theme: ColorTheme, color: bool) -> String {
+    let ranges: Vec<(usize, usize, usize)> = result
+        .matches
+        .iter()
+        .filter_map(|m| result.content_range(m).map(|(start, end)| (start, end, m.pattern_index)))
+        .collect();
+    highlight_tagged_ranges(&result.content, &ranges, |part, index| theme.paint_match(part, index, color))
+}
+
+/// Print a context line dimmed, with `-` in the gutter where matches have `:`
+fn print_context_line(line_number: usize, line: &str, show_line_numbers: bool, fit: Option<&Fit>) {
+    let number_width = fit.map_or(0, |fit| fit.number_width);
+    let prefix = if show_line_numbers {
+        format!("{line_number:>number_width$}-").dimmed().to_string()
+    } else {
+        String::new()
+    };
+    match fit {
+        Some(fit) => {
+            for (index, row) in fit.rows(line, &[], show_line_numbers, 0, |part, _| part.to_string()).iter().enumerate() {
+                let prefix = match (show_line_numbers, index) {
+                    (true, 0) => prefix.clone(),
+                    (true, _) => " ".repeat(number_width + 1),
+                    (false, _) => String::new(),
+                };
+                println!("  {prefix}{}", row.dimmed());
+            }
+        }
+        None => println!("  {prefix}{}", line.dimmed()),
+    }
+}
+
+/// Matched substrings of a result, in line order
+///
+/// With a `template` such as `$1`, each match is rewritten through the pattern's
+/// capture groups instead. Empty matches are skipped.
+pub fn only_matching_texts(result: &SearchResult, template: Option<(&PatternMatcher, &str)>) -> Vec<String> {
+    result
+        .matches
+        .iter()
+        .filter(|m| m.end > m.start)
+        .map(|m| match template {
+            Some((matcher, template)) => result
+                .content_range(m)
+                .and_then(|(start, _)| matcher.expand_at(&result.content, start, template))
+                .unwrap_or_else(|| m.text.clone()),
+            None => m.text.clone(),
+        })
+        .collect()
+}
+
+/// Print only the matched text, one match per line, like `grep -o`
+pub fn print_only_matching(
+    results: &[SearchResult],
+    show_filename: bool,
+    show_line_numbers: bool,
+    template: Option<(&PatternMatcher, &str)>,
+) {
+    for result in results {
+        let mut prefix = String::new();
+        if show_filename {
+            prefix.push_str(&format!("{}:", result.file.green()));
+        }
+        if show_line_numbers {
+            prefix.push_str(&format!("{}:", result.line_number.to_string().blue()));
+        }
+        for text in only_matching_texts(result, template) {
+            println!("{prefix}{}", text.red().bold());
+        }
     }
 }
 
-/// Print search statistics
+/// Print search statistics to stderr
 pub fn print_search_stats(results: &[SearchResult], query: &str) {
     if results.is_empty() {
         return;
     }
 
-    let total_files = results.iter().map(|r| &r.file).collect::<std::collections::HashSet<_>>().len();
-    let total_matches: usize = results.iter().map(|r| r.matches.len()).sum();
-    
-    println!("\n{}", "Search Statistics:".cyan().bold());
-    println!("  Query: {}", query.yellow());
-    println!("  Files with matches: {total_files}");
-    println!("  Total matches: {total_matches}");
-    
-    let avg_score: f64 = results.iter().map(|r| r.score).sum::<f64>() / results.len() as f64;
-    println!("  Average relevance score: {avg_score:.1}");
+    let stats = SearchStats::from_results(results);
+    eprintln!("\n{}", "Search Statistics:".cyan().bold());
+    eprintln!("  Query: {}", query.yellow());
+    eprintln!("  Files with matches: {}", stats.files_with_matches);
+    eprintln!("  Matching lines: {}", stats.total_matches);
+    eprintln!("  Total matches: {}", stats.total_occurrences);
+    eprintln!("  Average relevance score: {:.1}", stats.average_score);
+    let color = colored::control::SHOULD_COLORIZE.should_colorize();
+    for line in render_match_distribution(&stats, terminal_width(), color) {
+        eprintln!("  {line}");
+    }
+}
+
+/// Width of the terminal on stderr, from `COLUMNS`, or `None` if unknown
+fn terminal_width() -> Option<usize> {
+    if !std::io::stderr().is_terminal() {
+        return None;
+    }
+    std::env::var("COLUMNS").ok()?.trim().parse().ok().filter(|&w| w > 0)
+}
+
+/// Lines of the `--stats` match distribution, without leading indentation
+///
+/// Directory counts get a bar of `▇` blocks scaled to fit `width` (including two
+/// columns of indentation); with an unknown width or colors disabled only the
+/// numbers are shown.
+pub fn render_match_distribution(stats: &SearchStats, width: Option<usize>, color: bool) -> Vec<String> {
+    let mut lines = vec!["Files by matches per file:".to_string()];
+    for (label, files) in stats.files_by_match_count.buckets() {
+        lines.push(format!("  {label:<5} {files:>6} file{}", if files == 1 { "" } else { "s" }));
+    }
+    if stats.directories.is_empty() {
+        return lines;
+    }
+
+    lines.push("Matches by directory:".to_string());
+    let label_width = stats.directories.iter().map(|d| d.directory.chars().count()).max().unwrap_or(0);
+    let count_width = stats.directories.iter().map(|d| d.matches.to_string().len()).max().unwrap_or(1);
+    let most = stats.directories.iter().map(|d| d.matches).max().unwrap_or(0).max(1);
+    // Indentation, label, count and the spaces between them come before the bar
+    let bar_room = width
+        .filter(|_| color)
+        .map(|w| w.saturating_sub(2 + 2 + label_width + 1 + count_width + 1))
+        .filter(|&room| room > 0);
+
+    for dir in &stats.directories {
+        let mut line = format!("  {:<label_width$} {:>count_width$}", dir.directory, dir.matches);
+        if let Some(room) = bar_room {
+            let blocks = ((dir.matches as f64 / most as f64) * room as f64).round() as usize;
+            let blocks = if dir.matches > 0 { blocks.max(1) } else { 0 };
+            line.push_str(&format!(" {}", theme::glyph(Glyph::Bar).repeat(blocks).cyan()));
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// Print which result limits truncated the output, if any, to stderr
+pub fn print_limit_status(outcome: &SearchOutcome, options: &SearchOptions) {
+    let limit_reached = outcome.limit_reached;
+    if limit_reached.per_file {
+        eprintln!(
+            "  {} some files had more than {} matching lines (--max-results is per file)",
+            "Limit reached:".yellow(),
+            options.max_results
+        );
+    }
+    if let Some(max_total) = options.max_total.filter(|_| limit_reached.total) {
+        let dropped = if outcome.files_skipped {
+            format!("at least {} more not shown; remaining files were skipped", outcome.truncated)
+        } else {
+            format!("{} more not shown", outcome.truncated)
+        };
+        eprintln!(
+            "  {} output capped at {max_total} results (--max-total), {dropped}",
+            "Limit reached:".yellow()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{DirectoryMatches, MatchHistogram};
+
+    #[test]
+    fn test_highlight_ranges() {
+        let paint = |part: &str| format!("[{part}]");
+        assert_eq!(highlight_ranges("a total + total", &[(10, 15), (2, 7)], paint), "a [total] + [total]");
+        // Overlapping and touching ranges are painted once
+        assert_eq!(highlight_ranges("abcdef", &[(0, 3), (2, 4), (4, 5)], paint), "[abcde]f");
+        assert_eq!(highlight_ranges("abc", &[(1, 1)], paint), "abc");
+        // Ranges out of bounds or inside a character leave the line unpainted
+        assert_eq!(highlight_ranges("abc", &[(0, 1), (2, 9)], paint), "abc");
+        assert_eq!(highlight_ranges("// héllo wörld", &[(0, 2), (5, 9)], paint), "// héllo wörld");
+        assert_eq!(highlight_ranges("// héllo wörld", &[(3, 9), (10, 16)], paint), "// [héllo] [wörld]");
+        assert_eq!(highlight_ranges("日本語 🦀 crab", &[(10, 14), (0, 3)], paint), "[日]本語 [🦀] crab");
+        assert_eq!(highlight_ranges("fn a\nfn b", &[(3, 9)], paint), "fn [a]\n[fn b]");
+    }
+
+    #[test]
+    fn test_highlight_tagged_ranges() {
+        let paint = |part: &str, tag: usize| format!("[{tag}:{part}]");
+        assert_eq!(highlight_tagged_ranges("ab cd", &[(3, 5, 1), (0, 2, 0)], paint), "[0:ab] [1:cd]");
+        // Touching ranges only join when they come from the same pattern
+        assert_eq!(highlight_tagged_ranges("abcd", &[(0, 2, 0), (2, 4, 1)], paint), "[0:ab][1:cd]");
+        assert_eq!(highlight_tagged_ranges("abcd", &[(0, 2, 1), (2, 4, 1)], paint), "[1:abcd]");
+        assert_eq!(highlight_tagged_ranges("abcd", &[(0, 3, 2), (1, 4, 0)], paint), "[2:abcd]");
+    }
+
+    fn stats() -> SearchStats {
+        SearchStats {
+            files_with_matches: 6,
+            matching_lines: 40,
+            total_matches: 40,
+            total_occurrences: 70,
+            average_score: 50.0,
+            files_by_match_count: MatchHistogram { one: 3, two_to_five: 1, six_to_twenty: 2, over_twenty: 0 },
+            directories: vec![
+                DirectoryMatches { directory: "src".to_string(), matches: 60 },
+                DirectoryMatches { directory: "tests".to_string(), matches: 10 },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_distribution_without_width_or_color_is_plain_numbers() {
+        let expected = vec![
+            "Files by matches per file:",
+            "  1          3 files",
+            "  2-5        1 file",
+            "  6-20       2 files",
+            "  21+        0 files",
+            "Matches by directory:",
+            "  src   60",
+            "  tests 10",
+        ];
+        assert_eq!(render_match_distribution(&stats(), None, true), expected);
+        assert_eq!(render_match_distribution(&stats(), Some(80), false), expected);
+    }
+
+    #[test]
+    fn test_distribution_bars_scale_to_width() {
+        colored::control::set_override(false);
+        let lines = render_match_distribution(&stats(), Some(30), true);
+        colored::control::unset_override();
+
+        // 30 columns - 2 indent - 2 nesting - 5 label - 1 - 2 count - 1 = 17 blocks at most
+        assert_eq!(lines[6], format!("  src   60 {}", "▇".repeat(17)));
+        assert_eq!(lines[7], format!("  tests 10 {}", "▇".repeat(3)));
+        assert!(lines.iter().all(|l| 2 + l.chars().count() <= 30));
+    }
 }
@@ -2,8 +2,9 @@
 
 #[cfg(test)]
 mod edge_case_tests {
+    use crate::encoding::Encoding;
     use crate::search::{search_code, list_files};
-    use crate::types::SearchOptions;
+    use crate::types::{ContextLines, RegexEngine, SearchOptions, DEFAULT_MULTILINE_MAX_BYTES};
     use std::path::PathBuf;
 
     fn default_options() -> SearchOptions {
@@ -12,13 +13,25 @@ mod edge_case_tests {
             ignore_case: false,
             fuzzy: false,
             fuzzy_threshold: 0.8,
+            literal: false,
+            word_boundary: false,
             max_results: 10,
+            max_total: None,
             exclude: None,
+            gitignore: true,
             rank: false,
             cache: false,
             semantic: false,
             benchmark: false,
             vs_grep: false,
+            engine: RegexEngine::Default,
+            context: ContextLines::default(),
+            multiline: false,
+            multiline_max_bytes: DEFAULT_MULTILINE_MAX_BYTES,
+            encoding: Encoding::Auto,
+            index: None,
+            score_weights: Default::default(),
+            post_processors: Vec::new(),
         }
     }
 
@@ -177,4 +190,782 @@ mod edge_case_tests {
         let result = search_code("test", &path, &options);
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_search_fuzzy_typo_matches_intended_identifier() {
+        use tempfile::tempdir;
+        let dir = tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("lib.rs"),
+            "fn calculate_total(items: &[u32]) -> u32 {\n    items.iter().sum()\n}\n\nfn print_report() {}\nlet c_a_l_c_l_a_t_e_t_o_t_a_l = 0;\n",
+        )
+        .unwrap();
+        let options = SearchOptions { fuzzy: true, fuzzy_threshold: 0.8, ..default_options() };
+        let results = search_code("calclate_total", dir.path(), &options).unwrap();
+        let lines: Vec<usize> = results.iter().map(|r| r.line_number).collect();
+        assert_eq!(lines, vec![1]);
+
+        // At 0.0 any line containing the query's letters in order matches
+        let options = SearchOptions { fuzzy_threshold: 0.0, ..options };
+        let results = search_code("calclate_total", dir.path(), &options).unwrap();
+        assert_eq!(results.len(), 2);
+    }
+}
+
+#[cfg(test)]
+mod multibyte_tests {
+    use crate::search::{highlight_ranges, search_code};
+    use crate::types::{SearchOptions, SearchResult};
+    use tempfile::tempdir;
+
+    const CONTENT: &str = "// héllo wörld\nlet 名前 = \"🦀 wörld 🌍\";\nfn grüße_wörld() {}\n";
+
+    fn search(query: &str, options: &SearchOptions) -> Vec<SearchResult> {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("lib.rs"), CONTENT).unwrap();
+        search_code(query, dir.path(), options).unwrap()
+    }
+
+    fn render(result: &SearchResult) -> String {
+        let ranges: Vec<(usize, usize)> = result.matches.iter().filter_map(|m| result.content_range(m)).collect();
+        highlight_ranges(&result.content, &ranges, |part| format!("[{part}]"))
+    }
+
+    #[test]
+    fn test_match_offsets_are_bytes_around_multibyte_text() {
+        let results = search("wörld", &SearchOptions::default());
+        let rendered: Vec<String> = results.iter().map(render).collect();
+        assert_eq!(rendered, vec!["// héllo [wörld]", "let 名前 = \"🦀 [wörld] 🌍\";", "fn grüße_[wörld]() {}"]);
+
+        for result in &results {
+            for m in &result.matches {
+                assert_eq!(result.content.get(m.start..m.end), Some(m.text.as_str()));
+            }
+        }
+
+        let results = search("名前|🦀|🌍", &SearchOptions::default());
+        assert_eq!(render(&results[0]), "let [名前] = \"[🦀] wörld [🌍]\";");
+    }
+
+    #[test]
+    fn test_fuzzy_match_offsets_are_bytes() {
+        let options = SearchOptions { fuzzy: true, fuzzy_threshold: 0.0, ..SearchOptions::default() };
+        let results = search("grße", &options);
+        let grusse = results.iter().find(|r| r.line_number == 3).unwrap();
+        for m in &grusse.matches {
+            assert_eq!(grusse.content.get(m.start..m.end), Some(m.text.as_str()));
+        }
+        assert_eq!(render(grusse), "fn [gr]ü[ße]_wörld() {}");
+    }
+}
+
+#[cfg(test)]
+mod line_ending_tests {
+    use crate::codemetrics::analyze_file_metrics;
+    use crate::encoding::Encoding;
+    use crate::parser::{extract_functions, read_file_content};
+    use crate::search::{list_files, search_code};
+    use crate::types::SearchOptions;
+    use std::path::Path;
+
+    const FIXTURES: [&str; 3] = ["lf.rs", "crlf.rs", "cr.rs"];
+
+    fn fixture_dir() -> &'static Path {
+        Path::new("tests/fixtures/line_endings")
+    }
+
+    #[test]
+    fn test_search_line_numbers_and_spans_for_every_ending() {
+        let results = search_code("needle", fixture_dir(), &SearchOptions::default()).unwrap();
+
+        for name in FIXTURES {
+            let mut hits: Vec<_> = results.iter().filter(|r| Path::new(&r.file).file_name() == Some(name.as_ref())).collect();
+            hits.sort_by_key(|r| r.line_number);
+            let lines: Vec<usize> = hits.iter().map(|r| r.line_number).collect();
+            assert_eq!(lines, vec![3, 4, 8], "{name}");
+
+            for hit in hits {
+                assert!(!hit.content.contains('\r'), "{name}: {:?}", hit.content);
+                let m = &hit.matches[0];
+                assert_eq!(&hit.content[m.start..m.end], "needle", "{name}");
+                assert_eq!(m.text, "needle");
+            }
+        }
+    }
+
+    #[test]
+    fn test_line_counts_and_definitions_for_every_ending() {
+        let files = list_files(fixture_dir(), None, None).unwrap();
+        for name in FIXTURES {
+            let file = files.iter().find(|f| f.path.ends_with(name)).unwrap();
+            assert_eq!(file.lines, 9, "{name}");
+
+            let content = std::fs::read_to_string(fixture_dir().join(name)).unwrap();
+            let functions = extract_functions(&content, name);
+            assert!(functions.contains(&("first".to_string(), 2)), "{name}: {functions:?}");
+            assert!(functions.contains(&("second".to_string(), 7)), "{name}: {functions:?}");
+        }
+    }
+
+    /// `text` as UTF-16LE with a byte order mark, as Visual Studio saves it
+    fn utf16le(text: &str) -> Vec<u8> {
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+        bytes
+    }
+
+    #[test]
+    fn test_utf16_files_are_searched() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = "// généré\r\nfn main() {\r\n    let needle = \"wörld\";\r\n}\r\n";
+        std::fs::write(dir.path().join("utf16.cs"), utf16le(source)).unwrap();
+
+        let results = search_code("needle = \"wörld\";$", dir.path(), &SearchOptions::default()).unwrap();
+        assert_eq!(results.len(), 1);
+        let hit = &results[0];
+        assert_eq!((hit.line_number, hit.content.as_str()), (3, "    let needle = \"wörld\";"));
+        assert_eq!(hit.matches[0].text, "needle = \"wörld\";");
+
+        let multiline = SearchOptions::builder().multiline(true).build();
+        assert_eq!(search_code("main\\(\\) \\{\\s+let needle", dir.path(), &multiline).unwrap().len(), 1);
+
+        // Forced UTF-8, the file doesn't decode and is skipped
+        let utf8 = SearchOptions::builder().encoding(Encoding::Utf8).build();
+        assert!(search_code("needle", dir.path(), &utf8).unwrap().is_empty());
+
+        let files = list_files(dir.path(), None, None).unwrap();
+        assert_eq!(files[0].lines, 4);
+    }
+
+    #[test]
+    fn test_crlf_content_analyzes_like_lf() {
+        let dir = tempfile::tempdir().unwrap();
+        let lf = "fn used(a: u32) -> u32 {\n    a + 1\n}\n\nfn unused() {\n    let x = used(2);\n}\n";
+        std::fs::write(dir.path().join("lf.rs"), lf).unwrap();
+        std::fs::write(dir.path().join("crlf.rs"), lf.replace('\n', "\r\n")).unwrap();
+        std::fs::write(dir.path().join("utf16.rs"), utf16le(&lf.replace('\n', "\r\n"))).unwrap();
+
+        let path = |name: &str| dir.path().join(name).to_string_lossy().to_string();
+        assert_eq!(read_file_content(&path("crlf.rs")), lf);
+        assert_eq!(read_file_content(&path("utf16.rs")), lf);
+
+        let metrics = |name: &str| analyze_file_metrics(&dir.path().join(name)).unwrap();
+        let expected = metrics("lf.rs");
+        for name in ["crlf.rs", "utf16.rs"] {
+            let actual = metrics(name);
+            assert_eq!(actual.complexity.halstead.vocabulary, expected.complexity.halstead.vocabulary, "{name}");
+            assert_eq!(actual.complexity.halstead.length, expected.complexity.halstead.length, "{name}");
+            assert_eq!(actual.size.total_lines, expected.size.total_lines, "{name}");
+            assert_eq!(actual.size.source_lines, expected.size.source_lines, "{name}");
+        }
+
+        let files = list_files(dir.path(), None, None).unwrap();
+        assert!(files.iter().all(|f| f.lines == 7), "{files:?}");
+    }
+}
+
+#[cfg(test)]
+mod context_tests {
+    use crate::search::search_code;
+    use crate::types::{ContextLines, SearchOptions, SearchResult};
+    use tempfile::TempDir;
+
+    /// Ten lines with matches on lines 1, 4, 6 and 10
+    fn search_with_context(context: ContextLines, max_results: usize) -> Vec<SearchResult> {
+        let dir = TempDir::new().unwrap();
+        let content: Vec<String> = (1..=10)
+            .map(|n| if [1, 4, 6, 10].contains(&n) { format!("hit {n}") } else { format!("line {n}") })
+            .collect();
+        std::fs::write(dir.path().join("a.rs"), content.join("\n")).unwrap();
+
+        let options = SearchOptions::default().with_max_results(max_results).with_context(context);
+        let mut results = search_code("hit", dir.path(), &options).unwrap();
+        results.sort_by_key(|r| r.line_number);
+        results
+    }
+
+    #[test]
+    fn test_context_clipped_at_file_edges_and_never_repeated() {
+        let results = search_with_context(ContextLines::new(2, 2), 10);
+        let groups: Vec<(usize, Vec<String>, Vec<String>)> = results
+            .into_iter()
+            .map(|r| (r.line_number, r.context_before, r.context_after))
+            .collect();
+        let lines = |ns: &[usize]| ns.iter().map(|n| format!("line {n}")).collect::<Vec<_>>();
+
+        assert_eq!(
+            groups,
+            vec![
+                (1, vec![], lines(&[2, 3])),
+                (4, vec![], lines(&[5])),
+                (6, vec![], lines(&[7, 8])),
+                (10, lines(&[9]), vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_context_lines_do_not_count_against_max_results() {
+        let results = search_with_context(ContextLines::new(0, 3), 2);
+        assert_eq!(results.iter().map(|r| r.line_number).collect::<Vec<_>>(), vec![1, 4]);
+        assert_eq!(results[0].context_after, vec!["line 2", "line 3"]);
+        assert_eq!(results[1].context_after, vec!["line 5"]);
+    }
+
+    #[test]
+    fn test_no_context_by_default() {
+        let results = search_with_context(ContextLines::default(), 10);
+        assert_eq!(results.len(), 4);
+        assert!(results.iter().all(|r| r.context_before.is_empty() && r.context_after.is_empty()));
+    }
+}
+
+#[cfg(test)]
+mod gitignore_tests {
+    use crate::search::{list_files_with, search_code, search_path_or_glob};
+    use crate::types::SearchOptions;
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    /// A workspace with an ignored directory, ignored logs with one re-included,
+    /// and a nested .gitignore
+    fn workspace() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        for sub in ["out", "src"] {
+            std::fs::create_dir_all(root.join(sub)).unwrap();
+        }
+        std::fs::write(root.join(".gitignore"), "out/\n*.log\n!keep.log\n").unwrap();
+        std::fs::write(root.join("src/.gitignore"), "local.rs\n").unwrap();
+        for file in ["out/gen.rs", "src/main.rs", "src/local.rs", "debug.log", "keep.log"] {
+            std::fs::write(root.join(file), "let needle = 1;\n").unwrap();
+        }
+        dir
+    }
+
+    fn names(root: &Path, paths: impl Iterator<Item = String>) -> Vec<String> {
+        let mut names: Vec<String> = paths
+            .map(|p| Path::new(&p).strip_prefix(root).unwrap().to_string_lossy().replace('\\', "/"))
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    #[test]
+    fn test_search_skips_gitignored_paths() {
+        let dir = workspace();
+        let results = search_code("needle", dir.path(), &SearchOptions::default()).unwrap();
+        assert_eq!(names(dir.path(), results.into_iter().map(|r| r.file)), vec!["keep.log", "src/main.rs"]);
+
+        let options = SearchOptions::default().with_gitignore(false).with_exclude(vec!["out".to_string()]);
+        let results = search_code("needle", dir.path(), &options).unwrap();
+        assert_eq!(
+            names(dir.path(), results.into_iter().map(|r| r.file)),
+            vec!["debug.log", "keep.log", "src/local.rs", "src/main.rs"]
+        );
+    }
+
+    #[test]
+    fn test_glob_path_keeps_gitignore_and_excludes() {
+        let dir = workspace();
+        let pattern = dir.path().join("**/*.rs");
+        let results = search_path_or_glob("needle", &pattern, &SearchOptions::default()).unwrap();
+        assert_eq!(names(dir.path(), results.into_iter().map(|r| r.file)), vec!["src/main.rs"]);
+
+        let options = SearchOptions::default().with_gitignore(false).with_exclude(vec!["out".to_string()]);
+        let results = search_path_or_glob("needle", &pattern, &options).unwrap();
+        assert_eq!(names(dir.path(), results.into_iter().map(|r| r.file)), vec!["src/local.rs", "src/main.rs"]);
+    }
+
+    #[test]
+    fn test_list_files_skips_gitignored_paths() {
+        let dir = workspace();
+        let exts = ["rs".to_string(), "log".to_string()];
+
+        let files = list_files_with(dir.path(), Some(&exts), None, true).unwrap();
+        assert_eq!(names(dir.path(), files.into_iter().map(|f| f.path)), vec!["keep.log", "src/main.rs"]);
+
+        let files = list_files_with(dir.path(), Some(&exts), None, false).unwrap();
+        assert_eq!(names(dir.path(), files.into_iter().map(|f| f.path)).len(), 5);
+    }
+}
+
+#[cfg(test)]
+mod multiline_tests {
+    use crate::search::search_code;
+    use crate::types::SearchOptions;
+    use tempfile::TempDir;
+
+    const SOURCE: &str = "impl Point {\n    fn new() -> Self {\n        Self { x: 0 }\n    }\n}\n";
+
+    #[test]
+    fn test_multiline_matches_across_lines_only_when_enabled() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("a.rs"), SOURCE).unwrap();
+        let pattern = r"fn new\(\)[^{]*\{\s*Self";
+
+        assert!(search_code(pattern, dir.path(), &SearchOptions::default()).unwrap().is_empty());
+
+        let results = search_code(pattern, dir.path(), &SearchOptions::default().with_multiline(true)).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!((results[0].line_number, results[0].end_line_number), (2, Some(3)));
+
+        let json = serde_json::to_value(&results[0]).unwrap();
+        assert_eq!(json["end_line_number"], 3);
+    }
+
+    #[test]
+    fn test_multiline_skips_files_above_the_size_limit() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("a.rs"), SOURCE).unwrap();
+        let options = SearchOptions::default().with_multiline(true);
+
+        let limited = options.clone().with_multiline_max_bytes(SOURCE.len() as u64 - 1);
+        assert!(search_code("Self", dir.path(), &limited).unwrap().is_empty());
+        let exact = options.with_multiline_max_bytes(SOURCE.len() as u64);
+        assert_eq!(search_code("Self", dir.path(), &exact).unwrap().len(), 2);
+    }
+}
+
+#[cfg(test)]
+mod streaming_tests {
+    use crate::search::{search_code, search_code_streaming, sort_results_by_path};
+    use crate::types::SearchOptions;
+    use std::ops::ControlFlow;
+    use tempfile::TempDir;
+
+    fn tree(files: usize) -> TempDir {
+        let dir = TempDir::new().unwrap();
+        for i in 0..files {
+            std::fs::write(dir.path().join(format!("f{i}.rs")), "fn hit() {}\nlet x = 1;\nfn hit_again() {}\n").unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_streaming_delivers_what_search_code_returns() {
+        let dir = tree(20);
+        let options = SearchOptions::default();
+
+        let mut streamed = Vec::new();
+        let summary = search_code_streaming("hit", dir.path(), &options, |result| {
+            streamed.push(result);
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+        assert!(!summary.stopped);
+        assert_eq!(summary.files_processed, 20);
+
+        let mut collected = search_code("hit", dir.path(), &options).unwrap();
+        sort_results_by_path(&mut streamed);
+        sort_results_by_path(&mut collected);
+        let key = |r: &crate::types::SearchResult| (r.file.clone(), r.line_number);
+        assert_eq!(streamed.iter().map(key).collect::<Vec<_>>(), collected.iter().map(key).collect::<Vec<_>>());
+        assert_eq!(streamed.len(), 40);
+    }
+
+    #[test]
+    fn test_break_stops_the_search() {
+        let dir = tree(200);
+        let mut seen = 0;
+        let summary = search_code_streaming("hit", dir.path(), &SearchOptions::default(), |_| {
+            seen += 1;
+            ControlFlow::Break(())
+        })
+        .unwrap();
+        assert_eq!(seen, 1);
+        assert!(summary.stopped);
+    }
+
+    #[test]
+    fn test_callback_can_forward_into_a_channel() {
+        let dir = tree(5);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let consumer = std::thread::spawn(move || receiver.into_iter().count());
+
+        search_code_streaming("hit", dir.path(), &SearchOptions::default(), move |result| {
+            match sender.send(result) {
+                Ok(()) => ControlFlow::Continue(()),
+                Err(_) => ControlFlow::Break(()),
+            }
+        })
+        .unwrap();
+        assert_eq!(consumer.join().unwrap(), 10);
+    }
+}
+
+#[cfg(test)]
+mod file_list_tests {
+    use crate::search::{read_file_list, search_files_outcome, sort_results_by_path};
+    use crate::types::SearchOptions;
+    use tempfile::TempDir;
+
+    fn tree() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("a.rs"), "// TODO: a\n").unwrap();
+        std::fs::write(dir.path().join("b.py"), "# TODO: b\n").unwrap();
+        std::fs::write(dir.path().join("unlisted.rs"), "// TODO: unlisted\n").unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_searches_only_listed_files() {
+        let dir = tree();
+        let list = read_file_list("a.rs\nb.py\nmissing.rs\n".as_bytes(), dir.path()).unwrap();
+        assert_eq!(list.missing, vec![dir.path().join("missing.rs")]);
+
+        let mut outcome = search_files_outcome("TODO", &list.files, &SearchOptions::default(), true).unwrap();
+        sort_results_by_path(&mut outcome.results);
+        let files: Vec<&str> = outcome.results.iter().map(|r| r.file.rsplit('/').next().unwrap()).collect();
+        assert_eq!(files, vec!["a.rs", "b.py"]);
+        assert_eq!(outcome.metrics.files_processed, 2);
+    }
+
+    #[test]
+    fn test_extension_filter_applies_unless_disabled() {
+        let dir = tree();
+        let list = read_file_list("a.rs\nb.py\n".as_bytes(), dir.path()).unwrap();
+        let options = SearchOptions::default().with_extensions(vec!["rs".to_string()]);
+
+        let filtered = search_files_outcome("TODO", &list.files, &options, true).unwrap();
+        assert_eq!(filtered.results.len(), 1);
+        assert!(filtered.results[0].file.ends_with("a.rs"));
+
+        let unfiltered = search_files_outcome("TODO", &list.files, &options, false).unwrap();
+        assert_eq!(unfiltered.results.len(), 2);
+    }
+}
+
+#[cfg(test)]
+mod long_line_tests {
+    use crate::search::{search_code, search_in_file_limited, build_matcher};
+    use crate::types::{ContextLines, SearchOptions, ELLIPSIS, LONG_LINE_WINDOW};
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    /// A 1 MB minified line with `needle` at `at`, padded with multi-byte characters
+    fn minified(at: usize) -> String {
+        let mut line = "é".repeat(at / 2);
+        line.push_str("needle");
+        while line.len() < 1024 * 1024 {
+            line.push_str("var a=1;é");
+        }
+        line
+    }
+
+    #[test]
+    fn test_long_line_is_reported_as_a_window() {
+        let dir = TempDir::new().unwrap();
+        let line = minified(600_000);
+        let needle_at = line.find("needle").unwrap();
+        std::fs::write(dir.path().join("app.min.js"), &line).unwrap();
+
+        let results = search_code("needle", dir.path(), &SearchOptions::default()).unwrap();
+        assert_eq!(results.len(), 1);
+        let result = &results[0];
+        assert!(result.line_truncated);
+        assert!(result.content.len() <= 4 * (2 * LONG_LINE_WINDOW + 6) + 2 * ELLIPSIS.len());
+        assert!(result.content.starts_with(ELLIPSIS) && result.content.ends_with(ELLIPSIS));
+
+        // Offsets refer to the original line; the window maps back onto it
+        assert_eq!(result.matches[0].start, needle_at);
+        let offset = result.content_offset.unwrap();
+        let window = &result.content[ELLIPSIS.len()..result.content.len() - ELLIPSIS.len()];
+        assert_eq!(&line[offset..offset + window.len()], window);
+        let (start, end) = result.content_range(&result.matches[0]).unwrap();
+        assert_eq!(&result.content[start..end], "needle");
+
+        let json = serde_json::to_string(result).unwrap();
+        assert!(json.len() < 2048, "{} bytes", json.len());
+    }
+
+    #[test]
+    fn test_match_near_line_start_has_no_leading_ellipsis() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("a.js"), minified(10)).unwrap();
+
+        let results = search_code("needle", dir.path(), &SearchOptions::default()).unwrap();
+        let result = &results[0];
+        assert_eq!(result.content_offset, Some(0));
+        assert!(result.content.starts_with("éé") && result.content.ends_with(ELLIPSIS));
+        assert_eq!(result.content_range(&result.matches[0]), Some((10, 16)));
+    }
+
+    #[test]
+    fn test_long_context_lines_and_matches_are_cut() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("a.js");
+        let long = "x".repeat(5000);
+        std::fs::write(&path, format!("{long}\nneedle {long}\nshort\n")).unwrap();
+
+        let options = SearchOptions::default().with_context(ContextLines::new(1, 1));
+        let regex = Arc::new(build_matcher("needle x+", &options).unwrap());
+        let (results, _) = search_in_file_limited(&path, &regex, false, 0.0, "needle x+", 10, None, options.context, options.encoding).unwrap();
+        let result = &results[0];
+        assert_eq!(result.context_before[0].chars().count(), LONG_LINE_WINDOW + 1);
+        assert_eq!(result.context_after, vec!["short"]);
+        // The match runs to the end of the line; its text and the window are both capped
+        assert_eq!(result.matches[0].end, 5007);
+        assert_eq!(result.matches[0].text.chars().count(), LONG_LINE_WINDOW);
+        assert_eq!(result.content_range(&result.matches[0]), Some((0, result.content.len() - ELLIPSIS.len())));
+    }
+
+    #[test]
+    fn test_window_does_not_split_combining_marks() {
+        let dir = TempDir::new().unwrap();
+        // "e" plus a combining acute accent, so every other char attaches to the one
+        // before; the odd "z" puts a plain character cut between the two
+        let padding = "e\u{301}".repeat(1000);
+        let line = format!("{padding}zneedlez{padding}");
+        std::fs::write(dir.path().join("a.js"), &line).unwrap();
+
+        let results = search_code("needle", dir.path(), &SearchOptions::default()).unwrap();
+        let result = &results[0];
+        let offset = result.content_offset.unwrap();
+        let window = &result.content[ELLIPSIS.len()..result.content.len() - ELLIPSIS.len()];
+        assert!(window.starts_with('e') && window.ends_with('\u{301}'), "{window}");
+        assert!(line[offset + window.len()..].starts_with('e'));
+    }
+
+    #[test]
+    fn test_short_lines_are_untouched() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("a.rs"), "let needle = 1;\n").unwrap();
+        let results = search_code("needle", dir.path(), &SearchOptions::default()).unwrap();
+        assert!(!results[0].line_truncated);
+        assert_eq!(results[0].content_offset, None);
+        assert_eq!(results[0].content, "let needle = 1;");
+        assert!(!serde_json::to_string(&results[0]).unwrap().contains("line_truncated"));
+    }
+}
+
+#[cfg(test)]
+mod index_tests {
+    use crate::index::CodeIndex;
+    use crate::search::{search_code_outcome, sort_results_by_path};
+    use crate::types::SearchOptions;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    /// A tree of `files` files spread over directories, with `needle` in every 600th file
+    fn tree(files: usize) -> TempDir {
+        let dir = TempDir::new().unwrap();
+        for i in 0..files {
+            let sub = dir.path().join(format!("d{}", i % 30));
+            std::fs::create_dir_all(&sub).unwrap();
+            let body = if i % 600 == 0 { "let rare_needle = 1;\n" } else { "fn ordinary() {}\n" };
+            std::fs::write(sub.join(format!("f{i}.rs")), body).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_index_opens_fewer_files() {
+        let dir = tree(3000);
+        let index_dir = TempDir::new().unwrap();
+        let index = Arc::new(CodeIndex::new(index_dir.path().join("index.json")));
+        index.index_directory(dir.path(), None, None).unwrap();
+
+        let plain = SearchOptions::builder().max_results(100).build();
+        let indexed = SearchOptions::builder().max_results(100).index(index.clone()).build();
+        let without = search_code_outcome("rare_needle", dir.path(), &plain).unwrap();
+        let with = search_code_outcome("rare_needle", dir.path(), &indexed).unwrap();
+
+        assert_eq!(without.metrics.files_processed, 3000);
+        assert_eq!(with.metrics.files_processed, 5);
+        assert_eq!(with.metrics.files_ruled_out_by_index, 2995);
+        let (mut expected, mut found) = (without.results, with.results);
+        sort_results_by_path(&mut expected);
+        sort_results_by_path(&mut found);
+        assert_eq!(found.len(), 5);
+        assert_eq!(
+            found.iter().map(|r| &r.file).collect::<Vec<_>>(),
+            expected.iter().map(|r| &r.file).collect::<Vec<_>>()
+        );
+
+        // Case-insensitive and regex queries use the same trigrams
+        let regex = SearchOptions::builder().ignore_case(true).index(index.clone()).build();
+        let outcome = search_code_outcome(r"RARE_NEEDLE\s*=", dir.path(), &regex).unwrap();
+        assert_eq!(outcome.results.len(), 5);
+        assert_eq!(outcome.metrics.files_processed, 5);
+    }
+
+    #[test]
+    fn test_stale_entries_are_searched() {
+        let dir = tree(50);
+        let index_dir = TempDir::new().unwrap();
+        let index = Arc::new(CodeIndex::new(index_dir.path().join("index.json")));
+        index.index_directory(dir.path(), None, None).unwrap();
+
+        // Edited after indexing: size changed, so the entry no longer rules it out
+        std::fs::write(dir.path().join("d1/f1.rs"), "let rare_needle = 2; // edited\n").unwrap();
+        let options = SearchOptions::builder().index(index).build();
+        let outcome = search_code_outcome("rare_needle", dir.path(), &options).unwrap();
+        assert_eq!(outcome.results.len(), 2);
+        assert_eq!(outcome.metrics.files_processed, 2);
+
+        // Queries without three required characters can't use the index
+        let options = SearchOptions { index: options.index.clone(), ..SearchOptions::default() };
+        let outcome = search_code_outcome("r.n", dir.path(), &options).unwrap();
+        assert_eq!(outcome.metrics.files_ruled_out_by_index, 0);
+    }
+}
+
+#[cfg(test)]
+mod processor_tests {
+    use crate::search::{annotation_suffix, search_code, search_code_streaming, CodeOwnersAnnotator, ResultProcessor};
+    use crate::types::{SearchOptions, SearchResult};
+    use std::ops::ControlFlow;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tempfile::TempDir;
+
+    /// Appends its name to the `trail` annotation and counts its calls
+    #[derive(Debug, Default)]
+    struct Trail {
+        name: &'static str,
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl ResultProcessor for Trail {
+        fn process(&self, result: &mut SearchResult) {
+            self.calls.fetch_add(1, Ordering::Relaxed);
+            let trail = result.annotations.entry("trail".to_string()).or_default();
+            if !trail.is_empty() {
+                trail.push(',');
+            }
+            trail.push_str(self.name);
+        }
+    }
+
+    fn tree() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join("docs")).unwrap();
+        std::fs::write(dir.path().join("lib.rs"), "fn hit() {}\nlet hit = 1;\n").unwrap();
+        std::fs::write(dir.path().join("docs/guide.md"), "a hit in the docs\n").unwrap();
+        std::fs::write(dir.path().join("CODEOWNERS"), "*.rs @rust-team\n/docs/ @docs-team @writers\n").unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_post_processors_run_in_order_on_the_kept_results() {
+        let dir = tree();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let options = SearchOptions::builder()
+            .rank(true)
+            .max_total(2)
+            .post_processor(Trail { name: "first", calls: calls.clone() })
+            .post_processor(Trail { name: "second", calls: calls.clone() })
+            .build();
+
+        let results = search_code("hit", dir.path(), &options).unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| r.annotations["trail"] == "first,second"));
+        // Only the results left after ranking and the cap are processed
+        assert_eq!(calls.load(Ordering::Relaxed), 4);
+    }
+
+    #[test]
+    fn test_streaming_runs_post_processors_on_each_result() {
+        let dir = tree();
+        let options = SearchOptions::default().with_post_processor(Trail { name: "only", ..Trail::default() });
+        let mut streamed = Vec::new();
+        search_code_streaming("hit", dir.path(), &options, |result| {
+            streamed.push(result);
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+        assert_eq!(streamed.len(), 3);
+        assert!(streamed.iter().all(|r| r.annotations["trail"] == "only"));
+    }
+
+    #[test]
+    fn test_codeowners_annotator_adds_owners() {
+        let dir = tree();
+        let annotator = CodeOwnersAnnotator::discover(dir.path()).unwrap();
+        let options = SearchOptions::default().with_post_processor(annotator);
+        let results = search_code("hit", dir.path(), &options).unwrap();
+
+        let owners = |suffix: &str| {
+            let result = results.iter().find(|r| r.file.ends_with(suffix)).unwrap();
+            result.annotations.get(CodeOwnersAnnotator::KEY).cloned()
+        };
+        assert_eq!(owners("lib.rs").as_deref(), Some("@rust-team"));
+        assert_eq!(owners("guide.md").as_deref(), Some("@docs-team @writers"));
+        assert!(CodeOwnersAnnotator::discover(&dir.path().join("docs")).is_none());
+    }
+
+    #[test]
+    fn test_annotations_render_and_pass_through_json() {
+        let dir = tree();
+        let options = SearchOptions::default().with_post_processor(CodeOwnersAnnotator::discover(dir.path()).unwrap());
+        let mut result = search_code("fn hit", dir.path(), &options).unwrap().remove(0);
+        result.annotations.insert("ticket".to_string(), "PROJ-12".to_string());
+        assert_eq!(annotation_suffix(&result), " [owners: @rust-team] [ticket: PROJ-12]");
+
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["annotations"], serde_json::json!({ "owners": "@rust-team", "ticket": "PROJ-12" }));
+        assert_eq!(serde_json::from_value::<SearchResult>(json).unwrap(), result);
+
+        result.annotations.clear();
+        assert_eq!(annotation_suffix(&result), "");
+        assert!(serde_json::to_value(&result).unwrap().get("annotations").is_none());
+    }
+}
+
+#[cfg(test)]
+mod pattern_attribution_tests {
+    use crate::search::{highlight_result, search_code};
+    use crate::theme::ColorTheme;
+    use crate::types::{SearchOptions, SearchResult};
+    use tempfile::tempdir;
+
+    const CONTENT: &str = "// TODO: retry\n// FIXME and TODO both\nlet todo_list = fixme();\n";
+
+    fn search(query: &str, options: &SearchOptions) -> Vec<SearchResult> {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("lib.rs"), CONTENT).unwrap();
+        search_code(query, dir.path(), options).unwrap()
+    }
+
+    fn attributed(results: &[SearchResult]) -> Vec<Vec<(&str, usize)>> {
+        results
+            .iter()
+            .map(|r| r.matches.iter().map(|m| (m.text.as_str(), m.pattern_index)).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_matches_record_the_alternative_that_hit() {
+        let results = search("TODO|FIXME", &SearchOptions::default());
+        assert_eq!(attributed(&results), vec![vec![("TODO", 0)], vec![("FIXME", 1), ("TODO", 0)]]);
+
+        // Flags and word boundaries apply to each alternative too
+        let options = SearchOptions { ignore_case: true, word_boundary: true, ..SearchOptions::default() };
+        let results = search("(?:fixme)|todo", &options);
+        assert_eq!(attributed(&results), vec![vec![("TODO", 1)], vec![("FIXME", 0), ("TODO", 1)], vec![("fixme", 0)]]);
+
+        // Alternation inside a group is one pattern
+        let results = search("(TODO|FIXME)", &SearchOptions::default());
+        assert!(results.iter().flat_map(|r| &r.matches).all(|m| m.pattern_index == 0));
+
+        let options = SearchOptions { multiline: true, ..SearchOptions::default() };
+        let results = search("retry\n// FIXME|fixme", &options);
+        assert_eq!(attributed(&results), vec![vec![("retry\n// FIXME", 0)], vec![("fixme", 1)]]);
+    }
+
+    #[test]
+    fn test_alternatives_are_highlighted_in_distinct_colors() {
+        let results = search("TODO|FIXME", &SearchOptions::default());
+        assert_eq!(
+            highlight_result(&results[1], ColorTheme::Default, true),
+            "// \x1b[1;32mFIXME\x1b[0m and \x1b[1;31mTODO\x1b[0m both"
+        );
+        assert_eq!(
+            highlight_result(&results[1], ColorTheme::Mono, true),
+            "// \x1b[1;31mFIXME\x1b[0m and \x1b[1;31mTODO\x1b[0m both"
+        );
+        assert_eq!(highlight_result(&results[1], ColorTheme::Default, false), "// FIXME and TODO both");
+    }
 }
@@ -0,0 +1,133 @@
+//! Shortening strings for display
+//!
+//! Snippets in reports and suggestions are cut to a fixed length. Slicing a
+//! `str` at a byte index panics inside a multi-byte character, and cutting at
+//! any char boundary can still split an accented letter from its combining
+//! mark or break an emoji sequence apart, so every cut is made between
+//! extended grapheme clusters, through [`truncate_str_safe`] or [`prefix_end`].
+
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Marker appended to a string that was cut short
+pub const TRUNCATION_MARKER: &str = "...";
+
+/// Shorten `s` to at most `max_chars` characters, ending in [`TRUNCATION_MARKER`] when cut
+///
+/// Strings that fit are returned unchanged. Otherwise as many whole grapheme
+/// clusters are kept as fit before the marker. For a `max_chars` below the
+/// marker's length only the marker is left.
+pub fn truncate_str_safe(s: &str, max_chars: usize) -> String {
+    if s.chars().nth(max_chars).is_none() {
+        return s.to_string();
+    }
+    let end = prefix_end(s, max_chars.saturating_sub(TRUNCATION_MARKER.len()));
+    format!("{}{TRUNCATION_MARKER}", &s[..end])
+}
+
+/// Byte length of the longest prefix of `s` made of whole grapheme clusters and at most `max_chars` characters
+pub fn prefix_end(s: &str, max_chars: usize) -> usize {
+    let mut chars = 0;
+    for (i, cluster) in s.grapheme_indices(true) {
+        chars += cluster.chars().count();
+        if chars > max_chars {
+            return i;
+        }
+    }
+    s.len()
+}
+
+/// Byte offset of the longest suffix of `s` made of whole grapheme clusters and at most `max_chars` characters
+pub fn suffix_start(s: &str, max_chars: usize) -> usize {
+    let mut chars = 0;
+    for (i, cluster) in s.grapheme_indices(true).rev() {
+        chars += cluster.chars().count();
+        if chars > max_chars {
+            return i + cluster.len();
+        }
+    }
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_strings_are_unchanged() {
+        assert_eq!(truncate_str_safe("short", 10), "short");
+        assert_eq!(truncate_str_safe("exactly10!", 10), "exactly10!");
+        assert_eq!(truncate_str_safe("", 0), "");
+    }
+
+    #[test]
+    fn test_ascii_is_cut_to_max_chars_with_marker() {
+        assert_eq!(truncate_str_safe("this is a long string", 10), "this is...");
+        assert_eq!(truncate_str_safe("abcdef", 2), "...");
+    }
+
+    #[test]
+    fn test_cjk_is_cut_on_char_boundaries() {
+        let comment = "// 这是一个被注释掉的代码行";
+        for max in 0..=comment.chars().count() + 1 {
+            let cut = truncate_str_safe(comment, max);
+            assert!(cut.chars().count() <= max.max(TRUNCATION_MARKER.len()), "{max}: {cut}");
+        }
+        assert_eq!(truncate_str_safe(comment, 8), "// 这是...");
+        assert_eq!(truncate_str_safe("日本語", 3), "日本語");
+        assert_eq!(truncate_str_safe("日本語テキスト", 6), "日本語...");
+    }
+
+    #[test]
+    fn test_emoji_sequences_are_not_split() {
+        // Family: man, ZWJ, woman, ZWJ, girl
+        let family = "ab\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}cdefgh";
+        for max in 5..=9 {
+            assert_eq!(truncate_str_safe(family, max), "ab...", "{max}");
+        }
+        assert_eq!(truncate_str_safe(family, 4), "a...");
+        assert_eq!(truncate_str_safe(family, 10), "ab\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}...");
+
+        // Thumbs up with a skin tone
+        assert_eq!(truncate_str_safe("x\u{1F44D}\u{1F3FD}yzwv", 5), "x...");
+        assert_eq!(truncate_str_safe("x\u{1F44D}\u{1F3FD}yzwv", 6), "x\u{1F44D}\u{1F3FD}...");
+
+        // Flags: Japan then France
+        let flags = "\u{1F1EF}\u{1F1F5}\u{1F1EB}\u{1F1F7}zzzz";
+        assert_eq!(truncate_str_safe(flags, 4), "...");
+        assert_eq!(truncate_str_safe(flags, 5), "\u{1F1EF}\u{1F1F5}...");
+        assert_eq!(truncate_str_safe(flags, 6), "\u{1F1EF}\u{1F1F5}...");
+        assert_eq!(truncate_str_safe(flags, 7), "\u{1F1EF}\u{1F1F5}\u{1F1EB}\u{1F1F7}...");
+    }
+
+    #[test]
+    fn test_combining_marks_stay_with_their_letter() {
+        // "cafe" with a combining acute accent on the e
+        let cafe = "cafe\u{0301} au lait";
+        assert_eq!(truncate_str_safe(cafe, 7), "caf...");
+        assert_eq!(truncate_str_safe(cafe, 8), "cafe\u{0301}...");
+        assert_eq!(truncate_str_safe("e\u{0301}\u{0301}\u{0301}xyzw", 5), "...");
+    }
+
+    #[test]
+    fn test_hangul_jamo_and_indic_conjuncts_stay_whole() {
+        // Hangul "han" spelled with conjoining jamo: L, V, T
+        let han = "\u{1112}\u{1161}\u{11AB}abcdef";
+        assert_eq!(truncate_str_safe(han, 5), "...");
+        assert_eq!(truncate_str_safe(han, 6), "\u{1112}\u{1161}\u{11AB}...");
+        // Devanagari "ksha": ka, virama, ssa
+        let ksha = "\u{0915}\u{094D}\u{0937}abcdef";
+        assert_eq!(truncate_str_safe(ksha, 5), "...");
+        assert_eq!(truncate_str_safe(ksha, 6), "\u{0915}\u{094D}\u{0937}...");
+    }
+
+    #[test]
+    fn test_prefix_and_suffix_keep_whole_clusters() {
+        let s = "ab\u{1F44D}\u{1F3FD}cd";
+        assert_eq!(prefix_end(s, 3), 2);
+        assert_eq!(prefix_end(s, 4), s.len() - 2);
+        assert_eq!(prefix_end(s, 10), s.len());
+        assert_eq!(suffix_start(s, 3), s.len() - 2);
+        assert_eq!(suffix_start(s, 4), 2);
+        assert_eq!(suffix_start(s, 10), 0);
+    }
+}
@@ -0,0 +1,505 @@
+//! Output Theme Module
+//!
+//! Every decorative glyph in human-readable output (icons, rules, bullets,
+//! arrows, bar blocks) comes from here, so it can be swapped for plain ASCII
+//! in one place. Colors are handled by `colored`, except for highlighted
+//! matches: those take their color from the [`ColorTheme`], so each
+//! alternative of a `foo|bar` query stands out in its own.
+//!
+//! ASCII mode is chosen once at startup by [`init`]: `--no-emoji`, the
+//! `[output] emoji` config key, or automatically when stdout is not a
+//! terminal or the locale isn't UTF-8. JSON and other machine-readable
+//! formats never use these glyphs.
+//!
+//! File paths in text output can also be OSC 8 hyperlinks, via [`link`]. They
+//! are only emitted when stdout is a terminal, so pipes, exports and JSON never
+//! contain the escape sequences.
+
+use crate::config::OutputConfig;
+use colored::Color;
+use std::fmt::Write as _;
+use std::io::IsTerminal;
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static ASCII: AtomicBool = AtomicBool::new(false);
+static HYPERLINKS: AtomicBool = AtomicBool::new(false);
+static MONO: AtomicBool = AtomicBool::new(false);
+/// `[output] hyperlink_format`; `None` links to `file://` URLs
+static LINK_FORMAT: RwLock<Option<String>> = RwLock::new(None);
+
+/// `TERM_PROGRAM` values of terminals known to support OSC 8 hyperlinks
+const HYPERLINK_TERMINALS: [&str; 8] = ["iTerm.app", "WezTerm", "vscode", "Hyper", "ghostty", "Tabby", "rio", "zed"];
+
+/// `TERM` prefixes of terminals known to support OSC 8 hyperlinks
+const HYPERLINK_TERMS: [&str; 5] = ["xterm-kitty", "alacritty", "foot", "xterm-ghostty", "wezterm"];
+
+/// When file paths are printed as clickable hyperlinks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HyperlinkMode {
+    /// When stdout is a terminal that advertises support
+    #[default]
+    Auto,
+    /// When stdout is a terminal, whether it advertises support or not
+    Always,
+    Never,
+}
+
+impl std::str::FromStr for HyperlinkMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "always" => Ok(Self::Always),
+            "never" => Ok(Self::Never),
+            other => Err(format!("Unknown hyperlink mode: {other} (expected: auto, always, never)")),
+        }
+    }
+}
+
+/// Colors of highlighted matches, by alternative of the query, cycled when there are more
+pub const MATCH_PALETTE: [Color; 6] = [Color::Red, Color::Green, Color::Blue, Color::Magenta, Color::Cyan, Color::Yellow];
+
+/// How highlighted matches are colored
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColorTheme {
+    /// Each alternative of the query in the next [`MATCH_PALETTE`] color
+    #[default]
+    Default,
+    /// Every match in the first palette color
+    Mono,
+}
+
+impl std::str::FromStr for ColorTheme {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "default" => Ok(Self::Default),
+            "mono" => Ok(Self::Mono),
+            other => Err(format!("Unknown color theme: {other} (expected: default, mono)")),
+        }
+    }
+}
+
+impl ColorTheme {
+    /// Color of a match of alternative `pattern_index`
+    pub fn match_color(self, pattern_index: usize) -> Color {
+        match self {
+            Self::Default => MATCH_PALETTE[pattern_index % MATCH_PALETTE.len()],
+            Self::Mono => MATCH_PALETTE[0],
+        }
+    }
+
+    /// `text` in bold [`match_color`](Self::match_color), or unchanged when `color` is false
+    pub fn paint_match(self, text: &str, pattern_index: usize, color: bool) -> String {
+        if !color {
+            return text.to_string();
+        }
+        format!("\x1b[1;{}m{text}\x1b[0m", self.match_color(pattern_index).to_fg_str())
+    }
+}
+
+/// A decorative symbol with a Unicode and a plain ASCII rendering
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Glyph {
+    /// Horizontal rule segment under headers
+    Rule,
+    Bullet,
+    Arrow,
+    Up,
+    Down,
+    /// Histogram bar segment
+    Bar,
+    /// Inline separator between summary figures
+    Separator,
+    /// Marker for empty blocks in dead code listings
+    Empty,
+    File,
+    Stats,
+    Summary,
+    Duplicate,
+    Hint,
+    Bookmark,
+    Warning,
+    Critical,
+    Ok,
+    Refactor,
+    Group,
+    Languages,
+    Search,
+    Goodbye,
+    Help,
+    Toggles,
+}
+
+impl Glyph {
+    /// The Unicode rendering
+    pub fn unicode(self) -> &'static str {
+        match self {
+            Glyph::Rule => "─",
+            Glyph::Bullet => "•",
+            Glyph::Arrow => "→",
+            Glyph::Up => "▲",
+            Glyph::Down => "▼",
+            Glyph::Bar => "▇",
+            Glyph::Separator => "·",
+            Glyph::Empty => "∅",
+            Glyph::File => "📄",
+            Glyph::Stats => "📊",
+            Glyph::Summary => "📈",
+            Glyph::Duplicate => "🔄",
+            Glyph::Hint => "💡",
+            Glyph::Bookmark => "🔖",
+            Glyph::Warning => "⚠️",
+            Glyph::Critical => "🔴",
+            Glyph::Ok => "✅",
+            Glyph::Refactor => "🔧",
+            Glyph::Group => "📋",
+            Glyph::Languages => "🌐",
+            Glyph::Search => "🎮",
+            Glyph::Goodbye => "👋",
+            Glyph::Help => "📖",
+            Glyph::Toggles => "⚡",
+        }
+    }
+
+    /// The ASCII rendering; empty for icons that only decorate a self-describing heading
+    pub fn ascii(self) -> &'static str {
+        match self {
+            Glyph::Rule | Glyph::Separator => "-",
+            Glyph::Bullet => "*",
+            Glyph::Arrow => "->",
+            Glyph::Up => "^",
+            Glyph::Down => "v",
+            Glyph::Bar => "#",
+            Glyph::Empty => "0",
+            Glyph::File => "[file]",
+            Glyph::Stats => "[stats]",
+            Glyph::Summary => "[summary]",
+            Glyph::Duplicate => "[dup]",
+            Glyph::Hint => "[hint]",
+            Glyph::Bookmark => "[bookmark]",
+            Glyph::Warning => "[!]",
+            Glyph::Critical => "[!!]",
+            Glyph::Ok => "[ok]",
+            Glyph::Group => "[*]",
+            Glyph::Search => "[search]",
+            Glyph::Refactor | Glyph::Languages | Glyph::Goodbye | Glyph::Help | Glyph::Toggles => "",
+        }
+    }
+
+    /// The rendering for the given mode
+    pub fn render(self, ascii: bool) -> &'static str {
+        if ascii { self.ascii() } else { self.unicode() }
+    }
+}
+
+/// Pick ASCII or Unicode output, and whether paths are hyperlinks, for the rest of the process
+///
+/// `no_emoji` is the `--no-emoji` flag. Otherwise `[output] emoji` decides,
+/// and when that is unset the terminal and locale do. `hyperlinks` is
+/// `--hyperlinks`, or else `[output] hyperlinks`.
+pub fn init(no_emoji: bool, hyperlinks: HyperlinkMode, config: &OutputConfig) {
+    let ascii = no_emoji || !config.emoji.unwrap_or_else(terminal_supports_unicode);
+    set_ascii(ascii);
+    let enabled = should_hyperlink(hyperlinks, std::io::stdout().is_terminal(), &env_var);
+    set_hyperlinks(enabled, config.hyperlink_format.clone());
+}
+
+/// Use `theme` for highlighted matches for the rest of the process
+pub fn set_color_theme(theme: ColorTheme) {
+    MONO.store(theme == ColorTheme::Mono, Ordering::Relaxed);
+}
+
+/// The theme highlighted matches are colored by
+pub fn color_theme() -> ColorTheme {
+    if MONO.load(Ordering::Relaxed) { ColorTheme::Mono } else { ColorTheme::Default }
+}
+
+/// Force ASCII mode on or off
+pub fn set_ascii(ascii: bool) {
+    ASCII.store(ascii, Ordering::Relaxed);
+}
+
+/// Whether decorative output is plain ASCII
+pub fn is_ascii() -> bool {
+    ASCII.load(Ordering::Relaxed)
+}
+
+/// Turn hyperlinks on or off, linking to `format` (see [`hyperlink_url`])
+pub fn set_hyperlinks(enabled: bool, format: Option<String>) {
+    HYPERLINKS.store(enabled, Ordering::Relaxed);
+    if let Ok(mut link_format) = LINK_FORMAT.write() {
+        *link_format = format;
+    }
+}
+
+/// Whether file paths are printed as hyperlinks
+pub fn hyperlinks_enabled() -> bool {
+    HYPERLINKS.load(Ordering::Relaxed)
+}
+
+/// Whether stdout is a terminal running under a UTF-8 locale
+pub fn terminal_supports_unicode() -> bool {
+    std::io::stdout().is_terminal() && locale_is_utf8(&env_var)
+}
+
+fn env_var(name: &str) -> Option<String> {
+    std::env::var(name).ok()
+}
+
+/// Whether to print hyperlinks in `mode`; never when stdout isn't a terminal
+pub fn should_hyperlink(mode: HyperlinkMode, stdout_is_terminal: bool, var: &dyn Fn(&str) -> Option<String>) -> bool {
+    stdout_is_terminal
+        && match mode {
+            HyperlinkMode::Never => false,
+            HyperlinkMode::Always => true,
+            HyperlinkMode::Auto => terminal_supports_hyperlinks(var),
+        }
+}
+
+/// Whether the terminal's environment advertises OSC 8 support
+///
+/// Checks `TERM_PROGRAM`, `TERM`, and the variables VTE (0.50+), Windows
+/// Terminal, Konsole and DomTerm set.
+pub fn terminal_supports_hyperlinks(var: &dyn Fn(&str) -> Option<String>) -> bool {
+    let term = var("TERM").unwrap_or_default();
+    if term == "dumb" {
+        return false;
+    }
+    let is_set = |name: &str| var(name).is_some_and(|value| !value.is_empty());
+    var("TERM_PROGRAM").is_some_and(|program| HYPERLINK_TERMINALS.contains(&program.as_str()))
+        || HYPERLINK_TERMS.iter().any(|prefix| term.starts_with(prefix))
+        || var("VTE_VERSION").and_then(|v| v.parse::<u32>().ok()).is_some_and(|version| version >= 5000)
+        || is_set("WT_SESSION")
+        || is_set("KONSOLE_VERSION")
+        || is_set("DOMTERM")
+}
+
+/// Whether the effective locale (`LC_ALL`, then `LC_CTYPE`, then `LANG`) uses UTF-8
+///
+/// Windows consoles don't set these; they are assumed to cope with UTF-8.
+pub fn locale_is_utf8(var: &dyn Fn(&str) -> Option<String>) -> bool {
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .into_iter()
+        .filter_map(var)
+        .find(|value| !value.is_empty());
+    match locale {
+        Some(locale) => {
+            let locale = locale.to_ascii_lowercase();
+            locale.contains("utf-8") || locale.contains("utf8")
+        }
+        None => cfg!(windows),
+    }
+}
+
+/// The current rendering of `glyph`
+pub fn glyph(glyph: Glyph) -> &'static str {
+    glyph.render(is_ascii())
+}
+
+/// A horizontal rule `width` columns wide
+pub fn rule(width: usize) -> String {
+    glyph(Glyph::Rule).repeat(width)
+}
+
+/// A list bullet
+pub fn bullet() -> &'static str {
+    glyph(Glyph::Bullet)
+}
+
+/// An arrow between two things, e.g. a rename or a call chain
+pub fn arrow() -> &'static str {
+    glyph(Glyph::Arrow)
+}
+
+/// `text` prefixed with `icon`, or just `text` when the icon has no ASCII form
+pub fn heading(icon: Glyph, text: &str) -> String {
+    match glyph(icon) {
+        "" => text.to_string(),
+        icon => format!("{icon} {text}"),
+    }
+}
+
+/// `text` as a hyperlink to `path` (at `line`) when hyperlinks are on, else `text` as is
+///
+/// `path` may be relative to the current directory.
+pub fn link(text: impl std::fmt::Display, path: &str, line: Option<usize>) -> String {
+    if !hyperlinks_enabled() {
+        return text.to_string();
+    }
+    let Ok(absolute) = std::path::absolute(path) else {
+        return text.to_string();
+    };
+    let format = LINK_FORMAT.read().ok().and_then(|format| format.clone());
+    let url = hyperlink_url(format.as_deref(), &absolute.to_string_lossy(), line);
+    format!("\x1b]8;;{url}\x1b\\{text}\x1b]8;;\x1b\\")
+}
+
+/// The URL a hyperlink to the absolute `path` opens
+///
+/// `format` is a template such as `vscode://file/{path}:{line}`: `{path}` is the
+/// path with `/` separators and no leading `/` (`home/me/a.rs`, `C:/src/a.rs`),
+/// percent-encoded; `{line}` and `{column}` default to 1. Without a template
+/// the link is a `file:///{path}` URL.
+pub fn hyperlink_url(format: Option<&str>, path: &str, line: Option<usize>) -> String {
+    let path = url_path(path);
+    match format {
+        Some(format) => format
+            .replace("{path}", &path)
+            .replace("{line}", &line.unwrap_or(1).to_string())
+            .replace("{column}", "1"),
+        None => format!("file:///{path}"),
+    }
+}
+
+/// `path` for a URL: `/` separators, one leading `/` dropped, and every byte
+/// but unreserved characters, `/` and a drive letter's `:` percent-encoded
+fn url_path(path: &str) -> String {
+    let mut path = path.replace('\\', "/");
+    // Windows verbatim paths: `\\?\C:\x` and `\\?\UNC\server\share`
+    if let Some(unc) = path.strip_prefix("//?/UNC/") {
+        path = format!("//{unc}");
+    } else if let Some(local) = path.strip_prefix("//?/") {
+        path = local.to_string();
+    }
+    let path = path.strip_prefix('/').unwrap_or(&path);
+    let is_drive = |i: usize| i == 1 && path.as_bytes()[0].is_ascii_alphabetic();
+
+    let mut encoded = String::with_capacity(path.len());
+    for (i, byte) in path.bytes().enumerate() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~' | b'/') || (byte == b':' && is_drive(i)) {
+            encoded.push(byte as char);
+        } else {
+            let _ = write!(encoded, "%{byte:02X}");
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    const ALL: [Glyph; 24] = [
+        Glyph::Rule, Glyph::Bullet, Glyph::Arrow, Glyph::Up, Glyph::Down, Glyph::Bar,
+        Glyph::Separator, Glyph::Empty, Glyph::File, Glyph::Stats, Glyph::Summary,
+        Glyph::Duplicate, Glyph::Hint, Glyph::Bookmark, Glyph::Warning, Glyph::Critical,
+        Glyph::Ok, Glyph::Refactor, Glyph::Group, Glyph::Languages, Glyph::Search,
+        Glyph::Goodbye, Glyph::Help, Glyph::Toggles,
+    ];
+
+    #[test]
+    fn test_ascii_glyphs_are_ascii() {
+        for glyph in ALL {
+            assert!(glyph.render(true).is_ascii(), "{glyph:?}");
+            assert!(!glyph.render(false).is_ascii(), "{glyph:?}");
+        }
+    }
+
+    #[test]
+    fn test_locale_detection() {
+        let env = |pairs: &'static [(&'static str, &'static str)]| {
+            move |name: &str| pairs.iter().find(|(k, _)| *k == name).map(|(_, v)| v.to_string())
+        };
+        assert!(locale_is_utf8(&env(&[("LANG", "en_US.UTF-8")])));
+        assert!(locale_is_utf8(&env(&[("LC_CTYPE", "de_DE.utf8"), ("LANG", "C")])));
+        // LC_ALL wins over LANG
+        assert!(!locale_is_utf8(&env(&[("LC_ALL", "C"), ("LANG", "en_US.UTF-8")])));
+        // Empty values are skipped
+        assert!(locale_is_utf8(&env(&[("LC_ALL", ""), ("LANG", "en_US.UTF-8")])));
+        assert!(!locale_is_utf8(&env(&[("LANG", "en_US.ISO-8859-1")])));
+        assert_eq!(locale_is_utf8(&env(&[])), cfg!(windows));
+    }
+
+    #[test]
+    fn test_hyperlink_urls() {
+        assert_eq!(hyperlink_url(None, "/home/me/src/main.rs", Some(3)), "file:///home/me/src/main.rs");
+        assert_eq!(
+            hyperlink_url(None, "/home/me/my docs/#1 100%.rs", None),
+            "file:///home/me/my%20docs/%231%20100%25.rs"
+        );
+        assert_eq!(hyperlink_url(None, "/tmp/caf\u{e9}.rs", None), "file:///tmp/caf%C3%A9.rs");
+
+        // Windows drive letters keep their colon; other colons are encoded
+        assert_eq!(hyperlink_url(None, r"C:\Users\me\main.rs", None), "file:///C:/Users/me/main.rs");
+        assert_eq!(hyperlink_url(None, r"\\?\D:\work\a:b.rs", None), "file:///D:/work/a%3Ab.rs");
+
+        let vscode = Some("vscode://file/{path}:{line}:{column}");
+        assert_eq!(hyperlink_url(vscode, "/srv/app/lib.rs", Some(42)), "vscode://file/srv/app/lib.rs:42:1");
+        assert_eq!(hyperlink_url(vscode, r"C:\a b\x.rs", None), "vscode://file/C:/a%20b/x.rs:1:1");
+    }
+
+    #[test]
+    fn test_hyperlink_decision() {
+        let env = |pairs: &'static [(&'static str, &'static str)]| {
+            move |name: &str| pairs.iter().find(|(k, _)| *k == name).map(|(_, v)| v.to_string())
+        };
+        let iterm = env(&[("TERM_PROGRAM", "iTerm.app")]);
+        assert!(should_hyperlink(HyperlinkMode::Auto, true, &iterm));
+        // Pipes and redirects never get escape sequences, even with `always`
+        assert!(!should_hyperlink(HyperlinkMode::Auto, false, &iterm));
+        assert!(!should_hyperlink(HyperlinkMode::Always, false, &iterm));
+        assert!(!should_hyperlink(HyperlinkMode::Never, true, &iterm));
+
+        let unknown = env(&[("TERM", "xterm-256color")]);
+        assert!(!should_hyperlink(HyperlinkMode::Auto, true, &unknown));
+        assert!(should_hyperlink(HyperlinkMode::Always, true, &unknown));
+
+        assert!(terminal_supports_hyperlinks(&env(&[("TERM", "xterm-kitty")])));
+        assert!(terminal_supports_hyperlinks(&env(&[("VTE_VERSION", "6800")])));
+        assert!(!terminal_supports_hyperlinks(&env(&[("VTE_VERSION", "4600")])));
+        assert!(terminal_supports_hyperlinks(&env(&[("WT_SESSION", "abc")])));
+        assert!(!terminal_supports_hyperlinks(&env(&[("TERM", "dumb"), ("WT_SESSION", "abc")])));
+        assert!(!terminal_supports_hyperlinks(&env(&[])));
+
+        assert_eq!("Never".parse::<HyperlinkMode>(), Ok(HyperlinkMode::Never));
+        assert!("sometimes".parse::<HyperlinkMode>().is_err());
+    }
+
+    #[test]
+    fn test_link_is_plain_when_disabled() {
+        set_hyperlinks(false, None);
+        assert_eq!(link("src/main.rs", "src/main.rs", Some(1)), "src/main.rs");
+    }
+
+    /// Decorative symbols must go through [`Glyph`] so ASCII mode covers them;
+    /// a raw non-ASCII literal in output code would leak past `--no-emoji`.
+    #[test]
+    fn test_no_raw_non_ascii_in_output_code() {
+        // Searched content, not decoration
+        const ALLOWED: [&str; 1] = ["pub const ELLIPSIS"];
+
+        fn visit(dir: &Path, offenders: &mut Vec<String>) {
+            for entry in std::fs::read_dir(dir).unwrap() {
+                let path = entry.unwrap().path();
+                if path.is_dir() {
+                    visit(&path, offenders);
+                    continue;
+                }
+                let name = path.file_name().unwrap().to_string_lossy();
+                if !name.ends_with(".rs") || name.ends_with("_tests.rs") || name == "theme.rs" {
+                    continue;
+                }
+                let source = std::fs::read_to_string(&path).unwrap();
+                for (n, line) in source.lines().enumerate() {
+                    let code = line.trim_start();
+                    if code.starts_with("#[cfg(test)]") {
+                        break;
+                    }
+                    if code.starts_with("//") || code.is_ascii() || ALLOWED.iter().any(|a| code.starts_with(a)) {
+                        continue;
+                    }
+                    offenders.push(format!("{}:{}: {}", path.display(), n + 1, code));
+                }
+            }
+        }
+
+        let mut offenders = Vec::new();
+        visit(&Path::new(env!("CARGO_MANIFEST_DIR")).join("src"), &mut offenders);
+        assert!(offenders.is_empty(), "use crate::theme instead of:\n{}", offenders.join("\n"));
+    }
+}
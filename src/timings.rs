@@ -0,0 +1,170 @@
+//! Phase Timings
+//!
+//! Per-phase wall-clock timing for the analysis commands (`--verbose`). Phases
+//! timed on rayon workers are summed with atomics, so a parallel phase reports
+//! the time spent across all workers. A disabled recorder never reads the clock.
+
+use crate::theme;
+use colored::*;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// A named step of an analysis
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// Walking the tree for candidate files
+    Walk,
+    /// Reading file contents
+    Read,
+    /// Per-file extraction and metric computation
+    Extract,
+    /// Cross-file matching (dead-code references, similarity)
+    Compare,
+    /// Rolling per-file results up into totals and summaries
+    Aggregate,
+    /// Formatting the output
+    Render,
+}
+
+impl Phase {
+    pub const ALL: [Phase; 6] = [Phase::Walk, Phase::Read, Phase::Extract, Phase::Compare, Phase::Aggregate, Phase::Render];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Phase::Walk => "walk",
+            Phase::Read => "read",
+            Phase::Extract => "extract",
+            Phase::Compare => "compare",
+            Phase::Aggregate => "aggregate",
+            Phase::Render => "render",
+        }
+    }
+}
+
+/// Time recorded for one phase
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PhaseTiming {
+    pub phase: &'static str,
+    pub ms: f64,
+}
+
+/// `meta` section added to JSON documents when timings were recorded
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Meta {
+    pub timings: Vec<PhaseTiming>,
+}
+
+/// Recorder of per-phase durations, shared by reference across workers
+#[derive(Debug, Default)]
+pub struct Timings {
+    enabled: bool,
+    nanos: [AtomicU64; Phase::ALL.len()],
+}
+
+impl Timings {
+    /// A recorder that keeps timings
+    pub fn enabled() -> Self {
+        Self {
+            enabled: true,
+            ..Self::default()
+        }
+    }
+
+    /// A recorder that ignores everything (the default)
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Run `f`, adding its duration to `phase`
+    pub fn time<T>(&self, phase: Phase, f: impl FnOnce() -> T) -> T {
+        if !self.enabled {
+            return f();
+        }
+        let start = Instant::now();
+        let value = f();
+        self.add(phase, start.elapsed());
+        value
+    }
+
+    /// Add `duration` to `phase`
+    pub fn add(&self, phase: Phase, duration: Duration) {
+        if self.enabled {
+            let nanos = u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+            self.nanos[phase as usize].fetch_add(nanos, Ordering::Relaxed);
+        }
+    }
+
+    /// Time recorded so far for `phase`
+    pub fn get(&self, phase: Phase) -> Duration {
+        Duration::from_nanos(self.nanos[phase as usize].load(Ordering::Relaxed))
+    }
+
+    /// Sum of all phases
+    pub fn total(&self) -> Duration {
+        Phase::ALL.iter().map(|&phase| self.get(phase)).sum()
+    }
+
+    /// Every phase in order, in milliseconds
+    pub fn phases(&self) -> Vec<PhaseTiming> {
+        Phase::ALL
+            .iter()
+            .map(|&phase| PhaseTiming {
+                phase: phase.name(),
+                ms: self.get(phase).as_secs_f64() * 1000.0,
+            })
+            .collect()
+    }
+
+    /// The JSON `meta` section, if timings were recorded
+    pub fn meta(&self) -> Option<Meta> {
+        self.enabled.then(|| Meta { timings: self.phases() })
+    }
+
+    /// Print the timings as a table on stderr
+    pub fn print_table(&self) {
+        if !self.enabled {
+            return;
+        }
+        let total = self.total().as_secs_f64() * 1000.0;
+        eprintln!("\n{}", "Phase Timings".cyan().bold());
+        eprintln!("{}", theme::rule(36).cyan());
+        for timing in self.phases() {
+            let share = if total > 0.0 { timing.ms / total * 100.0 } else { 0.0 };
+            eprintln!("  {:<10} {:>10.2} ms {:>6.1}%", timing.phase, timing.ms, share);
+        }
+        eprintln!("  {:<10} {:>10.2} ms", "total".bold(), total);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rayon::prelude::*;
+
+    #[test]
+    fn test_disabled_records_nothing() {
+        let timings = Timings::disabled();
+        assert_eq!(timings.time(Phase::Read, || 7), 7);
+        timings.add(Phase::Walk, Duration::from_millis(5));
+        assert_eq!(timings.total(), Duration::ZERO);
+        assert!(timings.meta().is_none());
+    }
+
+    #[test]
+    fn test_phases_sum_across_workers() {
+        let timings = Timings::enabled();
+        (0..8).into_par_iter().for_each(|_| timings.add(Phase::Extract, Duration::from_millis(3)));
+        timings.add(Phase::Walk, Duration::from_millis(1));
+        assert_eq!(timings.get(Phase::Extract), Duration::from_millis(24));
+        assert_eq!(timings.total(), Duration::from_millis(25));
+
+        let meta = timings.meta().unwrap();
+        assert_eq!(meta.timings.len(), Phase::ALL.len());
+        assert_eq!(meta.timings[0], PhaseTiming { phase: "walk", ms: 1.0 });
+    }
+}
@@ -0,0 +1,606 @@
+//! Shared Types Module
+//!
+//! Common data structures used across the codebase search tool: search
+//! options and outcomes here, result types in [`results`].
+
+mod results;
+
+pub use results::*;
+
+use crate::encoding::Encoding;
+use crate::index::CodeIndex;
+use crate::search::ResultProcessor;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Version of the JSON output schema emitted by the CLI and MCP server
+///
+/// Every top-level JSON document carries this value in a `schema_version` field.
+/// Bump rules:
+/// - MAJOR: a field is removed or renamed, or its type or meaning changes
+/// - MINOR: a field or a new document type is added
+/// - PATCH: documentation or value-formatting fixes with no shape change
+///
+/// A MAJOR bump must add a conversion in `crate::schema` so the previous shape
+/// can still be requested with `--schema-version`.
+pub const SCHEMA_VERSION: &str = "1.31.0";
+
+/// Marks where a long line was cut
+pub const ELLIPSIS: &str = "…";
+
+/// Lines longer than this many bytes are reported as a window around the first match
+pub const LONG_LINE_BYTES: usize = 1000;
+
+/// Characters of a long line kept on each side of its first match
+pub const LONG_LINE_WINDOW: usize = 100;
+
+/// Search options to bundle related parameters
+#[derive(Debug, Clone)]
+pub struct SearchOptions {
+    pub extensions: Option<Vec<String>>,
+    pub ignore_case: bool,
+    pub fuzzy: bool,
+    pub fuzzy_threshold: f64,
+    /// Match the query as plain text instead of a regex
+    pub literal: bool,
+    /// Only match the query as a whole word
+    pub word_boundary: bool,
+    /// Maximum number of matching lines reported per file
+    pub max_results: usize,
+    /// Maximum number of results in total, applied after ranking
+    pub max_total: Option<usize>,
+    pub exclude: Option<Vec<String>>,
+    /// Skip paths matched by `.gitignore` files
+    pub gitignore: bool,
+    pub rank: bool,
+    pub cache: bool,
+    pub semantic: bool,
+    pub benchmark: bool,
+    pub vs_grep: bool,
+    pub engine: RegexEngine,
+    /// Lines of context reported around each match
+    pub context: ContextLines,
+    /// Match against whole files, so a pattern can span lines
+    pub multiline: bool,
+    /// Files larger than this are skipped in multiline mode
+    pub multiline_max_bytes: u64,
+    /// How file contents are decoded (UTF-16 files are detected by their byte order mark)
+    pub encoding: Encoding,
+    /// Index consulted to skip files that can't contain the query
+    pub index: Option<Arc<CodeIndex>>,
+    /// Weights of the relevance score, used with `rank`
+    pub score_weights: ScoreWeights,
+    /// Run over the results, in order, after ranking and before they are returned
+    /// (or, when streaming, before each is handed over); see [`ResultProcessor`]
+    pub post_processors: Vec<Arc<dyn ResultProcessor>>,
+}
+
+/// Default for [`SearchOptions::multiline_max_bytes`]
+pub const DEFAULT_MULTILINE_MAX_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Default for [`SearchOptions::max_results`] in the CLI and [`SearchOptions::builder`]
+pub const DEFAULT_MAX_RESULTS: usize = 10;
+
+/// Default for [`SearchOptions::fuzzy_threshold`] in the CLI and [`SearchOptions::builder`]
+pub const DEFAULT_FUZZY_THRESHOLD: f64 = 0.6;
+
+/// Number of surrounding lines reported with each match
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ContextLines {
+    pub before: usize,
+    pub after: usize,
+}
+
+impl ContextLines {
+    pub fn new(before: usize, after: usize) -> Self {
+        Self { before, after }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.before == 0 && self.after == 0
+    }
+}
+
+/// How much each boost of the relevance score adds (see
+/// [`calculate_relevance_score`](crate::search::calculate_relevance_score))
+///
+/// Scores start at 50 and are capped at 100, so large weights mostly decide
+/// which results reach the cap.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScoreWeights {
+    /// The line contains the query as typed
+    pub content_match: f64,
+    /// The line is among the first 100 of its file
+    pub early_line: f64,
+    /// The file is Rust, Python, JavaScript or TypeScript source
+    pub source_file: f64,
+    /// The file is Markdown or plain text
+    pub text_file: f64,
+    /// The line defines something (`fn`, `def`, `class`, ...)
+    pub definition: f64,
+    /// The name defined on the line is exactly the query
+    pub definition_name: f64,
+    /// The file name without its extension contains the query, ignoring case
+    pub file_stem: f64,
+    /// A directory on the file's path contains the query, ignoring case
+    pub directory: f64,
+}
+
+impl Default for ScoreWeights {
+    fn default() -> Self {
+        Self {
+            content_match: 30.0,
+            early_line: 5.0,
+            source_file: 10.0,
+            text_file: 5.0,
+            definition: 15.0,
+            definition_name: 20.0,
+            file_stem: 10.0,
+            directory: 5.0,
+        }
+    }
+}
+
+/// Regex engine used to compile search patterns
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RegexEngine {
+    /// The `regex` crate: linear time, no lookaround or backreferences
+    #[default]
+    Default,
+    /// `fancy-regex`: adds lookaround, backreferences and possessive quantifiers
+    /// (requires the `fancy` feature)
+    Fancy,
+}
+
+impl std::str::FromStr for RegexEngine {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "default" | "regex" => Ok(Self::Default),
+            "fancy" => Ok(Self::Fancy),
+            other => Err(format!("Unknown regex engine: {other} (expected: default, fancy)")),
+        }
+    }
+}
+
+/// How ranked search results are ordered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RankGroup {
+    /// Files by their best score, each file's results together in line order
+    File,
+    /// One flat list by score, interleaving files
+    None,
+}
+
+/// Deterministic order for search results (`--sort-by`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortKey {
+    /// By file path, then line
+    Path,
+}
+
+impl std::str::FromStr for RankGroup {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "file" => Ok(Self::File),
+            "none" => Ok(Self::None),
+            other => Err(format!("Unknown rank grouping: {other} (expected: file, none)")),
+        }
+    }
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            extensions: None,
+            ignore_case: false,
+            fuzzy: false,
+            fuzzy_threshold: DEFAULT_FUZZY_THRESHOLD,
+            literal: false,
+            word_boundary: false,
+            max_results: 100,
+            max_total: None,
+            exclude: None,
+            gitignore: true,
+            rank: false,
+            cache: false,
+            semantic: false,
+            benchmark: false,
+            vs_grep: false,
+            engine: RegexEngine::Default,
+            context: ContextLines::default(),
+            multiline: false,
+            multiline_max_bytes: DEFAULT_MULTILINE_MAX_BYTES,
+            encoding: Encoding::Auto,
+            index: None,
+            score_weights: ScoreWeights::default(),
+            post_processors: Vec::new(),
+        }
+    }
+}
+
+impl SearchOptions {
+    /// Start building options from the `codesearch search` defaults
+    ///
+    /// Unlike [`SearchOptions::default`], which keeps its historical limits, the
+    /// builder starts from what the CLI uses when no flags are given.
+    pub fn builder() -> SearchOptionsBuilder {
+        SearchOptionsBuilder::default()
+    }
+
+    /// Builder pattern: set extensions
+    pub fn with_extensions(mut self, extensions: Vec<String>) -> Self {
+        self.extensions = Some(extensions);
+        self
+    }
+
+    /// Builder pattern: set ignore_case
+    pub fn with_ignore_case(mut self, ignore_case: bool) -> Self {
+        self.ignore_case = ignore_case;
+        self
+    }
+
+    /// Builder pattern: set fuzzy
+    pub fn with_fuzzy(mut self, fuzzy: bool) -> Self {
+        self.fuzzy = fuzzy;
+        self
+    }
+
+    /// Builder pattern: set fuzzy_threshold
+    pub fn with_fuzzy_threshold(mut self, threshold: f64) -> Self {
+        self.fuzzy_threshold = threshold;
+        self
+    }
+
+    /// Builder pattern: set literal
+    pub fn with_literal(mut self, literal: bool) -> Self {
+        self.literal = literal;
+        self
+    }
+
+    /// Builder pattern: set word_boundary
+    pub fn with_word_boundary(mut self, word_boundary: bool) -> Self {
+        self.word_boundary = word_boundary;
+        self
+    }
+
+    /// Builder pattern: set max_results
+    pub fn with_max_results(mut self, max: usize) -> Self {
+        self.max_results = max;
+        self
+    }
+
+    /// Builder pattern: set max_total
+    pub fn with_max_total(mut self, max: usize) -> Self {
+        self.max_total = Some(max);
+        self
+    }
+
+    /// Builder pattern: set exclude
+    pub fn with_exclude(mut self, exclude: Vec<String>) -> Self {
+        self.exclude = Some(exclude);
+        self
+    }
+
+    /// Builder pattern: set gitignore
+    pub fn with_gitignore(mut self, gitignore: bool) -> Self {
+        self.gitignore = gitignore;
+        self
+    }
+
+    /// Builder pattern: set rank
+    pub fn with_rank(mut self, rank: bool) -> Self {
+        self.rank = rank;
+        self
+    }
+
+    /// Builder pattern: set cache
+    pub fn with_cache(mut self, cache: bool) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Builder pattern: set semantic
+    pub fn with_semantic(mut self, semantic: bool) -> Self {
+        self.semantic = semantic;
+        self
+    }
+
+    /// Builder pattern: set benchmark
+    pub fn with_benchmark(mut self, benchmark: bool) -> Self {
+        self.benchmark = benchmark;
+        self
+    }
+
+    /// Builder pattern: set vs_grep
+    pub fn with_vs_grep(mut self, vs_grep: bool) -> Self {
+        self.vs_grep = vs_grep;
+        self
+    }
+
+    /// Builder pattern: set engine
+    pub fn with_engine(mut self, engine: RegexEngine) -> Self {
+        self.engine = engine;
+        self
+    }
+
+    /// Builder pattern: set multiline
+    pub fn with_multiline(mut self, multiline: bool) -> Self {
+        self.multiline = multiline;
+        self
+    }
+
+    /// Builder pattern: set multiline_max_bytes
+    pub fn with_multiline_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.multiline_max_bytes = max_bytes;
+        self
+    }
+
+    /// Builder pattern: set encoding
+    pub fn with_encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Builder pattern: set context
+    pub fn with_context(mut self, context: ContextLines) -> Self {
+        self.context = context;
+        self
+    }
+
+    /// Builder pattern: set index
+    pub fn with_index(mut self, index: Arc<CodeIndex>) -> Self {
+        self.index = Some(index);
+        self
+    }
+
+    /// Builder pattern: set score_weights
+    pub fn with_score_weights(mut self, weights: ScoreWeights) -> Self {
+        self.score_weights = weights;
+        self
+    }
+
+    /// Score weights when results are ranked, `None` without `rank`
+    pub fn ranking(&self) -> Option<&ScoreWeights> {
+        self.rank.then_some(&self.score_weights)
+    }
+
+    /// Builder pattern: add a post-processor, run after those already added
+    pub fn with_post_processor(mut self, processor: impl ResultProcessor + 'static) -> Self {
+        self.post_processors.push(Arc::new(processor));
+        self
+    }
+}
+
+/// Builder for [`SearchOptions`], started with [`SearchOptions::builder`]
+///
+/// ```
+/// use codesearch::SearchOptions;
+///
+/// let options = SearchOptions::builder().fuzzy(true).rank(true).build();
+/// assert!(options.fuzzy && options.rank);
+/// assert_eq!(options.max_results, codesearch::types::DEFAULT_MAX_RESULTS);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SearchOptionsBuilder {
+    options: SearchOptions,
+}
+
+impl Default for SearchOptionsBuilder {
+    fn default() -> Self {
+        Self {
+            options: SearchOptions {
+                max_results: DEFAULT_MAX_RESULTS,
+                fuzzy_threshold: DEFAULT_FUZZY_THRESHOLD,
+                ..SearchOptions::default()
+            },
+        }
+    }
+}
+
+impl SearchOptionsBuilder {
+    pub fn extensions(mut self, extensions: impl Into<Option<Vec<String>>>) -> Self {
+        self.options.extensions = extensions.into();
+        self
+    }
+
+    pub fn ignore_case(mut self, ignore_case: bool) -> Self {
+        self.options.ignore_case = ignore_case;
+        self
+    }
+
+    pub fn fuzzy(mut self, fuzzy: bool) -> Self {
+        self.options.fuzzy = fuzzy;
+        self
+    }
+
+    pub fn fuzzy_threshold(mut self, fuzzy_threshold: f64) -> Self {
+        self.options.fuzzy_threshold = fuzzy_threshold;
+        self
+    }
+
+    pub fn literal(mut self, literal: bool) -> Self {
+        self.options.literal = literal;
+        self
+    }
+
+    pub fn word_boundary(mut self, word_boundary: bool) -> Self {
+        self.options.word_boundary = word_boundary;
+        self
+    }
+
+    pub fn max_results(mut self, max_results: usize) -> Self {
+        self.options.max_results = max_results;
+        self
+    }
+
+    pub fn max_total(mut self, max_total: impl Into<Option<usize>>) -> Self {
+        self.options.max_total = max_total.into();
+        self
+    }
+
+    pub fn exclude(mut self, exclude: impl Into<Option<Vec<String>>>) -> Self {
+        self.options.exclude = exclude.into();
+        self
+    }
+
+    pub fn gitignore(mut self, gitignore: bool) -> Self {
+        self.options.gitignore = gitignore;
+        self
+    }
+
+    pub fn rank(mut self, rank: bool) -> Self {
+        self.options.rank = rank;
+        self
+    }
+
+    pub fn cache(mut self, cache: bool) -> Self {
+        self.options.cache = cache;
+        self
+    }
+
+    pub fn semantic(mut self, semantic: bool) -> Self {
+        self.options.semantic = semantic;
+        self
+    }
+
+    pub fn benchmark(mut self, benchmark: bool) -> Self {
+        self.options.benchmark = benchmark;
+        self
+    }
+
+    pub fn vs_grep(mut self, vs_grep: bool) -> Self {
+        self.options.vs_grep = vs_grep;
+        self
+    }
+
+    pub fn engine(mut self, engine: RegexEngine) -> Self {
+        self.options.engine = engine;
+        self
+    }
+
+    pub fn context(mut self, context: ContextLines) -> Self {
+        self.options.context = context;
+        self
+    }
+
+    pub fn multiline(mut self, multiline: bool) -> Self {
+        self.options.multiline = multiline;
+        self
+    }
+
+    pub fn multiline_max_bytes(mut self, multiline_max_bytes: u64) -> Self {
+        self.options.multiline_max_bytes = multiline_max_bytes;
+        self
+    }
+
+    pub fn encoding(mut self, encoding: Encoding) -> Self {
+        self.options.encoding = encoding;
+        self
+    }
+
+    pub fn index(mut self, index: impl Into<Option<Arc<CodeIndex>>>) -> Self {
+        self.options.index = index.into();
+        self
+    }
+
+    pub fn score_weights(mut self, weights: ScoreWeights) -> Self {
+        self.options.score_weights = weights;
+        self
+    }
+
+    /// Add a post-processor, run after those already added
+    pub fn post_processor(mut self, processor: impl ResultProcessor + 'static) -> Self {
+        self.options.post_processors.push(Arc::new(processor));
+        self
+    }
+
+    pub fn build(self) -> SearchOptions {
+        self.options
+    }
+}
+
+/// Which result caps truncated a search
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LimitReached {
+    /// At least one file had more matching lines than `max_results`
+    pub per_file: bool,
+    /// More results were found than `max_total`
+    pub total: bool,
+}
+
+impl LimitReached {
+    pub fn any(&self) -> bool {
+        self.per_file || self.total
+    }
+
+    /// Combine the flags of two searches
+    pub fn merge(self, other: Self) -> Self {
+        Self {
+            per_file: self.per_file || other.per_file,
+            total: self.total || other.total,
+        }
+    }
+}
+
+/// Search results together with which limits truncated them
+#[derive(Debug, Clone, Default)]
+pub struct SearchOutcome {
+    pub results: Vec<SearchResult>,
+    pub limit_reached: LimitReached,
+    pub metrics: SearchMetrics,
+    /// Set when `vs_grep` was requested and grep could be run
+    pub grep_comparison: Option<GrepComparison>,
+    /// Results dropped by `max_total`; a lower bound when `files_skipped`
+    pub truncated: usize,
+    /// Files were left unsearched once the results `max_total` keeps were known
+    pub files_skipped: bool,
+}
+
+/// What a streaming search covered, returned once it has finished or was stopped
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StreamSummary {
+    /// Files searched, including any in flight when the search was stopped
+    pub files_processed: usize,
+    /// At least one file had more matching lines than `max_results`
+    pub per_file_limit: bool,
+    /// The callback returned `Break` before the search finished
+    pub stopped: bool,
+    /// Files were skipped because `max_total` was certain to be exceeded
+    /// (never set by `search_code_streaming`, which doesn't apply `max_total`)
+    pub files_skipped: bool,
+    /// Files not opened because the index showed they can't contain the query
+    pub files_ruled_out: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_options_builder() {
+        let options = SearchOptions::builder()
+            .extensions(vec!["rs".to_string()])
+            .fuzzy(true)
+            .rank(true)
+            .max_total(5)
+            .context(ContextLines::new(1, 2))
+            .build();
+        assert_eq!(options.extensions, Some(vec!["rs".to_string()]));
+        assert!(options.fuzzy && options.rank);
+        assert_eq!(options.max_total, Some(5));
+        assert_eq!(options.context, ContextLines::new(1, 2));
+        // Unset fields keep the CLI defaults
+        assert_eq!(options.max_results, DEFAULT_MAX_RESULTS);
+        assert_eq!(options.fuzzy_threshold, DEFAULT_FUZZY_THRESHOLD);
+        assert!(!options.cache && !options.semantic && options.gitignore);
+
+        let cleared = SearchOptions::builder().max_total(5).max_total(None::<usize>).build();
+        assert_eq!(cleared.max_total, None);
+    }
+}
+
@@ -0,0 +1,763 @@
+//! Result types returned by searches and analyses
+//!
+//! Every type serializes to the JSON documented in the README, deserializes
+//! back for tools reading that output, derives `JsonSchema` when the
+//! `schemars` dependency is enabled (the `mcp` feature), and has a one-line
+//! `Display` for plain-text output.
+
+use super::ELLIPSIS;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// A search result containing match information
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct SearchResult {
+    pub file: String,
+    pub line_number: usize,
+    pub content: String,
+    pub matches: Vec<Match>,
+    pub score: f64,
+    pub relevance: String,
+    /// Last line of a match spanning several lines (multiline search); `content`
+    /// then holds every line from `line_number` to here
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_line_number: Option<usize>,
+    /// Lines just before the match, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub context_before: Vec<String>,
+    /// Lines just after the match
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub context_after: Vec<String>,
+    /// The line was longer than [`LONG_LINE_BYTES`](super::LONG_LINE_BYTES) (e.g. minified code), so `content`
+    /// is a window around the first match, with `…` where it was cut, and match
+    /// texts are cut to [`LONG_LINE_WINDOW`](super::LONG_LINE_WINDOW) characters (schema 1.16+)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub line_truncated: bool,
+    /// With `line_truncated`, the byte offset in the original line where the window
+    /// starts (after any leading `…`). Match offsets stay relative to the original line.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_offset: Option<usize>,
+    /// Notes attached by [`ResultProcessor`](crate::search::ResultProcessor)s, e.g.
+    /// `owners`; passed through to JSON as is (schema 1.27+)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub annotations: BTreeMap<String, String>,
+}
+
+impl SearchResult {
+    /// Byte range of `m` within `content`
+    ///
+    /// For a truncated line the range is clipped to the window, and `None` when
+    /// the match lies outside it.
+    pub fn content_range(&self, m: &Match) -> Option<(usize, usize)> {
+        let Some(offset) = self.content_offset else {
+            return (m.start <= m.end && m.end <= self.content.len()).then_some((m.start, m.end));
+        };
+        let prefix = if offset > 0 { ELLIPSIS.len() } else { 0 };
+        let window_end = if self.content.ends_with(ELLIPSIS) {
+            self.content.len() - ELLIPSIS.len()
+        } else {
+            self.content.len()
+        };
+        let start = (m.start.checked_sub(offset)? + prefix).min(window_end);
+        let end = (m.end.saturating_sub(offset) + prefix).min(window_end);
+        (start < end || m.start == m.end).then_some((start, end))
+    }
+}
+
+/// A single match within a line
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Match {
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+    /// Which top-level alternative of the query matched (`foo|bar`: 0 or 1);
+    /// always 0 for queries without alternation, and left out of JSON when 0
+    #[serde(default, skip_serializing_if = "is_first_pattern")]
+    pub pattern_index: usize,
+}
+
+fn is_first_pattern(pattern_index: &usize) -> bool {
+    *pattern_index == 0
+}
+
+/// File information with path, size, and line count
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct FileInfo {
+    pub path: String,
+    pub size: u64,
+    pub lines: usize,
+}
+
+/// Kind of refactoring suggestion, serialized as its display name (`"Long Line"`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum SuggestionType {
+    #[serde(rename = "Long Line")]
+    LongLine,
+    #[serde(rename = "Todo Comment")]
+    TodoComment,
+    #[serde(rename = "Magic Number")]
+    MagicNumber,
+    #[serde(rename = "Deep Nesting")]
+    DeepNesting,
+    #[serde(rename = "Empty Catch Block")]
+    EmptyCatchBlock,
+    #[serde(rename = "Complex Function")]
+    ComplexFunction,
+    #[serde(rename = "Trivial Delegation")]
+    TrivialDelegation,
+}
+
+impl SuggestionType {
+    /// Every suggestion type, in the order they are listed
+    pub const ALL: [Self; 7] = [
+        Self::LongLine,
+        Self::TodoComment,
+        Self::MagicNumber,
+        Self::DeepNesting,
+        Self::EmptyCatchBlock,
+        Self::ComplexFunction,
+        Self::TrivialDelegation,
+    ];
+
+    /// Display name, as in output, `--disable` and `[refactor] disabled_types`
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::LongLine => "Long Line",
+            Self::TodoComment => "Todo Comment",
+            Self::MagicNumber => "Magic Number",
+            Self::DeepNesting => "Deep Nesting",
+            Self::EmptyCatchBlock => "Empty Catch Block",
+            Self::ComplexFunction => "Complex Function",
+            Self::TrivialDelegation => "Trivial Delegation",
+        }
+    }
+}
+
+/// Parses a display name, ignoring case, spaces, `-` and `_` (`long-line` works too)
+impl std::str::FromStr for SuggestionType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let key = |name: &str| name.chars().filter(|c| !matches!(c, ' ' | '-' | '_')).collect::<String>().to_lowercase();
+        let wanted = key(s);
+        Self::ALL.into_iter().find(|kind| key(kind.name()) == wanted).ok_or_else(|| {
+            let names: Vec<&str> = Self::ALL.iter().map(|kind| kind.name()).collect();
+            format!("Unknown suggestion type: {s} (expected one of: {})", names.join(", "))
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for SuggestionType {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Characters of source kept in [`RefactorSuggestion::code_snippet`]
+pub const SNIPPET_CHARS: usize = 50;
+
+/// Refactoring suggestion with priority and improvement
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct RefactorSuggestion {
+    pub file: String,
+    pub line_number: usize,
+    pub suggestion_type: SuggestionType,
+    pub description: String,
+    pub priority: u8, // 1-10, 10 being highest priority
+    pub code_snippet: String,
+    pub improvement: String,
+}
+
+/// Code complexity metrics for a file
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ComplexityMetrics {
+    pub file_path: String,
+    pub cyclomatic_complexity: u32,
+    pub cognitive_complexity: u32,
+    pub lines_of_code: usize,
+    pub function_count: usize,
+    pub max_nesting_depth: u32,
+}
+
+/// Code complexity metrics for a single function
+///
+/// Nested named functions are measured on their own and left out of the
+/// enclosing function's metrics; closures count toward their enclosing function.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct FunctionComplexity {
+    pub name: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    pub cyclomatic_complexity: u32,
+    pub cognitive_complexity: u32,
+    /// Block nesting inside the body; 0 for a body without nested blocks
+    pub max_nesting_depth: u32,
+}
+
+impl fmt::Display for SearchResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.end_line_number {
+            Some(end) => write!(f, "{}:{}-{}: {}", self.file, self.line_number, end, self.content.trim()),
+            None => write!(f, "{}:{}: {}", self.file, self.line_number, self.content.trim()),
+        }
+    }
+}
+
+impl fmt::Display for Match {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} at {}..{}", self.text, self.start, self.end)
+    }
+}
+
+impl fmt::Display for FileInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({} lines, {} bytes)", self.path, self.lines, self.size)
+    }
+}
+
+impl fmt::Display for SuggestionType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl fmt::Display for RefactorSuggestion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: [priority {}] {}: {}",
+            self.file, self.line_number, self.priority, self.suggestion_type, self.description
+        )
+    }
+}
+
+impl fmt::Display for ComplexityMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: CC {}, COG {}, LOC {}, functions {}, nesting {}",
+            self.file_path,
+            self.cyclomatic_complexity,
+            self.cognitive_complexity,
+            self.lines_of_code,
+            self.function_count,
+            self.max_nesting_depth
+        )
+    }
+}
+
+impl fmt::Display for FunctionComplexity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (lines {}-{}): CC {}, COG {}, nesting {}",
+            self.name,
+            self.start_line,
+            self.end_line,
+            self.cyclomatic_complexity,
+            self.cognitive_complexity,
+            self.max_nesting_depth
+        )
+    }
+}
+
+/// Search performance metrics
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct SearchMetrics {
+    pub files_processed: usize,
+    pub total_lines_scanned: usize,
+    pub search_time_ms: u128,
+    pub parallel_workers: usize,
+    pub cache_hits: usize,
+    pub cache_misses: usize,
+    /// Files not opened because the index showed they can't contain the query
+    #[serde(default)]
+    pub files_ruled_out_by_index: usize,
+}
+
+impl SearchMetrics {
+    /// Combine the metrics of searches run one after another
+    pub fn merge(self, other: Self) -> Self {
+        Self {
+            files_processed: self.files_processed + other.files_processed,
+            total_lines_scanned: self.total_lines_scanned + other.total_lines_scanned,
+            search_time_ms: self.search_time_ms + other.search_time_ms,
+            parallel_workers: self.parallel_workers.max(other.parallel_workers),
+            cache_hits: self.cache_hits + other.cache_hits,
+            cache_misses: self.cache_misses + other.cache_misses,
+            files_ruled_out_by_index: self.files_ruled_out_by_index + other.files_ruled_out_by_index,
+        }
+    }
+}
+
+/// Timing of the same search run with `grep -rn`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct GrepComparison {
+    pub grep_time_ms: u128,
+    pub grep_matches: usize,
+    /// grep time divided by codesearch time; above 1.0 codesearch was faster
+    pub speedup: f64,
+}
+
+/// Summary of a result set, as shown by `--stats`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct SearchStats {
+    pub files_with_matches: usize,
+    /// Results, one per matching line or multiline match (schema 1.25+)
+    #[serde(default)]
+    pub matching_lines: usize,
+    /// Results, one per matching line or multiline match; the same as `matching_lines`
+    pub total_matches: usize,
+    /// Occurrences, counting each match on a line (schema 1.31+)
+    #[serde(default)]
+    pub total_occurrences: usize,
+    pub average_score: f64,
+    /// How many files have how many matches (schema 1.14+)
+    #[serde(default)]
+    pub files_by_match_count: MatchHistogram,
+    /// Matches per top-level directory, most matches first (schema 1.14+)
+    #[serde(default)]
+    pub directories: Vec<DirectoryMatches>,
+}
+
+/// Number of files per bucket of matches in the file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct MatchHistogram {
+    pub one: usize,
+    pub two_to_five: usize,
+    pub six_to_twenty: usize,
+    pub over_twenty: usize,
+}
+
+impl MatchHistogram {
+    /// Bucket labels and file counts, in order
+    pub fn buckets(&self) -> [(&'static str, usize); 4] {
+        [
+            ("1", self.one),
+            ("2-5", self.two_to_five),
+            ("6-20", self.six_to_twenty),
+            ("21+", self.over_twenty),
+        ]
+    }
+
+    fn add(&mut self, matches: usize) {
+        match matches {
+            0 => {}
+            1 => self.one += 1,
+            2..=5 => self.two_to_five += 1,
+            6..=20 => self.six_to_twenty += 1,
+            _ => self.over_twenty += 1,
+        }
+    }
+}
+
+/// Matches under one top-level directory of the searched tree
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct DirectoryMatches {
+    /// First path component below the directory all results share, or `.` for files directly in it
+    pub directory: String,
+    pub matches: usize,
+}
+
+impl SearchStats {
+    pub fn from_results(results: &[SearchResult]) -> Self {
+        let mut per_file: std::collections::BTreeMap<&str, usize> = std::collections::BTreeMap::new();
+        for result in results {
+            *per_file.entry(result.file.as_str()).or_default() += result.matches.len();
+        }
+        let total_occurrences = per_file.values().sum();
+        let average_score = if results.is_empty() {
+            0.0
+        } else {
+            results.iter().map(|r| r.score).sum::<f64>() / results.len() as f64
+        };
+
+        let mut files_by_match_count = MatchHistogram::default();
+        for &matches in per_file.values() {
+            files_by_match_count.add(matches);
+        }
+
+        Self {
+            files_with_matches: per_file.len(),
+            matching_lines: results.len(),
+            total_matches: results.len(),
+            total_occurrences,
+            average_score,
+            files_by_match_count,
+            directories: matches_by_directory(&per_file),
+        }
+    }
+}
+
+/// Sum per-file match counts by the first directory below the files' common ancestor
+fn matches_by_directory(per_file: &std::collections::BTreeMap<&str, usize>) -> Vec<DirectoryMatches> {
+    use std::path::{Component, Path};
+
+    let components = |file: &str| -> Vec<String> {
+        Path::new(file)
+            .components()
+            .filter(|c| !matches!(c, Component::CurDir))
+            .map(|c| c.as_os_str().to_string_lossy().into_owned())
+            .collect()
+    };
+    let paths: Vec<(Vec<String>, usize)> = per_file.iter().map(|(file, &n)| (components(file), n)).collect();
+
+    // Directories every file is under; the file name itself never counts
+    let mut common = paths.first().map_or(0, |(first, _)| first.len().saturating_sub(1));
+    for (path, _) in &paths {
+        let shared = paths[0].0.iter().zip(path).take_while(|(a, b)| a == b).count();
+        common = common.min(shared).min(path.len().saturating_sub(1));
+    }
+
+    let mut totals: Vec<DirectoryMatches> = Vec::new();
+    for (path, matches) in &paths {
+        let directory = if path.len() > common + 1 { path[common].clone() } else { ".".to_string() };
+        match totals.iter_mut().find(|d| d.directory == directory) {
+            Some(entry) => entry.matches += matches,
+            None => totals.push(DirectoryMatches { directory, matches: *matches }),
+        }
+    }
+    totals.sort_by(|a, b| b.matches.cmp(&a.matches).then_with(|| a.directory.cmp(&b.directory)));
+    totals
+}
+
+impl fmt::Display for SearchMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} files, {} lines in {} ms ({} workers, cache {} hits / {} misses)",
+            self.files_processed,
+            self.total_lines_scanned,
+            self.search_time_ms,
+            self.parallel_workers,
+            self.cache_hits,
+            self.cache_misses
+        )?;
+        if self.files_ruled_out_by_index > 0 {
+            write!(f, ", {} files ruled out by the index", self.files_ruled_out_by_index)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for GrepComparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "grep: {} matches in {} ms ({:.1}x)", self.grep_matches, self.grep_time_ms, self.speedup)
+    }
+}
+
+impl fmt::Display for SearchStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} matches on {} lines in {} files, average score {:.1}",
+            self.total_occurrences, self.total_matches, self.files_with_matches, self.average_score
+        )
+    }
+}
+
+impl fmt::Display for MatchHistogram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let buckets: Vec<String> = self.buckets().iter().map(|(label, files)| format!("{label}: {files}")).collect();
+        write!(f, "{}", buckets.join(", "))
+    }
+}
+
+impl fmt::Display for DirectoryMatches {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.directory, self.matches)
+    }
+}
+
+/// Duplicate code block information
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct DuplicateBlock {
+    pub file1: String,
+    pub line1: usize,
+    pub file2: String,
+    pub line2: usize,
+    pub content: String,
+    pub similarity: f64,
+}
+
+impl fmt::Display for DuplicateBlock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{} duplicates {}:{} ({:.0}% similar)",
+            self.file1,
+            self.line1,
+            self.file2,
+            self.line2,
+            self.similarity * 100.0
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::de::DeserializeOwned;
+
+    fn sample_result() -> SearchResult {
+        SearchResult {
+            file: "src/lib.rs".to_string(),
+            line_number: 3,
+            end_line_number: Some(4),
+            content: "fn main() {\n}".to_string(),
+            matches: vec![Match { start: 3, end: 7, text: "main".to_string(), pattern_index: 0 }],
+            score: 87.5,
+            relevance: "High".to_string(),
+            context_before: vec!["// entry".to_string()],
+            context_after: Vec::new(),
+            line_truncated: false,
+            content_offset: None,
+            annotations: Default::default(),
+        }
+    }
+
+    fn sample_stats() -> SearchStats {
+        SearchStats {
+            files_with_matches: 2,
+            matching_lines: 5,
+            total_matches: 5,
+            total_occurrences: 7,
+            average_score: 50.0,
+            files_by_match_count: MatchHistogram { one: 1, two_to_five: 0, six_to_twenty: 1, over_twenty: 0 },
+            directories: vec![DirectoryMatches { directory: "src".to_string(), matches: 7 }],
+        }
+    }
+
+    fn sample_complexity() -> ComplexityMetrics {
+        ComplexityMetrics {
+            file_path: "src/lib.rs".to_string(),
+            cyclomatic_complexity: 12,
+            cognitive_complexity: 9,
+            lines_of_code: 140,
+            function_count: 6,
+            max_nesting_depth: 4,
+        }
+    }
+
+    fn sample_function() -> FunctionComplexity {
+        FunctionComplexity {
+            name: "parse".to_string(),
+            start_line: 10,
+            end_line: 42,
+            cyclomatic_complexity: 8,
+            cognitive_complexity: 11,
+            max_nesting_depth: 3,
+        }
+    }
+
+    fn sample_suggestion() -> RefactorSuggestion {
+        RefactorSuggestion {
+            file: "src/lib.rs".to_string(),
+            line_number: 5,
+            suggestion_type: SuggestionType::ComplexFunction,
+            description: "Function is 80 lines long".to_string(),
+            priority: 7,
+            code_snippet: "fn long() {".to_string(),
+            improvement: "Split it up".to_string(),
+        }
+    }
+
+    fn sample_metrics() -> SearchMetrics {
+        SearchMetrics {
+            files_processed: 10,
+            total_lines_scanned: 1200,
+            search_time_ms: 15,
+            parallel_workers: 4,
+            cache_hits: 2,
+            cache_misses: 8,
+            files_ruled_out_by_index: 3,
+        }
+    }
+
+    fn sample_duplicate() -> DuplicateBlock {
+        DuplicateBlock {
+            file1: "a.rs".to_string(),
+            line1: 1,
+            file2: "b.rs".to_string(),
+            line2: 20,
+            content: "let x = 1;".to_string(),
+            similarity: 0.92,
+        }
+    }
+
+    fn round_trip<T: Serialize + DeserializeOwned + PartialEq + fmt::Debug>(value: &T) {
+        let json = serde_json::to_string(value).unwrap();
+        let back: T = serde_json::from_str(&json).unwrap();
+        assert_eq!(&back, value, "{json}");
+    }
+
+    #[test]
+    fn test_search_result() {
+        let result = SearchResult {
+            file: "test.rs".to_string(),
+            line_number: 10,
+            end_line_number: None,
+            content: "fn main()".to_string(),
+            matches: vec![Match {
+                start: 0,
+                end: 2,
+                text: "fn".to_string(),
+                pattern_index: 0,
+            }],
+            score: 0.95,
+            relevance: "High".to_string(),
+            context_before: Vec::new(),
+            context_after: Vec::new(),
+            line_truncated: false,
+            content_offset: None,
+            annotations: Default::default(),
+        };
+        assert_eq!(result.file, "test.rs");
+        assert_eq!(result.matches.len(), 1);
+    }
+
+    #[test]
+    fn test_search_stats_distribution() {
+        let result = |file: &str, matches: usize| SearchResult {
+            file: file.to_string(),
+            line_number: 1,
+            end_line_number: None,
+            content: String::new(),
+            matches: (0..matches).map(|i| Match { start: i, end: i + 1, text: "x".to_string(), pattern_index: 0 }).collect(),
+            score: 50.0,
+            relevance: "Medium".to_string(),
+            context_before: Vec::new(),
+            context_after: Vec::new(),
+            line_truncated: false,
+            content_offset: None,
+            annotations: Default::default(),
+        };
+        // Per file: a.rs 1, lib.rs 3 (over two lines), b.rs 6, big.rs 25, c.rs 20
+        let mut results = vec![
+            result("./proj/src/a.rs", 1),
+            result("./proj/src/lib.rs", 2),
+            result("./proj/src/lib.rs", 1),
+            result("./proj/src/deep/b.rs", 6),
+            result("./proj/tests/big.rs", 25),
+            result("./proj/c.rs", 20),
+        ];
+
+        let stats = SearchStats::from_results(&results);
+        assert_eq!(stats.files_with_matches, 5);
+        assert_eq!(stats.matching_lines, 6);
+        assert_eq!(stats.total_matches, 6);
+        assert_eq!(stats.total_occurrences, 55);
+        assert_eq!(stats.files_by_match_count, MatchHistogram { one: 1, two_to_five: 1, six_to_twenty: 2, over_twenty: 1 });
+        let directories: Vec<(&str, usize)> = stats.directories.iter().map(|d| (d.directory.as_str(), d.matches)).collect();
+        assert_eq!(directories, vec![("tests", 25), (".", 20), ("src", 10)]);
+
+        // Without files directly in the common ancestor, the deeper level is used
+        results.pop();
+        results.remove(4);
+        let directories: Vec<String> = SearchStats::from_results(&results).directories.into_iter().map(|d| d.directory).collect();
+        assert_eq!(directories, vec!["deep", "."]);
+        assert!(SearchStats::from_results(&[]).directories.is_empty());
+    }
+
+    #[test]
+    fn test_complexity_metrics_default() {
+        let metrics = ComplexityMetrics::default();
+        assert_eq!(metrics.cyclomatic_complexity, 0);
+        assert_eq!(metrics.cognitive_complexity, 0);
+    }
+
+    #[test]
+    fn test_result_types_round_trip() {
+        round_trip(&sample_result());
+        round_trip(&Match { start: 0, end: 2, text: "fn".to_string(), pattern_index: 1 });
+        round_trip(&FileInfo { path: "src/lib.rs".to_string(), size: 2048, lines: 80 });
+        round_trip(&sample_suggestion());
+        round_trip(&sample_complexity());
+        round_trip(&sample_function());
+        round_trip(&sample_metrics());
+        round_trip(&GrepComparison { grep_time_ms: 30, grep_matches: 7, speedup: 2.0 });
+        round_trip(&sample_stats());
+        round_trip(&sample_duplicate());
+    }
+
+    #[test]
+    fn test_match_without_pattern_index_is_the_first_pattern() {
+        let m: Match = serde_json::from_str(r#"{"start": 3, "end": 7, "text": "main"}"#).unwrap();
+        assert_eq!(m.pattern_index, 0);
+    }
+
+    #[test]
+    fn test_suggestion_type_names() {
+        for kind in SuggestionType::ALL {
+            assert_eq!(kind.name().parse::<SuggestionType>(), Ok(kind));
+            assert_eq!(serde_json::to_value(kind).unwrap(), kind.name());
+        }
+        assert_eq!("magic-number".parse(), Ok(SuggestionType::MagicNumber));
+        assert_eq!("EMPTY_CATCH_BLOCK".parse(), Ok(SuggestionType::EmptyCatchBlock));
+
+        let err = "Long Lines".parse::<SuggestionType>().unwrap_err();
+        assert!(err.contains("expected one of: Long Line, Todo Comment, Magic Number"), "{err}");
+    }
+
+    #[test]
+    fn test_result_types_display() {
+        assert_eq!(sample_result().to_string(), "src/lib.rs:3-4: fn main() {\n}");
+        assert_eq!(Match { start: 3, end: 7, text: "main".to_string(), pattern_index: 0 }.to_string(), "\"main\" at 3..7");
+        assert_eq!(
+            FileInfo { path: "a.rs".to_string(), size: 10, lines: 2 }.to_string(),
+            "a.rs (2 lines, 10 bytes)"
+        );
+        assert_eq!(
+            sample_suggestion().to_string(),
+            "src/lib.rs:5: [priority 7] Complex Function: Function is 80 lines long"
+        );
+        assert_eq!(
+            sample_complexity().to_string(),
+            "src/lib.rs: CC 12, COG 9, LOC 140, functions 6, nesting 4"
+        );
+        assert_eq!(sample_function().to_string(), "parse (lines 10-42): CC 8, COG 11, nesting 3");
+        assert!(sample_metrics().to_string().ends_with(", 3 files ruled out by the index"));
+        assert_eq!(sample_stats().to_string(), "7 matches on 5 lines in 2 files, average score 50.0");
+        assert_eq!(sample_stats().files_by_match_count.to_string(), "1: 1, 2-5: 0, 6-20: 1, 21+: 0");
+        assert_eq!(sample_duplicate().to_string(), "a.rs:1 duplicates b.rs:20 (92% similar)");
+    }
+
+    #[cfg(feature = "schemars")]
+    fn schema_properties<T: schemars::JsonSchema>() -> Vec<String> {
+        let schema = serde_json::to_value(schemars::schema_for!(T)).unwrap();
+        schema["properties"].as_object().map_or_else(Vec::new, |p| p.keys().cloned().collect())
+    }
+
+    #[cfg(feature = "schemars")]
+    #[test]
+    fn test_result_type_schemas_list_fields() {
+        let has = |properties: Vec<String>, fields: &[&str]| {
+            for field in fields {
+                assert!(properties.iter().any(|p| p == field), "{field} missing from {properties:?}");
+            }
+        };
+        has(schema_properties::<SearchResult>(), &["file", "line_number", "matches", "score", "end_line_number"]);
+        has(schema_properties::<Match>(), &["start", "end", "text"]);
+        has(schema_properties::<FileInfo>(), &["path", "size", "lines"]);
+        has(schema_properties::<RefactorSuggestion>(), &["suggestion_type", "priority"]);
+        has(schema_properties::<ComplexityMetrics>(), &["file_path", "cyclomatic_complexity"]);
+        has(schema_properties::<FunctionComplexity>(), &["name", "start_line", "end_line"]);
+        has(schema_properties::<SearchMetrics>(), &["files_processed", "search_time_ms"]);
+        has(schema_properties::<SearchStats>(), &["total_matches", "total_occurrences", "files_by_match_count", "directories"]);
+        has(schema_properties::<DuplicateBlock>(), &["file1", "file2", "similarity"]);
+    }
+}
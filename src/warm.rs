@@ -0,0 +1,146 @@
+//! Cache Warming
+//!
+//! `codesearch warm` runs a list of common queries ahead of time, so the
+//! searches people actually type hit the persisted search cache
+//! (`.codesearch/cache/`) and the trigram index. Results aren't printed; each
+//! query gets a row with its match count, time and how much it grew the cache.
+
+use crate::cache::{get_search_cache, DEFAULT_CACHE_FILE};
+use crate::search::search_path_or_glob_outcome;
+use crate::types::SearchOptions;
+use colored::*;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Read the queries in `file`, one per line
+///
+/// Lines are trimmed; blank lines and lines starting with `#` are skipped.
+pub fn read_queries(file: &Path) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let text = fs::read_to_string(file).map_err(|e| format!("Cannot read queries file {}: {e}", file.display()))?;
+    Ok(parse_queries(&text))
+}
+
+fn parse_queries(text: &str) -> Vec<String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
+/// How warming one query went
+#[derive(Debug, Clone)]
+pub struct WarmOutcome {
+    pub query: String,
+    /// Matching lines, 0 when the query failed
+    pub matches: usize,
+    pub elapsed: Duration,
+    /// Change in the size of the cache file while the query ran
+    ///
+    /// With several queries in flight this includes what the others saved meanwhile.
+    pub cache_bytes_delta: i64,
+    /// Why the query failed, e.g. an invalid regex
+    pub error: Option<String>,
+}
+
+/// Run every query over `path` with `options`, at most `parallel` at a time
+///
+/// `options` should have `cache` set, or nothing is kept. A query failing
+/// doesn't stop the others; outcomes come back in the order of `queries`.
+pub fn warm_queries(queries: &[String], path: &Path, options: &SearchOptions, parallel: usize) -> Vec<WarmOutcome> {
+    if options.cache {
+        // Load the persisted cache before timing the first query
+        get_search_cache();
+    }
+    let next = AtomicUsize::new(0);
+    let outcomes = Mutex::new(vec![None; queries.len()]);
+    std::thread::scope(|scope| {
+        for _ in 0..parallel.clamp(1, queries.len().max(1)) {
+            scope.spawn(|| {
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(query) = queries.get(index) else { break };
+                    let outcome = warm_query(query, path, options);
+                    outcomes.lock().unwrap()[index] = Some(outcome);
+                }
+            });
+        }
+    });
+    outcomes.into_inner().unwrap().into_iter().flatten().collect()
+}
+
+fn warm_query(query: &str, path: &Path, options: &SearchOptions) -> WarmOutcome {
+    let before = cache_file_size();
+    let start = Instant::now();
+    let result = search_path_or_glob_outcome(query, path, options);
+    let elapsed = start.elapsed();
+    let cache_bytes_delta = cache_file_size() as i64 - before as i64;
+    let (matches, error) = match result {
+        Ok(outcome) => (outcome.results.len(), None),
+        Err(e) => (0, Some(e.to_string())),
+    };
+    WarmOutcome { query: query.to_string(), matches, elapsed, cache_bytes_delta, error }
+}
+
+fn cache_file_size() -> u64 {
+    let path = get_search_cache().path().unwrap_or(Path::new(DEFAULT_CACHE_FILE));
+    fs::metadata(path).map(|m| m.len()).unwrap_or(0)
+}
+
+/// Print one row per query and a total row, then the errors of the queries that failed
+pub fn print_warm_summary(outcomes: &[WarmOutcome]) {
+    let width = outcomes.iter().map(|o| o.query.chars().count()).max().unwrap_or(0).max("Query".len());
+    println!(
+        "{}",
+        format!("{:<width$}  {:>8}  {:>10}  {:>12}", "Query", "Matches", "Time", "Cache delta").cyan().bold()
+    );
+    for outcome in outcomes {
+        let row = format!(
+            "{:<width$}  {:>8}  {:>10}  {:>12}",
+            outcome.query,
+            outcome.matches,
+            format_elapsed(outcome.elapsed),
+            format_delta(outcome.cache_bytes_delta)
+        );
+        match outcome.error {
+            Some(_) => println!("{}  {}", row, "failed".red()),
+            None => println!("{row}"),
+        }
+    }
+    let matches: usize = outcomes.iter().map(|o| o.matches).sum();
+    let elapsed: Duration = outcomes.iter().map(|o| o.elapsed).sum();
+    let delta: i64 = outcomes.iter().map(|o| o.cache_bytes_delta).sum();
+    println!(
+        "{}",
+        format!("{:<width$}  {:>8}  {:>10}  {:>12}", "Total", matches, format_elapsed(elapsed), format_delta(delta)).bold()
+    );
+    // Regex errors span several lines, so they go after the table
+    for outcome in outcomes {
+        if let Some(error) = &outcome.error {
+            eprintln!("\n{} {}: {error}", "Error:".red().bold(), outcome.query);
+        }
+    }
+}
+
+fn format_elapsed(elapsed: Duration) -> String {
+    format!("{}ms", elapsed.as_millis())
+}
+
+fn format_delta(bytes: i64) -> String {
+    format!("{}{bytes} B", if bytes > 0 { "+" } else { "" })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_queries_skips_blank_lines_and_comments() {
+        let text = "# common searches\nfn main\n\n   TODO  \n# regexes\nerror\\(\n";
+        assert_eq!(parse_queries(text), vec!["fn main", "TODO", "error\\("]);
+        assert!(parse_queries("\n# only a comment\n").is_empty());
+    }
+}
@@ -0,0 +1,600 @@
+//! Watch Analysis Module
+//!
+//! Re-runs selected analyses on the files changed during `codesearch watch`
+//! and prints what changed since their previous run, one line per file.
+
+use crate::codemetrics::file_metrics_from_content;
+use crate::hygiene::{scan_reader, LineEndings};
+use crate::index::CodeIndex;
+use crate::options::has_extension;
+use crate::privacy::{scan_source, SensitiveNames};
+use crate::watcher::FileWatcher;
+use crate::theme::{self, Glyph};
+use colored::*;
+use notify::event::{ModifyKind, RenameMode};
+use notify::{Event, EventKind};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use walkdir::WalkDir;
+
+/// An analysis re-run on changed files
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchAnalysis {
+    /// Cyclomatic complexity of the whole file
+    Complexity,
+    /// Per-file rule findings: sensitive values in log calls and hygiene issues
+    Lint,
+}
+
+impl std::str::FromStr for WatchAnalysis {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "complexity" => Ok(Self::Complexity),
+            "lint" => Ok(Self::Lint),
+            other => Err(format!("Unknown watch analysis: {other} (expected: complexity, lint)")),
+        }
+    }
+}
+
+/// A file change, as reported to [`WatchAnalyzer::handle_batch`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChangeEvent {
+    /// Created or modified
+    Changed(PathBuf),
+    Removed(PathBuf),
+    Renamed { from: PathBuf, to: PathBuf },
+}
+
+impl ChangeEvent {
+    /// The path the file has after the change
+    pub fn path(&self) -> &Path {
+        match self {
+            ChangeEvent::Changed(path) | ChangeEvent::Removed(path) => path,
+            ChangeEvent::Renamed { to, .. } => to,
+        }
+    }
+}
+
+/// Translate a file system event into change events
+///
+/// Renames reported as separate "from" and "to" halves become a removal and a
+/// change, which [`WatchAnalyzer`] still handles correctly, only without
+/// carrying the old values over to the new name.
+pub fn change_events(event: &Event) -> Vec<ChangeEvent> {
+    match event.kind {
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if event.paths.len() == 2 => {
+            vec![ChangeEvent::Renamed { from: event.paths[0].clone(), to: event.paths[1].clone() }]
+        }
+        EventKind::Modify(ModifyKind::Name(RenameMode::From)) | EventKind::Remove(_) => {
+            event.paths.iter().cloned().map(ChangeEvent::Removed).collect()
+        }
+        EventKind::Modify(ModifyKind::Metadata(_)) => Vec::new(),
+        EventKind::Create(_) | EventKind::Modify(_) => event.paths.iter().cloned().map(ChangeEvent::Changed).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Collapse a debounced batch to at most one event per file, in first-seen order
+///
+/// Editors often save by writing a temporary file and renaming it over the
+/// original, so a single save can arrive as several events.
+pub fn coalesce(events: Vec<ChangeEvent>) -> Vec<ChangeEvent> {
+    let mut merged: Vec<Option<ChangeEvent>> = Vec::new();
+    let mut position: HashMap<PathBuf, usize> = HashMap::new();
+
+    for event in events {
+        match event {
+            ChangeEvent::Changed(path) => match position.get(&path) {
+                Some(&i) => {
+                    // A removal followed by a change is a rewrite; renames stay renames
+                    if let Some(ChangeEvent::Removed(_)) = merged[i] {
+                        merged[i] = Some(ChangeEvent::Changed(path));
+                    }
+                }
+                None => {
+                    position.insert(path.clone(), merged.len());
+                    merged.push(Some(ChangeEvent::Changed(path)));
+                }
+            },
+            ChangeEvent::Removed(path) => match position.get(&path) {
+                Some(&i) => {
+                    // Whatever the file was known as before this batch is what disappeared
+                    let removed = match merged[i].take() {
+                        Some(ChangeEvent::Renamed { from, .. }) => from,
+                        _ => path,
+                    };
+                    merged[i] = Some(ChangeEvent::Removed(removed));
+                }
+                None => {
+                    position.insert(path.clone(), merged.len());
+                    merged.push(Some(ChangeEvent::Removed(path)));
+                }
+            },
+            ChangeEvent::Renamed { from, to } => {
+                let earlier = position.remove(&from).and_then(|i| merged[i].take());
+                let event = match earlier {
+                    Some(ChangeEvent::Renamed { from: original, .. }) => {
+                        ChangeEvent::Renamed { from: original, to: to.clone() }
+                    }
+                    _ => ChangeEvent::Renamed { from, to: to.clone() },
+                };
+                if let Some(&i) = position.get(&to) {
+                    merged[i] = None;
+                }
+                position.insert(to, merged.len());
+                merged.push(Some(event));
+            }
+        }
+    }
+
+    merged.into_iter().flatten().collect()
+}
+
+/// Analysis values of one file at one point in time
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileSnapshot {
+    pub cyclomatic: Option<usize>,
+    /// One description per lint finding, without line numbers so moved code isn't "new"
+    pub lint: Vec<String>,
+}
+
+/// How a file's analysis values changed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileDelta {
+    pub path: PathBuf,
+    pub renamed_from: Option<PathBuf>,
+    /// No previous values were known for the file
+    pub created: bool,
+    pub removed: bool,
+    /// Previous and current cyclomatic complexity when it changed; for created
+    /// files, zero and the current value
+    pub cyclomatic: Option<(usize, usize)>,
+    pub new_lint: usize,
+    pub fixed_lint: usize,
+}
+
+impl FileDelta {
+    /// Complexity of an existing file went up, or lint findings were introduced
+    pub fn is_regression(&self) -> bool {
+        let more_complex = !self.created && self.cyclomatic.is_some_and(|(before, after)| after > before);
+        more_complex || self.new_lint > 0
+    }
+
+    fn is_noteworthy(&self) -> bool {
+        self.created
+            || self.removed
+            || self.renamed_from.is_some()
+            || self.cyclomatic.is_some()
+            || self.new_lint > 0
+            || self.fixed_lint > 0
+    }
+
+    /// One line such as `src/foo.rs: cyclomatic 14→17 ▲, 2 new lint findings`
+    pub fn summary(&self) -> String {
+        let name = match &self.renamed_from {
+            Some(from) => format!("{} {} {}", from.display(), theme::arrow(), self.path.display()),
+            None => self.path.display().to_string(),
+        };
+        if self.removed {
+            return format!("{name}: removed");
+        }
+
+        let mut parts = Vec::new();
+        if self.created {
+            parts.push("new file".to_string());
+        }
+        match self.cyclomatic {
+            Some((_, after)) if self.created => parts.push(format!("cyclomatic {after}")),
+            Some((before, after)) => {
+                let arrow = theme::glyph(if after > before { Glyph::Up } else { Glyph::Down });
+                parts.push(format!("cyclomatic {before}{}{after} {arrow}", theme::arrow()));
+            }
+            None => {}
+        }
+        if self.new_lint > 0 {
+            parts.push(format!("{} new lint finding{}", self.new_lint, plural(self.new_lint)));
+        }
+        if self.fixed_lint > 0 {
+            parts.push(format!("{} lint finding{} fixed", self.fixed_lint, plural(self.fixed_lint)));
+        }
+        if parts.is_empty() {
+            parts.push("no changes".to_string());
+        }
+        format!("{name}: {}", parts.join(", "))
+    }
+}
+
+fn plural(count: usize) -> &'static str {
+    if count == 1 { "" } else { "s" }
+}
+
+/// Keeps the previous analysis values of watched files and diffs changes against them
+pub struct WatchAnalyzer {
+    analyses: Vec<WatchAnalysis>,
+    extensions: Option<Vec<String>>,
+    exclude: Vec<String>,
+    names: SensitiveNames,
+    previous: HashMap<PathBuf, FileSnapshot>,
+    regressions: usize,
+}
+
+impl WatchAnalyzer {
+    /// Analyze files with one of `extensions` (all when `None`), outside directories named in `exclude`
+    pub fn new(
+        analyses: Vec<WatchAnalysis>,
+        extensions: Option<Vec<String>>,
+        exclude: Vec<String>,
+        names: SensitiveNames,
+    ) -> Self {
+        Self {
+            analyses,
+            extensions,
+            exclude,
+            names,
+            previous: HashMap::new(),
+            regressions: 0,
+        }
+    }
+
+    /// Record the current values of every matching file under `root`, so the
+    /// first change to a file already has something to compare against
+    pub fn prime(&mut self, root: &Path) {
+        let files = WalkDir::new(root)
+            .into_iter()
+            .filter_entry(|e| !self.exclude.iter().any(|d| e.file_name().to_string_lossy() == d.as_str()))
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file());
+        for entry in files {
+            if !self.wanted(entry.path()) {
+                continue;
+            }
+            if let Some(snapshot) = self.snapshot(entry.path()) {
+                self.previous.insert(entry.into_path(), snapshot);
+            }
+        }
+    }
+
+    /// Number of regressions seen so far
+    pub fn regressions(&self) -> usize {
+        self.regressions
+    }
+
+    /// Re-run the analyses on a debounced batch and return the changes worth reporting
+    pub fn handle_batch(&mut self, events: Vec<ChangeEvent>) -> Vec<FileDelta> {
+        let mut deltas = Vec::new();
+        for event in coalesce(events) {
+            if !self.wanted(event.path()) {
+                continue;
+            }
+            let delta = match event {
+                ChangeEvent::Changed(path) => self.update(path, None),
+                ChangeEvent::Renamed { from, to } => {
+                    let carried = self.previous.remove(&from);
+                    if let Some(snapshot) = carried {
+                        self.previous.insert(to.clone(), snapshot);
+                    }
+                    self.update(to, Some(from))
+                }
+                ChangeEvent::Removed(path) => self.previous.remove(&path).map(|_| FileDelta {
+                    path,
+                    renamed_from: None,
+                    created: false,
+                    removed: true,
+                    cyclomatic: None,
+                    new_lint: 0,
+                    fixed_lint: 0,
+                }),
+            };
+            if let Some(delta) = delta.filter(FileDelta::is_noteworthy) {
+                if delta.is_regression() {
+                    self.regressions += 1;
+                }
+                deltas.push(delta);
+            }
+        }
+        deltas
+    }
+
+    fn update(&mut self, path: PathBuf, renamed_from: Option<PathBuf>) -> Option<FileDelta> {
+        let current = self.snapshot(&path)?;
+        let previous = self.previous.insert(path.clone(), current.clone());
+        let created = previous.is_none();
+        let previous = previous.unwrap_or_default();
+
+        let cyclomatic = match (previous.cyclomatic, current.cyclomatic) {
+            (Some(before), Some(after)) if before != after => Some((before, after)),
+            (None, Some(after)) => Some((0, after)),
+            _ => None,
+        };
+        let (new_lint, fixed_lint) = diff_findings(&previous.lint, &current.lint);
+        Some(FileDelta {
+            path,
+            renamed_from,
+            created,
+            removed: false,
+            cyclomatic,
+            new_lint,
+            fixed_lint,
+        })
+    }
+
+    fn wanted(&self, path: &Path) -> bool {
+        let excluded = path
+            .components()
+            .any(|c| self.exclude.iter().any(|d| c.as_os_str() == d.as_str()));
+        !excluded && self.extensions.as_ref().is_none_or(|exts| has_extension(path, exts))
+    }
+
+    /// Current values of the selected analyses; `None` for unreadable or binary files
+    fn snapshot(&self, path: &Path) -> Option<FileSnapshot> {
+        let bytes = fs::read(path).ok()?;
+        let content = String::from_utf8(bytes).ok()?;
+        let mut snapshot = FileSnapshot::default();
+        if self.analyses.contains(&WatchAnalysis::Complexity) {
+            snapshot.cyclomatic = Some(file_metrics_from_content(path, &content).complexity.cyclomatic_complexity);
+        }
+        if self.analyses.contains(&WatchAnalysis::Lint) {
+            snapshot.lint = lint_findings(path, &content, &self.names);
+        }
+        Some(snapshot)
+    }
+}
+
+/// Sorted lint finding descriptions of a file
+pub fn lint_findings(path: &Path, content: &str, names: &SensitiveNames) -> Vec<String> {
+    let file = path.to_string_lossy();
+    let mut findings: Vec<String> = scan_source(&file, content, names)
+        .into_iter()
+        .map(|f| format!("sensitive value `{}` logged in {}", f.identifier, f.call))
+        .collect();
+
+    if let Ok(Some(hygiene)) = scan_reader(&file, content.as_bytes()) {
+        if hygiene.line_endings == LineEndings::Mixed {
+            findings.push("mixed line endings".to_string());
+        }
+        if hygiene.has_bom {
+            findings.push("byte order mark".to_string());
+        }
+        // One finding per offending line, so each new one counts
+        findings.extend((0..hygiene.trailing_whitespace_lines).map(|_| "trailing whitespace".to_string()));
+        if !hygiene.final_newline {
+            findings.push("missing final newline".to_string());
+        }
+    }
+    findings.sort();
+    findings
+}
+
+/// Count findings only in `after` and only in `before`, as multisets
+fn diff_findings(before: &[String], after: &[String]) -> (usize, usize) {
+    let mut counts: HashMap<&str, isize> = HashMap::new();
+    for finding in after {
+        *counts.entry(finding).or_default() += 1;
+    }
+    for finding in before {
+        *counts.entry(finding).or_default() -= 1;
+    }
+    let added = counts.values().filter(|&&c| c > 0).map(|&c| c as usize).sum();
+    let fixed = counts.values().filter(|&&c| c < 0).map(|&c| c.unsigned_abs()).sum();
+    (added, fixed)
+}
+
+/// Print one delta line per file, ringing the terminal bell on regressions when asked
+pub fn print_deltas(deltas: &[FileDelta], bell: bool) {
+    for delta in deltas {
+        let line = delta.summary();
+        if delta.is_regression() {
+            println!("{}", line.red());
+        } else if delta.removed {
+            println!("{}", line.dimmed());
+        } else {
+            println!("{}", line.green());
+        }
+    }
+    if bell && deltas.iter().any(FileDelta::is_regression) {
+        print!("\x07");
+    }
+}
+
+/// Settings for `codesearch watch --analyze`
+#[derive(Debug, Clone)]
+pub struct WatchAnalysisOptions {
+    /// Quiet time that ends a batch of events
+    pub debounce: Duration,
+    /// Stop watching after this long, or never when `None`
+    pub duration: Option<Duration>,
+    /// Ring the terminal bell when a batch contains a regression
+    pub bell: bool,
+}
+
+/// Watch `path`, keeping `index` fresh and printing analysis deltas for changed files
+///
+/// Returns the number of regressions seen once the watch ends.
+pub fn watch_with_analysis(
+    path: &Path,
+    index: &CodeIndex,
+    mut analyzer: WatchAnalyzer,
+    options: &WatchAnalysisOptions,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let mut watcher = FileWatcher::new()?;
+    watcher.watch(path)?;
+    analyzer.prime(path);
+    let deadline = options.duration.map(|d| Instant::now() + d);
+
+    println!("Watching {} for changes...", path.display());
+    while let Some(events) = watcher.next_batch(options.debounce, deadline) {
+        let changes: Vec<ChangeEvent> = events.iter().flat_map(change_events).collect();
+        for event in events {
+            watcher.handle_event(event, index, &analyzer.extensions)?;
+        }
+        print_deltas(&analyzer.handle_batch(changes), options.bell);
+    }
+
+    Ok(analyzer.regressions())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn analyzer() -> WatchAnalyzer {
+        WatchAnalyzer::new(
+            vec![WatchAnalysis::Complexity, WatchAnalysis::Lint],
+            Some(vec!["rs".to_string()]),
+            vec!["target".to_string()],
+            SensitiveNames::default(),
+        )
+    }
+
+    const SIMPLE: &str = "fn f(x: i32) -> i32 {\n    x\n}\n";
+    const BRANCHY: &str = "fn f(x: i32) -> i32 {\n    if x > 0 {\n        return 1;\n    }\n    if x < 0 {\n        return 2;\n    }\n    0\n}\n";
+
+    #[test]
+    fn test_parse_analyses() {
+        assert_eq!("Complexity".parse::<WatchAnalysis>(), Ok(WatchAnalysis::Complexity));
+        assert_eq!(" lint".parse::<WatchAnalysis>(), Ok(WatchAnalysis::Lint));
+        assert!("coverage".parse::<WatchAnalysis>().is_err());
+    }
+
+    #[test]
+    fn test_complexity_regression_and_improvement() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("foo.rs");
+        fs::write(&file, SIMPLE).unwrap();
+        let mut analyzer = analyzer();
+        analyzer.prime(dir.path());
+
+        fs::write(&file, BRANCHY).unwrap();
+        let deltas = analyzer.handle_batch(vec![ChangeEvent::Changed(file.clone())]);
+        assert_eq!(deltas.len(), 1);
+        let (before, after) = deltas[0].cyclomatic.unwrap();
+        assert!(after > before);
+        assert!(deltas[0].is_regression());
+        assert!(deltas[0].summary().contains(&format!("cyclomatic {before}→{after} ▲")));
+
+        fs::write(&file, SIMPLE).unwrap();
+        let deltas = analyzer.handle_batch(vec![ChangeEvent::Changed(file.clone())]);
+        assert!(!deltas[0].is_regression());
+        assert!(deltas[0].summary().contains('▼'));
+        assert_eq!(analyzer.regressions(), 1);
+
+        // Saving without a change reports nothing
+        assert!(analyzer.handle_batch(vec![ChangeEvent::Changed(file)]).is_empty());
+    }
+
+    #[test]
+    fn test_new_lint_findings_are_counted() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("foo.rs");
+        fs::write(&file, SIMPLE).unwrap();
+        let mut analyzer = analyzer();
+        analyzer.prime(dir.path());
+
+        fs::write(&file, "fn f(x: i32) -> i32 {   \n    x  \n}\n").unwrap();
+        let deltas = analyzer.handle_batch(vec![ChangeEvent::Changed(file.clone())]);
+        assert_eq!(deltas[0].new_lint, 2);
+        assert!(deltas[0].summary().ends_with("foo.rs: 2 new lint findings"));
+
+        fs::write(&file, SIMPLE).unwrap();
+        let deltas = analyzer.handle_batch(vec![ChangeEvent::Changed(file)]);
+        assert_eq!((deltas[0].new_lint, deltas[0].fixed_lint), (0, 2));
+    }
+
+    #[test]
+    fn test_rename_carries_previous_values() {
+        let dir = tempdir().unwrap();
+        let old = dir.path().join("old.rs");
+        let new = dir.path().join("new.rs");
+        fs::write(&old, SIMPLE).unwrap();
+        let mut analyzer = analyzer();
+        analyzer.prime(dir.path());
+
+        fs::rename(&old, &new).unwrap();
+        let deltas = analyzer.handle_batch(vec![ChangeEvent::Renamed { from: old.clone(), to: new.clone() }]);
+        assert_eq!(deltas.len(), 1);
+        assert_eq!(deltas[0].renamed_from.as_deref(), Some(old.as_path()));
+        // A pure rename is not a regression
+        assert_eq!(deltas[0].cyclomatic, None);
+        assert!(!deltas[0].is_regression());
+
+        fs::remove_file(&new).unwrap();
+        let deltas = analyzer.handle_batch(vec![ChangeEvent::Removed(new)]);
+        assert!(deltas[0].removed);
+    }
+
+    #[test]
+    fn test_created_file_is_not_a_complexity_regression() {
+        let dir = tempdir().unwrap();
+        let mut analyzer = analyzer();
+        analyzer.prime(dir.path());
+
+        let file = dir.path().join("foo.rs");
+        fs::write(&file, BRANCHY).unwrap();
+        let deltas = analyzer.handle_batch(vec![ChangeEvent::Changed(file)]);
+        assert!(deltas[0].created);
+        assert!(!deltas[0].is_regression());
+        assert!(deltas[0].summary().contains("new file, cyclomatic"));
+    }
+
+    #[test]
+    fn test_unwatched_extensions_are_ignored() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("notes.txt");
+        fs::write(&file, "trailing   \n").unwrap();
+        fs::create_dir(dir.path().join("target")).unwrap();
+        let built = dir.path().join("target/out.rs");
+        fs::write(&built, BRANCHY).unwrap();
+        let mut analyzer = analyzer();
+        assert!(analyzer.handle_batch(vec![ChangeEvent::Changed(file), ChangeEvent::Changed(built)]).is_empty());
+    }
+
+    #[test]
+    fn test_coalesce_batches() {
+        let a = PathBuf::from("a.rs");
+        let b = PathBuf::from("b.rs");
+        let tmp = PathBuf::from("a.rs.tmp");
+
+        // Repeated writes collapse into one change
+        let events = vec![ChangeEvent::Changed(a.clone()), ChangeEvent::Changed(a.clone())];
+        assert_eq!(coalesce(events), vec![ChangeEvent::Changed(a.clone())]);
+
+        // Renames chain, and a removal after a rename removes the original
+        let events = vec![
+            ChangeEvent::Renamed { from: a.clone(), to: tmp.clone() },
+            ChangeEvent::Renamed { from: tmp.clone(), to: b.clone() },
+        ];
+        assert_eq!(coalesce(events), vec![ChangeEvent::Renamed { from: a.clone(), to: b.clone() }]);
+        let events = vec![ChangeEvent::Renamed { from: a.clone(), to: b.clone() }, ChangeEvent::Removed(b.clone())];
+        assert_eq!(coalesce(events), vec![ChangeEvent::Removed(a.clone())]);
+
+        // Delete-and-recreate saves are a change
+        let events = vec![ChangeEvent::Removed(a.clone()), ChangeEvent::Changed(a.clone())];
+        assert_eq!(coalesce(events), vec![ChangeEvent::Changed(a)]);
+    }
+
+    #[test]
+    fn test_change_events_from_notify() {
+        use notify::event::{CreateKind, DataChange, MetadataKind, RemoveKind};
+
+        let path = PathBuf::from("a.rs");
+        let event = Event::new(EventKind::Modify(ModifyKind::Data(DataChange::Content))).add_path(path.clone());
+        assert_eq!(change_events(&event), vec![ChangeEvent::Changed(path.clone())]);
+        let event = Event::new(EventKind::Create(CreateKind::File)).add_path(path.clone());
+        assert_eq!(change_events(&event), vec![ChangeEvent::Changed(path.clone())]);
+        let event = Event::new(EventKind::Remove(RemoveKind::File)).add_path(path.clone());
+        assert_eq!(change_events(&event), vec![ChangeEvent::Removed(path.clone())]);
+        let event = Event::new(EventKind::Modify(ModifyKind::Metadata(MetadataKind::Any))).add_path(path.clone());
+        assert!(change_events(&event).is_empty());
+
+        let to = PathBuf::from("b.rs");
+        let event = Event::new(EventKind::Modify(ModifyKind::Name(RenameMode::Both)))
+            .add_path(path.clone())
+            .add_path(to.clone());
+        assert_eq!(change_events(&event), vec![ChangeEvent::Renamed { from: path, to }]);
+    }
+}
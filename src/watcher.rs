@@ -3,11 +3,12 @@
 //! Provides real-time file system monitoring for automatic index updates.
 
 use crate::index::CodeIndex;
+use crate::options::has_extension;
 use notify::{Config, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 use std::path::{Path, PathBuf};
-use std::sync::mpsc::{channel, Receiver};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 pub struct FileWatcher {
     watcher: RecommendedWatcher,
@@ -52,7 +53,32 @@ impl FileWatcher {
         Ok(())
     }
 
-    fn handle_event(
+    /// Wait for the next batch of events, collecting until none arrive for `debounce`
+    ///
+    /// Returns `None` once `deadline` passes or the watcher stops.
+    pub fn next_batch(&self, debounce: Duration, deadline: Option<Instant>) -> Option<Vec<Event>> {
+        let mut batch = Vec::new();
+        loop {
+            let wait = if batch.is_empty() {
+                match deadline {
+                    Some(deadline) => deadline.checked_duration_since(Instant::now())?,
+                    None => Duration::MAX,
+                }
+            } else {
+                debounce
+            };
+            match self.receiver.recv_timeout(wait) {
+                Ok(Ok(event)) => batch.push(event),
+                Ok(Err(e)) => eprintln!("Watch error: {e:?}"),
+                Err(RecvTimeoutError::Timeout) if !batch.is_empty() => return Some(batch),
+                Err(RecvTimeoutError::Timeout) => return None,
+                Err(RecvTimeoutError::Disconnected) => return (!batch.is_empty()).then_some(batch),
+            }
+        }
+    }
+
+    /// Update the index for a file system event
+    pub fn handle_event(
         &self,
         event: Event,
         index: &CodeIndex,
@@ -79,14 +105,10 @@ impl FileWatcher {
             return false;
         }
         
-        if let Some(exts) = extensions {
-            if let Some(ext) = path.extension().and_then(|s| s.to_str()) {
-                return exts.iter().any(|e| e == ext);
-            }
-            return false;
+        match extensions {
+            Some(exts) => has_extension(path, exts),
+            None => true,
         }
-        
-        true
     }
 }
 
@@ -217,7 +217,7 @@ fn test_deadcode_detection_across_files() {
         extensions: Some(vec![String::from("rs")]),
         ..Default::default()
     };
-    let result = codesearch::deadcode::detect_dead_code(temp_dir.path(), options.extensions.as_deref(), options.exclude.as_deref());
+    let result = codesearch::deadcode::detect_dead_code(temp_dir.path(), options.extensions.as_deref(), options.exclude.as_deref(), &codesearch::deadcode::DeadCodeOptions::default());
     
     // Should successfully analyze for dead code across files
     assert!(result.is_ok());
@@ -240,6 +240,30 @@ fn test_duplicate_detection_across_files() {
     }
 }
 
+#[test]
+fn test_block_in_four_files_is_one_cluster() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let block = "    let mut checksum = 17u64;\n    for byte in payload.bytes() {\n        checksum = checksum.wrapping_mul(31).wrapping_add(byte as u64);\n    }\n    checksum ^= checksum >> 7;\n    checksum\n";
+    for (dir, name) in [("a", "one"), ("b", "two"), ("c", "three"), ("d", "four")] {
+        fs::create_dir_all(temp_dir.path().join(dir)).unwrap();
+        fs::write(
+            temp_dir.path().join(dir).join(format!("{name}.rs")),
+            format!("fn hash_{name}(payload: &str) -> u64 {{\n{block}}}\n"),
+        )
+        .unwrap();
+    }
+
+    let config = codesearch::duplicates::DuplicateConfig { min_lines: 5, suggest_keep: false, ..Default::default() };
+    let pairs = codesearch::duplicates::find_duplicates_enhanced(temp_dir.path(), None, None, config.clone()).unwrap();
+    let clusters = codesearch::duplicates::find_duplicate_clusters(temp_dir.path(), None, None, config).unwrap();
+
+    assert!(pairs.len() >= 6, "expected every pair of the four copies, got {}", pairs.len());
+    assert_eq!(clusters.len(), 1, "{clusters:?}");
+    let files: std::collections::BTreeSet<&str> = clusters[0].occurrences.iter().map(|o| o.file.as_str()).collect();
+    assert_eq!(clusters[0].occurrences.len(), 4, "{:?}", clusters[0].occurrences);
+    assert_eq!(files.len(), 4);
+}
+
 #[test]
 fn test_complexity_analysis_across_folders() {
     let temp_dir = create_multi_file_structure();
@@ -0,0 +1,17 @@
+fn main() {
+    let report = build_report();
+    println!("{report}");
+}
+
+fn build_report() -> String {
+    format_line("ok")
+}
+
+fn format_line(text: &str) -> String {
+    format!("[{text}]")
+}
+
+fn orphan_helper() -> u32 {
+    let answer = 42;
+    answer
+}
@@ -0,0 +1,15 @@
+pub fn register_users(router: &mut Vec<String>) {
+    router.push(load_users());
+}
+
+fn load_users() -> String {
+    String::from("/users")
+}
+
+fn stale_route() -> String {
+    cleanup_stale()
+}
+
+fn cleanup_stale() -> String {
+    String::from("/stale")
+}
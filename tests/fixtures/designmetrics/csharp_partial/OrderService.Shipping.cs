@@ -0,0 +1,29 @@
+using System;
+
+namespace Shop.Orders
+{
+    public partial class OrderService
+    {
+        private string carrier = "post";
+
+        public void Ship(int orderId)
+        {
+            Console.WriteLine(carrier + orderId);
+        }
+
+        private static bool CanShip(int orderId)
+        {
+            return orderId > 0;
+        }
+    }
+
+    public class Receipt
+    {
+        private decimal total;
+
+        public string Print()
+        {
+            return total.ToString();
+        }
+    }
+}
@@ -0,0 +1,22 @@
+using System;
+using System.Collections.Generic;
+
+namespace Shop.Orders
+{
+    public partial class OrderService
+    {
+        private readonly List<string> orders = new List<string>();
+        private int nextId;
+
+        public void PlaceOrder(string item)
+        {
+            orders.Add(item);
+            nextId++;
+        }
+
+        public int CountOrders()
+        {
+            return orders.Count;
+        }
+    }
+}
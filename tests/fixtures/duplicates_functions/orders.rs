@@ -0,0 +1,23 @@
+use std::collections::HashMap;
+
+pub struct Order {
+    pub id: u64,
+    pub total: u64,
+}
+
+pub fn order_count(orders: &[Order]) -> usize {
+    orders.len()
+}
+
+pub fn totals_by_bucket(orders: &[Order], bucket: u64) -> HashMap<u64, u64> {
+    let mut totals = HashMap::new();
+    for order in orders {
+        let key = order.total / bucket;
+        *totals.entry(key).or_insert(0) += order.total;
+    }
+    totals
+}
+
+pub fn largest(orders: &[Order]) -> Option<&Order> {
+    orders.iter().max_by_key(|o| o.total)
+}
@@ -0,0 +1,25 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+pub struct Order {
+    pub id: u64,
+    pub total: u64,
+}
+
+pub fn render(title: &str, rows: &[(String, u64)]) -> String {
+    let mut out = String::new();
+    writeln!(out, "# {title}").unwrap();
+    for (name, value) in rows {
+        writeln!(out, "- {name}: {value}").unwrap();
+    }
+    out
+}
+
+pub fn totals_by_bucket(orders: &[Order], bucket: u64) -> HashMap<u64, u64> {
+    let mut totals = HashMap::new();
+    for order in orders {
+        let key = order.total / bucket;
+        *totals.entry(key).or_insert(0) += order.total;
+    }
+    totals
+}
@@ -0,0 +1,26 @@
+/*
+ * Copyright (c) 2024 Example Corp.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ */
+
+pub fn parse_port(value: &str) -> Option<u16> {
+    value.trim().parse().ok()
+}
+
+pub fn checksum(data: &[u8]) -> u32 {
+    let mut sum: u32 = 0;
+    for byte in data {
+        sum = sum.wrapping_mul(31).wrapping_add(u32::from(*byte));
+    }
+    sum ^= sum >> 16;
+    sum
+}
@@ -0,0 +1,10 @@
+use chrono::{DateTime, Utc};
+use crate::metrics::latency::Histogram;
+use std::path::PathBuf;
+use serde_json::Value;
+use crate::storage::archive::ArchiveReader;
+use std::collections::HashMap;
+
+pub fn export_count(values: &[Value]) -> usize {
+    values.iter().filter(|value| value.is_object()).count()
+}
@@ -0,0 +1,10 @@
+use std::collections::HashMap;
+use crate::storage::archive::ArchiveReader;
+use serde_json::Value;
+use crate::metrics::latency::Histogram;
+use std::path::PathBuf;
+use chrono::{DateTime, Utc};
+
+pub fn report_path(root: &str) -> PathBuf {
+    PathBuf::from(root).join("reports")
+}
@@ -0,0 +1,3 @@
+﻿pub fn with_bom() {}
+
+pub fn other() {}
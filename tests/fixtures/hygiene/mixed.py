@@ -0,0 +1,4 @@
+def main():  
+    x = 1
+    return x	
+print(main())
\ No newline at end of file
@@ -0,0 +1 @@
+// line endings fixturefn first() -> u32 {    let needle = 1;    needle}fn second() {    let value = "a needle here";}
\ No newline at end of file
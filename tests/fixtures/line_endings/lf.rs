@@ -0,0 +1,9 @@
+// line endings fixture
+fn first() -> u32 {
+    let needle = 1;
+    needle
+}
+
+fn second() {
+    let value = "a needle here";
+}
@@ -0,0 +1,18 @@
+// TODO: split the ranges into a table
+pub fn parse(x: i32) -> i32 {
+    if x > 0 {
+        if x > 10 {
+            if x > 100 {
+                return 3;
+            }
+            return 2;
+        }
+        1
+    } else {
+        0
+    }
+}
+
+pub fn clamp(x: i32) -> i32 {
+    if x < 0 { 0 } else { x }
+}
@@ -0,0 +1,4 @@
+function show(tokenCount) {
+  console.log(`tokens: ${tokenCount}`, "{password}");
+  console.log("email field", form.emailField);
+}
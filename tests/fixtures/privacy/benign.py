@@ -0,0 +1,4 @@
+def stats(num_tokens, email_verified):
+    print(f"{num_tokens} tokens, verified={email_verified}")
+    logger.info("password length %d", len(password))
+    print(f"{password_policy}")
@@ -0,0 +1,8 @@
+fn report(token_count: usize, tokenizer: &Tokenizer) {
+    println!("processed {token_count} tokens with {:?}", tokenizer);
+    let password = read();
+    info!("password length {}", password.len());
+    // println!("{password}");
+    log::debug!("{}", mask(&password));
+    println!("{password}"); // codesearch-ignore: privacy
+}
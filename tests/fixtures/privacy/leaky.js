@@ -0,0 +1,4 @@
+function register(user) {
+  console.log(`registered ${user.email}`);
+  logger.info("key", apiKey);
+}
@@ -0,0 +1,6 @@
+import logging
+logger = logging.getLogger(__name__)
+
+def login(username, password):
+    logger.info(f"login {username} {password}")
+    print("ssn:", ssn)
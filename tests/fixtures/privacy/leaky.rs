@@ -0,0 +1,6 @@
+use log::info;
+fn charge(card_number: &str, amount: u64) {
+    info!("charging {amount} to {card_number}");
+    let session_token = String::new();
+    println!("session {}", session_token);
+}
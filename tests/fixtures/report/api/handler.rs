@@ -0,0 +1,10 @@
+pub fn handle(path: &str) -> u16 {
+    // TODO: return 405 for unsupported methods
+    if path.is_empty() {
+        400
+    } else if path == "/health" {
+        200
+    } else {
+        404
+    }
+}
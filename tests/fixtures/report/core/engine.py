@@ -0,0 +1,7 @@
+def run(jobs):
+    # TODO: run jobs in parallel
+    results = []
+    for job in jobs:
+        if job:
+            results.append(job())
+    return results
@@ -0,0 +1,165 @@
+//! Integration tests for git-backed features on shallow, sparse and
+//! object-missing checkouts, as CI produces them
+
+use codesearch::duplicates::{find_duplicates_enhanced, DuplicateConfig};
+use codesearch::githistory::GitSearcher;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use tempfile::TempDir;
+
+const DUPLICATED: &str = "fn total(items: &[u32]) -> u32 {\n    let mut sum = 0;\n    for item in items {\n        sum += item;\n    }\n    sum\n}\n";
+
+/// Run git in `dir` with a fixed identity, returning whether it succeeded
+fn git(dir: &Path, args: &[&str]) -> bool {
+    Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["-c", "user.name=Test", "-c", "user.email=test@example.com", "-c", "init.defaultBranch=main"])
+        .args(args)
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+fn git_available() -> bool {
+    Command::new("git").arg("--version").output().is_ok_and(|o| o.status.success())
+}
+
+/// A repository with three commits, the last adding a duplicated function in two directories
+fn source_repo() -> TempDir {
+    let dir = TempDir::new().unwrap();
+    let root = dir.path();
+    assert!(git(root, &["init", "-q"]));
+    fs::create_dir_all(root.join("src")).unwrap();
+    fs::create_dir_all(root.join("other")).unwrap();
+
+    fs::write(root.join("src/lib.rs"), "pub fn first() {}\n").unwrap();
+    assert!(git(root, &["add", "-A"]) && git(root, &["commit", "-q", "-m", "first"]));
+
+    fs::write(root.join("src/lib.rs"), "pub fn first() {}\npub fn second() {}\n").unwrap();
+    assert!(git(root, &["commit", "-q", "-am", "second"]));
+
+    fs::write(root.join("src/lib.rs"), format!("pub fn first() {{}}\npub fn second() {{}}\n\n{DUPLICATED}")).unwrap();
+    fs::write(root.join("other/copy.rs"), format!("// copy\n{DUPLICATED}")).unwrap();
+    assert!(git(root, &["add", "-A"]) && git(root, &["commit", "-q", "-m", "third: add total"]));
+    dir
+}
+
+/// Clone `source` keeping only the last `depth` commits, optionally restricted
+/// to `sparse` directories with sparse-checkout
+fn shallow_clone(source: &Path, depth: usize, sparse: Option<&[&str]>) -> TempDir {
+    let dir = TempDir::new().unwrap();
+    let url = format!("file://{}", source.display());
+    let depth = depth.to_string();
+    let target = dir.path().join("clone");
+    assert!(git(dir.path(), &["clone", "-q", "--depth", &depth, &url, target.to_str().unwrap()]));
+    if let Some(dirs) = sparse {
+        let mut args = vec!["sparse-checkout", "set", "--no-cone"];
+        args.extend(dirs);
+        assert!(git(&target, &args));
+    }
+    dir
+}
+
+#[test]
+fn test_history_on_depth_one_clone_warns_instead_of_failing() {
+    if !git_available() {
+        return;
+    }
+    let source = source_repo();
+    let clone = shallow_clone(source.path(), 1, None);
+    let searcher = GitSearcher::new(&clone.path().join("clone")).unwrap();
+    assert!(searcher.is_shallow());
+
+    let commits = searcher.get_commit_history(10).unwrap();
+    assert_eq!(commits.len(), 1);
+    assert!(commits[0].files_changed.is_empty());
+
+    let results = searcher.search_history("total", 10).unwrap();
+    assert!(results.is_empty());
+    assert!(
+        searcher.warnings().iter().any(|w| w.contains("shallow clone")),
+        "{:?}",
+        searcher.warnings()
+    );
+}
+
+#[test]
+fn test_history_on_depth_two_clone_keeps_reachable_commits() {
+    if !git_available() {
+        return;
+    }
+    let source = source_repo();
+    let clone = shallow_clone(source.path(), 2, None);
+    let searcher = GitSearcher::new(&clone.path().join("clone")).unwrap();
+
+    let commits = searcher.get_commit_history(10).unwrap();
+    assert_eq!(commits.len(), 2);
+    assert!(commits[0].files_changed.contains(&"other/copy.rs".to_string()));
+
+    let results = searcher.search_history("fn total", 10).unwrap();
+    assert_eq!(results.len(), 2);
+    assert!(searcher.warnings().iter().any(|w| w.contains("shallow clone")));
+}
+
+#[test]
+fn test_blame_skips_files_missing_from_object_store() {
+    if !git_available() {
+        return;
+    }
+    let source = source_repo();
+    let root = source.path();
+
+    // Drop the loose blob of other/copy.rs, as a partial clone would lack it
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(root)
+        .args(["rev-parse", "HEAD:other/copy.rs"])
+        .output()
+        .unwrap();
+    let oid = String::from_utf8(output.stdout).unwrap().trim().to_string();
+    fs::remove_file(root.join(".git/objects").join(&oid[..2]).join(&oid[2..])).unwrap();
+
+    let searcher = GitSearcher::new(root).unwrap();
+    let err = searcher.get_blame_info(root.join("other/copy.rs").to_str().unwrap(), 2).unwrap_err();
+    assert!(err.to_string().contains("object store"), "{err}");
+
+    assert_eq!(searcher.line_timestamp(&root.join("other/copy.rs"), 2), None);
+    assert!(searcher.line_timestamp(&root.join("src/lib.rs"), 1).is_some());
+    assert!(searcher.warnings().iter().any(|w| w.starts_with("blame unavailable")));
+}
+
+#[test]
+fn test_sparse_clone_skips_paths_outside_checkout() {
+    if !git_available() {
+        return;
+    }
+    let source = source_repo();
+    let clone = shallow_clone(source.path(), 1, Some(&["src/"]));
+    let root = clone.path().join("clone");
+    assert!(!root.join("other/copy.rs").exists());
+
+    let searcher = GitSearcher::new(&root).unwrap();
+    assert_eq!(searcher.line_timestamp(&root.join("other/copy.rs"), 2), None);
+    assert!(searcher.search_file_history("other/copy.rs", "total", 10).is_ok());
+    assert!(searcher.get_commit_history(10).is_ok());
+}
+
+#[test]
+fn test_duplicates_are_complete_on_shallow_clone() {
+    if !git_available() {
+        return;
+    }
+    let source = source_repo();
+    let clone = shallow_clone(source.path(), 1, None);
+    let root = clone.path().join("clone");
+
+    let config = || DuplicateConfig { min_lines: 4, ..DuplicateConfig::default() };
+    let full = find_duplicates_enhanced(source.path(), None, Some(&[".git".to_string()]), config()).unwrap();
+    let shallow = find_duplicates_enhanced(&root, None, Some(&[".git".to_string()]), config()).unwrap();
+
+    assert!(!full.is_empty());
+    assert_eq!(shallow.len(), full.len());
+    assert!(shallow.iter().all(|d| d.suggestion.is_some()));
+}
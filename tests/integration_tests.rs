@@ -439,9 +439,53 @@ fn test_special() {
         ]);
 
         assert!(output.status.success(), "Command failed: {}", String::from_utf8_lossy(&output.stderr));
+        // Statistics are chrome: they go to stderr, even when stdout is piped
         let stdout = String::from_utf8(output.stdout).unwrap();
-        // Should contain statistics
-        assert!(stdout.contains("matches") || stdout.contains("files") || stdout.contains("Statistics"));
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stdout.contains("User"));
+        assert!(!stdout.contains("Search Statistics"));
+        assert!(stderr.contains("Search Statistics"));
+    }
+
+    #[test]
+    fn test_json_output_parses_with_benchmark_and_stats() {
+        let temp_dir = create_complex_test_files();
+        let output = run_command(&[
+            "search", "User", temp_dir.path().to_str().unwrap(),
+            "--format", "json", "--benchmark", "--stats", "--no-auto-exclude"
+        ]);
+
+        assert!(output.status.success(), "Command failed: {}", String::from_utf8_lossy(&output.stderr));
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&stdout)
+            .unwrap_or_else(|e| panic!("Invalid JSON output ({e}): {stdout}"));
+
+        // Stats and metrics are embedded in the document instead of printed
+        assert!(value["stats"]["total_matches"].as_u64().unwrap() > 0);
+        assert!(value["metrics"]["files_processed"].as_u64().unwrap() > 0);
+        assert_eq!(value["total_results"], value["results"].as_array().unwrap().len());
+    }
+
+    #[test]
+    fn test_piped_output_has_no_chrome() {
+        let temp_dir = create_complex_test_files();
+        let output = run_command(&[
+            "search", "User", temp_dir.path().to_str().unwrap(),
+            "--benchmark", "--no-auto-exclude"
+        ]);
+        assert!(output.status.success(), "Command failed: {}", String::from_utf8_lossy(&output.stderr));
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stdout.contains("User"));
+        assert!(!stdout.contains("Performance Metrics"));
+        assert!(!stderr.contains("Performance Metrics"));
+
+        let output = run_command(&[
+            "search", "no_such_identifier_anywhere", temp_dir.path().to_str().unwrap(),
+            "--no-auto-exclude"
+        ]);
+        assert!(output.status.success());
+        assert!(output.stdout.is_empty(), "{}", String::from_utf8_lossy(&output.stdout));
     }
 
     #[test]
@@ -560,6 +604,35 @@ fn test_special() {
         assert!(!stdout.is_empty());
     }
 
+    #[test]
+    fn test_complexity_json_applies_threshold_and_sort() {
+        let temp_dir = create_complex_test_files();
+        let complexity = |extra: &[&str]| {
+            let mut args = vec!["complexity", temp_dir.path().to_str().unwrap(), "-e", "rs,py,js", "--format", "json"];
+            args.extend(extra);
+            let output = run_command(&args);
+            assert!(output.status.success(), "Command failed: {}", String::from_utf8_lossy(&output.stderr));
+            serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap()
+        };
+
+        let all = complexity(&["--sort"]);
+        assert_eq!(all["schema_version"], codesearch::types::SCHEMA_VERSION);
+        let files = all["files"].as_array().unwrap();
+        assert_eq!(all["total_files"], files.len());
+        assert!(files.len() >= 2, "{all}");
+        for key in ["path", "cyclomatic", "cognitive", "functions", "max_nesting", "lines"] {
+            assert!(files[0].get(key).is_some(), "{key} missing from {}", files[0]);
+        }
+        let scores: Vec<u64> = files.iter().map(|f| f["cyclomatic"].as_u64().unwrap()).collect();
+        assert!(scores.windows(2).all(|w| w[0] >= w[1]), "{scores:?}");
+
+        let threshold = scores[0];
+        let kept = complexity(&["--threshold", &threshold.to_string()]);
+        let kept = kept["files"].as_array().unwrap();
+        assert!(!kept.is_empty() && kept.len() < files.len());
+        assert!(kept.iter().all(|f| f["cyclomatic"].as_u64().unwrap() >= threshold));
+    }
+
     #[test]
     fn test_files_command_with_extensions() {
         let temp_dir = create_complex_test_files();
@@ -667,4 +740,615 @@ fn test_special() {
         // Should find multiple patterns (struct, class, function)
         assert!(!stdout.is_empty());
     }
+
+    #[test]
+    fn test_files_from_stdin() {
+        use std::io::Write;
+        use std::process::Stdio;
+
+        let temp_dir = create_test_files();
+        // Relative list entries resolve against the cwd
+        let mut child = Command::new(env!("CARGO_BIN_EXE_codesearch"))
+            .args(["search", "Hello", "--files-from", "-", "--format", "json"])
+            .current_dir(temp_dir.path())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(b"# changed\ntest.rs\n\ntest.py\ngone.rs\n")
+            .unwrap();
+        let output = child.wait_with_output().unwrap();
+
+        assert!(output.status.success(), "Command failed: {}", String::from_utf8_lossy(&output.stderr));
+        let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        // test.js also says Hello but isn't listed
+        assert_eq!(json["total_results"], 2);
+        assert_eq!(json["missing_files"].as_array().unwrap().len(), 1);
+        assert!(json["missing_files"][0].as_str().unwrap().ends_with("gone.rs"));
+    }
+
+    #[test]
+    fn test_warm_fills_the_cache_for_later_searches() {
+        let temp_dir = create_test_files();
+        // The cache lives under the cwd
+        let run = |args: &[&str]| {
+            Command::new(env!("CARGO_BIN_EXE_codesearch")).args(args).current_dir(temp_dir.path()).output().unwrap()
+        };
+        fs::write(temp_dir.path().join("queries.txt"), "# greetings\nHello\n\nfn main\n").unwrap();
+
+        let output = run(&["warm", "--queries-file", "queries.txt", "--parallel", "2"]);
+        assert!(output.status.success(), "Command failed: {}", String::from_utf8_lossy(&output.stderr));
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("Hello") && stdout.contains("fn main") && stdout.contains("Total"), "{stdout}");
+        assert!(temp_dir.path().join(".codesearch/cache/search.cache").exists());
+        assert!(temp_dir.path().join(".codesearch/index.json").exists());
+
+        for query in ["Hello", "fn main"] {
+            let output = run(&["search", query, ".", "--cache", "--benchmark", "--stats"]);
+            assert!(output.status.success(), "Command failed: {}", String::from_utf8_lossy(&output.stderr));
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            assert!(stderr.contains("Cache hits: 1"), "{query}: {stderr}");
+        }
+
+        fs::write(temp_dir.path().join("bad.txt"), "Hello\n[unclosed\n").unwrap();
+        let output = run(&["warm", "--queries-file", "bad.txt"]);
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stdout).contains("failed"));
+        assert!(String::from_utf8_lossy(&output.stderr).contains("[unclosed"));
+    }
+
+    #[test]
+    fn test_every_subcommand_help_runs() {
+        use clap::CommandFactory;
+        use codesearch::cli::Cli;
+
+        for sub in Cli::command().get_subcommands() {
+            let output = Command::new(env!("CARGO_BIN_EXE_codesearch"))
+                .args([sub.get_name(), "--help"])
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "{} --help failed: {}", sub.get_name(), String::from_utf8_lossy(&output.stderr));
+            assert!(String::from_utf8_lossy(&output.stdout).contains("Usage:"), "{}", sub.get_name());
+        }
+    }
+
+    #[test]
+    fn test_no_emoji_output_is_ascii() {
+        let temp_dir = create_test_files();
+        let dir = temp_dir.path().to_str().unwrap();
+
+        for args in [
+            vec!["--no-emoji", "search", "Hello", dir, "--no-auto-exclude", "--stats"],
+            vec!["--no-emoji", "analyze", dir],
+            vec!["--no-emoji", "complexity", dir],
+            vec!["--no-emoji", "duplicates", dir],
+            vec!["--no-emoji", "deadcode", dir],
+        ] {
+            let output = run_command(&args);
+            assert!(output.status.success(), "{:?} failed: {}", args, String::from_utf8_lossy(&output.stderr));
+            for (stream, bytes) in [("stdout", &output.stdout), ("stderr", &output.stderr)] {
+                assert!(
+                    bytes.is_ascii(),
+                    "{:?} wrote non-ASCII to {}:\n{}",
+                    args,
+                    stream,
+                    String::from_utf8_lossy(bytes)
+                );
+            }
+        }
+    }
+
+    /// A tree with a branchy function, an unused function and a duplicated block
+    fn create_gate_test_files() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        let body = "    let mut total = 0;\n    for item in items {\n        if *item > 10 && *item < 100 {\n            total += item;\n        } else if *item == 0 {\n            total += 1;\n        }\n    }\n    total\n";
+        fs::write(
+            temp_dir.path().join("lib.rs"),
+            format!("pub fn sum_a(items: &[u32]) -> u32 {{\n{body}}}\n\nfn never_called() {{}}\n"),
+        )
+        .unwrap();
+        fs::write(temp_dir.path().join("other.rs"), format!("pub fn sum_b(items: &[u32]) -> u32 {{\n{body}}}\n")).unwrap();
+        temp_dir
+    }
+
+    #[test]
+    fn test_html_export_of_analysis_reports() {
+        let temp_dir = create_gate_test_files();
+        let dir = temp_dir.path().to_str().unwrap();
+        let out_dir = TempDir::new().unwrap();
+        for (command, heading, row) in [
+            ("complexity", "Top Complexity Files", "lib.rs:1</a>"),
+            ("duplicates", "Duplicate Clusters", "other.rs:1</a>"),
+            ("deadcode", "Dead Code", "never_called"),
+        ] {
+            let html = out_dir.path().join(format!("{command}.html"));
+            let output = run_command(&[command, dir, "--export", html.to_str().unwrap()]);
+            assert!(output.status.success(), "{command}: {}", String::from_utf8_lossy(&output.stderr));
+            let page = fs::read_to_string(&html).unwrap();
+            assert!(page.contains(&format!("<h2>{heading}</h2>")), "{command}: {page}");
+            assert!(page.contains(row), "{command}: {page}");
+        }
+    }
+
+    #[test]
+    fn test_findings_exit_zero_without_gate_flags() {
+        let temp_dir = create_gate_test_files();
+        let dir = temp_dir.path().to_str().unwrap();
+        for args in [
+            vec!["search", "total", dir, "--no-auto-exclude"],
+            vec!["complexity", dir],
+            vec!["duplicates", dir],
+            vec!["deadcode", dir],
+        ] {
+            let output = run_command(&args);
+            assert_eq!(output.status.code(), Some(0), "{:?}: {}", args, String::from_utf8_lossy(&output.stderr));
+        }
+    }
+
+    #[test]
+    fn test_gate_flags_set_distinct_exit_codes() {
+        let temp_dir = create_gate_test_files();
+        let dir = temp_dir.path().to_str().unwrap();
+        for (args, code) in [
+            (vec!["search", "total", dir, "--no-auto-exclude", "--fail-on-matches"], 1),
+            (vec!["search", "no_such_identifier", dir, "--no-auto-exclude", "--fail-on-matches"], 0),
+            (vec!["complexity", dir, "--max-complexity", "2"], 2),
+            (vec!["complexity", dir, "--per-function", "--max-complexity", "2"], 2),
+            (vec!["complexity", dir, "--max-complexity", "1000"], 0),
+            (vec!["duplicates", dir, "--min-lines", "4", "--max-duplicates", "0"], 3),
+            (vec!["duplicates", dir, "--min-lines", "4", "--max-duplicates", "1000"], 0),
+            (vec!["deadcode", dir, "--fail-on-deadcode"], 4),
+        ] {
+            let output = run_command(&args);
+            assert_eq!(output.status.code(), Some(code), "{:?}: {}", args, String::from_utf8_lossy(&output.stderr));
+        }
+    }
+
+    #[test]
+    fn test_errors_exit_with_their_own_code() {
+        let temp_dir = create_gate_test_files();
+        let dir = temp_dir.path().to_str().unwrap();
+        let missing = temp_dir.path().join("missing");
+        for args in [
+            vec!["search", "(unclosed", dir, "--fail-on-matches"],
+            vec!["search", "total", missing.to_str().unwrap(), "--fail-on-matches"],
+            vec!["complexity", dir, "--max-complexity", "not-a-number"],
+        ] {
+            let output = run_command(&args);
+            assert_eq!(output.status.code(), Some(10), "{:?}: {}", args, String::from_utf8_lossy(&output.stderr));
+        }
+    }
+
+    #[test]
+    fn test_export_with_json_format_warns_which_flag_won() {
+        let temp_dir = create_gate_test_files();
+        let dir = temp_dir.path().to_str().unwrap();
+        let export = temp_dir.path().join("results.csv");
+        let output = run_command(&["search", "total", dir, "--no-auto-exclude", "--format", "json", "--export", export.to_str().unwrap()]);
+
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains("--export wins over --format json: results go to the export file and no JSON is printed"),
+            "{stderr}"
+        );
+        assert!(export.exists());
+    }
+
+    #[test]
+    fn test_config_file_sets_analysis_defaults() {
+        let temp_dir = create_gate_test_files();
+        let dir = temp_dir.path().to_str().unwrap();
+        let config_dir = TempDir::new().unwrap();
+        let config_path = config_dir.path().join("codesearch.toml");
+        fs::write(&config_path, "[duplicates]\nmin_lines = 50\n\n[deadcode]\ninclude_public = true\n").unwrap();
+        let run = |args: &[&str]| Command::new(env!("CARGO_BIN_EXE_codesearch")).args(args).env("CODESEARCH_CONFIG", &config_path).output().unwrap();
+
+        // No block is 50 lines long, unless the flag asks for less
+        assert_eq!(run(&["duplicates", dir, "--max-duplicates", "0"]).status.code(), Some(0));
+        assert_eq!(run(&["duplicates", dir, "--min-lines", "4", "--max-duplicates", "0"]).status.code(), Some(3));
+
+        let output = run(&["deadcode", dir, "--format", "json"]);
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        let public = |item: &serde_json::Value| item["name"] == "sum_a" && item["item_type"] == "function";
+        assert!(json["dead_code"].as_array().unwrap().iter().any(public), "{json}");
+
+        let output = run(&["config", "show", "--format", "json"]);
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(json["source"], config_path.to_str().unwrap());
+        assert_eq!(json["duplicates"]["min_lines"], 50);
+        assert_eq!(json["duplicates"]["similarity"], 0.9);
+        assert_eq!(json["deadcode"]["include_public"], true);
+
+        let output = run(&["config", "show"]);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("[duplicates]\nmin_lines = 50\n"), "{stdout}");
+    }
+
+    #[test]
+    fn test_language_overrides_from_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(
+            root.join("helpers.inc"),
+            "<?php\n# helpers\ntrait Greets {}\nfunction greet($name) { return \"hi $name\"; }\nfunction shout($name) { return strtoupper(greet($name)); }\n",
+        )
+        .unwrap();
+        let config_path = root.join("overrides.toml");
+        fs::write(&config_path, "[language.overrides]\ninc = \"php\"\n").unwrap();
+        let analyze = |config: &std::path::Path| {
+            let output = Command::new(env!("CARGO_BIN_EXE_codesearch"))
+                .args(["analyze", root.to_str().unwrap(), "-e", "inc", "--format", "json"])
+                .env("CODESEARCH_CONFIG", config)
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+            serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap()
+        };
+
+        let stats = analyze(&config_path);
+        assert_eq!(stats["languages"][0]["extension"], "inc", "{stats}");
+        assert_eq!(stats["languages"][0]["language"], "PHP", "{stats}");
+        assert_eq!(stats["functions"], 2, "{stats}");
+        assert_eq!(stats["classes"], 1, "{stats}");
+
+        let empty = root.join("empty.toml");
+        fs::write(&empty, "").unwrap();
+        assert_eq!(analyze(&empty)["languages"][0]["language"], "inc");
+
+        // Per-function complexity only knows how to find functions in a known language
+        let functions_analyzed = |config: &std::path::Path| {
+            let output = Command::new(env!("CARGO_BIN_EXE_codesearch"))
+                .args(["complexity", root.to_str().unwrap(), "-e", "inc", "--per-function"])
+                .env("CODESEARCH_CONFIG", config)
+                .output()
+                .unwrap();
+            String::from_utf8_lossy(&output.stdout).contains("Functions analyzed: 2")
+        };
+        assert!(functions_analyzed(&config_path));
+        assert!(!functions_analyzed(&empty));
+
+        let output = Command::new(env!("CARGO_BIN_EXE_codesearch"))
+            .args(["languages", "--format", "json"])
+            .env("CODESEARCH_CONFIG", &config_path)
+            .output()
+            .unwrap();
+        let table: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        let php = table.as_array().unwrap().iter().find(|l| l["name"] == "PHP").unwrap();
+        assert_eq!(php["overridden_extensions"], serde_json::json!(["inc"]));
+
+        fs::write(&config_path, "[language.overrides]\ninc = \"Hack\"\n").unwrap();
+        let output = Command::new(env!("CARGO_BIN_EXE_codesearch"))
+            .args(["analyze", root.to_str().unwrap()])
+            .env("CODESEARCH_CONFIG", &config_path)
+            .output()
+            .unwrap();
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("overrides.toml") && stderr.contains("unknown language \"Hack\""), "{stderr}");
+    }
+
+    #[test]
+    fn test_config_precedence() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let package = root.join("repo/packages/web");
+        fs::create_dir_all(&package).unwrap();
+        fs::create_dir(root.join("repo/.git")).unwrap();
+        fs::write(root.join("repo/.codesearch.toml"), "[duplicates]\nmin_lines = 4\n").unwrap();
+        fs::write(package.join(".codesearch.toml"), "[duplicates]\nmin_lines = 5\n").unwrap();
+        fs::write(root.join("env.toml"), "[duplicates]\nmin_lines = 6\n").unwrap();
+        fs::write(root.join("flag.toml"), "[duplicates]\nmin_lines = 7\n").unwrap();
+        let home = TempDir::new().unwrap();
+        let run = |args: &[&str], env: Option<&str>| {
+            let mut command = Command::new(env!("CARGO_BIN_EXE_codesearch"));
+            // Run from elsewhere: discovery starts at the given path, not the working directory
+            command.args(args).current_dir(root).env("HOME", home.path()).env_remove("CODESEARCH_CONFIG");
+            if let Some(env) = env {
+                command.env("CODESEARCH_CONFIG", root.join(env));
+            }
+            command.output().unwrap()
+        };
+        let min_lines = |args: &[&str], env: Option<&str>| {
+            let args: Vec<&str> = ["config", "show", "--format", "json"].iter().chain(args).copied().collect();
+            let output = run(&args, env);
+            assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+            serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap()["duplicates"]["min_lines"].clone()
+        };
+        let package = package.to_str().unwrap();
+        let flag = root.join("flag.toml");
+        let flag = flag.to_str().unwrap();
+
+        assert_eq!(min_lines(&[], None), 3);
+        // The nested package's config wins over the repository's
+        assert_eq!(min_lines(&[package], None), 5);
+        assert_eq!(min_lines(&["repo"], None), 4);
+        assert_eq!(min_lines(&[package], Some("env.toml")), 6);
+        assert_eq!(min_lines(&[package, "--config", flag], Some("env.toml")), 7);
+
+        fs::write(root.join("broken.toml"), "[duplicates\nmin_lines = 4\n").unwrap();
+        let output = run(&["--config", root.join("broken.toml").to_str().unwrap(), "duplicates", "."], None);
+        assert_eq!(output.status.code(), Some(10));
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("Invalid config file") && stderr.contains("broken.toml"), "{stderr}");
+    }
+
+    #[test]
+    fn test_badge_command() {
+        let temp_dir = create_gate_test_files();
+        let run = |args: &[&str]| Command::new(env!("CARGO_BIN_EXE_codesearch")).args(args).current_dir(temp_dir.path()).output().unwrap();
+
+        let output = run(&["badge", "--metric", "complexity", "--output", "badge.svg"]);
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        let svg = fs::read_to_string(temp_dir.path().join("badge.svg")).unwrap();
+        assert!(svg.starts_with("<svg") && svg.contains("<title>complexity: "), "{svg}");
+
+        // --cached redraws the recorded value without analyzing
+        fs::remove_file(temp_dir.path().join("other.rs")).unwrap();
+        let json = |args: &[&str]| {
+            let output = run(args);
+            assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+            serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap()
+        };
+        let cached = json(&["badge", "--metric", "complexity", "--format", "json", "--cached"]);
+        assert_eq!(cached["schemaVersion"], 1);
+        assert_eq!(cached["label"], "complexity");
+        assert!(svg.contains(&format!("complexity: {}", cached["message"].as_str().unwrap())), "{cached}");
+        let findings = json(&["deadcode", ".", "--format", "json", "-e", "rs"])["total_items"].to_string();
+        assert_eq!(json(&["badge", "--metric", "deadcode", "--format", "json", "-e", "rs"])["message"], findings.as_str());
+
+        fs::create_dir(temp_dir.path().join("empty")).unwrap();
+        for args in [vec!["badge", "--metric", "maintainability", "empty"], vec!["badge", "--metric", "maintainability", "--cached", "empty"]] {
+            let output = run(&args);
+            assert_eq!(output.status.code(), Some(10), "{args:?}");
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            assert!(stderr.contains("maintainability") && stderr.contains("empty"), "{stderr}");
+        }
+    }
+
+    #[test]
+    fn test_lint_rules_of_each_kind_against_a_baseline() {
+        let fixture = "tests/fixtures/lint";
+        let lint = |extra: &[&str]| {
+            let mut args = vec!["lint", fixture, "-e", "rs", "--rules", "tests/fixtures/lint/rules.toml"];
+            args.extend(["--baseline", "tests/fixtures/lint/baseline.json"]);
+            args.extend(extra);
+            run_command(&args)
+        };
+
+        let output = lint(&["--format", "json"]);
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        let found: Vec<(&str, &str, u64)> = json["findings"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|f| (f["rule_id"].as_str().unwrap(), f["level"].as_str().unwrap(), f["line"].as_u64().unwrap()))
+            .collect();
+        // `parse` got more complex than its baseline; `clamp` didn't
+        assert_eq!(
+            found,
+            vec![("no-todo", "note", 1), ("cognitive-5", "warning", 2), ("no-regressions", "error", 2)]
+        );
+        assert_eq!(json["summary"]["error"], 1);
+
+        let output = lint(&["--format", "sarif", "--fail-on", "error"]);
+        assert_eq!(output.status.code(), Some(5), "{}", String::from_utf8_lossy(&output.stderr));
+        let sarif: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        let rules = sarif["runs"][0]["tool"]["driver"]["rules"].as_array().unwrap();
+        let ids: Vec<&str> = rules.iter().map(|r| r["id"].as_str().unwrap()).collect();
+        assert_eq!(ids, vec!["lint/no-todo", "lint/cognitive-5", "lint/no-regressions"]);
+        assert_eq!(sarif["runs"][0]["results"][2]["level"], "error");
+
+        // Against a baseline of the current code there is nothing to regress
+        let temp_dir = TempDir::new().unwrap();
+        let baseline = temp_dir.path().join("baseline.json");
+        let output = run_command(&["lint", fixture, "-e", "rs", "--write-baseline", baseline.to_str().unwrap()]);
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        let output = run_command(&[
+            "lint", fixture, "-e", "rs", "--rules", "tests/fixtures/lint/rules.toml",
+            "--baseline", baseline.to_str().unwrap(), "--fail-on", "error",
+        ]);
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        assert!(!String::from_utf8_lossy(&output.stdout).contains("no-regressions"));
+    }
+
+    #[test]
+    fn test_deadcode_json_and_quiet_output() {
+        let temp_dir = create_gate_test_files();
+        let dir = temp_dir.path().to_str().unwrap();
+
+        let output = run_command(&["deadcode", dir, "--format", "json"]);
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        let items = json["dead_code"].as_array().unwrap();
+        assert!(items.iter().any(|item| item["name"] == "never_called" && item["item_type"] == "function"));
+        assert_eq!(json["total_items"], items.len());
+        assert!(json["summary"]["by_type"].is_object());
+
+        let output = run_command(&["deadcode", dir, "--quiet"]);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("Summary:"), "{stdout}");
+        assert!(!stdout.contains("never_called"), "{stdout}");
+    }
+
+    #[test]
+    fn test_deps_unused_json() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir(root.join("src")).unwrap();
+        fs::write(root.join("Cargo.toml"), "[package]\nname = \"demo\"\n\n[dependencies]\nregex = \"1\"\nitoa = \"1\"\n").unwrap();
+        fs::write(root.join("src/main.rs"), "use regex::Regex;\nuse rand::Rng;\nfn main() {}\n").unwrap();
+
+        let output = run_command(&["deps", root.to_str().unwrap(), "--unused", "--format", "json"]);
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        let unused = json["unused"].as_array().unwrap();
+        assert_eq!(unused.len(), 1, "{json}");
+        assert_eq!(unused[0]["name"], "itoa");
+        assert_eq!(unused[0]["category"], "unused");
+        assert_eq!(json["undeclared"][0]["module"], "rand");
+        assert_eq!(json["undeclared"][0]["line"], 2);
+
+        let output = run_command(&["deps", root.to_str().unwrap(), "--unused"]);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("itoa") && stdout.contains("rand"), "{stdout}");
+    }
+}
+
+// Each run is a fresh process: the second one is served from the persisted cache
+#[test]
+fn test_cached_search_is_identical_across_runs() {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("lib.rs"), "fn parse() {}\nfn run() { parse(); }\n").unwrap();
+    let search = || {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_codesearch"))
+            .args(["search", "parse", ".", "--cache", "--format", "json-pretty"])
+            .current_dir(dir.path())
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        json["results"].clone()
+    };
+
+    let first = search();
+    assert_eq!(first.as_array().unwrap().len(), 2);
+    assert!(dir.path().join(".codesearch/cache/search.cache").is_file());
+    assert_eq!(search(), first);
+
+    // Editing a file invalidates the cached search
+    fs::write(dir.path().join("lib.rs"), "fn parse() {}\n").unwrap();
+    assert_eq!(search().as_array().unwrap().len(), 1);
+}
+
+// Equal scores across many files used to come back in whichever order the workers finished
+#[test]
+fn test_ranked_search_order_is_stable_across_runs() {
+    let dir = TempDir::new().unwrap();
+    for module in ["alpha", "beta", "gamma", "delta"] {
+        fs::create_dir(dir.path().join(module)).unwrap();
+        for file in ["a.rs", "b.rs", "c.py", "d.txt"] {
+            fs::write(
+                dir.path().join(module).join(file),
+                "let needle = 1;\nlet needle = 2;\n// needle in a comment that runs a little longer\n",
+            )
+            .unwrap();
+        }
+    }
+    let search = || {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_codesearch"))
+            .args(["search", "needle", ".", "--rank", "--format", "json"])
+            .current_dir(dir.path())
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        output.stdout
+    };
+
+    let first = search();
+    for _ in 0..4 {
+        assert_eq!(search(), first);
+    }
+
+    let json: serde_json::Value = serde_json::from_slice(&first).unwrap();
+    let results = json["results"].as_array().unwrap();
+    assert_eq!(results.len(), 48);
+    let keys: Vec<(f64, &str, u64)> = results
+        .iter()
+        .map(|r| (r["score"].as_f64().unwrap(), r["file"].as_str().unwrap(), r["line_number"].as_u64().unwrap()))
+        .collect();
+    for pair in keys.windows(2) {
+        let ((score_a, file_a, line_a), (score_b, file_b, line_b)) = (pair[0], pair[1]);
+        assert!(score_a > score_b || (score_a == score_b && (file_a, line_a) < (file_b, line_b)), "{pair:?}");
+    }
+}
+
+// Without a terminal the prompt reads plain lines, so scripted sessions keep working
+#[test]
+fn test_interactive_reads_commands_from_piped_stdin() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let dir = TempDir::new().unwrap();
+    let home = TempDir::new().unwrap();
+    fs::write(dir.path().join("lib.rs"), "fn parse() {}\nfn render() {}\n").unwrap();
+    fs::write(dir.path().join("notes.py"), "def parse():\n    pass\n").unwrap();
+
+    let mut child = std::process::Command::new(env!("CARGO_BIN_EXE_codesearch"))
+        .args(["interactive", "."])
+        .current_dir(dir.path())
+        .env("HOME", home.path())
+        .env("NO_COLOR", "1")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"ext rs\n/r\nparse\nstatus\nhistory\nquit\nrender\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Extensions set: rs"), "{stdout}");
+    assert!(stdout.contains("lib.rs"), "{stdout}");
+    assert!(!stdout.contains("notes.py"), "{stdout}");
+    assert!(stdout.contains("Ranking mode:     ON"), "{stdout}");
+    assert!(stdout.contains("  1 parse"), "{stdout}");
+    assert!(stdout.contains("Goodbye"), "{stdout}");
+    // Nothing after quit runs
+    assert!(!stdout.contains("render"), "{stdout}");
+
+    let state = fs::read_to_string(home.path().join(".codesearch/interactive.json")).unwrap();
+    let state: serde_json::Value = serde_json::from_str(&state).unwrap();
+    assert_eq!(state["history"], serde_json::json!(["parse"]));
+    assert_eq!(state["settings"]["ranking"], true);
+}
+
+// A bad regex or an unwritable file is reported at the prompt; the session goes on
+#[test]
+fn test_interactive_reports_errors_and_keeps_going() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let dir = TempDir::new().unwrap();
+    let home = TempDir::new().unwrap();
+    fs::write(dir.path().join("lib.rs"), "fn parse() {}
+").unwrap();
+    let unwritable = dir.path().join("missing/session.txt");
+
+    let mut child = std::process::Command::new(env!("CARGO_BIN_EXE_codesearch"))
+        .args(["interactive", "."])
+        .current_dir(dir.path())
+        .env("HOME", home.path())
+        .env("NO_COLOR", "1")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let script = format!("foo(\nsave-session {}\nparse\nexport {}\n", unwritable.display(), unwritable.display());
+    child.stdin.take().unwrap().write_all(script.as_bytes()).unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.matches("Error:").count(), 3, "{stdout}");
+    assert!(stdout.contains("lib.rs"), "{stdout}");
+
+    let state = fs::read_to_string(home.path().join(".codesearch/interactive.json")).unwrap();
+    let state: serde_json::Value = serde_json::from_str(&state).unwrap();
+    assert_eq!(state["history"], serde_json::json!(["foo(", "parse"]));
 }
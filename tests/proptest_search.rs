@@ -1,7 +1,8 @@
 //! Property-based tests for search functionality
 
+use codesearch::encoding::Encoding;
 use codesearch::search::search_code;
-use codesearch::types::SearchOptions;
+use codesearch::types::{ContextLines, RegexEngine, SearchOptions, DEFAULT_MULTILINE_MAX_BYTES};
 use proptest::prelude::*;
 use tempfile::tempdir;
 use std::fs;
@@ -43,13 +44,25 @@ fn search_options_strategy() -> impl Strategy<Value = SearchOptions> {
                 ignore_case,
                 fuzzy,
                 fuzzy_threshold: threshold,
+                literal: false,
+                word_boundary: false,
                 max_results,
+                max_total: None,
                 exclude: None,
+                gitignore: true,
                 rank: false,
                 cache: false,
                 semantic: false,
                 benchmark: false,
                 vs_grep: false,
+                engine: RegexEngine::Default,
+                context: ContextLines::default(),
+                multiline: false,
+                multiline_max_bytes: DEFAULT_MULTILINE_MAX_BYTES,
+                encoding: Encoding::Auto,
+                index: None,
+                score_weights: Default::default(),
+                post_processors: Vec::new(),
             }
         })
 }
@@ -117,10 +130,12 @@ proptest! {
         };
         
         if let Ok(results) = search_code(&query, dir.path(), &options) {
-            // Each file should have at most max_results matches
-            for result in results {
-                assert!(result.matches.len() <= max_results);
+            // Each file should have at most max_results matching lines
+            let mut per_file = std::collections::HashMap::new();
+            for result in &results {
+                *per_file.entry(result.file.as_str()).or_insert(0) += 1;
             }
+            assert!(per_file.values().all(|&lines| lines <= max_results));
         }
     }
 